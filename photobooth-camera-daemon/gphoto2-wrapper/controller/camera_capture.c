@@ -20,6 +20,7 @@
 #include <sys/types.h>
 
 #include "camera_capture.h"
+#include "protocol.h"
 
 /* Constants */
 #define MAX_FAILED_FILES 50
@@ -38,6 +39,7 @@ static int g_failed_file_count = 0;
 
 /* External globals (from main controller) */
 extern int g_status_fd;
+extern char g_photo_dir[];
 extern volatile sig_atomic_t g_running;
 
 /* External logging function (from main controller) */
@@ -81,12 +83,64 @@ static time_t get_file_mtime(const char *filepath) {
 }
 
 /*
- * Clean up old photos to free space. Returns number of files deleted.
+ * Load <photo dir>/.acknowledged, the daemon's list of photos the app has
+ * stored safely (one file name per line). Returns a malloc'd buffer or NULL.
+ */
+static char *load_acknowledged_list(void) {
+    char list_path[512];
+    snprintf(list_path, sizeof(list_path), "%s/.acknowledged", g_photo_dir);
+    FILE *f = fopen(list_path, "r");
+    if (!f) return NULL;
+
+    size_t capacity = 4096, length = 0;
+    char *list = malloc(capacity + 1);
+    size_t n;
+    while (list && (n = fread(list + length, 1, capacity - length, f)) > 0) {
+        length += n;
+        if (length == capacity) {
+            capacity *= 2;
+            char *grown = realloc(list, capacity + 1);
+            if (!grown) {
+                free(list);
+                list = NULL;
+                break;
+            }
+            list = grown;
+        }
+    }
+    fclose(f);
+    if (list) list[length] = '\0';
+    return list;
+}
+
+/*
+ * Check whether a file name is a whole line of the acknowledged list
+ */
+static int is_acknowledged(const char *list, const char *name) {
+    if (!list) return 0;
+    size_t name_len = strlen(name);
+    const char *line = list;
+    while (*line) {
+        const char *end = strchr(line, '\n');
+        size_t line_len = end ? (size_t)(end - line) : strlen(line);
+        if (line_len == name_len && strncmp(line, name, name_len) == 0) return 1;
+        if (!end) break;
+        line = end + 1;
+    }
+    return 0;
+}
+
+/*
+ * Clean up old photos to free space. Only photos the app has acknowledged
+ * are deleted; the rest are kept even if storage stays low.
+ * Returns number of files deleted.
  */
 static int cleanup_old_photos(unsigned long long target_free_bytes) {
-    DIR *dir = opendir("/tmp");
+    DIR *dir = opendir(g_photo_dir);
     if (!dir) return 0;
 
+    char *acknowledged = load_acknowledged_list();
+
     // Build list of image files with their mtimes
     struct {
         char path[512];
@@ -113,9 +167,12 @@ static int cleanup_old_photos(unsigned long long target_free_bytes) {
         }
         if (!is_image) continue;
 
+        // Never evict a photo the app hasn't confirmed it has
+        if (!is_acknowledged(acknowledged, entry->d_name)) continue;
+
         // Get file info
         char filepath[512];
-        snprintf(filepath, sizeof(filepath), "/tmp/%s", entry->d_name);
+        snprintf(filepath, sizeof(filepath), "%s/%s", g_photo_dir, entry->d_name);
         struct stat st;
         if (stat(filepath, &st) == 0) {
             strcpy(photos[photo_count].path, filepath);
@@ -125,6 +182,7 @@ static int cleanup_old_photos(unsigned long long target_free_bytes) {
         }
     }
     closedir(dir);
+    free(acknowledged);
 
     if (photo_count == 0) return 0;
 
@@ -155,7 +213,7 @@ static int cleanup_old_photos(unsigned long long target_free_bytes) {
 
     for (int i = 0; i < photo_count && freed_space < target_free_bytes; i++) {
         if (unlink(photos[i].path) == 0) {
-            log_ts("controller: Deleted old photo: %s (%lu bytes)\n", photos[i].path, photos[i].size);
+            log_ts("controller: Deleted acknowledged photo: %s (%lu bytes)\n", photos[i].path, photos[i].size);
             freed_space += photos[i].size;
             deleted_count++;
         } else {
@@ -174,7 +232,7 @@ static void ensure_storage_space(unsigned long long file_size_estimate) {
     const unsigned long long MIN_FREE_BYTES = MIN_FREE_MB * 1024 * 1024;
     const unsigned long long BUFFER_BYTES = 10 * 1024 * 1024; // 10MB buffer
 
-    unsigned long long available = get_available_space("/tmp");
+    unsigned long long available = get_available_space(g_photo_dir);
     unsigned long long available_mb = available / (1024 * 1024);
 
     // Check if we need cleanup (either below min free, or not enough for this file)
@@ -190,7 +248,7 @@ static void ensure_storage_space(unsigned long long file_size_estimate) {
         if (deleted > 0) {
             log_ts("controller: Cleaned up %d old photo(s) to free space\n", deleted);
         } else {
-            log_ts("controller: WARNING: No photos to delete, but storage is low!\n");
+            log_ts("controller: WARNING: No acknowledged photos to delete, but storage is low!\n");
         }
     }
 }
@@ -281,11 +339,11 @@ static void clear_failed_file(const char *filename) {
 }
 
 /*
- * Check if a file already exists locally in /tmp
+ * Check if a file already exists locally in the photo directory
  */
 static int file_exists_locally(const char *filename) {
     char path[512];
-    snprintf(path, sizeof(path), "/tmp/%s", filename);
+    snprintf(path, sizeof(path), "%s/%s", g_photo_dir, filename);
     FILE *f = fopen(path, "rb");
     if (f) {
         fclose(f);
@@ -421,7 +479,7 @@ int check_and_download_all_files(Camera *camera, GPContext *context) {
                                      GP_FILE_TYPE_NORMAL, file, context);
             if (ret >= GP_OK) {
                 char output_path[512];
-                snprintf(output_path, sizeof(output_path), "/tmp/%s", name);
+                snprintf(output_path, sizeof(output_path), "%s/%s", g_photo_dir, name);
 
                 // Check file size received from camera
                 const char *data;
@@ -493,7 +551,7 @@ int check_and_download_all_files(Camera *camera, GPContext *context) {
 
 /*
  * Download a single file from the camera by folder + name.
- * Saves to /tmp/<name> and emits a status event.
+ * Saves to <photo dir>/<name> and emits a status event.
  * Returns 0 on success, -1 on failure.
  */
 int download_file(Camera *camera, GPContext *context,
@@ -512,7 +570,7 @@ int download_file(Camera *camera, GPContext *context,
         return -1;
     }
 
-    snprintf(output_path, sizeof(output_path), "/tmp/%s", name);
+    snprintf(output_path, sizeof(output_path), "%s/%s", g_photo_dir, name);
 
     // Check file size received from camera
     const char *data;
@@ -617,10 +675,14 @@ void drain_camera_events(Camera *camera, GPContext *context, int timeout_ms) {
 /*
  * Execute software capture and download the resulting files
  */
-int do_capture(Camera *camera, GPContext *context) {
+int do_capture(Camera *camera, GPContext *context, long long *triggered_at_ms) {
     CameraFilePath path;
     int ret;
-    struct timespec t0, t1, t2;
+    struct timespec t0, t1, t2, wall;
+
+    clock_gettime(CLOCK_REALTIME, &wall);
+    if (triggered_at_ms)
+        *triggered_at_ms = (long long)wall.tv_sec * 1000 + wall.tv_nsec / 1000000;
 
     clock_gettime(CLOCK_MONOTONIC, &t0);
     log_ts("controller: Triggering capture...\n");
@@ -646,3 +708,62 @@ int do_capture(Camera *camera, GPContext *context) {
 
     return GP_OK;
 }
+
+/*
+ * Capture a burst of frames with the camera held open.
+ * Frames are triggered every interval_ms (measured trigger to trigger); if a capture
+ * and download take longer than the interval, the next frame fires immediately.
+ */
+int do_burst(Camera *camera, GPContext *context, long request_id, int count, int interval_ms) {
+    /* {"frames":[{"index":N,"triggered_at_ms":T},...],"requested":N,"error":"..."} */
+    size_t result_size = 128 + (size_t)count * 64;
+    char *result = malloc(result_size);
+    if (!result) {
+        send_response_error(request_id, "Out of memory starting burst");
+        return GP_ERROR_NO_MEMORY;
+    }
+
+    size_t pos = (size_t)snprintf(result, result_size, "{\"frames\":[");
+    int captured = 0;
+    int ret = GP_OK;
+    struct timespec start, now;
+    clock_gettime(CLOCK_MONOTONIC, &start);
+
+    log_ts("controller: Burst of %d frames every %dms\n", count, interval_ms);
+
+    for (int i = 0; i < count && g_running; i++) {
+        /* Wait for this frame's slot */
+        long long due_ms = (long long)i * interval_ms;
+        clock_gettime(CLOCK_MONOTONIC, &now);
+        long long elapsed_ms = (now.tv_sec - start.tv_sec) * 1000LL + (now.tv_nsec - start.tv_nsec) / 1000000;
+        if (elapsed_ms < due_ms)
+            usleep((useconds_t)((due_ms - elapsed_ms) * 1000));
+
+        long long triggered_at_ms = 0;
+        ret = do_capture(camera, context, &triggered_at_ms);
+        if (ret < GP_OK) {
+            log_ts("controller: Burst stopped at frame %d/%d: %s\n", i + 1, count, gp_result_as_string(ret));
+            break;
+        }
+
+        pos += (size_t)snprintf(result + pos, result_size - pos, "%s{\"index\":%d,\"triggered_at_ms\":%lld}",
+                                captured > 0 ? "," : "", i, triggered_at_ms);
+        captured++;
+    }
+
+    if (captured == 0) {
+        send_response_error(request_id, "Burst failed: %s", gp_result_as_string(ret));
+    } else {
+        if (ret < GP_OK) {
+            snprintf(result + pos, result_size - pos, "],\"requested\":%d,\"error\":\"%s\"}",
+                     count, gp_result_as_string(ret));
+        } else {
+            snprintf(result + pos, result_size - pos, "],\"requested\":%d}", count);
+        }
+        send_response_result(request_id, result);
+    }
+
+    log_ts("controller: Burst complete, %d/%d frames\n", captured, count);
+    free(result);
+    return ret;
+}
@@ -71,8 +71,23 @@ void drain_camera_events(Camera *camera, GPContext *context, int timeout_ms);
  *
  * @param camera The camera handle
  * @param context The gphoto2 context
+ * @param triggered_at_ms Set to the wall-clock time (ms since epoch) the capture was
+ *                        triggered, so the daemon can measure skew across cameras (optional)
  * @return GP_OK on success, error code on failure
  */
-int do_capture(Camera *camera, GPContext *context);
+int do_capture(Camera *camera, GPContext *context, long long *triggered_at_ms);
+
+/*
+ * Capture a burst of frames at a fixed interval without reopening the camera.
+ * Sends the response for request_id itself (frame trigger times, or the error).
+ *
+ * @param camera The camera handle
+ * @param context The gphoto2 context
+ * @param request_id Correlation id of the BURST request
+ * @param count Number of frames
+ * @param interval_ms Time between frame triggers
+ * @return GP_OK if every frame was captured, error code of the failing frame otherwise
+ */
+int do_burst(Camera *camera, GPContext *context, long request_id, int count, int interval_ms);
 
 #endif /* CAMERA_CAPTURE_H */
@@ -15,9 +15,7 @@
 #include <stdarg.h>
 
 #include "camera-brand.h"
-
-/* Path for config response JSON file */
-#define CONFIG_RESPONSE_FILE "/tmp/camera_config_response"
+#include "protocol.h"
 
 /* Timestamped logging - adds HH:MM:SS prefix to all stderr output */
 static void log_timestamped(const char *format, ...) {
@@ -39,31 +37,6 @@ static void log_timestamped(const char *format, ...) {
 
 #define log_ts(...) log_timestamped(__VA_ARGS__)
 
-/*
- * Extract a value from JSON string by key
- * Returns a newly allocated string that must be freed by caller, or NULL if not found
- */
-static char *extract_json_value(const char *json, const char *key) {
-    char search[128];
-    snprintf(search, sizeof(search), "\"%s\":\"", key);
-
-    const char *start = strstr(json, search);
-    if (!start) return NULL;
-
-    start += strlen(search);  // Move past the key and ":\"
-
-    const char *end = strchr(start, '"');
-    if (!end) return NULL;
-
-    size_t len = end - start;
-    char *result = malloc(len + 1);
-    if (result) {
-        memcpy(result, start, len);
-        result[len] = '\0';
-    }
-    return result;
-}
-
 /*
  * Find a widget by name or path (e.g., "parent.child") in the config tree
  */
@@ -357,26 +330,19 @@ static const char* widget_value_str(CameraWidget *widget) {
 }
 
 /*
- * Write full camera config JSON to CONFIG_RESPONSE_FILE.
+ * Send the full camera config JSON as the response to request_id.
  * Output matches the format produced by gphoto2-wrapper's get_config():
  *   {"iso":{"value":"800","label":"ISO Speed","type":"radio","choices":["100","200",...]}, ...}
  *
- * Uses atomic write (temp file + rename) to avoid partial reads.
- * Returns 0 on success, -1 on error.
+ * Returns 0 on success, -1 on error (an error response is sent in that case).
  */
-int write_full_config_json(Camera *camera, GPContext *context, CameraBrand current_brand) {
+int send_full_config_json(Camera *camera, GPContext *context, CameraBrand current_brand, long request_id) {
     CameraWidget *config = NULL;
     int ret;
 
     ret = gp_camera_get_config(camera, &config, context);
     if (ret < GP_OK) {
-        /* Write error JSON */
-        FILE *f = fopen(CONFIG_RESPONSE_FILE ".tmp", "w");
-        if (f) {
-            fprintf(f, "{\"error\":\"Failed to get config: %s\"}\n", gp_result_as_string(ret));
-            fclose(f);
-            rename(CONFIG_RESPONSE_FILE ".tmp", CONFIG_RESPONSE_FILE);
-        }
+        send_response_error(request_id, "Failed to get config: %s", gp_result_as_string(ret));
         return -1;
     }
 
@@ -472,6 +438,7 @@ int write_full_config_json(Camera *camera, GPContext *context, CameraBrand curre
     char *buf = malloc(CONFIG_BUF_SIZE);
     if (!buf) {
         gp_widget_free(config);
+        send_response_error(request_id, "Out of memory building config response");
         return -1;
     }
 
@@ -556,61 +523,26 @@ int write_full_config_json(Camera *camera, GPContext *context, CameraBrand curre
     off += snprintf(buf + off, CONFIG_BUF_SIZE - off, "}\n");
     gp_widget_free(config);
 
-    /* Atomic write: temp file + rename */
-    FILE *f = fopen(CONFIG_RESPONSE_FILE ".tmp", "w");
-    if (!f) {
-        log_ts("controller: Failed to open config response temp file: %s\n", strerror(errno));
-        free(buf);
-        return -1;
-    }
-    fwrite(buf, 1, off, f);
-    fclose(f);
+    send_response_result(request_id, buf);
     free(buf);
 
-    if (rename(CONFIG_RESPONSE_FILE ".tmp", CONFIG_RESPONSE_FILE) != 0) {
-        log_ts("controller: Failed to rename config response file: %s\n", strerror(errno));
-        return -1;
-    }
-
-    log_ts("controller: Wrote config response (%d bytes)\n", off);
+    log_ts("controller: Sent config response (%d bytes)\n", off);
     return 0;
 }
 
 /*
- * Set a single camera config value and write result to CONFIG_RESPONSE_FILE.
- * json_input format: {"setting":"iso","value":"800"}
+ * Set a single camera config value and send the result as the response to request_id.
  * Returns 0 on success, -1 on error.
  */
-int set_config_and_write_response(Camera *camera, GPContext *context, const char *json_input) {
-    char *setting = extract_json_value(json_input, "setting");
-    char *value = extract_json_value(json_input, "value");
-
-    if (!setting || !value) {
-        FILE *f = fopen(CONFIG_RESPONSE_FILE ".tmp", "w");
-        if (f) {
-            fprintf(f, "{\"error\":\"JSON must contain 'setting' and 'value' keys\"}\n");
-            fclose(f);
-            rename(CONFIG_RESPONSE_FILE ".tmp", CONFIG_RESPONSE_FILE);
-        }
-        if (setting) free(setting);
-        if (value) free(value);
-        return -1;
-    }
-
+int set_config_and_send_response(Camera *camera, GPContext *context, long request_id,
+                                 const char *setting, const char *value) {
     log_ts("controller: SETCONFIG %s = %s\n", setting, value);
 
     /* Get the widget */
     CameraWidget *widget = NULL;
     int ret = gp_camera_get_single_config(camera, setting, &widget, context);
     if (ret < GP_OK || !widget) {
-        FILE *f = fopen(CONFIG_RESPONSE_FILE ".tmp", "w");
-        if (f) {
-            fprintf(f, "{\"error\":\"Setting '%s' not found: %s\"}\n", setting, gp_result_as_string(ret));
-            fclose(f);
-            rename(CONFIG_RESPONSE_FILE ".tmp", CONFIG_RESPONSE_FILE);
-        }
-        free(setting);
-        free(value);
+        send_response_error(request_id, "Setting '%s' not found: %s", setting, gp_result_as_string(ret));
         return -1;
     }
 
@@ -645,29 +577,31 @@ int set_config_and_write_response(Camera *camera, GPContext *context, const char
         set_ret = GP_ERROR;
     }
 
-    /* Write response */
-    FILE *f = fopen(CONFIG_RESPONSE_FILE ".tmp", "w");
-    if (f) {
-        if (set_ret < GP_OK) {
-            fprintf(f, "{\"error\":\"Failed to set %s: %s\"}\n",
-                    setting, error_msg ? error_msg : gp_result_as_string(set_ret));
+    if (set_ret < GP_OK) {
+        send_response_error(request_id, "Failed to set %s: %s",
+                            setting, error_msg ? error_msg : gp_result_as_string(set_ret));
+    } else {
+        /* Save to camera */
+        int save_ret = gp_camera_set_single_config(camera, setting, widget, context);
+        char result[1024];
+        int off = 0;
+
+        if (save_ret < GP_OK) {
+            off += snprintf(result + off, sizeof(result) - off,
+                            "{\"success\":true,\"warning\":\"Value set but failed to save to camera: ");
+            off += json_escape_append(result + off, sizeof(result) - off, gp_result_as_string(save_ret));
+            off += snprintf(result + off, sizeof(result) - off, "\"}");
         } else {
-            /* Save to camera */
-            int save_ret = gp_camera_set_single_config(camera, setting, widget, context);
-            if (save_ret < GP_OK) {
-                fprintf(f, "{\"warning\":\"Value set but failed to save to camera: %s\"}\n",
-                        gp_result_as_string(save_ret));
-            } else {
-                fprintf(f, "{\"success\":true,\"setting\":\"%s\",\"value\":\"%s\"}\n", setting, value);
-            }
+            off += snprintf(result + off, sizeof(result) - off, "{\"success\":true,\"setting\":\"");
+            off += json_escape_append(result + off, sizeof(result) - off, setting);
+            off += snprintf(result + off, sizeof(result) - off, "\",\"value\":\"");
+            off += json_escape_append(result + off, sizeof(result) - off, value);
+            off += snprintf(result + off, sizeof(result) - off, "\"}");
         }
-        fclose(f);
-        rename(CONFIG_RESPONSE_FILE ".tmp", CONFIG_RESPONSE_FILE);
+        send_response_result(request_id, result);
     }
 
     gp_widget_free(widget);
-    free(setting);
-    free(value);
     return (set_ret >= GP_OK) ? 0 : -1;
 }
 
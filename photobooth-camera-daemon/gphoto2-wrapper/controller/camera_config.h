@@ -34,30 +34,32 @@ char *get_single_config_value(Camera *camera, GPContext *context, const char *se
 int get_camera_status_json(Camera *camera, GPContext *context, char *status_json, size_t max_size, CameraBrand current_brand);
 
 /*
- * Write full camera config JSON to CONFIG_RESPONSE_FILE
+ * Send full camera config JSON as the response to a CONFIG request
  * Output format: {"iso":{"value":"800","label":"ISO Speed","type":"radio","choices":[...]}, ...}
  *
- * Uses atomic write (temp file + rename) to avoid partial reads.
- * Returns 0 on success, -1 on error.
+ * Returns 0 on success, -1 on error (an error response is sent in that case).
  *
  * Parameters:
  *   camera - gphoto2 camera handle
  *   context - gphoto2 context
- *   current_brand - detected camera brand (unused internally but kept for API consistency)
+ *   current_brand - detected camera brand for the settings list
+ *   request_id - correlation id of the request (see protocol.h)
  */
-int write_full_config_json(Camera *camera, GPContext *context, CameraBrand current_brand);
+int send_full_config_json(Camera *camera, GPContext *context, CameraBrand current_brand, long request_id);
 
 /*
- * Set a single camera config value and write result to CONFIG_RESPONSE_FILE
- * json_input format: {"setting":"iso","value":"800"}
+ * Set a single camera config value and send the result as the response to a SETCONFIG request
  *
  * Returns 0 on success, -1 on error.
  *
  * Parameters:
  *   camera - gphoto2 camera handle
  *   context - gphoto2 context
- *   json_input - JSON string containing "setting" and "value" keys
+ *   request_id - correlation id of the request (see protocol.h)
+ *   setting - widget name (e.g. "iso")
+ *   value - new value as a string
  */
-int set_config_and_write_response(Camera *camera, GPContext *context, const char *json_input);
+int set_config_and_send_response(Camera *camera, GPContext *context, long request_id,
+                                 const char *setting, const char *value);
 
 #endif /* CAMERA_CONFIG_H */
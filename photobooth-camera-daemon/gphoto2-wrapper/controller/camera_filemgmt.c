@@ -12,8 +12,9 @@
 
 #include "camera_filemgmt.h"
 
-/* External logging function from gphoto2-controller.c */
+/* External logging function and photo directory from gphoto2-controller.c */
 extern void log_timestamped(const char *format, ...);
+extern char g_photo_dir[];
 #define log_ts(...) log_timestamped(__VA_ARGS__)
 
 /* Failed file tracking */
@@ -95,10 +96,10 @@ void clear_failed_file(const char *filename) {
     g_failed_file_count--;
 }
 
-/* Check if a file already exists locally in /tmp */
+/* Check if a file already exists locally in the photo directory */
 int file_exists_locally(const char *filename) {
     char path[512];
-    snprintf(path, sizeof(path), "/tmp/%s", filename);
+    snprintf(path, sizeof(path), "%s/%s", g_photo_dir, filename);
     FILE *f = fopen(path, "rb");
     if (f) {
         fclose(f);
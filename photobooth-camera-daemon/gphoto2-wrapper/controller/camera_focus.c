@@ -0,0 +1,243 @@
+/*
+ * camera_focus.c - Focus and live view zoom operations
+ *
+ * Handles the FOCUS request: autofocus, manual focus steps, AF point and live
+ * view zoom, using whichever focus widgets the body exposes (see camera_focus.h).
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <gphoto2/gphoto2.h>
+
+#include "camera_focus.h"
+#include "camera_preview.h"
+#include "widget_ops.h"
+
+/* Timestamped logging function from controller */
+extern void log_timestamped(const char *format, ...);
+#define log_ts(...) log_timestamped(__VA_ARGS__)
+
+/* Pause between manual focus steps (bodies answer busy when driven back to back) */
+#define FOCUS_STEP_DELAY_US 100000
+
+/* How long the Sony AF half-press is held before it is released */
+#define SONY_AF_HOLD_US 800000
+
+/* Widgets per action, most common first */
+static const char *autofocus_widgets[] = { "autofocusdrive", "autofocus", NULL };
+static const char *cancel_widgets[] = { "cancelautofocus", "autofocus", NULL };
+static const char *drive_widgets[] = { "manualfocusdrive", "manualfocus", NULL };
+static const char *point_widgets[] = { "changeafarea", NULL };
+static const char *zoom_widgets[] = { "eoszoom", NULL };
+
+/*
+ * Find the first widget in names the camera has.
+ * Returns its name, or NULL if none exists. When widget is not NULL it receives the
+ * widget, which the caller must free.
+ */
+static const char *find_widget(Camera *camera, GPContext *context, const char **names, CameraWidget **widget) {
+    for (int i = 0; names[i]; i++) {
+        CameraWidget *found = NULL;
+        if (gp_camera_get_single_config(camera, names[i], &found, context) >= GP_OK && found) {
+            if (widget) {
+                *widget = found;
+            } else {
+                gp_widget_free(found);
+            }
+            return names[i];
+        }
+    }
+    return NULL;
+}
+
+static int set_toggle(Camera *camera, GPContext *context, const char *name, CameraWidget *widget, int value) {
+    int ret = gp_widget_set_value(widget, &value);
+    if (ret < GP_OK) {
+        return ret;
+    }
+    return gp_camera_set_single_config(camera, name, widget, context);
+}
+
+static int set_float(Camera *camera, GPContext *context, const char *name, CameraWidget *widget, float value) {
+    int ret = gp_widget_set_value(widget, &value);
+    if (ret < GP_OK) {
+        return ret;
+    }
+    return gp_camera_set_single_config(camera, name, widget, context);
+}
+
+static int set_string(Camera *camera, GPContext *context, const char *name, CameraWidget *widget, const char *value) {
+    int ret = gp_widget_set_value(widget, value);
+    if (ret < GP_OK) {
+        return ret;
+    }
+    return gp_camera_set_single_config(camera, name, widget, context);
+}
+
+/* Append "key":"name" (or "key":null) to a JSON object being built in buf */
+static int append_widget_field(char *buf, size_t max, const char *key, const char *name) {
+    int off = snprintf(buf, max, "\"%s\":", key);
+    if (!name) {
+        return off + snprintf(buf + off, max - off, "null");
+    }
+    off += snprintf(buf + off, max - off, "\"");
+    off += json_escape_append(buf + off, max - off, name);
+    return off + snprintf(buf + off, max - off, "\"");
+}
+
+static int send_capabilities(Camera *camera, GPContext *context, long request_id) {
+    const char *drive = find_widget(camera, context, drive_widgets, NULL);
+    char result[512];
+    int off = snprintf(result, sizeof(result), "{");
+    off += append_widget_field(result + off, sizeof(result) - off, "autofocus",
+                               find_widget(camera, context, autofocus_widgets, NULL));
+    off += snprintf(result + off, sizeof(result) - off, ",");
+    off += append_widget_field(result + off, sizeof(result) - off, "cancel",
+                               find_widget(camera, context, cancel_widgets, NULL));
+    off += snprintf(result + off, sizeof(result) - off, ",");
+    off += append_widget_field(result + off, sizeof(result) - off, "near", drive);
+    off += snprintf(result + off, sizeof(result) - off, ",");
+    off += append_widget_field(result + off, sizeof(result) - off, "far", drive);
+    off += snprintf(result + off, sizeof(result) - off, ",");
+    off += append_widget_field(result + off, sizeof(result) - off, "point",
+                               find_widget(camera, context, point_widgets, NULL));
+    off += snprintf(result + off, sizeof(result) - off, ",");
+    off += append_widget_field(result + off, sizeof(result) - off, "zoom",
+                               find_widget(camera, context, zoom_widgets, NULL));
+    snprintf(result + off, sizeof(result) - off, "}");
+    send_response_result(request_id, result);
+    return 0;
+}
+
+static int autofocus(Camera *camera, GPContext *context, const char *name, CameraWidget *widget) {
+    int ret = set_toggle(camera, context, name, widget, 1);
+    /* Sony's "autofocus" is a half-press: hold it while the lens focuses, then let go */
+    if (ret >= GP_OK && strcmp(name, "autofocus") == 0) {
+        usleep(SONY_AF_HOLD_US);
+        ret = set_toggle(camera, context, name, widget, 0);
+    }
+    return ret;
+}
+
+static int cancel_autofocus(Camera *camera, GPContext *context, const char *name, CameraWidget *widget) {
+    return set_toggle(camera, context, name, widget, strcmp(name, "autofocus") == 0 ? 0 : 1);
+}
+
+/*
+ * Drive focus towards near or far by steps of the body's smallest unit.
+ * Returns the number of steps taken; *error receives the gphoto2 error if it stopped early.
+ */
+static int drive_focus(Camera *camera, GPContext *context, const char *name, CameraWidget *widget,
+                       int near, int steps, int *error) {
+    CameraWidgetType type;
+    gp_widget_get_type(widget, &type);
+    *error = GP_OK;
+
+    /* Nikon takes the whole move as one signed range value (negative = near) */
+    if (type == GP_WIDGET_RANGE && strcmp(name, "manualfocusdrive") == 0) {
+        *error = set_float(camera, context, name, widget, near ? -(float)steps : (float)steps);
+        return *error >= GP_OK ? steps : 0;
+    }
+
+    for (int i = 0; i < steps; i++) {
+        if (i > 0) {
+            usleep(FOCUS_STEP_DELAY_US);
+        }
+        int ret;
+        if (type == GP_WIDGET_RADIO || type == GP_WIDGET_MENU) {
+            /* Canon: "Near 1" / "Far 1" are the finest steps */
+            ret = set_string(camera, context, name, widget, near ? "Near 1" : "Far 1");
+        } else if (type == GP_WIDGET_RANGE) {
+            /* Sony: -7..7, magnitude is the step size */
+            ret = set_float(camera, context, name, widget, near ? -1.0f : 1.0f);
+        } else {
+            ret = GP_ERROR_NOT_SUPPORTED;
+        }
+        if (ret < GP_OK) {
+            *error = ret;
+            return i;
+        }
+    }
+    return steps;
+}
+
+int focus_and_send_response(Camera *camera, GPContext *context, const ControllerRequest *req) {
+    const char *action = req->action;
+    log_ts("controller: FOCUS %s (steps %d, point %d,%d, level %d)\n",
+           action, req->steps, req->x, req->y, req->level);
+
+    if (strcmp(action, "capabilities") == 0) {
+        return send_capabilities(camera, context, req->id);
+    }
+
+    const char **candidates;
+    if (strcmp(action, "autofocus") == 0) {
+        candidates = autofocus_widgets;
+    } else if (strcmp(action, "cancel") == 0) {
+        candidates = cancel_widgets;
+    } else if (strcmp(action, "near") == 0 || strcmp(action, "far") == 0) {
+        candidates = drive_widgets;
+    } else if (strcmp(action, "point") == 0) {
+        candidates = point_widgets;
+    } else if (strcmp(action, "zoom") == 0) {
+        candidates = zoom_widgets;
+    } else {
+        send_response_error(req->id, "Unknown FOCUS action: %s", action);
+        return -1;
+    }
+
+    CameraWidget *widget = NULL;
+    const char *name = find_widget(camera, context, candidates, &widget);
+    if (!name) {
+        send_response_error(req->id, "Focus action '%s' is not supported by this camera", action);
+        return -1;
+    }
+
+    char result[512];
+    int off = snprintf(result, sizeof(result), "{\"action\":\"%s\",\"widget\":\"%s\"", action, name);
+    int ret = GP_OK;
+
+    if (strcmp(action, "autofocus") == 0) {
+        ret = autofocus(camera, context, name, widget);
+    } else if (strcmp(action, "cancel") == 0) {
+        ret = cancel_autofocus(camera, context, name, widget);
+    } else if (strcmp(action, "near") == 0 || strcmp(action, "far") == 0) {
+        int taken = drive_focus(camera, context, name, widget, strcmp(action, "near") == 0, req->steps, &ret);
+        if (ret < GP_OK) {
+            send_response_error(req->id, "Focus drive stopped after %d of %d steps: %s",
+                                taken, req->steps, gp_result_as_string(ret));
+            gp_widget_free(widget);
+            return -1;
+        }
+        off += snprintf(result + off, sizeof(result) - off, ",\"steps\":%d", taken);
+    } else if (strcmp(action, "point") == 0) {
+        if (g_preview_width <= 0 || g_preview_height <= 0) {
+            send_response_error(req->id, "Setting the AF point needs live view running");
+            gp_widget_free(widget);
+            return -1;
+        }
+        int px = req->x * (g_preview_width - 1) / FOCUS_POINT_SCALE;
+        int py = req->y * (g_preview_height - 1) / FOCUS_POINT_SCALE;
+        char value[32];
+        snprintf(value, sizeof(value), "%dx%d", px, py);
+        ret = set_string(camera, context, name, widget, value);
+        off += snprintf(result + off, sizeof(result) - off, ",\"value\":\"%s\",\"frame\":[%d,%d]",
+                        value, g_preview_width, g_preview_height);
+    } else {
+        char value[16];
+        snprintf(value, sizeof(value), "%d", req->level);
+        ret = set_string(camera, context, name, widget, value);
+        off += snprintf(result + off, sizeof(result) - off, ",\"value\":\"%s\"", value);
+    }
+    gp_widget_free(widget);
+
+    if (ret < GP_OK) {
+        send_response_error(req->id, "Focus action '%s' failed: %s", action, gp_result_as_string(ret));
+        return -1;
+    }
+    snprintf(result + off, sizeof(result) - off, "}");
+    send_response_result(req->id, result);
+    return 0;
+}
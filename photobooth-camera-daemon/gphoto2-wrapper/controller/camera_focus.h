@@ -0,0 +1,34 @@
+/*
+ * camera_focus.h - Focus and live view zoom operations interface
+ *
+ * Drives autofocus, manual focus steps, the AF point and live view zoom through
+ * whichever widgets the body exposes:
+ *   autofocus  autofocusdrive (Canon, Nikon, Fuji), autofocus half-press (Sony)
+ *   cancel     cancelautofocus (Canon), autofocus release (Sony)
+ *   near/far   manualfocusdrive (Canon radio "Near 1".."Far 3", Nikon range),
+ *              manualfocus (Sony range)
+ *   point      changeafarea (Nikon, "XxY" in live view pixels)
+ *   zoom       eoszoom (Canon)
+ */
+
+#ifndef CAMERA_FOCUS_H
+#define CAMERA_FOCUS_H
+
+#include <gphoto2/gphoto2.h>
+#include "protocol.h"
+
+/*
+ * Run a FOCUS request and send the response.
+ * The "capabilities" action reports the widget used for each action (null when the
+ * body has none) without changing anything.
+ *
+ * Returns 0 on success, -1 on error (an error response is sent in that case).
+ *
+ * Parameters:
+ *   camera - gphoto2 camera handle
+ *   context - gphoto2 context
+ *   req - parsed FOCUS request (action, steps, x, y, level)
+ */
+int focus_and_send_response(Camera *camera, GPContext *context, const ControllerRequest *req);
+
+#endif /* CAMERA_FOCUS_H */
@@ -3,6 +3,7 @@
  *
  * Contains:
  * - stream_preview_frame: Stream preview frame in MJPEG format to pipe
+ * - g_preview_width/height: size of the last frame (maps live view points for FOCUS)
  */
 
 #include <stdio.h>
@@ -19,9 +20,8 @@
 
 #include "camera_preview.h"
 
-/* Pipe paths for streaming and status */
-#define STREAM_PIPE "/tmp/camera_stream"
-#define STATUS_PIPE "/tmp/camera_status"
+/* Pipe paths for streaming and status - defined in gphoto2-controller.c */
+extern char g_stream_pipe[];
 
 /* Global streaming state - defined in gphoto2-controller.c */
 extern volatile sig_atomic_t g_streaming_active;
@@ -34,6 +34,46 @@ extern volatile sig_atomic_t g_running;
 extern void log_timestamped(const char *format, ...);
 #define log_ts(...) log_timestamped(__VA_ARGS__)
 
+int g_preview_width = 0;
+int g_preview_height = 0;
+
+/*
+ * Read width and height from the first SOF marker of a JPEG.
+ * Returns 0 on success, -1 if the data is not a JPEG or has no frame header.
+ */
+static int jpeg_frame_size(const unsigned char *data, unsigned long size, int *width, int *height) {
+    if (size < 4 || data[0] != 0xFF || data[1] != 0xD8) {
+        return -1;
+    }
+
+    unsigned long pos = 2;
+    while (pos + 4 <= size) {
+        if (data[pos] != 0xFF) {
+            return -1;
+        }
+        unsigned char marker = data[pos + 1];
+        if (marker == 0xFF) {
+            pos++;
+            continue;
+        }
+        unsigned long length = ((unsigned long)data[pos + 2] << 8) | data[pos + 3];
+        /* SOF0..SOF15, except DHT (C4), JPG (C8) and DAC (CC) */
+        if (marker >= 0xC0 && marker <= 0xCF && marker != 0xC4 && marker != 0xC8 && marker != 0xCC) {
+            if (pos + 9 > size) {
+                return -1;
+            }
+            *height = (data[pos + 5] << 8) | data[pos + 6];
+            *width = (data[pos + 7] << 8) | data[pos + 8];
+            return 0;
+        }
+        if (marker == 0xDA || length < 2) {
+            return -1;
+        }
+        pos += 2 + length;
+    }
+    return -1;
+}
+
 /*
  * Stream a single preview frame to the stream pipe (MJPEG format)
  *
@@ -58,7 +98,7 @@ int stream_preview_frame(Camera *camera, GPContext *context) {
 
     /* Lazy open stream pipe if not already open */
     if (g_stream_fd < 0 && g_streaming_active) {
-        g_stream_fd = open(STREAM_PIPE, O_WRONLY | O_NONBLOCK);
+        g_stream_fd = open(g_stream_pipe, O_WRONLY | O_NONBLOCK);
         if (g_stream_fd < 0) {
             /* Pipe not ready yet (no reader), will retry next frame */
             return GP_OK;
@@ -102,6 +142,12 @@ int stream_preview_frame(Camera *camera, GPContext *context) {
         return ret;
     }
 
+    int width = 0, height = 0;
+    if (jpeg_frame_size((const unsigned char *)data, size, &width, &height) == 0) {
+        g_preview_width = width;
+        g_preview_height = height;
+    }
+
     /* Output frame with MJPEG boundary marker */
     if (g_stream_fd >= 0) {
         char header[256];
@@ -21,6 +21,10 @@ extern volatile sig_atomic_t g_streaming_paused;   /* Flag for pause during oper
 extern int g_stream_fd;                            /* MJPEG stream pipe file descriptor */
 extern int g_status_fd;                            /* Status pipe file descriptor */
 
+/* Size of the last streamed preview frame (0 until a frame has been read) */
+extern int g_preview_width;
+extern int g_preview_height;
+
 /*
  * Stream a single preview frame in MJPEG format
  *
@@ -141,3 +141,29 @@ void ensure_storage_space(unsigned long long file_size_estimate) {
         }
     }
 }
+
+/* Free space on the camera's memory card(s), summed over all storages.
+ * Sets -1 for values the camera doesn't report. Returns GP_OK or a gphoto2 error. */
+int get_card_space(Camera *camera, GPContext *context, long *shots_remaining, long long *free_mb) {
+    CameraStorageInformation *storages = NULL;
+    int count = 0;
+
+    *shots_remaining = -1;
+    *free_mb = -1;
+
+    int ret = gp_camera_get_storageinfo(camera, &storages, &count, context);
+    if (ret < GP_OK) {
+        return ret;
+    }
+
+    for (int i = 0; i < count; i++) {
+        if (storages[i].fields & GP_STORAGEINFO_FREESPACEIMAGES) {
+            *shots_remaining = (*shots_remaining < 0 ? 0 : *shots_remaining) + (long)storages[i].freeimages;
+        }
+        if (storages[i].fields & GP_STORAGEINFO_FREESPACEKBYTES) {
+            *free_mb = (*free_mb < 0 ? 0 : *free_mb) + (long long)(storages[i].freekbytes / 1024);
+        }
+    }
+    free(storages);
+    return GP_OK;
+}
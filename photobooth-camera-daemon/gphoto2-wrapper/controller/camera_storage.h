@@ -2,6 +2,7 @@
 #define CAMERA_STORAGE_H
 
 #include <sys/types.h>
+#include <gphoto2/gphoto2.h>
 
 /* Check available disk space in bytes for a given path */
 unsigned long long get_available_space(const char *path);
@@ -15,4 +16,8 @@ int cleanup_old_photos(unsigned long long target_free_bytes);
 /* Ensure sufficient storage space before saving a file */
 void ensure_storage_space(unsigned long long file_size_estimate);
 
+/* Free space on the camera's memory card(s), summed over all storages.
+ * Sets -1 for values the camera doesn't report. Returns GP_OK or a gphoto2 error. */
+int get_card_space(Camera *camera, GPContext *context, long *shots_remaining, long long *free_mb);
+
 #endif /* CAMERA_STORAGE_H */
@@ -0,0 +1,369 @@
+/*
+ * protocol.c - Request/response protocol between the camera daemon and the controller
+ *
+ * See protocol.h for the wire format.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <errno.h>
+#include <stdarg.h>
+#include <ctype.h>
+
+#include "protocol.h"
+
+extern int g_status_fd;
+extern void log_timestamped(const char *format, ...);
+#define log_ts(...) log_timestamped(__VA_ARGS__)
+
+/* ============================================================================
+ * REQUEST PARSING
+ * ============================================================================ */
+
+/*
+ * Find the value for a top-level key in a flat JSON object.
+ * Returns a pointer to the first character of the value, or NULL.
+ */
+static const char *json_find_value(const char *json, const char *key)
+{
+    char search[REQUEST_SETTING_MAX];
+    snprintf(search, sizeof(search), "\"%s\"", key);
+    size_t search_len = strlen(search);
+
+    const char *p = json;
+    while ((p = strstr(p, search)) != NULL)
+    {
+        const char *after = p + search_len;
+        while (isspace((unsigned char)*after))
+            after++;
+        if (*after == ':')
+        {
+            after++;
+            while (isspace((unsigned char)*after))
+                after++;
+            return after;
+        }
+        p = after;
+    }
+    return NULL;
+}
+
+/* Append a code point to out as UTF-8 (BMP only, surrogates are replaced) */
+static size_t utf8_append(char *out, size_t pos, size_t out_size, unsigned int cp)
+{
+    if (cp >= 0xD800 && cp <= 0xDFFF)
+        cp = '?';
+
+    if (cp < 0x80 && pos + 1 < out_size)
+    {
+        out[pos++] = (char)cp;
+    }
+    else if (cp < 0x800 && pos + 2 < out_size)
+    {
+        out[pos++] = (char)(0xC0 | (cp >> 6));
+        out[pos++] = (char)(0x80 | (cp & 0x3F));
+    }
+    else if (cp >= 0x800 && pos + 3 < out_size)
+    {
+        out[pos++] = (char)(0xE0 | (cp >> 12));
+        out[pos++] = (char)(0x80 | ((cp >> 6) & 0x3F));
+        out[pos++] = (char)(0x80 | (cp & 0x3F));
+    }
+    return pos;
+}
+
+/*
+ * Read a JSON string value into out, decoding escape sequences.
+ * Returns 0 on success, -1 if the key is missing, not a string, or too long.
+ */
+static int json_get_string(const char *json, const char *key, char *out, size_t out_size)
+{
+    const char *p = json_find_value(json, key);
+    if (!p || *p != '"')
+        return -1;
+    p++;
+
+    size_t pos = 0;
+    while (*p && *p != '"')
+    {
+        if (pos + 1 >= out_size)
+            return -1;
+
+        if (*p != '\\')
+        {
+            out[pos++] = *p++;
+            continue;
+        }
+
+        p++;
+        switch (*p)
+        {
+            case '"':  out[pos++] = '"';  break;
+            case '\\': out[pos++] = '\\'; break;
+            case '/':  out[pos++] = '/';  break;
+            case 'b':  out[pos++] = '\b'; break;
+            case 'f':  out[pos++] = '\f'; break;
+            case 'n':  out[pos++] = '\n'; break;
+            case 'r':  out[pos++] = '\r'; break;
+            case 't':  out[pos++] = '\t'; break;
+            case 'u':
+            {
+                char hex[5] = {0};
+                for (int i = 0; i < 4; i++)
+                {
+                    if (!isxdigit((unsigned char)p[1 + i]))
+                        return -1;
+                    hex[i] = p[1 + i];
+                }
+                pos = utf8_append(out, pos, out_size, (unsigned int)strtoul(hex, NULL, 16));
+                p += 4;
+                break;
+            }
+            default:
+                return -1;
+        }
+        p++;
+    }
+
+    if (*p != '"')
+        return -1;
+
+    out[pos] = '\0';
+    return 0;
+}
+
+/* Read a JSON integer value. Returns 0 on success, -1 if missing or not a number. */
+static int json_get_long(const char *json, const char *key, long *out)
+{
+    const char *p = json_find_value(json, key);
+    if (!p)
+        return -1;
+
+    char *end = NULL;
+    errno = 0;
+    long value = strtol(p, &end, 10);
+    if (end == p || errno != 0)
+        return -1;
+
+    *out = value;
+    return 0;
+}
+
+int parse_controller_request(const char *line, ControllerRequest *req)
+{
+    memset(req, 0, sizeof(*req));
+
+    /* JSON request from the daemon */
+    if (line[0] == '{')
+    {
+        long camera_index = 0;
+
+        if (json_get_long(line, "id", &req->id) != 0 || req->id <= 0)
+        {
+            log_ts("controller: Request without a valid id: %s\n", line);
+            return -1;
+        }
+        if (json_get_string(line, "cmd", req->cmd, sizeof(req->cmd)) != 0)
+        {
+            send_response_error(req->id, "Request is missing 'cmd'");
+            return -1;
+        }
+
+        if (strcmp(req->cmd, "SETCONFIG") == 0)
+        {
+            if (json_get_string(line, "setting", req->setting, sizeof(req->setting)) != 0 ||
+                json_get_string(line, "value", req->value, sizeof(req->value)) != 0)
+            {
+                send_response_error(req->id, "SETCONFIG requires string 'setting' and 'value'");
+                return -1;
+            }
+        }
+        else if (strcmp(req->cmd, "BURST") == 0)
+        {
+            long count = 0, interval_ms = 0;
+            if (json_get_long(line, "count", &count) != 0 || count < 1 || count > BURST_MAX_FRAMES)
+            {
+                send_response_error(req->id, "BURST requires 'count' between 1 and %d", BURST_MAX_FRAMES);
+                return -1;
+            }
+            if (json_get_long(line, "interval_ms", &interval_ms) != 0 || interval_ms < 0 || interval_ms > 60000)
+            {
+                send_response_error(req->id, "BURST requires 'interval_ms' between 0 and 60000");
+                return -1;
+            }
+            req->count = (int)count;
+            req->interval_ms = (int)interval_ms;
+        }
+        else if (strcmp(req->cmd, "FOCUS") == 0)
+        {
+            long steps = 1, x = 0, y = 0, level = 0;
+            if (json_get_string(line, "action", req->action, sizeof(req->action)) != 0)
+            {
+                send_response_error(req->id, "FOCUS requires a string 'action'");
+                return -1;
+            }
+            if ((strcmp(req->action, "near") == 0 || strcmp(req->action, "far") == 0) &&
+                json_find_value(line, "steps") &&
+                (json_get_long(line, "steps", &steps) != 0 || steps < 1 || steps > FOCUS_MAX_STEPS))
+            {
+                send_response_error(req->id, "FOCUS %s requires 'steps' between 1 and %d", req->action, FOCUS_MAX_STEPS);
+                return -1;
+            }
+            if (strcmp(req->action, "point") == 0 &&
+                (json_get_long(line, "x", &x) != 0 || x < 0 || x > FOCUS_POINT_SCALE ||
+                 json_get_long(line, "y", &y) != 0 || y < 0 || y > FOCUS_POINT_SCALE))
+            {
+                send_response_error(req->id, "FOCUS point requires 'x' and 'y' between 0 and %d", FOCUS_POINT_SCALE);
+                return -1;
+            }
+            if (strcmp(req->action, "zoom") == 0 &&
+                (json_get_long(line, "level", &level) != 0 || level < 1))
+            {
+                send_response_error(req->id, "FOCUS zoom requires a positive 'level'");
+                return -1;
+            }
+            req->steps = (int)steps;
+            req->x = (int)x;
+            req->y = (int)y;
+            req->level = (int)level;
+        }
+        else if (strcmp(req->cmd, "SWITCH_CAMERA") == 0)
+        {
+            if (json_get_long(line, "camera_index", &camera_index) != 0 || camera_index < 0)
+            {
+                send_response_error(req->id, "SWITCH_CAMERA requires a non-negative 'camera_index'");
+                return -1;
+            }
+            req->camera_index = (int)camera_index;
+        }
+        return 0;
+    }
+
+    /* Legacy plain-text command */
+    if (strncmp(line, "SWITCH_CAMERA ", 14) == 0)
+    {
+        strcpy(req->cmd, "SWITCH_CAMERA");
+        req->camera_index = atoi(line + 14);
+        return 0;
+    }
+    if (strncmp(line, "SETCONFIG ", 10) == 0)
+    {
+        strcpy(req->cmd, "SETCONFIG");
+        if (json_get_string(line + 10, "setting", req->setting, sizeof(req->setting)) != 0 ||
+            json_get_string(line + 10, "value", req->value, sizeof(req->value)) != 0)
+        {
+            log_ts("controller: Malformed SETCONFIG payload: %s\n", line + 10);
+            return -1;
+        }
+        return 0;
+    }
+
+    if (strlen(line) >= sizeof(req->cmd))
+    {
+        log_ts("controller: Command too long, ignoring\n");
+        return -1;
+    }
+    strcpy(req->cmd, line);
+    return 0;
+}
+
+/* ============================================================================
+ * RESPONSES
+ * ============================================================================ */
+
+/* Write the whole buffer to the status pipe (large config responses exceed PIPE_BUF) */
+static void write_status_line(const char *buf, size_t len)
+{
+    if (g_status_fd < 0)
+        return;
+
+    while (len > 0)
+    {
+        ssize_t written = write(g_status_fd, buf, len);
+        if (written < 0)
+        {
+            if (errno == EINTR)
+                continue;
+            log_ts("controller: Failed to write response: %s\n", strerror(errno));
+            return;
+        }
+        buf += written;
+        len -= (size_t)written;
+    }
+}
+
+void send_response_result(long id, const char *result_json)
+{
+    if (id <= 0)
+        return;
+
+    char header[96];
+    int header_len = snprintf(header, sizeof(header),
+                              "{\"type\":\"response\",\"id\":%ld,\"ok\":true,\"result\":", id);
+    size_t result_len = strlen(result_json);
+
+    /* Strip the trailing newline some producers add */
+    while (result_len > 0 && (result_json[result_len - 1] == '\n' || result_json[result_len - 1] == '\r'))
+        result_len--;
+
+    size_t total = (size_t)header_len + result_len + 2;
+    char *line = malloc(total + 1);
+    if (!line)
+    {
+        send_response_error(id, "Out of memory building response");
+        return;
+    }
+
+    memcpy(line, header, (size_t)header_len);
+    memcpy(line + header_len, result_json, result_len);
+    memcpy(line + header_len + result_len, "}\n", 3);
+
+    write_status_line(line, total);
+    free(line);
+}
+
+void send_response_ok(long id)
+{
+    send_response_result(id, "null");
+}
+
+void send_response_error(long id, const char *format, ...)
+{
+    if (id <= 0)
+        return;
+
+    char message[512];
+    va_list args;
+    va_start(args, format);
+    vsnprintf(message, sizeof(message), format, args);
+    va_end(args);
+
+    /* Escape the message - it may contain camera-provided strings */
+    char escaped[1024];
+    size_t pos = 0;
+    for (const char *p = message; *p && pos < sizeof(escaped) - 7; p++)
+    {
+        unsigned char c = (unsigned char)*p;
+        if (c == '"' || c == '\\')
+        {
+            escaped[pos++] = '\\';
+            escaped[pos++] = (char)c;
+        }
+        else if (c < 0x20)
+        {
+            pos += (size_t)snprintf(escaped + pos, sizeof(escaped) - pos, "\\u%04x", c);
+        }
+        else
+        {
+            escaped[pos++] = (char)c;
+        }
+    }
+    escaped[pos] = '\0';
+
+    char line[1200];
+    int len = snprintf(line, sizeof(line),
+                       "{\"type\":\"response\",\"id\":%ld,\"ok\":false,\"error\":\"%s\"}\n", id, escaped);
+    write_status_line(line, (size_t)len);
+}
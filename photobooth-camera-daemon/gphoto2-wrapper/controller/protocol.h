@@ -0,0 +1,64 @@
+/*
+ * protocol.h - Request/response protocol between the camera daemon and the controller
+ *
+ * The daemon writes one JSON request per line to the command pipe:
+ *   {"id":12,"cmd":"SETCONFIG","setting":"iso","value":"800"}
+ *   {"id":13,"cmd":"BURST","count":5,"interval_ms":250}
+ *   {"id":14,"cmd":"FOCUS","action":"near","steps":3}
+ *
+ * The controller answers on the status pipe with a line carrying the same id,
+ * interleaved with the regular status events:
+ *   {"type":"response","id":12,"ok":true,"result":{...}}
+ *   {"type":"response","id":12,"ok":false,"error":"Failed to open camera"}
+ *
+ * Plain-text commands ("CAPTURE", "SWITCH_CAMERA 1", ...) are still accepted so the
+ * controller can be driven by hand with echo. They get id 0 and no response.
+ */
+
+#ifndef PROTOCOL_H
+#define PROTOCOL_H
+
+#include <stddef.h>
+
+#define REQUEST_CMD_MAX 32
+#define REQUEST_SETTING_MAX 128
+#define REQUEST_VALUE_MAX 512
+#define BURST_MAX_FRAMES 100
+#define REQUEST_ACTION_MAX 16
+#define FOCUS_MAX_STEPS 100
+#define FOCUS_POINT_SCALE 1000          /* FOCUS point x/y are in 1/1000 of the live view frame */
+
+typedef struct {
+    long id;                            /* Correlation id, 0 = no response expected */
+    char cmd[REQUEST_CMD_MAX];          /* CAPTURE, CONFIG, SETCONFIG, ... */
+    char setting[REQUEST_SETTING_MAX];  /* SETCONFIG only */
+    char value[REQUEST_VALUE_MAX];      /* SETCONFIG only */
+    int camera_index;                   /* SWITCH_CAMERA only */
+    int count;                          /* BURST only: number of frames */
+    int interval_ms;                    /* BURST only: time between frame triggers */
+    char action[REQUEST_ACTION_MAX];    /* FOCUS only: autofocus, cancel, near, far, point, zoom, capabilities */
+    int steps;                          /* FOCUS near/far: drive steps */
+    int x;                              /* FOCUS point: 0..FOCUS_POINT_SCALE across the frame */
+    int y;                              /* FOCUS point: 0..FOCUS_POINT_SCALE down the frame */
+    int level;                          /* FOCUS zoom: live view magnification (1, 5, 10) */
+} ControllerRequest;
+
+/*
+ * Parse a command line (JSON request or legacy plain-text command).
+ * Returns 0 on success, -1 if the line is malformed.
+ */
+int parse_controller_request(const char *line, ControllerRequest *req);
+
+/*
+ * Send a successful response with a JSON result (object, array or literal).
+ * No-op for id 0.
+ */
+void send_response_result(long id, const char *result_json);
+
+/* Send a successful response without a result payload */
+void send_response_ok(long id);
+
+/* Send an error response (printf-style message, JSON-escaped on output) */
+void send_response_error(long id, const char *format, ...);
+
+#endif /* PROTOCOL_H */
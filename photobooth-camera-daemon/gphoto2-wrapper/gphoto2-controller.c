@@ -4,20 +4,37 @@
  * Manages camera connection with a command queue. Polls for new files
  * from physical shutter button while accepting commands via named pipe.
  *
- * Commands (write to /tmp/camera_cmd):
- *   CAPTURE                 - Trigger software capture
- *   STATUS                  - Get current status
- *   LIVEVIEW_STREAM_START   - Start continuous PTP streaming (MJPEG to /tmp/camera_stream)
- *   LIVEVIEW_STREAM_STOP    - Stop continuous PTP streaming
- *   QUIT                    - Shutdown the controller
+ * Commands (write to /tmp/camera_cmd, one JSON request per line - see controller/protocol.h):
+ *   {"id":1,"cmd":"CAPTURE"}                 - Trigger software capture (result: triggered_at_ms)
+ *   {"id":9,"cmd":"BURST","count":5,"interval_ms":250}
+ *                                            - Capture count frames with the camera held open
+ *                                              (result: frames[].triggered_at_ms)
+ *   {"id":2,"cmd":"STATUS"}                  - Get current status
+ *   {"id":3,"cmd":"LIVEVIEW_STREAM_START"}   - Start continuous PTP streaming (MJPEG to /tmp/camera_stream)
+ *   {"id":4,"cmd":"LIVEVIEW_STREAM_STOP"}    - Stop continuous PTP streaming
+ *   {"id":5,"cmd":"SWITCH_CAMERA","camera_index":1}
+ *   {"id":6,"cmd":"CONFIG"}                  - Full camera config (in the response)
+ *   {"id":7,"cmd":"SETCONFIG","setting":"iso","value":"800"}
+ *   {"id":10,"cmd":"FOCUS","action":"near","steps":3}
+ *                                            - Autofocus, focus steps, AF point or live view zoom
+ *                                              (actions: autofocus, cancel, near, far, point,
+ *                                              zoom, capabilities - see controller/camera_focus.h)
+ *   {"id":8,"cmd":"QUIT"}                    - Shutdown the controller
+ * Plain-text commands (CAPTURE, SWITCH_CAMERA 1, ...) still work but get no response.
  *
  * Status output (writes to /tmp/camera_status):
+ *   {"type":"response","id":N,"ok":true,"result":...} - Response to request N
  *   {"mode":"idle"}                - Polling for new files
  *   {"mode":"capture"}             - Capturing
  *   {"mode":"liveview"}            - Live view active
  *   {"mode":"liveview_streaming"}  - Continuous PTP streaming active
  *   {"mode":"idle","status":{...}} - Camera status (ISO, aperture, etc)
  *
+ * Usage: gphoto2-controller <camera_index> [photo_dir] [runtime_dir]
+ *   photo_dir holds the downloaded photos for this camera (default /tmp) and
+ *   runtime_dir its pipes (default: photo_dir), so the daemon can run one controller
+ *   per camera side by side and keep photos on persistent storage.
+ *
  * Stream output (writes to /tmp/camera_stream):
  *   MJPEG stream with boundary markers: --FRAME\nContent-Length: XXX\n\n<JPEG data>
  */
@@ -46,12 +63,12 @@
 #include "controller/camera_preview.h"
 #include "controller/camera_config.h"
 #include "controller/camera_filemgmt.h"
+#include "controller/camera_focus.h"
+#include "controller/protocol.h"
 
-/* Pipe and configuration paths */
-#define CMD_PIPE "/tmp/camera_cmd"
-#define STATUS_PIPE "/tmp/camera_status"
-#define STREAM_PIPE "/tmp/camera_stream"
-#define CONFIG_RESPONSE_FILE "/tmp/camera_config_response"
+/* Configuration */
+#define DEFAULT_RUNTIME_DIR "/tmp"
+#define CMD_BUFFER_SIZE 4096
 #define MAX_FILES 100
 #define POLL_INTERVAL_MS 1000
 #define MAX_OPEN_RETRIES 5
@@ -72,6 +89,12 @@ typedef enum
  * GLOBAL VARIABLES - referenced by modules via extern declarations
  * ============================================================================ */
 
+/* Pipe paths and photo directory - set from the photo dir / runtime dir arguments */
+char g_cmd_pipe[256];
+char g_status_pipe[256];
+char g_stream_pipe[256];
+char g_photo_dir[256] = DEFAULT_RUNTIME_DIR;
+
 /* Running state and file descriptors */
 volatile sig_atomic_t g_running = 1;
 int g_status_fd = -1; /* Status pipe file descriptor - used by preview module */
@@ -592,38 +615,6 @@ static void send_camera_connected_event(Camera *camera, GPContext *context, int
     }
 }
 
-/* ============================================================================
- * JSON HELPER FUNCTIONS - for config commands
- * ============================================================================ */
-
-/*
- * Extract a value from JSON string by key
- */
-static char *extract_json_value(const char *json, const char *key)
-{
-    char search[128];
-    snprintf(search, sizeof(search), "\"%s\":\"", key);
-
-    const char *start = strstr(json, search);
-    if (!start)
-        return NULL;
-
-    start += strlen(search);
-
-    const char *end = strchr(start, '"');
-    if (!end)
-        return NULL;
-
-    size_t len = end - start;
-    char *result = malloc(len + 1);
-    if (result)
-    {
-        memcpy(result, start, len);
-        result[len] = '\0';
-    }
-    return result;
-}
-
 /* ============================================================================
  * MAIN CONTROLLER LOOP
  * ============================================================================ */
@@ -642,14 +633,30 @@ int main(int argc, char *argv[])
     {
         camera_index = atoi(argv[1]);
     }
+    if (argc >= 3)
+    {
+        snprintf(g_photo_dir, sizeof(g_photo_dir), "%s", argv[2]);
+        mkdir(g_photo_dir, 0755);
+    }
+    /* Pipes go in the runtime dir (argv[3]), which defaults to the photo dir */
+    const char *runtime_dir = g_photo_dir;
+    if (argc >= 4)
+    {
+        runtime_dir = argv[3];
+        mkdir(runtime_dir, 0755);
+    }
+    snprintf(g_cmd_pipe, sizeof(g_cmd_pipe), "%s/camera_cmd", runtime_dir);
+    snprintf(g_status_pipe, sizeof(g_status_pipe), "%s/camera_status", runtime_dir);
+    snprintf(g_stream_pipe, sizeof(g_stream_pipe), "%s/camera_stream", runtime_dir);
 
     install_signal_handlers();
 
     log_ts("controller: ===== gphoto2-controller v1.3 (refactored) =====\n");
+    log_ts("controller: Camera index %d, photo dir %s, runtime dir %s\n", camera_index, g_photo_dir, runtime_dir);
 
     /* Create status pipe */
-    mkfifo(STATUS_PIPE, 0666);
-    g_status_fd = open(STATUS_PIPE, O_WRONLY);
+    mkfifo(g_status_pipe, 0666);
+    g_status_fd = open(g_status_pipe, O_WRONLY);
     if (g_status_fd < 0)
     {
         log_ts("controller: Warning - cannot open status pipe: %s\n", strerror(errno));
@@ -660,12 +667,12 @@ int main(int argc, char *argv[])
     }
 
     /* Create stream pipe */
-    mkfifo(STREAM_PIPE, 0666);
+    mkfifo(g_stream_pipe, 0666);
     /* Don't open yet - will be opened non-blocking when streaming starts */
 
     /* Create command pipe */
-    mkfifo(CMD_PIPE, 0666);
-    cmd_fd = open(CMD_PIPE, O_RDWR | O_NONBLOCK);
+    mkfifo(g_cmd_pipe, 0666);
+    cmd_fd = open(g_cmd_pipe, O_RDWR | O_NONBLOCK);
     if (cmd_fd < 0)
     {
         log_ts("controller: Failed to open command pipe: %s\n", strerror(errno));
@@ -694,8 +701,8 @@ int main(int argc, char *argv[])
         if (g_status_fd >= 0)
             close(g_status_fd);
         gp_context_unref(context);
-        unlink(CMD_PIPE);
-        unlink(STATUS_PIPE);
+        unlink(g_cmd_pipe);
+        unlink(g_status_pipe);
         return 1;
     }
 
@@ -730,10 +737,11 @@ int main(int argc, char *argv[])
     gp_camera_free(camera);
     camera = NULL;
 
-    log_ts("controller: Started, waiting for commands on %s\n", CMD_PIPE);
+    log_ts("controller: Started, waiting for commands on %s\n", g_cmd_pipe);
 
     /* Main loop */
-    char cmd_buffer[256];
+    char cmd_buffer[CMD_BUFFER_SIZE];
+    size_t cmd_buffered = 0;
     ssize_t cmd_len;
     int consecutive_open_failures = 0;
     int switch_received = 0;
@@ -741,25 +749,39 @@ int main(int argc, char *argv[])
 
     while (g_running)
     {
-        cmd_len = read(cmd_fd, cmd_buffer, sizeof(cmd_buffer) - 1);
+        /* Requests can arrive split across reads - only handle complete lines */
+        cmd_len = read(cmd_fd, cmd_buffer + cmd_buffered, sizeof(cmd_buffer) - 1 - cmd_buffered);
         if (cmd_len > 0)
         {
-            cmd_buffer[cmd_len] = '\0';
+            cmd_buffered += (size_t)cmd_len;
         }
+        cmd_buffer[cmd_buffered] = '\0';
 
-        char *cmd_line = (cmd_len > 0) ? strtok(cmd_buffer, "\n") : NULL;
-        while (cmd_line != NULL)
+        char *line_start = cmd_buffer;
+        char *line_end;
+        while ((line_end = strchr(line_start, '\n')) != NULL)
         {
+            *line_end = '\0';
+            char *cmd_line = line_start;
+            line_start = line_end + 1;
+
             if (cmd_line[0] == '\0')
             {
-                cmd_line = strtok(NULL, "\n");
                 continue;
             }
 
             log_ts("controller: Got command: '%s'\n", cmd_line);
 
-            if (strcmp(cmd_line, "CAPTURE") == 0)
+            ControllerRequest req;
+            if (parse_controller_request(cmd_line, &req) != 0)
             {
+                continue;
+            }
+            const char *cmd = req.cmd;
+
+            if (strcmp(cmd, "CAPTURE") == 0 || strcmp(cmd, "BURST") == 0)
+            {
+                int is_burst = strcmp(cmd, "BURST") == 0;
                 int was_streaming = g_streaming_active;
 
                 if (g_streaming_active)
@@ -815,9 +837,30 @@ int main(int argc, char *argv[])
                     consecutive_open_failures = 0;
                 }
 
-                if (camera)
+                if (camera && is_burst)
+                {
+                    int capture_ret = do_burst(camera, context, req.id, req.count, req.interval_ms);
+
+                    if (!was_streaming)
+                    {
+                        gp_camera_exit(camera, context);
+                        gp_camera_free(camera);
+                        camera = NULL;
+                    }
+
+                    if (capture_ret < GP_OK && g_status_fd >= 0)
+                    {
+                        char error_event[256];
+                        snprintf(error_event, sizeof(error_event),
+                                 "{\"type\":\"capture_error\",\"error\":\"%s\"}\n",
+                                 gp_result_as_string(capture_ret));
+                        write(g_status_fd, error_event, strlen(error_event));
+                    }
+                }
+                else if (camera)
                 {
-                    int capture_ret = do_capture(camera, context);
+                    long long triggered_at_ms = 0;
+                    int capture_ret = do_capture(camera, context, &triggered_at_ms);
 
                     if (!was_streaming)
                     {
@@ -834,6 +877,17 @@ int main(int argc, char *argv[])
                                  gp_result_as_string(capture_ret));
                         write(g_status_fd, error_event, strlen(error_event));
                     }
+
+                    if (capture_ret < GP_OK)
+                    {
+                        send_response_error(req.id, "Capture failed: %s", gp_result_as_string(capture_ret));
+                    }
+                    else
+                    {
+                        char result[64];
+                        snprintf(result, sizeof(result), "{\"triggered_at_ms\":%lld}", triggered_at_ms);
+                        send_response_result(req.id, result);
+                    }
                 }
                 else
                 {
@@ -846,6 +900,7 @@ int main(int argc, char *argv[])
                         const char *error_event = "{\"type\":\"capture_error\",\"error\":\"Failed to open camera\"}\n";
                         write(g_status_fd, error_event, strlen(error_event));
                     }
+                    send_response_error(req.id, "Failed to open camera: %s", gp_result_as_string(ret));
                 }
 
                 if (was_streaming && camera)
@@ -915,9 +970,9 @@ int main(int argc, char *argv[])
                     mode = MODE_IDLE;
                 }
             }
-            else if (strcmp(cmd_line, "STATUS") == 0)
+            else if (strcmp(cmd, "STATUS") == 0)
             {
-                const char *mode_str;
+                const char *mode_str = "liveview";
                 if (g_streaming_active)
                 {
                     mode_str = "liveview_streaming";
@@ -937,8 +992,12 @@ int main(int argc, char *argv[])
                     snprintf(status, sizeof(status), "{\"mode\":\"%s\"}\n", mode_str);
                     write(g_status_fd, status, strlen(status));
                 }
+
+                char result[64];
+                snprintf(result, sizeof(result), "{\"mode\":\"%s\"}", mode_str);
+                send_response_result(req.id, result);
             }
-            else if (strcmp(cmd_line, "LIVEVIEW_STREAM_START") == 0)
+            else if (strcmp(cmd, "LIVEVIEW_STREAM_START") == 0)
             {
                 log_ts("controller: Starting continuous PTP streaming...\n");
 
@@ -999,13 +1058,15 @@ int main(int argc, char *argv[])
                     {
                         write(g_status_fd, "{\"mode\":\"liveview_streaming\"}\n", 30);
                     }
+                    send_response_ok(req.id);
                 }
                 else
                 {
                     consecutive_open_failures++;
+                    send_response_error(req.id, "Failed to open camera: %s", gp_result_as_string(ret));
                 }
             }
-            else if (strcmp(cmd_line, "LIVEVIEW_STREAM_STOP") == 0)
+            else if (strcmp(cmd, "LIVEVIEW_STREAM_STOP") == 0)
             {
                 log_ts("controller: Stopping continuous PTP streaming...\n");
 
@@ -1033,10 +1094,11 @@ int main(int argc, char *argv[])
                 {
                     write(g_status_fd, "{\"mode\":\"idle\"}\n", 17);
                 }
+                send_response_ok(req.id);
             }
-            else if (strncmp(cmd_line, "SWITCH_CAMERA ", 14) == 0)
+            else if (strcmp(cmd, "SWITCH_CAMERA") == 0)
             {
-                int new_index = atoi(cmd_line + 14);
+                int new_index = req.camera_index;
                 log_ts("controller: Switching to camera %d\n", new_index);
                 switch_received = 1;
                 needs_connected_event = 1;
@@ -1069,16 +1131,38 @@ int main(int argc, char *argv[])
                              "{\"type\":\"camera_switched\",\"camera_index\":%d}\n", new_index);
                     write(g_status_fd, switch_msg, strlen(switch_msg));
                 }
+                send_response_ok(req.id);
             }
-            else if (strcmp(cmd_line, "DISCONNECT") == 0)
+            else if (strcmp(cmd, "DISCONNECT") == 0)
             {
                 switch_received = 0;
                 if (g_status_fd >= 0)
                 {
                     write(g_status_fd, "{\"type\":\"polling_stopped\"}\n", 28);
                 }
+                send_response_ok(req.id);
+            }
+            else if (strcmp(cmd, "USB_CHANGED") == 0)
+            {
+                /* The daemon saw a USB hotplug event: drop the cached detection so the
+                 * next open (right after this command wakes the loop) finds the camera
+                 * on its new bus address, or fails at once if it was unplugged */
+                log_ts("controller: USB devices changed, re-detecting\n");
+                g_detection_valid = 0;
+                g_cached_camera_index = -1;
+                if (g_cached_abilities_list)
+                {
+                    gp_abilities_list_free(g_cached_abilities_list);
+                    g_cached_abilities_list = NULL;
+                }
+                if (g_cached_port_info_list)
+                {
+                    gp_port_info_list_free(g_cached_port_info_list);
+                    g_cached_port_info_list = NULL;
+                }
+                send_response_ok(req.id);
             }
-            else if (strcmp(cmd_line, "PAUSE_POLLING") == 0)
+            else if (strcmp(cmd, "PAUSE_POLLING") == 0)
             {
                 switch_received = 0;
 
@@ -1122,8 +1206,9 @@ int main(int argc, char *argv[])
                 {
                     write(g_status_fd, "{\"type\":\"polling_paused\"}\n", 26);
                 }
+                send_response_ok(req.id);
             }
-            else if (strcmp(cmd_line, "RESUME_POLLING") == 0)
+            else if (strcmp(cmd, "RESUME_POLLING") == 0)
             {
                 switch_received = 1;
 
@@ -1179,8 +1264,9 @@ int main(int argc, char *argv[])
                 {
                     write(g_status_fd, "{\"type\":\"polling_resumed\"}\n", 27);
                 }
+                send_response_ok(req.id);
             }
-            else if (strcmp(cmd_line, "CONFIG") == 0)
+            else if (strcmp(cmd, "CONFIG") == 0)
             {
                 if (g_streaming_active)
                 {
@@ -1202,7 +1288,11 @@ int main(int argc, char *argv[])
 
                 if (camera)
                 {
-                    write_full_config_json(camera, context, g_current_brand);
+                    send_full_config_json(camera, context, g_current_brand, req.id);
+                }
+                else
+                {
+                    send_response_error(req.id, "Failed to open camera: %s", gp_result_as_string(ret));
                 }
 
                 if (we_opened && camera)
@@ -1217,10 +1307,50 @@ int main(int argc, char *argv[])
                     g_streaming_paused = 0;
                 }
             }
-            else if (strncmp(cmd_line, "SETCONFIG ", 10) == 0)
+            else if (strcmp(cmd, "SETCONFIG") == 0)
             {
-                const char *json_input = cmd_line + 10;
+                if (g_streaming_active)
+                {
+                    g_streaming_paused = 1;
+                    usleep(50000);
+                }
+
+                int we_opened = 0;
+
+                if (!camera)
+                {
+                    camera = open_camera(camera_index, &ret);
+                    if (camera)
+                    {
+                        we_opened = 1;
+                        consecutive_open_failures = 0;
+                    }
+                }
+
+                if (camera)
+                {
+                    set_config_and_send_response(camera, context, req.id, req.setting, req.value);
+                }
+                else
+                {
+                    send_response_error(req.id, "Failed to open camera: %s", gp_result_as_string(ret));
+                }
+
+                if (we_opened && camera)
+                {
+                    gp_camera_exit(camera, context);
+                    gp_camera_free(camera);
+                    camera = NULL;
+                }
 
+                if (g_streaming_active && g_streaming_paused)
+                {
+                    g_streaming_paused = 0;
+                }
+            }
+            else if (strcmp(cmd, "FOCUS") == 0)
+            {
+                /* Same as SETCONFIG: hold live view frames while the focus widgets are driven */
                 if (g_streaming_active)
                 {
                     g_streaming_paused = 1;
@@ -1241,7 +1371,11 @@ int main(int argc, char *argv[])
 
                 if (camera)
                 {
-                    set_config_and_write_response(camera, context, json_input);
+                    focus_and_send_response(camera, context, &req);
+                }
+                else
+                {
+                    send_response_error(req.id, "Failed to open camera: %s", gp_result_as_string(ret));
                 }
 
                 if (we_opened && camera)
@@ -1256,14 +1390,27 @@ int main(int argc, char *argv[])
                     g_streaming_paused = 0;
                 }
             }
-            else if (strcmp(cmd_line, "QUIT") == 0)
+            else if (strcmp(cmd, "QUIT") == 0)
             {
                 log_ts("controller: Quit command received\n");
+                send_response_ok(req.id);
                 g_running = 0;
             }
+            else
+            {
+                log_ts("controller: Unknown command '%s'\n", cmd);
+                send_response_error(req.id, "Unknown command '%s'", cmd);
+            }
+        }
 
-            cmd_line = strtok(NULL, "\n");
+        /* Keep a partial line for the next read; drop it if it can never fit */
+        cmd_buffered -= (size_t)(line_start - cmd_buffer);
+        if (cmd_buffered >= sizeof(cmd_buffer) - 1)
+        {
+            log_ts("controller: Command line exceeds %d bytes, discarding\n", CMD_BUFFER_SIZE);
+            cmd_buffered = 0;
         }
+        memmove(cmd_buffer, line_start, cmd_buffered);
 
         /* Polling loop for new files from physical shutter */
         int should_poll = switch_received && mode == MODE_IDLE;
@@ -1325,6 +1472,9 @@ int main(int argc, char *argv[])
                 char *ev = get_single_config_value(camera, context, widgets->ev);
                 char *wb = get_single_config_value(camera, context, widgets->wb);
                 char *shootingmode = get_single_config_value(camera, context, widgets->mode);
+                long shots_remaining = -1;
+                long long card_free_mb = -1;
+                get_card_space(camera, context, &shots_remaining, &card_free_mb);
 
                 if (!shootingmode && g_current_brand != BRAND_FUJI)
                 {
@@ -1363,7 +1513,7 @@ int main(int argc, char *argv[])
 
                     char status_msg[1536];
                     snprintf(status_msg, sizeof(status_msg),
-                             "{\"mode\":\"%s\",\"shootingmode\":\"%s\",\"battery\":\"%s\",\"iso\":\"%s\",\"aperture\":\"%s\",\"shutter\":\"%s\",\"ev\":\"%s\",\"wb\":\"%s\"}\n",
+                             "{\"mode\":\"%s\",\"shootingmode\":\"%s\",\"battery\":\"%s\",\"iso\":\"%s\",\"aperture\":\"%s\",\"shutter\":\"%s\",\"ev\":\"%s\",\"wb\":\"%s\",\"shots_remaining\":%ld,\"card_free_mb\":%lld}\n",
                              current_mode_str,
                              shootingmode ? shootingmode : "",
                              battery ? battery : "",
@@ -1371,7 +1521,9 @@ int main(int argc, char *argv[])
                              aperture ? aperture : "",
                              shutter ? shutter : "",
                              ev ? ev : "",
-                             wb ? wb : "");
+                             wb ? wb : "",
+                             shots_remaining,
+                             card_free_mb);
                     write(g_status_fd, status_msg, strlen(status_msg));
                 }
 
@@ -1612,9 +1764,9 @@ int main(int argc, char *argv[])
         close(g_status_fd);
     if (g_stream_fd >= 0)
         close(g_stream_fd);
-    unlink(CMD_PIPE);
-    unlink(STATUS_PIPE);
-    unlink(STREAM_PIPE);
+    unlink(g_cmd_pipe);
+    unlink(g_status_pipe);
+    unlink(g_stream_pipe);
 
     return 0;
 }
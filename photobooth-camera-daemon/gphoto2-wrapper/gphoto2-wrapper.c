@@ -4,6 +4,7 @@
  * Commands:
  *   gphoto2-wrapper version               - Check libgphoto2 availability
  *   gphoto2-wrapper list                  - List connected cameras (JSON)
+ *   gphoto2-wrapper detect                - Enumerate cameras without opening them (JSON)
  *   gphoto2-wrapper capture [camera_id]   - Capture image and print file path
  *   gphoto2-wrapper debug [camera_id]     - Print camera abilities and config summary
  *   gphoto2-wrapper config [camera_id]    - Get current camera configuration/settings (JSON)
@@ -293,6 +294,63 @@ static void print_cameras(void) {
     gp_context_unref(context);
 }
 
+/*
+ * Enumerate cameras without opening them: [{"index":0,"model":"...","port":"usb:001,004"}]
+ * Safe to run while controllers hold their cameras (nothing is claimed), so the
+ * daemon uses it to decide how many controllers to run.
+ */
+static void print_detected_cameras(void) {
+    GPContext *context = create_context();
+    CameraList *list = NULL;
+    GPPortInfoList *port_info_list = NULL;
+    CameraAbilitiesList *abilities_list = NULL;
+    int ret;
+
+    if (!context) {
+        printf("{\"error\":\"Failed to create context\"}\n");
+        return;
+    }
+
+    if (gp_list_new(&list) < GP_OK ||
+        gp_port_info_list_new(&port_info_list) < GP_OK ||
+        gp_port_info_list_load(port_info_list) < GP_OK ||
+        gp_abilities_list_new(&abilities_list) < GP_OK ||
+        gp_abilities_list_load(abilities_list, context) < GP_OK) {
+        printf("{\"error\":\"Failed to initialize camera detection\"}\n");
+        goto cleanup;
+    }
+
+    ret = gp_abilities_list_detect(abilities_list, port_info_list, list, context);
+    if (ret < GP_OK) {
+        printf("{\"error\":\"Failed to detect cameras: %s\"}\n", gp_result_as_string(ret));
+        goto cleanup;
+    }
+
+    printf("[");
+    int count = gp_list_count(list);
+    for (int i = 0; i < count; i++) {
+        const char *model = NULL;
+        const char *port = NULL;
+        gp_list_get_name(list, i, &model);
+        gp_list_get_value(list, i, &port);
+
+        printf("%s{\"index\":%d,\"model\":\"", i > 0 ? "," : "", i);
+        for (const char *p = model ? model : ""; *p; p++) {
+            if (*p == '"') printf("\\\"");
+            else if (*p == '\\') printf("\\\\");
+            else if (*p >= 32) putchar(*p);
+        }
+        printf("\",\"port\":\"%s\"}", port ? port : "");
+    }
+    printf("]\n");
+
+cleanup:
+    if (abilities_list) gp_abilities_list_free(abilities_list);
+    if (port_info_list) gp_port_info_list_free(port_info_list);
+    if (list) gp_list_free(list);
+    gp_context_unref(context);
+}
+
 static void debug_camera(int camera_index) {
     Camera *camera = NULL;
     GPContext *context = NULL;
@@ -477,7 +535,7 @@ static void watch_camera(int camera_index) {
 
 int main(int argc, char *argv[]) {
     if (argc < 2) {
-        fprintf(stderr, "Usage: gphoto2-wrapper <version|list|capture|debug|config|widgets|status|watch> [camera_id]\n");
+        fprintf(stderr, "Usage: gphoto2-wrapper <version|list|detect|capture|debug|config|widgets|status|watch> [camera_id]\n");
         return 1;
     }
 
@@ -495,6 +553,8 @@ int main(int argc, char *argv[]) {
         print_version();
     } else if (strcmp(argv[1], "list") == 0) {
         print_cameras();
+    } else if (strcmp(argv[1], "detect") == 0) {
+        print_detected_cameras();
     } else if (strcmp(argv[1], "capture") == 0) {
         capture_image(camera_index);
     } else if (strcmp(argv[1], "debug") == 0) {
@@ -0,0 +1,349 @@
+//! Camera health alerts
+//!
+//! Every event from a camera's status pipe passes through `AlertCenter::observe`, which
+//! checks it against the `[alerts]` thresholds in the daemon config:
+//!
+//! - battery_low: the status `battery` reading ("15%", "Low", ...)
+//! - shots_low / card_space_low: `shots_remaining` and `card_free_mb` from the status
+//! - capture_failures: consecutive `capture_error` events (reset by the next photo)
+//! - reconnect_loop: disconnects and controller restarts within a time window
+//!
+//! There is at most one open alert per camera and kind. It is broadcast as a
+//! `camera_alert` event when raised, when its severity changes and when it resolves
+//! (the condition cleared); repeated readings only update it. An operator can clear an
+//! alert, which silences it until it escalates or resolves. `/api/alerts` lists open
+//! alerts and recently closed ones.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Closed alerts kept for /api/alerts
+const RECENT_CAPACITY: usize = 100;
+
+/// Battery must climb this far above the warning threshold to resolve, so a reading
+/// that wobbles around the threshold doesn't raise and resolve over and over
+const BATTERY_HYSTERESIS_PERCENT: f64 = 5.0;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    BatteryLow,
+    ShotsLow,
+    CardSpaceLow,
+    CaptureFailures,
+    ReconnectLoop,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Active,
+    /// Dismissed by an operator; stays quiet unless it escalates
+    Cleared,
+    /// The condition went away
+    Resolved,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Alert {
+    pub id: u64,
+    pub camera_id: String,
+    pub kind: AlertKind,
+    pub severity: Severity,
+    pub state: AlertState,
+    pub message: String,
+    /// Reading that triggered the alert (percent, shots, MB, failures, reconnects)
+    pub value: Option<f64>,
+    pub threshold: f64,
+    pub raised_at_ms: u64,
+    pub updated_at_ms: u64,
+    /// Times the condition was seen while the alert was open
+    pub occurrences: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_at_ms: Option<u64>,
+}
+
+#[derive(Default)]
+struct CameraHealth {
+    consecutive_failures: u32,
+    reconnects: VecDeque<Instant>,
+}
+
+#[derive(Default)]
+struct Alerts {
+    open: BTreeMap<(String, AlertKind), Alert>,
+    recent: VecDeque<Alert>,
+    cameras: HashMap<String, CameraHealth>,
+    next_id: u64,
+}
+
+/// Open and recent alerts for every camera
+#[derive(Clone)]
+pub struct AlertCenter {
+    alerts: Arc<StdMutex<Alerts>>,
+    ws_tx: broadcast::Sender<Message>,
+}
+
+impl AlertCenter {
+    pub fn new(ws_tx: broadcast::Sender<Message>) -> Self {
+        Self { alerts: Arc::new(StdMutex::new(Alerts::default())), ws_tx }
+    }
+
+    /// Check one event from a camera's status pipe
+    pub fn observe(&self, camera_id: &str, event: &Value) {
+        match event.get("type").and_then(|t| t.as_str()) {
+            Some("capture_error") => {
+                let error = event.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error");
+                self.capture_failed(camera_id, error);
+            }
+            Some("photo_downloaded") => {
+                self.alerts.lock().unwrap().cameras.entry(camera_id.to_string()).or_default().consecutive_failures = 0;
+                self.resolve(camera_id, AlertKind::CaptureFailures);
+            }
+            Some("camera_disconnected") => {
+                let reason = event.get("reason").and_then(|r| r.as_str()).unwrap_or("disconnected");
+                self.record_reconnect(camera_id, reason);
+            }
+            // Periodic status: {"mode":..., "battery":..., ...}
+            None if event.get("mode").is_some() => self.check_status(camera_id, event),
+            _ => {}
+        }
+    }
+
+    /// The camera's controller exited and is being restarted
+    pub fn controller_restarted(&self, camera_id: &str) {
+        self.record_reconnect(camera_id, "controller restarted");
+    }
+
+    /// Open alerts (optionally for one camera), then recently closed ones, newest first
+    pub fn list(&self, camera_id: Option<&str>) -> (Vec<Alert>, Vec<Alert>) {
+        let alerts = self.alerts.lock().unwrap();
+        let wanted = |a: &&Alert| camera_id.is_none_or(|id| a.camera_id == id);
+        let mut open: Vec<Alert> = alerts.open.values().filter(wanted).cloned().collect();
+        open.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.updated_at_ms.cmp(&a.updated_at_ms)));
+        let recent = alerts.recent.iter().rev().filter(wanted).cloned().collect();
+        (open, recent)
+    }
+
+    /// Operator dismissed an alert
+    pub fn clear(&self, id: u64) -> Result<Alert, String> {
+        let mut alerts = self.alerts.lock().unwrap();
+        let alert = alerts
+            .open
+            .values_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| format!("No open alert {}", id))?;
+        if alert.state != AlertState::Cleared {
+            alert.state = AlertState::Cleared;
+            alert.updated_at_ms = now_ms();
+            self.broadcast("cleared", alert);
+        }
+        Ok(alert.clone())
+    }
+
+    fn check_status(&self, camera_id: &str, status: &Value) {
+        let config = &crate::config::get().alerts;
+
+        if let Some(percent) = status.get("battery").and_then(|b| b.as_str()).and_then(battery_percent) {
+            let warning = config.battery_warning_percent;
+            if percent <= config.battery_critical_percent {
+                self.raise(camera_id, AlertKind::BatteryLow, Severity::Critical, Some(percent), config.battery_critical_percent,
+                    format!("Battery {}% - swap now, the camera is about to shut down", percent));
+            } else if percent <= warning {
+                self.raise(camera_id, AlertKind::BatteryLow, Severity::Warning, Some(percent), warning,
+                    format!("Battery {}%, swap now", percent));
+            } else if percent >= warning + BATTERY_HYSTERESIS_PERCENT {
+                self.resolve(camera_id, AlertKind::BatteryLow);
+            }
+        }
+
+        // -1 or missing: the camera doesn't report it
+        if let Some(shots) = number(status, "shots_remaining").filter(|s| *s >= 0.0) {
+            let threshold = config.shots_remaining_warning as f64;
+            if shots <= threshold {
+                let (severity, message) = if shots == 0.0 {
+                    (Severity::Critical, "Memory card full, no shots left".to_string())
+                } else {
+                    (Severity::Warning, format!("Room for {} more shots on the memory card", shots))
+                };
+                self.raise(camera_id, AlertKind::ShotsLow, severity, Some(shots), threshold, message);
+            } else {
+                self.resolve(camera_id, AlertKind::ShotsLow);
+            }
+        }
+
+        if let Some(free_mb) = number(status, "card_free_mb").filter(|s| *s >= 0.0) {
+            let threshold = config.card_free_warning_mb as f64;
+            if free_mb <= threshold {
+                let severity = if free_mb <= threshold / 10.0 { Severity::Critical } else { Severity::Warning };
+                self.raise(camera_id, AlertKind::CardSpaceLow, severity, Some(free_mb), threshold,
+                    format!("{} MB left on the memory card", free_mb));
+            } else {
+                self.resolve(camera_id, AlertKind::CardSpaceLow);
+            }
+        }
+
+        // A camera that has been stable for the whole window is out of its reconnect loop
+        let reconnects = self.recent_reconnects(camera_id);
+        if reconnects < config.reconnects as usize {
+            self.resolve(camera_id, AlertKind::ReconnectLoop);
+        }
+    }
+
+    fn capture_failed(&self, camera_id: &str, error: &str) {
+        let config = &crate::config::get().alerts;
+        let failures = {
+            let mut alerts = self.alerts.lock().unwrap();
+            let health = alerts.cameras.entry(camera_id.to_string()).or_default();
+            health.consecutive_failures += 1;
+            health.consecutive_failures
+        };
+        if failures >= config.capture_failures {
+            let severity = if failures >= config.capture_failures * 2 { Severity::Critical } else { Severity::Warning };
+            self.raise(camera_id, AlertKind::CaptureFailures, severity, Some(failures as f64), config.capture_failures as f64,
+                format!("{} captures in a row failed (last error: {})", failures, error));
+        }
+    }
+
+    fn record_reconnect(&self, camera_id: &str, reason: &str) {
+        let config = &crate::config::get().alerts;
+        self.alerts
+            .lock()
+            .unwrap()
+            .cameras
+            .entry(camera_id.to_string())
+            .or_default()
+            .reconnects
+            .push_back(Instant::now());
+        let reconnects = self.recent_reconnects(camera_id);
+        if reconnects >= config.reconnects as usize {
+            let severity = if reconnects >= config.reconnects as usize * 2 { Severity::Critical } else { Severity::Warning };
+            self.raise(camera_id, AlertKind::ReconnectLoop, severity, Some(reconnects as f64), config.reconnects as f64,
+                format!("Camera reconnected {} times in {} min (last: {}) - check the USB cable and power",
+                    reconnects, config.reconnect_window_secs.div_ceil(60), reason));
+        }
+    }
+
+    /// Reconnects within the window (older ones are dropped)
+    fn recent_reconnects(&self, camera_id: &str) -> usize {
+        let window = Duration::from_secs(crate::config::get().alerts.reconnect_window_secs);
+        let mut alerts = self.alerts.lock().unwrap();
+        let Some(health) = alerts.cameras.get_mut(camera_id) else { return 0 };
+        while health.reconnects.front().is_some_and(|t| t.elapsed() > window) {
+            health.reconnects.pop_front();
+        }
+        health.reconnects.len()
+    }
+
+    /// Open or update the alert for (camera, kind). Only new alerts and severity
+    /// changes are broadcast.
+    fn raise(&self, camera_id: &str, kind: AlertKind, severity: Severity, value: Option<f64>, threshold: f64, message: String) {
+        let now = now_ms();
+        let mut alerts = self.alerts.lock().unwrap();
+        let key = (camera_id.to_string(), kind);
+
+        if let Some(alert) = alerts.open.get_mut(&key) {
+            let previous = alert.severity;
+            alert.severity = severity;
+            alert.value = value;
+            alert.threshold = threshold;
+            alert.message = message;
+            alert.updated_at_ms = now;
+            alert.occurrences += 1;
+            if severity > previous {
+                // Escalation gets through even if the operator cleared the alert
+                alert.state = AlertState::Active;
+                println!("[alerts] Camera {}: {:?} escalated: {}", camera_id, kind, alert.message);
+                self.broadcast("escalated", alert);
+            } else if severity < previous && alert.state == AlertState::Active {
+                self.broadcast("updated", alert);
+            }
+            return;
+        }
+
+        alerts.next_id += 1;
+        let alert = Alert {
+            id: alerts.next_id,
+            camera_id: camera_id.to_string(),
+            kind,
+            severity,
+            state: AlertState::Active,
+            message,
+            value,
+            threshold,
+            raised_at_ms: now,
+            updated_at_ms: now,
+            occurrences: 1,
+            closed_at_ms: None,
+        };
+        println!("[alerts] Camera {}: {:?} {:?}: {}", camera_id, severity, kind, alert.message);
+        self.broadcast("raised", &alert);
+        alerts.open.insert(key, alert);
+    }
+
+    fn resolve(&self, camera_id: &str, kind: AlertKind) {
+        let mut alerts = self.alerts.lock().unwrap();
+        let Some(mut alert) = alerts.open.remove(&(camera_id.to_string(), kind)) else { return };
+        let now = now_ms();
+        alert.state = AlertState::Resolved;
+        alert.updated_at_ms = now;
+        alert.closed_at_ms = Some(now);
+        println!("[alerts] Camera {}: {:?} resolved", camera_id, kind);
+        self.broadcast("resolved", &alert);
+        if alerts.recent.len() >= RECENT_CAPACITY {
+            alerts.recent.pop_front();
+        }
+        alerts.recent.push_back(alert);
+    }
+
+    fn broadcast(&self, action: &str, alert: &Alert) {
+        let event = serde_json::json!({
+            "type": "camera_alert",
+            "action": action,
+            "camera_id": alert.camera_id,
+            "alert": alert,
+        });
+        let _ = self.ws_tx.send(Message::Text(event.to_string().into()));
+    }
+}
+
+/// Battery reading as a percentage: "65%", "100", or a level name some bodies report
+fn battery_percent(reading: &str) -> Option<f64> {
+    let lower = reading.trim().to_lowercase();
+    if let Ok(percent) = lower.trim_end_matches('%').trim().parse::<f64>() {
+        return (0.0..=100.0).contains(&percent).then_some(percent);
+    }
+    match lower.as_str() {
+        "full" => Some(100.0),
+        "high" => Some(75.0),
+        "half" | "medium" => Some(50.0),
+        "low" => Some(15.0),
+        "empty" | "critical" => Some(0.0),
+        _ => None,
+    }
+}
+
+/// Status fields arrive as numbers or numeric strings
+fn number(status: &Value, key: &str) -> Option<f64> {
+    match status.get(key)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn now_ms() -> u64 {
+    crate::group_capture::unix_millis() as u64
+}
@@ -0,0 +1,176 @@
+//! Optional shared-secret authentication and origin restriction
+//!
+//! With bridged VM networking the daemon is reachable from the venue network, so
+//! capture/delete/config must not be open to anyone who finds the port.
+//!
+//! - `PHOTOBOOTH_AUTH_TOKEN` (or `PHOTOBOOTH_AUTH_TOKEN_FILE`): when set, every route and
+//!   the /ws upgrade need the token, as `Authorization: Bearer <token>`,
+//!   `X-Photobooth-Token: <token>` or `?token=<token>` (for <img> and WebSocket URLs,
+//!   which can't carry headers). CORS preflights are exempt since browsers never
+//!   send credentials on them.
+//! - `PHOTOBOOTH_ALLOWED_ORIGINS`: comma-separated list. Requests with an `Origin`
+//!   header outside the list are rejected and CORS headers name the caller's origin
+//!   instead of `*`. Requests without an Origin (the Tauri backend, curl) aren't affected.
+//!
+//! Without either variable the daemon behaves as before (open, `*` CORS). Both can also
+//! be set in the config file (`[server]` auth_token / auth_token_file / allowed_origins).
+
+use crate::http::{full_body, ResponseBody};
+use hyper::{Method, Request, Response, StatusCode};
+
+#[derive(Clone, Default, Debug)]
+pub struct AuthConfig {
+    token: Option<String>,
+    /// Empty = any origin
+    allowed_origins: Vec<String>,
+}
+
+impl AuthConfig {
+    pub fn new(token: Option<String>, allowed_origins: Vec<String>) -> Self {
+        Self {
+            token: token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()),
+            allowed_origins: allowed_origins
+                .into_iter()
+                .map(|o| o.trim().trim_end_matches('/').to_string())
+                .filter(|o| !o.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Token and allowed origins from the daemon config (file or environment)
+    pub fn from_config(config: &crate::config::ServerConfig) -> Self {
+        Self::new(config.auth_token.clone(), config.allowed_origins.clone())
+    }
+
+    pub fn token_required(&self) -> bool {
+        self.token.is_some()
+    }
+
+    pub fn allowed_origins(&self) -> &[String] {
+        &self.allowed_origins
+    }
+
+    fn origin_allowed(&self, origin: &str) -> bool {
+        self.allowed_origins.is_empty()
+            || self.allowed_origins.iter().any(|o| o.eq_ignore_ascii_case(origin.trim_end_matches('/')))
+    }
+
+    /// Check origin and token
+    pub fn authorize<B>(&self, req: &Request<B>) -> Result<(), AuthError> {
+        if let Some(origin) = request_origin(req) {
+            if !self.origin_allowed(&origin) {
+                println!("[auth] Rejected {} {} from origin {}", req.method(), req.uri().path(), origin);
+                return Err(AuthError::OriginNotAllowed);
+            }
+        }
+
+        let Some(expected) = &self.token else { return Ok(()) };
+        if req.method() == Method::OPTIONS {
+            return Ok(());
+        }
+        match presented_token(req) {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(()),
+            Some(_) => {
+                println!("[auth] Rejected {} {}: wrong token", req.method(), req.uri().path());
+                Err(AuthError::InvalidToken)
+            }
+            None => {
+                println!("[auth] Rejected {} {}: no token", req.method(), req.uri().path());
+                Err(AuthError::MissingToken)
+            }
+        }
+    }
+
+    /// Narrow `Access-Control-Allow-Origin` to the caller's origin when a list is configured
+    pub fn apply_cors(&self, origin: Option<&str>, response: &mut Response<ResponseBody>) {
+        if self.allowed_origins.is_empty() {
+            return;
+        }
+        let headers = response.headers_mut();
+        headers.remove("access-control-allow-origin");
+        if let Some(origin) = origin.filter(|o| self.origin_allowed(o)) {
+            if let Ok(value) = origin.parse() {
+                headers.insert("access-control-allow-origin", value);
+            }
+        }
+        headers.insert("vary", hyper::header::HeaderValue::from_static("Origin"));
+    }
+}
+
+/// `Origin` header of a request, if any
+pub fn request_origin<B>(req: &Request<B>) -> Option<String> {
+    req.headers()
+        .get("origin")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && *v != "null")
+        .map(|v| v.to_string())
+}
+
+fn presented_token<B>(req: &Request<B>) -> Option<String> {
+    let headers = req.headers();
+    if let Some(bearer) = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(bearer.trim().to_string());
+    }
+    if let Some(token) = headers.get("x-photobooth-token").and_then(|v| v.to_str().ok()) {
+        return Some(token.trim().to_string());
+    }
+    req.uri().query()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == "token").then(|| percent_decode(value))
+    })
+}
+
+/// Minimal %XX decoding for the token query parameter
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Why a request was refused
+#[derive(Debug)]
+pub enum AuthError {
+    OriginNotAllowed,
+    MissingToken,
+    InvalidToken,
+}
+
+impl AuthError {
+    pub fn into_response(self) -> Response<ResponseBody> {
+        let (status, message) = match self {
+            Self::OriginNotAllowed => (StatusCode::FORBIDDEN, "Origin not allowed"),
+            Self::MissingToken => (StatusCode::UNAUTHORIZED, "Authentication required"),
+            Self::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token"),
+        };
+        Response::builder()
+            .status(status)
+            .header("content-type", "application/json")
+            .header("www-authenticate", "Bearer")
+            .body(full_body(serde_json::json!({ "success": false, "error": message }).to_string()))
+            .unwrap()
+    }
+}
@@ -1,35 +1,70 @@
 //! Camera operations using gphoto2-wrapper
 
+use crate::hotplug::UsbWatch;
+use crate::simulator::Simulation;
 use crate::types::CameraInfo;
 use std::collections::HashMap;
 use std::process::Command as StdCommand;
+use std::sync::{Arc, Mutex};
+
+/// `gphoto2-wrapper list` result and the USB generation it was read at
+type CameraListCache = Option<(u64, Vec<CameraInfo>)>;
 
 /// State for camera sessions
 #[derive(Clone)]
 pub struct CameraState {
     pub sessions: HashMap<String, CameraInfo>,
+    /// Simulated cameras answer instead of gphoto2-wrapper when set
+    pub simulation: Option<Arc<Simulation>>,
+    /// USB change counter; the camera list is only re-read after a change
+    usb: UsbWatch,
+    camera_list: Arc<Mutex<CameraListCache>>,
 }
 
 impl CameraState {
-    pub fn new() -> Self {
+    pub fn new(simulation: Option<Arc<Simulation>>, usb: UsbWatch) -> Self {
         Self {
             sessions: HashMap::new(),
+            simulation,
+            usb,
+            camera_list: Arc::new(Mutex::new(None)),
         }
     }
 
     /// Check if libgphoto2 is available via gphoto2-wrapper
     pub fn check_libgphoto2(&self) -> bool {
+        if self.simulation.is_some() {
+            return true;
+        }
         // Check if gphoto2-wrapper is available
-        StdCommand::new("/opt/photobooth/gphoto2-wrapper")
+        StdCommand::new(&crate::config::get().paths.wrapper)
             .arg("version")
             .output()
             .map(|_| true)
             .unwrap_or(false)
     }
 
-    /// List connected cameras
+    /// List connected cameras. While USB hotplug events are received the result is
+    /// reused until a device comes or goes; otherwise gphoto2-wrapper runs every time.
     pub fn list_cameras(&self) -> Vec<CameraInfo> {
-        match StdCommand::new("/opt/photobooth/gphoto2-wrapper")
+        if let Some(simulation) = &self.simulation {
+            return simulation.cameras().iter().map(|c| c.info()).collect();
+        }
+        let generation = self.usb.generation();
+        if let (Some(generation), Some((cached_generation, cameras))) = (generation, &*self.camera_list.lock().unwrap()) {
+            if *cached_generation == generation {
+                return cameras.clone();
+            }
+        }
+        let cameras = self.run_list_cameras();
+        if let Some(generation) = generation {
+            *self.camera_list.lock().unwrap() = Some((generation, cameras.clone()));
+        }
+        cameras
+    }
+
+    fn run_list_cameras(&self) -> Vec<CameraInfo> {
+        match StdCommand::new(&crate::config::get().paths.wrapper)
             .arg("list")
             .output()
         {
@@ -61,9 +96,15 @@ impl CameraState {
     }
 
     /// Get debug info from camera
-    pub fn debug_camera(&self, camera_id: Option<u32>) -> serde_json::Value {
-        let camera_idx = camera_id.unwrap_or(0).to_string();
-        match StdCommand::new("/opt/photobooth/gphoto2-wrapper")
+    pub fn debug_camera(&self, camera_index: Option<u32>) -> serde_json::Value {
+        let camera_idx = camera_index.unwrap_or(0).to_string();
+        if let Some(simulation) = &self.simulation {
+            return simulation
+                .camera(&camera_idx)
+                .map(|c| c.debug_info())
+                .unwrap_or_else(|| serde_json::json!({ "error": format!("No simulated camera {}", camera_idx) }));
+        }
+        match StdCommand::new(&crate::config::get().paths.wrapper)
             .arg("debug")
             .arg(&camera_idx)
             .output()
@@ -90,9 +131,15 @@ impl CameraState {
     }
 
     /// List all available configuration widgets
-    pub fn list_widgets(&self, camera_id: Option<u32>) -> serde_json::Value {
-        let camera_idx = camera_id.unwrap_or(0).to_string();
-        match StdCommand::new("/opt/photobooth/gphoto2-wrapper")
+    pub fn list_widgets(&self, camera_index: Option<u32>) -> serde_json::Value {
+        let camera_idx = camera_index.unwrap_or(0).to_string();
+        if let Some(simulation) = &self.simulation {
+            return simulation
+                .camera(&camera_idx)
+                .map(|c| c.widgets())
+                .unwrap_or_else(|| serde_json::json!({ "error": format!("No simulated camera {}", camera_idx) }));
+        }
+        match StdCommand::new(&crate::config::get().paths.wrapper)
             .arg("widgets")
             .arg(&camera_idx)
             .output()
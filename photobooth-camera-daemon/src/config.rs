@@ -0,0 +1,403 @@
+//! Daemon configuration: TOML file plus PHOTOBOOTH_* environment overrides
+//!
+//! The file is `--config <path>`, else `$PHOTOBOOTH_CONFIG`, else
+//! /etc/photobooth/daemon.toml if it exists; without one the built-in defaults match
+//! the original hard-coded layout (everything in /tmp, binaries in /opt/photobooth).
+//! Environment variables override the file (see `ENV_OVERRIDES`), so existing
+//! deployments that only set PHOTOBOOTH_PORT etc. keep working.
+//!
+//! Pipes live under `paths.runtime_dir` and captures under `paths.photo_dir`; cameras
+//! other than "0" use `<dir>/camera<id>` for both, the id being their USB port path.
+//! Keep the runtime dir on tmpfs and point the photo dir at persistent storage to keep
+//! captures across reboots.
+//!
+//! The config is validated once at startup and then available through `get()`.
+
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const DEFAULT_CONFIG_PATH: &str = "/etc/photobooth/daemon.toml";
+
+static CONFIG: OnceLock<DaemonConfig> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub bind: String,
+    /// Shared secret required on every request (never reported by /api/status)
+    #[serde(skip_serializing)]
+    pub auth_token: Option<String>,
+    /// File holding the token, read at startup
+    pub auth_token_file: Option<PathBuf>,
+    /// Origins allowed to call the daemon from a browser; empty = any
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    pub wrapper: PathBuf,
+    pub controller: PathBuf,
+    /// camera_cmd / camera_status / camera_stream pipes
+    pub runtime_dir: PathBuf,
+    /// Downloaded captures
+    pub photo_dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Free space to keep in the photo dir (only acknowledged photos are evicted)
+    pub min_free_mb: u64,
+    pub monitor_interval_secs: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ControllerConfig {
+    /// How long to wait for the controller to read the camera config
+    pub config_timeout_secs: u64,
+}
+
+/// Camera health thresholds (see alerts)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    pub battery_warning_percent: f64,
+    pub battery_critical_percent: f64,
+    /// Shots the card has room for
+    pub shots_remaining_warning: u64,
+    pub card_free_warning_mb: u64,
+    /// Capture errors in a row before alerting
+    pub capture_failures: u32,
+    /// Disconnects/controller restarts within `reconnect_window_secs` that count as a loop
+    pub reconnects: u32,
+    pub reconnect_window_secs: u64,
+}
+
+/// USB hotplug detection (see hotplug)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotplugConfig {
+    /// Watch kernel USB events; off = rely on the periodic camera scan only
+    pub enabled: bool,
+    /// Quiet time after the last USB event before cameras are rescanned
+    pub debounce_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    /// Simulated camera fixtures, e.g. "canon,sony" (see simulator); empty = real cameras
+    pub cameras: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    pub server: ServerConfig,
+    pub paths: PathsConfig,
+    pub storage: StorageConfig,
+    pub controller: ControllerConfig,
+    pub alerts: AlertsConfig,
+    pub hotplug: HotplugConfig,
+    pub simulation: SimulationConfig,
+    /// Config file that was loaded, if any
+    #[serde(skip_deserializing)]
+    pub source: Option<PathBuf>,
+    /// Environment variables that overrode the file
+    #[serde(skip_deserializing)]
+    pub env_overrides: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 58321,
+            bind: "0.0.0.0".to_string(),
+            auth_token: None,
+            auth_token_file: None,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            wrapper: PathBuf::from("/opt/photobooth/gphoto2-wrapper"),
+            controller: PathBuf::from("/opt/photobooth/gphoto2-controller"),
+            runtime_dir: PathBuf::from("/tmp"),
+            photo_dir: PathBuf::from("/tmp"),
+        }
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            min_free_mb: 50,
+            monitor_interval_secs: 30,
+        }
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            battery_warning_percent: 20.0,
+            battery_critical_percent: 10.0,
+            shots_remaining_warning: 50,
+            card_free_warning_mb: 500,
+            capture_failures: 3,
+            reconnects: 3,
+            reconnect_window_secs: 300,
+        }
+    }
+}
+
+impl Default for HotplugConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_ms: 250,
+        }
+    }
+}
+
+impl Default for ControllerConfig {
+    fn default() -> Self {
+        Self { config_timeout_secs: 20 }
+    }
+}
+
+/// Environment variable -> what it overrides
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("PHOTOBOOTH_PORT", "server.port"),
+    ("PHOTOBOOTH_BIND", "server.bind"),
+    ("PHOTOBOOTH_AUTH_TOKEN", "server.auth_token"),
+    ("PHOTOBOOTH_AUTH_TOKEN_FILE", "server.auth_token_file"),
+    ("PHOTOBOOTH_ALLOWED_ORIGINS", "server.allowed_origins (comma-separated)"),
+    ("PHOTOBOOTH_WRAPPER", "paths.wrapper"),
+    ("PHOTOBOOTH_CONTROLLER", "paths.controller"),
+    ("PHOTOBOOTH_RUNTIME_DIR", "paths.runtime_dir"),
+    ("PHOTOBOOTH_PHOTO_DIR", "paths.photo_dir"),
+    ("PHOTOBOOTH_MIN_FREE_MB", "storage.min_free_mb"),
+    ("PHOTOBOOTH_STORAGE_INTERVAL_SECS", "storage.monitor_interval_secs"),
+    ("PHOTOBOOTH_CONFIG_TIMEOUT_SECS", "controller.config_timeout_secs"),
+    ("PHOTOBOOTH_HOTPLUG", "hotplug.enabled"),
+    ("PHOTOBOOTH_SIMULATE", "simulation.cameras"),
+];
+
+impl DaemonConfig {
+    /// Load the config file (if any), apply environment overrides and validate.
+    /// Every problem is reported at once.
+    pub fn load() -> Result<Self, String> {
+        // A file named explicitly must exist; the default location is optional
+        let path = match config_path_arg().or_else(|| std::env::var("PHOTOBOOTH_CONFIG").ok()) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_PATH)).filter(|p| p.exists()),
+        };
+
+        let mut config = match &path {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+                let mut config: DaemonConfig = toml::from_str(&content)
+                    .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+                config.source = Some(path.clone());
+                config
+            }
+            None => DaemonConfig::default(),
+        };
+
+        let mut errors = Vec::new();
+        for (var, _) in ENV_OVERRIDES {
+            if let Ok(value) = std::env::var(var) {
+                if let Err(e) = config.apply_env(var, value.trim()) {
+                    errors.push(format!("{}: {}", var, e));
+                }
+                config.env_overrides.push(var.to_string());
+            }
+        }
+
+        errors.extend(config.validate());
+        if !errors.is_empty() {
+            return Err(format!("Invalid daemon configuration:\n  - {}", errors.join("\n  - ")));
+        }
+        Ok(config)
+    }
+
+    fn apply_env(&mut self, var: &str, value: &str) -> Result<(), String> {
+        fn number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+            value.parse().map_err(|_| format!("'{}' is not a valid number", value))
+        }
+        let path = || PathBuf::from(value);
+        match var {
+            "PHOTOBOOTH_PORT" => self.server.port = number(value)?,
+            "PHOTOBOOTH_BIND" => self.server.bind = value.to_string(),
+            // An explicit token wins over any token file (as before the config file existed)
+            "PHOTOBOOTH_AUTH_TOKEN" => {
+                self.server.auth_token = Some(value.to_string());
+                self.server.auth_token_file = None;
+            }
+            "PHOTOBOOTH_AUTH_TOKEN_FILE" if std::env::var("PHOTOBOOTH_AUTH_TOKEN").is_err() => {
+                self.server.auth_token = None;
+                self.server.auth_token_file = Some(path());
+            }
+            "PHOTOBOOTH_AUTH_TOKEN_FILE" => {}
+            "PHOTOBOOTH_ALLOWED_ORIGINS" => {
+                self.server.allowed_origins = value.split(',').map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect();
+            }
+            "PHOTOBOOTH_WRAPPER" => self.paths.wrapper = path(),
+            "PHOTOBOOTH_CONTROLLER" => self.paths.controller = path(),
+            "PHOTOBOOTH_RUNTIME_DIR" => self.paths.runtime_dir = path(),
+            "PHOTOBOOTH_PHOTO_DIR" => self.paths.photo_dir = path(),
+            "PHOTOBOOTH_MIN_FREE_MB" => self.storage.min_free_mb = number(value)?,
+            "PHOTOBOOTH_STORAGE_INTERVAL_SECS" => self.storage.monitor_interval_secs = number(value)?,
+            "PHOTOBOOTH_CONFIG_TIMEOUT_SECS" => self.controller.config_timeout_secs = number(value)?,
+            "PHOTOBOOTH_HOTPLUG" => {
+                self.hotplug.enabled = match value.to_lowercase().as_str() {
+                    "1" | "true" | "on" | "yes" => true,
+                    "0" | "false" | "off" | "no" => false,
+                    _ => return Err(format!("'{}' is not a boolean (true/false)", value)),
+                }
+            }
+            "PHOTOBOOTH_SIMULATE" => self.simulation.cameras = Some(value.to_string()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn validate(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.server.port == 0 {
+            errors.push("server.port must be between 1 and 65535".to_string());
+        }
+        if self.server.bind.trim().parse::<IpAddr>().is_err() {
+            errors.push(format!("server.bind '{}' is not an IP address", self.server.bind));
+        }
+        if self.server.auth_token.is_some() && self.server.auth_token_file.is_some() {
+            errors.push("set only one of server.auth_token and server.auth_token_file".to_string());
+        }
+        if let Some(path) = &self.server.auth_token_file {
+            match std::fs::read_to_string(path) {
+                Ok(token) if !token.trim().is_empty() => self.server.auth_token = Some(token.trim().to_string()),
+                Ok(_) => errors.push(format!("server.auth_token_file {} is empty", path.display())),
+                Err(e) => errors.push(format!("server.auth_token_file {}: {}", path.display(), e)),
+            }
+        }
+        for origin in &self.server.allowed_origins {
+            if !origin.contains("://") {
+                errors.push(format!("server.allowed_origins entry '{}' must include a scheme (e.g. http://host:port)", origin));
+            }
+        }
+
+        // Simulated cameras don't use the gphoto2 binaries
+        if !self.simulated() {
+            for (key, path) in [("paths.wrapper", &self.paths.wrapper), ("paths.controller", &self.paths.controller)] {
+                if let Err(e) = check_executable(path) {
+                    errors.push(format!("{} {}: {}", key, path.display(), e));
+                }
+            }
+        }
+        for (key, dir) in [("paths.runtime_dir", &self.paths.runtime_dir), ("paths.photo_dir", &self.paths.photo_dir)] {
+            if let Err(e) = check_writable_dir(dir) {
+                errors.push(format!("{} {}: {}", key, dir.display(), e));
+            }
+        }
+
+        if self.storage.monitor_interval_secs == 0 {
+            errors.push("storage.monitor_interval_secs must be at least 1".to_string());
+        }
+        if !(1..=600).contains(&self.controller.config_timeout_secs) {
+            errors.push("controller.config_timeout_secs must be between 1 and 600".to_string());
+        }
+        let alerts = &self.alerts;
+        if !(0.0..=100.0).contains(&alerts.battery_warning_percent)
+            || !(0.0..=alerts.battery_warning_percent).contains(&alerts.battery_critical_percent)
+        {
+            errors.push("alerts: battery thresholds must satisfy 0 <= battery_critical_percent <= battery_warning_percent <= 100".to_string());
+        }
+        if alerts.capture_failures == 0 || alerts.reconnects == 0 || alerts.reconnect_window_secs == 0 {
+            errors.push("alerts.capture_failures, alerts.reconnects and alerts.reconnect_window_secs must be at least 1".to_string());
+        }
+        if !(10..=5000).contains(&self.hotplug.debounce_ms) {
+            errors.push("hotplug.debounce_ms must be between 10 and 5000".to_string());
+        }
+
+        errors
+    }
+
+    /// Simulated cameras requested (the `--simulate` flag is handled by the simulator)
+    pub fn simulated(&self) -> bool {
+        self.simulation.cameras.as_deref().is_some_and(|s| !s.trim().is_empty() && s != "0")
+            || std::env::args().any(|a| a == "--simulate" || a.starts_with("--simulate="))
+    }
+
+    pub fn bind_ip(&self) -> IpAddr {
+        self.server.bind.trim().parse().unwrap_or(IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED))
+    }
+
+    /// Effective configuration for /api/status (the token itself is never included)
+    pub fn status_json(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["server"]["token_required"] = serde_json::json!(self.server.auth_token.is_some());
+        value
+    }
+}
+
+/// `--config <path>` or `--config=<path>`
+fn config_path_arg() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--config=") {
+            path = Some(value.to_string());
+        } else if arg == "--config" {
+            path = args.next();
+        }
+    }
+    path
+}
+
+fn check_executable(path: &Path) -> Result<(), String> {
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    if !metadata.is_file() {
+        return Err("not a file".to_string());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err("not executable".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Create the directory if needed and make sure files can be written to it
+fn check_writable_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err("must be an absolute path".to_string());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let probe = dir.join(format!(".photobooth-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("not writable: {}", e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Make the validated config available to the rest of the daemon
+pub fn init(config: DaemonConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// The daemon's configuration (defaults if `init` hasn't run)
+pub fn get() -> &'static DaemonConfig {
+    CONFIG.get_or_init(DaemonConfig::default)
+}
@@ -0,0 +1,170 @@
+//! Apply several camera settings in one call (camera presets)
+//!
+//! The current config is read once so unchanged settings are skipped and every change
+//! knows the value it replaces. Settings are applied in request order (exposure mode
+//! before shutter/aperture). If one fails, the ones already applied are put back unless
+//! the caller opts out, so the camera never ends up half way between two looks.
+//! The controller's config lock is held throughout, so single-setting changes can't
+//! interleave with a batch.
+
+use crate::controller::{get_camera_config, set_camera_config, ControllerState};
+use crate::protocol::ControllerError;
+use serde::{Deserialize, Serialize};
+
+const MAX_BATCH_SETTINGS: usize = 64;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ConfigBatchEntry {
+    pub setting: String,
+    pub value: String,
+}
+
+/// Body of POST /api/camera/config/batch
+#[derive(Deserialize, Debug)]
+pub struct ConfigBatchRequest {
+    pub settings: Vec<ConfigBatchEntry>,
+    /// Restore already-applied settings when one fails (default true)
+    #[serde(default)]
+    pub rollback_on_failure: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigBatchStatus {
+    /// Camera already had this value
+    Unchanged,
+    Applied,
+    Failed,
+    /// Not attempted because an earlier setting failed
+    Skipped,
+    /// Applied, then restored after a later failure
+    RolledBack,
+}
+
+/// Per-setting outcome of a batch
+#[derive(Serialize, Clone, Debug)]
+pub struct ConfigBatchResult {
+    pub setting: String,
+    pub value: String,
+    /// Value before the batch (None if the camera doesn't report this setting)
+    pub previous: Option<String>,
+    pub status: ConfigBatchStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConfigBatchReport {
+    /// True when every setting ended up at the requested value
+    pub success: bool,
+    pub applied: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    pub rolled_back: usize,
+    pub results: Vec<ConfigBatchResult>,
+}
+
+/// Validate a batch before touching the camera
+pub fn validate_batch(request: &ConfigBatchRequest) -> Result<(), String> {
+    if request.settings.is_empty() {
+        return Err("settings must not be empty".to_string());
+    }
+    if request.settings.len() > MAX_BATCH_SETTINGS {
+        return Err(format!("At most {} settings per batch", MAX_BATCH_SETTINGS));
+    }
+    for (i, entry) in request.settings.iter().enumerate() {
+        if entry.setting.is_empty() {
+            return Err(format!("settings[{}] has an empty setting name", i));
+        }
+        if request.settings[..i].iter().any(|e| e.setting == entry.setting) {
+            return Err(format!("Setting '{}' is listed twice", entry.setting));
+        }
+    }
+    Ok(())
+}
+
+/// Apply a batch of settings. Errors only if the current config can't be read;
+/// per-setting failures are reported in the results.
+pub async fn apply_config_batch(
+    controller_state: &ControllerState,
+    request: ConfigBatchRequest,
+) -> Result<ConfigBatchReport, ControllerError> {
+    let _config_guard = controller_state.config_lock.lock().await;
+    let rollback = request.rollback_on_failure.unwrap_or(true);
+
+    let current = get_camera_config(controller_state).await?;
+    let current_value = |setting: &str| {
+        current
+            .get(setting)
+            .and_then(|s| s.get("value"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+    };
+
+    println!("[config-batch] Camera {}: applying {} setting(s)", controller_state.camera_id, request.settings.len());
+
+    let mut results: Vec<ConfigBatchResult> = Vec::with_capacity(request.settings.len());
+    let mut failed = false;
+    for entry in &request.settings {
+        let previous = current_value(&entry.setting);
+        let mut result = ConfigBatchResult {
+            setting: entry.setting.clone(),
+            value: entry.value.clone(),
+            previous: previous.clone(),
+            status: ConfigBatchStatus::Skipped,
+            error: None,
+        };
+
+        if failed {
+            results.push(result);
+            continue;
+        }
+
+        if previous.as_deref() == Some(entry.value.as_str()) {
+            result.status = ConfigBatchStatus::Unchanged;
+        } else {
+            match set_camera_config(controller_state, &entry.setting, &entry.value).await {
+                Ok(_) => result.status = ConfigBatchStatus::Applied,
+                Err(e) => {
+                    println!("[config-batch] {} = {} failed: {}", entry.setting, entry.value, e);
+                    result.status = ConfigBatchStatus::Failed;
+                    result.error = Some(e.to_string());
+                    failed = true;
+                }
+            }
+        }
+        results.push(result);
+    }
+
+    if failed && rollback {
+        // Undo in reverse order so dependent settings come back before the ones they depend on
+        for result in results.iter_mut().rev() {
+            if result.status != ConfigBatchStatus::Applied {
+                continue;
+            }
+            let Some(previous) = result.previous.clone() else { continue };
+            match set_camera_config(controller_state, &result.setting, &previous).await {
+                Ok(_) => result.status = ConfigBatchStatus::RolledBack,
+                Err(e) => {
+                    eprintln!("[config-batch] Failed to restore {} to {}: {}", result.setting, previous, e);
+                    result.error = Some(format!("Rollback failed: {}", e));
+                }
+            }
+        }
+    }
+
+    let count = |status: ConfigBatchStatus| results.iter().filter(|r| r.status == status).count();
+    let report = ConfigBatchReport {
+        success: !failed,
+        applied: count(ConfigBatchStatus::Applied),
+        unchanged: count(ConfigBatchStatus::Unchanged),
+        failed: count(ConfigBatchStatus::Failed),
+        rolled_back: count(ConfigBatchStatus::RolledBack),
+        results,
+    };
+
+    println!("[config-batch] Camera {}: {} applied, {} unchanged, {} failed, {} rolled back",
+        controller_state.camera_id, report.applied, report.unchanged, report.failed, report.rolled_back);
+
+    Ok(report)
+}
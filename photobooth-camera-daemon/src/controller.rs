@@ -2,66 +2,521 @@
 
 use crate::types::CameraInfo;
 use crate::storage::ensure_storage_space;
-use std::sync::Arc;
+use crate::retention::RetentionIndex;
+use crate::alerts::AlertCenter;
+use crate::raw::{RawPairing, Routed, PAIR_WINDOW};
+use crate::metrics::CaptureTimer;
+use crate::group_capture::GroupPhoto;
+use crate::protocol::{ControllerCommand, ControllerError, ControllerRequest, ControllerResponse};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::Mutex as TokioMutex;
+use tokio::sync::{oneshot, watch};
 use tokio::process::Command as TokioCommand;
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncWriteExt, AsyncBufReadExt};
 
-const CONFIG_RESPONSE_FILE: &str = "/tmp/camera_config_response";
+/// Requests must fit in one atomic pipe write (PIPE_BUF) so concurrent
+/// writers can't interleave, and in the controller's 4KB line buffer.
+const MAX_REQUEST_LINE: usize = 4000;
+
+type PendingRequests = Arc<StdMutex<HashMap<u64, oneshot::Sender<ControllerResponse>>>>;
+
+/// Directories for a camera's controller: its pipes (runtime dir) and downloaded
+/// photos (photo dir), both /tmp by default. Camera "0" uses the configured
+/// directories directly so single-camera setups and older clients see no change;
+/// additional cameras get a camera<id> subdirectory of each.
+#[derive(Clone, Debug)]
+pub struct ControllerPaths {
+    pub runtime_dir: PathBuf,
+    pub photo_dir: PathBuf,
+    pub cmd_pipe: PathBuf,
+    pub status_pipe: PathBuf,
+    pub stream_pipe: PathBuf,
+}
+
+impl ControllerPaths {
+    pub fn for_camera(camera_id: &str) -> Self {
+        let paths = &crate::config::get().paths;
+        let for_camera = |dir: &Path| {
+            if camera_id == "0" {
+                dir.to_path_buf()
+            } else {
+                dir.join(format!("camera{}", camera_id))
+            }
+        };
+        let runtime_dir = for_camera(&paths.runtime_dir);
+        Self {
+            cmd_pipe: runtime_dir.join("camera_cmd"),
+            status_pipe: runtime_dir.join("camera_status"),
+            stream_pipe: runtime_dir.join("camera_stream"),
+            runtime_dir,
+            photo_dir: for_camera(&paths.photo_dir),
+        }
+    }
+
+    /// Remove stale pipes left behind by a previous controller
+    pub fn remove_pipes(&self) {
+        let _ = std::fs::remove_file(&self.cmd_pipe);
+        let _ = std::fs::remove_file(&self.status_pipe);
+    }
+}
 
-/// Shared state for controller communication
+/// Shared state for one camera's controller
 #[derive(Clone)]
 pub struct ControllerState {
-    /// Cached camera status: camera_id -> (status_json, last_update_time)
-    pub cached_status: Arc<TokioMutex<std::collections::HashMap<String, (serde_json::Value, std::time::Instant)>>>,
-    /// Cached camera info from controller (manufacturer, model, port)
+    /// Camera id this controller drives ("0" or the camera's USB port, see supervisor.rs)
+    pub camera_id: String,
+    /// gphoto2 autodetect index the controller opens its camera with; changes as other
+    /// cameras come and go
+    camera_index: Arc<AtomicU32>,
+    /// Pipes and photo directory for this controller
+    pub paths: ControllerPaths,
+    /// Last status event from the controller and when it arrived
+    pub cached_status: Arc<TokioMutex<Option<(serde_json::Value, std::time::Instant)>>>,
+    /// Camera info from controller (manufacturer, model, port)
     /// Only populated when controller successfully connects to camera
-    pub cached_cameras: Arc<TokioMutex<Vec<CameraInfo>>>,
+    pub camera_info: Arc<TokioMutex<Option<CameraInfo>>>,
     /// Controller running flag
     pub controller_active: Arc<TokioMutex<bool>>,
     /// PTP streaming active flag (also used for /api/liveview/status)
     pub ptp_streaming_active: Arc<TokioMutex<bool>>,
+    /// Requests waiting for a controller response: correlation id -> completion channel
+    pending_requests: PendingRequests,
+    /// Next correlation id
+    next_request_id: Arc<AtomicU64>,
+    /// Group capture or sequence this camera is taking part in; its photo events get tagged with it
+    capture_group: Arc<StdMutex<Option<TaggedCapture>>>,
+    /// Held while changing settings so a preset batch isn't interleaved with single changes
+    pub config_lock: Arc<TokioMutex<()>>,
+    /// Shared acknowledgement index; downloaded photos are recorded as captured
+    pub retention: RetentionIndex,
+    /// Shared health alerts; every status event is checked against the thresholds
+    pub alerts: AlertCenter,
+    /// RAW photo events waiting for the JPEG of the same shot
+    pub raw_pairing: RawPairing,
+    /// Capture requests waiting for their photo (capture -> download metric)
+    capture_timer: CaptureTimer,
+}
+
+/// What a camera's photo events are currently tagged with
+#[derive(Clone, Debug)]
+pub enum CaptureTag {
+    /// Multi-camera group capture (`group_id`)
+    Group(String),
+    /// Burst or bracket on this camera (`sequence_id`)
+    Sequence(String),
+}
+
+/// A running group capture or sequence and the photos it produced so far
+#[derive(Debug)]
+struct TaggedCapture {
+    tag: CaptureTag,
+    photos: Vec<GroupPhoto>,
+}
+
+impl CaptureTag {
+    /// Field added to tagged photo_downloaded events
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Group(_) => "group_id",
+            Self::Sequence(_) => "sequence_id",
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Group(id) | Self::Sequence(id) => id,
+        }
+    }
 }
 
 impl ControllerState {
-    pub fn new() -> Self {
+    pub fn new(camera_id: &str, camera_index: u32, retention: RetentionIndex, alerts: AlertCenter) -> Self {
         Self {
-            cached_status: Arc::new(TokioMutex::new(std::collections::HashMap::new())),
-            cached_cameras: Arc::new(TokioMutex::new(Vec::new())),
+            camera_id: camera_id.to_string(),
+            camera_index: Arc::new(AtomicU32::new(camera_index)),
+            paths: ControllerPaths::for_camera(camera_id),
+            cached_status: Arc::new(TokioMutex::new(None)),
+            camera_info: Arc::new(TokioMutex::new(None)),
             controller_active: Arc::new(TokioMutex::new(false)),
             ptp_streaming_active: Arc::new(TokioMutex::new(false)),
+            pending_requests: Arc::new(StdMutex::new(HashMap::new())),
+            next_request_id: Arc::new(AtomicU64::new(1)),
+            capture_group: Arc::new(StdMutex::new(None)),
+            config_lock: Arc::new(TokioMutex::new(())),
+            retention,
+            alerts,
+            raw_pairing: RawPairing::new(),
+            capture_timer: CaptureTimer::default(),
+        }
+    }
+
+    /// gphoto2 autodetect index of the camera
+    pub fn camera_index(&self) -> u32 {
+        self.camera_index.load(Ordering::SeqCst)
+    }
+
+    /// Record the camera's new autodetect index; returns whether it changed
+    pub fn set_camera_index(&self, index: u32) -> bool {
+        self.camera_index.swap(index, Ordering::SeqCst) != index
+    }
+
+    /// Join a group capture or start a sequence. Returns false if the camera is
+    /// already taking part in another one.
+    pub fn begin_capture_group(&self, tag: CaptureTag) -> bool {
+        let mut group = self.capture_group.lock().unwrap();
+        if group.is_some() {
+            return false;
+        }
+        *group = Some(TaggedCapture { tag, photos: Vec::new() });
+        true
+    }
+
+    /// Leave the current group capture or sequence (later photos are ordinary shots again).
+    /// Returns the photos tagged with it, in arrival order.
+    pub fn end_capture_group(&self) -> Vec<GroupPhoto> {
+        self.capture_group.lock().unwrap().take().map(|capture| capture.photos).unwrap_or_default()
+    }
+
+    fn current_capture_group(&self) -> Option<CaptureTag> {
+        self.capture_group.lock().unwrap().as_ref().map(|capture| capture.tag.clone())
+    }
+
+    /// Track a downloaded photo (and the RAW paired with it) until the app acknowledges it,
+    /// and add it to the group capture or sequence it is tagged with
+    fn record_photo(&self, event: &serde_json::Value) {
+        for field in ["file_path", "raw_path"] {
+            if let Some(path) = event.get(field).and_then(|v| v.as_str()) {
+                self.retention.record_captured(Path::new(path), &self.camera_id);
+            }
+        }
+
+        if event.get("type").and_then(|v| v.as_str()) != Some("photo_downloaded") {
+            return;
+        }
+        if let Some(capture) = self.capture_group.lock().unwrap().as_mut() {
+            if event.get(capture.tag.field()).and_then(|v| v.as_str()) == Some(capture.tag.id()) {
+                capture.photos.push(GroupPhoto::from_event(event));
+            }
+        }
+    }
+
+    /// Send a command to the controller and wait for its response.
+    /// Each request gets its own correlation id and completion channel, so concurrent
+    /// callers can't pick up each other's results.
+    pub async fn request(&self, command: ControllerCommand) -> Result<serde_json::Value, ControllerError> {
+        let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let line = serde_json::to_string(&ControllerRequest { id, command: &command })
+            .map_err(|e| ControllerError::InvalidRequest(e.to_string()))?;
+        if line.len() > MAX_REQUEST_LINE {
+            return Err(ControllerError::InvalidRequest(format!(
+                "{} request is {} bytes (limit {})",
+                command.name(),
+                line.len(),
+                MAX_REQUEST_LINE
+            )));
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.pending_requests.lock().unwrap().insert(id, tx);
+        // Removes the entry if we time out or the caller goes away
+        let _pending = PendingGuard { pending: self.pending_requests.clone(), id };
+
+        write_command_line(&self.paths.cmd_pipe, &line)
+            .await
+            .map_err(|e| ControllerError::Unavailable(e.to_string()))?;
+
+        let capture = matches!(command, ControllerCommand::Capture | ControllerCommand::Burst { .. });
+        if capture {
+            crate::metrics::get().capture_requested(&self.camera_id);
+            self.capture_timer.start(id, matches!(command, ControllerCommand::Burst { .. }));
+        }
+
+        let timeout = command.timeout();
+        let started = std::time::Instant::now();
+        let result = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) if response.ok => Ok(response.result),
+            Ok(Ok(response)) => Err(ControllerError::Camera(
+                response.error.unwrap_or_else(|| format!("{} failed", command.name())),
+            )),
+            Ok(Err(_)) => Err(ControllerError::Restarted { command: command.name() }),
+            Err(_) => Err(ControllerError::Timeout { command: command.name(), after: timeout }),
+        };
+
+        let metrics = crate::metrics::get();
+        metrics.request_finished(command.name(), started.elapsed(), matches!(result, Err(ControllerError::Timeout { .. })));
+        if capture {
+            self.capture_timer.finish(id);
+            metrics.capture_finished(&self.camera_id, result.is_ok());
+        }
+        result
+    }
+
+    /// Reset state after the supervisor stopped this camera's controller
+    pub async fn mark_stopped(&self) {
+        *self.controller_active.lock().await = false;
+        *self.ptp_streaming_active.lock().await = false;
+        *self.camera_info.lock().await = None;
+        self.fail_pending_requests();
+    }
+
+    /// Hand a response from the status pipe to whoever is waiting for it
+    fn complete_request(&self, response: ControllerResponse) {
+        let id = response.id;
+        match self.pending_requests.lock().unwrap().remove(&id) {
+            Some(tx) => {
+                let _ = tx.send(response);
+            }
+            None => println!("[protocol] Dropping response {} (caller already gave up)", id),
+        }
+    }
+
+    /// Fail every in-flight request (the controller exited and won't answer)
+    fn fail_pending_requests(&self) {
+        let mut pending = self.pending_requests.lock().unwrap();
+        if !pending.is_empty() {
+            eprintln!("[protocol] Controller gone, failing {} pending request(s)", pending.len());
+        }
+        pending.clear();
+    }
+}
+
+struct PendingGuard {
+    pending: PendingRequests,
+    id: u64,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Tag a status pipe event with the camera it came from, and photo events with
+/// the group capture they belong to. Non-JSON lines and non-object payloads are
+/// passed through unchanged.
+fn tag_event(line: &str, camera_id: &str, capture_tag: Option<&CaptureTag>) -> (Option<serde_json::Value>, String) {
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(mut event) => {
+            if let Some(obj) = event.as_object_mut() {
+                obj.insert("camera_id".to_string(), serde_json::json!(camera_id));
+                let is_photo = obj.get("type").and_then(|v| v.as_str()) == Some("photo_downloaded");
+                if let (true, Some(tag)) = (is_photo, capture_tag) {
+                    obj.insert(tag.field().to_string(), serde_json::json!(tag.id()));
+                }
+            }
+            let text = event.to_string();
+            (Some(event), text)
+        }
+        Err(_) => (None, line.to_string()),
+    }
+}
+
+/// Announce a held RAW once its JPEG had the chance to arrive
+async fn flush_raw(
+    controller_state: ControllerState,
+    ws_tx: tokio::sync::broadcast::Sender<tokio_tungstenite::tungstenite::Message>,
+    key: PathBuf,
+) {
+    tokio::time::sleep(PAIR_WINDOW).await;
+    if let Some(event) = controller_state.raw_pairing.flush(&key).await {
+        controller_state.record_photo(&event);
+        controller_state.alerts.observe(&controller_state.camera_id, &event);
+        println!("[status-pipe] Broadcasting RAW photo from camera {}", controller_state.camera_id);
+        let _ = ws_tx.send(tokio_tungstenite::tungstenite::Message::Text(event.to_string().into()));
+    }
+    controller_state.raw_pairing.finished();
+}
+
+/// Monitor a controller's status pipe: route responses to waiting requests,
+/// cache status/camera info, and broadcast events to WebSocket clients.
+pub async fn monitor_status_pipe(
+    controller_state: ControllerState,
+    ws_tx: tokio::sync::broadcast::Sender<tokio_tungstenite::tungstenite::Message>,
+) {
+    let camera_id = controller_state.camera_id.clone();
+    let status_pipe = controller_state.paths.status_pipe.clone();
+    let mut retry_count = 0;
+    loop {
+        // Try to open the status pipe
+        match tokio::fs::File::open(&status_pipe).await {
+            Ok(file) => {
+                let mut reader = tokio::io::BufReader::new(file);
+                let mut line = String::new();
+                retry_count = 0; // Reset retry count on success
+
+                // Mark controller as active
+                *controller_state.controller_active.lock().await = true;
+
+                loop {
+                    line.clear();
+                    match reader.read_line(&mut line).await {
+                        Ok(0) => {
+                            eprintln!("[camera {}] Status pipe closed (controller died?)", camera_id);
+                            // Mark controller as inactive
+                            *controller_state.controller_active.lock().await = false;
+                            controller_state.fail_pending_requests();
+                            break;
+                        }
+                        Ok(_) => {
+                            let trimmed = line.trim();
+                            if trimmed.is_empty() {
+                                continue;
+                            }
+
+                            // Responses go to the waiting request, not to WS clients
+                            if let Some(response) = ControllerResponse::from_status_line(trimmed) {
+                                controller_state.complete_request(response);
+                                continue;
+                            }
+
+                            // Log photo events so we can trace the broadcast path
+                            if trimmed.contains("photo_downloaded") {
+                                println!("[status-pipe] photo_downloaded event received from camera {}, broadcasting to WS", camera_id);
+                            }
+
+                            let capture_tag = controller_state.current_capture_group();
+                            let (mut parsed, mut tagged) = tag_event(trimmed, &camera_id, capture_tag.as_ref());
+
+                            // RAW files wait for the camera's JPEG of the same shot, or get
+                            // their embedded preview extracted if it doesn't come
+                            if let Some(event) = parsed.as_mut() {
+                                match controller_state.raw_pairing.route(event) {
+                                    Routed::Unchanged => {}
+                                    Routed::Rewritten => tagged = event.to_string(),
+                                    Routed::Held(key) => {
+                                        tokio::spawn(flush_raw(controller_state.clone(), ws_tx.clone(), key));
+                                        continue;
+                                    }
+                                }
+                            }
+
+                            // Parse and cache the status for /api/camera/status endpoint
+                            if let Some(status_json) = parsed {
+                                // Check for camera_connected event with camera info
+                                if status_json.get("type").and_then(|v| v.as_str()) == Some("camera_connected") {
+                                    let manufacturer = status_json.get("manufacturer").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                                    let model = status_json.get("model").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                                    let port = status_json.get("port").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                                    let usb_version = status_json.get("usb_version").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                                    let serial_number = status_json.get("serial_number").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                                    let firmware = status_json.get("firmware").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                                    let lens = status_json.get("lens").and_then(|v| v.as_str()).unwrap_or("").to_string();
+
+                                    if !manufacturer.is_empty() && !model.is_empty() {
+                                        let cam_info = CameraInfo {
+                                            id: camera_id.clone(),
+                                            manufacturer,
+                                            model,
+                                            port,
+                                            usb_version,
+                                            serial_number,
+                                            firmware,
+                                            lens,
+                                        };
+                                        println!("[status-pipe] Camera {} connected, caching camera info: {} {}", camera_id, cam_info.manufacturer, cam_info.model);
+                                        *controller_state.camera_info.lock().await = Some(cam_info);
+                                    }
+                                }
+
+                                // Track the new file until the app acknowledges it
+                                if matches!(status_json.get("type").and_then(|v| v.as_str()), Some("photo_downloaded" | "raw_attached")) {
+                                    controller_state.record_photo(&status_json);
+                                }
+                                if status_json.get("type").and_then(|v| v.as_str()) == Some("photo_downloaded") {
+                                    if let Some(elapsed) = controller_state.capture_timer.downloaded() {
+                                        crate::metrics::get().capture_downloaded(&camera_id, elapsed);
+                                    }
+                                }
+
+                                // Check for camera_disconnected event - clear cache
+                                if status_json.get("type").and_then(|v| v.as_str()) == Some("camera_disconnected") {
+                                    println!("[status-pipe] Camera {} disconnected, clearing camera cache", camera_id);
+                                    *controller_state.camera_info.lock().await = None;
+                                }
+
+                                controller_state.alerts.observe(&camera_id, &status_json);
+                                *controller_state.cached_status.lock().await = Some((status_json, std::time::Instant::now()));
+                            }
+
+                            // Broadcast to all WebSocket clients
+                            match ws_tx.send(tokio_tungstenite::tungstenite::Message::Text(tagged.into())) {
+                                Ok(n) => {
+                                    if trimmed.contains("photo_downloaded") {
+                                        println!("[status-pipe] Broadcast photo_downloaded to {} WS clients", n);
+                                    }
+                                }
+                                Err(_) => {
+                                    // No active WebSocket receivers - this is normal when no clients connected
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("[camera {}] Error reading status pipe: {}", camera_id, e);
+                            *controller_state.controller_active.lock().await = false;
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                retry_count += 1;
+                if retry_count <= 5 {
+                    eprintln!("[camera {}] Failed to open status pipe (attempt {}): {}, retrying in 1s...", camera_id, retry_count, e);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                } else {
+                    eprintln!("[camera {}] Failed to open status pipe after {} attempts, giving up: {}", camera_id, retry_count, e);
+                    *controller_state.controller_active.lock().await = false;
+                    break;
+                }
+            }
         }
     }
 }
 
-/// Spawn the gphoto2-controller process with respawn logic.
-/// If the controller crashes, it will be automatically restarted.
+/// Spawn the gphoto2-controller process for one camera with respawn logic.
+/// If the controller crashes, it will be automatically restarted. Returns once
+/// `stop` is set (camera unplugged), after killing the controller.
 pub async fn start_controller_process(
     controller_state: ControllerState,
     ws_tx: tokio::sync::broadcast::Sender<tokio_tungstenite::tungstenite::Message>,
+    mut stop: watch::Receiver<bool>,
 ) {
-    // Spawn storage monitor task (runs once every 30 seconds)
-    tokio::spawn(async {
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-            ensure_storage_space(50).await; // Ensure at least 50MB free
+    let camera_id = controller_state.camera_id.clone();
+    let paths = controller_state.paths.clone();
+
+    for dir in [&paths.runtime_dir, &paths.photo_dir] {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("[camera {}] Failed to create {}: {}", camera_id, dir.display(), e);
         }
-    });
+    }
+    paths.remove_pipes();
 
     loop {
+        let stopped = *stop.borrow();
+        if stopped {
+            return;
+        }
+
         // Check storage before starting controller
-        ensure_storage_space(50).await;
+        ensure_storage_space(&controller_state.retention, &ws_tx).await;
 
-        let mut child = match TokioCommand::new("/opt/photobooth/gphoto2-controller")
-            .arg("0")
+        let mut child = match TokioCommand::new(&crate::config::get().paths.controller)
+            .arg(controller_state.camera_index().to_string())
+            .arg(&paths.photo_dir)
+            .arg(&paths.runtime_dir)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::inherit())
+            .kill_on_drop(true)
             .spawn()
         {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("Failed to spawn gphoto2-controller: {}", e);
+                eprintln!("[camera {}] Failed to spawn gphoto2-controller: {}", camera_id, e);
                 eprintln!("Retrying in 5 seconds...");
                 tokio::time::sleep(std::time::Duration::from_secs(5)).await;
                 continue;
@@ -69,161 +524,65 @@ pub async fn start_controller_process(
         };
 
         let pid = child.id();
-        println!("gphoto2-controller started (pid: {:?})", pid);
+        println!("[camera {}] gphoto2-controller started (pid: {:?})", camera_id, pid);
 
         // Wait for the status pipe to be created
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
 
         // Spawn a task to monitor the status pipe for events
-        let ws_tx = ws_tx.clone();
-        let cached_status = controller_state.cached_status.clone();
-        let cached_cameras = controller_state.cached_cameras.clone();
-        let controller_active = controller_state.controller_active.clone();
-        let _status_monitor = tokio::spawn(async move {
-            let mut retry_count = 0;
-            loop {
-                // Try to open the status pipe
-                match tokio::fs::File::open("/tmp/camera_status").await {
-                    Ok(file) => {
-                        let mut reader = tokio::io::BufReader::new(file);
-                        let mut line = String::new();
-                        retry_count = 0; // Reset retry count on success
-
-                        // Mark controller as active
-                        *controller_active.lock().await = true;
-
-                        loop {
-                            line.clear();
-                            match reader.read_line(&mut line).await {
-                                Ok(0) => {
-                                    eprintln!("Status pipe closed (controller died?)");
-                                    // Mark controller as inactive
-                                    *controller_active.lock().await = false;
-                                    break;
-                                }
-                                Ok(_) => {
-                                    let trimmed = line.trim();
-                                    if trimmed.is_empty() {
-                                        continue;
-                                    }
-
-                                    // Log photo events so we can trace the broadcast path
-                                    if trimmed.contains("photo_downloaded") {
-                                        println!("[status-pipe] photo_downloaded event received, broadcasting to WS");
-                                    }
-
-                                    // Parse and cache the status for /api/camera/status endpoint
-                                    if let Ok(status_json) = serde_json::from_str::<serde_json::Value>(&trimmed) {
-                                        // Check for camera_connected event with camera info
-                                        if status_json.get("type").and_then(|v| v.as_str()) == Some("camera_connected") {
-                                            let cam_id = status_json.get("camera_id").and_then(|v| v.as_str()).unwrap_or("0").to_string();
-                                            let manufacturer = status_json.get("manufacturer").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                            let model = status_json.get("model").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                            let port = status_json.get("port").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                            let usb_version = status_json.get("usb_version").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                            let serial_number = status_json.get("serial_number").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                            let firmware = status_json.get("firmware").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                            let lens = status_json.get("lens").and_then(|v| v.as_str()).unwrap_or("").to_string();
-
-                                            if !manufacturer.is_empty() && !model.is_empty() {
-                                                let cam_info = CameraInfo {
-                                                    id: cam_id,
-                                                    manufacturer,
-                                                    model,
-                                                    port,
-                                                    usb_version,
-                                                    serial_number,
-                                                    firmware,
-                                                    lens,
-                                                };
-                                                println!("[status-pipe] Camera connected event, caching camera info: {} {}", cam_info.manufacturer, cam_info.model);
-                                                *cached_cameras.lock().await = vec![cam_info];
-                                            }
-                                        }
-
-                                        // Check for camera_disconnected event - clear cache
-                                        if status_json.get("type").and_then(|v| v.as_str()) == Some("camera_disconnected") {
-                                            println!("[status-pipe] Camera disconnected event, clearing camera cache");
-                                            cached_cameras.lock().await.clear();
-                                        }
-
-                                        let mut cached = cached_status.lock().await;
-                                        // Use "0" as default camera ID for single camera setup
-                                        cached.insert("0".to_string(), (status_json, std::time::Instant::now()));
-                                    }
-
-                                    // Broadcast to all WebSocket clients
-                                    match ws_tx.send(tokio_tungstenite::tungstenite::Message::Text(trimmed.to_string().into())) {
-                                        Ok(n) => {
-                                            if trimmed.contains("photo_downloaded") {
-                                                println!("[status-pipe] Broadcast photo_downloaded to {} WS clients", n);
-                                            }
-                                        }
-                                        Err(_) => {
-                                            // No active WebSocket receivers - this is normal when no clients connected
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Error reading status pipe: {}", e);
-                                    *controller_active.lock().await = false;
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        retry_count += 1;
-                        if retry_count <= 5 {
-                            eprintln!("Failed to open status pipe (attempt {}): {}, retrying in 1s...", retry_count, e);
-                            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                            continue;
-                        } else {
-                            eprintln!("Failed to open status pipe after {} attempts, giving up: {}", retry_count, e);
-                            *controller_active.lock().await = false;
-                            break;
-                        }
-                    }
-                }
+        let status_monitor = tokio::spawn(monitor_status_pipe(controller_state.clone(), ws_tx.clone()));
+
+        // Wait for the process to exit, or for the supervisor to stop us
+        let status = tokio::select! {
+            result = child.wait() => result,
+            _ = wait_for_stop(&mut stop) => {
+                println!("[camera {}] Stopping gphoto2-controller", camera_id);
+                let _ = child.kill().await;
+                status_monitor.abort();
+                controller_state.mark_stopped().await;
+                paths.remove_pipes();
+                return;
             }
-        });
+        };
 
-        // Wait for the process to exit
-        let status = match child.wait().await {
-            Ok(s) => s,
+        match status {
+            Ok(s) => eprintln!("[camera {}] gphoto2-controller exited with status: {}", camera_id, s),
             Err(e) => {
-                eprintln!("Error waiting for controller: {}", e);
+                eprintln!("[camera {}] Error waiting for controller: {}", camera_id, e);
                 tokio::time::sleep(std::time::Duration::from_secs(5)).await;
                 continue;
             }
-        };
-
-        eprintln!("gphoto2-controller exited with status: {}", status);
+        }
+        controller_state.fail_pending_requests();
+        controller_state.alerts.controller_restarted(&camera_id);
+        crate::metrics::get().controller_restarted(&camera_id);
 
         // Clean up pipes
-        let _ = std::fs::remove_file("/tmp/camera_cmd");
-        let _ = std::fs::remove_file("/tmp/camera_status");
+        paths.remove_pipes();
 
         // Wait a bit before restarting
-        println!("Restarting controller in 3 seconds...");
+        println!("[camera {}] Restarting controller in 3 seconds...", camera_id);
         tokio::time::sleep(std::time::Duration::from_secs(3)).await;
     }
 }
 
-/// Send a command to the gphoto2-controller via named pipe.
-/// Retries a few times if the pipe isn't available yet.
-pub async fn send_command(cmd: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let cmd_pipe = "/tmp/camera_cmd";
+/// Resolve once the supervisor asks this controller to stop
+pub async fn wait_for_stop(stop: &mut watch::Receiver<bool>) {
+    let _ = stop.wait_for(|stopped| *stopped).await;
+}
 
+/// Write one request line to the gphoto2-controller command pipe.
+/// Retries a few times if the pipe isn't available yet.
+async fn write_command_line(cmd_pipe: &Path, line: &str) -> std::io::Result<()> {
     // Retry opening the pipe in case the controller is briefly unavailable
     let mut last_err = None;
     for attempt in 1..=5 {
         match OpenOptions::new().write(true).open(cmd_pipe).await {
             Ok(mut file) => {
-                let full_cmd = format!("{}\n", cmd);
-                file.write_all(full_cmd.as_bytes()).await?;
+                // Single write so the line stays atomic on the FIFO
+                file.write_all(format!("{}\n", line).as_bytes()).await?;
                 file.flush().await?;
-                println!("Sent command to controller: {}", cmd);
+                println!("Sent request to controller: {}", line);
                 return Ok(());
             }
             Err(e) => {
@@ -234,120 +593,74 @@ pub async fn send_command(cmd: &str) -> Result<(), Box<dyn std::error::Error + S
         }
     }
 
-    Err(Box::new(last_err.unwrap()))
-}
-
-/// Wait for the controller to write a config response file.
-/// Polls the file with a 200ms interval until content appears or timeout.
-pub async fn wait_for_config_response(timeout_ms: u64) -> Result<serde_json::Value, String> {
-    let start = std::time::Instant::now();
-    let timeout = std::time::Duration::from_millis(timeout_ms);
-    let mut poll_count = 0u32;
-
-    loop {
-        if start.elapsed() > timeout {
-            return Err(format!(
-                "Timeout waiting for controller config response after {}ms ({} polls). \
-                 Is the controller running with CONFIG command support?",
-                timeout_ms, poll_count
-            ));
-        }
-
-        poll_count += 1;
-        match tokio::fs::read_to_string(CONFIG_RESPONSE_FILE).await {
-            Ok(content) => {
-                let trimmed = content.trim();
-                if !trimmed.is_empty() {
-                    println!("[config] Got response after {}ms ({} polls)",
-                             start.elapsed().as_millis(), poll_count);
-                    // Delete the response file so it doesn't get stale
-                    let _ = tokio::fs::remove_file(CONFIG_RESPONSE_FILE).await;
-                    return serde_json::from_str(trimmed)
-                        .map_err(|e| format!("Failed to parse config response: {}. Raw: {}", e, trimmed));
-                }
-            }
-            Err(_) => {
-                // File doesn't exist yet, keep waiting
-            }
-        }
-
-        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-    }
+    Err(last_err.unwrap())
 }
 
 /// Get camera config by routing through the controller (avoids USB contention).
-pub async fn get_camera_config() -> serde_json::Value {
-    println!("[config] get_camera_config called");
-
-    // Check if controller command pipe exists
-    match tokio::fs::metadata("/tmp/camera_cmd").await {
-        Ok(_) => println!("[config] Command pipe /tmp/camera_cmd exists"),
-        Err(e) => {
-            println!("[config] Command pipe /tmp/camera_cmd NOT found: {}", e);
-            return serde_json::json!({
-                "error": format!("Controller command pipe not found - is the controller running? ({})", e)
-            });
-        }
-    }
-
-    // Remove stale response file
-    match tokio::fs::remove_file(CONFIG_RESPONSE_FILE).await {
-        Ok(_) => println!("[config] Removed stale response file"),
-        Err(_) => println!("[config] No stale response file to remove (ok)"),
-    }
-
-    // Send CONFIG command to controller
-    println!("[config] Sending CONFIG command to controller...");
-    if let Err(e) = send_command("CONFIG").await {
-        println!("[config] ERROR: Failed to send CONFIG command: {}", e);
-        return serde_json::json!({
-            "error": format!("Failed to send CONFIG command: {}", e)
-        });
-    }
-    println!("[config] CONFIG command sent successfully, waiting for response...");
-
-    // Wait for response (up to 20s — controller may need to finish its current poll cycle first)
-    match wait_for_config_response(20000).await {
-        Ok(val) => {
-            println!("[config] Got config response successfully");
-            val
-        },
-        Err(e) => {
-            println!("[config] ERROR: {}", e);
-            // Check if controller process is running
-            if let Ok(output) = std::process::Command::new("pgrep").arg("-f").arg("gphoto2-controller").output() {
-                let pid = String::from_utf8_lossy(&output.stdout);
-                if pid.trim().is_empty() {
-                    println!("[config] WARNING: gphoto2-controller process NOT running!");
-                } else {
-                    println!("[config] gphoto2-controller PID: {}", pid.trim());
-                }
-            }
-            serde_json::json!({
-                "error": e
-            })
-        },
+pub async fn get_camera_config(controller_state: &ControllerState) -> Result<serde_json::Value, ControllerError> {
+    println!("[config] Requesting camera config from controller...");
+    let result = controller_state.request(ControllerCommand::Config).await;
+    match &result {
+        Ok(_) => println!("[config] Got config response successfully"),
+        Err(e) => println!("[config] ERROR: {}", e),
     }
+    result
 }
 
 /// Set camera config by routing through the controller (avoids USB contention).
-pub async fn set_camera_config(setting: &str, value: &str) -> serde_json::Value {
-    // Remove stale response file
-    let _ = tokio::fs::remove_file(CONFIG_RESPONSE_FILE).await;
-
-    // Send SETCONFIG command with JSON payload
-    let cmd = format!("SETCONFIG {{\"setting\":\"{}\",\"value\":\"{}\"}}", setting, value);
-    if let Err(e) = send_command(&cmd).await {
-        return serde_json::json!({
-            "error": format!("Failed to send SETCONFIG command: {}", e)
+pub async fn set_camera_config(
+    controller_state: &ControllerState,
+    setting: &str,
+    value: &str,
+) -> Result<serde_json::Value, ControllerError> {
+    controller_state
+        .request(ControllerCommand::SetConfig {
+            setting: setting.to_string(),
+            value: value.to_string(),
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn photo_event(file: &str, tag: Option<(&str, &str)>) -> serde_json::Value {
+        let mut event = serde_json::json!({
+            "type": "photo_downloaded",
+            "file_path": format!("/photos/{}", file),
+            "camera_path": format!("/store/{}", file),
         });
+        if let Some((field, id)) = tag {
+            event[field] = serde_json::json!(id);
+        }
+        event
     }
 
-    // Wait for response
-    match wait_for_config_response(15000).await {
-        Ok(val) => val,
-        Err(e) => serde_json::json!({
-            "error": e
-        }),
+    #[test]
+    fn collects_photos_tagged_with_the_running_capture() {
+        let alerts = AlertCenter::new(tokio::sync::broadcast::channel(16).0);
+        let state = ControllerState::new("1-1", 0, RetentionIndex::in_memory(), alerts);
+        assert!(state.begin_capture_group(CaptureTag::Group("grp_1".to_string())));
+        assert!(!state.begin_capture_group(CaptureTag::Sequence("seq_1".to_string())));
+
+        // More photos than the WS broadcast channel holds, mixed with other events
+        for i in 0..1500 {
+            state.record_photo(&photo_event(&format!("IMG_{}.JPG", i), Some(("group_id", "grp_1"))));
+            state.record_photo(&serde_json::json!({ "type": "raw_attached", "raw_path": "/photos/x.CR3", "group_id": "grp_1" }));
+        }
+        state.record_photo(&photo_event("untagged.JPG", None));
+        state.record_photo(&photo_event("other.JPG", Some(("group_id", "grp_2"))));
+        state.record_photo(&photo_event("sequence.JPG", Some(("sequence_id", "grp_1"))));
+
+        let photos = state.end_capture_group();
+        assert_eq!(photos.len(), 1500);
+        assert_eq!(photos[0].file_path, "/photos/IMG_0.JPG");
+        assert_eq!(photos[1499].camera_path, "/store/IMG_1499.JPG");
+
+        // Later photos are ordinary shots
+        state.record_photo(&photo_event("late.JPG", Some(("group_id", "grp_1"))));
+        assert!(state.end_capture_group().is_empty());
+        assert!(state.begin_capture_group(CaptureTag::Sequence("seq_1".to_string())));
     }
 }
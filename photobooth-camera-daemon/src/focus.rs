@@ -0,0 +1,123 @@
+//! Focus and live view zoom
+//!
+//! `POST /api/camera/focus` triggers autofocus, steps the focus towards near or far,
+//! moves the AF point to a position in the live view frame or sets the live view zoom.
+//! Every action goes to the camera's gphoto2-controller as a FOCUS request, like
+//! SETCONFIG: the controller holds live view frames while it drives the focus widgets
+//! (`autofocusdrive`, `manualfocusdrive`, `changeafarea`, `eoszoom`, ...), so focusing
+//! works during PTP streaming without a second process opening the camera.
+//!
+//! `GET /api/camera/focus` reports the widget each action would use on this body, or
+//! null when the body has none (see gphoto2-wrapper/controller/camera_focus.h).
+
+use crate::controller::ControllerState;
+use crate::protocol::{ControllerCommand, ControllerError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Most manual focus steps in one request
+pub const MAX_FOCUS_STEPS: u32 = 100;
+
+/// The controller takes AF point coordinates in thousandths of the frame
+const POINT_SCALE: f64 = 1000.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusAction {
+    Autofocus,
+    /// Stop a running autofocus
+    Cancel,
+    Near,
+    Far,
+    /// Move the AF point (needs live view running)
+    Point,
+    /// Live view magnification
+    Zoom,
+    /// Which of the above the body supports (GET /api/camera/focus)
+    Capabilities,
+}
+
+/// Body of POST /api/camera/focus
+#[derive(Deserialize, Debug)]
+pub struct FocusRequest {
+    pub action: FocusAction,
+    /// near/far: steps of the body's smallest focus unit (default 1)
+    #[serde(default)]
+    pub steps: Option<u32>,
+    /// point: position in the live view frame, 0.0 (left/top) to 1.0 (right/bottom)
+    #[serde(default)]
+    pub x: Option<f64>,
+    #[serde(default)]
+    pub y: Option<f64>,
+    /// zoom: magnification as the body names it (Canon: 1, 5 or 10)
+    #[serde(default)]
+    pub level: Option<u32>,
+}
+
+impl FocusRequest {
+    /// Check the request and turn it into the controller command
+    fn into_command(self) -> Result<ControllerCommand, String> {
+        let (mut steps, mut x, mut y, mut level) = (None, None, None, None);
+        match self.action {
+            FocusAction::Autofocus | FocusAction::Cancel => {}
+            FocusAction::Near | FocusAction::Far => {
+                let count = self.steps.unwrap_or(1);
+                if !(1..=MAX_FOCUS_STEPS).contains(&count) {
+                    return Err(format!("steps must be between 1 and {}", MAX_FOCUS_STEPS));
+                }
+                steps = Some(count);
+            }
+            FocusAction::Point => {
+                let (fx, fy) = match (self.x, self.y) {
+                    (Some(fx), Some(fy)) => (fx, fy),
+                    _ => return Err("point requires x and y".to_string()),
+                };
+                if !(0.0..=1.0).contains(&fx) || !(0.0..=1.0).contains(&fy) {
+                    return Err("x and y must be between 0.0 and 1.0 (fraction of the live view frame)".to_string());
+                }
+                x = Some((fx * POINT_SCALE).round() as u32);
+                y = Some((fy * POINT_SCALE).round() as u32);
+            }
+            FocusAction::Zoom => match self.level {
+                Some(requested) if requested >= 1 => level = Some(requested),
+                _ => return Err("zoom requires a level of 1 or more".to_string()),
+            },
+            FocusAction::Capabilities => {
+                return Err("Use GET /api/camera/focus for the supported actions".to_string());
+            }
+        }
+        Ok(ControllerCommand::Focus { action: self.action, steps, x, y, level })
+    }
+}
+
+/// Run a focus action on the camera
+pub async fn focus(controller_state: &ControllerState, request: FocusRequest) -> Result<Value, ControllerError> {
+    let action = request.action;
+    let command = request.into_command().map_err(ControllerError::InvalidRequest)?;
+    println!("[focus] Camera {}: {:?}", controller_state.camera_id, command);
+    let result = controller_state.request(command).await?;
+    Ok(serde_json::json!({
+        "success": true,
+        "camera_id": controller_state.camera_id,
+        "action": action,
+        "result": result,
+    }))
+}
+
+/// Focus actions the camera supports, with the widget each one drives
+pub async fn focus_capabilities(controller_state: &ControllerState) -> Result<Value, ControllerError> {
+    let actions = controller_state
+        .request(ControllerCommand::Focus {
+            action: FocusAction::Capabilities,
+            steps: None,
+            x: None,
+            y: None,
+            level: None,
+        })
+        .await?;
+    Ok(serde_json::json!({
+        "success": true,
+        "camera_id": controller_state.camera_id,
+        "actions": actions,
+    }))
+}
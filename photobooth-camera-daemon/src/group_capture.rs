@@ -0,0 +1,226 @@
+//! Synchronized capture across several cameras ("bullet-time")
+//!
+//! Every camera in the group gets its CAPTURE request released at the same moment.
+//! The controllers report when they actually triggered (wall clock, same host), which
+//! gives the per-camera skew. Photo events from group members are tagged with the
+//! group id while the capture runs, and each controller keeps the photos tagged with it
+//! (see ControllerState::end_capture_group), so a slow WS subscriber can't lose any.
+//! A single `group_captured` event lists them all.
+
+use crate::controller::{CaptureTag, ControllerState};
+use crate::protocol::ControllerCommand;
+use crate::supervisor::Controllers;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::Barrier;
+use tokio_tungstenite::tungstenite::Message;
+
+/// How long to keep collecting photo events after the captures return, so
+/// companion files (RAW+JPEG) picked up by the polling loop land in the group
+pub const DEFAULT_SETTLE_MS: u64 = 1000;
+pub const MAX_SETTLE_MS: u64 = 10_000;
+
+static NEXT_GROUP: AtomicU64 = AtomicU64::new(1);
+
+/// Body of POST /api/capture/group (all fields optional)
+#[derive(Deserialize, Default, Debug)]
+pub struct GroupCaptureRequest {
+    /// Cameras to fire; defaults to every running controller
+    #[serde(default)]
+    pub cameras: Option<Vec<String>>,
+    /// Extra time to wait for late photo events, in ms
+    #[serde(default)]
+    pub settle_ms: Option<u64>,
+}
+
+/// A photo downloaded by one of the group's cameras
+#[derive(Serialize, Clone, Debug)]
+pub struct GroupPhoto {
+    pub file_path: String,
+    pub camera_path: String,
+    /// RAW of the same shot (RAW+JPEG mode, or the RAW `file_path` was extracted from)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_path: Option<String>,
+}
+
+impl GroupPhoto {
+    /// Photo from a photo_downloaded event
+    pub fn from_event(event: &serde_json::Value) -> Self {
+        let field = |name: &str| event.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        Self {
+            file_path: field("file_path"),
+            camera_path: field("camera_path"),
+            raw_path: event.get("raw_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        }
+    }
+}
+
+/// Outcome for one camera in the group
+#[derive(Serialize, Clone, Debug)]
+pub struct GroupMember {
+    pub camera_id: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Wall-clock trigger time reported by the controller (ms since epoch)
+    pub triggered_at_ms: Option<i64>,
+    /// Trigger delay relative to the earliest camera in the group
+    pub skew_ms: Option<i64>,
+    pub photos: Vec<GroupPhoto>,
+}
+
+/// Result of a group capture, also broadcast as the `group_captured` event
+#[derive(Serialize, Clone, Debug)]
+pub struct GroupCaptureResult {
+    pub group_id: String,
+    pub members: Vec<GroupMember>,
+    /// Largest trigger skew across cameras that fired
+    pub max_skew_ms: Option<i64>,
+}
+
+/// Why a group capture could not start
+#[derive(Debug)]
+pub enum GroupCaptureError {
+    NoCameras,
+    UnknownCamera(String),
+    /// Camera is already part of another group capture
+    Busy(String),
+}
+
+impl GroupCaptureError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NoCameras => StatusCode::SERVICE_UNAVAILABLE,
+            Self::UnknownCamera(_) => StatusCode::NOT_FOUND,
+            Self::Busy(_) => StatusCode::CONFLICT,
+        }
+    }
+}
+
+impl std::fmt::Display for GroupCaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoCameras => write!(f, "No cameras available for group capture"),
+            Self::UnknownCamera(id) => write!(f, "Unknown camera: {}", id),
+            Self::Busy(id) => write!(f, "Camera {} is already in a group capture", id),
+        }
+    }
+}
+
+/// Fire CAPTURE on a set of cameras at once and collect the resulting photos
+pub async fn capture_group(
+    controllers: &Controllers,
+    request: GroupCaptureRequest,
+) -> Result<GroupCaptureResult, GroupCaptureError> {
+    let members: Vec<ControllerState> = match &request.cameras {
+        Some(ids) => {
+            let mut members = Vec::with_capacity(ids.len());
+            for id in ids {
+                if members.iter().any(|m: &ControllerState| &m.camera_id == id) {
+                    continue;
+                }
+                match controllers.get(id).await {
+                    Some(state) => members.push(state),
+                    None => return Err(GroupCaptureError::UnknownCamera(id.clone())),
+                }
+            }
+            members
+        }
+        None => controllers.all().await,
+    };
+    if members.is_empty() {
+        return Err(GroupCaptureError::NoCameras);
+    }
+
+    let group_id = format!(
+        "grp_{}_{}",
+        unix_millis(),
+        NEXT_GROUP.fetch_add(1, Ordering::Relaxed)
+    );
+
+    // Claim every camera before firing any of them
+    for (i, member) in members.iter().enumerate() {
+        if !member.begin_capture_group(CaptureTag::Group(group_id.clone())) {
+            for claimed in &members[..i] {
+                claimed.end_capture_group();
+            }
+            return Err(GroupCaptureError::Busy(member.camera_id.clone()));
+        }
+    }
+
+    let ws_tx = controllers.ws_sender();
+
+    println!("[group] Capturing {} on cameras {:?}", group_id,
+        members.iter().map(|m| m.camera_id.as_str()).collect::<Vec<_>>());
+
+    // Release all CAPTURE requests together
+    let barrier = Arc::new(Barrier::new(members.len()));
+    let results = futures_util::future::join_all(members.iter().map(|member| {
+        let barrier = barrier.clone();
+        async move {
+            barrier.wait().await;
+            member.request(ControllerCommand::Capture).await
+        }
+    }))
+    .await;
+
+    let settle_ms = request.settle_ms.unwrap_or(DEFAULT_SETTLE_MS).min(MAX_SETTLE_MS);
+    tokio::time::sleep(std::time::Duration::from_millis(settle_ms)).await;
+    let mut photos = Vec::with_capacity(members.len());
+    for member in &members {
+        member.raw_pairing.settled().await;
+        photos.push(member.end_capture_group());
+    }
+
+    let mut group_members: Vec<GroupMember> = members
+        .iter()
+        .zip(results)
+        .zip(photos)
+        .map(|((member, result), photos)| {
+            let (ok, error, triggered_at_ms) = match result {
+                Ok(value) => (true, None, value.get("triggered_at_ms").and_then(|v| v.as_i64())),
+                Err(e) => (false, Some(e.to_string()), None),
+            };
+            GroupMember {
+                camera_id: member.camera_id.clone(),
+                ok,
+                error,
+                triggered_at_ms,
+                skew_ms: None,
+                photos,
+            }
+        })
+        .collect();
+
+    let earliest = group_members.iter().filter_map(|m| m.triggered_at_ms).min();
+    if let Some(earliest) = earliest {
+        for member in &mut group_members {
+            member.skew_ms = member.triggered_at_ms.map(|t| t - earliest);
+        }
+    }
+    let max_skew_ms = group_members.iter().filter_map(|m| m.skew_ms).max();
+
+    let result = GroupCaptureResult {
+        group_id,
+        members: group_members,
+        max_skew_ms,
+    };
+
+    println!("[group] {} done: {} photo(s), max skew {:?}ms",
+        result.group_id, result.members.iter().map(|m| m.photos.len()).sum::<usize>(), result.max_skew_ms);
+
+    let mut event = serde_json::to_value(&result).unwrap_or_default();
+    event["type"] = serde_json::json!("group_captured");
+    let _ = ws_tx.send(Message::Text(event.to_string().into()));
+
+    Ok(result)
+}
+
+pub fn unix_millis() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,462 @@
+//! USB hotplug detection
+//!
+//! Listens to the kernel's uevent netlink broadcast (the same feed udevd reads, so no
+//! udevd is needed on the VM) for `usb_device` add/remove events. A burst of events
+//! (a cable bump produces several: interfaces, then the device) is debounced for
+//! `hotplug.debounce_ms`, then the supervisor rescans right away: controllers for new
+//! cameras start, controllers for unplugged cameras stop, and the running ones get
+//! USB_CHANGED so they reopen on the new bus address instead of waiting for their
+//! next poll. Each device is broadcast as `usb_device_added` / `usb_device_removed`
+//! with its vendor/product ids.
+//!
+//! The periodic scan in the supervisor keeps running as a fallback, so if the netlink
+//! socket cannot be opened (non-Linux, containers without netlink) nothing breaks.
+
+use crate::supervisor::{detect_cameras, Controllers, DetectedCamera};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+/// USB interface class of still image (PTP) cameras
+const STILL_IMAGE_CLASS: &str = "6";
+
+/// Tracks whether the USB device set changed since a caller last looked.
+/// Lets `CameraState::list_cameras` reuse its last result while hotplug is running.
+#[derive(Clone, Default)]
+pub struct UsbWatch {
+    generation: Arc<AtomicU64>,
+    active: Arc<AtomicBool>,
+}
+
+impl UsbWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counter bumped on every debounced USB change, or None while hotplug events
+    /// are not being received (callers must then assume anything may have changed)
+    pub fn generation(&self) -> Option<u64> {
+        if self.active.load(Ordering::SeqCst) {
+            Some(self.generation.load(Ordering::SeqCst))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum UsbAction {
+    Added,
+    Removed,
+}
+
+/// A USB device as described by its uevent
+#[derive(Clone, Debug)]
+struct UsbDevice {
+    devpath: String,
+    vendor_id: String,
+    product_id: String,
+    busnum: u32,
+    devnum: u32,
+    /// Has a still image (PTP) interface
+    still_image: bool,
+}
+
+impl UsbDevice {
+    /// gphoto2 port of the device ("usb:001,005"), as reported by `gphoto2-wrapper detect`
+    fn port(&self) -> String {
+        format!("usb:{:03},{:03}", self.busnum, self.devnum)
+    }
+}
+
+/// One parsed uevent: "ACTION@DEVPATH\0KEY=VALUE\0..."
+struct Uevent {
+    action: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Uevent {
+    fn parse(buf: &[u8]) -> Option<Self> {
+        let mut parts = buf.split(|b| *b == 0).filter(|p| !p.is_empty());
+        let header = String::from_utf8_lossy(parts.next()?);
+        // udevd rebroadcasts start with "libudev"; only the kernel's "action@devpath" form is read
+        let (action, _) = header.split_once('@')?;
+        let fields = parts
+            .filter_map(|p| {
+                let text = String::from_utf8_lossy(p);
+                text.split_once('=').map(|(k, v)| (k.to_string(), v.to_string()))
+            })
+            .collect();
+        Some(Self { action: action.to_string(), fields })
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|s| s.as_str())
+    }
+}
+
+/// Turns raw uevents into USB device changes
+#[derive(Default)]
+struct UsbTracker {
+    /// Devpaths of devices seen with a still image interface
+    still_image: HashSet<String>,
+}
+
+impl UsbTracker {
+    fn handle(&mut self, event: &Uevent) -> Option<(UsbAction, UsbDevice)> {
+        if event.get("SUBSYSTEM") != Some("usb") {
+            return None;
+        }
+        let devpath = event.get("DEVPATH")?;
+        match event.get("DEVTYPE") {
+            // Interface events come before the device's remove and after its add;
+            // INTERFACE is "class/subclass/protocol" in decimal
+            Some("usb_interface") => {
+                if event.get("INTERFACE").and_then(|i| i.split('/').next()) == Some(STILL_IMAGE_CLASS) {
+                    if let Some((parent, _)) = devpath.rsplit_once('/') {
+                        self.still_image.insert(parent.to_string());
+                    }
+                }
+                None
+            }
+            Some("usb_device") => {
+                let action = match event.action.as_str() {
+                    "add" => UsbAction::Added,
+                    "remove" => UsbAction::Removed,
+                    _ => return None,
+                };
+                // PRODUCT is "vid/pid/bcdDevice" in hex without leading zeros
+                let mut product = event.get("PRODUCT")?.split('/');
+                let vendor_id = u16::from_str_radix(product.next()?, 16).ok()?;
+                let product_id = u16::from_str_radix(product.next()?, 16).ok()?;
+                let device_class = event.get("TYPE").and_then(|t| t.split('/').next());
+                let still_image = device_class == Some(STILL_IMAGE_CLASS) || self.still_image.contains(devpath);
+                if action == UsbAction::Removed {
+                    self.still_image.remove(devpath);
+                }
+                Some((action, UsbDevice {
+                    devpath: devpath.to_string(),
+                    vendor_id: format!("{:04x}", vendor_id),
+                    product_id: format!("{:04x}", product_id),
+                    busnum: event.get("BUSNUM")?.parse().ok()?,
+                    devnum: event.get("DEVNUM")?.parse().ok()?,
+                    still_image,
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Watch USB hotplug events and keep the controllers in sync. Runs forever, or
+/// returns right away (logging why) if uevents cannot be received.
+pub async fn run(watch: UsbWatch, controllers: Controllers) {
+    let mut socket = match UeventSocket::open() {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("[hotplug] USB hotplug unavailable, relying on periodic camera scans: {}", e);
+            return;
+        }
+    };
+    let debounce = Duration::from_millis(crate::config::get().hotplug.debounce_ms);
+    println!("[hotplug] Watching USB hotplug events (debounce {}ms)", debounce.as_millis());
+    watch.active.store(true, Ordering::SeqCst);
+
+    let mut tracker = UsbTracker::default();
+    // Camera id of each port at the last rescan, to name the camera in removal events
+    let mut camera_ports = match detect_cameras().await {
+        Ok(detected) => camera_ports_of(&controllers, &detected).await,
+        Err(_) => BTreeMap::new(),
+    };
+    let mut buf = vec![0u8; 16 * 1024];
+
+    loop {
+        let mut changes = Vec::new();
+        // The kernel drops events when the socket buffer overflows; rescan anyway then
+        let mut overflowed = false;
+        match socket.recv(&mut buf).await {
+            Ok(len) => changes.extend(Uevent::parse(&buf[..len]).and_then(|e| tracker.handle(&e))),
+            Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => overflowed = true,
+            Err(e) => {
+                eprintln!("[hotplug] Reading uevents failed, relying on periodic camera scans: {}", e);
+                watch.active.store(false, Ordering::SeqCst);
+                return;
+            }
+        }
+        if changes.is_empty() && !overflowed {
+            continue;
+        }
+
+        // Collect the rest of the burst until the bus has been quiet for the debounce time
+        while let Ok(received) = tokio::time::timeout(debounce, socket.recv(&mut buf)).await {
+            match received {
+                Ok(len) => changes.extend(Uevent::parse(&buf[..len]).and_then(|e| tracker.handle(&e))),
+                Err(_) => overflowed = true,
+            }
+        }
+        if overflowed {
+            eprintln!("[hotplug] Uevent buffer overflowed, some USB events were lost");
+        }
+
+        watch.generation.fetch_add(1, Ordering::SeqCst);
+        let removed = overflowed || changes.iter().any(|(action, _)| *action == UsbAction::Removed);
+        let detected = controllers.rescan(removed).await;
+
+        for (action, device) in &changes {
+            let port = device.port();
+            let camera_id = match action {
+                UsbAction::Added => match detected.iter().flatten().find(|c| c.port == port) {
+                    Some(camera) => controllers.camera_id_of(camera).await,
+                    None => None,
+                },
+                UsbAction::Removed => camera_ports.get(&port).cloned(),
+            };
+            broadcast(&controllers, *action, device, camera_id);
+        }
+        if let Some(detected) = detected {
+            camera_ports = camera_ports_of(&controllers, &detected).await;
+        }
+    }
+}
+
+/// Camera id of each detected camera's gphoto2 port
+async fn camera_ports_of(controllers: &Controllers, detected: &[DetectedCamera]) -> BTreeMap<String, String> {
+    let mut ports = BTreeMap::new();
+    for camera in detected {
+        if let Some(camera_id) = controllers.camera_id_of(camera).await {
+            ports.insert(camera.port.clone(), camera_id);
+        }
+    }
+    ports
+}
+
+fn broadcast(controllers: &Controllers, action: UsbAction, device: &UsbDevice, camera_id: Option<String>) {
+    let event_type = match action {
+        UsbAction::Added => "usb_device_added",
+        UsbAction::Removed => "usb_device_removed",
+    };
+    println!(
+        "[hotplug] {} {}:{} at {}{}",
+        event_type,
+        device.vendor_id,
+        device.product_id,
+        device.port(),
+        camera_id.as_ref().map(|id| format!(" (camera {})", id)).unwrap_or_default()
+    );
+    let event = serde_json::json!({
+        "type": event_type,
+        "vendor_id": device.vendor_id,
+        "product_id": device.product_id,
+        "port": device.port(),
+        "busnum": device.busnum,
+        "devnum": device.devnum,
+        "devpath": device.devpath,
+        "still_image": device.still_image,
+        "camera_id": camera_id,
+    });
+    let _ = controllers.ws_sender().send(Message::Text(event.to_string().into()));
+}
+
+/// Netlink socket subscribed to kernel uevents
+#[cfg(target_os = "linux")]
+struct UeventSocket {
+    fd: tokio::io::unix::AsyncFd<std::os::fd::OwnedFd>,
+}
+
+#[cfg(target_os = "linux")]
+impl UeventSocket {
+    /// Multicast group the kernel sends uevents to (udevd rebroadcasts on group 2)
+    const KERNEL_GROUP: u32 = 1;
+
+    fn open() -> Result<Self, String> {
+        use std::os::fd::FromRawFd;
+
+        unsafe {
+            let raw = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            );
+            if raw < 0 {
+                return Err(format!("Failed to open netlink socket: {}", std::io::Error::last_os_error()));
+            }
+            let fd = std::os::fd::OwnedFd::from_raw_fd(raw);
+
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = Self::KERNEL_GROUP;
+            if libc::bind(
+                raw,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            ) != 0
+            {
+                return Err(format!("Failed to bind netlink socket: {}", std::io::Error::last_os_error()));
+            }
+
+            let fd = tokio::io::unix::AsyncFd::new(fd)
+                .map_err(|e| format!("Failed to register netlink socket: {}", e))?;
+            Ok(Self { fd })
+        }
+    }
+
+    async fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        use std::os::fd::AsRawFd;
+
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let result = guard.try_io(|fd| {
+                let len = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+                if len < 0 {
+                    Err(std::io::Error::last_os_error())
+                } else {
+                    Ok(len as usize)
+                }
+            });
+            match result {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+/// Stub for non-Linux platforms (used only for development)
+#[cfg(not(target_os = "linux"))]
+struct UeventSocket;
+
+#[cfg(not(target_os = "linux"))]
+impl UeventSocket {
+    fn open() -> Result<Self, String> {
+        Err("USB hotplug events are only available on Linux".to_string())
+    }
+
+    async fn recv(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        std::future::pending().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Kernel uevents for plugging in and unplugging a Canon EOS R100, in the order
+    // `udevadm monitor --kernel --property` lists them
+    const DEVICE_ADD: &[u8] = b"add@/devices/pci0000:00/0000:00:14.0/usb1/1-2\0ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2\0SUBSYSTEM=usb\0MAJOR=189\0MINOR=4\0\
+        DEVNAME=bus/usb/001/005\0DEVTYPE=usb_device\0PRODUCT=4a9/32d4/2\0TYPE=0/0/0\0\
+        BUSNUM=001\0DEVNUM=005\0SEQNUM=4521\0";
+    const INTERFACE_ADD: &[u8] = b"add@/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0\0ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0\0SUBSYSTEM=usb\0\
+        DEVTYPE=usb_interface\0PRODUCT=4a9/32d4/2\0TYPE=0/0/0\0INTERFACE=6/1/1\0\
+        MODALIAS=usb:v04A9p32D4d0002dc00dsc00dp00ic06isc01ip01in00\0SEQNUM=4522\0";
+    const DEVICE_BIND: &[u8] = b"bind@/devices/pci0000:00/0000:00:14.0/usb1/1-2\0ACTION=bind\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2\0SUBSYSTEM=usb\0DEVTYPE=usb_device\0\
+        PRODUCT=4a9/32d4/2\0TYPE=0/0/0\0BUSNUM=001\0DEVNUM=005\0DRIVER=usb\0SEQNUM=4523\0";
+    const INTERFACE_REMOVE: &[u8] = b"remove@/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0\0ACTION=remove\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0\0SUBSYSTEM=usb\0\
+        DEVTYPE=usb_interface\0PRODUCT=4a9/32d4/2\0TYPE=0/0/0\0INTERFACE=6/1/1\0SEQNUM=4530\0";
+    const DEVICE_REMOVE: &[u8] = b"remove@/devices/pci0000:00/0000:00:14.0/usb1/1-2\0ACTION=remove\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2\0SUBSYSTEM=usb\0MAJOR=189\0MINOR=4\0\
+        DEVNAME=bus/usb/001/005\0DEVTYPE=usb_device\0PRODUCT=4a9/32d4/2\0TYPE=0/0/0\0\
+        BUSNUM=001\0DEVNUM=005\0SEQNUM=4531\0";
+    // A USB stick (mass storage interface)
+    const STORAGE_INTERFACE_ADD: &[u8] = b"add@/devices/pci0000:00/0000:00:14.0/usb1/1-3/1-3:1.0\0ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-3/1-3:1.0\0SUBSYSTEM=usb\0\
+        DEVTYPE=usb_interface\0PRODUCT=781/5567/100\0TYPE=0/0/0\0INTERFACE=8/6/80\0SEQNUM=4540\0";
+    const STORAGE_REMOVE: &[u8] = b"remove@/devices/pci0000:00/0000:00:14.0/usb1/1-3\0ACTION=remove\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-3\0SUBSYSTEM=usb\0DEVTYPE=usb_device\0\
+        PRODUCT=781/5567/100\0TYPE=0/0/0\0BUSNUM=001\0DEVNUM=006\0SEQNUM=4541\0";
+    const BLOCK_ADD: &[u8] = b"add@/devices/virtual/block/loop0\0ACTION=add\0\
+        DEVPATH=/devices/virtual/block/loop0\0SUBSYSTEM=block\0DEVTYPE=disk\0SEQNUM=4550\0";
+
+    fn handle(tracker: &mut UsbTracker, buf: &[u8]) -> Option<(UsbAction, UsbDevice)> {
+        tracker.handle(&Uevent::parse(buf).expect("uevent"))
+    }
+
+    #[test]
+    fn test_uevent_parse() {
+        let event = Uevent::parse(DEVICE_ADD).unwrap();
+        assert_eq!(event.action, "add");
+        assert_eq!(event.get("SUBSYSTEM"), Some("usb"));
+        assert_eq!(event.get("DEVTYPE"), Some("usb_device"));
+        assert_eq!(event.get("PRODUCT"), Some("4a9/32d4/2"));
+        assert_eq!(event.get("DEVNAME"), Some("bus/usb/001/005"));
+        assert_eq!(event.get("INTERFACE"), None);
+
+        let event = Uevent::parse(INTERFACE_ADD).unwrap();
+        assert_eq!(event.get("INTERFACE"), Some("6/1/1"));
+        // Values may contain '='
+        let event = Uevent::parse(b"change@/devices/x\0ACTION=change\0KEY=a=b\0").unwrap();
+        assert_eq!(event.get("KEY"), Some("a=b"));
+    }
+
+    #[test]
+    fn test_uevent_parse_ignores_non_kernel_messages() {
+        // udevd rebroadcast header
+        assert!(Uevent::parse(b"libudev\0\xfe\xed\xca\xfe\0ACTION=add\0").is_none());
+        assert!(Uevent::parse(b"").is_none());
+        assert!(Uevent::parse(b"\0\0").is_none());
+    }
+
+    #[test]
+    fn test_tracker_camera_plug_cycle() {
+        let mut tracker = UsbTracker::default();
+
+        // The device add comes before its interfaces; a PTP camera with a class 0
+        // device descriptor isn't known to be one yet
+        let (action, device) = handle(&mut tracker, DEVICE_ADD).unwrap();
+        assert_eq!(action, UsbAction::Added);
+        assert_eq!(device.devpath, "/devices/pci0000:00/0000:00:14.0/usb1/1-2");
+        assert_eq!(device.vendor_id, "04a9");
+        assert_eq!(device.product_id, "32d4");
+        assert_eq!((device.busnum, device.devnum), (1, 5));
+        assert_eq!(device.port(), "usb:001,005");
+        assert!(!device.still_image);
+
+        assert!(handle(&mut tracker, INTERFACE_ADD).is_none());
+        assert!(handle(&mut tracker, DEVICE_BIND).is_none());
+        assert!(handle(&mut tracker, INTERFACE_REMOVE).is_none());
+
+        let (action, device) = handle(&mut tracker, DEVICE_REMOVE).unwrap();
+        assert_eq!(action, UsbAction::Removed);
+        assert_eq!(device.port(), "usb:001,005");
+        assert!(device.still_image);
+        assert!(tracker.still_image.is_empty());
+    }
+
+    #[test]
+    fn test_tracker_device_class_still_image() {
+        let mut tracker = UsbTracker::default();
+        let buf = b"add@/devices/pci0000:00/0000:00:14.0/usb2/2-1\0ACTION=add\0\
+            DEVPATH=/devices/pci0000:00/0000:00:14.0/usb2/2-1\0SUBSYSTEM=usb\0DEVTYPE=usb_device\0\
+            PRODUCT=54c/c34/100\0TYPE=6/1/1\0BUSNUM=002\0DEVNUM=012\0SEQNUM=4600\0";
+        let (action, device) = handle(&mut tracker, buf).unwrap();
+        assert_eq!(action, UsbAction::Added);
+        assert_eq!((device.vendor_id.as_str(), device.product_id.as_str()), ("054c", "0c34"));
+        assert_eq!(device.port(), "usb:002,012");
+        assert!(device.still_image);
+    }
+
+    #[test]
+    fn test_tracker_ignores_other_devices() {
+        let mut tracker = UsbTracker::default();
+        assert!(handle(&mut tracker, BLOCK_ADD).is_none());
+        assert!(handle(&mut tracker, STORAGE_INTERFACE_ADD).is_none());
+        assert!(tracker.still_image.is_empty());
+
+        let (action, device) = handle(&mut tracker, STORAGE_REMOVE).unwrap();
+        assert_eq!(action, UsbAction::Removed);
+        assert!(!device.still_image);
+
+        // Missing or malformed ids
+        let buf = b"add@/devices/x/usb1/1-4\0ACTION=add\0DEVPATH=/devices/x/usb1/1-4\0\
+            SUBSYSTEM=usb\0DEVTYPE=usb_device\0PRODUCT=zz/1/1\0BUSNUM=001\0DEVNUM=007\0";
+        assert!(handle(&mut tracker, buf).is_none());
+        let buf = b"add@/devices/x/usb1/1-4\0ACTION=add\0DEVPATH=/devices/x/usb1/1-4\0\
+            SUBSYSTEM=usb\0DEVTYPE=usb_device\0PRODUCT=4a9/32d4/2\0DEVNUM=007\0";
+        assert!(handle(&mut tracker, buf).is_none());
+    }
+}
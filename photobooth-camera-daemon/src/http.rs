@@ -6,7 +6,6 @@ use hyper::body::{Bytes, Frame};
 use http_body_util::{Full, StreamBody, BodyExt, combinators::BoxBody};
 use tokio_util::io::ReaderStream;
 use futures_util::stream::StreamExt;
-use std::path::Path;
 use serde::Serialize;
 use sha1::{Sha1, Digest};
 use base64::Engine;
@@ -28,15 +27,31 @@ pub fn compute_websocket_accept(key: &str) -> String {
     hasher.update(key.as_bytes());
     hasher.update(WS_MAGIC.as_bytes());
     let result = hasher.finalize();
-    base64::engine::general_purpose::STANDARD.encode(&result)
+    base64::engine::general_purpose::STANDARD.encode(result)
 }
 
 use crate::camera::CameraState;
-use crate::controller::ControllerState;
-use crate::storage::ensure_storage_space;
+use crate::controller::{ControllerPaths, ControllerState};
+use crate::supervisor::{Controllers, PRIMARY_CAMERA_ID};
+use crate::group_capture::{capture_group, GroupCaptureRequest};
+use crate::config_batch::{apply_config_batch, validate_batch, ConfigBatchRequest};
+use crate::settings::{apply_settings, read_settings, SettingsError, SettingsRequest};
+use crate::focus::{focus, focus_capabilities, FocusRequest};
+use crate::sequence_capture::{
+    capture_bracket, capture_burst, BracketRequest, BurstRequest, SequenceCaptureError, SequenceCaptureResult,
+};
+use crate::protocol::{ControllerCommand, ControllerError};
+use crate::storage::{ensure_storage_space, get_available_space, sync_retention};
+use crate::photos::{self, list_photos};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
 /// Parse query parameter from URI
 pub fn parse_query_param(uri: &str, param_name: &str) -> Option<u32> {
+    parse_query_string(uri, param_name)?.parse::<u32>().ok()
+}
+
+/// Raw value of a query parameter
+fn parse_query_string(uri: &str, param_name: &str) -> Option<String> {
     let query_start = uri.find('?')?;
     let query = &uri[query_start + 1..];
 
@@ -44,20 +59,96 @@ pub fn parse_query_param(uri: &str, param_name: &str) -> Option<u32> {
         let mut parts = pair.splitn(2, '=');
         if let Some(key) = parts.next() {
             if key == param_name {
-                if let Some(value) = parts.next() {
-                    return value.parse::<u32>().ok();
-                }
+                return parts.next().filter(|value| !value.is_empty()).map(|value| value.to_string());
             }
         }
     }
     None
 }
 
+/// Camera id from the `camera` query parameter (defaults to camera "0"). Ids name
+/// directories (camera<id>), so anything but a USB port path or index is ignored.
+pub fn camera_id_param(uri: &str) -> String {
+    parse_query_string(uri, "camera")
+        .filter(|id| id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.'))
+        .unwrap_or_else(|| PRIMARY_CAMERA_ID.to_string())
+}
+
+/// Look up the controller for the camera named in the request.
+/// Unknown cameras get a 404 so callers can tell a typo from a camera error.
+async fn resolve_controller(
+    controllers: &Controllers,
+    camera_id: &str,
+) -> Result<ControllerState, Response<ResponseBody>> {
+    match controllers.get(camera_id).await {
+        Some(state) => Ok(state),
+        None => Err(make_api_response_with_status(StatusCode::NOT_FOUND, serde_json::json!({
+            "success": false,
+            "error": format!("Unknown camera: {}", camera_id)
+        }))),
+    }
+}
+
+/// Parse a JSON request body. An empty body gives `Ok(None)`; unreadable or
+/// malformed bodies get a 400 naming the request (`what`).
+async fn read_json_body<T: serde::de::DeserializeOwned>(
+    req: Request<Incoming>,
+    what: &str,
+) -> Result<Option<T>, Response<ResponseBody>> {
+    let body_bytes = match BodyExt::collect(req.into_body()).await {
+        Ok(collected) => collected.to_bytes(),
+        Err(e) => {
+            return Err(make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+                "success": false,
+                "error": format!("Failed to read request body: {}", e)
+            })));
+        }
+    };
+    if body_bytes.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(None);
+    }
+    serde_json::from_slice::<T>(&body_bytes).map(Some).map_err(|e| {
+        make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+            "success": false,
+            "error": format!("Invalid {} request: {}", what, e)
+        }))
+    })
+}
+
+fn missing_body_response(what: &str) -> Response<ResponseBody> {
+    make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+        "success": false,
+        "error": format!("Missing {} request body", what)
+    }))
+}
+
+/// Response for a burst/bracket: the sequence result, `success` only if every frame was taken
+fn sequence_response(result: Result<SequenceCaptureResult, SequenceCaptureError>) -> Response<ResponseBody> {
+    match result {
+        Ok(result) => {
+            let complete = result.error.is_none() && result.frames.len() as u32 == result.requested;
+            let mut body = serde_json::to_value(&result).unwrap_or_default();
+            body["success"] = serde_json::json!(complete);
+            make_api_response(body)
+        }
+        Err(e) => make_api_response_with_status(e.status_code(), serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
 /// Make a JSON API response
 pub fn make_api_response(data: impl Serialize) -> Response<ResponseBody> {
+    make_api_response_with_status(StatusCode::OK, data)
+}
+
+/// Make a JSON API response with an explicit status code
+pub fn make_api_response_with_status(status: StatusCode, data: impl Serialize) -> Response<ResponseBody> {
     match serde_json::to_string(&data) {
         Ok(json) => {
             Response::builder()
+                .status(status)
                 .header("content-type", "application/json")
                 .header("access-control-allow-origin", "*")
                 .body(full_body(json))
@@ -72,6 +163,15 @@ pub fn make_api_response(data: impl Serialize) -> Response<ResponseBody> {
     }
 }
 
+/// JSON error response for a failed controller request.
+/// Timeouts become 504, camera-side failures 502 and a missing controller 503.
+pub fn controller_error_response(error: &ControllerError) -> Response<ResponseBody> {
+    make_api_response_with_status(error.status_code(), serde_json::json!({
+        "success": false,
+        "error": error.to_string()
+    }))
+}
+
 /// Get USB device info from VBoxManage (runs on Windows host)
 pub fn get_vbox_usb_info() -> serde_json::Value {
     use std::process::Command;
@@ -82,12 +182,7 @@ pub fn get_vbox_usb_info() -> serde_json::Value {
     ];
 
     let find_vbox = || -> Option<&'static str> {
-        for path in &vbox_paths {
-            if std::path::Path::new(path).exists() {
-                return Some(path);
-            }
-        }
-        None
+        vbox_paths.iter().copied().find(|path| std::path::Path::new(path).exists())
     };
 
     let vbox_path = match find_vbox() {
@@ -106,7 +201,7 @@ pub fn get_vbox_usb_info() -> serde_json::Value {
 
     // Get VM info to check USB controller type
     if let Ok(output) = Command::new(vbox_path)
-        .args(&["showvminfo", "PhotoboothLinux", "--machinereadable"])
+        .args(["showvminfo", "PhotoboothLinux", "--machinereadable"])
         .output()
     {
         if output.status.success() {
@@ -130,7 +225,7 @@ pub fn get_vbox_usb_info() -> serde_json::Value {
 
     // Get host USB devices with actual speeds
     if let Ok(output) = Command::new(vbox_path)
-        .args(&["list", "usbhost"])
+        .args(["list", "usbhost"])
         .output()
     {
         if output.status.success() {
@@ -195,7 +290,7 @@ pub fn get_vbox_usb_info() -> serde_json::Value {
 /// Handle incoming HTTP requests
 pub async fn handle_request(
     state: CameraState,
-    controller_state: ControllerState,
+    controllers: Controllers,
     req: Request<Incoming>,
 ) -> Result<Option<Response<ResponseBody>>, hyper::Error> {
     let method = req.method();
@@ -209,8 +304,8 @@ pub async fn handle_request(
         return Ok(Some(Response::builder()
             .status(StatusCode::OK)
             .header("access-control-allow-origin", "*")
-            .header("access-control-allow-methods", "GET, POST, OPTIONS")
-            .header("access-control-allow-headers", "Content-Type, Authorization")
+            .header("access-control-allow-methods", "GET, HEAD, POST, DELETE, OPTIONS")
+            .header("access-control-allow-headers", "Content-Type, Authorization, X-Photobooth-Token, Range, If-Range, If-None-Match")
             .body(full_body(""))
             .unwrap()));
     }
@@ -228,6 +323,18 @@ pub async fn handle_request(
         }
     }
 
+    let camera_id = camera_id_param(&uri_str);
+
+    // Endpoints that drive a camera resolve its controller up front
+    macro_rules! controller_for_camera {
+        () => {
+            match resolve_controller(&controllers, &camera_id).await {
+                Ok(state) => state,
+                Err(resp) => return Ok(Some(resp)),
+            }
+        };
+    }
+
     let response = match (method, path) {
         // Health check
         (&Method::GET, "/api/health") => {
@@ -236,21 +343,35 @@ pub async fn handle_request(
 
         // List cameras
         (&Method::GET, "/api/cameras") => {
-            let controller_active = *controller_state.controller_active.lock().await;
-            let cached_cameras = controller_state.cached_cameras.lock().await.clone();
+            let mut controller_active = false;
+            let mut cached_cameras = Vec::new();
+            for controller_state in controllers.all().await {
+                if *controller_state.controller_active.lock().await {
+                    controller_active = true;
+                    if let Some(info) = controller_state.camera_info.lock().await.clone() {
+                        cached_cameras.push(info);
+                    }
+                }
+            }
 
             let cameras = if controller_active && !cached_cameras.is_empty() {
-                println!("GET /api/cameras - returning cached info from controller");
+                println!("GET /api/cameras - returning cached info from {} controller(s)", cached_cameras.len());
                 cached_cameras
             } else if controller_active {
                 println!("GET /api/cameras - controller active but no cache yet, returning empty");
                 vec![]
             } else {
                 println!("GET /api/cameras - controller not active, calling gphoto2-wrapper list");
-                let cameras = state.list_cameras();
+                let mut cameras = state.list_cameras();
                 if cameras.iter().any(|c| c.model == "Unknown Camera") {
                     eprintln!("WARNING: Got 'Unknown Camera' even though controller is inactive");
                 }
+                // The wrapper numbers cameras by autodetect index; report controller ids
+                for camera in &mut cameras {
+                    if !camera.port.is_empty() {
+                        camera.id = controllers.camera_id_for_port(&camera.port).await;
+                    }
+                }
                 cameras
             };
 
@@ -259,90 +380,151 @@ pub async fn handle_request(
 
         // Capture photo
         (&Method::POST, "/api/capture") => {
-            ensure_storage_space(50).await;
-            match crate::controller::send_command("CAPTURE").await {
+            let controller_state = controller_for_camera!();
+            ensure_storage_space(controllers.retention(), &controllers.ws_sender()).await;
+            match controller_state.request(ControllerCommand::Capture).await {
                 Ok(_) => Some(make_api_response(serde_json::json!({
                     "success": true,
-                    "message": "Capture command sent"
+                    "message": "Capture completed",
+                    "camera_id": camera_id
                 }))),
-                Err(e) => Some(make_api_response(serde_json::json!({
+                Err(e) => Some(controller_error_response(&e)),
+            }
+        }
+
+        // Synchronized capture on several cameras
+        (&Method::POST, "/api/capture/group") => {
+            let request = match read_json_body::<GroupCaptureRequest>(req, "group capture").await {
+                Ok(request) => request.unwrap_or_default(),
+                Err(resp) => return Ok(Some(resp)),
+            };
+
+            ensure_storage_space(controllers.retention(), &controllers.ws_sender()).await;
+            match capture_group(&controllers, request).await {
+                Ok(result) => {
+                    let all_ok = result.members.iter().all(|m| m.ok);
+                    let mut body = serde_json::to_value(&result).unwrap_or_default();
+                    body["success"] = serde_json::json!(all_ok);
+                    Some(make_api_response(body))
+                }
+                Err(e) => Some(make_api_response_with_status(e.status_code(), serde_json::json!({
                     "success": false,
-                    "error": format!("Failed to send capture command: {}", e)
-                })))
+                    "error": e.to_string()
+                }))),
             }
         }
 
+        // Burst: N frames at a fixed interval on one camera
+        (&Method::POST, "/api/capture/burst") => {
+            let controller_state = controller_for_camera!();
+            let request = match read_json_body::<BurstRequest>(req, "burst").await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(Some(missing_body_response("burst"))),
+                Err(resp) => return Ok(Some(resp)),
+            };
+
+            ensure_storage_space(controllers.retention(), &controllers.ws_sender()).await;
+            let ws_tx = controllers.ws_sender();
+            Some(sequence_response(capture_burst(&controller_state, &ws_tx, request).await))
+        }
+
+        // Exposure bracket: one frame per shutterspeed / exposurecompensation value
+        (&Method::POST, "/api/capture/bracket") => {
+            let controller_state = controller_for_camera!();
+            let request = match read_json_body::<BracketRequest>(req, "bracket").await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(Some(missing_body_response("bracket"))),
+                Err(resp) => return Ok(Some(resp)),
+            };
+
+            ensure_storage_space(controllers.retention(), &controllers.ws_sender()).await;
+            let ws_tx = controllers.ws_sender();
+            Some(sequence_response(capture_bracket(&controller_state, &ws_tx, request).await))
+        }
+
         // Controller switch camera
+        // `index` is the camera the controller should switch to; `camera` picks the
+        // controller (older clients pass only `camera`, meaning the index on controller 0).
+        // The supervisor keeps every controller on its camera, so this is for debugging.
         (&Method::POST, path) if path.starts_with("/api/controller/switch") => {
-            let camera_index = parse_query_param(&uri_str, "camera").unwrap_or(0);
-            let cmd = format!("SWITCH_CAMERA {}", camera_index);
-            match crate::controller::send_command(&cmd).await {
-                Ok(_) => Some(make_api_response(serde_json::json!({
+            let legacy_index = parse_query_string(&uri_str, "camera").map(|camera| camera.parse::<u32>());
+            let (controller_state, camera_index) = match (parse_query_param(&uri_str, "index"), legacy_index) {
+                (Some(index), _) => (controller_for_camera!(), index),
+                (None, Some(Ok(index))) => match resolve_controller(&controllers, PRIMARY_CAMERA_ID).await {
+                    Ok(state) => (state, index),
+                    Err(resp) => return Ok(Some(resp)),
+                },
+                (None, Some(Err(_)) | None) => {
+                    return Ok(Some(make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+                        "success": false,
+                        "error": "Pass the camera index to switch to as `index` (camera ids aren't indexes)"
+                    }))));
+                }
+            };
+            match controller_state.request(ControllerCommand::SwitchCamera { camera_index }).await {
+                Ok(_) => {
+                    controller_state.set_camera_index(camera_index);
+                    Some(make_api_response(serde_json::json!({
                     "success": true,
-                    "message": format!("Switched to camera {}", camera_index),
-                    "camera_index": camera_index
-                }))),
-                Err(e) => Some(make_api_response(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to switch camera: {}", e)
-                })))
+                        "message": format!("Switched to camera {}", camera_index),
+                        "camera_index": camera_index
+                    })))
+                }
+                Err(e) => Some(controller_error_response(&e)),
             }
         }
 
         // Controller disconnect
         (&Method::POST, "/api/controller/disconnect") => {
-            match crate::controller::send_command("DISCONNECT").await {
+            let controller_state = controller_for_camera!();
+            match controller_state.request(ControllerCommand::Disconnect).await {
                 Ok(_) => Some(make_api_response(serde_json::json!({
                     "success": true,
                     "message": "Polling stopped"
                 }))),
-                Err(e) => Some(make_api_response(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to stop polling: {}", e)
-                })))
+                Err(e) => Some(controller_error_response(&e)),
             }
         }
 
         // Pause polling
         (&Method::POST, "/api/controller/pause-polling") => {
-            match crate::controller::send_command("PAUSE_POLLING").await {
+            let controller_state = controller_for_camera!();
+            match controller_state.request(ControllerCommand::PausePolling).await {
                 Ok(_) => Some(make_api_response(serde_json::json!({
                     "success": true,
                     "message": "Polling paused"
                 }))),
-                Err(e) => Some(make_api_response(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to pause polling: {}", e)
-                })))
+                Err(e) => Some(controller_error_response(&e)),
             }
         }
 
         // Resume polling
         (&Method::POST, "/api/controller/resume-polling") => {
-            match crate::controller::send_command("RESUME_POLLING").await {
+            let controller_state = controller_for_camera!();
+            match controller_state.request(ControllerCommand::ResumePolling).await {
                 Ok(_) => Some(make_api_response(serde_json::json!({
                     "success": true,
                     "message": "Polling resumed"
                 }))),
-                Err(e) => Some(make_api_response(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to resume polling: {}", e)
-                })))
+                Err(e) => Some(controller_error_response(&e)),
             }
         }
 
         // Live view status
         (&Method::GET, "/api/liveview/status") => {
+            let controller_state = controller_for_camera!();
             let active = *controller_state.ptp_streaming_active.lock().await;
             Some(make_api_response(serde_json::json!({
                 "success": true,
-                "active": active
+                "active": active,
+                "camera_id": camera_id
             })))
         }
 
         // PTP stream start
         (&Method::POST, "/api/liveview/ptp-stream/start") => {
-            match crate::controller::send_command("LIVEVIEW_STREAM_START").await {
+            let controller_state = controller_for_camera!();
+            match controller_state.request(ControllerCommand::LiveviewStreamStart).await {
                 Ok(_) => {
                     *controller_state.ptp_streaming_active.lock().await = true;
                     Some(make_api_response(serde_json::json!({
@@ -350,16 +532,14 @@ pub async fn handle_request(
                         "message": "PTP streaming started - connect to GET /api/liveview/ptp-stream to receive frames"
                     })))
                 }
-                Err(e) => Some(make_api_response(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to start PTP streaming: {}", e)
-                })))
+                Err(e) => Some(controller_error_response(&e)),
             }
         }
 
         // PTP stream stop
         (&Method::POST, "/api/liveview/ptp-stream/stop") => {
-            match crate::controller::send_command("LIVEVIEW_STREAM_STOP").await {
+            let controller_state = controller_for_camera!();
+            match controller_state.request(ControllerCommand::LiveviewStreamStop).await {
                 Ok(_) => {
                     *controller_state.ptp_streaming_active.lock().await = false;
                     Some(make_api_response(serde_json::json!({
@@ -367,10 +547,7 @@ pub async fn handle_request(
                         "message": "PTP streaming stopped"
                     })))
                 }
-                Err(e) => Some(make_api_response(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to stop PTP streaming: {}", e)
-                })))
+                Err(e) => Some(controller_error_response(&e)),
             }
         }
 
@@ -378,7 +555,8 @@ pub async fn handle_request(
         (&Method::GET, "/api/liveview/ptp-stream") => {
             use tokio::fs::File;
 
-            let stream_file = match File::open("/tmp/camera_stream").await {
+            let controller_state = controller_for_camera!();
+            let stream_file = match File::open(&controller_state.paths.stream_pipe).await {
                 Ok(f) => f,
                 Err(e) => {
                     return Ok(Some(make_api_response(serde_json::json!({
@@ -389,7 +567,13 @@ pub async fn handle_request(
             };
 
             let byte_stream = ReaderStream::with_capacity(stream_file, 256 * 1024);
-            let body = StreamBody::new(byte_stream.map(|r| r.map(Frame::data)));
+            let mut frames = crate::metrics::FrameCounter::new(&camera_id);
+            let body = StreamBody::new(byte_stream.map(move |r| {
+                if let Ok(chunk) = &r {
+                    frames.feed(chunk);
+                }
+                r.map(Frame::data)
+            }));
             let boxed_body = BodyExt::boxed(body);
 
             let response = Response::builder()
@@ -412,40 +596,41 @@ pub async fn handle_request(
 
         // Debug info
         (&Method::GET, "/api/debug") => {
-            let camera_id = parse_query_param(&uri_str, "camera");
-            let debug = state.debug_camera(camera_id);
+            let camera_index = controllers.get(&camera_id).await.map(|c| c.camera_index());
+            let debug = state.debug_camera(camera_index);
             Some(make_api_response(debug))
         }
 
         // Camera config (GET)
         (&Method::GET, "/api/camera/config") => {
-            let config = crate::controller::get_camera_config().await;
-            Some(make_api_response(config))
+            let controller_state = controller_for_camera!();
+            match crate::controller::get_camera_config(&controller_state).await {
+                Ok(config) => Some(make_api_response(config)),
+                Err(e) => Some(controller_error_response(&e)),
+            }
         }
 
         // Camera status
         (&Method::GET, "/api/camera/status") => {
-            let camera_id = parse_query_param(&uri_str, "camera");
-            let camera_id_str = camera_id.unwrap_or(0).to_string();
-
-            let cached = controller_state.cached_status.lock().await;
-            let status = cached.get(&camera_id_str)
+            let controller_state = controller_for_camera!();
+            let status = controller_state.cached_status.lock().await
+                .as_ref()
                 .map(|(v, _)| v.clone())
                 .unwrap_or_else(|| serde_json::json!({"error": "No cached status available"}));
-            drop(cached);
 
             Some(make_api_response(status))
         }
 
         // Widgets
         (&Method::GET, "/api/widgets") => {
-            let camera_id = parse_query_param(&uri_str, "camera");
-            let widgets = state.list_widgets(camera_id);
+            let camera_index = controllers.get(&camera_id).await.map(|c| c.camera_index());
+            let widgets = state.list_widgets(camera_index);
             Some(make_api_response(widgets))
         }
 
         // Camera config (POST)
         (&Method::POST, "/api/camera/config") => {
+            let controller_state = controller_for_camera!();
             let body_bytes = match BodyExt::collect(req.into_body()).await {
                 Ok(collected) => collected.to_bytes(),
                 Err(e) => {
@@ -457,64 +642,180 @@ pub async fn handle_request(
             };
 
             let body_str = String::from_utf8_lossy(&body_bytes);
-            let result: serde_json::Value;
-
-            if let Ok(config) = serde_json::from_str::<serde_json::Value>(&body_str) {
-                let setting = config.get("setting").and_then(|v| v.as_str());
-                let value_json = config.get("value");
-
-                let value_string;
-                let value = if let Some(v) = value_json.and_then(|v| v.as_str()) {
-                    Some(v)
-                } else if let Some(v) = value_json.and_then(|v| v.as_i64()) {
-                    value_string = v.to_string();
-                    Some(value_string.as_str())
-                } else if let Some(v) = value_json.and_then(|v| v.as_u64()) {
-                    value_string = v.to_string();
-                    Some(value_string.as_str())
-                } else {
-                    None
-                };
-
-                if let (Some(s), Some(v)) = (setting, value) {
-                    result = crate::controller::set_camera_config(s, v).await;
-                } else {
-                    result = serde_json::json!({
-                        "error": "JSON must contain 'setting' and 'value' fields. Example: {\"setting\":\"iso\",\"value\":\"800\"}"
-                    });
-                }
-            } else {
-                if let Some(eq_pos) = body_str.find('=') {
-                    let setting = &body_str[..eq_pos];
-                    let value = &body_str[eq_pos + 1..];
+            let parsed: Result<(String, String), &str> =
+                if let Ok(config) = serde_json::from_str::<serde_json::Value>(&body_str) {
+                    let setting = config.get("setting").and_then(|v| v.as_str());
+                    let value = match config.get("value") {
+                        Some(serde_json::Value::String(v)) => Some(v.clone()),
+                        Some(serde_json::Value::Number(v)) if v.is_i64() || v.is_u64() => Some(v.to_string()),
+                        _ => None,
+                    };
+
+                    match (setting, value) {
+                        (Some(s), Some(v)) => Ok((s.to_string(), v)),
+                        _ => Err("JSON must contain 'setting' and 'value' fields. Example: {\"setting\":\"iso\",\"value\":\"800\"}"),
+                    }
+                } else if let Some((setting, value)) = body_str.split_once('=') {
                     if !setting.is_empty() && !value.is_empty() {
-                        result = crate::controller::set_camera_config(setting, value).await;
+                        Ok((setting.to_string(), value.to_string()))
                     } else {
-                        result = serde_json::json!({
-                            "error": "Invalid format. Use JSON or 'setting=value' format"
-                        });
+                        Err("Invalid format. Use JSON or 'setting=value' format")
                     }
                 } else {
-                    result = serde_json::json!({
-                        "error": "Invalid format. Use JSON like {\"setting\":\"iso\",\"value\":\"800\"} or form data like 'iso=800'"
-                    });
+                    Err("Invalid format. Use JSON like {\"setting\":\"iso\",\"value\":\"800\"} or form data like 'iso=800'")
+                };
+
+            match parsed {
+                Ok((setting, value)) => {
+                    let _config_guard = controller_state.config_lock.lock().await;
+                    match crate::controller::set_camera_config(&controller_state, &setting, &value).await {
+                        Ok(result) => Some(make_api_response(result)),
+                        Err(e) => Some(controller_error_response(&e)),
+                    }
                 }
+                Err(message) => Some(make_api_response_with_status(
+                    StatusCode::BAD_REQUEST,
+                    serde_json::json!({ "error": message }),
+                )),
             }
+        }
+
+        // Camera config batch (presets): several settings, per-setting report
+        (&Method::POST, "/api/camera/config/batch") => {
+            let controller_state = controller_for_camera!();
+            let request = match read_json_body::<ConfigBatchRequest>(req, "config batch").await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(Some(missing_body_response("config batch"))),
+                Err(resp) => return Ok(Some(resp)),
+            };
+            if let Err(message) = validate_batch(&request) {
+                return Ok(Some(make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+                    "success": false,
+                    "error": message
+                }))));
+            }
+
+            match apply_config_batch(&controller_state, request).await {
+                Ok(report) => Some(make_api_response(report)),
+                Err(e) => Some(controller_error_response(&e)),
+            }
+        }
+
+        // Camera health alerts
+        (&Method::GET, "/api/alerts") => {
+            let camera_filter = parse_query_string(&uri_str, "camera");
+            let (open, recent) = controllers.alerts().list(camera_filter.as_deref());
+            Some(make_api_response(serde_json::json!({
+                "success": true,
+                "open": open,
+                "recent": recent
+            })))
+        }
 
-            Some(make_api_response(result))
+        (&Method::POST, path) if path.starts_with("/api/alerts/") && path.ends_with("/clear") => {
+            let id = path.strip_prefix("/api/alerts/")
+                .and_then(|p| p.strip_suffix("/clear"))
+                .and_then(|id| id.parse::<u64>().ok());
+            match id.map(|id| controllers.alerts().clear(id)) {
+                Some(Ok(alert)) => Some(make_api_response(serde_json::json!({
+                    "success": true,
+                    "alert": alert
+                }))),
+                Some(Err(message)) => Some(make_api_response_with_status(StatusCode::NOT_FOUND, serde_json::json!({
+                    "success": false,
+                    "error": message
+                }))),
+                None => Some(make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+                    "success": false,
+                    "error": "Expected /api/alerts/{id}/clear"
+                }))),
+            }
+        }
+
+        // Canonical settings (same schema for every brand)
+        (&Method::GET, "/api/camera/settings") => {
+            let controller_state = controller_for_camera!();
+            match read_settings(&controller_state).await {
+                Ok(settings) => Some(make_api_response(settings)),
+                Err(e) => Some(controller_error_response(&e)),
+            }
+        }
+
+        (&Method::POST, "/api/camera/settings") => {
+            let controller_state = controller_for_camera!();
+            let request = match read_json_body::<SettingsRequest>(req, "settings").await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(Some(missing_body_response("settings"))),
+                Err(resp) => return Ok(Some(resp)),
+            };
+
+            match apply_settings(&controller_state, request).await {
+                Ok(report) => Some(make_api_response(report)),
+                Err(SettingsError::Invalid(errors)) => Some(make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+                    "success": false,
+                    "error": errors.join("; "),
+                    "errors": errors
+                }))),
+                Err(SettingsError::Controller(e)) => Some(controller_error_response(&e)),
+            }
+        }
+
+        // Focus and live view zoom (routed through the controller, works during live view)
+        (&Method::GET, "/api/camera/focus") => {
+            let controller_state = controller_for_camera!();
+            match focus_capabilities(&controller_state).await {
+                Ok(capabilities) => Some(make_api_response(capabilities)),
+                Err(e) => Some(controller_error_response(&e)),
+            }
+        }
+
+        (&Method::POST, "/api/camera/focus") => {
+            let controller_state = controller_for_camera!();
+            let request = match read_json_body::<FocusRequest>(req, "focus").await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(Some(missing_body_response("focus"))),
+                Err(resp) => return Ok(Some(resp)),
+            };
+
+            match focus(&controller_state, request).await {
+                Ok(result) => Some(make_api_response(result)),
+                Err(e) => Some(controller_error_response(&e)),
+            }
+        }
+
+        // Prometheus metrics
+        (&Method::GET, "/metrics") => {
+            Some(Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", crate::metrics::CONTENT_TYPE)
+                .body(full_body(crate::metrics::get().render()))
+                .unwrap())
         }
 
         // Status endpoint
         (&Method::GET, "/api/status") => {
+            let mut cameras = Vec::new();
+            for controller_state in controllers.all().await {
+                let info = controller_state.camera_info.lock().await.clone();
+                cameras.push(serde_json::json!({
+                    "camera_id": controller_state.camera_id,
+                    "controller_active": *controller_state.controller_active.lock().await,
+                    "streaming": *controller_state.ptp_streaming_active.lock().await,
+                    "model": info.map(|i| i.model),
+                }));
+            }
             Some(make_api_response(serde_json::json!({
                 "daemon_running": true,
                 "libgphoto2_available": state.check_libgphoto2(),
                 "active_sessions": state.sessions.len(),
+                "simulated": state.simulation.is_some(),
+                "cameras": cameras,
+                "config": crate::config::get().status_json(),
             })))
         }
 
-        // Get photo
-        (&Method::GET, path) if path.starts_with("/api/photo/") => {
+        // Get photo (HEAD for size and ETag before resuming)
+        (&Method::GET | &Method::HEAD, path) if path.starts_with("/api/photo/") => {
             let filename = path.strip_prefix("/api/photo/")
                 .unwrap_or_default()
                 .trim_start_matches('/');
@@ -526,9 +827,9 @@ pub async fn handle_request(
                     .body(full_body(r#"{"error":"Invalid filename"}"#))
                     .unwrap())
             } else {
-                let file_path = Path::new("/tmp").join(filename);
+                let file_path = ControllerPaths::for_camera(&camera_id).photo_dir.join(filename);
                 match tokio::fs::File::open(&file_path).await {
-                    Ok(file) => {
+                    Ok(mut file) => {
                         let content_type = if filename.ends_with(".jpg") || filename.ends_with(".jpeg") {
                             "image/jpeg"
                         } else if filename.ends_with(".png") {
@@ -537,22 +838,86 @@ pub async fn handle_request(
                             "application/octet-stream"
                         };
 
-                        let file_size = file.metadata().await.ok().map(|m| m.len());
+                        let metadata = match file.metadata().await {
+                            Ok(metadata) => metadata,
+                            Err(e) => return Ok(Some(make_api_response_with_status(StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({
+                                "error": format!("Failed to read {}: {}", filename, e)
+                            })))),
+                        };
+                        let file_size = metadata.len();
+                        let etag = match controllers.digests().sha256(&file_path, &metadata).await {
+                            Ok(sha256) => Some(photos::etag(&sha256)),
+                            Err(e) => {
+                                eprintln!("Failed to hash {}: {}", file_path.display(), e);
+                                None
+                            }
+                        };
 
-                        let stream = ReaderStream::new(file);
-                        let body: ResponseBody = BodyExt::boxed(StreamBody::new(stream.map(|result| {
-                            result.map(Frame::data)
-                        })));
+                        let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+                        let not_modified = matches!((header("if-none-match"), &etag), (Some(tags), Some(etag)) if photos::etag_matches(tags, etag));
+                        // A stale If-Range means the file changed: send all of it
+                        let range_applies = match (header("if-range"), &etag) {
+                            (None, _) => true,
+                            (Some(tag), Some(etag)) => tag.trim() == etag,
+                            (Some(_), None) => false,
+                        };
+                        let range = match header("range") {
+                            Some(range) if range_applies && !not_modified => photos::parse_range(range, file_size),
+                            _ => Ok(None),
+                        };
 
                         let mut builder = Response::builder()
-                            .status(StatusCode::OK)
                             .header("content-type", content_type)
-                            .header("access-control-allow-origin", "*");
+                            .header("accept-ranges", "bytes")
+                            .header("access-control-allow-origin", "*")
+                            .header("access-control-expose-headers", "ETag, Content-Range, Accept-Ranges, Content-Length");
+                        if let Some(etag) = &etag {
+                            builder = builder.header("etag", etag);
+                        }
+
+                        if not_modified {
+                            return Ok(Some(builder.status(StatusCode::NOT_MODIFIED).body(full_body("")).unwrap()));
+                        }
+
+                        let range = match range {
+                            Ok(range) => range,
+                            Err(()) => {
+                                return Ok(Some(builder
+                                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                                    .header("content-range", format!("bytes */{}", file_size))
+                                    .body(full_body(""))
+                                    .unwrap()));
+                            }
+                        };
 
-                        if let Some(size) = file_size {
-                            builder = builder.header("content-length", size);
+                        // Only a transfer that reaches the end of the file counts as a download
+                        if method == Method::GET && range.is_none_or(|r| r.end + 1 == file_size) {
+                            controllers.retention().mark_downloaded(&file_path, &camera_id);
                         }
 
+                        let body: ResponseBody = match range {
+                            Some(range) => {
+                                if let Err(e) = file.seek(std::io::SeekFrom::Start(range.start)).await {
+                                    return Ok(Some(make_api_response_with_status(StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({
+                                        "error": format!("Failed to read {}: {}", filename, e)
+                                    }))));
+                                }
+                                builder = builder
+                                    .status(StatusCode::PARTIAL_CONTENT)
+                                    .header("content-range", format!("bytes {}-{}/{}", range.start, range.end, file_size))
+                                    .header("content-length", range.len());
+                                let stream = ReaderStream::new(file.take(range.len()));
+                                BodyExt::boxed(StreamBody::new(stream.map(|result| result.map(Frame::data))))
+                            }
+                            None => {
+                                builder = builder
+                                    .status(StatusCode::OK)
+                                    .header("content-length", file_size);
+                                let stream = ReaderStream::new(file);
+                                BodyExt::boxed(StreamBody::new(stream.map(|result| result.map(Frame::data))))
+                            }
+                        };
+
                         Some(builder.body(body).unwrap())
                     }
                     Err(e) => {
@@ -567,6 +932,69 @@ pub async fn handle_request(
             }
         }
 
+        // Acknowledge a photo: the app has it stored, so cleanup may evict it
+        (&Method::POST, path) if path.starts_with("/api/photo/") && path.ends_with("/ack") => {
+            let filename = path.strip_prefix("/api/photo/")
+                .and_then(|p| p.strip_suffix("/ack"))
+                .unwrap_or_default()
+                .trim_start_matches('/');
+
+            if filename.is_empty() || !filename.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '_' || c == '-') {
+                Some(make_api_response_with_status(StatusCode::BAD_REQUEST, serde_json::json!({
+                    "success": false,
+                    "error": "Invalid filename"
+                })))
+            } else {
+                let file_path = ControllerPaths::for_camera(&camera_id).photo_dir.join(filename);
+                match controllers.retention().acknowledge(&file_path, &camera_id) {
+                    Ok(photo) => {
+                        println!("Acknowledged photo: {}", file_path.display());
+                        Some(make_api_response(serde_json::json!({
+                            "success": true,
+                            "filename": filename,
+                            "camera_id": camera_id,
+                            "photo": photo
+                        })))
+                    }
+                    Err(e) => Some(make_api_response_with_status(StatusCode::NOT_FOUND, serde_json::json!({
+                        "success": false,
+                        "error": e
+                    }))),
+                }
+            }
+        }
+
+        // Inventory of stored photos (all cameras unless ?camera= is given)
+        (&Method::GET, "/api/photos") => {
+            let camera_filter = parse_query_string(&uri_str, "camera");
+            match list_photos(camera_filter.as_deref(), controllers.retention(), controllers.digests()).await {
+                Ok(photos) => Some(make_api_response(serde_json::json!({
+                    "success": true,
+                    "now_ms": crate::group_capture::unix_millis() as u64,
+                    "count": photos.len(),
+                    "total_bytes": photos.iter().map(|p| p.size).sum::<u64>(),
+                    "photos": photos
+                }))),
+                Err(e) => Some(make_api_response_with_status(StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({
+                    "success": false,
+                    "error": e
+                }))),
+            }
+        }
+
+        // Free space and retained photos by state
+        (&Method::GET, "/api/storage") => {
+            let retention = controllers.retention();
+            if let Err(e) = sync_retention(retention) {
+                eprintln!("Failed to scan photos: {}", e);
+            }
+            let available = get_available_space(&crate::config::get().paths.photo_dir.to_string_lossy()).ok();
+            Some(make_api_response(serde_json::json!({
+                "available_mb": available.map(|a| a / (1024 * 1024)),
+                "photos": retention.summary()
+            })))
+        }
+
         // Delete photo
         (&Method::DELETE, path) if path.starts_with("/api/photo/") => {
             let filename = path.strip_prefix("/api/photo/")
@@ -579,10 +1007,11 @@ pub async fn handle_request(
                     "error": "Invalid filename"
                 })))
             } else {
-                let file_path = Path::new("/tmp").join(filename);
+                let file_path = ControllerPaths::for_camera(&camera_id).photo_dir.join(filename);
                 match std::fs::remove_file(&file_path) {
                     Ok(_) => {
                         println!("Deleted file: {}", file_path.display());
+                        controllers.retention().forget(&file_path);
                         Some(make_api_response(serde_json::json!({
                             "success": true,
                             "message": format!("Deleted {}", filename)
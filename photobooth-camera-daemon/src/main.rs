@@ -3,11 +3,27 @@
 //! HTTP server for camera operations using libgphoto2
 //! Runs on minimal Linux, exposed via HTTP API
 
+mod config;
 mod types;
 mod storage;
+mod retention;
+mod alerts;
+mod metrics;
+mod photos;
+mod raw;
 mod camera;
 mod controller;
+mod protocol;
+mod group_capture;
+mod config_batch;
+mod settings;
+mod focus;
+mod sequence_capture;
+mod supervisor;
+mod hotplug;
+mod simulator;
 mod http;
+mod auth;
 mod websocket;
 
 use hyper::Response;
@@ -19,15 +35,53 @@ use std::net::SocketAddr;
 use tokio_tungstenite::WebSocketStream;
 
 use camera::CameraState;
-use controller::{ControllerState, start_controller_process};
+use controller::ControllerPaths;
+use storage::ensure_storage_space;
+use supervisor::{Controllers, PRIMARY_CAMERA_ID};
 use http::{handle_request, full_body, compute_websocket_accept};
-use websocket::{SharedState, handle_websocket};
+use websocket::{SharedState, ResumeFrom, handle_websocket};
+use auth::{AuthConfig, request_origin};
+use simulator::Simulation;
+use retention::RetentionIndex;
+use hotplug::UsbWatch;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let state = CameraState::new();
-    let controller_state = ControllerState::new();
+    // Config file (--config, PHOTOBOOTH_CONFIG or /etc/photobooth/daemon.toml) plus PHOTOBOOTH_* overrides
+    let config = match config::DaemonConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    match &config.source {
+        Some(path) => println!("Config: {}", path.display()),
+        None => println!("Config: built-in defaults (no config file)"),
+    }
+    if !config.env_overrides.is_empty() {
+        println!("Config overridden by environment: {}", config.env_overrides.join(", "));
+    }
+    println!("Photo dir: {}, runtime dir: {}", config.paths.photo_dir.display(), config.paths.runtime_dir.display());
+    config::init(config);
+    let config = config::get();
+
+    // Simulated cameras instead of gphoto2 (PHOTOBOOTH_SIMULATE=canon or --simulate canon)
+    let simulation = Simulation::from_args_or_config()?;
+    if let Some(simulation) = &simulation {
+        println!("SIMULATION MODE: no hardware is used");
+        for camera in simulation.describe() {
+            println!("  Simulated camera {}", camera);
+        }
+    }
+
+    let usb_watch = UsbWatch::new();
+    let state = CameraState::new(simulation.clone(), usb_watch.clone());
     let ws_state = SharedState::new();
+    ws_state.start_sequencer();
+    let retention = RetentionIndex::load();
+    let simulated = simulation.is_some();
+    let controllers = Controllers::new(ws_state.ws_tx.clone(), simulation, retention.clone());
 
     // Check if libgphoto2 is available at startup
     println!("Checking libgphoto2 availability...");
@@ -38,51 +92,81 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("libgphoto2 is available!");
     }
 
-    // Clean up old pipes before starting the controller
-    let _ = std::fs::remove_file("/tmp/camera_cmd");
-    let _ = std::fs::remove_file("/tmp/camera_status");
-
-    // Start the gphoto2-controller process with respawn logic
-    println!("Starting gphoto2-controller with auto-restart...");
-    let controller_state_for_controller = controller_state.clone();
-    let ws_tx = ws_state.ws_tx.clone();
+    // Storage monitor (every storage.monitor_interval_secs, covers every camera's photo dir)
+    // Only acknowledged photos are evicted; storage_low is broadcast if that isn't enough
+    let storage_ws_tx = ws_state.ws_tx.clone();
     tokio::spawn(async move {
-        start_controller_process(controller_state_for_controller, ws_tx).await;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(config.storage.monitor_interval_secs)).await;
+            ensure_storage_space(&retention, &storage_ws_tx).await;
+        }
     });
 
-    // Wait for controller to create the status pipe (typically <500ms)
+    // Start one gphoto2-controller per connected camera, each with auto-restart
+    println!("Starting camera supervisor...");
+    tokio::spawn(controllers.clone().run());
+
+    // USB hotplug: start/stop controllers as soon as cameras are plugged in or out
+    if config.hotplug.enabled && !simulated {
+        tokio::spawn(hotplug::run(usb_watch.clone(), controllers.clone()));
+    }
+
+    // Wait for the primary controller to create its status pipe (typically <500ms)
     {
+        let status_pipe = ControllerPaths::for_camera(PRIMARY_CAMERA_ID).status_pipe;
         let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
         while std::time::Instant::now() < deadline {
-            if std::path::Path::new("/tmp/camera_status").exists() {
+            if status_pipe.exists() {
                 break;
             }
             tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
     }
 
-    // Get port from environment variable or use default
-    let port = std::env::var("PHOTOBOOTH_PORT")
-        .ok()
-        .and_then(|p| p.parse::<u16>().ok())
-        .unwrap_or(58321);
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    // Bind address (e.g. 127.0.0.1 behind NAT port forwarding); all interfaces by default
+    let auth = AuthConfig::from_config(&config.server);
+    let addr = SocketAddr::new(config.bind_ip(), config.server.port);
     println!("Photobooth Camera Daemon v1.4");
     println!("Listening on http://{}", addr);
+    if auth.token_required() {
+        println!("Authentication: token required (Authorization: Bearer, X-Photobooth-Token or ?token=)");
+    } else {
+        println!("Authentication: disabled (set PHOTOBOOTH_AUTH_TOKEN to require a token)");
+    }
+    if !auth.allowed_origins().is_empty() {
+        println!("Allowed origins: {}", auth.allowed_origins().join(", "));
+    }
     println!();
     println!("API Endpoints:");
     println!("  GET    /api/health           - Health check");
     println!("  GET    /api/cameras          - List cameras");
     println!("  POST   /api/capture         - Trigger capture");
+    println!("  POST   /api/capture/group   - Synchronized capture on several cameras");
+    println!("  POST   /api/capture/burst   - Burst of N frames at a fixed interval");
+    println!("  POST   /api/capture/bracket - Exposure bracket (shutterspeed / exposurecompensation)");
+    println!();
+    println!("  Camera endpoints take ?camera=<id> (default 0); WS events carry camera_id");
     println!("  GET    /api/debug           - Camera debug info");
-    println!("  GET    /api/status          - Daemon status");
+    println!("  GET    /api/status          - Daemon status and effective config");
+    println!("  GET    /metrics             - Prometheus metrics (captures, latencies, restarts, WS clients, free space)");
     println!("  GET    /api/camera/config   - Camera settings (ISO, aperture, etc)");
     println!("  POST   /api/camera/config   - Set camera setting (JSON or form data)");
+    println!("  POST   /api/camera/config/batch - Apply several settings (presets), per-setting report");
+    println!("  GET    /api/camera/settings - Canonical settings (ISO, aperture, shutter, EV, WB, drive, battery)");
+    println!("  POST   /api/camera/settings - Apply canonical settings, mapped to this camera's widgets");
+    println!("  GET    /api/camera/focus    - Focus actions this camera supports");
+    println!("  POST   /api/camera/focus    - Autofocus, focus steps near/far, AF point, live view zoom");
     println!("  GET    /api/camera/status   - Quick status check (battery, ISO, etc)");
-    println!("  GET    /api/photo/{{filename}} - Download captured image");
+    println!("  GET    /api/photos          - Stored photos with size, capture time, camera id, sha256");
+    println!("  GET    /api/photo/{{filename}} - Download captured image (Range, ETag = sha256)");
+    println!("  POST   /api/photo/{{filename}}/ack - Confirm image is stored; only then may cleanup evict it");
     println!("  DELETE /api/photo/{{filename}} - Delete image from VM");
-    println!("  WS     /ws                  - WebSocket for photo events");
+    println!("  GET    /api/storage         - Free space and photos by retention state");
+    println!("  GET    /api/alerts          - Open and recent camera alerts (battery, card, failures, reconnects)");
+    println!("  POST   /api/alerts/{{id}}/clear - Dismiss an alert until it escalates");
+    println!("  WS     /ws                  - WebSocket for photo events (events carry seq)");
+    println!("                                 usb_device_added / usb_device_removed carry vendor_id, product_id, port");
+    println!("  WS     /ws?since=<seq>&epoch=<epoch> - Resume: replay missed events, event_gap if truncated");
     println!();
     println!("Live View Options:");
     println!("  HDMI Capture: Uses HDMI-to-USB adapter (no camera lock, low latency)");
@@ -102,18 +186,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Connection from {}", remote_addr);
 
         let state = state.clone();
-        let controller_state = controller_state.clone();
+        let controllers = controllers.clone();
         let ws_state = ws_state.clone();
+        let auth = auth.clone();
 
         tokio::task::spawn(async move {
             let socket_wrapper = TokioIo::new(socket);
 
             let svc = service_fn(move |mut req| {
                 let state = state.clone();
-                let controller_state = controller_state.clone();
+                let controllers = controllers.clone();
                 let ws_state = ws_state.clone();
+                let auth = auth.clone();
 
                 async move {
+                    // Origin and token checks apply to every route, including the WS upgrade
+                    if let Err(e) = auth.authorize(&req) {
+                        return Ok(e.into_response());
+                    }
+                    let origin = request_origin(&req);
+
                     // Check if this is a WebSocket upgrade request
                     if req.uri().path() == "/ws" &&
                        req.headers().get("upgrade")
@@ -126,6 +218,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             .unwrap_or("")
                             .to_string();
                         let accept_key = compute_websocket_accept(&ws_key);
+                        let resume = ResumeFrom::from_query(req.uri().query());
 
                         // Schedule the WebSocket handler to run after the 101 response is sent
                         let upgrade_future = hyper::upgrade::on(&mut req);
@@ -137,7 +230,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         tokio_tungstenite::tungstenite::protocol::Role::Server,
                                         None,
                                     ).await;
-                                    handle_websocket(ws_stream, ws_state).await;
+                                    handle_websocket(ws_stream, ws_state, resume).await;
                                 }
                                 Err(e) => {
                                     eprintln!("WebSocket upgrade error: {}", e);
@@ -155,8 +248,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             .unwrap())
                     } else {
                         // Regular HTTP request
-                        match handle_request(state, controller_state, req).await {
-                            Ok(Some(resp)) => Ok(resp),
+                        match handle_request(state, controllers, req).await {
+                            Ok(Some(mut resp)) => {
+                                auth.apply_cors(origin.as_deref(), &mut resp);
+                                Ok(resp)
+                            }
                             Ok(None) => {
                                 // Should not happen for non-WS requests
                                 Ok(Response::builder()
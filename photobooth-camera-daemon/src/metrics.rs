@@ -0,0 +1,315 @@
+//! Prometheus metrics
+//!
+//! `GET /metrics` exports counters, gauges and histograms in the Prometheus text
+//! format (version 0.0.4), for a Prometheus instance on the booth laptop to scrape.
+//! Everything is kept in one process-wide registry (`metrics::get()`) that the
+//! controllers, the WebSocket layer and the live view stream update as they go;
+//! free space is read when scraped.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Content-Type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Trigger to photo_downloaded: a few seconds normally, long exposures and slow cards more
+const CAPTURE_BUCKETS: &[f64] = &[0.5, 1.0, 2.0, 3.0, 5.0, 8.0, 13.0, 20.0, 30.0, 60.0];
+
+/// Controller round trips: status queries are milliseconds, CONFIG on a big body up to its timeout
+const REQUEST_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 45.0];
+
+/// Multipart boundary the controllers put before every live view frame
+const FRAME_BOUNDARY: &[u8] = b"--FRAME";
+
+static METRICS: Metrics = Metrics::new();
+
+/// The daemon's metrics registry
+pub fn get() -> &'static Metrics {
+    &METRICS
+}
+
+/// Counter with one label
+struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    label: &'static str,
+    values: Mutex<BTreeMap<String, u64>>,
+}
+
+impl CounterVec {
+    const fn new(name: &'static str, help: &'static str, label: &'static str) -> Self {
+        Self { name, help, label, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    fn add(&self, label_value: &str, n: u64) {
+        *self.values.lock().unwrap().entry(label_value.to_string()).or_default() += n;
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        for (value, count) in self.values.lock().unwrap().iter() {
+            let _ = writeln!(out, "{}{{{}}} {}", self.name, label(self.label, value), count);
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistogramData {
+    /// Observations per bucket (not cumulative; summed when rendered)
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Histogram with one label, in seconds
+struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    label: &'static str,
+    bounds: &'static [f64],
+    values: Mutex<BTreeMap<String, HistogramData>>,
+}
+
+impl HistogramVec {
+    const fn new(name: &'static str, help: &'static str, label: &'static str, bounds: &'static [f64]) -> Self {
+        Self { name, help, label, bounds, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    fn observe(&self, label_value: &str, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let mut values = self.values.lock().unwrap();
+        let data = values.entry(label_value.to_string()).or_default();
+        if data.buckets.is_empty() {
+            data.buckets = vec![0; self.bounds.len()];
+        }
+        if let Some(bucket) = self.bounds.iter().position(|bound| seconds <= *bound) {
+            data.buckets[bucket] += 1;
+        }
+        data.sum += seconds;
+        data.count += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        for (value, data) in self.values.lock().unwrap().iter() {
+            let labels = label(self.label, value);
+            let mut cumulative = 0;
+            for (bound, count) in self.bounds.iter().zip(&data.buckets) {
+                cumulative += count;
+                let _ = writeln!(out, "{}_bucket{{{},le=\"{}\"}} {}", self.name, labels, bound, cumulative);
+            }
+            let _ = writeln!(out, "{}_bucket{{{},le=\"+Inf\"}} {}", self.name, labels, data.count);
+            let _ = writeln!(out, "{}_sum{{{}}} {}", self.name, labels, data.sum);
+            let _ = writeln!(out, "{}_count{{{}}} {}", self.name, labels, data.count);
+        }
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// `name="value"` with the value escaped for the text format
+fn label(name: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("{}=\"{}\"", name, escaped)
+}
+
+pub struct Metrics {
+    captures_requested: CounterVec,
+    captures_completed: CounterVec,
+    captures_failed: CounterVec,
+    capture_download: HistogramVec,
+    controller_restarts: CounterVec,
+    request_duration: HistogramVec,
+    request_timeouts: CounterVec,
+    ws_clients: AtomicI64,
+    ws_dropped: CounterVec,
+    liveview_frames: CounterVec,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            captures_requested: CounterVec::new(
+                "photobooth_captures_requested_total",
+                "Capture and burst requests sent to the camera",
+                "camera",
+            ),
+            captures_completed: CounterVec::new(
+                "photobooth_captures_completed_total",
+                "Capture and burst requests the camera completed",
+                "camera",
+            ),
+            captures_failed: CounterVec::new(
+                "photobooth_captures_failed_total",
+                "Capture and burst requests that failed or timed out",
+                "camera",
+            ),
+            capture_download: HistogramVec::new(
+                "photobooth_capture_download_seconds",
+                "Time from a capture request to the photo being downloaded from the camera",
+                "camera",
+                CAPTURE_BUCKETS,
+            ),
+            controller_restarts: CounterVec::new(
+                "photobooth_controller_restarts_total",
+                "gphoto2-controller processes restarted after exiting",
+                "camera",
+            ),
+            request_duration: HistogramVec::new(
+                "photobooth_controller_request_seconds",
+                "Controller request round trip time (CONFIG, SETCONFIG, CAPTURE, ...)",
+                "command",
+                REQUEST_BUCKETS,
+            ),
+            request_timeouts: CounterVec::new(
+                "photobooth_controller_request_timeouts_total",
+                "Controller requests that got no response in time",
+                "command",
+            ),
+            ws_clients: AtomicI64::new(0),
+            ws_dropped: CounterVec::new(
+                "photobooth_ws_dropped_messages_total",
+                "WebSocket events that never reached a client",
+                "reason",
+            ),
+            liveview_frames: CounterVec::new(
+                "photobooth_liveview_frames_total",
+                "Live view frames served on /api/liveview/ptp-stream",
+                "camera",
+            ),
+        }
+    }
+
+    pub fn capture_requested(&self, camera_id: &str) {
+        self.captures_requested.add(camera_id, 1);
+    }
+
+    pub fn capture_finished(&self, camera_id: &str, succeeded: bool) {
+        if succeeded {
+            self.captures_completed.add(camera_id, 1);
+        } else {
+            self.captures_failed.add(camera_id, 1);
+        }
+    }
+
+    pub fn capture_downloaded(&self, camera_id: &str, since_request: Duration) {
+        self.capture_download.observe(camera_id, since_request);
+    }
+
+    pub fn controller_restarted(&self, camera_id: &str) {
+        self.controller_restarts.add(camera_id, 1);
+    }
+
+    /// A controller request finished (`timed_out`: no response before its timeout)
+    pub fn request_finished(&self, command: &str, elapsed: Duration, timed_out: bool) {
+        self.request_duration.observe(command, elapsed);
+        if timed_out {
+            self.request_timeouts.add(command, 1);
+        }
+    }
+
+    pub fn ws_client_connected(&self) {
+        self.ws_clients.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn ws_client_disconnected(&self) {
+        self.ws_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Events lost: the sequencer lagged, or a client needed events already evicted from the replay log
+    pub fn ws_dropped(&self, reason: &str, count: u64) {
+        self.ws_dropped.add(reason, count);
+    }
+
+    /// Render every metric in the text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.captures_requested.render(&mut out);
+        self.captures_completed.render(&mut out);
+        self.captures_failed.render(&mut out);
+        self.capture_download.render(&mut out);
+        self.controller_restarts.render(&mut out);
+        self.request_duration.render(&mut out);
+        self.request_timeouts.render(&mut out);
+
+        header(&mut out, "photobooth_ws_clients", "Connected WebSocket clients", "gauge");
+        let _ = writeln!(out, "photobooth_ws_clients {}", self.ws_clients.load(Ordering::Relaxed));
+        self.ws_dropped.render(&mut out);
+        self.liveview_frames.render(&mut out);
+
+        let photo_dir = &crate::config::get().paths.photo_dir;
+        header(&mut out, "photobooth_storage_free_bytes", "Free space in the photo directory", "gauge");
+        match crate::storage::get_available_space(&photo_dir.to_string_lossy()) {
+            Ok(free) => {
+                let _ = writeln!(
+                    out,
+                    "photobooth_storage_free_bytes{{{}}} {}",
+                    label("dir", &photo_dir.to_string_lossy()),
+                    free
+                );
+            }
+            Err(e) => eprintln!("[metrics] Failed to read free space of {}: {}", photo_dir.display(), e),
+        }
+        out
+    }
+}
+
+/// Counts live view frames in a camera's MJPEG stream as it is sent
+pub struct FrameCounter {
+    camera_id: String,
+    /// End of the previous chunk, in case a boundary is split across chunks
+    tail: Vec<u8>,
+}
+
+impl FrameCounter {
+    pub fn new(camera_id: &str) -> Self {
+        Self { camera_id: camera_id.to_string(), tail: Vec::new() }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        let mut data = std::mem::take(&mut self.tail);
+        data.extend_from_slice(chunk);
+        let frames = data.windows(FRAME_BOUNDARY.len()).filter(|w| *w == FRAME_BOUNDARY).count();
+        if frames > 0 {
+            get().liveview_frames.add(&self.camera_id, frames as u64);
+        }
+        // Shorter than a boundary, so nothing counted here can be counted again
+        let keep = data.len().min(FRAME_BOUNDARY.len() - 1);
+        self.tail = data.split_off(data.len() - keep);
+    }
+}
+
+/// Capture requests waiting for their photo, to time capture -> download
+#[derive(Clone, Default)]
+pub struct CaptureTimer {
+    /// Request id -> when it was sent and whether it is a burst (several photos)
+    pending: std::sync::Arc<Mutex<BTreeMap<u64, (Instant, bool)>>>,
+}
+
+impl CaptureTimer {
+    pub fn start(&self, request_id: u64, burst: bool) {
+        self.pending.lock().unwrap().insert(request_id, (Instant::now(), burst));
+    }
+
+    pub fn finish(&self, request_id: u64) {
+        self.pending.lock().unwrap().remove(&request_id);
+    }
+
+    /// A photo arrived: time since the oldest capture still waiting. A single capture
+    /// is done with its photo; a burst keeps timing until its request completes.
+    /// None for photos taken with the camera's own shutter button.
+    pub fn downloaded(&self) -> Option<Duration> {
+        let mut pending = self.pending.lock().unwrap();
+        let (&id, &(started, burst)) = pending.iter().next()?;
+        if !burst {
+            pending.remove(&id);
+        }
+        Some(started.elapsed())
+    }
+}
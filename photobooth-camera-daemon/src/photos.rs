@@ -0,0 +1,245 @@
+//! Photo inventory and resumable downloads
+//!
+//! `GET /api/photos` lists every photo still stored on the daemon (all cameras, or one
+//! with `?camera=`), so a client that was disconnected can sweep up what it missed.
+//! Each entry carries the file's SHA-256, which is also its ETag on `/api/photo/{name}`;
+//! with `Range`/`If-Range` a client can resume a large RAW transfer and verify the result.
+//!
+//! Digests are cached by path and invalidated when the file's size or mtime changes.
+
+use crate::retention::{RetentionIndex, RetentionState};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::SystemTime;
+
+/// One stored photo, as listed by /api/photos
+#[derive(Serialize, Clone, Debug)]
+pub struct PhotoEntry {
+    pub filename: String,
+    pub camera_id: String,
+    pub size: u64,
+    pub captured_at_ms: u64,
+    pub sha256: String,
+    /// Retention state (captured / downloaded / acknowledged)
+    pub state: RetentionState,
+}
+
+struct CachedDigest {
+    size: u64,
+    modified: Option<SystemTime>,
+    sha256: String,
+}
+
+/// SHA-256 of stored photos, computed once per file version
+#[derive(Clone, Default)]
+pub struct DigestCache {
+    digests: Arc<StdMutex<HashMap<PathBuf, CachedDigest>>>,
+}
+
+impl DigestCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hex SHA-256 of a file, hashed off the async runtime if not cached
+    pub async fn sha256(&self, path: &Path, metadata: &std::fs::Metadata) -> Result<String, String> {
+        let size = metadata.len();
+        let modified = metadata.modified().ok();
+        if let Some(cached) = self.digests.lock().unwrap().get(path) {
+            if cached.size == size && cached.modified == modified {
+                return Ok(cached.sha256.clone());
+            }
+        }
+
+        let owned_path = path.to_path_buf();
+        let sha256 = tokio::task::spawn_blocking(move || hash_file(&owned_path))
+            .await
+            .map_err(|e| format!("Hashing task failed: {}", e))??;
+
+        let mut digests = self.digests.lock().unwrap();
+        // Forget files that have been deleted since they were hashed
+        digests.retain(|p, _| p.exists());
+        digests.insert(path.to_path_buf(), CachedDigest { size, modified, sha256: sha256.clone() });
+        Ok(sha256)
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Every stored photo, oldest first; `camera_id` limits the list to one camera
+pub async fn list_photos(
+    camera_id: Option<&str>,
+    retention: &RetentionIndex,
+    digests: &DigestCache,
+) -> Result<Vec<PhotoEntry>, String> {
+    let files = tokio::task::spawn_blocking(crate::storage::photo_files)
+        .await
+        .map_err(|e| format!("Photo scan task failed: {}", e))??;
+    retention.sync_with_disk(&files.iter().map(|(path, id, _)| (path.clone(), id.clone())).collect::<Vec<_>>());
+
+    let mut photos = Vec::new();
+    for (path, id, metadata) in files {
+        if camera_id.is_some_and(|wanted| wanted != id) {
+            continue;
+        }
+        let sha256 = match digests.sha256(&path, &metadata).await {
+            Ok(sha256) => sha256,
+            // Deleted between the scan and hashing
+            Err(e) => {
+                eprintln!("[photos] Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let retained = retention.get(&path);
+        photos.push(PhotoEntry {
+            filename: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            camera_id: id,
+            size: metadata.len(),
+            captured_at_ms: retained.as_ref().map(|r| r.captured_at_ms).unwrap_or_else(|| modified_ms(&metadata)),
+            sha256,
+            state: retained.map(|r| r.state).unwrap_or(RetentionState::Captured),
+        });
+    }
+    photos.sort_by(|a, b| a.captured_at_ms.cmp(&b.captured_at_ms).then_with(|| a.filename.cmp(&b.filename)));
+    Ok(photos)
+}
+
+fn modified_ms(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Strong ETag for a photo: its quoted SHA-256
+pub fn etag(sha256: &str) -> String {
+    format!("\"{}\"", sha256)
+}
+
+/// Whether an If-None-Match / If-Range value names this ETag
+pub fn etag_matches(header: &str, etag: &str) -> bool {
+    header.split(',').map(str::trim).any(|tag| tag == "*" || tag == etag)
+}
+
+/// Requested byte range, inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Parse a `Range` header for a file of `size` bytes.
+/// Ok(None): serve the whole file (no range, or a multi-range/unknown unit, which may be ignored).
+/// Err(()): the range can't be satisfied (416).
+pub fn parse_range(header: &str, size: u64) -> Result<Option<ByteRange>, ()> {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Err(());
+    };
+    let range = match (start.trim(), end.trim()) {
+        // Last N bytes
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| ())?;
+            if suffix == 0 || size == 0 {
+                return Err(());
+            }
+            ByteRange { start: size.saturating_sub(suffix), end: size - 1 }
+        }
+        (start, "") => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            if start >= size {
+                return Err(());
+            }
+            ByteRange { start, end: size - 1 }
+        }
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            let end: u64 = end.parse().map_err(|_| ())?;
+            if start > end || start >= size {
+                return Err(());
+            }
+            ByteRange { start, end: end.min(size - 1) }
+        }
+    };
+    Ok(Some(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u64, end: u64) -> Result<Option<ByteRange>, ()> {
+        Ok(Some(ByteRange { start, end }))
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let cases = [
+            // Closed ranges
+            ("bytes=0-99", 1000, range(0, 99)),
+            ("bytes=100-199", 1000, range(100, 199)),
+            ("bytes=999-999", 1000, range(999, 999)),
+            (" bytes= 10 - 20 ", 1000, range(10, 20)),
+            // End past EOF is clamped to the last byte
+            ("bytes=900-5000", 1000, range(900, 999)),
+            // Open-ended
+            ("bytes=500-", 1000, range(500, 999)),
+            ("bytes=0-", 1, range(0, 0)),
+            // Suffix: last N bytes, the whole file when N exceeds it
+            ("bytes=-100", 1000, range(900, 999)),
+            ("bytes=-1", 1000, range(999, 999)),
+            ("bytes=-5000", 1000, range(0, 999)),
+            // Not satisfiable
+            ("bytes=200-100", 1000, Err(())),
+            ("bytes=1000-", 1000, Err(())),
+            ("bytes=1000-1100", 1000, Err(())),
+            ("bytes=-0", 1000, Err(())),
+            ("bytes=-10", 0, Err(())),
+            ("bytes=0-", 0, Err(())),
+            ("bytes=abc-10", 1000, Err(())),
+            ("bytes=10", 1000, Err(())),
+            // Served whole
+            ("", 1000, Ok(None)),
+            ("items=0-99", 1000, Ok(None)),
+            ("bytes=0-99,200-299", 1000, Ok(None)),
+        ];
+        for (header, size, expected) in cases {
+            assert_eq!(parse_range(header, size), expected, "'{}' of {} bytes", header, size);
+        }
+    }
+
+    #[test]
+    fn test_byte_range_len() {
+        assert_eq!(ByteRange { start: 0, end: 0 }.len(), 1);
+        assert_eq!(ByteRange { start: 900, end: 999 }.len(), 100);
+    }
+}
@@ -0,0 +1,168 @@
+//! Typed request/response protocol with gphoto2-controller
+//!
+//! Requests are written to the command pipe as one JSON object per line, tagged with a
+//! correlation id. The controller answers on the status pipe with a `{"type":"response"}`
+//! line carrying the same id (see gphoto2-wrapper/controller/protocol.h).
+
+use crate::focus::FocusAction;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Commands the daemon sends to gphoto2-controller
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "cmd", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ControllerCommand {
+    Capture,
+    /// `count` frames, triggered every `interval_ms`, with the camera held open
+    Burst { count: u32, interval_ms: u32 },
+    LiveviewStreamStart,
+    LiveviewStreamStop,
+    SwitchCamera { camera_index: u32 },
+    Disconnect,
+    PausePolling,
+    ResumePolling,
+    /// A USB device came or went: drop the cached detection and reopen now (see hotplug.rs)
+    UsbChanged,
+    Config,
+    #[serde(rename = "SETCONFIG")]
+    SetConfig { setting: String, value: String },
+    /// Autofocus, focus steps, AF point or live view zoom (see focus.rs)
+    Focus {
+        action: FocusAction,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        steps: Option<u32>,
+        /// AF point in thousandths of the live view frame
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        y: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        level: Option<u32>,
+    },
+}
+
+impl ControllerCommand {
+    /// Command name as it appears on the wire (for logs and errors)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Capture => "CAPTURE",
+            Self::Burst { .. } => "BURST",
+            Self::LiveviewStreamStart => "LIVEVIEW_STREAM_START",
+            Self::LiveviewStreamStop => "LIVEVIEW_STREAM_STOP",
+            Self::SwitchCamera { .. } => "SWITCH_CAMERA",
+            Self::Disconnect => "DISCONNECT",
+            Self::PausePolling => "PAUSE_POLLING",
+            Self::ResumePolling => "RESUME_POLLING",
+            Self::UsbChanged => "USB_CHANGED",
+            Self::Config => "CONFIG",
+            Self::SetConfig { .. } => "SETCONFIG",
+            Self::Focus { .. } => "FOCUS",
+        }
+    }
+
+    /// How long to wait for the controller's response.
+    /// Commands that open the camera can sit behind the current poll cycle and
+    /// the controller's open retries (5 x 2s), so they get more room.
+    pub fn timeout(&self) -> Duration {
+        match self {
+            Self::Capture => Duration::from_secs(45),
+            // Same room as a single capture, plus the interval and a download per frame
+            Self::Burst { count, interval_ms } => {
+                Duration::from_secs(45)
+                    + Duration::from_millis(*count as u64 * (*interval_ms as u64 + 10_000))
+            }
+            Self::LiveviewStreamStart | Self::ResumePolling => Duration::from_secs(20),
+            Self::Config => Duration::from_secs(crate::config::get().controller.config_timeout_secs),
+            Self::SetConfig { .. } => Duration::from_secs(15),
+            // The controller pauses between manual focus steps
+            Self::Focus { steps, .. } => {
+                Duration::from_secs(15) + Duration::from_millis(steps.unwrap_or(1) as u64 * 200)
+            }
+            _ => Duration::from_secs(10),
+        }
+    }
+}
+
+/// A command plus its correlation id, as written to the command pipe
+#[derive(Serialize, Debug)]
+pub struct ControllerRequest<'a> {
+    pub id: u64,
+    #[serde(flatten)]
+    pub command: &'a ControllerCommand,
+}
+
+/// Response line emitted by the controller on the status pipe
+#[derive(Deserialize, Debug)]
+pub struct ControllerResponse {
+    pub id: u64,
+    pub ok: bool,
+    #[serde(default)]
+    pub result: serde_json::Value,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ControllerResponse {
+    /// Parse a status pipe line if it is a response (regular events return None)
+    pub fn from_status_line(line: &str) -> Option<Self> {
+        if !line.starts_with("{\"type\":\"response\"") {
+            return None;
+        }
+        match serde_json::from_str(line) {
+            Ok(response) => Some(response),
+            Err(e) => {
+                eprintln!("[protocol] Malformed response from controller: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Why a controller request did not produce a result
+#[derive(Debug)]
+pub enum ControllerError {
+    /// Command pipe missing or not writable - the controller isn't running
+    Unavailable(String),
+    /// No response within the command's timeout
+    Timeout { command: &'static str, after: Duration },
+    /// Controller exited before answering
+    Restarted { command: &'static str },
+    /// The controller answered with an error (camera busy, setting not found, ...)
+    Camera(String),
+    /// Request could not be encoded for the pipe
+    InvalidRequest(String),
+}
+
+impl ControllerError {
+    /// HTTP status the API should answer with
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Unavailable(_) | Self::Restarted { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            Self::Camera(_) => StatusCode::BAD_GATEWAY,
+            Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl std::fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unavailable(e) => write!(f, "Controller unavailable: {}", e),
+            Self::Timeout { command, after } => write!(
+                f,
+                "Timeout waiting for controller response to {} after {}ms",
+                command,
+                after.as_millis()
+            ),
+            Self::Restarted { command } => {
+                write!(f, "Controller exited before answering {}", command)
+            }
+            Self::Camera(e) => write!(f, "{}", e),
+            Self::InvalidRequest(e) => write!(f, "Invalid request: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ControllerError {}
@@ -0,0 +1,169 @@
+//! RAW captures: embedded JPEG previews and RAW+JPEG pairing
+//!
+//! The app can't decode RAW files, so for a RAW-only capture the largest JPEG preview
+//! embedded in it (found by the raw-preview crate the app uses too) is written next
+//! to the RAW as `<stem>.JPG`.
+//!
+//! In RAW+JPEG mode the camera delivers two files per shot. A RAW's photo_downloaded
+//! event is held for `PAIR_WINDOW`; if the camera's JPEG arrives in the meantime the two
+//! become one event (`file_path` the JPEG, `raw_path` the RAW). Otherwise the preview is
+//! extracted and announced in the RAW's place. A RAW that arrives after its JPEG was
+//! already announced is reported as a `raw_attached` event.
+
+use raw_preview::{embedded_jpeg, preview_path, sibling_jpeg};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+pub use raw_preview::is_raw;
+
+/// How long a RAW waits for the camera's JPEG of the same shot
+pub const PAIR_WINDOW: Duration = Duration::from_secs(3);
+
+fn is_jpeg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg"))
+}
+
+/// Write the RAW's embedded preview to `preview_path`
+pub fn extract_preview(raw: &Path) -> Result<PathBuf, String> {
+    let data = std::fs::read(raw).map_err(|e| format!("Failed to read {}: {}", raw.display(), e))?;
+    let jpeg = embedded_jpeg(&data).ok_or_else(|| format!("No embedded JPEG preview in {}", raw.display()))?;
+    let path = preview_path(raw);
+    std::fs::write(&path, jpeg).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// What to do with a status pipe event after RAW pairing
+pub enum Routed {
+    /// Broadcast the event as it is
+    Unchanged,
+    /// The event was rewritten (merged pair or `raw_attached`); broadcast the new one
+    Rewritten,
+    /// A RAW held back for its JPEG; call `RawPairing::flush` after `PAIR_WINDOW`
+    Held(PathBuf),
+}
+
+/// Pairs RAW and JPEG photo_downloaded events of one camera
+#[derive(Clone, Default)]
+pub struct RawPairing {
+    state: Arc<StdMutex<PairingState>>,
+}
+
+#[derive(Default)]
+struct PairingState {
+    /// RAW events waiting for their JPEG, by path without extension
+    held: HashMap<PathBuf, Value>,
+    /// JPEGs announced recently, by path without extension
+    announced: HashMap<PathBuf, (String, Instant)>,
+    /// Held RAWs not yet announced, including ones being flushed
+    outstanding: usize,
+}
+
+impl RawPairing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route a status pipe event; only photo_downloaded events are affected
+    pub fn route(&self, event: &mut Value) -> Routed {
+        if event.get("type").and_then(|v| v.as_str()) != Some("photo_downloaded") {
+            return Routed::Unchanged;
+        }
+        let Some(path) = event.get("file_path").and_then(|v| v.as_str()).map(PathBuf::from) else {
+            return Routed::Unchanged;
+        };
+        let key = path.with_extension("");
+        let mut state = self.state.lock().unwrap();
+        state.announced.retain(|_, (_, at)| at.elapsed() < PAIR_WINDOW);
+
+        if is_raw(&path) {
+            if let Some((jpeg, _)) = state.announced.remove(&key) {
+                event["type"] = Value::from("raw_attached");
+                event["raw_path"] = Value::from(path.to_string_lossy());
+                event["file_path"] = Value::from(jpeg);
+                if let Some(camera_path) = event.get_mut("camera_path").map(Value::take) {
+                    event["raw_camera_path"] = camera_path;
+                }
+                return Routed::Rewritten;
+            }
+            state.held.insert(key.clone(), event.clone());
+            state.outstanding += 1;
+            return Routed::Held(key);
+        }
+
+        if is_jpeg(&path) {
+            if let Some(raw) = state.held.remove(&key) {
+                merge_raw(event, &raw, "camera");
+                return Routed::Rewritten;
+            }
+            state.announced.insert(key, (path.to_string_lossy().into_owned(), Instant::now()));
+        }
+        Routed::Unchanged
+    }
+
+    /// A flushed RAW has been announced (or turned out to be paired already)
+    pub fn finished(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.outstanding = state.outstanding.saturating_sub(1);
+    }
+
+    /// Wait until every held RAW has been announced, so group captures and sequences
+    /// collect RAW-only shots too
+    pub async fn settled(&self) {
+        let deadline = Instant::now() + PAIR_WINDOW * 2;
+        while self.state.lock().unwrap().outstanding > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// The held RAW's event once `PAIR_WINDOW` has passed without its JPEG, announcing the
+    /// embedded preview (or the RAW itself, with `preview_error`, if it has none).
+    /// None if the RAW was paired in the meantime.
+    pub async fn flush(&self, key: &Path) -> Option<Value> {
+        let mut raw = self.state.lock().unwrap().held.remove(key)?;
+        let raw_path = PathBuf::from(raw.get("file_path").and_then(|v| v.as_str())?);
+
+        if let Some(jpeg) = sibling_jpeg(&raw_path) {
+            let mut event = raw.clone();
+            event["file_path"] = Value::from(jpeg.to_string_lossy());
+            merge_raw(&mut event, &raw, "camera");
+            return Some(event);
+        }
+
+        let path = raw_path.clone();
+        match tokio::task::spawn_blocking(move || extract_preview(&path)).await {
+            Ok(Ok(preview)) => {
+                println!("[raw] Extracted preview of {} to {}", raw_path.display(), preview.display());
+                let mut event = raw.clone();
+                event["file_path"] = Value::from(preview.to_string_lossy());
+                merge_raw(&mut event, &raw, "embedded");
+                Some(event)
+            }
+            Ok(Err(e)) => {
+                eprintln!("[raw] {}", e);
+                raw["preview_error"] = Value::from(e);
+                Some(raw)
+            }
+            Err(e) => {
+                eprintln!("[raw] Preview task failed: {}", e);
+                raw["preview_error"] = Value::from(e.to_string());
+                Some(raw)
+            }
+        }
+    }
+}
+
+/// Turn a JPEG's event into the pair's: add the RAW's paths and where the JPEG came from
+fn merge_raw(event: &mut Value, raw: &Value, preview: &str) {
+    if let Some(raw_path) = raw.get("file_path") {
+        event["raw_path"] = raw_path.clone();
+    }
+    if let Some(raw_camera_path) = raw.get("camera_path") {
+        event["raw_camera_path"] = raw_camera_path.clone();
+    }
+    event["preview"] = Value::from(preview);
+}
@@ -0,0 +1,341 @@
+//! Acknowledgement-based retention for downloaded photos
+//!
+//! Every photo in a camera's runtime dir is tracked through three states:
+//! captured (the controller downloaded it from the camera), downloaded (the app fetched
+//! it from /api/photo) and acknowledged (the app confirmed it is stored safely, via
+//! POST /api/photo/{name}/ack). Low-space cleanup only evicts acknowledged photos, so a
+//! slow or disconnected host never loses a shot.
+//!
+//! The index is persisted to <photo dir>/.photobooth-retention.json so a daemon restart keeps
+//! acknowledgements. The controller does its own cleanup before saving a file, so each
+//! runtime dir also gets a `.acknowledged` list (one file name per line) it can honour.
+//! Both are written by a background task, batched over a short window and off the runtime
+//! threads; a list is only rewritten when an acknowledgement in its dir changed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+const INDEX_FILE: &str = ".photobooth-retention.json";
+
+/// Changes within this window are written together
+const WRITE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// File read by gphoto2-controller's cleanup (see controller/camera_capture.c)
+pub const ACKNOWLEDGED_LIST: &str = ".acknowledged";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionState {
+    Captured,
+    Downloaded,
+    Acknowledged,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RetainedPhoto {
+    pub camera_id: String,
+    pub state: RetentionState,
+    pub size: u64,
+    pub captured_at_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloaded_at_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at_ms: Option<u64>,
+}
+
+/// Counts per state, for /api/storage and the storage_low event
+#[derive(Serialize, Default, Debug, Clone)]
+pub struct RetentionSummary {
+    pub captured: usize,
+    pub downloaded: usize,
+    pub acknowledged: usize,
+    /// Photos cleanup may not evict (captured + downloaded) and their total size
+    pub unacknowledged_files: usize,
+    pub unacknowledged_bytes: u64,
+}
+
+/// What the writer task still has to write
+#[derive(Default)]
+struct PendingWrite {
+    index: bool,
+    /// Runtime dirs whose acknowledged list changed
+    acknowledged_dirs: BTreeSet<PathBuf>,
+}
+
+/// Per-file retention state, keyed by full path
+#[derive(Clone)]
+pub struct RetentionIndex {
+    photos: Arc<StdMutex<BTreeMap<PathBuf, RetainedPhoto>>>,
+    index_path: PathBuf,
+    pending: Arc<StdMutex<PendingWrite>>,
+    wake_writer: Arc<Notify>,
+}
+
+impl RetentionIndex {
+    /// Load the persisted index, dropping entries whose files are gone, and start the
+    /// writer task (must be called from within the tokio runtime)
+    pub fn load() -> Self {
+        let index_path = crate::config::get().paths.photo_dir.join(INDEX_FILE);
+        let photos: BTreeMap<PathBuf, RetainedPhoto> = match std::fs::read_to_string(&index_path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("[retention] Ignoring unreadable index {}: {}", index_path.display(), e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        let index = Self::new(index_path, photos.into_iter().filter(|(path, _)| path.exists()).collect());
+        println!("[retention] Tracking {} photo(s) from the previous run", index.photos.lock().unwrap().len());
+
+        // Start from lists that match the index, for every runtime dir
+        let mut dirs: BTreeSet<PathBuf> = crate::storage::photo_dirs().into_iter().collect();
+        dirs.extend(index.photos.lock().unwrap().keys().filter_map(|path| path.parent().map(Path::to_path_buf)));
+        index.schedule_write(dirs);
+        tokio::spawn(index.clone().run_writer());
+        index
+    }
+
+    fn new(index_path: PathBuf, photos: BTreeMap<PathBuf, RetainedPhoto>) -> Self {
+        Self {
+            photos: Arc::new(StdMutex::new(photos)),
+            index_path,
+            pending: Arc::new(StdMutex::new(PendingWrite::default())),
+            wake_writer: Arc::new(Notify::new()),
+        }
+    }
+
+    /// An empty index without a writer task, so nothing is written
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self::new(PathBuf::from(INDEX_FILE), BTreeMap::new())
+    }
+
+    /// A photo arrived from the camera
+    pub fn record_captured(&self, path: &Path, camera_id: &str) {
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let mut photos = self.photos.lock().unwrap();
+        photos.insert(path.to_path_buf(), RetainedPhoto {
+            camera_id: camera_id.to_string(),
+            state: RetentionState::Captured,
+            size,
+            captured_at_ms: now_ms(),
+            downloaded_at_ms: None,
+            acknowledged_at_ms: None,
+        });
+        drop(photos);
+        self.schedule_write([]);
+    }
+
+    /// The app fetched the photo (it may still fail to store it)
+    pub fn mark_downloaded(&self, path: &Path, camera_id: &str) {
+        let mut photos = self.photos.lock().unwrap();
+        let photo = photos.entry(path.to_path_buf()).or_insert_with(|| untracked(path, camera_id));
+        if photo.state == RetentionState::Captured {
+            photo.state = RetentionState::Downloaded;
+        }
+        photo.downloaded_at_ms.get_or_insert_with(now_ms);
+        drop(photos);
+        self.schedule_write([]);
+    }
+
+    /// The app has the photo stored; cleanup may now evict it
+    pub fn acknowledge(&self, path: &Path, camera_id: &str) -> Result<RetainedPhoto, String> {
+        if !path.is_file() {
+            return Err(format!("No such photo: {}", path.display()));
+        }
+        let mut photos = self.photos.lock().unwrap();
+        let photo = photos.entry(path.to_path_buf()).or_insert_with(|| untracked(path, camera_id));
+        photo.state = RetentionState::Acknowledged;
+        photo.acknowledged_at_ms.get_or_insert_with(now_ms);
+        let photo = photo.clone();
+        drop(photos);
+        self.schedule_write(path.parent().map(Path::to_path_buf));
+        Ok(photo)
+    }
+
+    /// The photo was deleted (by the app or by cleanup)
+    pub fn forget(&self, path: &Path) {
+        let removed = self.photos.lock().unwrap().remove(path);
+        if let Some(photo) = removed {
+            let dir = (photo.state == RetentionState::Acknowledged).then(|| path.parent().map(Path::to_path_buf));
+            self.schedule_write(dir.flatten());
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<RetainedPhoto> {
+        self.photos.lock().unwrap().get(path).cloned()
+    }
+
+    pub fn is_acknowledged(&self, path: &Path) -> bool {
+        self.photos
+            .lock()
+            .unwrap()
+            .get(path)
+            .is_some_and(|p| p.state == RetentionState::Acknowledged)
+    }
+
+    /// Reconcile with the photos actually on disk: files the index doesn't know (e.g.
+    /// downloaded while the daemon was down) count as captured, missing files are dropped
+    pub fn sync_with_disk(&self, on_disk: &[(PathBuf, String)]) {
+        let present: HashSet<&PathBuf> = on_disk.iter().map(|(path, _)| path).collect();
+        let mut photos = self.photos.lock().unwrap();
+        let before = photos.len();
+        let mut acknowledged_dirs = BTreeSet::new();
+        photos.retain(|path, photo| {
+            let keep = present.contains(path);
+            if !keep && photo.state == RetentionState::Acknowledged {
+                acknowledged_dirs.extend(path.parent().map(Path::to_path_buf));
+            }
+            keep
+        });
+        let mut changed = photos.len() != before;
+        for (path, camera_id) in on_disk {
+            if !photos.contains_key(path) {
+                photos.insert(path.clone(), untracked(path, camera_id));
+                changed = true;
+            }
+        }
+        drop(photos);
+        if changed {
+            self.schedule_write(acknowledged_dirs);
+        }
+    }
+
+    pub fn summary(&self) -> RetentionSummary {
+        let photos = self.photos.lock().unwrap();
+        let mut summary = RetentionSummary::default();
+        for photo in photos.values() {
+            match photo.state {
+                RetentionState::Captured => summary.captured += 1,
+                RetentionState::Downloaded => summary.downloaded += 1,
+                RetentionState::Acknowledged => summary.acknowledged += 1,
+            }
+            if photo.state != RetentionState::Acknowledged {
+                summary.unacknowledged_files += 1;
+                summary.unacknowledged_bytes += photo.size;
+            }
+        }
+        summary
+    }
+
+    /// Queue a write of the index, and of the acknowledged lists of `acknowledged_dirs`
+    fn schedule_write(&self, acknowledged_dirs: impl IntoIterator<Item = PathBuf>) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.index = true;
+        pending.acknowledged_dirs.extend(acknowledged_dirs);
+        drop(pending);
+        self.wake_writer.notify_one();
+    }
+
+    /// Write queued changes, a batch at a time, on the blocking pool
+    async fn run_writer(self) {
+        loop {
+            self.wake_writer.notified().await;
+            tokio::time::sleep(WRITE_DEBOUNCE).await;
+
+            let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+            if !pending.index && pending.acknowledged_dirs.is_empty() {
+                continue;
+            }
+            let photos = self.photos.lock().unwrap().clone();
+            let index_path = self.index_path.clone();
+            let written = tokio::task::spawn_blocking(move || {
+                write_index(&index_path, &photos, pending.index);
+                write_acknowledged_lists(&photos, &pending.acknowledged_dirs);
+            })
+            .await;
+            if let Err(e) = written {
+                eprintln!("[retention] Writer task failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Write the index (temp file + rename)
+fn write_index(index_path: &Path, photos: &BTreeMap<PathBuf, RetainedPhoto>, changed: bool) {
+    if !changed {
+        return;
+    }
+    match serde_json::to_string(photos) {
+        Ok(json) => {
+            if let Err(e) = write_atomic(index_path, json.as_bytes()) {
+                eprintln!("[retention] Failed to write {}: {}", index_path.display(), e);
+            }
+        }
+        Err(e) => eprintln!("[retention] Failed to serialize the index: {}", e),
+    }
+}
+
+/// Rewrite the acknowledged list of each of `dirs` (temp file + rename)
+fn write_acknowledged_lists(photos: &BTreeMap<PathBuf, RetainedPhoto>, dirs: &BTreeSet<PathBuf>) {
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        let mut list = String::new();
+        for (path, _) in photos.iter().filter(|(_, p)| p.state == RetentionState::Acknowledged) {
+            if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+                if parent == dir {
+                    list.push_str(&name.to_string_lossy());
+                    list.push('\n');
+                }
+            }
+        }
+        if let Err(e) = write_atomic(&dir.join(ACKNOWLEDGED_LIST), list.as_bytes()) {
+            eprintln!("[retention] Failed to write {}: {}", dir.join(ACKNOWLEDGED_LIST).display(), e);
+        }
+    }
+}
+
+fn untracked(path: &Path, camera_id: &str) -> RetainedPhoto {
+    let metadata = std::fs::metadata(path).ok();
+    RetainedPhoto {
+        camera_id: camera_id.to_string(),
+        state: RetentionState::Captured,
+        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+        captured_at_ms: metadata
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_else(now_ms),
+        downloaded_at_ms: None,
+        acknowledged_at_ms: None,
+    }
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn now_ms() -> u64 {
+    crate::group_capture::unix_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_with_disk_tracks_new_files_and_drops_missing_ones() {
+        let dir = std::env::temp_dir().join(format!("retention_sync_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (kept, gone, new) = (dir.join("kept.jpg"), dir.join("gone.jpg"), dir.join("new.jpg"));
+        for path in [&kept, &gone, &new] {
+            std::fs::write(path, b"jpeg").unwrap();
+        }
+
+        let index = RetentionIndex::in_memory();
+        index.acknowledge(&kept, "0").unwrap();
+        index.record_captured(&gone, "0");
+        index.sync_with_disk(&[(kept.clone(), "0".to_string()), (new.clone(), "1-1".to_string())]);
+
+        assert!(index.is_acknowledged(&kept));
+        assert!(index.get(&gone).is_none());
+        let added = index.get(&new).unwrap();
+        assert_eq!((added.camera_id.as_str(), added.state, added.size), ("1-1", RetentionState::Captured, 4));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
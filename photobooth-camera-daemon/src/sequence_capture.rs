@@ -0,0 +1,311 @@
+//! Burst and exposure-bracketed capture on a single camera
+//!
+//! Bursts run inside the controller (BURST command) so frames aren't spaced by
+//! daemon round trips. Brackets step one exposure setting through the same
+//! SETCONFIG path as POST /api/camera/config, capturing once per value, and put the
+//! original value back afterwards. Either way the photo events are tagged with a
+//! `sequence_id` while it runs and one `sequence_captured` event lists them all.
+
+use crate::controller::{get_camera_config, set_camera_config, CaptureTag, ControllerState};
+use crate::group_capture::{unix_millis, GroupPhoto, DEFAULT_SETTLE_MS, MAX_SETTLE_MS};
+use crate::protocol::{ControllerCommand, ControllerError};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Must match BURST_MAX_FRAMES in gphoto2-wrapper/controller/protocol.h
+const MAX_BURST_FRAMES: u32 = 100;
+const MAX_BURST_INTERVAL_MS: u32 = 60_000;
+const MAX_BRACKET_FRAMES: usize = 15;
+
+/// Settings a bracket may step through
+const BRACKET_SETTINGS: &[&str] = &["shutterspeed", "exposurecompensation"];
+
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+/// Body of POST /api/capture/burst
+#[derive(Deserialize, Debug)]
+pub struct BurstRequest {
+    pub count: u32,
+    /// Time between frame triggers; 0 fires as fast as the camera allows
+    #[serde(default)]
+    pub interval_ms: u32,
+    #[serde(default)]
+    pub settle_ms: Option<u64>,
+}
+
+/// Body of POST /api/capture/bracket
+#[derive(Deserialize, Debug)]
+pub struct BracketRequest {
+    /// "shutterspeed" or "exposurecompensation"
+    pub setting: String,
+    /// Values to capture at, in order, as the camera lists them (e.g. "1/125", "-1")
+    pub values: Vec<String>,
+    /// Put the original value back afterwards (default true)
+    #[serde(default)]
+    pub restore: Option<bool>,
+    #[serde(default)]
+    pub settle_ms: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceKind {
+    Burst,
+    Bracket,
+}
+
+/// One trigger in a sequence
+#[derive(Serialize, Clone, Debug)]
+pub struct SequenceFrame {
+    pub index: u32,
+    /// Setting value this frame was taken at (brackets only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Wall-clock trigger time reported by the controller (ms since epoch)
+    pub triggered_at_ms: Option<i64>,
+    /// Trigger time relative to the first frame
+    pub offset_ms: Option<i64>,
+}
+
+/// Result of a burst or bracket, also broadcast as the `sequence_captured` event
+#[derive(Serialize, Clone, Debug)]
+pub struct SequenceCaptureResult {
+    pub sequence_id: String,
+    pub kind: SequenceKind,
+    pub camera_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub setting: Option<String>,
+    pub requested: u32,
+    pub frames: Vec<SequenceFrame>,
+    /// Downloaded files in arrival order (RAW+JPEG gives two per frame)
+    pub photos: Vec<GroupPhoto>,
+    /// Why the sequence stopped early, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Why a sequence could not run
+#[derive(Debug)]
+pub enum SequenceCaptureError {
+    Invalid(String),
+    /// Camera is already in a group capture or another sequence
+    Busy(String),
+    Controller(ControllerError),
+}
+
+impl SequenceCaptureError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Invalid(_) => StatusCode::BAD_REQUEST,
+            Self::Busy(_) => StatusCode::CONFLICT,
+            Self::Controller(e) => e.status_code(),
+        }
+    }
+}
+
+impl std::fmt::Display for SequenceCaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "{}", e),
+            Self::Busy(id) => write!(f, "Camera {} is already capturing a group or sequence", id),
+            Self::Controller(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Capture `count` frames at a fixed interval on one camera
+pub async fn capture_burst(
+    controller_state: &ControllerState,
+    ws_tx: &tokio::sync::broadcast::Sender<Message>,
+    request: BurstRequest,
+) -> Result<SequenceCaptureResult, SequenceCaptureError> {
+    if request.count == 0 || request.count > MAX_BURST_FRAMES {
+        return Err(SequenceCaptureError::Invalid(format!(
+            "count must be between 1 and {}", MAX_BURST_FRAMES
+        )));
+    }
+    if request.interval_ms > MAX_BURST_INTERVAL_MS {
+        return Err(SequenceCaptureError::Invalid(format!(
+            "interval_ms must be at most {}", MAX_BURST_INTERVAL_MS
+        )));
+    }
+
+    let sequence_id = next_sequence_id();
+    if !controller_state.begin_capture_group(CaptureTag::Sequence(sequence_id.clone())) {
+        return Err(SequenceCaptureError::Busy(controller_state.camera_id.clone()));
+    }
+
+    println!("[sequence] Burst {} on camera {}: {} frames every {}ms",
+        sequence_id, controller_state.camera_id, request.count, request.interval_ms);
+
+    let result = controller_state
+        .request(ControllerCommand::Burst {
+            count: request.count,
+            interval_ms: request.interval_ms,
+        })
+        .await;
+
+    let (frames, error) = match result {
+        Ok(value) => {
+            let frames = value
+                .get("frames")
+                .and_then(|v| v.as_array())
+                .map(|frames| {
+                    frames
+                        .iter()
+                        .map(|frame| SequenceFrame {
+                            index: frame.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+                            value: None,
+                            triggered_at_ms: frame.get("triggered_at_ms").and_then(|v| v.as_i64()),
+                            offset_ms: None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let error = value.get("error").and_then(|v| v.as_str()).map(|e| e.to_string());
+            (frames, error)
+        }
+        Err(e) => {
+            controller_state.end_capture_group();
+            return Err(SequenceCaptureError::Controller(e));
+        }
+    };
+
+    Ok(finish_sequence(controller_state, ws_tx, SequenceCaptureResult {
+        sequence_id,
+        kind: SequenceKind::Burst,
+        camera_id: controller_state.camera_id.clone(),
+        setting: None,
+        requested: request.count,
+        frames,
+        photos: Vec::new(),
+        error,
+    }, request.settle_ms).await)
+}
+
+/// Capture once per value of an exposure setting, restoring the original value afterwards
+pub async fn capture_bracket(
+    controller_state: &ControllerState,
+    ws_tx: &tokio::sync::broadcast::Sender<Message>,
+    request: BracketRequest,
+) -> Result<SequenceCaptureResult, SequenceCaptureError> {
+    if !BRACKET_SETTINGS.contains(&request.setting.as_str()) {
+        return Err(SequenceCaptureError::Invalid(format!(
+            "Cannot bracket '{}' (supported: {})", request.setting, BRACKET_SETTINGS.join(", ")
+        )));
+    }
+    if request.values.is_empty() || request.values.len() > MAX_BRACKET_FRAMES {
+        return Err(SequenceCaptureError::Invalid(format!(
+            "values must list between 1 and {} settings", MAX_BRACKET_FRAMES
+        )));
+    }
+
+    let sequence_id = next_sequence_id();
+    if !controller_state.begin_capture_group(CaptureTag::Sequence(sequence_id.clone())) {
+        return Err(SequenceCaptureError::Busy(controller_state.camera_id.clone()));
+    }
+
+    let original = if request.restore.unwrap_or(true) {
+        match get_camera_config(controller_state).await {
+            Ok(config) => {
+                let original = config
+                    .get(&request.setting)
+                    .and_then(|s| s.get("value"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string());
+                if original.is_none() {
+                    eprintln!("[sequence] Camera doesn't report '{}', it won't be restored", request.setting);
+                }
+                original
+            }
+            Err(e) => {
+                controller_state.end_capture_group();
+                return Err(SequenceCaptureError::Controller(e));
+            }
+        }
+    } else {
+        None
+    };
+
+    println!("[sequence] Bracket {} on camera {}: {} = {:?}",
+        sequence_id, controller_state.camera_id, request.setting, request.values);
+
+    let mut frames = Vec::with_capacity(request.values.len());
+    let mut error = None;
+    for (index, value) in request.values.iter().enumerate() {
+        if let Err(e) = set_camera_config(controller_state, &request.setting, value).await {
+            error = Some(format!("Failed to set {} to {}: {}", request.setting, value, e));
+            break;
+        }
+        match controller_state.request(ControllerCommand::Capture).await {
+            Ok(result) => frames.push(SequenceFrame {
+                index: index as u32,
+                value: Some(value.clone()),
+                triggered_at_ms: result.get("triggered_at_ms").and_then(|v| v.as_i64()),
+                offset_ms: None,
+            }),
+            Err(e) => {
+                error = Some(format!("Capture at {} = {} failed: {}", request.setting, value, e));
+                break;
+            }
+        }
+    }
+
+    if let Some(original) = &original {
+        if let Err(e) = set_camera_config(controller_state, &request.setting, original).await {
+            eprintln!("[sequence] Failed to restore {} to {}: {}", request.setting, original, e);
+        }
+    }
+
+    if frames.is_empty() {
+        controller_state.end_capture_group();
+        return Err(SequenceCaptureError::Controller(ControllerError::Camera(
+            error.unwrap_or_else(|| "Bracket captured no frames".to_string()),
+        )));
+    }
+
+    Ok(finish_sequence(controller_state, ws_tx, SequenceCaptureResult {
+        sequence_id,
+        kind: SequenceKind::Bracket,
+        camera_id: controller_state.camera_id.clone(),
+        setting: Some(request.setting),
+        requested: request.values.len() as u32,
+        frames,
+        photos: Vec::new(),
+        error,
+    }, request.settle_ms).await)
+}
+
+/// Wait for late photo events, collect the sequence's photos and broadcast the result
+async fn finish_sequence(
+    controller_state: &ControllerState,
+    ws_tx: &tokio::sync::broadcast::Sender<Message>,
+    mut result: SequenceCaptureResult,
+    settle_ms: Option<u64>,
+) -> SequenceCaptureResult {
+    let settle_ms = settle_ms.unwrap_or(DEFAULT_SETTLE_MS).min(MAX_SETTLE_MS);
+    tokio::time::sleep(std::time::Duration::from_millis(settle_ms)).await;
+    controller_state.raw_pairing.settled().await;
+    result.photos = controller_state.end_capture_group();
+
+    if let Some(first) = result.frames.iter().filter_map(|f| f.triggered_at_ms).min() {
+        for frame in &mut result.frames {
+            frame.offset_ms = frame.triggered_at_ms.map(|t| t - first);
+        }
+    }
+
+    println!("[sequence] {} done: {}/{} frame(s), {} photo(s)",
+        result.sequence_id, result.frames.len(), result.requested, result.photos.len());
+
+    let mut event = serde_json::to_value(&result).unwrap_or_default();
+    event["type"] = serde_json::json!("sequence_captured");
+    let _ = ws_tx.send(Message::Text(event.to_string().into()));
+
+    result
+}
+
+fn next_sequence_id() -> String {
+    format!("seq_{}_{}", unix_millis(), NEXT_SEQUENCE.fetch_add(1, Ordering::Relaxed))
+}
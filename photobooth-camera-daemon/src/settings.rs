@@ -0,0 +1,701 @@
+//! Canonical camera settings across brands
+//!
+//! gphoto2 exposes the same concepts under different widget names and choice strings:
+//! Canon reports aperture as "aperture" with "5.6", Fuji and Sony as "f-number" with
+//! "f/5.6"; shutter speeds come as "1/125", "0.4s" or "32/10"; Fuji's auto ISO is "-1";
+//! battery is "batterylevel" ("65%") or the PTP property "5001" ("100").
+//!
+//! `GET /api/camera/settings` reads the raw config once and maps each known widget to a
+//! canonical setting with a typed value and unit (ISO, f-number, seconds, EV, percent)
+//! or a named value (white balance, drive mode, "auto", "bulb"), plus the camera's
+//! options in the same form. `POST /api/camera/settings` takes canonical values, picks
+//! the matching raw choice for this body and applies them as one config batch (see
+//! config_batch), so a failure rolls back the settings already changed.
+
+use crate::config_batch::{apply_config_batch, ConfigBatchEntry, ConfigBatchReport, ConfigBatchRequest};
+use crate::controller::{get_camera_config, ControllerState};
+use crate::protocol::ControllerError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Brand {
+    Canon,
+    Fujifilm,
+    Sony,
+    Nikon,
+    Other,
+}
+
+impl Brand {
+    pub fn from_manufacturer(manufacturer: &str) -> Self {
+        let lower = manufacturer.to_lowercase();
+        if lower.contains("canon") {
+            Self::Canon
+        } else if lower.contains("fuji") {
+            Self::Fujifilm
+        } else if lower.contains("sony") {
+            Self::Sony
+        } else if lower.contains("nikon") {
+            Self::Nikon
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Canonical settings, in the order a change is applied
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Setting {
+    DriveMode,
+    Iso,
+    Aperture,
+    ShutterSpeed,
+    ExposureCompensation,
+    WhiteBalance,
+    Battery,
+}
+
+const ALL_SETTINGS: [Setting; 7] = [
+    Setting::DriveMode,
+    Setting::Iso,
+    Setting::Aperture,
+    Setting::ShutterSpeed,
+    Setting::ExposureCompensation,
+    Setting::WhiteBalance,
+    Setting::Battery,
+];
+
+impl Setting {
+    /// gphoto2 widgets that carry this setting, most specific first
+    fn widgets(self, brand: Brand) -> &'static [&'static str] {
+        match self {
+            Setting::DriveMode => &["drivemode", "capturemode", "stillcapturemode"],
+            Setting::Iso => &["iso", "isospeed"],
+            Setting::Aperture if brand == Brand::Canon => &["aperture", "f-number"],
+            Setting::Aperture => &["f-number", "aperture"],
+            Setting::ShutterSpeed => &["shutterspeed", "shutterspeed2"],
+            // Fuji also reports "5010" in thousandths of a stop
+            Setting::ExposureCompensation => &["exposurecompensation", "5010"],
+            Setting::WhiteBalance => &["whitebalance"],
+            Setting::Battery => &["batterylevel", "5001"],
+        }
+    }
+
+    fn unit(self) -> Option<&'static str> {
+        match self {
+            Setting::Iso => Some("iso"),
+            Setting::Aperture => Some("f"),
+            Setting::ShutterSpeed => Some("s"),
+            Setting::ExposureCompensation => Some("ev"),
+            Setting::Battery => Some("%"),
+            Setting::DriveMode | Setting::WhiteBalance => None,
+        }
+    }
+
+    fn read_only(self) -> bool {
+        self == Setting::Battery
+    }
+}
+
+/// A canonical value: a number in the setting's unit, or a name ("auto", "bulb",
+/// "daylight", "continuous_high", ...)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum CanonicalValue {
+    Number(f64),
+    Named(String),
+}
+
+impl std::fmt::Display for CanonicalValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CanonicalValue::Number(n) => write!(f, "{}", n),
+            CanonicalValue::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SettingOption {
+    pub raw: String,
+    /// None when the choice isn't understood (e.g. "Unknown value 0001")
+    pub value: Option<CanonicalValue>,
+}
+
+/// One canonical setting as the camera reports it
+#[derive(Serialize, Clone, Debug)]
+pub struct SettingState {
+    /// gphoto2 widget it was read from
+    pub widget: String,
+    pub raw: String,
+    pub value: Option<CanonicalValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<&'static str>,
+    pub read_only: bool,
+    /// Settable values (empty when the camera doesn't list them or the setting is read-only)
+    pub options: Vec<SettingOption>,
+}
+
+/// Body of GET /api/camera/settings
+#[derive(Serialize, Debug)]
+pub struct CanonicalSettings {
+    pub camera_id: String,
+    pub brand: Brand,
+    pub settings: BTreeMap<Setting, SettingState>,
+    /// Canonical settings this camera has no widget for
+    pub unsupported: Vec<Setting>,
+}
+
+/// Body of POST /api/camera/settings
+#[derive(Deserialize, Debug)]
+pub struct SettingsRequest {
+    pub settings: BTreeMap<Setting, CanonicalValue>,
+    /// Restore already-applied settings when one fails (default true)
+    #[serde(default)]
+    pub rollback_on_failure: Option<bool>,
+}
+
+/// A canonical value resolved to this camera's widget and choice
+#[derive(Serialize, Clone, Debug)]
+pub struct ResolvedSetting {
+    pub value: CanonicalValue,
+    pub widget: String,
+    pub raw: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SettingsReport {
+    pub success: bool,
+    pub settings: BTreeMap<Setting, ResolvedSetting>,
+    /// Per-widget outcome of the underlying config batch
+    pub report: ConfigBatchReport,
+}
+
+pub enum SettingsError {
+    /// The request can't be mapped onto this camera (400)
+    Invalid(Vec<String>),
+    Controller(ControllerError),
+}
+
+async fn brand_of(controller_state: &ControllerState) -> Brand {
+    controller_state
+        .camera_info
+        .lock()
+        .await
+        .as_ref()
+        .map(|info| Brand::from_manufacturer(&info.manufacturer))
+        .unwrap_or(Brand::Other)
+}
+
+/// Read the camera config and map it to canonical settings
+pub async fn read_settings(controller_state: &ControllerState) -> Result<CanonicalSettings, ControllerError> {
+    let brand = brand_of(controller_state).await;
+    let config = get_camera_config(controller_state).await?;
+    Ok(canonical_settings(&controller_state.camera_id, brand, &config))
+}
+
+fn canonical_settings(camera_id: &str, brand: Brand, config: &Value) -> CanonicalSettings {
+    let mut settings = BTreeMap::new();
+    let mut unsupported = Vec::new();
+    for setting in ALL_SETTINGS {
+        match find_widget(setting, brand, config) {
+            Some((widget, raw, choices)) => {
+                let options = if setting.read_only() {
+                    Vec::new()
+                } else {
+                    choices
+                        .iter()
+                        .map(|choice| SettingOption { raw: choice.clone(), value: parse(setting, widget, choice) })
+                        .collect()
+                };
+                settings.insert(setting, SettingState {
+                    widget: widget.to_string(),
+                    value: parse(setting, widget, &raw),
+                    raw,
+                    unit: setting.unit(),
+                    read_only: setting.read_only(),
+                    options,
+                });
+            }
+            None => unsupported.push(setting),
+        }
+    }
+    CanonicalSettings { camera_id: camera_id.to_string(), brand, settings, unsupported }
+}
+
+/// First widget for `setting` present in the config: name, current value and choices
+fn find_widget(setting: Setting, brand: Brand, config: &Value) -> Option<(&'static str, String, Vec<String>)> {
+    setting.widgets(brand).iter().find_map(|&name| {
+        let widget = config.get(name)?;
+        let raw = match widget.get("value")? {
+            Value::String(s) => s.clone(),
+            Value::Null => return None,
+            other => other.to_string(),
+        };
+        let choices = widget
+            .get("choices")
+            .and_then(|c| c.as_array())
+            .map(|c| {
+                c.iter()
+                    .filter_map(|v| match v {
+                        Value::String(s) => Some(s.clone()),
+                        Value::Number(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some((name, raw, choices))
+    })
+}
+
+/// Map canonical values to this camera's choices and apply them as one batch
+pub async fn apply_settings(
+    controller_state: &ControllerState,
+    request: SettingsRequest,
+) -> Result<SettingsReport, SettingsError> {
+    if request.settings.is_empty() {
+        return Err(SettingsError::Invalid(vec!["settings must not be empty".to_string()]));
+    }
+    let brand = brand_of(controller_state).await;
+    let config = get_camera_config(controller_state).await.map_err(SettingsError::Controller)?;
+
+    let mut resolved = BTreeMap::new();
+    let mut errors = Vec::new();
+    for (setting, value) in &request.settings {
+        match resolve(*setting, brand, &config, value) {
+            Ok((widget, raw, value)) => {
+                resolved.insert(*setting, ResolvedSetting { value, widget: widget.to_string(), raw });
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(SettingsError::Invalid(errors));
+    }
+
+    println!("[settings] Camera {} ({:?}): {}", controller_state.camera_id, brand,
+        resolved.iter().map(|(s, r)| format!("{:?}={} -> {}={}", s, r.value, r.widget, r.raw)).collect::<Vec<_>>().join(", "));
+
+    let batch = ConfigBatchRequest {
+        settings: resolved
+            .values()
+            .map(|r| ConfigBatchEntry { setting: r.widget.clone(), value: r.raw.clone() })
+            .collect(),
+        rollback_on_failure: request.rollback_on_failure,
+    };
+    let report = apply_config_batch(controller_state, batch).await.map_err(SettingsError::Controller)?;
+    Ok(SettingsReport { success: report.success, settings: resolved, report })
+}
+
+/// The widget and raw choice for a requested value, and the request as a canonical value
+fn resolve(
+    setting: Setting,
+    brand: Brand,
+    config: &Value,
+    value: &CanonicalValue,
+) -> Result<(&'static str, String, CanonicalValue), String> {
+    let name = serde_json::to_value(setting).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+    if setting.read_only() {
+        return Err(format!("{} is read-only", name));
+    }
+    let (widget, _, choices) = find_widget(setting, brand, config)
+        .ok_or_else(|| format!("{} is not supported by this camera", name))?;
+
+    // Strings like "1/125", "f/2.8" or "+0.3" are read the same way as camera choices
+    let wanted = match value {
+        CanonicalValue::Named(text) => parse(setting, "", &text.replace('_', " "))
+            .ok_or_else(|| format!("{}: '{}' is not a valid value", name, text))?,
+        number => number.clone(),
+    };
+
+    if choices.is_empty() {
+        // Free-form widget (e.g. Sony ISO): write the value in the camera's format
+        return match &wanted {
+            CanonicalValue::Number(n) => Ok((widget, format_raw(setting, widget, *n), wanted.clone())),
+            CanonicalValue::Named(n) if setting == Setting::Iso && n == "auto" => Ok((widget, "Auto".to_string(), wanted.clone())),
+            CanonicalValue::Named(n) => Err(format!("{}: '{}' can't be set on this camera", name, n)),
+        };
+    }
+
+    let parsed: Vec<(&String, CanonicalValue)> =
+        choices.iter().filter_map(|c| parse(setting, widget, c).map(|v| (c, v))).collect();
+    let matched = match &wanted {
+        CanonicalValue::Named(n) => parsed
+            .iter()
+            .find(|(_, v)| matches!(v, CanonicalValue::Named(c) if c == n))
+            .map(|(raw, _)| *raw),
+        CanonicalValue::Number(n) => parsed
+            .iter()
+            .filter_map(|(raw, v)| match v {
+                CanonicalValue::Number(c) => Some((*raw, distance(setting, *n, *c))),
+                CanonicalValue::Named(_) => None,
+            })
+            .filter(|(_, d)| *d <= tolerance(setting))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(raw, _)| raw),
+    };
+
+    match matched {
+        Some(raw) => Ok((widget, raw.clone(), wanted)),
+        None => {
+            let mut available: Vec<String> = parsed.iter().map(|(_, v)| v.to_string()).collect();
+            available.dedup();
+            Err(format!("{}: {} is not available on this camera (available: {})", name, wanted, available.join(", ")))
+        }
+    }
+}
+
+/// How far apart two numeric values are, in the setting's natural steps
+fn distance(setting: Setting, a: f64, b: f64) -> f64 {
+    match setting {
+        // Stops
+        Setting::Aperture => (2.0 * (a / b).log2()).abs(),
+        Setting::ShutterSpeed => (a / b).log2().abs(),
+        _ => (a - b).abs(),
+    }
+}
+
+/// Largest distance that still counts as the requested value
+fn tolerance(setting: Setting) -> f64 {
+    match setting {
+        Setting::Iso => 0.5,
+        // Less than half a third of a stop, so 1/125 never lands on 1/100
+        Setting::Aperture | Setting::ShutterSpeed | Setting::ExposureCompensation => 0.15,
+        _ => 0.0,
+    }
+}
+
+fn format_raw(setting: Setting, widget: &str, value: f64) -> String {
+    match setting {
+        Setting::ExposureCompensation if widget == "5010" => format!("{}", (value * 1000.0).round() as i64),
+        Setting::Iso => format!("{}", value.round() as u64),
+        Setting::ShutterSpeed if value < 1.0 && value > 0.0 => format!("1/{}", (1.0 / value).round() as u64),
+        _ => format!("{}", value),
+    }
+}
+
+/// Canonical value of a raw choice
+fn parse(setting: Setting, widget: &str, raw: &str) -> Option<CanonicalValue> {
+    let text = raw.trim();
+    let lower = text.to_lowercase();
+    match setting {
+        Setting::Iso => {
+            // Fuji auto ISO presets are "-1".."-3"
+            if lower.starts_with("auto") || lower.starts_with('-') {
+                return Some(CanonicalValue::Named("auto".to_string()));
+            }
+            lower.strip_prefix("iso").unwrap_or(&lower).trim().parse::<u32>().ok().map(|n| CanonicalValue::Number(n as f64))
+        }
+        Setting::Aperture => {
+            let number = lower.strip_prefix("f/").or_else(|| lower.strip_prefix('f')).unwrap_or(&lower);
+            number.trim().parse::<f64>().ok().filter(|n| *n > 0.0).map(CanonicalValue::Number)
+        }
+        Setting::ShutterSpeed => {
+            if lower == "bulb" || lower == "time" {
+                return Some(CanonicalValue::Named("bulb".to_string()));
+            }
+            let number = lower.trim_end_matches(['s', '"']).trim();
+            let seconds = match number.split_once('/') {
+                Some((a, b)) => a.trim().parse::<f64>().ok()? / b.trim().parse::<f64>().ok().filter(|b| *b != 0.0)?,
+                None => number.parse::<f64>().ok()?,
+            };
+            (seconds > 0.0).then(|| CanonicalValue::Number(round_to(seconds, 6)))
+        }
+        Setting::ExposureCompensation => {
+            let number: f64 = lower.trim_start_matches('+').parse().ok()?;
+            let ev = if widget == "5010" { number / 1000.0 } else { number };
+            Some(CanonicalValue::Number(snap_ev(ev)))
+        }
+        Setting::WhiteBalance => white_balance(&lower).map(|n| CanonicalValue::Named(n.to_string())),
+        Setting::DriveMode => drive_mode(&lower).map(|n| CanonicalValue::Named(n.to_string())),
+        Setting::Battery => lower
+            .trim_end_matches('%')
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| (0.0..=100.0).contains(n))
+            .map(CanonicalValue::Number),
+    }
+}
+
+/// Some bodies truncate thirds of a stop ("0.3", "-0.6", "1.6"); report them as thirds
+fn snap_ev(ev: f64) -> f64 {
+    let third = (ev * 3.0).round() / 3.0;
+    let half = (ev * 2.0).round() / 2.0;
+    let snapped = if (ev - third).abs() <= (ev - half).abs() { third } else { half };
+    let snapped = if (ev - snapped).abs() <= 0.1 { snapped } else { ev };
+    round_to(snapped, 2)
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+fn white_balance(lower: &str) -> Option<&'static str> {
+    let name = if lower.contains("fluorescent") {
+        "fluorescent"
+    } else if lower.contains("underwater") {
+        "underwater"
+    } else if lower.contains("temperature") || lower.contains("kelvin") {
+        "color_temperature"
+    } else if lower.contains("preset") || lower.contains("manual") || lower.contains("custom") {
+        "custom"
+    } else if lower.contains("daylight") || lower == "fine" {
+        "daylight"
+    } else if lower.contains("shade") || lower.contains("shadow") {
+        "shade"
+    } else if lower.contains("cloud") {
+        "cloudy"
+    } else if lower.contains("tungsten") || lower.contains("incandescent") {
+        "tungsten"
+    } else if lower.contains("flash") {
+        "flash"
+    } else if lower.starts_with("auto") || lower.starts_with("awb") {
+        "auto"
+    } else {
+        return None;
+    };
+    Some(name)
+}
+
+fn drive_mode(lower: &str) -> Option<&'static str> {
+    let name = if lower.contains("timer") {
+        if lower.contains("continuous") {
+            "self_timer_continuous"
+        } else if lower.contains("10") {
+            "self_timer_10s"
+        } else if lower.contains('2') {
+            "self_timer_2s"
+        } else {
+            "self_timer"
+        }
+    } else if lower.contains("continuous") || lower.contains("burst") {
+        if lower.contains("super high") {
+            "continuous_super_high"
+        } else if lower.contains("high") {
+            "continuous_high"
+        } else if lower.contains("low") {
+            "continuous_low"
+        } else {
+            "continuous"
+        }
+    } else if lower.contains("single") {
+        "single"
+    } else {
+        return None;
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canon() -> Value {
+        serde_json::from_str(include_str!("../../configs/canon.json")).unwrap()
+    }
+
+    fn fuji() -> Value {
+        serde_json::from_str(include_str!("../../configs/fuji.json")).unwrap()
+    }
+
+    fn sony() -> Value {
+        serde_json::from_str(include_str!("../../widgets/sony.json")).unwrap()
+    }
+
+    fn number(n: f64) -> Option<CanonicalValue> {
+        Some(CanonicalValue::Number(n))
+    }
+
+    fn named(name: &str) -> Option<CanonicalValue> {
+        Some(CanonicalValue::Named(name.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let cases = [
+            // ISO
+            (Setting::Iso, "iso", "Auto", named("auto")),
+            (Setting::Iso, "iso", "100", number(100.0)),
+            (Setting::Iso, "iso", "ISO 400", number(400.0)),
+            (Setting::Iso, "iso", "-1", named("auto")),
+            (Setting::Iso, "iso", "-3", named("auto")),
+            (Setting::Iso, "iso", "Unknown value 0001", None),
+            // Aperture
+            (Setting::Aperture, "aperture", "5.6", number(5.6)),
+            (Setting::Aperture, "f-number", "f/5.6", number(5.6)),
+            (Setting::Aperture, "f-number", "f/1", number(1.0)),
+            // Shutter speed
+            (Setting::ShutterSpeed, "shutterspeed", "bulb", named("bulb")),
+            (Setting::ShutterSpeed, "shutterspeed", "Bulb", named("bulb")),
+            (Setting::ShutterSpeed, "shutterspeed", "1/125", number(0.008)),
+            (Setting::ShutterSpeed, "shutterspeed", "0.4", number(0.4)),
+            (Setting::ShutterSpeed, "shutterspeed", "0.4s", number(0.4)),
+            (Setting::ShutterSpeed, "shutterspeed", "1s", number(1.0)),
+            (Setting::ShutterSpeed, "shutterspeed", "32/10", number(3.2)),
+            (Setting::ShutterSpeed, "shutterspeed", "30", number(30.0)),
+            // Exposure compensation
+            (Setting::ExposureCompensation, "exposurecompensation", "0", number(0.0)),
+            (Setting::ExposureCompensation, "exposurecompensation", "-0.6", number(-0.67)),
+            (Setting::ExposureCompensation, "exposurecompensation", "0.3", number(0.33)),
+            (Setting::ExposureCompensation, "exposurecompensation", "1.6", number(1.67)),
+            (Setting::ExposureCompensation, "exposurecompensation", "-4.667", number(-4.67)),
+            (Setting::ExposureCompensation, "exposurecompensation", "+0.5", number(0.5)),
+            (Setting::ExposureCompensation, "5010", "-4667", number(-4.67)),
+            (Setting::ExposureCompensation, "5010", "333", number(0.33)),
+            (Setting::ExposureCompensation, "5010", "1000", number(1.0)),
+            // Battery
+            (Setting::Battery, "batterylevel", "65%", number(65.0)),
+            (Setting::Battery, "5001", "100", number(100.0)),
+            (Setting::Battery, "5001", "150", None),
+            // Named settings
+            (Setting::WhiteBalance, "whitebalance", "Shadow", named("shade")),
+            (Setting::WhiteBalance, "whitebalance", "Unknown value 0008", None),
+            (Setting::DriveMode, "drivemode", "Continuous timer", named("self_timer_continuous")),
+            (Setting::DriveMode, "drivemode", "Manual focus", None),
+        ];
+        for (setting, widget, raw, expected) in cases {
+            assert_eq!(parse(setting, widget, raw), expected, "{:?} {} '{}'", setting, widget, raw);
+        }
+    }
+
+    #[test]
+    fn test_snap_ev() {
+        let cases = [
+            (0.0, 0.0),
+            (0.3, 0.33),
+            (0.6, 0.67),
+            (-0.6, -0.67),
+            (-2.3, -2.33),
+            (0.5, 0.5),
+            (1.5, 1.5),
+            (0.667, 0.67),
+            // Too far from a third or a half to be a truncated step
+            (0.2, 0.2),
+            (1.2, 1.2),
+        ];
+        for (ev, expected) in cases {
+            assert_eq!(snap_ev(ev), expected, "{}", ev);
+        }
+    }
+
+    #[test]
+    fn test_white_balance() {
+        let cases = [
+            // Canon
+            ("Auto", Some("auto")),
+            ("Daylight", Some("daylight")),
+            ("Shadow", Some("shade")),
+            ("Cloudy", Some("cloudy")),
+            ("Tungsten", Some("tungsten")),
+            ("Fluorescent", Some("fluorescent")),
+            ("Flash", Some("flash")),
+            ("Manual", Some("custom")),
+            ("Color Temperature", Some("color_temperature")),
+            ("AWB White", Some("auto")),
+            // Fuji
+            ("Automatic", Some("auto")),
+            ("Fluorescent Lamp 1", Some("fluorescent")),
+            ("Shade", Some("shade")),
+            ("Choose Color Temperature", Some("color_temperature")),
+            ("Preset Custom 1", Some("custom")),
+            ("Unknown value 8020", None),
+            // Sony
+            ("Fluorescent: Daylight", Some("fluorescent")),
+            ("Fluorescent: Warm White", Some("fluorescent")),
+            ("Underwater: Auto", Some("underwater")),
+            ("Preset 2", Some("custom")),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(white_balance(&raw.to_lowercase()), expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_drive_mode() {
+        let cases = [
+            ("Single", Some("single")),
+            ("Super high speed continuous shooting", Some("continuous_super_high")),
+            ("Continuous high speed", Some("continuous_high")),
+            ("Continuous low speed", Some("continuous_low")),
+            ("Continuous", Some("continuous")),
+            ("Burst", Some("continuous")),
+            ("Timer 10 sec", Some("self_timer_10s")),
+            ("Timer 2 sec", Some("self_timer_2s")),
+            ("Continuous timer", Some("self_timer_continuous")),
+            ("Self-timer", Some("self_timer")),
+            ("Bracketing", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(drive_mode(&raw.to_lowercase()), expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut fuji_5010 = fuji();
+        fuji_5010.as_object_mut().unwrap().remove("exposurecompensation");
+
+        let cases = [
+            // Canon lists apertures without the "f/" prefix
+            (Setting::Aperture, Brand::Canon, canon(), CanonicalValue::Number(5.6), "aperture", "5.6", CanonicalValue::Number(5.6)),
+            (Setting::Aperture, Brand::Canon, canon(), CanonicalValue::Named("f/8".into()), "aperture", "8", CanonicalValue::Number(8.0)),
+            (Setting::Aperture, Brand::Fujifilm, fuji(), CanonicalValue::Number(5.6), "f-number", "f/5.6", CanonicalValue::Number(5.6)),
+            (Setting::Aperture, Brand::Sony, sony(), CanonicalValue::Number(2.8), "f-number", "f/2.8", CanonicalValue::Number(2.8)),
+            // ISO
+            (Setting::Iso, Brand::Canon, canon(), CanonicalValue::Named("auto".into()), "iso", "Auto", CanonicalValue::Named("auto".into())),
+            (Setting::Iso, Brand::Fujifilm, fuji(), CanonicalValue::Named("auto".into()), "iso", "-1", CanonicalValue::Named("auto".into())),
+            (Setting::Iso, Brand::Fujifilm, fuji(), CanonicalValue::Number(400.0), "iso", "400", CanonicalValue::Number(400.0)),
+            // Sony ISO is free-form
+            (Setting::Iso, Brand::Sony, sony(), CanonicalValue::Number(400.0), "iso", "400", CanonicalValue::Number(400.0)),
+            (Setting::Iso, Brand::Sony, sony(), CanonicalValue::Named("auto".into()), "iso", "Auto", CanonicalValue::Named("auto".into())),
+            // Shutter speed, within a sixth of a stop
+            (Setting::ShutterSpeed, Brand::Canon, canon(), CanonicalValue::Named("1/125".into()), "shutterspeed", "1/125", CanonicalValue::Number(0.008)),
+            (Setting::ShutterSpeed, Brand::Canon, canon(), CanonicalValue::Named("1/120".into()), "shutterspeed", "1/125", CanonicalValue::Number(0.008333)),
+            (Setting::ShutterSpeed, Brand::Fujifilm, fuji(), CanonicalValue::Number(0.4), "shutterspeed", "0.4s", CanonicalValue::Number(0.4)),
+            (Setting::ShutterSpeed, Brand::Sony, sony(), CanonicalValue::Number(3.2), "shutterspeed", "32/10", CanonicalValue::Number(3.2)),
+            (Setting::ShutterSpeed, Brand::Sony, sony(), CanonicalValue::Named("bulb".into()), "shutterspeed", "Bulb", CanonicalValue::Named("bulb".into())),
+            // Exposure compensation
+            (Setting::ExposureCompensation, Brand::Canon, canon(), CanonicalValue::Number(-0.67), "exposurecompensation", "-0.6", CanonicalValue::Number(-0.67)),
+            (Setting::ExposureCompensation, Brand::Fujifilm, fuji(), CanonicalValue::Number(0.33), "exposurecompensation", "0.333", CanonicalValue::Number(0.33)),
+            (Setting::ExposureCompensation, Brand::Fujifilm, fuji_5010, CanonicalValue::Named("-0.3".into()), "5010", "-333", CanonicalValue::Number(-0.33)),
+            (Setting::ExposureCompensation, Brand::Sony, sony(), CanonicalValue::Number(0.33), "exposurecompensation", "0.33", CanonicalValue::Number(0.33)),
+            // Named settings
+            (Setting::WhiteBalance, Brand::Canon, canon(), CanonicalValue::Named("color_temperature".into()), "whitebalance", "Color Temperature", CanonicalValue::Named("color_temperature".into())),
+            (Setting::WhiteBalance, Brand::Fujifilm, fuji(), CanonicalValue::Named("custom".into()), "whitebalance", "Preset Custom 1", CanonicalValue::Named("custom".into())),
+            (Setting::WhiteBalance, Brand::Sony, sony(), CanonicalValue::Named("fluorescent".into()), "whitebalance", "Fluorescent: Warm White", CanonicalValue::Named("fluorescent".into())),
+            (Setting::DriveMode, Brand::Canon, canon(), CanonicalValue::Named("continuous_high".into()), "drivemode", "Continuous high speed", CanonicalValue::Named("continuous_high".into())),
+            (Setting::DriveMode, Brand::Canon, canon(), CanonicalValue::Named("self_timer_10s".into()), "drivemode", "Timer 10 sec", CanonicalValue::Named("self_timer_10s".into())),
+        ];
+        for (setting, brand, config, value, widget, raw, canonical) in cases {
+            assert_eq!(
+                resolve(setting, brand, &config, &value),
+                Ok((widget, raw.to_string(), canonical)),
+                "{:?} {:?} {}", setting, brand, value
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let cases = [
+            (Setting::Battery, canon(), CanonicalValue::Number(50.0), "battery is read-only"),
+            (Setting::DriveMode, sony(), CanonicalValue::Named("single".into()), "drive_mode is not supported by this camera"),
+            (Setting::WhiteBalance, canon(), CanonicalValue::Named("sunny".into()), "white_balance: 'sunny' is not a valid value"),
+            (Setting::WhiteBalance, canon(), CanonicalValue::Named("underwater".into()), "white_balance: underwater is not available on this camera"),
+            (Setting::Aperture, canon(), CanonicalValue::Number(2.8), "aperture: 2.8 is not available on this camera"),
+            (Setting::Iso, canon(), CanonicalValue::Number(420.0), "iso: 420 is not available on this camera"),
+            (Setting::DriveMode, sony(), CanonicalValue::Named("bulb".into()), "drive_mode is not supported by this camera"),
+        ];
+        for (setting, config, value, expected) in cases {
+            let error = resolve(setting, Brand::Canon, &config, &value).unwrap_err();
+            assert!(error.starts_with(expected), "{:?} {}: {}", setting, value, error);
+        }
+    }
+}
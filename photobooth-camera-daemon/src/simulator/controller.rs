@@ -0,0 +1,504 @@
+//! In-process stand-in for gphoto2-controller
+//!
+//! Creates the same three FIFOs in the camera's runtime dir, reads requests from the
+//! command pipe and writes responses and events to the status pipe in the controller's
+//! line format, so `ControllerState::request` and `monitor_status_pipe` can't tell the
+//! difference. Commands are handled one at a time, like the real controller.
+
+use super::{render_frame, status_fields, SimulatedCamera};
+use crate::controller::{monitor_status_pipe, wait_for_stop, ControllerPaths, ControllerState};
+use serde_json::{Map, Value};
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::pipe;
+use tokio::sync::{watch, Mutex as TokioMutex};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Simulated captures: 1536x1024
+const CAPTURE_BLOCKS: (usize, usize) = (192, 128);
+/// Simulated live view frames: 640x424
+const LIVEVIEW_BLOCKS: (usize, usize) = (80, 53);
+const LIVEVIEW_FRAME_INTERVAL: Duration = Duration::from_millis(66);
+/// Time from trigger until the photo shows up, like a camera writing and downloading the file
+const CAPTURE_DOWNLOAD_DELAY: Duration = Duration::from_millis(300);
+const STATUS_INTERVAL: Duration = Duration::from_secs(3);
+const BURST_MAX_FRAMES: u64 = 100;
+const FOCUS_MAX_STEPS: u64 = 100;
+/// Pause between manual focus steps, as in the controller
+const FOCUS_STEP_DELAY: Duration = Duration::from_millis(100);
+/// Widgets each FOCUS action drives, most common first (see controller/camera_focus.c)
+const FOCUS_WIDGETS: &[(&str, &[&str])] = &[
+    ("autofocus", &["autofocusdrive", "autofocus"]),
+    ("cancel", &["cancelautofocus", "autofocus"]),
+    ("near", &["manualfocusdrive", "manualfocus"]),
+    ("far", &["manualfocusdrive", "manualfocus"]),
+    ("point", &["changeafarea"]),
+    ("zoom", &["eoszoom"]),
+];
+/// Simulated memory card: shots it holds when empty and the size of one shot
+const CARD_CAPACITY_SHOTS: u64 = 999;
+const CARD_MB_PER_SHOT: u64 = 25;
+
+/// Run a simulated controller for one camera until `stop` is set.
+/// Counterpart of `start_controller_process` for simulation mode.
+pub async fn run_simulated_controller(
+    controller_state: ControllerState,
+    ws_tx: tokio::sync::broadcast::Sender<Message>,
+    mut stop: watch::Receiver<bool>,
+    camera: SimulatedCamera,
+) {
+    let camera_id = controller_state.camera_id.clone();
+    let paths = controller_state.paths.clone();
+
+    if let Err(e) = create_pipes(&paths) {
+        eprintln!("[simulator] Camera {}: {}", camera_id, e);
+        return;
+    }
+    // Opened read-write so neither open blocks and the pipes never see EOF
+    // while the daemon reopens them
+    let (status, commands) = match (
+        open_fifo(&paths.status_pipe).and_then(pipe::Sender::from_file),
+        open_fifo(&paths.cmd_pipe).and_then(pipe::Receiver::from_file),
+    ) {
+        (Ok(status), Ok(commands)) => (status, commands),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("[simulator] Camera {}: failed to open pipes: {}", camera_id, e);
+            return;
+        }
+    };
+
+    println!(
+        "[simulator] Camera {} simulating {} {} ({} fixture)",
+        camera_id, camera.manufacturer, camera.model, camera.fixture
+    );
+
+    let status_monitor = tokio::spawn(monitor_status_pipe(controller_state.clone(), ws_tx));
+    let simulated = SimulatedController {
+        config: Arc::new(TokioMutex::new(camera.config.clone())),
+        camera,
+        paths: paths.clone(),
+        status: Arc::new(TokioMutex::new(status)),
+        streaming: Arc::new(AtomicBool::new(false)),
+        next_photo: Arc::new(AtomicU64::new(1)),
+    };
+
+    simulated.announce().await;
+    let status_loop = tokio::spawn(simulated.clone().status_loop());
+    let liveview_loop = tokio::spawn(simulated.clone().liveview_loop());
+
+    tokio::select! {
+        _ = simulated.run(commands) => println!("[simulator] Camera {} controller quit", camera_id),
+        _ = wait_for_stop(&mut stop) => println!("[simulator] Stopping simulated camera {}", camera_id),
+    }
+    status_loop.abort();
+    liveview_loop.abort();
+    status_monitor.abort();
+    controller_state.mark_stopped().await;
+    paths.remove_pipes();
+    let _ = std::fs::remove_file(&paths.stream_pipe);
+}
+
+fn create_pipes(paths: &ControllerPaths) -> Result<(), String> {
+    for dir in [&paths.runtime_dir, &paths.photo_dir] {
+        std::fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+    }
+    for path in [&paths.cmd_pipe, &paths.status_pipe, &paths.stream_pipe] {
+        let _ = std::fs::remove_file(path);
+        mkfifo(path).map_err(|e| format!("failed to create {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+fn open_fifo(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new().read(true).write(true).open(path)
+}
+
+fn mkfifo(path: &Path) -> std::io::Result<()> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: c_path is a valid NUL-terminated path
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o666) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+struct SimulatedController {
+    camera: SimulatedCamera,
+    paths: ControllerPaths,
+    /// Current settings; SETCONFIG changes them
+    config: Arc<TokioMutex<Map<String, Value>>>,
+    status: Arc<TokioMutex<pipe::Sender>>,
+    streaming: Arc<AtomicBool>,
+    next_photo: Arc<AtomicU64>,
+}
+
+impl SimulatedController {
+    /// camera_connecting + camera_connected, as sent once the camera is opened
+    async fn announce(&self) {
+        self.emit(&serde_json::json!({ "type": "camera_connecting", "camera_id": self.camera.camera_id() })).await;
+        let info = self.camera.info();
+        self.emit(&serde_json::json!({
+            "type": "camera_connected",
+            "camera_id": info.id,
+            "manufacturer": info.manufacturer,
+            "model": info.model,
+            "port": info.port,
+            "usb_version": info.usb_version,
+            "serial_number": info.serial_number,
+            "firmware": info.firmware,
+            "lens": info.lens,
+        }))
+        .await;
+    }
+
+    async fn run(&self, commands: pipe::Receiver) {
+        let mut lines = BufReader::new(commands).lines();
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => return,
+                Err(e) => {
+                    eprintln!("[simulator] Camera {}: command pipe error: {}", self.camera.camera_id(), e);
+                    return;
+                }
+            };
+            let request: Value = match serde_json::from_str(line.trim()) {
+                Ok(request) => request,
+                Err(_) => {
+                    // Plain-text commands get no response, as with the real controller
+                    continue;
+                }
+            };
+            let id = request.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+            let cmd = request.get("cmd").and_then(|v| v.as_str()).unwrap_or("");
+            if cmd == "QUIT" {
+                self.respond(id, Ok(Value::Null)).await;
+                return;
+            }
+            let result = self.handle(cmd, &request).await;
+            self.respond(id, result).await;
+        }
+    }
+
+    async fn handle(&self, cmd: &str, request: &Value) -> Result<Value, String> {
+        match cmd {
+            "CAPTURE" => {
+                self.emit_mode("capture").await;
+                let triggered_at_ms = self.capture().await?;
+                self.emit_mode(self.idle_mode()).await;
+                Ok(serde_json::json!({ "triggered_at_ms": triggered_at_ms }))
+            }
+            "BURST" => {
+                let count = request.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+                if !(1..=BURST_MAX_FRAMES).contains(&count) {
+                    return Err(format!("BURST requires 'count' between 1 and {}", BURST_MAX_FRAMES));
+                }
+                let interval = Duration::from_millis(request.get("interval_ms").and_then(|v| v.as_u64()).unwrap_or(0));
+                self.emit_mode("capture").await;
+                let mut frames = Vec::new();
+                for index in 0..count {
+                    if index > 0 {
+                        tokio::time::sleep(interval).await;
+                    }
+                    let triggered_at_ms = self.capture().await?;
+                    frames.push(serde_json::json!({ "index": index, "triggered_at_ms": triggered_at_ms }));
+                }
+                self.emit_mode(self.idle_mode()).await;
+                Ok(serde_json::json!({ "frames": frames, "requested": count }))
+            }
+            "CONFIG" => Ok(Value::Object(self.config.lock().await.clone())),
+            "SETCONFIG" => {
+                let setting = request.get("setting").and_then(|v| v.as_str()).unwrap_or("");
+                let value = request.get("value").and_then(|v| v.as_str()).unwrap_or("");
+                let applied = self.set_config(setting, value).await?;
+                self.emit_status().await;
+                Ok(serde_json::json!({ "success": true, "setting": setting, "value": applied }))
+            }
+            "FOCUS" => self.focus(request).await,
+            "STATUS" => Ok(serde_json::json!({ "mode": self.idle_mode() })),
+            "LIVEVIEW_STREAM_START" => {
+                self.streaming.store(true, Ordering::SeqCst);
+                self.emit_mode("liveview_streaming").await;
+                Ok(Value::Null)
+            }
+            "LIVEVIEW_STREAM_STOP" => {
+                self.streaming.store(false, Ordering::SeqCst);
+                self.emit_mode("idle").await;
+                Ok(Value::Null)
+            }
+            "SWITCH_CAMERA" => {
+                let camera_index = request.get("camera_index").and_then(|v| v.as_u64()).unwrap_or(0);
+                self.emit(&serde_json::json!({ "type": "camera_switched", "camera_index": camera_index })).await;
+                Ok(Value::Null)
+            }
+            "PAUSE_POLLING" => {
+                self.emit(&serde_json::json!({ "type": "polling_paused" })).await;
+                Ok(Value::Null)
+            }
+            "RESUME_POLLING" => {
+                self.emit(&serde_json::json!({ "type": "polling_resumed" })).await;
+                Ok(Value::Null)
+            }
+            "DISCONNECT" => {
+                self.emit(&serde_json::json!({ "type": "polling_stopped" })).await;
+                Ok(Value::Null)
+            }
+            // Simulated cameras never move between USB ports
+            "USB_CHANGED" => Ok(Value::Null),
+            other => Err(format!("Unknown command: {}", other)),
+        }
+    }
+
+    /// FOCUS: answers like the controller, from the widgets this body has
+    async fn focus(&self, request: &Value) -> Result<Value, String> {
+        let action = request.get("action").and_then(|v| v.as_str()).unwrap_or("");
+        let widget_for = |action: &str| {
+            FOCUS_WIDGETS
+                .iter()
+                .find(|(name, _)| *name == action)
+                .and_then(|(_, widgets)| widgets.iter().find(|w| self.camera.has_widget(w)).copied())
+        };
+
+        if action == "capabilities" {
+            let actions: Map<String, Value> = FOCUS_WIDGETS
+                .iter()
+                .map(|(name, _)| (name.to_string(), widget_for(name).map_or(Value::Null, |w| Value::String(w.to_string()))))
+                .collect();
+            return Ok(Value::Object(actions));
+        }
+        if !FOCUS_WIDGETS.iter().any(|(name, _)| *name == action) {
+            return Err(format!("Unknown FOCUS action: {}", action));
+        }
+        let widget = widget_for(action)
+            .ok_or_else(|| format!("Focus action '{}' is not supported by this camera", action))?;
+        let mut result = serde_json::json!({ "action": action, "widget": widget });
+
+        match action {
+            "near" | "far" => {
+                let steps = request.get("steps").and_then(|v| v.as_u64()).unwrap_or(1);
+                if !(1..=FOCUS_MAX_STEPS).contains(&steps) {
+                    return Err(format!("FOCUS {} requires 'steps' between 1 and {}", action, FOCUS_MAX_STEPS));
+                }
+                tokio::time::sleep(FOCUS_STEP_DELAY * (steps as u32 - 1)).await;
+                result["steps"] = steps.into();
+            }
+            "point" => {
+                if !self.streaming.load(Ordering::SeqCst) {
+                    return Err("Setting the AF point needs live view running".to_string());
+                }
+                let (width, height) = (LIVEVIEW_BLOCKS.0 as u64 * 8, LIVEVIEW_BLOCKS.1 as u64 * 8);
+                let x = request.get("x").and_then(|v| v.as_u64()).unwrap_or(0).min(1000);
+                let y = request.get("y").and_then(|v| v.as_u64()).unwrap_or(0).min(1000);
+                result["value"] = format!("{}x{}", x * (width - 1) / 1000, y * (height - 1) / 1000).into();
+                result["frame"] = serde_json::json!([width, height]);
+            }
+            "zoom" => {
+                let level = request.get("level").and_then(|v| v.as_u64()).unwrap_or(0);
+                if level < 1 {
+                    return Err("FOCUS zoom requires a positive 'level'".to_string());
+                }
+                result["value"] = level.to_string().into();
+            }
+            _ => {}
+        }
+        Ok(result)
+    }
+
+    fn idle_mode(&self) -> &'static str {
+        if self.streaming.load(Ordering::SeqCst) {
+            "liveview_streaming"
+        } else {
+            "idle"
+        }
+    }
+
+    /// Trigger a simulated shot; the photo arrives shortly after, like a real download.
+    /// With a RAW `imageformat` the RAW is delivered first, then the JPEG if the format
+    /// includes one, like a camera in RAW+JPEG mode.
+    async fn capture(&self) -> Result<u128, String> {
+        let triggered_at_ms = crate::group_capture::unix_millis();
+        let number = self.next_photo.fetch_add(1, Ordering::SeqCst);
+        let stem = format!("{}{:04}", self.camera.file_prefix(), number % 10_000);
+        let jpeg = render_frame(
+            self.camera.index,
+            number,
+            CAPTURE_BLOCKS.0,
+            CAPTURE_BLOCKS.1,
+            &format!("photobooth simulator: {} {} shot {}", self.camera.manufacturer, self.camera.model, number),
+        );
+        let format = self
+            .config
+            .lock()
+            .await
+            .get("imageformat")
+            .and_then(|w| w.get("value"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_uppercase();
+        let raw = format.contains("RAW");
+
+        tokio::time::sleep(CAPTURE_DOWNLOAD_DELAY).await;
+        if raw {
+            let raw_extension = self.camera.raw_extension();
+            let raw_file = fake_raw(raw_extension, &jpeg);
+            self.save_photo(&format!("{}.{}", stem, raw_extension), &raw_file).await?;
+            if !format.contains("JPEG") {
+                return Ok(triggered_at_ms);
+            }
+            tokio::time::sleep(CAPTURE_DOWNLOAD_DELAY).await;
+        }
+        self.save_photo(&format!("{}.JPG", stem), &jpeg).await?;
+        Ok(triggered_at_ms)
+    }
+
+    /// Write a photo to the photo dir and announce it
+    async fn save_photo(&self, name: &str, data: &[u8]) -> Result<(), String> {
+        let path = self.paths.photo_dir.join(name);
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+        self.emit(&serde_json::json!({
+            "type": "photo_downloaded",
+            "file_path": path.to_string_lossy(),
+            "camera_path": format!("/store_00020001/DCIM/100SIM/{}", name),
+        }))
+        .await;
+        Ok(())
+    }
+
+    /// Apply a setting the way the controller does: choices match case-insensitively
+    async fn set_config(&self, setting: &str, value: &str) -> Result<String, String> {
+        let mut config = self.config.lock().await;
+        let widget = config
+            .get_mut(setting)
+            .and_then(|w| w.as_object_mut())
+            .ok_or_else(|| format!("Failed to set {}: Widget not found", setting))?;
+
+        let choices: Vec<String> = widget
+            .get("choices")
+            .and_then(|c| c.as_array())
+            .map(|c| c.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        let applied = if choices.is_empty() {
+            value.to_string()
+        } else {
+            choices
+                .into_iter()
+                .find(|c| c.eq_ignore_ascii_case(value))
+                .ok_or_else(|| format!("Failed to set {}: Choice not found in available options", setting))?
+        };
+        widget.insert("value".to_string(), Value::String(applied.clone()));
+        Ok(applied)
+    }
+
+    /// Periodic `{"mode":...,"iso":...}` status, as the controller sends while polling
+    async fn status_loop(self) {
+        loop {
+            tokio::time::sleep(STATUS_INTERVAL).await;
+            self.emit_status().await;
+        }
+    }
+
+    async fn emit_status(&self) {
+        let mut status = status_fields(&*self.config.lock().await);
+        status.insert("mode".to_string(), Value::String(self.idle_mode().to_string()));
+        let taken = self.next_photo.load(Ordering::SeqCst) - 1;
+        let shots_remaining = CARD_CAPACITY_SHOTS.saturating_sub(taken);
+        status.insert("shots_remaining".to_string(), shots_remaining.into());
+        status.insert("card_free_mb".to_string(), (shots_remaining * CARD_MB_PER_SHOT).into());
+        self.emit(&Value::Object(status)).await;
+    }
+
+    /// Write MJPEG frames to the stream pipe while streaming and a reader is attached
+    async fn liveview_loop(self) {
+        let mut sender: Option<pipe::Sender> = None;
+        let mut frame = 0u64;
+        loop {
+            tokio::time::sleep(LIVEVIEW_FRAME_INTERVAL).await;
+            if !self.streaming.load(Ordering::SeqCst) {
+                sender = None;
+                continue;
+            }
+            if sender.is_none() {
+                // Fails until /api/liveview/ptp-stream opens the pipe for reading
+                sender = pipe::OpenOptions::new().open_sender(&self.paths.stream_pipe).ok();
+            }
+            let Some(stream) = sender.as_mut() else { continue };
+
+            frame += 1;
+            let jpeg = render_frame(self.camera.index, frame, LIVEVIEW_BLOCKS.0, LIVEVIEW_BLOCKS.1, "photobooth simulator live view");
+            let header = format!("--FRAME\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n", jpeg.len());
+            if stream.write_all(header.as_bytes()).await.is_err() || stream.write_all(&jpeg).await.is_err() {
+                // Reader went away; reopen when the next one connects
+                sender = None;
+            }
+        }
+    }
+
+    async fn emit_mode(&self, mode: &str) {
+        self.emit(&serde_json::json!({ "mode": mode })).await;
+    }
+
+    async fn emit(&self, event: &Value) {
+        self.write_line(&event.to_string()).await;
+    }
+
+    /// Response lines must start with `{"type":"response"` (see `ControllerResponse::from_status_line`)
+    async fn respond(&self, id: u64, result: Result<Value, String>) {
+        let line = match result {
+            Ok(result) => format!("{{\"type\":\"response\",\"id\":{},\"ok\":true,\"result\":{}}}", id, result),
+            Err(error) => format!(
+                "{{\"type\":\"response\",\"id\":{},\"ok\":false,\"error\":{}}}",
+                id,
+                Value::String(error)
+            ),
+        };
+        self.write_line(&line).await;
+    }
+
+    async fn write_line(&self, line: &str) {
+        let mut status = self.status.lock().await;
+        if let Err(e) = status.write_all(format!("{}\n", line).as_bytes()).await {
+            eprintln!("[simulator] Camera {}: failed to write status pipe: {}", self.camera.camera_id(), e);
+        }
+    }
+}
+
+/// A RAW file with the capture as its embedded preview: a RAF header for Fujifilm,
+/// otherwise a TIFF whose first IFD points at the JPEG (like ARW/CR2), followed by
+/// stand-in sensor data
+fn fake_raw(extension: &str, jpeg: &[u8]) -> Vec<u8> {
+    const SENSOR_DATA: usize = 64 * 1024;
+    let mut raw = Vec::new();
+    if extension == "RAF" {
+        raw.extend_from_slice(b"FUJIFILMCCD-RAW 0201FF383501");
+        raw.resize(84, 0);
+        let offset = 100u32;
+        raw.extend_from_slice(&offset.to_be_bytes());
+        raw.extend_from_slice(&(jpeg.len() as u32).to_be_bytes());
+        raw.resize(offset as usize, 0);
+    } else {
+        // Header, then IFD0 with JPEGInterchangeFormat and JPEGInterchangeFormatLength
+        let offset = 8u32 + 2 + 2 * 12 + 4;
+        raw.extend_from_slice(b"II*\0");
+        raw.extend_from_slice(&8u32.to_le_bytes());
+        raw.extend_from_slice(&2u16.to_le_bytes());
+        for (tag, value) in [(0x0201u16, offset), (0x0202, jpeg.len() as u32)] {
+            raw.extend_from_slice(&tag.to_le_bytes());
+            raw.extend_from_slice(&4u16.to_le_bytes());
+            raw.extend_from_slice(&1u32.to_le_bytes());
+            raw.extend_from_slice(&value.to_le_bytes());
+        }
+        raw.extend_from_slice(&0u32.to_le_bytes());
+    }
+    raw.extend_from_slice(jpeg);
+    raw.resize(raw.len() + SENSOR_DATA, 0x5A);
+    raw
+}
@@ -0,0 +1,173 @@
+//! Minimal baseline JPEG encoder for simulated captures and live view frames
+//!
+//! Images are built from flat 8x8 blocks, so every block is just its DC coefficient
+//! followed by an end-of-block code. That keeps the encoder tiny (no DCT, no image
+//! crate) while still producing real JPEGs that browsers, the app and exiftool accept.
+
+/// Standard luminance DC table (ITU T.81 Annex K.3), used for all three components
+const DC_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// AC table with a single symbol: end-of-block, coded as one `0` bit
+const AC_BITS: [u8; 16] = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+const AC_VALUES: [u8; 1] = [0x00];
+
+/// Quantizer of 8 makes the quantized DC of a flat block equal to its level-shifted value
+const QUANT: u8 = 8;
+
+/// Image of `width_blocks` x `height_blocks` flat 8x8 blocks
+pub struct BlockImage {
+    width_blocks: usize,
+    height_blocks: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl BlockImage {
+    pub fn new(width_blocks: usize, height_blocks: usize, fill: [u8; 3]) -> Self {
+        Self {
+            width_blocks,
+            height_blocks,
+            pixels: vec![fill; width_blocks * height_blocks],
+        }
+    }
+
+    /// Set one block's colour (out-of-range coordinates are ignored)
+    pub fn set(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        if x < self.width_blocks && y < self.height_blocks {
+            self.pixels[y * self.width_blocks + x] = rgb;
+        }
+    }
+
+    /// Encode as a baseline JFIF JPEG, with `comment` in a COM segment
+    pub fn encode(&self, comment: &str) -> Vec<u8> {
+        let width = (self.width_blocks * 8) as u16;
+        let height = (self.height_blocks * 8) as u16;
+        let mut out = Vec::with_capacity(1024 + self.pixels.len());
+
+        out.extend_from_slice(&[0xFF, 0xD8]);
+        write_segment(&mut out, 0xE0, b"JFIF\0\x01\x01\x00\x00\x01\x00\x01\x00\x00");
+        write_segment(&mut out, 0xFE, comment.as_bytes());
+
+        let mut dqt = vec![0u8];
+        dqt.extend_from_slice(&[QUANT; 64]);
+        write_segment(&mut out, 0xDB, &dqt);
+
+        let mut sof = vec![8];
+        sof.extend_from_slice(&height.to_be_bytes());
+        sof.extend_from_slice(&width.to_be_bytes());
+        sof.push(3);
+        for component in 1..=3u8 {
+            sof.extend_from_slice(&[component, 0x11, 0]);
+        }
+        write_segment(&mut out, 0xC0, &sof);
+
+        let mut dht = vec![0x00];
+        dht.extend_from_slice(&DC_BITS);
+        dht.extend_from_slice(&DC_VALUES);
+        dht.push(0x10);
+        dht.extend_from_slice(&AC_BITS);
+        dht.extend_from_slice(&AC_VALUES);
+        write_segment(&mut out, 0xC4, &dht);
+
+        write_segment(&mut out, 0xDA, &[3, 1, 0x00, 2, 0x00, 3, 0x00, 0, 63, 0]);
+
+        let dc_codes = huffman_codes(&DC_BITS, &DC_VALUES);
+        let mut bits = BitWriter::new(&mut out);
+        let mut predictors = [0i32; 3];
+        for rgb in &self.pixels {
+            for (component, value) in ycbcr(*rgb).into_iter().enumerate() {
+                let dc = value as i32 - 128;
+                let diff = dc - predictors[component];
+                predictors[component] = dc;
+
+                let category = magnitude_category(diff);
+                let (code, length) = dc_codes[category as usize];
+                bits.write(code, length);
+                if category > 0 {
+                    let extra = if diff < 0 { diff + (1 << category) - 1 } else { diff };
+                    bits.write(extra as u16, category);
+                }
+                // End of block
+                bits.write(0, 1);
+            }
+        }
+        bits.flush();
+
+        out.extend_from_slice(&[0xFF, 0xD9]);
+        out
+    }
+}
+
+fn write_segment(out: &mut Vec<u8>, marker: u8, payload: &[u8]) {
+    out.extend_from_slice(&[0xFF, marker]);
+    out.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// JFIF RGB -> YCbCr
+fn ycbcr([r, g, b]: [u8; 3]) -> [u8; 3] {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+    let cb = 128.0 - 0.168_736 * r - 0.331_264 * g + 0.5 * b;
+    let cr = 128.0 + 0.5 * r - 0.418_688 * g - 0.081_312 * b;
+    [y, cb, cr].map(|v| v.round().clamp(0.0, 255.0) as u8)
+}
+
+/// Number of bits needed for |value| (the JPEG "SSSS" category)
+fn magnitude_category(value: i32) -> u8 {
+    (32 - value.unsigned_abs().leading_zeros()) as u8
+}
+
+/// Canonical Huffman codes, indexed by symbol: (code, length)
+fn huffman_codes(bits: &[u8; 16], values: &[u8]) -> Vec<(u16, u8)> {
+    let mut codes = vec![(0u16, 0u8); 256];
+    let mut code = 0u16;
+    let mut symbols = values.iter();
+    for (index, &count) in bits.iter().enumerate() {
+        for _ in 0..count {
+            if let Some(&symbol) = symbols.next() {
+                codes[symbol as usize] = (code, index as u8 + 1);
+            }
+            code += 1;
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+/// Entropy-coded segment writer with 0xFF byte stuffing
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self { out, buffer: 0, count: 0 }
+    }
+
+    fn write(&mut self, value: u16, length: u8) {
+        self.buffer = (self.buffer << length) | (value as u32 & ((1 << length) - 1));
+        self.count += length;
+        while self.count >= 8 {
+            self.count -= 8;
+            self.push_byte((self.buffer >> self.count) as u8);
+        }
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        self.out.push(byte);
+        if byte == 0xFF {
+            self.out.push(0x00);
+        }
+    }
+
+    /// Pad the last byte with 1 bits
+    fn flush(&mut self) {
+        if self.count > 0 {
+            let padding = 8 - self.count;
+            self.write((1 << padding) - 1, padding);
+        }
+    }
+}
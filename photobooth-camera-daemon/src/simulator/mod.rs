@@ -0,0 +1,401 @@
+//! Simulated cameras for running the daemon without hardware
+//!
+//! `PHOTOBOOTH_SIMULATE=canon` (or `--simulate canon`) replaces gphoto2-wrapper and
+//! gphoto2-controller with in-process fakes built from the settings fixtures in
+//! configs/ and widgets/. A comma-separated list (`canon,sony`) simulates several
+//! cameras with ids "0", "1", ...; an entry can also be the path to a config JSON file
+//! in the same shape as configs/canon.json.
+//!
+//! The simulated controller speaks the real pipe protocol, so everything above the
+//! pipes (request routing, event tagging, WebSocket, group/sequence capture, the MJPEG
+//! stream) runs unchanged. Captures produce synthetic JPEGs in the camera's runtime dir.
+
+mod controller;
+mod jpeg;
+
+pub use controller::run_simulated_controller;
+
+use crate::supervisor::DetectedCamera;
+use crate::types::CameraInfo;
+use jpeg::BlockImage;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// Built-in fixtures: name, manufacturer, model, config map, widget tree (if captured)
+const FIXTURES: &[(&str, &str, &str, &str, Option<&str>)] = &[
+    (
+        "canon",
+        "Canon Inc.",
+        "Canon EOS R100",
+        include_str!("../../../configs/canon.json"),
+        Some(include_str!("../../../widgets/canon.json")),
+    ),
+    (
+        "fuji",
+        "Fujifilm",
+        "X-T5",
+        include_str!("../../../configs/fuji.json"),
+        Some(include_str!("../../../widgets/fuji.json")),
+    ),
+    (
+        "sony",
+        "Sony Corporation",
+        "ILCE-7M3",
+        include_str!("../../../widgets/sony.json"),
+        None,
+    ),
+];
+
+/// Widgets the periodic status event reports, with per-brand alternatives
+const STATUS_WIDGETS: &[(&str, &[&str])] = &[
+    ("shootingmode", &["expprogram", "autoexposuremode", "autoexposuremodedial"]),
+    ("battery", &["batterylevel", "5001", "500b"]),
+    ("iso", &["iso"]),
+    ("aperture", &["aperture", "f-number"]),
+    ("shutter", &["shutterspeed", "shutterspeed2"]),
+    ("ev", &["exposurecompensation", "5010"]),
+    ("wb", &["whitebalance"]),
+];
+
+/// One simulated camera, built from a fixture
+#[derive(Clone, Debug)]
+pub struct SimulatedCamera {
+    pub index: u32,
+    pub fixture: String,
+    pub manufacturer: String,
+    pub model: String,
+    /// Initial settings (the simulated controller keeps its own copy it can change)
+    pub config: Map<String, Value>,
+    widgets: Option<Value>,
+}
+
+impl SimulatedCamera {
+    fn from_spec(index: u32, spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if let Some((name, manufacturer, model, config, widgets)) =
+            FIXTURES.iter().find(|(name, ..)| name.eq_ignore_ascii_case(spec))
+        {
+            return Ok(Self {
+                index,
+                fixture: name.to_string(),
+                manufacturer: manufacturer.to_string(),
+                model: model.to_string(),
+                config: parse_config(config, name)?,
+                widgets: widgets
+                    .map(|w| serde_json::from_str(w).map_err(|e| format!("Invalid {} widgets fixture: {}", name, e)))
+                    .transpose()?,
+            });
+        }
+
+        let content = std::fs::read_to_string(spec).map_err(|e| {
+            format!(
+                "Unknown simulator fixture '{}' (expected {} or a config JSON file): {}",
+                spec,
+                FIXTURES.iter().map(|(name, ..)| *name).collect::<Vec<_>>().join(", "),
+                e
+            )
+        })?;
+        let stem = std::path::Path::new(spec)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("custom")
+            .to_string();
+        Ok(Self {
+            index,
+            manufacturer: "Simulated".to_string(),
+            model: format!("Simulated {}", stem),
+            fixture: stem,
+            config: parse_config(&content, spec)?,
+            widgets: None,
+        })
+    }
+
+    pub fn camera_id(&self) -> String {
+        self.index.to_string()
+    }
+
+    /// Fake USB port, unique per simulated camera
+    pub fn port(&self) -> String {
+        format!("usb:001,{:03}", self.index + 2)
+    }
+
+    /// Fake USB port path, what supervisor.rs keys the camera by
+    pub fn usb_path(&self) -> String {
+        format!("1-{}", self.index + 1)
+    }
+
+    pub fn serial_number(&self) -> String {
+        format!("SIM{:08}", self.index + 1)
+    }
+
+    pub fn lens(&self) -> String {
+        self.config
+            .get("lensname")
+            .and_then(|w| w.get("value"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    }
+
+    /// File name prefix the brand's cameras use on the card
+    pub fn file_prefix(&self) -> &'static str {
+        match self.fixture.as_str() {
+            "canon" => "IMG_",
+            "fuji" => "DSCF",
+            "sony" => "DSC0",
+            _ => "SIM_",
+        }
+    }
+
+    /// Extension of the simulated RAW files, matching the brand's real ones
+    pub fn raw_extension(&self) -> &'static str {
+        match self.fixture.as_str() {
+            "canon" => "CR2",
+            "fuji" => "RAF",
+            _ => "ARW",
+        }
+    }
+
+    pub fn info(&self) -> CameraInfo {
+        CameraInfo {
+            id: self.camera_id(),
+            manufacturer: self.manufacturer.clone(),
+            model: self.model.clone(),
+            port: self.port(),
+            usb_version: "2.0".to_string(),
+            serial_number: self.serial_number(),
+            firmware: "1.0.0-sim".to_string(),
+            lens: self.lens(),
+        }
+    }
+
+    /// Output of `gphoto2-wrapper debug` for this camera
+    pub fn debug_info(&self) -> Value {
+        serde_json::json!({
+            "model": self.model,
+            "driver_status": 0,
+            "capture_supported": true,
+            "preview_supported": true,
+            "config_supported": true,
+            "summary": format!(
+                "Manufacturer: {}\nModel: {}\nSerial Number: {}\n(simulated from the {} fixture)",
+                self.manufacturer, self.model, self.serial_number(), self.fixture
+            ),
+            "simulated": true,
+        })
+    }
+
+    /// Whether the camera has a widget, in the captured tree or the settings fixture
+    pub fn has_widget(&self, name: &str) -> bool {
+        fn in_tree(node: &Value, name: &str) -> bool {
+            match node {
+                Value::Object(map) => {
+                    map.get("name").and_then(|n| n.as_str()) == Some(name)
+                        || map.values().any(|child| in_tree(child, name))
+                }
+                Value::Array(items) => items.iter().any(|child| in_tree(child, name)),
+                _ => false,
+            }
+        }
+        self.config.contains_key(name) || self.widgets.as_ref().is_some_and(|tree| in_tree(tree, name))
+    }
+
+    /// Output of `gphoto2-wrapper widgets`: the captured widget tree, or one built
+    /// from the config fixture when no tree was captured for this brand
+    pub fn widgets(&self) -> Value {
+        if let Some(widgets) = &self.widgets {
+            return widgets.clone();
+        }
+        let children: Vec<Value> = self
+            .config
+            .iter()
+            .map(|(name, widget)| {
+                serde_json::json!({
+                    "name": name,
+                    "label": widget.get("label").cloned().unwrap_or_else(|| Value::String(name.clone())),
+                    "type": widget_type_code(widget.get("type").and_then(|t| t.as_str()).unwrap_or("text")),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "widgets": [{
+                "name": "main",
+                "label": "Camera and Driver Configuration",
+                "type": 0,
+                "children": [{
+                    "name": "settings",
+                    "label": "Camera Settings",
+                    "type": 1,
+                    "children": children,
+                }],
+            }]
+        })
+    }
+}
+
+/// Status fields (ISO, aperture, ...) for the controller's periodic `{"mode":...}` event
+pub fn status_fields(config: &Map<String, Value>) -> Map<String, Value> {
+    STATUS_WIDGETS
+        .iter()
+        .map(|(field, widgets)| {
+            let value = widgets
+                .iter()
+                .find_map(|w| config.get(*w)?.get("value")?.as_str())
+                .unwrap_or("");
+            (field.to_string(), Value::String(value.to_string()))
+        })
+        .collect()
+}
+
+/// gphoto2 CameraWidgetType number for a config fixture type name
+fn widget_type_code(type_name: &str) -> u8 {
+    match type_name {
+        "text" => 2,
+        "range" => 3,
+        "toggle" => 4,
+        "radio" => 5,
+        "menu" => 6,
+        "button" => 7,
+        "date" => 8,
+        _ => 2,
+    }
+}
+
+fn parse_config(content: &str, source: &str) -> Result<Map<String, Value>, String> {
+    match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(format!("Simulator config {} is not a JSON object", source)),
+        Err(e) => Err(format!("Invalid simulator config {}: {}", source, e)),
+    }
+}
+
+/// The set of simulated cameras for this daemon run
+#[derive(Debug)]
+pub struct Simulation {
+    cameras: Vec<SimulatedCamera>,
+}
+
+impl Simulation {
+    /// Parse a fixture list such as `canon` or `canon,sony,/path/to/config.json`
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        let cameras = spec
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .enumerate()
+            .map(|(index, s)| SimulatedCamera::from_spec(index as u32, s))
+            .collect::<Result<Vec<_>, _>>()?;
+        if cameras.is_empty() {
+            return Err("Simulator needs at least one fixture".to_string());
+        }
+        Ok(Self { cameras })
+    }
+
+    /// `--simulate[=fixtures]` on the command line, else `[simulation] cameras` in the
+    /// config (or PHOTOBOOTH_SIMULATE).
+    /// A bare `--simulate` simulates one Canon.
+    pub fn from_args_or_config() -> Result<Option<Arc<Self>>, String> {
+        let mut args = std::env::args().skip(1);
+        let mut spec = None;
+        while let Some(arg) = args.next() {
+            if let Some(value) = arg.strip_prefix("--simulate=") {
+                spec = Some(value.to_string());
+            } else if arg == "--simulate" {
+                spec = Some(args.next().filter(|a| !a.starts_with("--")).unwrap_or_else(|| "canon".to_string()));
+            }
+        }
+        let spec = match spec.or_else(|| crate::config::get().simulation.cameras.clone()) {
+            Some(spec) if !spec.trim().is_empty() && spec != "0" => spec,
+            _ => return Ok(None),
+        };
+        Self::from_spec(&spec).map(|s| Some(Arc::new(s)))
+    }
+
+    pub fn cameras(&self) -> &[SimulatedCamera] {
+        &self.cameras
+    }
+
+    pub fn camera(&self, camera_id: &str) -> Option<&SimulatedCamera> {
+        self.cameras.iter().find(|c| c.camera_id() == camera_id)
+    }
+
+    /// Simulated camera at a gphoto2 autodetect index
+    pub fn camera_at(&self, index: u32) -> Option<&SimulatedCamera> {
+        self.cameras.iter().find(|c| c.index == index)
+    }
+
+    /// What `gphoto2-wrapper detect` would report
+    pub fn detected(&self) -> Vec<DetectedCamera> {
+        self.cameras
+            .iter()
+            .map(|c| DetectedCamera {
+                index: c.index,
+                model: c.model.clone(),
+                port: c.port(),
+                usb_path: Some(c.usb_path()),
+            })
+            .collect()
+    }
+
+    /// Short description for startup logs and /api/status
+    pub fn describe(&self) -> Vec<String> {
+        self.cameras
+            .iter()
+            .map(|c| format!("{}: {} {} ({})", c.index, c.manufacturer, c.model, c.fixture))
+            .collect()
+    }
+}
+
+/// Synthetic frame: a per-camera gradient with a bar that moves with `frame` and the
+/// frame number drawn in blocks, so consecutive images are visibly different.
+pub fn render_frame(camera_index: u32, frame: u64, width_blocks: usize, height_blocks: usize, comment: &str) -> Vec<u8> {
+    const DIGITS: [[u8; 5]; 10] = [
+        [0b111, 0b101, 0b101, 0b101, 0b111],
+        [0b010, 0b110, 0b010, 0b010, 0b111],
+        [0b111, 0b001, 0b111, 0b100, 0b111],
+        [0b111, 0b001, 0b111, 0b001, 0b111],
+        [0b101, 0b101, 0b111, 0b001, 0b001],
+        [0b111, 0b100, 0b111, 0b001, 0b111],
+        [0b111, 0b100, 0b111, 0b101, 0b111],
+        [0b111, 0b001, 0b001, 0b001, 0b001],
+        [0b111, 0b101, 0b111, 0b101, 0b111],
+        [0b111, 0b101, 0b111, 0b001, 0b111],
+    ];
+
+    let mut image = BlockImage::new(width_blocks, height_blocks, [0, 0, 0]);
+    let hue = (camera_index as usize * 97) % 256;
+    for y in 0..height_blocks {
+        let shade = (40 + y * 160 / height_blocks.max(1)) as u8;
+        for x in 0..width_blocks {
+            let r = shade / 2 + ((x * 64 / width_blocks.max(1) + hue) % 128) as u8;
+            let b = 200u8.saturating_sub(shade / 2);
+            image.set(x, y, [r, shade, b]);
+        }
+    }
+
+    let bar_x = (frame as usize * 2) % width_blocks.max(1);
+    for y in 0..height_blocks {
+        for dx in 0..3 {
+            image.set(bar_x + dx, y, [240, 240, 240]);
+        }
+    }
+
+    // Frame number, 2x2 blocks per font pixel, top-left
+    let scale = (height_blocks / 40).max(1);
+    for (position, digit) in frame.to_string().bytes().enumerate() {
+        let glyph = DIGITS[(digit - b'0') as usize];
+        let origin_x = 2 + position * 4 * scale;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    for sy in 0..scale {
+                        for sx in 0..scale {
+                            image.set(origin_x + col * scale + sx, 2 + row * scale + sy, [255, 255, 255]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    image.encode(comment)
+}
@@ -3,6 +3,10 @@
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
+use crate::retention::RetentionIndex;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
 /// Get available disk space in bytes for a given path (Unix only)
 #[cfg(unix)]
 pub fn get_available_space(path: &str) -> Result<u64, String> {
@@ -29,34 +33,98 @@ pub fn get_available_space(_path: &str) -> Result<u64, String> {
     Ok(1024 * 1024 * 1024) // 1GB
 }
 
-/// Clean up old photos to free space. Deletes oldest files first until target space is freed.
-/// Returns number of files deleted.
-pub fn cleanup_old_photos(target_free_bytes: u64) -> Result<usize, String> {
-    let photo_dir = std::path::Path::new("/tmp");
-
-    // Get all photo files with their metadata
-    let mut photos: Vec<(std::path::PathBuf, std::fs::Metadata)> = Vec::new();
-
-    match std::fs::read_dir(photo_dir) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(ext) = path.extension() {
-                    let ext_str = ext.to_string_lossy().to_lowercase();
-                    // Only consider image files
-                    if ext_str == "jpg" || ext_str == "jpeg" || ext_str == "png" || ext_str == "raf" || ext_str == "arw" {
-                        if let Ok(metadata) = entry.metadata() {
-                            photos.push((path, metadata));
+/// Directories holding downloaded photos: the photo dir for camera 0 plus
+/// <photo dir>/camera<id> for every additional camera (see ControllerPaths::for_camera).
+pub fn photo_dirs() -> Vec<std::path::PathBuf> {
+    let photo_dir = &crate::config::get().paths.photo_dir;
+    let mut dirs = vec![photo_dir.clone()];
+    if let Ok(entries) = std::fs::read_dir(photo_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Ids are USB port paths ("1-1.2"), or indexes from older versions
+            let is_camera_dir = name
+                .strip_prefix("camera")
+                .map(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.'))
+                .unwrap_or(false);
+            if is_camera_dir && entry.path().is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    dirs
+}
+
+/// Camera id for a photo directory: "0" for the photo dir, id for <photo dir>/camera<id>
+fn camera_id_for_dir(dir: &std::path::Path) -> String {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("camera"))
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| "0".to_string())
+}
+
+/// All downloaded photos across cameras, with their camera id
+pub fn photo_files() -> Result<Vec<(std::path::PathBuf, String, std::fs::Metadata)>, String> {
+    let mut photos = Vec::new();
+
+    for photo_dir in photo_dirs() {
+        let camera_id = camera_id_for_dir(&photo_dir);
+        match std::fs::read_dir(&photo_dir) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if let Some(ext) = path.extension() {
+                        let ext_str = ext.to_string_lossy().to_lowercase();
+                        // Only consider image files
+                        if ext_str == "jpg" || ext_str == "jpeg" || ext_str == "png" || crate::raw::is_raw(&path) {
+                            if let Ok(metadata) = entry.metadata() {
+                                photos.push((path, camera_id.clone(), metadata));
+                            }
                         }
                     }
                 }
             }
+            Err(e) => return Err(format!("Failed to read photo directory {}: {}", photo_dir.display(), e)),
         }
-        Err(e) => return Err(format!("Failed to read photo directory: {}", e)),
     }
 
+    Ok(photos)
+}
+
+/// Bring the retention index in line with the photos on disk
+pub fn sync_retention(retention: &RetentionIndex) -> Result<(), String> {
+    let on_disk: Vec<_> = photo_files()?
+        .into_iter()
+        .map(|(path, camera_id, _)| (path, camera_id))
+        .collect();
+    retention.sync_with_disk(&on_disk);
+    Ok(())
+}
+
+/// Clean up old photos to free space. Deletes the oldest acknowledged files first
+/// (across all cameras) until target space is freed; photos the app hasn't
+/// acknowledged are never deleted.
+/// Returns number of files deleted.
+pub fn cleanup_old_photos(target_free_bytes: u64, retention: &RetentionIndex) -> Result<usize, String> {
+    Ok(evict_acknowledged(photo_files()?, target_free_bytes, retention))
+}
+
+/// Delete acknowledged photos from `files`, oldest first, until `target_free_bytes` are freed
+fn evict_acknowledged(
+    files: Vec<(std::path::PathBuf, String, std::fs::Metadata)>,
+    target_free_bytes: u64,
+    retention: &RetentionIndex,
+) -> usize {
+    // Get all evictable photo files with their metadata
+    let mut photos: Vec<(std::path::PathBuf, std::fs::Metadata)> = files
+        .into_iter()
+        .filter(|(path, _, _)| retention.is_acknowledged(path))
+        .map(|(path, _, metadata)| (path, metadata))
+        .collect();
+
     if photos.is_empty() {
-        return Ok(0);
+        return 0;
     }
 
     // Sort by modification time (oldest first)
@@ -82,7 +150,8 @@ pub fn cleanup_old_photos(target_free_bytes: u64) -> Result<usize, String> {
         let file_size = metadata.len();
         match std::fs::remove_file(&path) {
             Ok(_) => {
-                println!("Deleted old photo: {} ({} bytes)", path.display(), file_size);
+                println!("Deleted acknowledged photo: {} ({} bytes)", path.display(), file_size);
+                retention.forget(&path);
                 deleted_count += 1;
                 freed_space += file_size;
             }
@@ -92,36 +161,65 @@ pub fn cleanup_old_photos(target_free_bytes: u64) -> Result<usize, String> {
         }
     }
 
-    Ok(deleted_count)
+    deleted_count
 }
 
 /// Check available space and cleanup if needed
-/// Ensures at least min_free_mb MB is available
-pub async fn ensure_storage_space(min_free_mb: u64) {
+/// Ensures at least storage.min_free_mb MB is available in the photo dir. If evicting
+/// acknowledged photos isn't enough, broadcasts a storage_low event so the app can
+/// fetch and acknowledge the rest.
+pub async fn ensure_storage_space(retention: &RetentionIndex, ws_tx: &broadcast::Sender<Message>) {
+    let min_free_mb = crate::config::get().storage.min_free_mb;
+    let photo_dir = crate::config::get().paths.photo_dir.to_string_lossy();
     let min_free_bytes = min_free_mb * 1024 * 1024;
 
-    match get_available_space("/tmp") {
+    // Scanning the photo dirs is blocking filesystem work; keep it off the runtime threads
+    let scan = retention.clone();
+    match tokio::task::spawn_blocking(move || sync_retention(&scan)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("Failed to scan photos: {}", e),
+        Err(e) => eprintln!("Photo scan task failed: {}", e),
+    }
+
+    match get_available_space(&photo_dir) {
         Ok(available) => {
             let available_mb = available / (1024 * 1024);
-            println!("Storage: {} MB available in /tmp", available_mb);
+            println!("Storage: {} MB available in {}", available_mb, photo_dir);
 
             if available < min_free_bytes {
                 let needed = min_free_bytes - available;
-                let _needed_mb = needed / (1024 * 1024);
-                println!("WARNING: Low storage! Only {} MB free, need {} MB. Cleaning up old photos...",
+                println!("WARNING: Low storage! Only {} MB free, need {} MB. Cleaning up acknowledged photos...",
                     available_mb, min_free_mb);
 
-                match cleanup_old_photos(needed + (10 * 1024 * 1024)) { // Add 10MB buffer
-                    Ok(count) => {
+                let cleanup = retention.clone();
+                let target = needed + (10 * 1024 * 1024); // Add 10MB buffer
+                match tokio::task::spawn_blocking(move || cleanup_old_photos(target, &cleanup)).await {
+                    Ok(Ok(count)) => {
                         if count > 0 {
-                            println!("Cleaned up {} old photo(s) to free space", count);
-                        } else {
-                            eprintln!("WARNING: No photos to delete, but storage is low!");
+                            println!("Cleaned up {} acknowledged photo(s) to free space", count);
                         }
                     }
-                    Err(e) => {
+                    Ok(Err(e)) => {
                         eprintln!("Failed to cleanup old photos: {}", e);
                     }
+                    Err(e) => {
+                        eprintln!("Cleanup task failed: {}", e);
+                    }
+                }
+
+                let available = get_available_space(&photo_dir).unwrap_or(available);
+                if available < min_free_bytes {
+                    let summary = retention.summary();
+                    eprintln!("WARNING: Storage still low ({} MB free), {} unacknowledged photo(s) ({} bytes) are being kept",
+                        available / (1024 * 1024), summary.unacknowledged_files, summary.unacknowledged_bytes);
+                    let event = serde_json::json!({
+                        "type": "storage_low",
+                        "available_mb": available / (1024 * 1024),
+                        "required_mb": min_free_mb,
+                        "unacknowledged_files": summary.unacknowledged_files,
+                        "unacknowledged_bytes": summary.unacknowledged_bytes,
+                    });
+                    let _ = ws_tx.send(Message::Text(event.to_string().into()));
                 }
             }
         }
@@ -130,3 +228,64 @@ pub async fn ensure_storage_space(min_free_mb: u64) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn evicts_only_acknowledged_photos_oldest_first() {
+        let dir = std::env::temp_dir().join(format!("storage_evict_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let retention = RetentionIndex::in_memory();
+
+        // (name, age in minutes, acknowledged); every file is 100 bytes
+        let photos = [
+            ("oldest_unacked.jpg", 40, false),
+            ("old_acked.jpg", 30, true),
+            ("newer_acked.jpg", 20, true),
+            ("downloaded.jpg", 10, false),
+            ("newest_acked.jpg", 5, true),
+        ];
+        for (name, age, acknowledged) in photos {
+            let path = dir.join(name);
+            std::fs::write(&path, [0u8; 100]).unwrap();
+            let modified = SystemTime::now() - Duration::from_secs(age * 60);
+            std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+            if acknowledged {
+                retention.acknowledge(&path, "0").unwrap();
+            } else {
+                retention.mark_downloaded(&path, "0");
+            }
+        }
+        let files = || -> Vec<_> {
+            let mut files: Vec<_> = std::fs::read_dir(&dir)
+                .unwrap()
+                .flatten()
+                .map(|entry| (entry.path(), "0".to_string(), entry.metadata().unwrap()))
+                .collect();
+            // Directory order must not matter
+            files.sort_by(|a, b| b.0.cmp(&a.0));
+            files
+        };
+        let remaining = || -> Vec<String> {
+            let mut names: Vec<String> =
+                std::fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+            names.sort();
+            names
+        };
+
+        // 150 bytes takes the two oldest acknowledged photos
+        assert_eq!(evict_acknowledged(files(), 150, &retention), 2);
+        assert_eq!(remaining(), ["downloaded.jpg", "newest_acked.jpg", "oldest_unacked.jpg"]);
+        assert!(retention.get(&dir.join("old_acked.jpg")).is_none());
+
+        // Unacknowledged photos stay however much space is needed
+        assert_eq!(evict_acknowledged(files(), u64::MAX, &retention), 1);
+        assert_eq!(remaining(), ["downloaded.jpg", "oldest_unacked.jpg"]);
+        assert_eq!(evict_acknowledged(files(), u64::MAX, &retention), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,540 @@
+//! Supervises one gphoto2-controller per connected camera
+//!
+//! Controllers are keyed by a stable camera id: the physical USB port the camera is
+//! plugged into ("1-1.2", see `DetectedCamera::key`), so a camera keeps its id and its
+//! camera<id> directories when other cameras come and go. The gphoto2 autodetect index
+//! shifts as cameras are added and removed; it is only what a controller opens its
+//! camera with, and is updated with SWITCH_CAMERA when it changes.
+//!
+//! The primary controller (camera "0", using the configured directories) takes the
+//! first camera detected. With a single camera it keeps running and takes whichever
+//! camera is plugged in next (it handles reconnects on its own). Controllers for
+//! further cameras are started when `gphoto2-wrapper detect` reports them and stopped
+//! once they have been missing for a few scans; so is the primary while other cameras
+//! are connected, and it restarts when its camera comes back. USB hotplug events (see
+//! hotplug.rs) trigger an immediate rescan on top of the periodic one.
+//!
+//! In simulation mode the controllers are in-process fakes and detection reports the
+//! simulated cameras.
+
+use crate::alerts::AlertCenter;
+use crate::controller::{start_controller_process, ControllerState};
+use crate::protocol::ControllerCommand;
+use crate::retention::RetentionIndex;
+use crate::photos::DigestCache;
+use crate::simulator::{run_simulated_controller, Simulation};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex as TokioMutex};
+use tokio_tungstenite::tungstenite::Message;
+
+/// How often to look for newly connected cameras
+const DETECT_INTERVAL_SECS: u64 = 5;
+
+/// Consecutive scans a camera may be missing before its controller is stopped.
+/// Cameras briefly drop off the bus while the controller reopens them.
+const MISSING_SCANS_BEFORE_STOP: u32 = 2;
+
+/// The camera that is always supervised, even when nothing is detected
+pub const PRIMARY_CAMERA_ID: &str = "0";
+
+/// Camera as reported by `gphoto2-wrapper detect`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DetectedCamera {
+    pub index: u32,
+    pub model: String,
+    pub port: String,
+    /// Physical USB port path ("1-1.2"), looked up in sysfs after detection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usb_path: Option<String>,
+}
+
+impl DetectedCamera {
+    /// Stable identity of the camera: the USB port path it is plugged into. The gphoto2
+    /// port ("usb:001,005") gets a new device number on every replug, so it is only
+    /// used where sysfs isn't available.
+    pub fn key(&self) -> String {
+        self.usb_path
+            .clone()
+            .unwrap_or_else(|| self.port.replace([':', ','], "-"))
+    }
+}
+
+struct RunningController {
+    state: ControllerState,
+    stop: watch::Sender<bool>,
+    missing_scans: u32,
+    /// Key of the camera it drives; None for the primary until a camera is detected
+    key: Option<String>,
+}
+
+struct Supervised {
+    /// Running controllers, keyed by camera id
+    running: BTreeMap<String, RunningController>,
+    /// Key of the primary camera, kept while its controller is stopped so the camera
+    /// gets camera id "0" again when it comes back
+    primary_key: Option<String>,
+}
+
+impl Supervised {
+    /// Match controllers to the detected cameras. `spawn` starts a controller (camera id,
+    /// key, index); one stops once its camera has been missing for
+    /// `missing_scans_before_stop` scans. Returns the controllers whose camera's index
+    /// changed, which need a SWITCH_CAMERA.
+    fn reconcile(
+        &mut self,
+        detected: &[DetectedCamera],
+        missing_scans_before_stop: u32,
+        mut spawn: impl FnMut(&str, Option<String>, u32) -> RunningController,
+    ) -> Vec<ControllerState> {
+        let mut moved = Vec::new();
+
+        for camera in detected {
+            let key = camera.key();
+            let driving = self
+                .running
+                .iter()
+                .find(|(_, c)| c.key.as_deref() == Some(key.as_str()))
+                .map(|(camera_id, _)| camera_id.clone());
+            let camera_id = match driving {
+                Some(camera_id) => camera_id,
+                None => {
+                    // The primary takes the first camera, and any camera while it is the
+                    // only controller and its own camera is gone
+                    let single_camera = self.running.len() == 1;
+                    let primary_free = self.running.get(PRIMARY_CAMERA_ID).is_some_and(|primary| match &primary.key {
+                        None => true,
+                        Some(primary_key) => single_camera && !detected.iter().any(|c| c.key() == *primary_key),
+                    });
+                    if !primary_free {
+                        let camera_id = if self.primary_key.as_deref() == Some(key.as_str()) {
+                            PRIMARY_CAMERA_ID.to_string()
+                        } else {
+                            key.clone()
+                        };
+                        let controller = spawn(&camera_id, Some(key), camera.index);
+                        self.running.insert(camera_id, controller);
+                        continue;
+                    }
+                    println!("[supervisor] Camera {} is at USB port {}", PRIMARY_CAMERA_ID, key);
+                    self.primary_key = Some(key.clone());
+                    if let Some(primary) = self.running.get_mut(PRIMARY_CAMERA_ID) {
+                        primary.key = Some(key);
+                    }
+                    PRIMARY_CAMERA_ID.to_string()
+                }
+            };
+            let Some(controller) = self.running.get_mut(&camera_id) else {
+                continue;
+            };
+            controller.missing_scans = 0;
+            if controller.state.set_camera_index(camera.index) {
+                moved.push(controller.state.clone());
+            }
+        }
+
+        let single_camera = self.running.len() == 1;
+        let mut stopped = Vec::new();
+        for (camera_id, controller) in self.running.iter_mut() {
+            let Some(key) = &controller.key else { continue };
+            // Alone, the primary waits for a camera to come back instead of stopping
+            if detected.iter().any(|c| c.key() == *key) || (camera_id == PRIMARY_CAMERA_ID && single_camera) {
+                continue;
+            }
+            controller.missing_scans += 1;
+            if controller.missing_scans >= missing_scans_before_stop {
+                println!("[supervisor] Camera {} no longer detected, stopping its controller", camera_id);
+                let _ = controller.stop.send(true);
+                stopped.push(camera_id.clone());
+            }
+        }
+        for camera_id in stopped {
+            self.running.remove(&camera_id);
+        }
+
+        // With every camera gone the primary waits for the next one
+        if self.running.is_empty() {
+            let controller = spawn(PRIMARY_CAMERA_ID, self.primary_key.clone(), 0);
+            self.running.insert(PRIMARY_CAMERA_ID.to_string(), controller);
+        }
+        moved
+    }
+}
+
+/// All running controllers, keyed by camera id
+#[derive(Clone)]
+pub struct Controllers {
+    supervised: Arc<TokioMutex<Supervised>>,
+    ws_tx: tokio::sync::broadcast::Sender<Message>,
+    simulation: Option<Arc<Simulation>>,
+    retention: RetentionIndex,
+    digests: DigestCache,
+    alerts: AlertCenter,
+}
+
+impl Controllers {
+    pub fn new(
+        ws_tx: tokio::sync::broadcast::Sender<Message>,
+        simulation: Option<Arc<Simulation>>,
+        retention: RetentionIndex,
+    ) -> Self {
+        Self {
+            supervised: Arc::new(TokioMutex::new(Supervised {
+                running: BTreeMap::new(),
+                primary_key: None,
+            })),
+            simulation,
+            retention,
+            digests: DigestCache::new(),
+            alerts: AlertCenter::new(ws_tx.clone()),
+            ws_tx,
+        }
+    }
+
+    /// Controller for a camera id, if one is running
+    pub async fn get(&self, camera_id: &str) -> Option<ControllerState> {
+        self.supervised.lock().await.running.get(camera_id).map(|c| c.state.clone())
+    }
+
+    /// All running controllers, ordered by camera id
+    pub async fn all(&self) -> Vec<ControllerState> {
+        self.supervised.lock().await.running.values().map(|c| c.state.clone()).collect()
+    }
+
+    /// Camera id of the controller driving a detected camera, if any
+    pub async fn camera_id_of(&self, camera: &DetectedCamera) -> Option<String> {
+        let key = camera.key();
+        self.supervised
+            .lock()
+            .await
+            .running
+            .iter()
+            .find(|(_, c)| c.key.as_deref() == Some(key.as_str()))
+            .map(|(camera_id, _)| camera_id.clone())
+    }
+
+    /// Camera id for the camera at a gphoto2 port ("usb:001,005"): its controller's, or
+    /// the id its controller will get. Used for camera lists that come from
+    /// `gphoto2-wrapper list`, which numbers cameras by autodetect index.
+    pub async fn camera_id_for_port(&self, port: &str) -> String {
+        let camera = DetectedCamera { index: 0, model: String::new(), port: port.to_string(), usb_path: usb_path_of(port) };
+        if let Some(camera_id) = self.camera_id_of(&camera).await {
+            return camera_id;
+        }
+        let key = camera.key();
+        let supervised = self.supervised.lock().await;
+        match &supervised.primary_key {
+            Some(primary_key) if *primary_key != key => key,
+            _ => PRIMARY_CAMERA_ID.to_string(),
+        }
+    }
+
+    /// Sender for daemon-generated WebSocket events
+    pub fn ws_sender(&self) -> tokio::sync::broadcast::Sender<Message> {
+        self.ws_tx.clone()
+    }
+
+    /// Acknowledgement index shared by every camera's photo dir
+    pub fn retention(&self) -> &RetentionIndex {
+        &self.retention
+    }
+
+    /// SHA-256 of stored photos, for /api/photos and photo ETags
+    pub fn digests(&self) -> &DigestCache {
+        &self.digests
+    }
+
+    /// Camera health alerts for every camera
+    pub fn alerts(&self) -> &AlertCenter {
+        &self.alerts
+    }
+
+    /// Start a controller that opens the camera at `index`
+    fn spawn(&self, camera_id: &str, key: Option<String>, index: u32) -> RunningController {
+        println!(
+            "[supervisor] Starting controller for camera {} (index {})",
+            camera_id, index
+        );
+        let state = ControllerState::new(camera_id, index, self.retention.clone(), self.alerts.clone());
+        let (stop_tx, stop_rx) = watch::channel(false);
+        match self.simulation.as_ref().and_then(|s| s.camera_at(index)) {
+            Some(camera) => {
+                tokio::spawn(run_simulated_controller(state.clone(), self.ws_tx.clone(), stop_rx, camera.clone()));
+            }
+            None => {
+                tokio::spawn(start_controller_process(state.clone(), self.ws_tx.clone(), stop_rx));
+            }
+        }
+        RunningController {
+            state,
+            stop: stop_tx,
+            missing_scans: 0,
+            key,
+        }
+    }
+
+    /// Reconcile running controllers with the cameras currently on the bus, starting
+    /// and stopping controllers and sending SWITCH_CAMERA to the ones whose camera moved
+    async fn reconcile(&self, detected: &[DetectedCamera], missing_scans_before_stop: u32) {
+        let moved = self.supervised.lock().await.reconcile(detected, missing_scans_before_stop, |camera_id, key, index| {
+            self.spawn(camera_id, key, index)
+        });
+        for state in moved {
+            let camera_index = state.camera_index();
+            println!("[supervisor] Camera {} moved to index {}", state.camera_id, camera_index);
+            tokio::spawn(async move {
+                if let Err(e) = state.request(ControllerCommand::SwitchCamera { camera_index }).await {
+                    eprintln!("[supervisor] Camera {}: SWITCH_CAMERA failed: {}", state.camera_id, e);
+                }
+            });
+        }
+    }
+
+    /// Rescan right after a USB hotplug event. When a device was removed, controllers of
+    /// cameras that are gone stop at once instead of after a few scans. The remaining
+    /// controllers get USB_CHANGED so they reopen their camera now rather than on their
+    /// next poll (a replugged camera comes back on a new device number).
+    /// Returns the detected cameras, or None if detection failed.
+    pub async fn rescan(&self, usb_removed: bool) -> Option<Vec<DetectedCamera>> {
+        let detected = match self.detect().await {
+            Ok(detected) => detected,
+            Err(e) => {
+                eprintln!("[supervisor] Camera detection after USB change failed: {}", e);
+                return None;
+            }
+        };
+        let missing_scans_before_stop = if usb_removed { 1 } else { MISSING_SCANS_BEFORE_STOP };
+        self.reconcile(&detected, missing_scans_before_stop).await;
+
+        for controller_state in self.all().await {
+            tokio::spawn(async move {
+                if let Err(e) = controller_state.request(ControllerCommand::UsbChanged).await {
+                    eprintln!("[supervisor] Camera {}: USB_CHANGED failed: {}", controller_state.camera_id, e);
+                }
+            });
+        }
+        Some(detected)
+    }
+
+    async fn detect(&self) -> Result<Vec<DetectedCamera>, String> {
+        match &self.simulation {
+            Some(simulation) => Ok(simulation.detected()),
+            None => detect_cameras().await,
+        }
+    }
+
+    /// Start the primary controller, then keep the set of controllers in sync with
+    /// the connected cameras. Runs forever.
+    pub async fn run(self) {
+        {
+            let controller = self.spawn(PRIMARY_CAMERA_ID, None, 0);
+            self.supervised.lock().await.running.insert(PRIMARY_CAMERA_ID.to_string(), controller);
+        }
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(DETECT_INTERVAL_SECS)).await;
+            match self.detect().await {
+                Ok(detected) => self.reconcile(&detected, MISSING_SCANS_BEFORE_STOP).await,
+                Err(e) => eprintln!("[supervisor] Camera detection failed: {}", e),
+            }
+        }
+    }
+}
+
+/// List cameras on the bus without opening them (safe while controllers hold their cameras)
+pub async fn detect_cameras() -> Result<Vec<DetectedCamera>, String> {
+    let output = tokio::process::Command::new(&crate::config::get().paths.wrapper)
+        .arg("detect")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gphoto2-wrapper detect: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut cameras = parse_detected_cameras(stdout.trim())?;
+    for camera in &mut cameras {
+        camera.usb_path = usb_path_of(&camera.port);
+    }
+    Ok(cameras)
+}
+
+/// Physical USB port path of a gphoto2 port ("usb:001,005" -> "1-1.2"), from the
+/// busnum/devnum of the devices in sysfs
+fn usb_path_of(port: &str) -> Option<String> {
+    let (busnum, devnum) = port.strip_prefix("usb:")?.split_once(',')?;
+    let (busnum, devnum): (u32, u32) = (busnum.parse().ok()?, devnum.parse().ok()?);
+    std::fs::read_dir("/sys/bus/usb/devices").ok()?.flatten().find_map(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Interfaces ("1-1.2:1.0") and root hubs ("usb1") are not ports
+        if name.contains(':') || name.starts_with("usb") {
+            return None;
+        }
+        let read = |file: &str| -> Option<u32> {
+            std::fs::read_to_string(entry.path().join(file)).ok()?.trim().parse().ok()
+        };
+        (read("busnum")? == busnum && read("devnum")? == devnum).then_some(name)
+    })
+}
+
+fn parse_detected_cameras(output: &str) -> Result<Vec<DetectedCamera>, String> {
+    if let Ok(cameras) = serde_json::from_str::<Vec<DetectedCamera>>(output) {
+        return Ok(cameras);
+    }
+    match serde_json::from_str::<serde_json::Value>(output) {
+        Ok(value) => Err(value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unexpected detect output")
+            .to_string()),
+        Err(e) => Err(format!("Failed to parse detect output: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast;
+
+    fn camera(index: u32, usb_path: &str) -> DetectedCamera {
+        DetectedCamera {
+            index,
+            model: "Canon EOS R100".to_string(),
+            port: format!("usb:001,{:03}", index + 5),
+            usb_path: Some(usb_path.to_string()),
+        }
+    }
+
+    fn controller(camera_id: &str, key: Option<String>, index: u32) -> RunningController {
+        let alerts = AlertCenter::new(broadcast::channel(16).0);
+        RunningController {
+            state: ControllerState::new(camera_id, index, RetentionIndex::in_memory(), alerts),
+            stop: watch::channel(false).0,
+            missing_scans: 0,
+            key,
+        }
+    }
+
+    /// Supervisor state right after `Controllers::run` started the primary
+    fn started() -> Supervised {
+        let mut running = BTreeMap::new();
+        running.insert(PRIMARY_CAMERA_ID.to_string(), controller(PRIMARY_CAMERA_ID, None, 0));
+        Supervised { running, primary_key: None }
+    }
+
+    /// One scan; returns the camera ids started and the ones that moved
+    fn scan(supervised: &mut Supervised, detected: &[DetectedCamera], missing_scans_before_stop: u32) -> (Vec<String>, Vec<String>) {
+        let mut started = Vec::new();
+        let moved = supervised.reconcile(detected, missing_scans_before_stop, |camera_id, key, index| {
+            started.push(camera_id.to_string());
+            controller(camera_id, key, index)
+        });
+        (started, moved.into_iter().map(|state| state.camera_id).collect())
+    }
+
+    /// Running controllers as (camera id, key, index)
+    fn running(supervised: &Supervised) -> Vec<(String, Option<String>, u32)> {
+        supervised
+            .running
+            .iter()
+            .map(|(camera_id, c)| (camera_id.clone(), c.key.clone(), c.state.camera_index()))
+            .collect()
+    }
+
+    fn entry(camera_id: &str, key: &str, index: u32) -> (String, Option<String>, u32) {
+        (camera_id.to_string(), Some(key.to_string()), index)
+    }
+
+    #[test]
+    fn test_keeps_controllers_of_detected_cameras() {
+        let mut supervised = started();
+        let detected = [camera(0, "1-1"), camera(1, "1-2")];
+        let (started_ids, moved) = scan(&mut supervised, &detected, MISSING_SCANS_BEFORE_STOP);
+        assert_eq!(started_ids, vec!["1-2"]);
+        assert!(moved.is_empty());
+        assert_eq!(running(&supervised), vec![entry("0", "1-1", 0), entry("1-2", "1-2", 1)]);
+        assert_eq!(supervised.primary_key.as_deref(), Some("1-1"));
+
+        // Nothing changes while the same cameras stay connected
+        for _ in 0..3 {
+            assert_eq!(scan(&mut supervised, &detected, MISSING_SCANS_BEFORE_STOP), (vec![], vec![]));
+        }
+        assert_eq!(running(&supervised), vec![entry("0", "1-1", 0), entry("1-2", "1-2", 1)]);
+    }
+
+    #[test]
+    fn test_stops_after_missing_scans() {
+        let mut supervised = started();
+        scan(&mut supervised, &[camera(0, "1-1"), camera(1, "1-2")], MISSING_SCANS_BEFORE_STOP);
+        let stop = supervised.running["1-2"].stop.subscribe();
+
+        // Missing once is a camera reopening; missing twice is gone
+        scan(&mut supervised, &[camera(0, "1-1")], 2);
+        assert_eq!(running(&supervised).len(), 2);
+        assert!(!*stop.borrow());
+        scan(&mut supervised, &[camera(0, "1-1")], 2);
+        assert_eq!(running(&supervised), vec![entry("0", "1-1", 0)]);
+        assert!(*stop.borrow());
+
+        // Back again: a new controller under the same id
+        let (started_ids, _) = scan(&mut supervised, &[camera(0, "1-1"), camera(1, "1-2")], 2);
+        assert_eq!(started_ids, vec!["1-2"]);
+    }
+
+    #[test]
+    fn test_lone_primary_waits_and_adopts_next_camera() {
+        let mut supervised = started();
+        scan(&mut supervised, &[camera(0, "1-1")], 1);
+        let stop = supervised.running[PRIMARY_CAMERA_ID].stop.subscribe();
+
+        // Alone, the primary keeps running without its camera
+        for _ in 0..3 {
+            assert_eq!(scan(&mut supervised, &[], 1), (vec![], vec![]));
+        }
+        assert_eq!(running(&supervised), vec![entry("0", "1-1", 0)]);
+        assert!(!*stop.borrow());
+
+        // ...and takes whichever camera is plugged in next
+        let (started_ids, _) = scan(&mut supervised, &[camera(0, "2-4")], 1);
+        assert!(started_ids.is_empty());
+        assert_eq!(running(&supervised), vec![entry("0", "2-4", 0)]);
+        assert_eq!(supervised.primary_key.as_deref(), Some("2-4"));
+    }
+
+    #[test]
+    fn test_primary_camera_gets_its_id_back() {
+        let mut supervised = started();
+        scan(&mut supervised, &[camera(0, "1-1"), camera(1, "1-2")], 1);
+
+        // With another camera connected the primary stops like any other controller
+        scan(&mut supervised, &[camera(0, "1-2")], 1);
+        assert_eq!(running(&supervised), vec![entry("1-2", "1-2", 0)]);
+
+        // The primary's camera comes back as camera 0, not as a new camera
+        let (started_ids, _) = scan(&mut supervised, &[camera(0, "1-2"), camera(1, "1-1")], 1);
+        assert_eq!(started_ids, vec!["0"]);
+        assert_eq!(running(&supervised), vec![entry("0", "1-1", 1), entry("1-2", "1-2", 0)]);
+
+        // With every camera gone the primary restarts for its camera
+        scan(&mut supervised, &[], 1);
+        assert_eq!(running(&supervised), vec![entry("0", "1-1", 0)]);
+    }
+
+    #[test]
+    fn test_index_shift_moves_controllers() {
+        let mut supervised = started();
+        scan(&mut supervised, &[camera(0, "1-1"), camera(1, "1-2"), camera(2, "1-3")], 2);
+
+        // Unplugging the first camera renumbers the others
+        let (started_ids, moved) = scan(&mut supervised, &[camera(0, "1-2"), camera(1, "1-3")], 2);
+        assert!(started_ids.is_empty());
+        assert_eq!(moved, vec!["1-2", "1-3"]);
+        assert_eq!(
+            running(&supervised),
+            vec![entry("0", "1-1", 0), entry("1-2", "1-2", 0), entry("1-3", "1-3", 1)]
+        );
+
+        // Reported once, not on every scan
+        let (_, moved) = scan(&mut supervised, &[camera(0, "1-2"), camera(1, "1-3")], 2);
+        assert!(moved.is_empty());
+    }
+}
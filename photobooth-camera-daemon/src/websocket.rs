@@ -1,41 +1,250 @@
 //! WebSocket handling for real-time camera events
+//!
+//! Every event broadcast on `ws_tx` gets a sequence number (`"seq"`) from a single
+//! sequencer task before it reaches clients. Events with a `"type"` (photo_downloaded,
+//! camera_connected, group_captured, ...) are also kept in a bounded replay log; periodic
+//! status snapshots are not, since only the latest one matters.
+//!
+//! Clients resume with `/ws?since=<seq>&epoch=<epoch>`: they get everything after `seq`
+//! still in the log, an `event_gap` notice for anything that was already dropped, then
+//! `replay_complete` with the current seq and the daemon's epoch (which changes on restart,
+//! resetting seq). `/ws?since=latest` skips the replay and only reports the position.
+//! Plain `/ws` behaves as before, apart from the `seq` field.
 
 use hyper_util::rt::TokioIo;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::WebSocketStream;
 use futures_util::{stream::StreamExt, sink::SinkExt};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Replayable events kept for reconnecting clients
+const REPLAY_LOG_CAPACITY: usize = 1000;
+
+/// Producers -> sequencer. Large enough that the sequencer never lags.
+const RAW_CHANNEL_CAPACITY: usize = 1024;
+
+/// Sequencer -> each client. A client that falls further behind is caught up from the log.
+const CLIENT_CHANNEL_CAPACITY: usize = 256;
+
+/// An event with its sequence number, as sent to clients
+#[derive(Clone, Debug)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub text: Arc<str>,
+}
+
+struct ReplayLog {
+    events: VecDeque<SequencedEvent>,
+    /// Last assigned sequence number
+    latest_seq: u64,
+    /// Highest seq of a replayable event that has been dropped from the log
+    evicted_through: u64,
+}
+
+/// Where a reconnecting client left off (`/ws?since=N&epoch=E`)
+#[derive(Clone, Copy, Debug)]
+pub enum ResumeFrom {
+    /// Only report the current position, no replay
+    Latest,
+    Seq { since: u64, epoch: Option<u64> },
+}
+
+impl ResumeFrom {
+    /// Parse the /ws query string; `None` for a plain connection
+    pub fn from_query(query: Option<&str>) -> Option<Self> {
+        let mut since = None;
+        let mut epoch = None;
+        for pair in query?.split('&') {
+            match pair.split_once('=') {
+                Some(("since", value)) => since = Some(value.to_string()),
+                Some(("epoch", value)) => epoch = value.parse().ok(),
+                _ => {}
+            }
+        }
+        match since?.as_str() {
+            "latest" => Some(Self::Latest),
+            value => value.parse().ok().map(|since| Self::Seq { since, epoch }),
+        }
+    }
+}
 
 /// Shared state for WebSocket clients
 #[derive(Clone)]
 pub struct SharedState {
+    /// Producers (status pipes, group/sequence capture) send raw events here
     pub ws_tx: broadcast::Sender<Message>,
+    /// Sequenced events for clients
+    events_tx: broadcast::Sender<SequencedEvent>,
+    log: Arc<StdMutex<ReplayLog>>,
+    /// Identifies this daemon run, so clients can tell a restart (seq reset) from a gap
+    pub epoch: u64,
 }
 
 impl SharedState {
     pub fn new() -> Self {
-        let (tx, _) = broadcast::channel(100);
+        let (tx, _) = broadcast::channel(RAW_CHANNEL_CAPACITY);
+        let (events_tx, _) = broadcast::channel(CLIENT_CHANNEL_CAPACITY);
         Self {
             ws_tx: tx,
+            events_tx,
+            log: Arc::new(StdMutex::new(ReplayLog {
+                events: VecDeque::with_capacity(REPLAY_LOG_CAPACITY),
+                latest_seq: 0,
+                evicted_through: 0,
+            })),
+            epoch: crate::group_capture::unix_millis() as u64,
         }
     }
+
+    /// Start numbering events. Subscribes before returning so nothing sent
+    /// afterwards is missed.
+    pub fn start_sequencer(&self) {
+        let mut raw = self.ws_tx.subscribe();
+        let state = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match raw.recv().await {
+                    Ok(Message::Text(text)) => state.publish(text.as_str()),
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        eprintln!("[ws] Sequencer lagged, {} events were not sequenced", n);
+                        crate::metrics::get().ws_dropped("sequencer_lagged", n);
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    /// Assign the next seq, log the event if it is replayable, and send it to clients
+    fn publish(&self, text: &str) {
+        let mut log = self.log.lock().unwrap();
+        log.latest_seq += 1;
+        let seq = log.latest_seq;
+
+        let (text, replayable) = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Object(mut event)) => {
+                let replayable = event.get("type").and_then(|t| t.as_str()).is_some_and(|t| t != "liveview_frame");
+                event.insert("seq".to_string(), seq.into());
+                (serde_json::Value::Object(event).to_string(), replayable)
+            }
+            _ => (text.to_string(), false),
+        };
+        let event = SequencedEvent { seq, text: text.into() };
+
+        if replayable {
+            if log.events.len() >= REPLAY_LOG_CAPACITY {
+                if let Some(dropped) = log.events.pop_front() {
+                    log.evicted_through = dropped.seq;
+                }
+            }
+            log.events.push_back(event.clone());
+        }
+        let _ = self.events_tx.send(event);
+    }
+
+    /// Subscribe to live events, plus the messages a resuming client needs first.
+    /// Both happen under the log lock so the replay and the live stream neither
+    /// overlap nor leave a hole. Returns the seq the live stream continues after.
+    fn subscribe(&self, resume: Option<ResumeFrom>) -> (broadcast::Receiver<SequencedEvent>, Vec<String>, u64) {
+        let log = self.log.lock().unwrap();
+        let rx = self.events_tx.subscribe();
+        let messages = match resume {
+            None => Vec::new(),
+            Some(ResumeFrom::Latest) => vec![self.replay_complete(&log, 0)],
+            Some(ResumeFrom::Seq { since, epoch }) => {
+                // After a daemon restart every event in the log is new to the client
+                let since = if epoch.is_some_and(|e| e != self.epoch) || since > log.latest_seq { 0 } else { since };
+                let (gap, events) = self.backlog(&log, since, "log_truncated");
+                let replay_complete = self.replay_complete(&log, events.len());
+                gap.into_iter().chain(events).chain([replay_complete]).collect()
+            }
+        };
+        (rx, messages, log.latest_seq)
+    }
+
+    /// Logged events after `since`, plus a gap notice if some of them were already dropped
+    fn backlog(&self, log: &ReplayLog, since: u64, gap_reason: &str) -> (Option<String>, Vec<String>) {
+        let gap = (since < log.evicted_through).then(|| {
+            println!("[ws] Client needs events after seq {}, those up to {} are no longer available", since, log.evicted_through);
+            crate::metrics::get().ws_dropped(gap_reason, log.evicted_through - since);
+            serde_json::json!({
+                "type": "event_gap",
+                "epoch": self.epoch,
+                "first_missing_seq": since + 1,
+                "last_missing_seq": log.evicted_through,
+                "reason": gap_reason,
+            }).to_string()
+        });
+        let events = log.events.iter().filter(|e| e.seq > since).map(|e| e.text.to_string()).collect();
+        (gap, events)
+    }
+
+    fn replay_complete(&self, log: &ReplayLog, replayed: usize) -> String {
+        serde_json::json!({
+            "type": "replay_complete",
+            "epoch": self.epoch,
+            "seq": log.latest_seq,
+            "replayed": replayed,
+        }).to_string()
+    }
+
+    /// Catch up a client whose channel overflowed: what it missed from the log
+    fn catch_up(&self, since: u64) -> (Vec<String>, u64) {
+        let log = self.log.lock().unwrap();
+        let (gap, events) = self.backlog(&log, since, "client_lagged");
+        (gap.into_iter().chain(events).collect(), log.latest_seq)
+    }
 }
 
 /// Handle WebSocket connection
 pub async fn handle_websocket(
     ws_stream: WebSocketStream<TokioIo<hyper::upgrade::Upgraded>>,
     shared_state: SharedState,
+    resume: Option<ResumeFrom>,
 ) {
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-    let mut rx = shared_state.ws_tx.subscribe();
+    crate::metrics::get().ws_client_connected();
+    let (mut rx, backlog, mut last_seq) = shared_state.subscribe(resume);
 
-    println!("WebSocket client connected");
+    match resume {
+        Some(resume) => println!("WebSocket client connected (resume: {:?}, {} messages to replay)", resume, backlog.len()),
+        None => println!("WebSocket client connected"),
+    }
 
     // Task to forward broadcast messages to this client
     let send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            if ws_sender.send(msg).await.is_err() {
-                break;
+        for text in backlog {
+            if ws_sender.send(Message::Text(text.into())).await.is_err() {
+                return;
+            }
+        }
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    // Already delivered by a replay
+                    if event.seq <= last_seq {
+                        continue;
+                    }
+                    last_seq = event.seq;
+                    if ws_sender.send(Message::Text(event.text.to_string().into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    println!("[ws] Client fell {} events behind, catching up from the replay log", n);
+                    let (missed, latest) = shared_state.catch_up(last_seq);
+                    last_seq = latest;
+                    for text in missed {
+                        if ws_sender.send(Message::Text(text.into())).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(RecvError::Closed) => break,
             }
         }
     });
@@ -60,5 +269,6 @@ pub async fn handle_websocket(
         _ = recv_task => {},
     }
 
+    crate::metrics::get().ws_client_disconnected();
     println!("WebSocket client disconnected");
 }
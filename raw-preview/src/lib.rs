@@ -0,0 +1,253 @@
+//! Embedded JPEG previews of camera RAW files
+//!
+//! The app can't decode RAW files, but every format cameras write carries a full-size
+//! JPEG preview: RAF points at it from its header, TIFF-based formats (ARW, NEF, CR2,
+//! DNG, ORF, RW2) from an IFD, and CR3 keeps it in a track of its media container.
+//! Shared by the camera daemon (previews of RAW-only captures) and the app (RAW
+//! ingest), so both pick the same preview from the same file.
+
+pub mod tiff;
+
+use std::path::{Path, PathBuf};
+use tiff::Tiff;
+
+/// Extensions of RAW files cameras write (lowercase)
+pub const RAW_EXTENSIONS: &[&str] = &["raf", "arw", "srf", "sr2", "cr2", "cr3", "nef", "nrw", "dng", "orf", "rw2", "pef", "srw"];
+
+/// Whether a file is a camera RAW, by extension
+pub fn is_raw(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| RAW_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Where a RAW's preview goes: `<stem>.JPG` (lowercase if the RAW's extension is)
+pub fn preview_path(raw: &Path) -> PathBuf {
+    let lowercase = raw
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.chars().all(|c| c.is_ascii_lowercase()));
+    raw.with_extension(if lowercase { "jpg" } else { "JPG" })
+}
+
+/// A JPEG of the same shot next to the RAW (the camera's, or one made earlier)
+pub fn sibling_jpeg(raw: &Path) -> Option<PathBuf> {
+    ["JPG", "jpg", "JPEG", "jpeg"]
+        .iter()
+        .map(|ext| raw.with_extension(ext))
+        .find(|p| p.is_file())
+}
+
+/// Largest baseline or progressive JPEG embedded in a RAW file
+pub fn embedded_jpeg(data: &[u8]) -> Option<&[u8]> {
+    let mut candidates = Vec::new();
+    if data.starts_with(b"FUJIFILMCCD-RAW") {
+        // RAF: big-endian offset of the preview at 84
+        let offset = u32::from_be_bytes(data.get(84..88)?.try_into().ok()?) as usize;
+        candidates.extend(jpeg_at(data, offset));
+    } else if let Some(tiff) = Tiff::parse(data) {
+        candidates.extend(tiff.preview_offsets().into_iter().filter_map(|offset| jpeg_at(data, offset)));
+    }
+    if candidates.is_empty() {
+        // CR3 and anything unrecognised: scan the whole file
+        scan_jpegs(data, &mut candidates);
+    }
+    candidates
+        .into_iter()
+        .max_by_key(|j| (j.pixels, j.data.len()))
+        .map(|j| j.data)
+}
+
+struct EmbeddedJpeg<'a> {
+    data: &'a [u8],
+    pixels: u64,
+}
+
+/// Baseline or progressive JPEG starting at `offset`; lossless JPEG (RAW sensor data
+/// in CR2/DNG) and truncated streams are rejected
+fn jpeg_at(data: &[u8], offset: usize) -> Option<EmbeddedJpeg<'_>> {
+    if data.get(offset..offset + 3)? != [0xFF, 0xD8, 0xFF] {
+        return None;
+    }
+    let mut pos = offset + 2;
+    let mut pixels = None;
+    loop {
+        // Markers may be padded with fill bytes
+        while *data.get(pos)? == 0xFF && *data.get(pos + 1)? == 0xFF {
+            pos += 1;
+        }
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        match marker {
+            0xD9 => {
+                let end = pos + 2;
+                return pixels.map(|pixels| EmbeddedJpeg { data: &data[offset..end], pixels });
+            }
+            0x01 | 0xD0..=0xD7 => {
+                pos += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+        if length < 2 {
+            return None;
+        }
+        match marker {
+            0xC0..=0xC2 => {
+                let height = u16::from_be_bytes([*data.get(pos + 5)?, *data.get(pos + 6)?]) as u64;
+                let width = u16::from_be_bytes([*data.get(pos + 7)?, *data.get(pos + 8)?]) as u64;
+                pixels = Some(width * height);
+            }
+            // Lossless, hierarchical and arithmetic-coded frames
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return None,
+            _ => {}
+        }
+        pos += 2 + length;
+        if marker == 0xDA {
+            // Entropy-coded data runs until a marker other than a stuffed byte or restart
+            loop {
+                if *data.get(pos)? != 0xFF {
+                    pos += 1;
+                    continue;
+                }
+                match *data.get(pos + 1)? {
+                    0x00 | 0xD0..=0xD7 => pos += 2,
+                    0xFF => pos += 1,
+                    _ => break,
+                }
+            }
+        }
+    }
+}
+
+/// Every decodable JPEG in the file, by looking for start-of-image markers
+fn scan_jpegs<'a>(data: &'a [u8], found: &mut Vec<EmbeddedJpeg<'a>>) {
+    let mut pos = 0;
+    while pos + 3 <= data.len() {
+        if data[pos] == 0xFF && data[pos + 1] == 0xD8 && data[pos + 2] == 0xFF {
+            if let Some(jpeg) = jpeg_at(data, pos) {
+                pos += jpeg.data.len();
+                found.push(jpeg);
+                continue;
+            }
+        }
+        pos += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smallest stream `jpeg_at` accepts: SOF, SOS, a few bytes of entropy-coded data
+    fn jpeg(width: u16, height: u16, sof: u8) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, sof, 0x00, 0x0B, 0x08];
+        data.extend(height.to_be_bytes());
+        data.extend(width.to_be_bytes());
+        data.extend([0x01, 0x01, 0x11, 0x00]);
+        data.extend([0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00]);
+        data.extend([0x12, 0x34, 0xFF, 0x00, 0x56, 0xFF, 0xD0, 0x78]);
+        data.extend([0xFF, 0xD9]);
+        data
+    }
+
+    /// TIFF with one IFD of SHORT/LONG entries (tag, kind, value), followed by `tail`
+    /// at offset `tail_offset(entries)`
+    fn tiff(little_endian: bool, entries: &[(u16, u16, u32)], tail: &[u8]) -> Vec<u8> {
+        let u16_bytes = |v: u16| if little_endian { v.to_le_bytes() } else { v.to_be_bytes() };
+        let u32_bytes = |v: u32| if little_endian { v.to_le_bytes() } else { v.to_be_bytes() };
+        let mut data = Vec::new();
+        data.extend(if little_endian { b"II" } else { b"MM" });
+        data.extend(u16_bytes(42));
+        data.extend(u32_bytes(8));
+        data.extend(u16_bytes(entries.len() as u16));
+        for &(tag, kind, value) in entries {
+            data.extend(u16_bytes(tag));
+            data.extend(u16_bytes(kind));
+            data.extend(u32_bytes(1));
+            if kind == 3 {
+                data.extend(u16_bytes(value as u16));
+                data.extend([0, 0]);
+            } else {
+                data.extend(u32_bytes(value));
+            }
+        }
+        data.extend(u32_bytes(0));
+        data.extend(tail);
+        data
+    }
+
+    fn tail_offset(entries: usize) -> u32 {
+        (8 + 2 + entries * 12 + 4) as u32
+    }
+
+    #[test]
+    fn test_tiff_jpeg_strip() {
+        let preview = jpeg(1620, 1080, 0xC0);
+        for little_endian in [true, false] {
+            for compression in [6, 7] {
+                let entries = [(0x0103, 3, compression), (0x0111, 4, tail_offset(2))];
+                let data = tiff(little_endian, &entries, &preview);
+                assert_eq!(embedded_jpeg(&data), Some(&preview[..]), "little endian {}, compression {}", little_endian, compression);
+            }
+        }
+        // Uncompressed strips aren't previews
+        let entries = [(0x0103, 3, 1), (0x0111, 4, tail_offset(2))];
+        let data = tiff(true, &entries, &[0; 64]);
+        assert_eq!(embedded_jpeg(&data), None);
+    }
+
+    #[test]
+    fn test_tiff_jpeg_offset_picks_largest() {
+        let thumbnail = jpeg(160, 120, 0xC0);
+        let preview = jpeg(6000, 4000, 0xC2);
+        let mut tail = thumbnail.clone();
+        tail.extend(&preview);
+        let entries = [
+            (0x0201, 4, tail_offset(3)),
+            (0x0103, 3, 6),
+            (0x0111, 4, tail_offset(3) + thumbnail.len() as u32),
+        ];
+        let data = tiff(true, &entries, &tail);
+        assert_eq!(embedded_jpeg(&data), Some(&preview[..]));
+
+        // Both found by a scan when no IFD points at them
+        let mut data = b"....ftypcrx ....".to_vec();
+        data.extend(&tail);
+        assert_eq!(embedded_jpeg(&data), Some(&preview[..]));
+    }
+
+    #[test]
+    fn test_raf_header() {
+        let preview = jpeg(3000, 2000, 0xC0);
+        let mut data = b"FUJIFILMCCD-RAW 0201FF383501".to_vec();
+        data.resize(84, 0);
+        data.extend(100u32.to_be_bytes());
+        data.extend((preview.len() as u32).to_be_bytes());
+        data.resize(100, 0);
+        data.extend(&preview);
+        assert_eq!(embedded_jpeg(&data), Some(&preview[..]));
+    }
+
+    #[test]
+    fn test_rejects_lossless_and_truncated() {
+        // Lossless JPEG is sensor data, not a preview
+        assert_eq!(embedded_jpeg(&jpeg(6000, 4000, 0xC3)), None);
+        let preview = jpeg(1620, 1080, 0xC0);
+        assert_eq!(embedded_jpeg(&preview[..preview.len() - 2]), None);
+        assert_eq!(embedded_jpeg(&[]), None);
+    }
+
+    #[test]
+    fn test_paths() {
+        assert!(is_raw(Path::new("/photos/DSCF0001.RAF")));
+        assert!(is_raw(Path::new("IMG_0001.cr3")));
+        assert!(!is_raw(Path::new("IMG_0001.JPG")));
+        assert!(!is_raw(Path::new("raf")));
+        assert_eq!(preview_path(Path::new("/photos/DSC00001.ARW")), Path::new("/photos/DSC00001.JPG"));
+        assert_eq!(preview_path(Path::new("/photos/dsc00001.arw")), Path::new("/photos/dsc00001.jpg"));
+    }
+}
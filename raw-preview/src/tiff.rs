@@ -0,0 +1,148 @@
+//! Just enough TIFF to find the images in ARW/NEF/CR2/DNG/ORF/RW2 files
+
+use std::collections::HashSet;
+
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_SUB_IFDS: u16 = 0x014A;
+const TAG_JPEG_OFFSET: u16 = 0x0201;
+const TAG_EXIF_IFD: u16 = 0x8769;
+/// Panasonic RW2: the preview is stored inline as the tag's value
+const TAG_RW2_JPEG: u16 = 0x002E;
+/// Compression values of JPEG strips (old-style and new-style)
+const COMPRESSION_JPEG: [u32; 2] = [6, 7];
+
+pub struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+    first_ifd: usize,
+}
+
+/// One IFD entry: tag, field type, value count and where the values are
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    pub tag: u16,
+    pub kind: u16,
+    pub count: usize,
+    pub position: usize,
+}
+
+impl<'a> Tiff<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        let tiff = Self { data, little_endian, first_ifd: 0 };
+        // 42 for TIFF, "RO"/"RS" for Olympus, 0x55 for Panasonic
+        if !matches!(tiff.u16_at(2)?, 42 | 0x4F52 | 0x5352 | 0x55) {
+            return None;
+        }
+        let first_ifd = tiff.u32_at(4)? as usize;
+        Some(Self { first_ifd, ..tiff })
+    }
+
+    /// Byte order of the file, which is also the order of 16-bit sample data
+    pub fn little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn u16_at(&self, pos: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn u32_at(&self, pos: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    /// Entries of the IFD at `ifd` and the offset of the next IFD
+    fn entries(&self, ifd: usize) -> Option<(Vec<Entry>, usize)> {
+        let count = self.u16_at(ifd)? as usize;
+        let mut entries = Vec::with_capacity(count);
+        for i in 0..count {
+            let at = ifd + 2 + i * 12;
+            let tag = self.u16_at(at)?;
+            let kind = self.u16_at(at + 2)?;
+            let count = self.u32_at(at + 4)? as usize;
+            let size = match kind {
+                1 | 2 | 6 | 7 => 1,
+                3 | 8 => 2,
+                4 | 9 | 11 | 13 => 4,
+                5 | 10 | 12 => 8,
+                _ => continue,
+            };
+            let position = if size * count <= 4 { at + 8 } else { self.u32_at(at + 8)? as usize };
+            entries.push(Entry { tag, kind, count, position });
+        }
+        let next = self.u32_at(ifd + 2 + count * 12).unwrap_or(0) as usize;
+        Some((entries, next))
+    }
+
+    /// Numeric values of an entry (at most `limit`); rationals are divided out
+    pub fn values(&self, entry: &Entry, limit: usize) -> Vec<f64> {
+        (0..entry.count.min(limit))
+            .filter_map(|i| match entry.kind {
+                1 | 7 => self.data.get(entry.position + i).map(|&b| b as f64),
+                3 => self.u16_at(entry.position + i * 2).map(|v| v as f64),
+                4 | 13 => self.u32_at(entry.position + i * 4).map(|v| v as f64),
+                5 => {
+                    let at = entry.position + i * 8;
+                    let (num, den) = (self.u32_at(at)?, self.u32_at(at + 4)?);
+                    (den != 0).then(|| num as f64 / den as f64)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// First value of `tag` in an IFD
+    pub fn first(&self, entries: &[Entry], tag: u16) -> Option<f64> {
+        entries.iter().find(|e| e.tag == tag).and_then(|e| self.values(e, 1).first().copied())
+    }
+
+    /// Every IFD reachable from the first one (chained, SubIFDs and Exif)
+    pub fn ifds(&self) -> Vec<Vec<Entry>> {
+        let mut queue = vec![self.first_ifd];
+        let mut visited = HashSet::new();
+        let mut ifds = Vec::new();
+        while let Some(ifd) = queue.pop() {
+            if ifd == 0 || visited.len() > 32 || !visited.insert(ifd) {
+                continue;
+            }
+            let Some((entries, next)) = self.entries(ifd) else { continue };
+            for entry in &entries {
+                if matches!(entry.tag, TAG_SUB_IFDS | TAG_EXIF_IFD) {
+                    queue.extend(self.values(entry, 64).into_iter().map(|o| o as usize));
+                }
+            }
+            queue.push(next);
+            ifds.push(entries);
+        }
+        ifds
+    }
+
+    /// Offsets of the JPEGs referenced from any IFD
+    pub(crate) fn preview_offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::new();
+        for entries in self.ifds() {
+            for entry in &entries {
+                match entry.tag {
+                    TAG_JPEG_OFFSET => offsets.extend(self.values(entry, 1).first().map(|&o| o as usize)),
+                    TAG_RW2_JPEG => offsets.push(entry.position),
+                    _ => {}
+                }
+            }
+            let compression = self.first(&entries, TAG_COMPRESSION).map(|c| c as u32);
+            let strip = self.first(&entries, TAG_STRIP_OFFSETS);
+            if let (Some(compression), Some(strip)) = (compression, strip) {
+                if COMPRESSION_JPEG.contains(&compression) {
+                    offsets.push(strip as usize);
+                }
+            }
+        }
+        offsets
+    }
+}
@@ -99,6 +99,16 @@ $CC "${WRAPPER_DIR}/controller/camera_filemgmt.c" \
     -c -fPIC -o camera_filemgmt.o
 echo "  camera_filemgmt.o"
 
+$CC "${WRAPPER_DIR}/controller/camera_focus.c" \
+    -I"${WRAPPER_DIR}" -I"${WRAPPER_DIR}/common" \
+    -c -fPIC -o camera_focus.o
+echo "  camera_focus.o"
+
+$CC "${WRAPPER_DIR}/controller/protocol.c" \
+    -I"${WRAPPER_DIR}" -I"${WRAPPER_DIR}/common" \
+    -c -fPIC -o protocol.o
+echo "  protocol.o"
+
 $CC "${WRAPPER_DIR}/controller/streaming.c" \
     -I"${WRAPPER_DIR}" -I"${WRAPPER_DIR}/common" \
     -c -fPIC -o streaming.o
@@ -128,7 +138,7 @@ echo "Compiling gphoto2-controller..."
 $CC "${WRAPPER_DIR}/gphoto2-controller.c" \
     camera-brand.o widget_ops.o \
     camera_open.o camera_storage.o camera_capture.o camera_preview.o \
-    camera_config.o camera_filemgmt.o \
+    camera_config.o camera_filemgmt.o camera_focus.o protocol.o \
     -I"${WRAPPER_DIR}" -I"${WRAPPER_DIR}/common" \
     -I"$SYSROOT/usr/include/gphoto2" \
     -L"$SYSROOT/usr/lib" \
@@ -0,0 +1,291 @@
+use crate::camera_presets::types::{
+    CameraPreset, CameraPresetApplyReport, CameraPresetDiffEntry, CameraPresetSetting, PresetDiffStatus,
+};
+use crate::daemon_auth::{load_daemon_token, with_daemon_auth};
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// Widgets the camera reports but that can't (or shouldn't) be set from a preset
+const READ_ONLY_SETTINGS: &[&str] = &["batterylevel", "5001", "d36b", "lensname"];
+
+/// Mode widgets go first so shutter/aperture/ISO aren't rejected by the old mode
+const MODE_SETTINGS: &[&str] = &["expprogram", "autoexposuremode", "autoexposuremodedial"];
+
+fn get_camera_presets_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let dir = app_data_dir.join("camera_presets");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create camera presets dir: {}", e))?;
+    Ok(dir)
+}
+
+fn load_camera_preset(app: &tauri::AppHandle, preset_id: &str) -> Result<CameraPreset, String> {
+    if preset_id.contains(['/', '\\']) || preset_id.contains("..") {
+        return Err(format!("Invalid preset id: {}", preset_id));
+    }
+    let path = get_camera_presets_dir(app)?.join(format!("{}.json", preset_id));
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read camera preset {}: {}", preset_id, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse camera preset {}: {}", preset_id, e))
+}
+
+/// Daemon URL for a camera endpoint (camera "0" / none needs no query parameter)
+fn daemon_camera_url(daemon_url: &str, path: &str, camera_id: Option<&str>) -> String {
+    match camera_id {
+        Some(id) if !id.is_empty() && id != "0" => format!("{}{}?camera={}", daemon_url, path, id),
+        _ => format!("{}{}", daemon_url, path),
+    }
+}
+
+/// Fetch the camera's current config tree from the daemon
+async fn fetch_camera_config(
+    daemon_url: &str,
+    camera_id: Option<&str>,
+    token: Option<&str>,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let url = daemon_camera_url(daemon_url, "/api/camera/config", camera_id);
+    let response = with_daemon_auth(reqwest::Client::new().get(&url), token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch camera config: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Daemon returned {} for camera config", response.status()));
+    }
+    let config: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse camera config: {}", e))?;
+    match config {
+        serde_json::Value::Object(map) => Ok(map),
+        _ => Err("Unexpected camera config format".to_string()),
+    }
+}
+
+/// Model of the given camera according to the daemon's status (best effort)
+async fn fetch_camera_model(daemon_url: &str, camera_id: Option<&str>, token: Option<&str>) -> Option<String> {
+    let status: serde_json::Value = with_daemon_auth(reqwest::Client::new().get(format!("{}/api/status", daemon_url)), token)
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    let camera_id = camera_id.filter(|id| !id.is_empty()).unwrap_or("0");
+    status
+        .get("cameras")?
+        .as_array()?
+        .iter()
+        .find(|c| c.get("camera_id").and_then(|v| v.as_str()) == Some(camera_id))?
+        .get("model")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+#[tauri::command]
+pub async fn list_camera_presets(app: tauri::AppHandle) -> Result<Vec<CameraPreset>, String> {
+    let presets_dir = get_camera_presets_dir(&app)?;
+    let mut presets = Vec::new();
+
+    let entries = fs::read_dir(&presets_dir)
+        .map_err(|e| format!("Failed to read camera presets dir: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match fs::read_to_string(&path).map(|c| serde_json::from_str::<CameraPreset>(&c)) {
+            Ok(Ok(preset)) => presets.push(preset),
+            Ok(Err(e)) => println!("[camera_presets] Skipping invalid preset {:?}: {}", path, e),
+            Err(e) => println!("[camera_presets] Failed to read {:?}: {}", path, e),
+        }
+    }
+
+    presets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(presets)
+}
+
+#[tauri::command]
+pub async fn save_camera_preset(
+    app: tauri::AppHandle,
+    mut preset: CameraPreset,
+) -> Result<CameraPreset, String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    let presets_dir = get_camera_presets_dir(&app)?;
+
+    if preset.id.is_empty() {
+        preset.id = format!("preset-{}", uuid::Uuid::new_v4());
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    if preset.created_at.is_empty() {
+        preset.created_at = now.clone();
+    }
+    preset.modified_at = now;
+
+    let json = serde_json::to_string_pretty(&preset)
+        .map_err(|e| format!("Failed to serialize camera preset: {}", e))?;
+    fs::write(presets_dir.join(format!("{}.json", preset.id)), json)
+        .map_err(|e| format!("Failed to write camera preset: {}", e))?;
+
+    Ok(preset)
+}
+
+#[tauri::command]
+pub async fn delete_camera_preset(app: tauri::AppHandle, preset_id: String) -> Result<(), String> {
+    // Validates the id and that the preset exists
+    load_camera_preset(&app, &preset_id)?;
+    let path = get_camera_presets_dir(&app)?.join(format!("{}.json", preset_id));
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete camera preset: {}", e))
+}
+
+/// Capture the camera's current settings as a new preset.
+/// `settings` limits the capture to those widgets; by default every writable widget
+/// the daemon reports (ISO, aperture, shutter, EV, WB, focus, metering, ...) is kept.
+#[tauri::command]
+pub async fn capture_camera_preset(
+    app: tauri::AppHandle,
+    daemon_url: String,
+    camera_id: Option<String>,
+    name: String,
+    settings: Option<Vec<String>>,
+) -> Result<CameraPreset, String> {
+    let token = load_daemon_token(&app);
+    let config = fetch_camera_config(&daemon_url, camera_id.as_deref(), token.as_deref()).await?;
+
+    let mut captured: Vec<CameraPresetSetting> = config
+        .iter()
+        .filter(|(setting, _)| match &settings {
+            Some(wanted) => wanted.iter().any(|w| w == *setting),
+            None => !READ_ONLY_SETTINGS.contains(&setting.as_str()),
+        })
+        .filter_map(|(setting, widget)| {
+            Some(CameraPresetSetting {
+                setting: setting.clone(),
+                value: widget.get("value")?.as_str()?.to_string(),
+                label: widget.get("label").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            })
+        })
+        .collect();
+    if captured.is_empty() {
+        return Err("Camera reported none of the requested settings".to_string());
+    }
+    captured.sort_by_key(|s| !MODE_SETTINGS.contains(&s.setting.as_str()));
+
+    println!("[camera_presets] Captured {} setting(s) for preset '{}'", captured.len(), name);
+
+    save_camera_preset(app, CameraPreset {
+        id: String::new(),
+        name,
+        camera_model: fetch_camera_model(&daemon_url, camera_id.as_deref(), token.as_deref()).await,
+        settings: captured,
+        created_at: String::new(),
+        modified_at: String::new(),
+    })
+    .await
+}
+
+/// Compare a preset against the live camera
+#[tauri::command]
+pub async fn diff_camera_preset(
+    app: tauri::AppHandle,
+    daemon_url: String,
+    camera_id: Option<String>,
+    preset_id: String,
+) -> Result<Vec<CameraPresetDiffEntry>, String> {
+    let preset = load_camera_preset(&app, &preset_id)?;
+    let token = load_daemon_token(&app);
+    let config = fetch_camera_config(&daemon_url, camera_id.as_deref(), token.as_deref()).await?;
+
+    Ok(preset
+        .settings
+        .iter()
+        .map(|entry| {
+            let widget = config.get(&entry.setting);
+            let camera_value = widget
+                .and_then(|w| w.get("value"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            let offered = widget
+                .and_then(|w| w.get("choices"))
+                .and_then(|c| c.as_array())
+                .map(|choices| {
+                    choices
+                        .iter()
+                        .filter_map(|c| c.as_str())
+                        .any(|c| c.eq_ignore_ascii_case(&entry.value))
+                })
+                .unwrap_or(true);
+
+            let status = match &camera_value {
+                None => PresetDiffStatus::Missing,
+                Some(v) if *v == entry.value => PresetDiffStatus::Same,
+                Some(_) if !offered => PresetDiffStatus::InvalidChoice,
+                Some(_) => PresetDiffStatus::Changed,
+            };
+
+            CameraPresetDiffEntry {
+                setting: entry.setting.clone(),
+                label: entry
+                    .label
+                    .clone()
+                    .or_else(|| widget.and_then(|w| w.get("label")).and_then(|v| v.as_str()).map(|s| s.to_string())),
+                preset_value: entry.value.clone(),
+                camera_value,
+                status,
+            }
+        })
+        .collect())
+}
+
+/// Apply every setting in a preset in one daemon call.
+/// Unless `rollback_on_failure` is false, a failing setting restores the ones already changed.
+#[tauri::command]
+pub async fn apply_camera_preset(
+    app: tauri::AppHandle,
+    daemon_url: String,
+    camera_id: Option<String>,
+    preset_id: String,
+    rollback_on_failure: Option<bool>,
+) -> Result<CameraPresetApplyReport, String> {
+    let preset = load_camera_preset(&app, &preset_id)?;
+    println!("[camera_presets] Applying preset '{}' ({} settings)", preset.name, preset.settings.len());
+
+    let body = serde_json::json!({
+        "settings": preset
+            .settings
+            .iter()
+            .map(|s| serde_json::json!({ "setting": s.setting, "value": s.value }))
+            .collect::<Vec<_>>(),
+        "rollback_on_failure": rollback_on_failure.unwrap_or(true),
+    });
+
+    let url = daemon_camera_url(&daemon_url, "/api/camera/config/batch", camera_id.as_deref());
+    let token = load_daemon_token(&app);
+    let response = with_daemon_auth(reqwest::Client::new().post(&url), token.as_deref())
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to apply preset: {}", e))?;
+
+    let status = response.status();
+    let value: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse preset apply response: {}", e))?;
+    if !status.is_success() {
+        let error = value.get("error").and_then(|v| v.as_str()).unwrap_or("unknown error");
+        return Err(format!("Daemon rejected preset ({}): {}", status, error));
+    }
+
+    let report: CameraPresetApplyReport = serde_json::from_value(value)
+        .map_err(|e| format!("Unexpected preset apply response: {}", e))?;
+    println!(
+        "[camera_presets] Preset '{}': {} applied, {} unchanged, {} failed, {} rolled back",
+        preset.name, report.applied, report.unchanged, report.failed, report.rolled_back
+    );
+    Ok(report)
+}
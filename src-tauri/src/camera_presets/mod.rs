@@ -0,0 +1,6 @@
+// Camera settings presets: captured from the camera, stored in the app, diffed and applied via the daemon
+
+pub mod types;
+mod commands;
+
+pub use commands::*;
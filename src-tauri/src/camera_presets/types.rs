@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// One camera widget value stored in a preset
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraPresetSetting {
+    /// gphoto2 widget name (e.g. "iso", "shutterspeed", "5010")
+    pub setting: String,
+    pub value: String,
+    /// Human-readable label from the camera, if known
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraPreset {
+    pub id: String,
+    pub name: String,
+    /// Model the preset was captured from (widget names and values are model-specific)
+    #[serde(default)]
+    pub camera_model: Option<String>,
+    /// Applied in this order (exposure mode first, so shutter/aperture stick)
+    pub settings: Vec<CameraPresetSetting>,
+    pub created_at: String,
+    pub modified_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PresetDiffStatus {
+    /// Camera already has the preset value
+    Same,
+    /// Camera has a different value; applying will change it
+    Changed,
+    /// Camera doesn't report this setting
+    Missing,
+    /// Camera doesn't offer the preset value as a choice
+    InvalidChoice,
+}
+
+/// Preset value vs live camera value for one setting
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraPresetDiffEntry {
+    pub setting: String,
+    pub label: Option<String>,
+    pub preset_value: String,
+    pub camera_value: Option<String>,
+    pub status: PresetDiffStatus,
+}
+
+/// Per-setting outcome from the daemon's config batch
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct CameraPresetApplyResult {
+    pub setting: String,
+    pub value: String,
+    pub previous: Option<String>,
+    /// unchanged | applied | failed | skipped | rolled_back
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct CameraPresetApplyReport {
+    pub success: bool,
+    pub applied: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    pub rolled_back: usize,
+    pub results: Vec<CameraPresetApplyResult>,
+}
@@ -0,0 +1,118 @@
+use crate::asset_library::commands::get_library_dir;
+use crate::collage_renderer::render::render_custom_set;
+use crate::collage_renderer::types::{
+    CollagePhoto, CollageRenderJob, CollageRenderProgress, CollageRenderResult,
+};
+use crate::custom_sets::types::CustomSet;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, RgbaImage};
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+const DEFAULT_JPEG_QUALITY: u8 = 95;
+
+/// Encode the rendered canvas based on the output extension (.png, or .jpg/.jpeg).
+fn save_rendered_collage(
+    canvas: RgbaImage,
+    output_path: &Path,
+    jpeg_quality: u8,
+) -> Result<CollageRenderResult, String> {
+    let ext = output_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+
+    let (width, height) = canvas.dimensions();
+
+    match ext.as_str() {
+        "png" => canvas
+            .save_with_format(output_path, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to write PNG collage: {}", e))?,
+        "jpg" | "jpeg" => {
+            let file = fs::File::create(output_path)
+                .map_err(|e| format!("Failed to create output file: {}", e))?;
+            let rgb = DynamicImage::ImageRgba8(canvas).to_rgb8();
+            JpegEncoder::new_with_quality(BufWriter::new(file), jpeg_quality.clamp(1, 100))
+                .encode_image(&rgb)
+                .map_err(|e| format!("Failed to encode JPEG collage: {}", e))?;
+        }
+        other => return Err(format!("Unsupported collage output format: '{}'", other)),
+    }
+
+    let file_size = fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+
+    Ok(CollageRenderResult {
+        file_path: output_path.to_string_lossy().replace('\\', "/"),
+        width,
+        height,
+        file_size,
+    })
+}
+
+/// Render a custom set with the given session photos and save it as JPEG or PNG.
+/// Photos go into the zones they name, or fill the zones in order. The output format follows the file extension.
+#[tauri::command]
+pub async fn render_collage(
+    app: tauri::AppHandle,
+    custom_set: CustomSet,
+    photos: Vec<CollagePhoto>,
+    output_path: String,
+    jpeg_quality: Option<u8>,
+) -> Result<CollageRenderResult, String> {
+    let library_dir = get_library_dir(&app)?;
+    let quality = jpeg_quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+
+    tokio::task::spawn_blocking(move || {
+        let canvas = render_custom_set(&custom_set, &photos, &library_dir)?;
+        save_rendered_collage(canvas, &PathBuf::from(&output_path), quality)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Re-render many collages from one saved custom set (e.g. a whole event after a template tweak).
+/// Emits `collage-render-progress` after each job. Stops at the first failure.
+#[tauri::command]
+pub async fn render_collages_batch(
+    app: tauri::AppHandle,
+    set_id: String,
+    jobs: Vec<CollageRenderJob>,
+    jpeg_quality: Option<u8>,
+) -> Result<Vec<CollageRenderResult>, String> {
+    let custom_set = crate::custom_sets::get_custom_set(app.clone(), set_id).await?;
+    let library_dir = get_library_dir(&app)?;
+    let quality = jpeg_quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+
+    tokio::task::spawn_blocking(move || {
+        let total = jobs.len();
+        let mut results = Vec::with_capacity(total);
+
+        for (i, job) in jobs.into_iter().enumerate() {
+            println!("[COLLAGE] Rendering {}/{}: {}", i + 1, total, job.output_path);
+            let canvas = render_custom_set(&custom_set, &job.photos, &library_dir)?;
+            let result = save_rendered_collage(canvas, &PathBuf::from(&job.output_path), quality)?;
+
+            let _ = app.emit(
+                "collage-render-progress",
+                CollageRenderProgress {
+                    current: i + 1,
+                    total,
+                    file_path: result.file_path.clone(),
+                },
+            );
+            results.push(result);
+        }
+
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
@@ -0,0 +1,7 @@
+// Server-side collage rendering module
+
+pub mod types;
+pub mod render;
+mod commands;
+
+pub use commands::*;
@@ -0,0 +1,1154 @@
+use crate::asset_library::commands::{asset_file_path, load_registry};
+use crate::asset_library::types::AssetRegistry;
+use crate::collage_renderer::types::{CollagePhoto, PhotoTransform};
+use crate::custom_sets::types::{BackgroundTransform, CustomSet, OverlayLayer};
+use crate::frames::FrameZone;
+use crate::gif_generator::commands::load_and_prepare_image;
+use image::RgbaImage;
+use rayon::prelude::*;
+use std::path::PathBuf;
+
+// ---------------------------------------------------------------------------
+// Geometry
+// ---------------------------------------------------------------------------
+
+/// 2D affine transform mapping (x, y) -> (a*x + c*y + e, b*x + d*y + f)
+#[derive(Clone, Copy, Debug)]
+struct Affine {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl Affine {
+    fn identity() -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    fn translate(tx: f64, ty: f64) -> Self {
+        Self { e: tx, f: ty, ..Self::identity() }
+    }
+
+    fn scale(sx: f64, sy: f64) -> Self {
+        Self { a: sx, d: sy, ..Self::identity() }
+    }
+
+    /// Clockwise rotation in degrees (screen coordinates, y pointing down) — same as CSS `rotate()`
+    fn rotate(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 }
+    }
+
+    /// Apply `self` first, then `next`
+    fn then(self, next: Affine) -> Affine {
+        Affine {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+
+    fn invert(&self) -> Option<Affine> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Affine {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + c * self.f),
+            f: -(b * self.e + d * self.f),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Colors and blending
+// ---------------------------------------------------------------------------
+
+/// Parse a CSS color: #rgb, #rrggbb, #rrggbbaa, rgb(), rgba(), or a few common keywords.
+fn parse_css_color(value: &str) -> Option<[f64; 4]> {
+    let v = value.trim().to_lowercase();
+    if let Some(hex) = v.strip_prefix('#') {
+        let expanded: String = if hex.len() == 3 || hex.len() == 4 {
+            hex.chars().flat_map(|c| [c, c]).collect()
+        } else {
+            hex.to_string()
+        };
+        if expanded.len() != 6 && expanded.len() != 8 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&expanded[i..i + 2], 16).ok();
+        let alpha = if expanded.len() == 8 { channel(6)? } else { 255 };
+        return Some([
+            channel(0)? as f64 / 255.0,
+            channel(2)? as f64 / 255.0,
+            channel(4)? as f64 / 255.0,
+            alpha as f64 / 255.0,
+        ]);
+    }
+    if let Some(args) = v
+        .strip_prefix("rgba(")
+        .or_else(|| v.strip_prefix("rgb("))
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        let parts: Vec<f64> = args
+            .split([',', '/', ' '])
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| s.trim().parse::<f64>().ok())
+            .collect();
+        if parts.len() < 3 {
+            return None;
+        }
+        return Some([
+            parts[0] / 255.0,
+            parts[1] / 255.0,
+            parts[2] / 255.0,
+            parts.get(3).copied().unwrap_or(1.0),
+        ]);
+    }
+    match v.as_str() {
+        "white" => Some([1.0, 1.0, 1.0, 1.0]),
+        "black" => Some([0.0, 0.0, 0.0, 1.0]),
+        "transparent" => Some([0.0, 0.0, 0.0, 0.0]),
+        _ => None,
+    }
+}
+
+fn lum(c: [f64; 3]) -> f64 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn clip_color(c: [f64; 3]) -> [f64; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut out = c;
+    if n < 0.0 {
+        for v in out.iter_mut() {
+            *v = l + (*v - l) * l / (l - n);
+        }
+    }
+    if x > 1.0 {
+        for v in out.iter_mut() {
+            *v = l + (*v - l) * (1.0 - l) / (x - l);
+        }
+    }
+    out
+}
+
+fn set_lum(c: [f64; 3], l: f64) -> [f64; 3] {
+    let d = l - lum(c);
+    clip_color([c[0] + d, c[1] + d, c[2] + d])
+}
+
+fn sat(c: [f64; 3]) -> f64 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: [f64; 3], s: f64) -> [f64; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    if max > min {
+        [
+            (c[0] - min) * s / (max - min),
+            (c[1] - min) * s / (max - min),
+            (c[2] - min) * s / (max - min),
+        ]
+    } else {
+        [0.0, 0.0, 0.0]
+    }
+}
+
+/// Blend modes following CSS `mix-blend-mode` (W3C Compositing and Blending Level 1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    fn from_css(name: &str) -> Self {
+        match name {
+            "multiply" => BlendMode::Multiply,
+            "screen" => BlendMode::Screen,
+            "overlay" => BlendMode::Overlay,
+            "darken" => BlendMode::Darken,
+            "lighten" => BlendMode::Lighten,
+            "color-dodge" => BlendMode::ColorDodge,
+            "color-burn" => BlendMode::ColorBurn,
+            "hard-light" => BlendMode::HardLight,
+            "soft-light" => BlendMode::SoftLight,
+            "difference" => BlendMode::Difference,
+            "exclusion" => BlendMode::Exclusion,
+            "hue" => BlendMode::Hue,
+            "saturation" => BlendMode::Saturation,
+            "color" => BlendMode::Color,
+            "luminosity" => BlendMode::Luminosity,
+            _ => BlendMode::Normal,
+        }
+    }
+
+    fn separable(self, cb: f64, cs: f64) -> f64 {
+        let multiply = |a: f64, b: f64| a * b;
+        let screen = |a: f64, b: f64| a + b - a * b;
+        let hard_light = |cb: f64, cs: f64| {
+            if cs <= 0.5 {
+                multiply(cb, 2.0 * cs)
+            } else {
+                screen(cb, 2.0 * cs - 1.0)
+            }
+        };
+        match self {
+            BlendMode::Multiply => multiply(cb, cs),
+            BlendMode::Screen => screen(cb, cs),
+            BlendMode::Overlay => hard_light(cs, cb),
+            BlendMode::Darken => cb.min(cs),
+            BlendMode::Lighten => cb.max(cs),
+            BlendMode::ColorDodge => {
+                if cb == 0.0 {
+                    0.0
+                } else if cs >= 1.0 {
+                    1.0
+                } else {
+                    (cb / (1.0 - cs)).min(1.0)
+                }
+            }
+            BlendMode::ColorBurn => {
+                if cb >= 1.0 {
+                    1.0
+                } else if cs <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - cb) / cs).min(1.0)
+                }
+            }
+            BlendMode::HardLight => hard_light(cb, cs),
+            BlendMode::SoftLight => {
+                if cs <= 0.5 {
+                    cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+                } else {
+                    let d = if cb <= 0.25 {
+                        ((16.0 * cb - 12.0) * cb + 4.0) * cb
+                    } else {
+                        cb.sqrt()
+                    };
+                    cb + (2.0 * cs - 1.0) * (d - cb)
+                }
+            }
+            BlendMode::Difference => (cb - cs).abs(),
+            BlendMode::Exclusion => cb + cs - 2.0 * cb * cs,
+            _ => cs,
+        }
+    }
+
+    fn blend(self, cb: [f64; 3], cs: [f64; 3]) -> [f64; 3] {
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+            BlendMode::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+            BlendMode::Color => set_lum(cs, lum(cb)),
+            BlendMode::Luminosity => set_lum(cb, lum(cs)),
+            _ => [
+                self.separable(cb[0], cs[0]),
+                self.separable(cb[1], cs[1]),
+                self.separable(cb[2], cs[2]),
+            ],
+        }
+    }
+}
+
+/// Source-over composite of one straight-alpha source pixel onto a backdrop pixel.
+fn composite_pixel(dst: &mut [u8], src: [f64; 3], src_alpha: f64, mode: BlendMode) {
+    if src_alpha <= 0.0 {
+        return;
+    }
+    let cb = [dst[0] as f64 / 255.0, dst[1] as f64 / 255.0, dst[2] as f64 / 255.0];
+    let ab = dst[3] as f64 / 255.0;
+    let blended = mode.blend(cb, src);
+
+    let out_alpha = src_alpha + ab * (1.0 - src_alpha);
+    if out_alpha <= 0.0 {
+        return;
+    }
+    for i in 0..3 {
+        let cs = (1.0 - ab) * src[i] + ab * blended[i];
+        let co = src_alpha * cs + ab * cb[i] * (1.0 - src_alpha);
+        dst[i] = ((co / out_alpha).clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+    dst[3] = (out_alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+}
+
+// ---------------------------------------------------------------------------
+// Sampling and drawing
+// ---------------------------------------------------------------------------
+
+/// Bilinear sample at continuous coordinates (pixel centers at i + 0.5).
+/// Pixels outside the image are treated as transparent so edges are antialiased.
+fn sample_bilinear(img: &RgbaImage, x: f64, y: f64) -> [f64; 4] {
+    let (w, h) = (img.width() as i64, img.height() as i64);
+    let fx = x - 0.5;
+    let fy = y - 0.5;
+    let x0 = fx.floor() as i64;
+    let y0 = fy.floor() as i64;
+    let tx = fx - x0 as f64;
+    let ty = fy - y0 as f64;
+
+    let fetch = |px: i64, py: i64| -> [f64; 4] {
+        if px < 0 || py < 0 || px >= w || py >= h {
+            return [0.0; 4];
+        }
+        let p = img.get_pixel(px as u32, py as u32).0;
+        let a = p[3] as f64 / 255.0;
+        // Premultiply so transparent neighbours don't bleed black into edges
+        [p[0] as f64 / 255.0 * a, p[1] as f64 / 255.0 * a, p[2] as f64 / 255.0 * a, a]
+    };
+
+    let p00 = fetch(x0, y0);
+    let p10 = fetch(x0 + 1, y0);
+    let p01 = fetch(x0, y0 + 1);
+    let p11 = fetch(x0 + 1, y0 + 1);
+
+    let mut out = [0.0; 4];
+    for i in 0..4 {
+        let top = p00[i] * (1.0 - tx) + p10[i] * tx;
+        let bottom = p01[i] * (1.0 - tx) + p11[i] * tx;
+        out[i] = top * (1.0 - ty) + bottom * ty;
+    }
+    out
+}
+
+/// Draw `src` onto `canvas` through `transform` (source pixels -> canvas pixels).
+/// `mask` receives canvas coordinates and returns a coverage factor in [0, 1].
+fn draw_transformed<M>(
+    canvas: &mut RgbaImage,
+    src: &RgbaImage,
+    transform: Affine,
+    opacity: f64,
+    mode: BlendMode,
+    mask: M,
+) where
+    M: Fn(f64, f64) -> f64 + Sync,
+{
+    let inverse = match transform.invert() {
+        Some(inv) => inv,
+        None => return,
+    };
+
+    // Bounding box of the transformed source in canvas space
+    let (sw, sh) = (src.width() as f64, src.height() as f64);
+    let corners = [
+        transform.apply(0.0, 0.0),
+        transform.apply(sw, 0.0),
+        transform.apply(0.0, sh),
+        transform.apply(sw, sh),
+    ];
+    let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min).floor().max(0.0) as u32;
+    let max_x = corners.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max).ceil()
+        .min(canvas.width() as f64) as u32;
+    let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min).floor().max(0.0) as u32;
+    let max_y = corners.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max).ceil()
+        .min(canvas.height() as f64) as u32;
+    if min_x >= max_x || min_y >= max_y {
+        return;
+    }
+
+    let row_len = canvas.width() as usize * 4;
+    let buffer: &mut [u8] = canvas;
+    buffer
+        .par_chunks_mut(row_len)
+        .enumerate()
+        .skip(min_y as usize)
+        .take((max_y - min_y) as usize)
+        .for_each(|(y, row)| {
+            let cy = y as f64 + 0.5;
+            for x in min_x..max_x {
+                let cx = x as f64 + 0.5;
+                let coverage = mask(cx, cy);
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let (sx, sy) = inverse.apply(cx, cy);
+                if sx < -0.5 || sy < -0.5 || sx > sw + 0.5 || sy > sh + 0.5 {
+                    continue;
+                }
+                let s = sample_bilinear(src, sx, sy);
+                if s[3] <= 0.0 {
+                    continue;
+                }
+                let color = [s[0] / s[3], s[1] / s[3], s[2] / s[3]];
+                let px = x as usize * 4;
+                composite_pixel(&mut row[px..px + 4], color, s[3] * opacity * coverage, mode);
+            }
+        });
+}
+
+// ---------------------------------------------------------------------------
+// Zone shapes
+// ---------------------------------------------------------------------------
+
+/// Clip-path polygons used by the canvas for the polygonal zone shapes (fractions of w/h)
+fn zone_polygon(shape: &str) -> Option<&'static [(f64, f64)]> {
+    match shape {
+        "triangle" => Some(&[(0.5, 0.0), (0.0, 1.0), (1.0, 1.0)]),
+        "pentagon" => Some(&[(0.5, 0.0), (1.0, 0.38), (0.82, 1.0), (0.18, 1.0), (0.0, 0.38)]),
+        "hexagon" => Some(&[(0.25, 0.0), (0.75, 0.0), (1.0, 0.5), (0.75, 1.0), (0.25, 1.0), (0.0, 0.5)]),
+        "octagon" => Some(&[
+            (0.3, 0.0), (0.7, 0.0), (1.0, 0.3), (1.0, 0.7),
+            (0.7, 1.0), (0.3, 1.0), (0.0, 0.7), (0.0, 0.3),
+        ]),
+        "star" => Some(&[
+            (0.5, 0.0), (0.61, 0.35), (0.98, 0.35), (0.68, 0.57), (0.79, 0.91),
+            (0.5, 0.7), (0.21, 0.91), (0.32, 0.57), (0.02, 0.35), (0.39, 0.35),
+        ]),
+        "diamond" => Some(&[(0.5, 0.0), (0.78, 0.5), (0.5, 1.0), (0.22, 0.5)]),
+        "heart" => Some(&[
+            (0.5, 0.15), (0.65, 0.0), (0.85, 0.0), (1.0, 0.15), (1.0, 0.35), (0.85, 0.5),
+            (0.5, 1.0), (0.15, 0.5), (0.0, 0.35), (0.0, 0.15), (0.15, 0.0), (0.35, 0.0),
+        ]),
+        "cross" => Some(&[
+            (0.2, 0.0), (0.8, 0.0), (0.8, 0.2), (1.0, 0.2), (1.0, 0.8), (0.8, 0.8),
+            (0.8, 1.0), (0.2, 1.0), (0.2, 0.8), (0.0, 0.8), (0.0, 0.2), (0.2, 0.2),
+        ]),
+        _ => None,
+    }
+}
+
+/// Corner radii (rx, ry) matching the canvas `border-radius` for each zone shape
+fn zone_corner_radii(zone: &FrameZone) -> (f64, f64) {
+    let (w, h) = (zone.width as f64, zone.height as f64);
+    let (rx, ry) = match zone.shape.as_str() {
+        "circle" => (w * 0.5, h * 0.5),
+        "ellipse" => (w * 0.5, h * 0.4),
+        "rounded_rect" => {
+            let r = zone.border_radius.filter(|r| *r > 0).unwrap_or(12) as f64;
+            (r, r)
+        }
+        "pill" => (999.0, 999.0),
+        _ => (2.0, 2.0),
+    };
+    // CSS scales all radii down uniformly when adjacent radii would overlap
+    let factor = (w / (2.0 * rx)).min(h / (2.0 * ry)).min(1.0);
+    (rx * factor, ry * factor)
+}
+
+fn point_in_polygon(points: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (xi, yi) = points[i];
+        let (xj, yj) = points[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn point_in_rounded_rect(w: f64, h: f64, rx: f64, ry: f64, x: f64, y: f64) -> bool {
+    if x < 0.0 || y < 0.0 || x > w || y > h {
+        return false;
+    }
+    if rx <= 0.0 || ry <= 0.0 {
+        return true;
+    }
+    let cx = if x < rx { rx } else if x > w - rx { w - rx } else { return true };
+    let cy = if y < ry { ry } else if y > h - ry { h - ry } else { return true };
+    let dx = (x - cx) / rx;
+    let dy = (y - cy) / ry;
+    dx * dx + dy * dy <= 1.0
+}
+
+/// Precomputed clip shape of a zone in zone-local coordinates
+struct ZoneMask {
+    width: f64,
+    height: f64,
+    polygon: Option<Vec<(f64, f64)>>,
+    radii: (f64, f64),
+    canvas_to_zone: Affine,
+}
+
+impl ZoneMask {
+    fn new(zone: &FrameZone, canvas_to_zone: Affine) -> Self {
+        let (w, h) = (zone.width as f64, zone.height as f64);
+        Self {
+            width: w,
+            height: h,
+            polygon: zone_polygon(&zone.shape)
+                .map(|pts| pts.iter().map(|(px, py)| (px * w, py * h)).collect()),
+            radii: zone_corner_radii(zone),
+            canvas_to_zone,
+        }
+    }
+
+    /// Coverage at canvas coordinates, using 4x4 supersampling for smooth edges
+    fn coverage(&self, x: f64, y: f64) -> f64 {
+        const STEPS: usize = 4;
+        let mut hits = 0;
+        for sy in 0..STEPS {
+            for sx in 0..STEPS {
+                let ox = (sx as f64 + 0.5) / STEPS as f64 - 0.5;
+                let oy = (sy as f64 + 0.5) / STEPS as f64 - 0.5;
+                let (u, v) = self.canvas_to_zone.apply(x + ox, y + oy);
+                let inside = match &self.polygon {
+                    Some(points) => point_in_polygon(points, u, v),
+                    None => point_in_rounded_rect(self.width, self.height, self.radii.0, self.radii.1, u, v),
+                };
+                if inside {
+                    hits += 1;
+                }
+            }
+        }
+        hits as f64 / (STEPS * STEPS) as f64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Layers
+// ---------------------------------------------------------------------------
+
+fn fill_color(canvas: &mut RgbaImage, color: [f64; 4]) {
+    let px = [
+        (color[0] * 255.0).round() as u8,
+        (color[1] * 255.0).round() as u8,
+        (color[2] * 255.0).round() as u8,
+        (color[3] * 255.0).round() as u8,
+    ];
+    for p in canvas.pixels_mut() {
+        p.0 = px;
+    }
+}
+
+/// Render a CSS `linear-gradient(...)` across the whole canvas.
+/// Supports angles (`135deg`), `to <side>` directions and color stops with optional percentages.
+fn fill_linear_gradient(canvas: &mut RgbaImage, css: &str) -> Result<(), String> {
+    let inner = css
+        .trim()
+        .strip_prefix("linear-gradient(")
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(|| format!("Unsupported gradient: {}", css))?;
+
+    // Split on top-level commas (rgb()/rgba() colors contain commas of their own)
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for ch in inner.chars() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    args.push(current.trim().to_string());
+
+    let mut angle = 180.0;
+    let mut stop_args = args.as_slice();
+    if let Some(first) = args.first() {
+        if let Some(deg) = first.strip_suffix("deg") {
+            angle = deg.trim().parse::<f64>().map_err(|_| format!("Invalid gradient angle: {}", first))?;
+            stop_args = &args[1..];
+        } else if let Some(direction) = first.strip_prefix("to ") {
+            angle = match direction.trim() {
+                "top" => 0.0,
+                "right" => 90.0,
+                "bottom" => 180.0,
+                "left" => 270.0,
+                "top right" | "right top" => 45.0,
+                "bottom right" | "right bottom" => 135.0,
+                "bottom left" | "left bottom" => 225.0,
+                "top left" | "left top" => 315.0,
+                other => return Err(format!("Unsupported gradient direction: {}", other)),
+            };
+            stop_args = &args[1..];
+        }
+    }
+
+    // Parse stops; missing positions are distributed evenly between known ones
+    let mut stops: Vec<([f64; 4], Option<f64>)> = Vec::new();
+    for arg in stop_args {
+        let (color_part, position) = match arg.rsplit_once(' ') {
+            Some((c, p)) if p.ends_with('%') => {
+                (c, p.trim_end_matches('%').parse::<f64>().ok().map(|v| v / 100.0))
+            }
+            _ => (arg.as_str(), None),
+        };
+        let color = parse_css_color(color_part)
+            .ok_or_else(|| format!("Invalid gradient color: {}", color_part))?;
+        stops.push((color, position));
+    }
+    if stops.is_empty() {
+        return Err(format!("Gradient has no color stops: {}", css));
+    }
+    let last = stops.len() - 1;
+    if stops[0].1.is_none() {
+        stops[0].1 = Some(0.0);
+    }
+    if stops[last].1.is_none() {
+        stops[last].1 = Some(1.0);
+    }
+    let mut i = 0;
+    while i < stops.len() {
+        if stops[i].1.is_none() {
+            let start = i - 1;
+            let mut end = i;
+            while stops[end].1.is_none() {
+                end += 1;
+            }
+            let (p0, p1) = (stops[start].1.unwrap(), stops[end].1.unwrap());
+            for (k, stop) in stops.iter_mut().enumerate().take(end).skip(i) {
+                stop.1 = Some(p0 + (p1 - p0) * (k - start) as f64 / (end - start) as f64);
+            }
+            i = end;
+        }
+        i += 1;
+    }
+    let stops: Vec<([f64; 4], f64)> = stops.into_iter().map(|(c, p)| (c, p.unwrap_or(0.0))).collect();
+
+    let (w, h) = (canvas.width() as f64, canvas.height() as f64);
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let line_length = (w * sin).abs() + (h * cos).abs();
+
+    for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+        let dx = x as f64 + 0.5 - w / 2.0;
+        let dy = y as f64 + 0.5 - h / 2.0;
+        let t = (dx * sin - dy * cos) / line_length + 0.5;
+
+        let color = if t <= stops[0].1 {
+            stops[0].0
+        } else if t >= stops[stops.len() - 1].1 {
+            stops[stops.len() - 1].0
+        } else {
+            let idx = stops.windows(2).position(|s| t >= s[0].1 && t <= s[1].1).unwrap_or(0);
+            let (c0, p0) = stops[idx];
+            let (c1, p1) = stops[idx + 1];
+            let f = if p1 > p0 { (t - p0) / (p1 - p0) } else { 0.0 };
+            [
+                c0[0] + (c1[0] - c0[0]) * f,
+                c0[1] + (c1[1] - c0[1]) * f,
+                c0[2] + (c1[2] - c0[2]) * f,
+                c0[3] + (c1[3] - c0[3]) * f,
+            ]
+        };
+        pixel.0 = [
+            (color[0] * 255.0).round() as u8,
+            (color[1] * 255.0).round() as u8,
+            (color[2] * 255.0).round() as u8,
+            (color[3] * 255.0).round() as u8,
+        ];
+    }
+    Ok(())
+}
+
+/// Resolve an asset library id to its file on disk
+fn resolve_asset_path(library_dir: &PathBuf, registry: &AssetRegistry, asset_id: &str) -> Option<PathBuf> {
+    registry
+        .get(asset_id)
+        .map(|asset| asset_file_path(library_dir, asset_id, &asset.file_ext))
+        .filter(|path| path.exists())
+}
+
+/// Draw an image background cover-fitted to the canvas, centered, then
+/// `scale(s) translate(offsetX, offsetY)` about the canvas center — same as the canvas CSS.
+fn draw_background_image(canvas: &mut RgbaImage, img: &RgbaImage, transform: &BackgroundTransform) {
+    let (cw, ch) = (canvas.width() as f64, canvas.height() as f64);
+    let (iw, ih) = (img.width() as f64, img.height() as f64);
+    let cover = (cw / iw).max(ch / ih);
+    let placement = Affine::translate(-iw / 2.0, -ih / 2.0)
+        .then(Affine::scale(cover, cover))
+        .then(Affine::translate(transform.offset_x, transform.offset_y))
+        .then(Affine::scale(transform.scale, transform.scale))
+        .then(Affine::translate(cw / 2.0, ch / 2.0));
+    draw_transformed(canvas, img, placement, 1.0, BlendMode::Normal, |_, _| 1.0);
+}
+
+/// Draw an overlay layer: natural size at (x, y), rotated/scaled/flipped about its own center
+fn draw_overlay(canvas: &mut RgbaImage, img: &RgbaImage, layer: &OverlayLayer) {
+    let t = &layer.transform;
+    let (iw, ih) = (img.width() as f64, img.height() as f64);
+    let flip_x = if t.flip_horizontal { -1.0 } else { 1.0 };
+    let flip_y = if t.flip_vertical { -1.0 } else { 1.0 };
+    let placement = Affine::translate(-iw / 2.0, -ih / 2.0)
+        .then(Affine::scale(flip_x, flip_y))
+        .then(Affine::scale(t.scale, t.scale))
+        .then(Affine::rotate(t.rotation))
+        .then(Affine::translate(t.x + iw / 2.0, t.y + ih / 2.0));
+    draw_transformed(
+        canvas,
+        img,
+        placement,
+        t.opacity.clamp(0.0, 1.0),
+        BlendMode::from_css(&layer.blend_mode),
+        |_, _| 1.0,
+    );
+}
+
+/// Scale that makes a contain-fitted photo cover its zone, snapped like `calculateCoverScale`
+/// in autoPlacement.ts: within 1% counts as 1.0, anything else rounds up to the next 0.1.
+fn default_zone_scale(zw: f64, zh: f64, iw: f64, ih: f64) -> f64 {
+    let scale = (zw / iw).max(zh / ih) / (zw / iw).min(zh / ih);
+    if scale > 0.99 && scale < 1.01 {
+        1.0
+    } else {
+        (scale * 10.0 - 1e-9).ceil() / 10.0
+    }
+}
+
+/// Zone-local space (origin at the zone top-left, unrotated) -> canvas, rotated about the zone center
+fn zone_to_canvas(zone: &FrameZone) -> Affine {
+    let (zw, zh) = (zone.width as f64, zone.height as f64);
+    Affine::translate(-zw / 2.0, -zh / 2.0)
+        .then(Affine::rotate(zone.rotation as f64))
+        .then(Affine::translate(zone.x as f64 + zw / 2.0, zone.y as f64 + zh / 2.0))
+}
+
+/// Source pixels -> canvas for a photo of size `iw`x`ih` placed in `zone`.
+/// CSS: scale(s) translate(ox, oy) rotate(r) scaleX(fx) scaleY(fy), origin at the zone center,
+/// applied to the contain-fitted photo inside the zone's `rotate(zone.rotation)` wrapper.
+fn photo_placement(zone: &FrameZone, iw: f64, ih: f64, t: &PhotoTransform) -> Affine {
+    let (zw, zh) = (zone.width as f64, zone.height as f64);
+    let contain = (zw / iw).min(zh / ih);
+    let flip_x = if t.flip_horizontal { -1.0 } else { 1.0 };
+    let flip_y = if t.flip_vertical { -1.0 } else { 1.0 };
+    Affine::translate(-iw / 2.0, -ih / 2.0)
+        .then(Affine::scale(contain, contain))
+        .then(Affine::scale(flip_x, flip_y))
+        .then(Affine::rotate(t.rotation))
+        .then(Affine::translate(t.offset_x, t.offset_y))
+        .then(Affine::scale(t.scale, t.scale))
+        .then(Affine::translate(zw / 2.0, zh / 2.0))
+        .then(zone_to_canvas(zone))
+}
+
+/// Draw a photo into a frame zone, clipped to the zone shape.
+/// Without a transform the photo is scaled to cover the zone, centered.
+fn draw_photo_in_zone(canvas: &mut RgbaImage, img: &RgbaImage, zone: &FrameZone, transform: Option<&PhotoTransform>) {
+    let (zw, zh) = (zone.width as f64, zone.height as f64);
+    let (iw, ih) = (img.width() as f64, img.height() as f64);
+    if zw <= 0.0 || zh <= 0.0 || iw <= 0.0 || ih <= 0.0 {
+        return;
+    }
+
+    let default_transform = PhotoTransform {
+        scale: default_zone_scale(zw, zh, iw, ih),
+        rotation: 0.0,
+        offset_x: 0.0,
+        offset_y: 0.0,
+        flip_horizontal: false,
+        flip_vertical: false,
+    };
+    let t = transform.unwrap_or(&default_transform);
+
+    let canvas_to_zone = match zone_to_canvas(zone).invert() {
+        Some(inv) => inv,
+        None => return,
+    };
+    let mask = ZoneMask::new(zone, canvas_to_zone);
+    draw_transformed(canvas, img, photo_placement(zone, iw, ih, t), 1.0, BlendMode::Normal, |x, y| {
+        mask.coverage(x, y)
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+/// Pair each photo with its frame zone: by `zone_id` when the photos carry one, otherwise in order.
+/// Extra photos and unknown zone ids are ignored.
+fn zone_placements<'a>(zones: &'a [FrameZone], photos: &'a [CollagePhoto]) -> Vec<(&'a FrameZone, &'a CollagePhoto)> {
+    if photos.iter().any(|p| p.zone_id.is_some()) {
+        photos
+            .iter()
+            .filter_map(|photo| {
+                let zone = zones.iter().find(|z| Some(&z.id) == photo.zone_id.as_ref())?;
+                Some((zone, photo))
+            })
+            .collect()
+    } else {
+        zones.iter().zip(photos.iter()).collect()
+    }
+}
+
+/// Render a custom set with session photos at the set's full canvas resolution.
+/// Zones without a photo are left empty, as in the canvas.
+pub fn render_custom_set(
+    custom_set: &CustomSet,
+    photos: &[CollagePhoto],
+    library_dir: &PathBuf,
+) -> Result<RgbaImage, String> {
+    let width = custom_set.canvas_size.width;
+    let height = custom_set.canvas_size.height;
+    if width == 0 || height == 0 {
+        return Err(format!("Invalid canvas size: {}x{}", width, height));
+    }
+
+    let registry = load_registry(library_dir);
+    let mut canvas = RgbaImage::from_pixel(width, height, image::Rgba([255, 255, 255, 255]));
+
+    // Background
+    let background = &custom_set.background;
+    match background.background_type.as_str() {
+        "gradient" => fill_linear_gradient(&mut canvas, &background.value)?,
+        "image" => {
+            let path = background
+                .asset_id
+                .as_deref()
+                .and_then(|id| resolve_asset_path(library_dir, &registry, id))
+                .unwrap_or_else(|| PathBuf::from(background.value.trim_start_matches("asset://")));
+            let img = load_and_prepare_image(&path.to_string_lossy(), u32::MAX)
+                .map_err(|e| format!("Failed to load background image: {}", e))?;
+            draw_background_image(&mut canvas, &img, &custom_set.background_transform);
+        }
+        _ => {
+            let color = parse_css_color(&background.value)
+                .ok_or_else(|| format!("Invalid background color: {}", background.value))?;
+            fill_color(&mut canvas, color);
+        }
+    }
+
+    // Overlays are stacked like the canvas: below-frames, then zones, then frames/above-frames
+    let mut overlays: Vec<&OverlayLayer> = custom_set.overlays.iter().filter(|o| o.visible).collect();
+    overlays.sort_by_key(|o| o.layer_order);
+
+    let load_overlay = |layer: &OverlayLayer| -> Result<RgbaImage, String> {
+        let path = resolve_asset_path(library_dir, &registry, &layer.asset_id)
+            .ok_or_else(|| format!("Overlay asset not found in library: {} ({})", layer.name, layer.asset_id))?;
+        load_and_prepare_image(&path.to_string_lossy(), u32::MAX)
+    };
+
+    for layer in overlays.iter().filter(|o| o.position == "below-frames") {
+        let img = load_overlay(layer)?;
+        draw_overlay(&mut canvas, &img, layer);
+    }
+
+    for (zone, photo) in zone_placements(&custom_set.frame.zones, photos) {
+        let img = load_and_prepare_image(&photo.path, u32::MAX)?;
+        draw_photo_in_zone(&mut canvas, &img, zone, photo.transform.as_ref());
+    }
+
+    for position in ["frames", "above-frames"] {
+        for layer in overlays.iter().filter(|o| o.position == position) {
+            let img = load_overlay(layer)?;
+            draw_overlay(&mut canvas, &img, layer);
+        }
+    }
+
+    Ok(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn zone(id: &str, x: i32, y: i32, width: u32, height: u32, rotation: f32) -> FrameZone {
+        FrameZone {
+            id: id.to_string(),
+            x,
+            y,
+            width,
+            height,
+            rotation,
+            shape: "rectangle".to_string(),
+            border_radius: None,
+            margin_right: None,
+            margin_bottom: None,
+        }
+    }
+
+    fn photo(path: &str, zone_id: Option<&str>) -> CollagePhoto {
+        CollagePhoto { path: path.to_string(), zone_id: zone_id.map(str::to_string), transform: None }
+    }
+
+    fn transform(scale: f64, rotation: f64, offset: (f64, f64), flip_horizontal: bool) -> PhotoTransform {
+        PhotoTransform {
+            scale,
+            rotation,
+            offset_x: offset.0,
+            offset_y: offset.1,
+            flip_horizontal,
+            flip_vertical: false,
+        }
+    }
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn row(canvas: &RgbaImage) -> Vec<[u8; 4]> {
+        canvas.pixels().map(|p| p.0).collect()
+    }
+
+    #[test]
+    fn parses_css_colors() {
+        let cases = [
+            ("#fff", Some([1.0, 1.0, 1.0, 1.0])),
+            ("#FF0000", Some([1.0, 0.0, 0.0, 1.0])),
+            ("#00ff0000", Some([0.0, 1.0, 0.0, 0.0])),
+            ("#f008", Some([1.0, 0.0, 0.0, 136.0 / 255.0])),
+            ("rgb(255, 0, 51)", Some([1.0, 0.0, 0.2, 1.0])),
+            ("rgba(0,0,255,0.5)", Some([0.0, 0.0, 1.0, 0.5])),
+            ("rgb(255 255 0 / 0.25)", Some([1.0, 1.0, 0.0, 0.25])),
+            (" White ", Some([1.0, 1.0, 1.0, 1.0])),
+            ("transparent", Some([0.0, 0.0, 0.0, 0.0])),
+            ("#12345", None),
+            ("#gggggg", None),
+            ("rgb(1, 2)", None),
+            ("tomato", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_css_color(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn gradient_interpolates_between_stops() {
+        // 90deg on a 4px row samples t = 0.125, 0.375, 0.625, 0.875; the middle stop sits at 50%
+        let mut canvas = RgbaImage::new(4, 1);
+        fill_linear_gradient(&mut canvas, "linear-gradient(to right, #000, #fff, #000)").unwrap();
+        let grays: Vec<u8> = row(&canvas).iter().map(|p| p[0]).collect();
+        assert_eq!(grays, vec![64, 191, 191, 64]);
+
+        // No direction means top to bottom
+        let mut canvas = RgbaImage::new(1, 4);
+        fill_linear_gradient(&mut canvas, "linear-gradient(#000000, #ffffff)").unwrap();
+        let grays: Vec<u8> = row(&canvas).iter().map(|p| p[0]).collect();
+        assert_eq!(grays, vec![32, 96, 159, 223]);
+    }
+
+    #[test]
+    fn gradient_spreads_consecutive_stops_without_positions() {
+        // Green and blue have no position, so they land at 40% and 60% between red 20% and white 80%
+        let mut canvas = RgbaImage::new(10, 1);
+        fill_linear_gradient(&mut canvas, "linear-gradient(90deg, #f00 20%, #0f0, #00f, #fff 80%)").unwrap();
+        let pixels = row(&canvas);
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[3], [64, 191, 0, 255]);
+        assert_eq!(pixels[5], [0, 64, 191, 255]);
+        assert_eq!(pixels[9], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn gradient_keeps_rgba_stops_and_alpha() {
+        let mut canvas = RgbaImage::new(2, 1);
+        fill_linear_gradient(&mut canvas, "linear-gradient(to right, rgba(255, 0, 0, 0.5) 0%, #00f 100%)").unwrap();
+        assert_eq!(row(&canvas)[0], [191, 0, 64, 159]);
+    }
+
+    #[test]
+    fn rejects_invalid_gradients() {
+        let cases = [
+            "radial-gradient(#000, #fff)",
+            "linear-gradient(to middle, #000, #fff)",
+            "linear-gradient(sideways deg, #000, #fff)",
+            "linear-gradient(#000, nope)",
+            "linear-gradient(90deg)",
+        ];
+        for css in cases {
+            let mut canvas = RgbaImage::new(2, 2);
+            assert!(fill_linear_gradient(&mut canvas, css).is_err(), "{}", css);
+        }
+    }
+
+    #[test]
+    fn separable_blend_modes() {
+        let cases = [
+            (BlendMode::Multiply, 0.5, 0.5, 0.25),
+            (BlendMode::Screen, 0.5, 0.5, 0.75),
+            (BlendMode::Overlay, 0.25, 0.5, 0.25),
+            (BlendMode::Overlay, 0.75, 0.5, 0.75),
+            (BlendMode::HardLight, 0.5, 0.25, 0.25),
+            (BlendMode::HardLight, 0.5, 0.75, 0.75),
+            (BlendMode::Darken, 0.2, 0.7, 0.2),
+            (BlendMode::Lighten, 0.2, 0.7, 0.7),
+            (BlendMode::ColorDodge, 0.5, 0.5, 1.0),
+            (BlendMode::ColorDodge, 0.0, 1.0, 0.0),
+            (BlendMode::ColorBurn, 0.5, 0.5, 0.0),
+            (BlendMode::ColorBurn, 1.0, 0.0, 1.0),
+            (BlendMode::SoftLight, 0.5, 1.0, 0.5_f64.sqrt()),
+            (BlendMode::SoftLight, 0.5, 0.0, 0.25),
+            (BlendMode::Difference, 0.2, 0.7, 0.5),
+            (BlendMode::Exclusion, 0.5, 0.5, 0.5),
+        ];
+        for (mode, cb, cs, expected) in cases {
+            let actual = mode.blend([cb; 3], [cs; 3]);
+            assert!((actual[0] - expected).abs() < 1e-9, "{:?}({}, {}) = {}", mode, cb, cs, actual[0]);
+        }
+        assert_eq!(BlendMode::from_css("color-dodge"), BlendMode::ColorDodge);
+        assert_eq!(BlendMode::from_css("plus-lighter"), BlendMode::Normal);
+    }
+
+    #[test]
+    fn non_separable_blend_modes_keep_the_right_luminosity() {
+        let red = [1.0, 0.0, 0.0];
+        let gray = [0.5, 0.5, 0.5];
+
+        // Luminosity: backdrop hue with source luminosity, clipped back into gamut
+        let out = BlendMode::Luminosity.blend(red, gray);
+        assert!((lum(out) - 0.5).abs() < 1e-9);
+        assert!((out[0] - 1.0).abs() < 1e-9 && (out[1] - 2.0 / 7.0).abs() < 1e-9 && out[1] == out[2]);
+
+        // Color: source hue and saturation with backdrop luminosity
+        let out = BlendMode::Color.blend(gray, red);
+        assert!((lum(out) - 0.5).abs() < 1e-9 && out[0] > out[1]);
+
+        // Hue over a gray backdrop has no saturation to keep
+        let out = BlendMode::Hue.blend(gray, red);
+        assert!(out.iter().all(|v| (v - 0.5).abs() < 1e-9));
+
+        // Saturation of a gray source removes the backdrop's saturation
+        let out = BlendMode::Saturation.blend(red, gray);
+        assert!(out.iter().all(|v| (v - 0.3).abs() < 1e-9));
+    }
+
+    #[test]
+    fn composites_source_over_backdrop() {
+        let mut px = [255, 255, 255, 255];
+        composite_pixel(&mut px, [0.0, 0.0, 0.0], 0.5, BlendMode::Normal);
+        assert_eq!(px, [128, 128, 128, 255]);
+
+        let mut px = [128, 255, 255, 255];
+        composite_pixel(&mut px, [0.5, 0.5, 0.5], 1.0, BlendMode::Multiply);
+        assert_eq!(px, [64, 128, 128, 255]);
+
+        // Blend modes don't apply over a transparent backdrop
+        let mut px = [0, 0, 0, 0];
+        composite_pixel(&mut px, [1.0, 0.0, 0.0], 0.5, BlendMode::Multiply);
+        assert_eq!(px, [255, 0, 0, 128]);
+
+        let mut px = [10, 20, 30, 40];
+        composite_pixel(&mut px, [1.0, 1.0, 1.0], 0.0, BlendMode::Normal);
+        assert_eq!(px, [10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn default_zone_scale_matches_auto_placement() {
+        let cases = [
+            ((100.0, 100.0, 300.0, 200.0), 1.5),
+            ((100.0, 100.0, 299.0, 200.0), 1.5),
+            ((100.0, 100.0, 100.5, 100.0), 1.0),
+            ((100.0, 100.0, 102.0, 100.0), 1.1),
+            ((100.0, 100.0, 400.0, 100.0), 4.0),
+            ((200.0, 100.0, 100.0, 100.0), 2.0),
+        ];
+        for ((zw, zh, iw, ih), expected) in cases {
+            let actual = default_zone_scale(zw, zh, iw, ih);
+            assert!((actual - expected).abs() < 1e-9, "{}x{} in {}x{}: {}", iw, ih, zw, zh, actual);
+        }
+    }
+
+    #[test]
+    fn photo_transform_follows_image_zone_css_order() {
+        // 40x20 photo contain-fits a 200x100 zone at 5x; the zone is rotated 90deg about (200, 100)
+        let rotated = zone("a", 100, 50, 200, 100, 90.0);
+        let t = transform(1.5, 0.0, (10.0, 0.0), true);
+        let placement = photo_placement(&rotated, 40.0, 20.0, &t);
+
+        // The flipped top-left corner sits at (100, -50) from the zone center, is panned by 10
+        // and scaled by 1.5 (the pan is scaled too), giving (165, -75) before the zone rotation
+        assert_close(placement.apply(0.0, 0.0), (275.0, 265.0));
+        assert_close(placement.apply(20.0, 10.0), (200.0, 115.0));
+
+        // Photo rotation happens inside the pan: a 90deg turn doesn't rotate the offset
+        let upright = zone("a", 100, 50, 200, 100, 0.0);
+        let t = transform(1.0, 90.0, (10.0, 0.0), false);
+        let placement = photo_placement(&upright, 40.0, 20.0, &t);
+        assert_close(placement.apply(20.0, 10.0), (210.0, 100.0));
+        assert_close(placement.apply(0.0, 0.0), (260.0, 0.0));
+    }
+
+    #[test]
+    fn draws_photos_into_zones_with_flips() {
+        // Left half red, right half blue, exactly filling a 20x10 zone
+        let mut img = RgbaImage::new(4, 2);
+        for (x, _, p) in img.enumerate_pixels_mut() {
+            *p = if x < 2 { Rgba([255, 0, 0, 255]) } else { Rgba([0, 0, 255, 255]) };
+        }
+        let target = zone("a", 5, 0, 20, 10, 0.0);
+
+        let mut canvas = RgbaImage::from_pixel(30, 10, Rgba([255, 255, 255, 255]));
+        draw_photo_in_zone(&mut canvas, &img, &target, None);
+        assert_eq!(canvas.get_pixel(2, 5).0, [255, 255, 255, 255]);
+        assert_eq!(canvas.get_pixel(8, 5).0, [255, 0, 0, 255]);
+        assert_eq!(canvas.get_pixel(21, 5).0, [0, 0, 255, 255]);
+        assert_eq!(canvas.get_pixel(27, 5).0, [255, 255, 255, 255]);
+
+        let mut canvas = RgbaImage::from_pixel(30, 10, Rgba([255, 255, 255, 255]));
+        draw_photo_in_zone(&mut canvas, &img, &target, Some(&transform(1.0, 0.0, (0.0, 0.0), true)));
+        assert_eq!(canvas.get_pixel(8, 5).0, [0, 0, 255, 255]);
+        assert_eq!(canvas.get_pixel(21, 5).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn background_image_covers_the_canvas() {
+        let img = RgbaImage::from_pixel(2, 2, Rgba([0, 128, 0, 255]));
+        let mut canvas = RgbaImage::new(6, 3);
+        let transform = BackgroundTransform { scale: 1.0, offset_x: 0.0, offset_y: 0.0 };
+        draw_background_image(&mut canvas, &img, &transform);
+        // At its natural size the image would leave the outer columns empty
+        assert!(canvas.pixels().all(|p| p.0[3] > 0));
+        assert_eq!(canvas.get_pixel(1, 1).0, [0, 128, 0, 255]);
+        assert_eq!(canvas.get_pixel(3, 1).0, [0, 128, 0, 255]);
+    }
+
+    #[test]
+    fn pairs_photos_with_zones() {
+        let zones = [zone("a", 0, 0, 1, 1, 0.0), zone("b", 0, 0, 1, 1, 0.0), zone("c", 0, 0, 1, 1, 0.0)];
+        let ids = |placements: Vec<(&FrameZone, &CollagePhoto)>| -> Vec<(String, String)> {
+            placements.into_iter().map(|(z, p)| (z.id.clone(), p.path.clone())).collect()
+        };
+
+        let in_order = [photo("1.jpg", None), photo("2.jpg", None)];
+        assert_eq!(
+            ids(zone_placements(&zones, &in_order)),
+            vec![("a".into(), "1.jpg".into()), ("b".into(), "2.jpg".into())]
+        );
+
+        let by_zone = [photo("1.jpg", Some("c")), photo("2.jpg", Some("missing")), photo("3.jpg", Some("a"))];
+        assert_eq!(
+            ids(zone_placements(&zones, &by_zone)),
+            vec![("c".into(), "1.jpg".into()), ("a".into(), "3.jpg".into())]
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Placement of a photo inside its frame zone.
+/// Mirrors the canvas `ImageTransform`: the photo is first fitted with
+/// `object-fit: contain`, then scaled, panned, rotated and flipped about the zone center.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoTransform {
+    pub scale: f64,
+    pub rotation: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    #[serde(default)]
+    pub flip_horizontal: bool,
+    #[serde(default)]
+    pub flip_vertical: bool,
+}
+
+/// A session photo to place into a frame zone.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CollagePhoto {
+    pub path: String,
+    /// Zone to place the photo in; when no photo sets it, photos fill the zones in order.
+    #[serde(default)]
+    pub zone_id: Option<String>,
+    /// When omitted the photo is scaled to cover the zone, centered.
+    #[serde(default)]
+    pub transform: Option<PhotoTransform>,
+}
+
+/// One collage to produce in a batch render.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CollageRenderJob {
+    pub photos: Vec<CollagePhoto>,
+    pub output_path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CollageRenderResult {
+    pub file_path: String,
+    pub width: u32,
+    pub height: u32,
+    pub file_size: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct CollageRenderProgress {
+    pub current: usize,
+    pub total: usize,
+    pub file_path: String,
+}
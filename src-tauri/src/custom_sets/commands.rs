@@ -4,6 +4,7 @@ use crate::asset_library::commands::{
 };
 use crate::asset_library::types::BundledAsset;
 use crate::custom_sets::types::{CustomSet, CustomSetPreview, PortableCustomSet};
+use crate::utils::schema;
 use base64::{engine::general_purpose, Engine as _};
 use std::collections::HashSet;
 use std::fs;
@@ -30,9 +31,9 @@ fn read_set(sets_dir: &PathBuf, set_id: &str) -> Result<CustomSet, String> {
     if !set_path.exists() {
         return Err(format!("Custom set not found: {}", set_id));
     }
-    let json = fs::read_to_string(&set_path)
-        .map_err(|e| format!("Failed to read custom set: {}", e))?;
-    serde_json::from_str(&json).map_err(|e| format!("Failed to parse custom set: {}", e))
+    schema::CUSTOM_SET
+        .load_file(&set_path)
+        .map_err(|e| format!("Failed to load custom set: {}", e))
 }
 
 fn write_set(sets_dir: &PathBuf, set: &CustomSet) -> Result<(), String> {
@@ -107,14 +108,12 @@ pub async fn load_custom_sets(app: tauri::AppHandle) -> Result<Vec<CustomSetPrev
             continue;
         }
 
-        let json = match fs::read_to_string(&path) {
-            Ok(j) => j,
-            Err(_) => continue,
-        };
-
-        let custom_set: CustomSet = match serde_json::from_str(&json) {
+        let custom_set: CustomSet = match schema::CUSTOM_SET.load_file(&path) {
             Ok(s) => s,
-            Err(_) => continue,
+            Err(e) => {
+                eprintln!("[custom_sets] Skipping {:?}: {}", path, e);
+                continue;
+            }
         };
 
         sets.push(CustomSetPreview {
@@ -294,7 +293,15 @@ pub async fn import_custom_set(
     let json = fs::read_to_string(&file_path)
         .map_err(|e| format!("Failed to read import file: {}", e))?;
 
-    let portable: PortableCustomSet = serde_json::from_str(&json).map_err(|_| {
+    let mut document: serde_json::Value = serde_json::from_str(&json).map_err(|_| {
+        "Invalid .ptbs file. This file may have been exported with an incompatible version."
+            .to_string()
+    })?;
+    // The bundled set may come from an older (or newer) app version
+    if let Some(custom_set) = document.get_mut("custom_set") {
+        schema::CUSTOM_SET.migrate(custom_set)?;
+    }
+    let portable: PortableCustomSet = serde_json::from_value(document).map_err(|_| {
         "Invalid .ptbs file. This file may have been exported with an incompatible version."
             .to_string()
     })?;
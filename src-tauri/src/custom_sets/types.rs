@@ -52,6 +52,9 @@ pub struct CanvasSize {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CustomSet {
+    /// See utils::schema
+    #[serde(default = "crate::utils::schema::custom_set_version")]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub description: String,
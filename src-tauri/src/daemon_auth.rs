@@ -0,0 +1,65 @@
+//! Credential for the camera daemon's optional token authentication
+//!
+//! When the daemon runs with PHOTOBOOTH_AUTH_TOKEN, every request needs the same token.
+//! It's kept in the app data dir (or PHOTOBOOTH_DAEMON_TOKEN for development) and attached
+//! to every request the backend makes to the daemon. The webview gets it through
+//! `get_daemon_auth_token` for its own fetches, the WebSocket and the live view stream.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+const DAEMON_AUTH_FILE: &str = "daemon_auth.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct DaemonAuthFile {
+    token: Option<String>,
+}
+
+fn daemon_auth_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(app_data_dir.join(DAEMON_AUTH_FILE))
+}
+
+/// Token to send to the daemon, if one is configured
+pub fn load_daemon_token(app: &tauri::AppHandle) -> Option<String> {
+    if let Ok(token) = std::env::var("PHOTOBOOTH_DAEMON_TOKEN") {
+        let token = token.trim().to_string();
+        if !token.is_empty() {
+            return Some(token);
+        }
+    }
+    let path = daemon_auth_path(app).ok()?;
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<DaemonAuthFile>(&content)
+        .ok()?
+        .token
+        .filter(|t| !t.trim().is_empty())
+}
+
+/// Attach the daemon token to a request (no-op when authentication is off)
+pub fn with_daemon_auth(builder: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => builder.bearer_auth(token),
+        None => builder,
+    }
+}
+
+#[tauri::command]
+pub async fn get_daemon_auth_token(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    Ok(load_daemon_token(&app))
+}
+
+/// Store (or with `None`, clear) the daemon token
+#[tauri::command]
+pub async fn set_daemon_auth_token(app: tauri::AppHandle, token: Option<String>) -> Result<(), String> {
+    let token = token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let json = serde_json::to_string_pretty(&DaemonAuthFile { token })
+        .map_err(|e| format!("Failed to serialize daemon auth: {}", e))?;
+    fs::write(daemon_auth_path(&app)?, json).map_err(|e| format!("Failed to write daemon auth: {}", e))
+}
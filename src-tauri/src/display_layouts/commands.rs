@@ -1,4 +1,5 @@
 use crate::display_layouts::types::{DisplayLayout, DisplayLayoutPreview};
+use crate::utils::schema;
 use base64::{engine::general_purpose, Engine as _};
 use percent_encoding::percent_decode;
 use serde::{Deserialize, Serialize};
@@ -266,11 +267,9 @@ pub async fn load_display_layouts(
             continue;
         }
 
-        let json = fs::read_to_string(&path)
-            .map_err(|e| format!("Failed to read display layout file: {}", e))?;
-
-        let layout: DisplayLayout = serde_json::from_str(&json)
-            .map_err(|e| format!("Failed to parse display layout file: {}", e))?;
+        let layout: DisplayLayout = schema::DISPLAY_LAYOUT
+            .load_file(&path)
+            .map_err(|e| format!("Failed to load display layout file: {}", e))?;
 
         layouts.push(DisplayLayoutPreview {
             id: layout.id,
@@ -298,13 +297,9 @@ pub async fn get_display_layout(
         return Err(format!("Display layout not found: {}", layout_id));
     }
 
-    let json = fs::read_to_string(&layout_path)
-        .map_err(|e| format!("Failed to read display layout file: {}", e))?;
-
-    let layout: DisplayLayout = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse display layout file: {}", e))?;
-
-    Ok(layout)
+    schema::DISPLAY_LAYOUT
+        .load_file(&layout_path)
+        .map_err(|e| format!("Failed to load display layout file: {}", e))
 }
 
 #[tauri::command]
@@ -320,10 +315,9 @@ pub async fn delete_display_layout(
     }
 
     // Refuse to delete protected default layouts
-    let json = fs::read_to_string(&layout_path)
-        .map_err(|e| format!("Failed to read display layout file: {}", e))?;
-    let layout: crate::display_layouts::types::DisplayLayout = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse display layout file: {}", e))?;
+    let layout: DisplayLayout = schema::DISPLAY_LAYOUT
+        .load_file(&layout_path)
+        .map_err(|e| format!("Failed to load display layout file: {}", e))?;
     if layout.is_default {
         return Err("Cannot delete the default layout".to_string());
     }
@@ -348,6 +342,7 @@ pub async fn duplicate_display_layout(
     let original = get_display_layout(app.clone(), layout_id).await?;
 
     let duplicated = DisplayLayout {
+        schema_version: original.schema_version,
         id: format!("layout-{}", uuid::Uuid::new_v4()),
         name: format!("{} (Copy)", original.name),
         background_color: original.background_color,
@@ -463,7 +458,13 @@ pub async fn import_display_layout(
     let json = fs::read_to_string(&file_path)
         .map_err(|e| format!("Failed to read import file: {}", e))?;
 
-    let portable: PortableDisplayLayout = serde_json::from_str(&json)
+    let mut document: serde_json::Value = serde_json::from_str(&json)
+        .map_err(|_| "Invalid .iplayout file or unsupported format version.".to_string())?;
+    // The bundled layout may come from an older (or newer) app version
+    if let Some(layout) = document.get_mut("layout") {
+        schema::DISPLAY_LAYOUT.migrate(layout)?;
+    }
+    let portable: PortableDisplayLayout = serde_json::from_value(document)
         .map_err(|_| "Invalid .iplayout file or unsupported format version.".to_string())?;
 
     let mut layout = portable.layout;
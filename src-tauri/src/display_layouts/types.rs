@@ -43,6 +43,9 @@ pub struct DisplayElement {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DisplayLayout {
+    /// See utils::schema
+    #[serde(default = "crate::utils::schema::display_layout_version")]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub background_color: String,
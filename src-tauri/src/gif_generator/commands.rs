@@ -26,9 +26,12 @@ pub struct GifResult {
     pub file_size: u64,
 }
 
-/// Load an image from disk, apply EXIF orientation, and downscale to max_dimension.
-fn load_and_prepare_image(path: &str, max_dimension: u32) -> Result<RgbaImage, String> {
+/// Load an image from disk (RAW files via their JPEG), apply EXIF orientation, and downscale to max_dimension.
+pub(crate) fn load_and_prepare_image(path: &str, max_dimension: u32) -> Result<RgbaImage, String> {
     let total_start = Instant::now();
+    // RAW files are loaded from their JPEG (converted on first use)
+    let usable_path = crate::working_folder::raw::usable_image_path(std::path::Path::new(path))?;
+    let path = usable_path.to_str().ok_or_else(|| format!("Invalid path '{}'", path))?;
     let path_buf = PathBuf::from(path);
     let filename = path_buf.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
 
@@ -348,6 +351,67 @@ pub async fn generate_gif(
     result
 }
 
+/// Build a sweep animation from a group capture (one photo per camera, in camera order).
+/// With `bounce` the sweep plays forward then back, which loops without a jump.
+#[tauri::command]
+pub async fn generate_group_sweep_gif(
+    app: tauri::AppHandle,
+    folder_path: String,
+    session_id: String,
+    group_id: String,
+    frame_delay_ms: Option<u32>,
+    max_dimension: Option<u32>,
+    bounce: Option<bool>,
+) -> Result<GifResult, String> {
+    let workspace = crate::photobooth_sessions::load_ptb_workspace(folder_path.clone()).await?;
+    let session = workspace
+        .sessions
+        .iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    let group = session
+        .photo_groups
+        .iter()
+        .find(|g| g.id == group_id)
+        .ok_or_else(|| format!("Photo group not found: {}", group_id))?;
+
+    let session_dir = PathBuf::from(&folder_path).join(&session.folder_name);
+    let mut image_paths: Vec<String> = group
+        .members
+        .iter()
+        .map(|m| session_dir.join(&m.filename).to_string_lossy().to_string())
+        .collect();
+    if image_paths.len() < 2 {
+        return Err(format!(
+            "Group {} has {} photo(s), a sweep needs at least 2",
+            group_id,
+            image_paths.len()
+        ));
+    }
+    if bounce.unwrap_or(true) {
+        // Forward then back, without repeating the end frames
+        let back: Vec<String> = image_paths[1..image_paths.len() - 1].iter().rev().cloned().collect();
+        image_paths.extend(back);
+    }
+
+    let delay = frame_delay_ms.unwrap_or(120);
+    let max_dim = max_dimension.unwrap_or(1024);
+    let output_path = session_dir.join(format!("Sweep_{}.gif", generate_random_suffix()));
+
+    println!(
+        "[GIF] Sweep for group {}: {} frames, {}ms per frame",
+        group_id,
+        image_paths.len(),
+        delay
+    );
+
+    tokio::task::spawn_blocking(move || {
+        encode_gif_internal(&app, &image_paths, &output_path, delay, max_dim, Repeat::Infinite)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Load images, prepare frames, and save them as temporary PNGs for ffmpeg.
 /// Returns (temp_dir, list of frame paths, frame dimensions w x h).
 fn prepare_frames_for_video(
@@ -13,11 +13,15 @@ mod history;
 mod printing;
 mod vm;
 mod usb_camera;
+mod camera_presets;
+mod daemon_auth;
 mod hdmi_capture;
 mod working_folder;
 mod custom_sets;
 mod photobooth_sessions;
 mod upload_queue;
+mod upload_targets;
+mod local_gallery;
 mod gif_generator;
 mod system_requirements;
 mod version;
@@ -25,6 +29,7 @@ mod ffmpeg_sidecar;
 mod display_layouts;
 mod ffmpeg_manager;
 mod system_fonts;
+mod collage_renderer;
 
 // Re-export state
 use state::AppState;
@@ -40,6 +45,8 @@ use history::*;
 use printing::*;
 use vm::*;
 use usb_camera::*;
+use camera_presets::*;
+use daemon_auth::*;
 use hdmi_capture::*;
 use utils::*;
 use working_folder::*;
@@ -48,7 +55,10 @@ use display_layouts::*;
 use photobooth_sessions::*;
 use upload_queue::*;
 use upload_queue::queue::UploadQueue;
+use upload_targets::*;
+use local_gallery::*;
 use gif_generator::*;
+use collage_renderer::*;
 use ffmpeg_manager::*;
 use system_requirements::*;
 use version::*;
@@ -69,6 +79,7 @@ pub fn run() {
         .manage(UploadQueueStateWrapper {
             queue: Arc::new(UploadQueue::new()),
         })
+        .manage(LocalGalleryState::default())
         .setup(|app| {
             use tauri::Manager;
 
@@ -132,6 +143,7 @@ pub fn run() {
             generate_cached_thumbnails_batch_ultra,
             clear_temp_images,
             remove_temp_image,
+            convert_raw_image,
             // Frames
             save_frame,
             load_frames,
@@ -187,6 +199,7 @@ pub fn run() {
             get_session_data,
             update_session_drive_metadata,
             add_session_drive_upload,
+            update_session_upload_target,
             is_image_uploaded_to_drive,
             clear_session_drive_uploads,
             save_photo_to_working_folder,
@@ -194,9 +207,20 @@ pub fn run() {
             save_file_to_session_folder,
             save_file_to_path,
             download_photo_from_daemon,
+            attach_daemon_raw,
+            sweep_daemon_photos,
             get_photo_exif,
             update_session_qr_setting,
             update_session_naming_scheme,
+            get_catalog_session_photos,
+            get_catalog_photos_not_uploaded,
+            get_catalog_photos_captured_between,
+            record_catalog_media,
+            list_catalog_media,
+            record_print_job,
+            update_print_job_status,
+            list_print_jobs,
+            export_catalog_to_ptb,
             // Upload Queue
             enqueue_upload_items,
             get_session_upload_queue,
@@ -204,6 +228,14 @@ pub fn run() {
             retry_upload,
             cancel_queued_upload,
             remove_session_uploads,
+            // Upload Targets
+            get_upload_targets_config,
+            save_upload_targets_config,
+            // Local Gallery
+            start_local_gallery,
+            stop_local_gallery,
+            get_local_gallery_status,
+            get_local_gallery_session_link,
             // History
             get_history,
             clear_history,
@@ -226,6 +258,16 @@ pub fn run() {
             cleanup_all_cameras,
             attach_all_cameras,
             ensure_usb_filters,
+            // Daemon Auth
+            get_daemon_auth_token,
+            set_daemon_auth_token,
+            // Camera Presets
+            list_camera_presets,
+            save_camera_preset,
+            delete_camera_preset,
+            capture_camera_preset,
+            diff_camera_preset,
+            apply_camera_preset,
             // HDMI Capture
             list_capture_devices,
             start_hdmi_capture,
@@ -253,12 +295,16 @@ pub fn run() {
             close_splash_and_show_main,
             // GIF/Video Generation
             generate_gif,
+            generate_group_sweep_gif,
             check_ffmpeg_installed,
             get_ffmpeg_version,
             get_ffmpeg_size,
             download_ffmpeg_command,
             delete_ffmpeg_command,
             generate_slideshow_video,
+            // Collage Rendering
+            render_collage,
+            render_collages_batch,
             // QR Code
             utils::qr_code::generate_qr_code,
             // System Fonts
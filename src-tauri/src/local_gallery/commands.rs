@@ -0,0 +1,122 @@
+use crate::local_gallery::server::{
+    detect_lan_ip, session_gallery_path, start_gallery_server, GalleryContext,
+};
+use crate::local_gallery::types::LocalGalleryStatus;
+use crate::types::ProcessResult;
+use crate::utils::generate_qr_code_base64;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::{oneshot, Mutex};
+
+const DEFAULT_GALLERY_PORT: u16 = 8765;
+
+struct RunningGallery {
+    port: u16,
+    base_url: String,
+    context: Arc<GalleryContext>,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// Global local gallery server instance
+#[derive(Default)]
+pub struct LocalGalleryState {
+    running: Mutex<Option<RunningGallery>>,
+}
+
+async fn status_of(running: &Option<RunningGallery>) -> LocalGalleryStatus {
+    match running {
+        Some(gallery) => LocalGalleryStatus {
+            running: true,
+            port: Some(gallery.port),
+            base_url: Some(gallery.base_url.clone()),
+            working_folder: Some(gallery.context.working_folder.read().await.clone()),
+        },
+        None => LocalGalleryStatus {
+            running: false,
+            port: None,
+            base_url: None,
+            working_folder: None,
+        },
+    }
+}
+
+/// Start serving the working folder's sessions on the LAN.
+/// If the server is already running it switches to the given working folder.
+/// `host` overrides the auto-detected LAN address used in links and QR codes.
+#[tauri::command]
+pub async fn start_local_gallery(
+    folder_path: String,
+    port: Option<u16>,
+    host: Option<String>,
+    state: State<'_, LocalGalleryState>,
+) -> Result<LocalGalleryStatus, String> {
+    if !std::path::Path::new(&folder_path).join(".ptb").exists() {
+        return Err(format!("Not a photobooth working folder: {}", folder_path));
+    }
+
+    let mut running = state.running.lock().await;
+
+    if let Some(gallery) = running.as_ref() {
+        *gallery.context.working_folder.write().await = folder_path;
+        return Ok(status_of(&running).await);
+    }
+
+    let port = port.unwrap_or(DEFAULT_GALLERY_PORT);
+    let host = match host {
+        Some(host) => host,
+        None => detect_lan_ip()?,
+    };
+
+    let context = Arc::new(GalleryContext::new(folder_path));
+    let shutdown = start_gallery_server(context.clone(), port)?;
+
+    *running = Some(RunningGallery {
+        port,
+        base_url: format!("http://{}:{}", host, port),
+        context,
+        shutdown,
+    });
+
+    Ok(status_of(&running).await)
+}
+
+/// Stop the local gallery server
+#[tauri::command]
+pub async fn stop_local_gallery(state: State<'_, LocalGalleryState>) -> Result<(), String> {
+    if let Some(gallery) = state.running.lock().await.take() {
+        let _ = gallery.shutdown.send(());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_local_gallery_status(
+    state: State<'_, LocalGalleryState>,
+) -> Result<LocalGalleryStatus, String> {
+    let running = state.running.lock().await;
+    Ok(status_of(&running).await)
+}
+
+/// Local gallery link and QR code for a session (same shape as the Drive upload result)
+#[tauri::command]
+pub async fn get_local_gallery_session_link(
+    session_id: String,
+    state: State<'_, LocalGalleryState>,
+) -> Result<ProcessResult, String> {
+    let running = state.running.lock().await;
+    let gallery = running.as_ref().ok_or("Local gallery is not running")?;
+
+    let working_folder = gallery.context.working_folder.read().await.clone();
+    let link = format!(
+        "{}{}",
+        gallery.base_url,
+        session_gallery_path(&working_folder, &session_id)?
+    );
+    let qr_data = generate_qr_code_base64(&link)?;
+
+    Ok(ProcessResult {
+        folder_name: session_id,
+        link,
+        qr_data,
+    })
+}
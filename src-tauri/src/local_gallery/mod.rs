@@ -0,0 +1,8 @@
+// Offline local gallery - serves session photos over the booth's LAN/hotspot
+
+pub mod types;
+pub mod server;
+mod zip_stream;
+mod commands;
+
+pub use commands::*;
@@ -0,0 +1,611 @@
+use crate::local_gallery::types::GalleryFile;
+use crate::local_gallery::zip_stream::{check_zip_limits, stream_zip};
+use crate::photobooth_sessions::store;
+use crate::photobooth_sessions::types::PtbSessionData;
+use crate::upload_targets::target::content_type_for;
+use hmac::{Hmac, Mac};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use sha2::Sha256;
+use std::convert::Infallible;
+use std::fs;
+use std::io::SeekFrom;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::{oneshot, Mutex, RwLock};
+
+/// Per-working-folder secret used to derive session access tokens.
+/// Stored in the working folder so printed QR codes keep working after a restart.
+const GALLERY_SECRET_FILE: &str = ".gallery_secret";
+
+/// Length of a session access token (truncated HMAC-SHA256), in bytes
+const TOKEN_BYTES: usize = 16;
+
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Shared server state: the working folder can be switched without restarting the server
+pub struct GalleryContext {
+    pub working_folder: RwLock<String>,
+    /// Sessions of the working folder, reloaded when its .ptb changes
+    pub sessions: Mutex<Option<SessionCache>>,
+}
+
+pub struct SessionCache {
+    working_folder: String,
+    /// Modification time and size of the .ptb the sessions were read from
+    stamp: (SystemTime, u64),
+    sessions: Vec<PtbSessionData>,
+}
+
+impl GalleryContext {
+    pub fn new(working_folder: String) -> Self {
+        Self {
+            working_folder: RwLock::new(working_folder),
+            sessions: Mutex::new(None),
+        }
+    }
+}
+
+/// Start the gallery HTTP server on all interfaces. Returns the shutdown trigger.
+pub fn start_gallery_server(
+    context: Arc<GalleryContext>,
+    port: u16,
+) -> Result<oneshot::Sender<()>, String> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+    let make_svc = make_service_fn(move |_conn| {
+        let context = context.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| handle_request(context.clone(), req)))
+        }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| format!("Failed to bind gallery server on port {}: {}", port, e))?
+        .serve(make_svc);
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let graceful = server.with_graceful_shutdown(async {
+        let _ = shutdown_rx.await;
+    });
+
+    tauri::async_runtime::spawn(async move {
+        println!("[local_gallery] Serving on {}", addr);
+        if let Err(e) = graceful.await {
+            println!("[local_gallery] Server error: {}", e);
+        }
+        println!("[local_gallery] Server stopped");
+    });
+
+    Ok(shutdown_tx)
+}
+
+/// Interfaces of container/VM bridges, which guests can't reach
+const VIRTUAL_INTERFACE_PREFIXES: &[&str] = &["docker", "br-", "veth", "virbr", "vmnet", "vboxnet", "vEthernet"];
+
+/// Address guests on the LAN/hotspot can reach: a private IPv4 address of a real
+/// interface, preferring 192.168.x.x (what hotspots and home routers hand out).
+pub fn detect_lan_ip() -> Result<String, String> {
+    let interfaces = if_addrs::get_if_addrs()
+        .map_err(|e| format!("Failed to list network interfaces: {}", e))?;
+
+    interfaces
+        .iter()
+        .filter(|iface| !iface.is_loopback())
+        .filter(|iface| !VIRTUAL_INTERFACE_PREFIXES.iter().any(|prefix| iface.name.starts_with(prefix)))
+        .filter_map(|iface| match iface.ip() {
+            IpAddr::V4(ip) if ip.is_private() => Some(ip),
+            _ => None,
+        })
+        .min_by_key(|ip| ip.octets()[0] != 192)
+        .map(|ip| ip.to_string())
+        .ok_or_else(|| {
+            "No private IPv4 address found on any network interface. Connect to the LAN or hotspot guests will use, or set the gallery host manually.".to_string()
+        })
+}
+
+/// Access token for a session's gallery: a keyed hash of the session id, so session
+/// URLs can't be guessed by changing the session number.
+pub fn session_token(working_folder: &str, session_id: &str) -> Result<String, String> {
+    let tag = session_mac(working_folder, session_id)?.finalize().into_bytes();
+    Ok(tag.iter().take(TOKEN_BYTES).map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Check a token from a URL against the session's (constant time)
+fn token_matches(working_folder: &str, session_id: &str, token: &str) -> bool {
+    let Some(token) = decode_hex(token).filter(|t| t.len() == TOKEN_BYTES) else {
+        return false;
+    };
+    session_mac(working_folder, session_id)
+        .map(|mac| mac.verify_truncated_left(&token).is_ok())
+        .unwrap_or(false)
+}
+
+fn session_mac(working_folder: &str, session_id: &str) -> Result<Hmac<Sha256>, String> {
+    let secret = load_or_create_secret(working_folder)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(session_id.as_bytes());
+    Ok(mac)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Relative gallery path for a session, e.g. "/s/Myshoot_001/3fa2.../"
+pub fn session_gallery_path(working_folder: &str, session_id: &str) -> Result<String, String> {
+    let token = session_token(working_folder, session_id)?;
+    Ok(format!(
+        "/s/{}/{}/",
+        utf8_percent_encode(session_id, NON_ALPHANUMERIC),
+        token
+    ))
+}
+
+fn load_or_create_secret(working_folder: &str) -> Result<String, String> {
+    let secret_path = Path::new(working_folder).join(GALLERY_SECRET_FILE);
+
+    if let Ok(secret) = fs::read_to_string(&secret_path) {
+        let secret = secret.trim().to_string();
+        if !secret.is_empty() {
+            return Ok(secret);
+        }
+    }
+
+    let secret: String = (0..32).map(|_| format!("{:02x}", rand::random::<u8>())).collect();
+    fs::write(&secret_path, &secret)
+        .map_err(|e| format!("Failed to write gallery secret: {}", e))?;
+    Ok(secret)
+}
+
+// ----------------------------------------------------------------------------
+// Request handling
+// ----------------------------------------------------------------------------
+
+async fn handle_request(
+    context: Arc<GalleryContext>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return Ok(text_response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"));
+    }
+
+    let working_folder = context.working_folder.read().await.clone();
+    let path = req.uri().path().to_string();
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| percent_decode_str(s).decode_utf8_lossy().to_string())
+        .collect();
+
+    let response = match segments.iter().map(|s| s.as_str()).collect::<Vec<_>>().as_slice() {
+        [] => html_response(StatusCode::OK, landing_page()),
+        ["s", session_id, token, rest @ ..] => {
+            match find_session(&context, &working_folder, session_id, token).await {
+                Some(session) => {
+                    let session_dir = Path::new(&working_folder).join(&session.folder_name);
+                    match rest {
+                        [] if !path.ends_with('/') => redirect(&format!("{}/", path)),
+                        [] => gallery_response(&session, &session_dir),
+                        ["download.zip"] => zip_response(&session, &session_dir),
+                        ["files", filename] => file_response(&session_dir, filename, req.headers()).await,
+                        _ => not_found(),
+                    }
+                }
+                None => not_found(),
+            }
+        }
+        _ => not_found(),
+    };
+
+    Ok(response)
+}
+
+/// Look up a session and check its access token
+async fn find_session(
+    context: &GalleryContext,
+    working_folder: &str,
+    session_id: &str,
+    token: &str,
+) -> Option<PtbSessionData> {
+    if !token_matches(working_folder, session_id, token) {
+        return None;
+    }
+    let sessions = load_sessions(context, working_folder).await?;
+    sessions.into_iter().find(|s| s.id == session_id)
+}
+
+/// Sessions of the working folder. The .ptb is only read (with the folder lock held)
+/// when it changed since the last request.
+async fn load_sessions(context: &GalleryContext, working_folder: &str) -> Option<Vec<PtbSessionData>> {
+    let stamp = ptb_stamp(working_folder)?;
+    let mut cache = context.sessions.lock().await;
+    if let Some(cached) = cache.as_ref() {
+        if cached.working_folder == working_folder && cached.stamp == stamp {
+            return Some(cached.sessions.clone());
+        }
+    }
+
+    let _lock = store::lock_folder(working_folder).await;
+    let workspace = match store::read_workspace(working_folder) {
+        Ok(workspace) => workspace?,
+        Err(e) => {
+            println!("[local_gallery] Failed to read the workspace: {}", e);
+            return None;
+        }
+    };
+    // Reading may have migrated or restored the file; stamp the cache with what's on disk now
+    *cache = Some(SessionCache {
+        working_folder: working_folder.to_string(),
+        stamp: ptb_stamp(working_folder).unwrap_or(stamp),
+        sessions: workspace.sessions.clone(),
+    });
+    Some(workspace.sessions)
+}
+
+fn ptb_stamp(working_folder: &str) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(store::ptb_path(working_folder)).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Media files in a session folder that guests can see (photos, collages, GIFs, videos)
+fn list_gallery_files(session_dir: &Path) -> Vec<GalleryFile> {
+    let mut files: Vec<GalleryFile> = fs::read_dir(session_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let filename = path.file_name()?.to_str()?.to_string();
+                    let metadata = entry.metadata().ok()?;
+                    if filename.starts_with('.') || !metadata.is_file() {
+                        return None;
+                    }
+                    let content_type = content_type_for(&path).ok()?;
+                    Some(GalleryFile {
+                        filename,
+                        size: metadata.len(),
+                        is_video: content_type.starts_with("video/"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    files
+}
+
+fn gallery_response(session: &PtbSessionData, session_dir: &Path) -> Response<Body> {
+    let files = list_gallery_files(session_dir);
+    html_response(StatusCode::OK, gallery_page(session, &files))
+}
+
+fn zip_response(session: &PtbSessionData, session_dir: &Path) -> Response<Body> {
+    let files = list_gallery_files(session_dir);
+    // Checked up front: once streaming started, a failure can only cut the download short
+    if let Err(e) = check_zip_limits(files.iter().map(|f| (f.filename.as_str(), f.size))) {
+        return html_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            page_shell(&session.name, &format!("<p class=\"empty\">{} Save the files one by one instead.</p>", escape_html(&e))),
+        );
+    }
+    let files: Vec<PathBuf> = files.into_iter().map(|f| session_dir.join(f.filename)).collect();
+
+    let (sender, body) = Body::channel();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = stream_zip(sender, files).await {
+            println!("[local_gallery] Zip download aborted: {}", e);
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.zip\"", sanitize_filename(&session.name)),
+        )
+        .body(body)
+        .unwrap()
+}
+
+/// A photo or video, streamed from disk. A single byte range is honoured (video
+/// players seek with them); other ranges get the whole file.
+async fn file_response(session_dir: &Path, filename: &str, headers: &header::HeaderMap) -> Response<Body> {
+    // Only plain file names from the listing - never paths
+    if filename.contains(['/', '\\']) || filename.starts_with('.') {
+        return not_found();
+    }
+
+    let path = session_dir.join(filename);
+    let content_type = match content_type_for(&path) {
+        Ok(ct) => ct,
+        Err(_) => return not_found(),
+    };
+    let mut file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(_) => return not_found(),
+    };
+    let size = match file.metadata().await {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        _ => return not_found(),
+    };
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| parse_range(value, size))
+        .unwrap_or(Ok(None));
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "public, max-age=3600")
+        .header(header::ACCEPT_RANGES, "bytes");
+    let (response, start, length) = match range {
+        Ok(Some((start, end))) => (
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size)),
+            start,
+            end - start + 1,
+        ),
+        Ok(None) => (response.status(StatusCode::OK), 0, size),
+        Err(()) => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                .body(Body::empty())
+                .unwrap();
+        }
+    };
+    if start > 0 && file.seek(SeekFrom::Start(start)).await.is_err() {
+        return not_found();
+    }
+
+    let (mut sender, body) = Body::channel();
+    tauri::async_runtime::spawn(async move {
+        let mut remaining = length;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        while remaining > 0 {
+            let want = remaining.min(CHUNK_SIZE as u64) as usize;
+            let n = match file.read(&mut buffer[..want]).await {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) => {
+                    println!("[local_gallery] Failed to read {}: {}", path.display(), e);
+                    sender.abort();
+                    break;
+                }
+            };
+            if sender.send_data(hyper::body::Bytes::copy_from_slice(&buffer[..n])).await.is_err() {
+                break;
+            }
+            remaining -= n as u64;
+        }
+    });
+
+    response
+        .header(header::CONTENT_LENGTH, length)
+        .body(body)
+        .unwrap()
+}
+
+/// Parse a `Range` header for a file of `size` bytes into an inclusive (start, end).
+/// Ok(None): serve the whole file (no range, or a multi-range/unknown unit, which may be ignored).
+/// Err(()): the range can't be satisfied (416).
+fn parse_range(header: &str, size: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Err(());
+    };
+    let range = match (start.trim(), end.trim()) {
+        // Last N bytes
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| ())?;
+            if suffix == 0 || size == 0 {
+                return Err(());
+            }
+            (size.saturating_sub(suffix), size - 1)
+        }
+        (start, "") => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            if start >= size {
+                return Err(());
+            }
+            (start, size - 1)
+        }
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            let end: u64 = end.parse().map_err(|_| ())?;
+            if start > end || start >= size {
+                return Err(());
+            }
+            (start, end.min(size - 1))
+        }
+    };
+    Ok(Some(range))
+}
+
+fn html_response(status: StatusCode, html: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(html))
+        .unwrap()
+}
+
+fn text_response(status: StatusCode, text: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(text.to_string()))
+        .unwrap()
+}
+
+fn redirect(location: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::MOVED_PERMANENTLY)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn not_found() -> Response<Body> {
+    html_response(
+        StatusCode::NOT_FOUND,
+        page_shell("Not found", "<p class=\"empty\">This gallery doesn't exist. Please scan the QR code again.</p>"),
+    )
+}
+
+// ----------------------------------------------------------------------------
+// Pages
+// ----------------------------------------------------------------------------
+
+const PAGE_STYLE: &str = r#"
+* { box-sizing: border-box; }
+body { margin: 0; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif; background: #111; color: #eee; }
+header { padding: 20px 16px 12px; text-align: center; }
+h1 { margin: 0 0 4px; font-size: 22px; }
+.sub { color: #999; font-size: 14px; }
+.download-all { display: inline-block; margin-top: 14px; padding: 12px 22px; border-radius: 999px; background: #fff; color: #111; font-weight: 600; text-decoration: none; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(150px, 1fr)); gap: 8px; padding: 8px; }
+.item { background: #222; border-radius: 8px; overflow: hidden; }
+.item img, .item video { display: block; width: 100%; height: auto; }
+.item a.save { display: block; padding: 8px; text-align: center; color: #ddd; font-size: 13px; text-decoration: none; }
+.empty { text-align: center; color: #999; padding: 40px 16px; }
+"#;
+
+fn page_shell(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{}</title><style>{}</style></head><body>{}</body></html>",
+        escape_html(title),
+        PAGE_STYLE,
+        body
+    )
+}
+
+fn landing_page() -> String {
+    page_shell(
+        "Photobooth Gallery",
+        "<header><h1>Photobooth Gallery</h1></header>\
+         <p class=\"empty\">Scan the QR code from your photo to open your gallery.</p>",
+    )
+}
+
+fn gallery_page(session: &PtbSessionData, files: &[GalleryFile]) -> String {
+    let mut body = format!(
+        "<header><h1>{}</h1><div class=\"sub\">{} file(s)</div>",
+        escape_html(&session.name),
+        files.len()
+    );
+    if !files.is_empty() {
+        body.push_str("<a class=\"download-all\" href=\"download.zip\">Download all</a>");
+    }
+    body.push_str("</header>");
+
+    if files.is_empty() {
+        body.push_str("<p class=\"empty\">No photos yet. Check back in a moment.</p>");
+        return page_shell(&session.name, &body);
+    }
+
+    body.push_str("<div class=\"grid\">");
+    for file in files {
+        let href = format!("files/{}", utf8_percent_encode(&file.filename, NON_ALPHANUMERIC));
+        let name = escape_html(&file.filename);
+        if file.is_video {
+            body.push_str(&format!(
+                "<div class=\"item\"><video src=\"{href}\" controls playsinline preload=\"metadata\"></video>\
+                 <a class=\"save\" href=\"{href}\" download=\"{name}\">Save video</a></div>"
+            ));
+        } else {
+            body.push_str(&format!(
+                "<div class=\"item\"><a href=\"{href}\"><img src=\"{href}\" alt=\"{name}\" loading=\"lazy\"></a>\
+                 <a class=\"save\" href=\"{href}\" download=\"{name}\">Save photo</a></div>"
+            ));
+        }
+    }
+    body.push_str("</div>");
+
+    page_shell(&session.name, &body)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() {
+        "photos".to_string()
+    } else {
+        cleaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let cases = [
+            ("bytes=0-99", Ok(Some((0, 99)))),
+            ("bytes=500-", Ok(Some((500, 999)))),
+            ("bytes=-100", Ok(Some((900, 999)))),
+            ("bytes=900-5000", Ok(Some((900, 999)))),
+            ("bytes=0-1,5-9", Ok(None)),
+            ("items=0-1", Ok(None)),
+            ("bytes=1000-", Err(())),
+            ("bytes=5-1", Err(())),
+            ("bytes=-0", Err(())),
+            ("bytes=abc", Err(())),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(parse_range(header, 1000), expected, "{}", header);
+        }
+    }
+
+    #[test]
+    fn test_session_token() {
+        let folder = std::env::temp_dir().join(format!("gallery_test_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let folder = folder.to_string_lossy().to_string();
+
+        let token = session_token(&folder, "Wedding_001").unwrap();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert_eq!(token, session_token(&folder, "Wedding_001").unwrap());
+        assert!(token_matches(&folder, "Wedding_001", &token));
+        assert!(token_matches(&folder, "Wedding_001", &token.to_uppercase()));
+        assert!(!token_matches(&folder, "Wedding_002", &token));
+        assert!(!token_matches(&folder, "Wedding_001", &token[..16]));
+        assert!(!token_matches(&folder, "Wedding_001", "not hex"));
+        assert_ne!(token, session_token(&folder, "Wedding_002").unwrap());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalGalleryStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    /// Base URL guests can reach, e.g. "http://192.168.4.1:8765"
+    pub base_url: Option<String>,
+    pub working_folder: Option<String>,
+}
+
+/// A file shown in a session's gallery page
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryFile {
+    pub filename: String,
+    pub size: u64,
+    pub is_video: bool,
+}
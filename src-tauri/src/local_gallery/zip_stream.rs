@@ -0,0 +1,334 @@
+use hyper::body::{Bytes, Sender};
+use std::path::PathBuf;
+use tokio::io::AsyncReadExt;
+
+// ----------------------------------------------------------------------------
+// Streaming ZIP writer
+// Photos and videos are already compressed, so entries are STORED (no deflate)
+// and streamed straight from disk. CRC and sizes go into a data descriptor after
+// each entry, so nothing has to be buffered in memory.
+// ----------------------------------------------------------------------------
+
+const LOCAL_HEADER_SIG: u32 = 0x04034b50;
+const DATA_DESCRIPTOR_SIG: u32 = 0x08074b50;
+const CENTRAL_HEADER_SIG: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIR_SIG: u32 = 0x06054b50;
+
+/// General purpose flags: bit 3 = sizes in data descriptor, bit 11 = UTF-8 names
+const ZIP_FLAGS: u16 = (1 << 3) | (1 << 11);
+const ZIP_VERSION: u16 = 20;
+const CHUNK_SIZE: usize = 256 * 1024;
+
+struct CentralEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+/// Entries the end of central directory record can count (without ZIP64)
+const MAX_ENTRIES: usize = u16::MAX as usize;
+
+/// Size of the archive `stream_zip` writes for files of the given names and sizes
+pub fn zip_size<'a>(files: impl IntoIterator<Item = (&'a str, u64)>) -> u64 {
+    let entries: u64 = files
+        .into_iter()
+        // Local header, data, data descriptor and central directory header
+        .map(|(name, size)| 30 + name.len() as u64 + size + 16 + 46 + name.len() as u64)
+        .sum();
+    entries + 22
+}
+
+/// Check that files fit a zip without ZIP64 extensions: at most 65535 entries and
+/// 4 GiB in total
+pub fn check_zip_limits<'a>(files: impl IntoIterator<Item = (&'a str, u64)> + Clone) -> Result<(), String> {
+    let count = files.clone().into_iter().count();
+    if count > MAX_ENTRIES {
+        return Err(format!("Too many files for one download ({}, at most {}).", count, MAX_ENTRIES));
+    }
+    if zip_size(files) > u32::MAX as u64 {
+        return Err("These files are more than 4 GB together, too much for one download.".to_string());
+    }
+    Ok(())
+}
+
+/// Stream a STORED zip archive of `files` into a hyper body channel.
+/// Entries are named after the file name only.
+pub async fn stream_zip(mut sender: Sender, files: Vec<PathBuf>) -> Result<(), String> {
+    let mut entries = Vec::with_capacity(files.len());
+    let mut offset: u64 = 0;
+
+    for path in files {
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
+
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| format!("Failed to open {}: {}", name, e))?;
+        let (dos_time, dos_date) = file
+            .metadata()
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .map(dos_date_time)
+            .unwrap_or((0, 0x21)); // 1980-01-01
+
+        // Local file header (CRC and sizes follow in the data descriptor)
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&LOCAL_HEADER_SIG.to_le_bytes());
+        header.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        header.extend_from_slice(&ZIP_FLAGS.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        header.extend_from_slice(&dos_time.to_le_bytes());
+        header.extend_from_slice(&dos_date.to_le_bytes());
+        header.extend_from_slice(&[0u8; 12]); // crc, compressed size, size
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        header.extend_from_slice(name.as_bytes());
+
+        let entry_offset = to_u32(offset)?;
+        offset += header.len() as u64;
+        send(&mut sender, header).await?;
+
+        // File data
+        let mut crc = Crc32::new();
+        let mut size: u64 = 0;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = file
+                .read(&mut buffer)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            if n == 0 {
+                break;
+            }
+            crc.update(&buffer[..n]);
+            size += n as u64;
+            send(&mut sender, buffer[..n].to_vec()).await?;
+        }
+        offset += size;
+
+        let crc = crc.finish();
+        let size = to_u32(size)?;
+
+        // Data descriptor
+        let mut descriptor = Vec::with_capacity(16);
+        descriptor.extend_from_slice(&DATA_DESCRIPTOR_SIG.to_le_bytes());
+        descriptor.extend_from_slice(&crc.to_le_bytes());
+        descriptor.extend_from_slice(&size.to_le_bytes());
+        descriptor.extend_from_slice(&size.to_le_bytes());
+        offset += descriptor.len() as u64;
+        send(&mut sender, descriptor).await?;
+
+        entries.push(CentralEntry {
+            name,
+            crc,
+            size,
+            offset: entry_offset,
+            dos_time,
+            dos_date,
+        });
+    }
+
+    // Central directory
+    let central_start = to_u32(offset)?;
+    let mut central = Vec::new();
+    for entry in &entries {
+        central.extend_from_slice(&CENTRAL_HEADER_SIG.to_le_bytes());
+        central.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // version made by
+        central.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // version needed
+        central.extend_from_slice(&ZIP_FLAGS.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        central.extend_from_slice(&entry.dos_time.to_le_bytes());
+        central.extend_from_slice(&entry.dos_date.to_le_bytes());
+        central.extend_from_slice(&entry.crc.to_le_bytes());
+        central.extend_from_slice(&entry.size.to_le_bytes());
+        central.extend_from_slice(&entry.size.to_le_bytes());
+        central.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        central.extend_from_slice(&[0u8; 12]); // extra, comment, disk start, internal and external attrs
+        central.extend_from_slice(&entry.offset.to_le_bytes());
+        central.extend_from_slice(entry.name.as_bytes());
+    }
+    let central_size = central.len() as u32;
+
+    // End of central directory record
+    central.extend_from_slice(&END_OF_CENTRAL_DIR_SIG.to_le_bytes());
+    central.extend_from_slice(&[0u8; 4]); // disk numbers
+    central.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    central.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    central.extend_from_slice(&central_size.to_le_bytes());
+    central.extend_from_slice(&central_start.to_le_bytes());
+    central.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+    send(&mut sender, central).await
+}
+
+async fn send(sender: &mut Sender, data: Vec<u8>) -> Result<(), String> {
+    sender
+        .send_data(Bytes::from(data))
+        .await
+        .map_err(|e| format!("Client disconnected: {}", e))
+}
+
+fn to_u32(value: u64) -> Result<u32, String> {
+    u32::try_from(value).map_err(|_| "Archive is larger than 4 GB".to_string())
+}
+
+/// MS-DOS time and date fields for a file timestamp (local time)
+fn dos_date_time(time: std::time::SystemTime) -> (u16, u16) {
+    use chrono::{Datelike, Timelike};
+
+    let dt: chrono::DateTime<chrono::Local> = time.into();
+    if dt.year() < 1980 {
+        return (0, 0x21);
+    }
+    let dos_time = ((dt.hour() << 11) | (dt.minute() << 5) | (dt.second() / 2)) as u16;
+    let dos_date = (((dt.year() - 1980) as u32) << 9 | (dt.month() << 5) | dt.day()) as u16;
+    (dos_time, dos_date)
+}
+
+/// CRC-32 (IEEE 802.3), as required by the ZIP format
+struct Crc32 {
+    value: u32,
+}
+
+impl Crc32 {
+    fn new() -> Self {
+        Self { value: 0xFFFF_FFFF }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        let table = crc32_table();
+        for &byte in data {
+            self.value = table[((self.value ^ byte as u32) & 0xFF) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.value ^ 0xFFFF_FFFF
+    }
+}
+
+fn crc32_table() -> &'static [u32; 256] {
+    static TABLE: once_cell::sync::Lazy<[u32; 256]> = once_cell::sync::Lazy::new(|| {
+        let mut table = [0u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut c = i as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+            *entry = c;
+        }
+        table
+    });
+    &TABLE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Body;
+
+    fn u16_at(data: &[u8], pos: usize) -> u16 {
+        u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+    }
+
+    fn u32_at(data: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_crc32() {
+        let cases: &[(&[u8], u32)] = &[
+            (b"", 0),
+            (b"a", 0xE8B7_BE43),
+            (b"123456789", 0xCBF4_3926),
+            (b"The quick brown fox jumps over the lazy dog", 0x414F_A339),
+        ];
+        for (data, expected) in cases {
+            let mut crc = Crc32::new();
+            crc.update(data);
+            assert_eq!(crc.finish(), *expected, "{:?}", String::from_utf8_lossy(data));
+        }
+        // Updating in chunks gives the same result
+        let mut crc = Crc32::new();
+        crc.update(b"12345");
+        crc.update(b"6789");
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_check_zip_limits() {
+        assert!(check_zip_limits([("IMG_0001.jpg", 5_000_000)]).is_ok());
+        assert!(check_zip_limits([("a.mp4", 3_000_000_000), ("b.mp4", 1_500_000_000)]).is_err());
+        let names: Vec<String> = (0..=MAX_ENTRIES).map(|i| format!("{}.jpg", i)).collect();
+        assert!(check_zip_limits(names.iter().map(|n| (n.as_str(), 0))).is_err());
+        assert!(check_zip_limits(names[1..].iter().map(|n| (n.as_str(), 0))).is_ok());
+    }
+
+    /// Read the archive back through its central directory, checking every entry
+    #[tokio::test]
+    async fn test_stream_zip_round_trip() {
+        let dir = std::env::temp_dir().join(format!("zip_stream_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let contents: Vec<(&str, Vec<u8>)> = vec![
+            ("IMG_0001.jpg", b"123456789".to_vec()),
+            ("Café.mp4", (0..600_000u32).map(|i| (i % 251) as u8).collect()),
+            ("empty.gif", Vec::new()),
+        ];
+        let files: Vec<PathBuf> = contents
+            .iter()
+            .map(|(name, data)| {
+                std::fs::write(dir.join(name), data).unwrap();
+                dir.join(name)
+            })
+            .collect();
+
+        let (sender, body) = Body::channel();
+        let writer = tokio::spawn(stream_zip(sender, files));
+        let zip = hyper::body::to_bytes(body).await.unwrap();
+        writer.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(zip.len() as u64, zip_size(contents.iter().map(|(n, d)| (*n, d.len() as u64))));
+
+        // End of central directory record
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(&zip, end), END_OF_CENTRAL_DIR_SIG);
+        assert_eq!(u16_at(&zip, end + 10) as usize, contents.len());
+        let central_size = u32_at(&zip, end + 12) as usize;
+        let mut pos = u32_at(&zip, end + 16) as usize;
+        assert_eq!(pos + central_size, end);
+
+        for (name, data) in &contents {
+            assert_eq!(u32_at(&zip, pos), CENTRAL_HEADER_SIG);
+            let crc = u32_at(&zip, pos + 16);
+            let size = u32_at(&zip, pos + 24) as usize;
+            let name_len = u16_at(&zip, pos + 28) as usize;
+            let offset = u32_at(&zip, pos + 42) as usize;
+            assert_eq!(&zip[pos + 46..pos + 46 + name_len], name.as_bytes());
+            assert_eq!(size, data.len());
+            let mut expected_crc = Crc32::new();
+            expected_crc.update(data);
+            assert_eq!(crc, expected_crc.finish(), "{}", name);
+
+            // Local header, data, then the data descriptor
+            assert_eq!(u32_at(&zip, offset), LOCAL_HEADER_SIG);
+            assert_eq!(u16_at(&zip, offset + 6), ZIP_FLAGS);
+            assert_eq!(u16_at(&zip, offset + 8), 0);
+            assert_eq!(&zip[offset + 30..offset + 30 + name_len], name.as_bytes());
+            let data_start = offset + 30 + name_len;
+            assert_eq!(&zip[data_start..data_start + size], &data[..]);
+            let descriptor = data_start + size;
+            assert_eq!(u32_at(&zip, descriptor), DATA_DESCRIPTOR_SIG);
+            assert_eq!(u32_at(&zip, descriptor + 4), crc);
+            assert_eq!(u32_at(&zip, descriptor + 8) as usize, size);
+
+            pos += 46 + name_len;
+        }
+    }
+}
@@ -0,0 +1,738 @@
+// SQLite catalog of a working folder's sessions, photos, uploads, generated media and prints
+//
+// The .ptb keeps everything in one JSON document, which can't be queried without loading
+// all of it. The catalog (.catalog.db next to the .ptb) holds the same sessions, photos
+// and uploads in indexed tables, plus the GIFs, videos, collages and print jobs made from
+// them, which the .ptb has no place for. It is created on first use by importing the .ptb
+// (and any session folder's .session.json the .ptb doesn't list), and every .ptb save is
+// mirrored into it afterwards, writing only the rows that changed. `export_workspace`
+// rebuilds the .ptb from it.
+
+use super::types::{
+    DriveUploadedImage, GoogleDriveMetadata, PtbPhoto, PtbPhotoGroup, PtbSessionData, PtbWorkspace,
+    SessionUploadTarget, SessionUploadedFile,
+};
+use crate::upload_targets::types::UploadBackend;
+use crate::utils::schema;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Catalog database at the working folder root
+pub const CATALOG_FILE: &str = ".catalog.db";
+
+/// Bumped whenever the tables below change
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    folder_name TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    last_used_at TEXT NOT NULL,
+    shot_count INTEGER NOT NULL,
+    qr_upload_enabled INTEGER NOT NULL,
+    qr_upload_all_images INTEGER NOT NULL,
+    photo_naming_scheme TEXT NOT NULL,
+    upload_backend TEXT,
+    upload_target TEXT NOT NULL,
+    google_drive_metadata TEXT NOT NULL,
+    photo_groups TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS photos (
+    session_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    filename TEXT NOT NULL,
+    original_path TEXT NOT NULL,
+    camera_path TEXT NOT NULL,
+    captured_at TEXT NOT NULL,
+    captured_ms INTEGER,
+    camera_id TEXT,
+    group_id TEXT,
+    sha256 TEXT,
+    raw_filename TEXT,
+    PRIMARY KEY (session_id, filename)
+);
+CREATE INDEX IF NOT EXISTS photos_by_session ON photos (session_id, position);
+CREATE INDEX IF NOT EXISTS photos_by_capture_time ON photos (captured_ms);
+CREATE TABLE IF NOT EXISTS uploads (
+    id INTEGER PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    filename TEXT NOT NULL,
+    backend TEXT NOT NULL,
+    remote_id TEXT NOT NULL,
+    link TEXT,
+    uploaded_at TEXT NOT NULL,
+    legacy_drive INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS uploads_by_photo ON uploads (session_id, filename);
+CREATE TABLE IF NOT EXISTS media (
+    id INTEGER PRIMARY KEY,
+    session_id TEXT,
+    kind TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    source_photos TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS media_by_session ON media (session_id);
+CREATE TABLE IF NOT EXISTS print_jobs (
+    id INTEGER PRIMARY KEY,
+    session_id TEXT,
+    file_path TEXT NOT NULL,
+    copies INTEGER NOT NULL,
+    printer TEXT,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS print_jobs_by_session ON print_jobs (session_id);
+";
+
+/// Columns written by `import_workspace`, after the key columns
+const SESSION_COLUMNS: &[&str] = &[
+    "position",
+    "name",
+    "folder_name",
+    "created_at",
+    "last_used_at",
+    "shot_count",
+    "qr_upload_enabled",
+    "qr_upload_all_images",
+    "photo_naming_scheme",
+    "upload_backend",
+    "upload_target",
+    "google_drive_metadata",
+    "photo_groups",
+];
+const PHOTO_ROW_COLUMNS: &[&str] = &[
+    "position",
+    "original_path",
+    "camera_path",
+    "captured_at",
+    "captured_ms",
+    "camera_id",
+    "group_id",
+    "sha256",
+    "raw_filename",
+];
+
+const PHOTO_COLUMNS: &str = "p.session_id, p.filename, p.original_path, p.camera_path, p.captured_at, \
+                             p.camera_id, p.group_id, p.sha256, p.raw_filename";
+
+/// A photo with the session it belongs to
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogPhoto {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub photo: PtbPhoto,
+}
+
+/// A GIF, video or collage generated from session photos
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogMedia {
+    #[serde(default)]
+    pub id: i64,
+    pub session_id: Option<String>,
+    /// "gif" | "video" | "collage"
+    pub kind: String,
+    pub file_path: String,
+    /// Filenames of the photos it was made from
+    #[serde(default)]
+    pub source_photos: Vec<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// A print sent to the printer
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogPrintJob {
+    #[serde(default)]
+    pub id: i64,
+    pub session_id: Option<String>,
+    pub file_path: String,
+    #[serde(default = "default_copies")]
+    pub copies: u32,
+    #[serde(default)]
+    pub printer: Option<String>,
+    /// "queued" | "printed" | "failed"
+    #[serde(default = "default_print_status")]
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+fn default_copies() -> u32 {
+    1
+}
+
+fn default_print_status() -> String {
+    "queued".to_string()
+}
+
+const MEDIA_KINDS: &[&str] = &["gif", "video", "collage"];
+const PRINT_STATUSES: &[&str] = &["queued", "printed", "failed"];
+
+fn catalog_path(folder_path: &str) -> PathBuf {
+    Path::new(folder_path).join(CATALOG_FILE)
+}
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("Catalog database error: {}", e)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("Failed to serialize catalog entry: {}", e))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(json: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse catalog entry: {}", e))
+}
+
+/// Milliseconds since the epoch, for comparing capture times written with different offsets
+fn timestamp_ms(rfc3339: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(rfc3339).ok().map(|t| t.timestamp_millis())
+}
+
+/// INSERT of `keys` then `columns` (as ?1, ?2, ...) that updates an existing row with the
+/// same keys, and leaves it untouched when no column changed
+fn upsert_sql(table: &str, keys: &[&str], columns: &[&str]) -> String {
+    let all: Vec<&str> = keys.iter().chain(columns).copied().collect();
+    let placeholders: Vec<String> = (1..=all.len()).map(|i| format!("?{}", i)).collect();
+    let set: Vec<String> = columns.iter().map(|c| format!("{c} = excluded.{c}")).collect();
+    let current: Vec<String> = columns.iter().map(|c| format!("{table}.{c}")).collect();
+    let excluded: Vec<String> = columns.iter().map(|c| format!("excluded.{c}")).collect();
+    format!(
+        "INSERT INTO {table} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {} WHERE ({}) IS NOT ({})",
+        all.join(", "),
+        placeholders.join(", "),
+        keys.join(", "),
+        set.join(", "),
+        current.join(", "),
+        excluded.join(", "),
+    )
+}
+
+/// An uploads row without its id
+#[derive(PartialEq, Eq, Hash)]
+struct UploadRow {
+    filename: String,
+    backend: String,
+    remote_id: String,
+    link: Option<String>,
+    uploaded_at: String,
+    legacy_drive: bool,
+}
+
+fn photo_from_row(row: &Row) -> rusqlite::Result<CatalogPhoto> {
+    Ok(CatalogPhoto {
+        session_id: row.get(0)?,
+        photo: PtbPhoto {
+            filename: row.get(1)?,
+            original_path: row.get(2)?,
+            camera_path: row.get(3)?,
+            captured_at: row.get(4)?,
+            camera_id: row.get(5)?,
+            group_id: row.get(6)?,
+            sha256: row.get(7)?,
+            raw_filename: row.get(8)?,
+        },
+    })
+}
+
+fn media_from_row(row: &Row) -> rusqlite::Result<(CatalogMedia, String)> {
+    Ok((
+        CatalogMedia {
+            id: row.get(0)?,
+            session_id: row.get(1)?,
+            kind: row.get(2)?,
+            file_path: row.get(3)?,
+            source_photos: Vec::new(),
+            created_at: row.get(5)?,
+        },
+        row.get(4)?,
+    ))
+}
+
+fn print_job_from_row(row: &Row) -> rusqlite::Result<CatalogPrintJob> {
+    Ok(CatalogPrintJob {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        file_path: row.get(2)?,
+        copies: row.get(3)?,
+        printer: row.get(4)?,
+        status: row.get(5)?,
+        error: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+/// Whether the working folder has a catalog yet
+pub fn exists(folder_path: &str) -> bool {
+    catalog_path(folder_path).exists()
+}
+
+/// Mirror a saved workspace into the folder's catalog, if it has one.
+/// The .ptb is already on disk, so a failure here is only logged.
+pub fn sync_workspace(folder_path: &str, workspace: &PtbWorkspace) {
+    if !exists(folder_path) {
+        return;
+    }
+    if let Err(e) = Catalog::open(folder_path).and_then(|mut catalog| catalog.import_workspace(workspace)) {
+        eprintln!("[catalog] Failed to update the catalog of {}: {}", folder_path, e);
+    }
+}
+
+pub struct Catalog {
+    conn: Connection,
+}
+
+impl Catalog {
+    /// Open (or create) the folder's catalog. Call with the folder lock held.
+    pub fn open(folder_path: &str) -> Result<Self, String> {
+        let conn = Connection::open(catalog_path(folder_path))
+            .map_err(|e| format!("Failed to open catalog database: {}", e))?;
+        conn.busy_timeout(Duration::from_secs(5)).map_err(db_error)?;
+
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(db_error)?;
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "The catalog in this folder was created by a newer version of the app (schema {}, this version supports {})",
+                version, SCHEMA_VERSION
+            ));
+        }
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(db_error)?;
+        Ok(Self { conn })
+    }
+
+    /// Whether the one-time import from the .ptb has run
+    pub fn is_imported(&self) -> Result<bool, String> {
+        self.conn
+            .query_row("SELECT 1 FROM meta WHERE key = 'imported_at'", [], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+            .map_err(db_error)
+    }
+
+    /// Bring the sessions, photos and uploads in line with the workspace's. Rows are
+    /// upserted and only written when they changed; rows the workspace no longer has are
+    /// deleted. Generated media and print jobs are kept.
+    pub fn import_workspace(&mut self, workspace: &PtbWorkspace) -> Result<(), String> {
+        // Workspace settings are stored as the workspace without its sessions
+        let settings = to_json(&PtbWorkspace {
+            sessions: Vec::new(),
+            ..workspace.clone()
+        })?;
+
+        let tx = self.conn.transaction().map_err(db_error)?;
+        {
+            let mut upsert_session = tx.prepare(&upsert_sql("sessions", &["id"], SESSION_COLUMNS)).map_err(db_error)?;
+            let mut upsert_photo = tx
+                .prepare(&upsert_sql("photos", &["session_id", "filename"], PHOTO_ROW_COLUMNS))
+                .map_err(db_error)?;
+            let mut insert_upload = tx
+                .prepare(
+                    "INSERT INTO uploads (session_id, filename, backend, remote_id, link, uploaded_at, legacy_drive)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )
+                .map_err(db_error)?;
+            let mut photo_filenames = tx.prepare("SELECT filename FROM photos WHERE session_id = ?1").map_err(db_error)?;
+            let mut upload_rows = tx
+                .prepare(
+                    "SELECT id, filename, backend, remote_id, link, uploaded_at, legacy_drive
+                     FROM uploads WHERE session_id = ?1",
+                )
+                .map_err(db_error)?;
+
+            let listed: HashSet<&str> = workspace.sessions.iter().map(|s| s.id.as_str()).collect();
+            let stored: Vec<String> = tx
+                .prepare("SELECT id FROM sessions")
+                .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect())
+                .map_err(db_error)?;
+            for id in stored.iter().filter(|id| !listed.contains(id.as_str())) {
+                tx.execute("DELETE FROM sessions WHERE id = ?1", params![id]).map_err(db_error)?;
+                tx.execute("DELETE FROM photos WHERE session_id = ?1", params![id]).map_err(db_error)?;
+                tx.execute("DELETE FROM uploads WHERE session_id = ?1", params![id]).map_err(db_error)?;
+            }
+
+            for (position, session) in workspace.sessions.iter().enumerate() {
+                // Uploaded files live in the uploads table
+                let upload_target = SessionUploadTarget {
+                    uploaded_files: Vec::new(),
+                    ..session.upload_target.clone()
+                };
+                let drive_metadata = GoogleDriveMetadata {
+                    uploaded_images: Vec::new(),
+                    ..session.google_drive_metadata.clone()
+                };
+                let backend = session.upload_target.backend.as_ref().map(|b| b.as_str());
+                upsert_session
+                    .execute(params![
+                        session.id,
+                        position as i64,
+                        session.name,
+                        session.folder_name,
+                        session.created_at,
+                        session.last_used_at,
+                        session.shot_count,
+                        session.qr_upload_enabled,
+                        session.qr_upload_all_images,
+                        session.photo_naming_scheme,
+                        backend,
+                        to_json(&upload_target)?,
+                        to_json(&drive_metadata)?,
+                        to_json(&session.photo_groups)?,
+                    ])
+                    .map_err(|e| format!("Failed to import session {}: {}", session.id, e))?;
+
+                let filenames: HashSet<&str> = session.photos.iter().map(|p| p.filename.as_str()).collect();
+                let stored: Vec<String> = photo_filenames
+                    .query_map(params![session.id], |row| row.get(0))
+                    .and_then(|rows| rows.collect())
+                    .map_err(db_error)?;
+                for filename in stored.iter().filter(|f| !filenames.contains(f.as_str())) {
+                    tx.execute(
+                        "DELETE FROM photos WHERE session_id = ?1 AND filename = ?2",
+                        params![session.id, filename],
+                    )
+                    .map_err(db_error)?;
+                }
+                for (position, photo) in session.photos.iter().enumerate() {
+                    upsert_photo
+                        .execute(params![
+                            session.id,
+                            photo.filename,
+                            position as i64,
+                            photo.original_path,
+                            photo.camera_path,
+                            photo.captured_at,
+                            timestamp_ms(&photo.captured_at),
+                            photo.camera_id,
+                            photo.group_id,
+                            photo.sha256,
+                            photo.raw_filename,
+                        ])
+                        .map_err(|e| format!("Failed to import photo {}: {}", photo.filename, e))?;
+                }
+
+                // Uploads have no natural key: match whole rows, insert the new ones and
+                // delete the ones the workspace dropped
+                let uploads = session
+                    .upload_target
+                    .uploaded_files
+                    .iter()
+                    .map(|file| UploadRow {
+                        filename: file.filename.clone(),
+                        backend: backend.unwrap_or("").to_string(),
+                        remote_id: file.remote_id.clone(),
+                        link: file.link.clone(),
+                        uploaded_at: file.uploaded_at.clone(),
+                        legacy_drive: false,
+                    })
+                    .chain(session.google_drive_metadata.uploaded_images.iter().map(|image| UploadRow {
+                        filename: image.filename.clone(),
+                        backend: UploadBackend::GoogleDrive.as_str().to_string(),
+                        remote_id: image.drive_file_id.clone(),
+                        link: None,
+                        uploaded_at: image.uploaded_at.clone(),
+                        legacy_drive: true,
+                    }));
+                let mut stored: HashMap<UploadRow, Vec<i64>> = HashMap::new();
+                let rows = upload_rows
+                    .query_map(params![session.id], |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            UploadRow {
+                                filename: row.get(1)?,
+                                backend: row.get(2)?,
+                                remote_id: row.get(3)?,
+                                link: row.get(4)?,
+                                uploaded_at: row.get(5)?,
+                                legacy_drive: row.get(6)?,
+                            },
+                        ))
+                    })
+                    .map_err(db_error)?;
+                for row in rows {
+                    let (id, upload) = row.map_err(db_error)?;
+                    stored.entry(upload).or_default().push(id);
+                }
+                for upload in uploads {
+                    if stored.get_mut(&upload).and_then(|ids| ids.pop()).is_some() {
+                        continue;
+                    }
+                    insert_upload
+                        .execute(params![
+                            session.id,
+                            upload.filename,
+                            upload.backend,
+                            upload.remote_id,
+                            upload.link,
+                            upload.uploaded_at,
+                            upload.legacy_drive,
+                        ])
+                        .map_err(db_error)?;
+                }
+                for id in stored.into_values().flatten() {
+                    tx.execute("DELETE FROM uploads WHERE id = ?1", params![id]).map_err(db_error)?;
+                }
+            }
+        }
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES ('workspace', ?1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value WHERE value IS NOT excluded.value",
+            params![settings],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "INSERT OR IGNORE INTO meta (key, value) VALUES ('imported_at', ?1)",
+            params![chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Rebuild the workspace (.ptb contents) from the catalog, or None before the import
+    pub fn export_workspace(&self) -> Result<Option<PtbWorkspace>, String> {
+        let settings: Option<String> = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = 'workspace'", [], |row| row.get(0))
+            .optional()
+            .map_err(db_error)?;
+        let Some(settings) = settings else {
+            return Ok(None);
+        };
+        let mut workspace: PtbWorkspace = from_json(&settings)?;
+
+        let mut sessions_stmt = self
+            .conn
+            .prepare(
+                "SELECT id, name, folder_name, created_at, last_used_at, shot_count, qr_upload_enabled,
+                     qr_upload_all_images, photo_naming_scheme, upload_target, google_drive_metadata, photo_groups
+                 FROM sessions ORDER BY position",
+            )
+            .map_err(db_error)?;
+        let rows = sessions_stmt
+            .query_map([], |row| {
+                Ok((
+                    PtbSessionData {
+                        schema_version: schema::SESSION.current,
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        folder_name: row.get(2)?,
+                        created_at: row.get(3)?,
+                        last_used_at: row.get(4)?,
+                        shot_count: row.get(5)?,
+                        photos: Vec::new(),
+                        google_drive_metadata: GoogleDriveMetadata::default(),
+                        upload_target: SessionUploadTarget::default(),
+                        qr_upload_enabled: row.get(6)?,
+                        qr_upload_all_images: row.get(7)?,
+                        photo_naming_scheme: row.get(8)?,
+                        photo_groups: Vec::new(),
+                    },
+                    row.get::<_, String>(9)?,
+                    row.get::<_, String>(10)?,
+                    row.get::<_, String>(11)?,
+                ))
+            })
+            .map_err(db_error)?;
+
+        for row in rows {
+            let (mut session, upload_target, drive_metadata, photo_groups) = row.map_err(db_error)?;
+            session.upload_target = from_json(&upload_target)?;
+            session.google_drive_metadata = from_json(&drive_metadata)?;
+            session.photo_groups = from_json::<Vec<PtbPhotoGroup>>(&photo_groups)?;
+            session.photos = self.photos_in_session(&session.id)?.into_iter().map(|p| p.photo).collect();
+
+            let mut uploads_stmt = self
+                .conn
+                .prepare_cached(
+                    "SELECT filename, remote_id, link, uploaded_at, legacy_drive
+                     FROM uploads WHERE session_id = ?1 ORDER BY id",
+                )
+                .map_err(db_error)?;
+            let uploads = uploads_stmt
+                .query_map(params![session.id], |row| {
+                    Ok((
+                        SessionUploadedFile {
+                            filename: row.get(0)?,
+                            remote_id: row.get(1)?,
+                            link: row.get(2)?,
+                            uploaded_at: row.get(3)?,
+                        },
+                        row.get::<_, bool>(4)?,
+                    ))
+                })
+                .map_err(db_error)?;
+            for upload in uploads {
+                let (file, legacy_drive) = upload.map_err(db_error)?;
+                if legacy_drive {
+                    session.google_drive_metadata.uploaded_images.push(DriveUploadedImage {
+                        filename: file.filename,
+                        drive_file_id: file.remote_id,
+                        uploaded_at: file.uploaded_at,
+                    });
+                } else {
+                    session.upload_target.uploaded_files.push(file);
+                }
+            }
+            workspace.sessions.push(session);
+        }
+        Ok(Some(workspace))
+    }
+
+    fn query_photos(&self, filter: &str, params: impl rusqlite::Params) -> Result<Vec<CatalogPhoto>, String> {
+        let sql = format!("SELECT {} FROM photos p {}", PHOTO_COLUMNS, filter);
+        let mut stmt = self.conn.prepare_cached(&sql).map_err(db_error)?;
+        let photos = stmt
+            .query_map(params, photo_from_row)
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+        Ok(photos)
+    }
+
+    /// Photos of a session, in capture order
+    pub fn photos_in_session(&self, session_id: &str) -> Result<Vec<CatalogPhoto>, String> {
+        self.query_photos("WHERE p.session_id = ?1 ORDER BY p.position", params![session_id])
+    }
+
+    /// Photos not uploaded to their session's upload target yet (any upload counts for
+    /// sessions without a target), for one session or all of them
+    pub fn photos_not_uploaded(&self, session_id: Option<&str>) -> Result<Vec<CatalogPhoto>, String> {
+        self.query_photos(
+            "JOIN sessions s ON s.id = p.session_id
+             WHERE (?1 IS NULL OR p.session_id = ?1)
+               AND NOT EXISTS (
+                   SELECT 1 FROM uploads u
+                   WHERE u.session_id = p.session_id AND u.filename = p.filename
+                     AND (s.upload_backend IS NULL OR u.backend = s.upload_backend))
+             ORDER BY s.position, p.position",
+            params![session_id],
+        )
+    }
+
+    /// Photos captured in [from, to] (RFC 3339), across all sessions, oldest first
+    pub fn photos_captured_between(&self, from: &str, to: &str) -> Result<Vec<CatalogPhoto>, String> {
+        let from_ms = timestamp_ms(from).ok_or_else(|| format!("Invalid start time: {}", from))?;
+        let to_ms = timestamp_ms(to).ok_or_else(|| format!("Invalid end time: {}", to))?;
+        self.query_photos(
+            "WHERE p.captured_ms BETWEEN ?1 AND ?2 ORDER BY p.captured_ms",
+            params![from_ms, to_ms],
+        )
+    }
+
+    /// Record a generated GIF, video or collage; returns it with its id
+    pub fn add_media(&self, mut media: CatalogMedia) -> Result<CatalogMedia, String> {
+        if !MEDIA_KINDS.contains(&media.kind.as_str()) {
+            return Err(format!("Unknown media kind: {} (expected one of {})", media.kind, MEDIA_KINDS.join(", ")));
+        }
+        media.created_at = chrono::Utc::now().to_rfc3339();
+        self.conn
+            .execute(
+                "INSERT INTO media (session_id, kind, file_path, source_photos, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![media.session_id, media.kind, media.file_path, to_json(&media.source_photos)?, media.created_at],
+            )
+            .map_err(db_error)?;
+        media.id = self.conn.last_insert_rowid();
+        Ok(media)
+    }
+
+    /// Generated media, for one session or all of them, newest first
+    pub fn media(&self, session_id: Option<&str>) -> Result<Vec<CatalogMedia>, String> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT id, session_id, kind, file_path, source_photos, created_at FROM media
+                 WHERE ?1 IS NULL OR session_id = ?1 ORDER BY id DESC",
+            )
+            .map_err(db_error)?;
+        let rows = stmt.query_map(params![session_id], media_from_row).map_err(db_error)?;
+        rows.map(|row| {
+            let (mut media, source_photos) = row.map_err(db_error)?;
+            media.source_photos = from_json(&source_photos)?;
+            Ok(media)
+        })
+        .collect()
+    }
+
+    /// Record a print job; returns it with its id
+    pub fn add_print_job(&self, mut job: CatalogPrintJob) -> Result<CatalogPrintJob, String> {
+        if !PRINT_STATUSES.contains(&job.status.as_str()) {
+            return Err(format!("Unknown print status: {}", job.status));
+        }
+        job.created_at = chrono::Utc::now().to_rfc3339();
+        job.updated_at = job.created_at.clone();
+        self.conn
+            .execute(
+                "INSERT INTO print_jobs (session_id, file_path, copies, printer, status, error, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    job.session_id,
+                    job.file_path,
+                    job.copies,
+                    job.printer,
+                    job.status,
+                    job.error,
+                    job.created_at,
+                    job.updated_at
+                ],
+            )
+            .map_err(db_error)?;
+        job.id = self.conn.last_insert_rowid();
+        Ok(job)
+    }
+
+    /// Update a print job's status once the printer reports back
+    pub fn set_print_job_status(&self, id: i64, status: &str, error: Option<&str>) -> Result<(), String> {
+        if !PRINT_STATUSES.contains(&status) {
+            return Err(format!("Unknown print status: {}", status));
+        }
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE print_jobs SET status = ?2, error = ?3, updated_at = ?4 WHERE id = ?1",
+                params![id, status, error, chrono::Utc::now().to_rfc3339()],
+            )
+            .map_err(db_error)?;
+        if updated == 0 {
+            return Err(format!("Print job not found: {}", id));
+        }
+        Ok(())
+    }
+
+    /// Print jobs, for one session or all of them, newest first
+    pub fn print_jobs(&self, session_id: Option<&str>) -> Result<Vec<CatalogPrintJob>, String> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT id, session_id, file_path, copies, printer, status, error, created_at, updated_at
+                 FROM print_jobs WHERE ?1 IS NULL OR session_id = ?1 ORDER BY id DESC",
+            )
+            .map_err(db_error)?;
+        let jobs = stmt
+            .query_map(params![session_id], print_job_from_row)
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+        Ok(jobs)
+    }
+}
@@ -1,8 +1,16 @@
 use crate::photobooth_sessions::types::{
-    DelaySettings, DriveUploadedImage, GifSettings, GoogleDriveMetadata, PhotoboothSessionInfo, PhotoboothSettings, PhotoExifData, PrintSettings, PtbPhoto,
-    PtbSessionData, PtbWorkspace,
+    DaemonSweepResult, DelaySettings, DriveUploadedImage, GifSettings, GoogleDriveMetadata, PhotoGroupCapture, PhotoboothSessionInfo, PhotoboothSettings, PhotoExifData, PrintSettings, PtbPhoto,
+    PtbPhotoGroup, PtbPhotoGroupMember, PtbSessionData, PtbWorkspace, SessionUploadTarget, SessionUploadedFile,
 };
+use crate::daemon_auth::{load_daemon_token, with_daemon_auth};
+use crate::photobooth_sessions::catalog::{self, Catalog, CatalogMedia, CatalogPhoto, CatalogPrintJob};
+use crate::photobooth_sessions::naming::{self, NamingContext, NamingSchemePreview, NamingTemplate};
+use crate::photobooth_sessions::store::{self, WorkspaceGuard};
+use crate::photobooth_sessions::daemon_transfer::{daemon_photo_url, fetch_photo_verified, list_daemon_photos, DaemonPhoto};
+use crate::upload_targets::types::{RemoteFile, UploadBackend};
 use crate::working_folder::commands::generate_cached_thumbnail_high_res;
+use crate::utils::schema;
+use crate::working_folder::raw;
 use std::fs;
 
 /// Scan for existing session folders in the working folder
@@ -46,12 +54,14 @@ fn scan_existing_sessions(folder_path: &str) -> Vec<PtbSessionData> {
                                 fs::read_to_string(&session_json_path)
                                     .ok()
                                     .and_then(|content| {
-                                        serde_json::from_str::<PtbSessionData>(&content).ok()
+                                        schema::SESSION.parse::<PtbSessionData>(&content).ok()
                                     })
+                                    .map(|(session, _)| session)
                             } else {
                                 // Create minimal session data from folder metadata
                                 path.metadata().ok().and_then(|metadata| {
                                     Some(PtbSessionData {
+                                        schema_version: schema::SESSION.current,
                                         id: folder_name.to_string(),
                                         name: format!("Session {}", num_str),
                                         folder_name: folder_name.to_string(),
@@ -82,9 +92,11 @@ fn scan_existing_sessions(folder_path: &str) -> Vec<PtbSessionData> {
                                         shot_count: 0,
                                         photos: Vec::new(),
                                         google_drive_metadata: GoogleDriveMetadata::default(),
+                                        upload_target: SessionUploadTarget::default(),
                                         qr_upload_enabled: true,
                                         qr_upload_all_images: false,
                                         photo_naming_scheme: "IPH_{number}".to_string(),
+                                        photo_groups: Vec::new(),
                                     })
                                 })
                             };
@@ -109,28 +121,31 @@ fn scan_existing_sessions(folder_path: &str) -> Vec<PtbSessionData> {
     sessions
 }
 
-/// Load or create .ptb workspace file at root level
+/// Load or create .ptb workspace file at root level. Call with the folder lock held.
 /// Returns (workspace, was_created) where was_created indicates if a new file was created
-async fn load_ptb_workspace_internal(folder_path: String) -> Result<(PtbWorkspace, bool), String> {
-    let ptb_path = std::path::Path::new(&folder_path).join(".ptb");
-
-    if ptb_path.exists() {
-        let content = fs::read_to_string(&ptb_path)
-            .map_err(|e| format!("Failed to read .ptb file: {}", e))?;
-
-        let workspace: PtbWorkspace = serde_json::from_str(&content)
-            .map_err(|e| format!("The workspace file (.ptb) is corrupted and could not be read ({}). Open the file to fix it manually, or choose a new folder.", e))?;
+fn open_ptb_workspace(folder_path: &str) -> Result<(PtbWorkspace, bool), String> {
+    if let Some(workspace) = store::read_workspace(folder_path)? {
         println!(
             "[load_ptb_workspace_internal] Loaded existing .ptb file with {} sessions",
             workspace.sessions.len()
         );
         Ok((workspace, false))
+    } else if let Some(workspace) = workspace_from_catalog(folder_path)? {
+        // The .ptb is gone but the catalog still has the workspace: saving a fresh one
+        // would mirror it over the catalog and lose every session
+        store::write_workspace(folder_path, &workspace)?;
+        println!(
+            "[load_ptb_workspace_internal] Restored .ptb file from the catalog with {} sessions",
+            workspace.sessions.len()
+        );
+        Ok((workspace, false))
     } else {
         // Create a new workspace, but first scan for existing session folders
-        let existing_sessions = scan_existing_sessions(&folder_path);
+        let existing_sessions = scan_existing_sessions(folder_path);
 
         let now = chrono::Utc::now().to_rfc3339();
         let workspace = PtbWorkspace {
+            schema_version: schema::WORKSPACE.current,
             name: "Photobooth Workspace".to_string(),
             created_at: now.clone(),
             last_used_at: now,
@@ -143,20 +158,41 @@ async fn load_ptb_workspace_internal(folder_path: String) -> Result<(PtbWorkspac
         };
 
         // Save the new workspace to disk
-        let json = serde_json::to_string_pretty(&workspace)
-            .map_err(|e| format!("Failed to serialize .ptb workspace: {}", e))?;
-        fs::write(&ptb_path, json)
-            .map_err(|e| format!("Failed to write .ptb file: {}", e))?;
+        store::write_workspace(folder_path, &workspace)?;
 
         println!(
-            "[load_ptb_workspace_internal] Created new .ptb file at: {:?} with {} existing sessions",
-            ptb_path,
+            "[load_ptb_workspace_internal] Created new .ptb file in: {} with {} existing sessions",
+            folder_path,
             workspace.sessions.len()
         );
         Ok((workspace, true))
     }
 }
 
+/// The workspace stored in the folder's catalog, if it has an imported one
+fn workspace_from_catalog(folder_path: &str) -> Result<Option<PtbWorkspace>, String> {
+    if !catalog::exists(folder_path) {
+        return Ok(None);
+    }
+    store::flush_catalog_sync(folder_path);
+    Catalog::open(folder_path)?.export_workspace()
+}
+
+/// Load or create .ptb workspace file at root level
+/// Returns (workspace, was_created) where was_created indicates if a new file was created
+async fn load_ptb_workspace_internal(folder_path: String) -> Result<(PtbWorkspace, bool), String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_ptb_workspace(&folder_path)
+}
+
+/// Load the workspace with its folder locked, for a read-modify-write that ends in
+/// `save()`. Other writers wait until then, so no change is lost.
+async fn lock_ptb_workspace(folder_path: &str) -> Result<WorkspaceGuard, String> {
+    let lock = store::lock_folder(folder_path).await;
+    let (workspace, _) = open_ptb_workspace(folder_path)?;
+    Ok(WorkspaceGuard::new(lock, folder_path, workspace))
+}
+
 /// Load or create .ptb workspace file at root level (Tauri command wrapper)
 #[tauri::command]
 pub async fn load_ptb_workspace(folder_path: String) -> Result<PtbWorkspace, String> {
@@ -171,19 +207,14 @@ pub async fn save_ptb_workspace(
     folder_path: String,
     workspace: PtbWorkspace,
 ) -> Result<(), String> {
-    let ptb_path = std::path::Path::new(&folder_path).join(".ptb");
+    let _lock = store::lock_folder(&folder_path).await;
 
     let workspace_to_save = PtbWorkspace {
         last_used_at: chrono::Utc::now().to_rfc3339(),
         ..workspace
     };
 
-    let json = serde_json::to_string_pretty(&workspace_to_save)
-        .map_err(|e| format!("Failed to serialize .ptb workspace: {}", e))?;
-
-    fs::write(&ptb_path, json).map_err(|e| format!("Failed to write .ptb file: {}", e))?;
-
-    Ok(())
+    store::write_workspace(&folder_path, &workspace_to_save)
 }
 
 /// Save delay settings to the .ptb workspace file
@@ -192,12 +223,12 @@ pub async fn save_delay_settings(
     folder_path: String,
     delay_settings: DelaySettings,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     workspace.delay_settings = delay_settings;
     workspace.last_used_at = chrono::Utc::now().to_rfc3339();
 
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     Ok(())
 }
 
@@ -207,12 +238,13 @@ pub async fn save_photobooth_settings(
     folder_path: String,
     photobooth_settings: PhotoboothSettings,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    NamingTemplate::parse(&photobooth_settings.photo_naming_scheme)?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     workspace.photobooth_settings = photobooth_settings;
     workspace.last_used_at = chrono::Utc::now().to_rfc3339();
 
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     Ok(())
 }
 
@@ -222,12 +254,12 @@ pub async fn save_gif_settings(
     folder_path: String,
     gif_settings: GifSettings,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     workspace.gif_settings = gif_settings;
     workspace.last_used_at = chrono::Utc::now().to_rfc3339();
 
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     Ok(())
 }
 
@@ -237,12 +269,12 @@ pub async fn save_print_settings(
     folder_path: String,
     print_settings: PrintSettings,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     workspace.print_settings = print_settings;
     workspace.last_used_at = chrono::Utc::now().to_rfc3339();
 
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     Ok(())
 }
 
@@ -257,6 +289,7 @@ fn ptb_session_to_info(session: &PtbSessionData) -> PhotoboothSessionInfo {
         last_used_at: session.last_used_at.clone(),
         thumbnails: Vec::new(), // Empty thumbnails for simple conversion
         google_drive_metadata: session.google_drive_metadata.clone(),
+        upload_target: session.upload_target.clone(),
     }
 }
 
@@ -333,6 +366,7 @@ async fn ptb_session_to_info_with_thumbnails(
         last_used_at: session.last_used_at.clone(),
         thumbnails,
         google_drive_metadata: session.google_drive_metadata.clone(),
+        upload_target: session.upload_target.clone(),
     }
 }
 
@@ -345,6 +379,16 @@ pub async fn list_photobooth_sessions(
 ) -> Result<(Vec<PhotoboothSessionInfo>, bool), String> {
     let (workspace, was_created) = load_ptb_workspace_internal(folder_path.clone()).await?;
 
+    // Resume any uploads journaled in this working folder (e.g. after a crash or restart)
+    {
+        use tauri::Manager;
+        let queue = app.state::<crate::upload_queue::UploadQueueStateWrapper>().queue.clone();
+        queue.set_app_handle(app.clone()).await;
+        if let Err(e) = queue.restore_from_folder(&folder_path).await {
+            println!("[list_photobooth_sessions] Failed to restore upload queue: {}", e);
+        }
+    }
+
     // Convert sessions to info format with thumbnails (in parallel for performance)
     let sessions_futures: Vec<_> = workspace
         .sessions
@@ -369,7 +413,7 @@ pub async fn create_photobooth_session(
     folder_path: String,
     session_name: String,
 ) -> Result<PhotoboothSessionInfo, String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     // Get the base name from the working folder (e.g., "Myshoot" from "C:\Photos\Myshoot")
     let base_name = std::path::Path::new(&folder_path)
@@ -434,6 +478,7 @@ pub async fn create_photobooth_session(
     // Create session data
     let now = chrono::Utc::now().to_rfc3339();
     let session_data = PtbSessionData {
+        schema_version: schema::SESSION.current,
         id: session_id.clone(),
         name: session_name.clone(),
         folder_name: session_id.clone(),
@@ -442,9 +487,11 @@ pub async fn create_photobooth_session(
         shot_count: 0,
         photos: Vec::new(),
         google_drive_metadata: GoogleDriveMetadata::default(),
+        upload_target: SessionUploadTarget::default(),
         qr_upload_enabled: true,
         qr_upload_all_images: false,
         photo_naming_scheme: "IPH_{number}".to_string(),
+        photo_groups: Vec::new(),
     };
 
     // Create session info
@@ -453,7 +500,7 @@ pub async fn create_photobooth_session(
     // Add to workspace and save
     workspace.sessions.push(session_data);
     workspace.current_session_id = Some(session_info.id.clone());
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
 
     Ok(session_info)
 }
@@ -482,12 +529,12 @@ pub async fn get_current_session(
 /// Set the current active session
 #[tauri::command]
 pub async fn set_current_session(folder_path: String, session_id: String) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     // Verify session exists
     if workspace.sessions.iter().any(|s| s.id == session_id) {
         workspace.current_session_id = Some(session_id);
-        save_ptb_workspace(folder_path, workspace).await?;
+        workspace.save()?;
         Ok(())
     } else {
         Err(format!("Session not found: {}", session_id))
@@ -519,48 +566,125 @@ pub async fn update_session_drive_metadata(
     folder_link: Option<String>,
     account_id: Option<String>,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
         session.google_drive_metadata.folder_id = folder_id;
         session.google_drive_metadata.folder_name = folder_name;
         session.google_drive_metadata.folder_link = folder_link;
         session.google_drive_metadata.account_id = account_id;
+        session.upload_target = SessionUploadTarget::from_drive_metadata(&session.google_drive_metadata);
         session.last_used_at = chrono::Utc::now().to_rfc3339();
 
-        save_ptb_workspace(folder_path, workspace).await?;
+        workspace.save()?;
         Ok(())
     } else {
         Err(format!("Session not found: {}", session_id))
     }
 }
 
-/// Add an uploaded image to session's Google Drive metadata
+/// Update where a session's files are uploaded (any backend)
 #[tauri::command]
-pub async fn add_session_drive_upload(
+pub async fn update_session_upload_target(
+    folder_path: String,
+    session_id: String,
+    backend: UploadBackend,
+    remote_location: Option<String>,
+    remote_name: Option<String>,
+    remote_link: Option<String>,
+    account_id: Option<String>,
+) -> Result<(), String> {
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
+
+    if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
+        // Uploaded files only make sense for the location they were uploaded to
+        let same_location = session.upload_target.backend.as_ref() == Some(&backend)
+            && session.upload_target.remote_location == remote_location;
+        if !same_location {
+            session.upload_target.uploaded_files.clear();
+        }
+
+        if backend == UploadBackend::GoogleDrive {
+            session.google_drive_metadata.folder_id = remote_location.clone();
+            session.google_drive_metadata.folder_name = remote_name.clone();
+            session.google_drive_metadata.folder_link = remote_link.clone();
+            session.google_drive_metadata.account_id = account_id.clone();
+        }
+
+        session.upload_target.backend = Some(backend);
+        session.upload_target.remote_location = remote_location;
+        session.upload_target.remote_name = remote_name;
+        session.upload_target.remote_link = remote_link;
+        session.upload_target.account_id = account_id;
+        session.last_used_at = chrono::Utc::now().to_rfc3339();
+
+        workspace.save()?;
+        Ok(())
+    } else {
+        Err(format!("Session not found: {}", session_id))
+    }
+}
+
+/// Record a file uploaded by the upload queue in the session metadata
+pub async fn add_session_upload(
     folder_path: String,
     session_id: String,
     filename: String,
-    drive_file_id: String,
+    backend: UploadBackend,
+    remote_file: RemoteFile,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
-        let upload = DriveUploadedImage {
+        let uploaded_at = chrono::Utc::now().to_rfc3339();
+
+        if backend == UploadBackend::GoogleDrive {
+            session.google_drive_metadata.uploaded_images.push(DriveUploadedImage {
+                filename: filename.clone(),
+                drive_file_id: remote_file.remote_id.clone(),
+                uploaded_at: uploaded_at.clone(),
+            });
+        }
+
+        if session.upload_target.backend.is_none() {
+            session.upload_target.backend = Some(backend);
+        }
+        session.upload_target.uploaded_files.push(SessionUploadedFile {
             filename,
-            drive_file_id,
-            uploaded_at: chrono::Utc::now().to_rfc3339(),
-        };
-        session.google_drive_metadata.uploaded_images.push(upload);
+            remote_id: remote_file.remote_id,
+            link: remote_file.link,
+            uploaded_at,
+        });
         session.last_used_at = chrono::Utc::now().to_rfc3339();
 
-        save_ptb_workspace(folder_path, workspace).await?;
+        workspace.save()?;
         Ok(())
     } else {
         Err(format!("Session not found: {}", session_id))
     }
 }
 
+/// Add an uploaded image to session's Google Drive metadata
+#[tauri::command]
+pub async fn add_session_drive_upload(
+    folder_path: String,
+    session_id: String,
+    filename: String,
+    drive_file_id: String,
+) -> Result<(), String> {
+    add_session_upload(
+        folder_path,
+        session_id,
+        filename,
+        UploadBackend::GoogleDrive,
+        RemoteFile {
+            remote_id: drive_file_id,
+            link: None,
+        },
+    )
+    .await
+}
+
 /// Check if an image has been uploaded to Google Drive for a session
 #[tauri::command]
 pub async fn is_image_uploaded_to_drive(
@@ -575,7 +699,12 @@ pub async fn is_image_uploaded_to_drive(
             .google_drive_metadata
             .uploaded_images
             .iter()
-            .any(|img| img.filename == filename);
+            .any(|img| img.filename == filename)
+            || session
+                .upload_target
+                .uploaded_files
+                .iter()
+                .any(|f| f.filename == filename);
         Ok(is_uploaded)
     } else {
         Err(format!("Session not found: {}", session_id))
@@ -588,13 +717,14 @@ pub async fn clear_session_drive_uploads(
     folder_path: String,
     session_id: String,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
         session.google_drive_metadata.uploaded_images.clear();
+        session.upload_target.uploaded_files.clear();
         session.last_used_at = chrono::Utc::now().to_rfc3339();
 
-        save_ptb_workspace(folder_path, workspace).await?;
+        workspace.save()?;
         Ok(())
     } else {
         Err(format!("Session not found: {}", session_id))
@@ -609,7 +739,7 @@ pub async fn update_session_qr_setting(
     qr_upload_all_images: bool,
     qr_upload_enabled: Option<bool>,
 ) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
         session.qr_upload_all_images = qr_upload_all_images;
@@ -618,31 +748,63 @@ pub async fn update_session_qr_setting(
         }
         session.last_used_at = chrono::Utc::now().to_rfc3339();
 
-        save_ptb_workspace(folder_path, workspace).await?;
+        workspace.save()?;
         Ok(())
     } else {
         Err(format!("Session not found: {}", session_id))
     }
 }
 
-/// Update photo naming scheme for a session
+/// Update photo naming scheme for a session.
+/// Invalid templates are rejected; returns the file name the next photo would get.
 #[tauri::command]
 pub async fn update_session_naming_scheme(
     folder_path: String,
     session_id: String,
     photo_naming_scheme: String,
-) -> Result<(), String> {
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+) -> Result<NamingSchemePreview, String> {
+    let template = NamingTemplate::parse(&photo_naming_scheme)?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
-    if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
-        session.photo_naming_scheme = photo_naming_scheme;
-        session.last_used_at = chrono::Utc::now().to_rfc3339();
+    let Some(position) = workspace.sessions.iter().position(|s| s.id == session_id) else {
+        return Err(format!("Session not found: {}", session_id));
+    };
+    let session = &mut workspace.sessions[position];
+    session.photo_naming_scheme = photo_naming_scheme.trim().to_string();
+    session.last_used_at = chrono::Utc::now().to_rfc3339();
+
+    // Preview with the session's last photo standing in for the camera's file name
+    let session_folder = std::path::Path::new(&folder_path).join(&session.folder_name);
+    let original = session
+        .photos
+        .last()
+        .and_then(|p| std::path::Path::new(&p.original_path).file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("IMG_0001.JPG")
+        .to_string();
+    let context = NamingContext {
+        event: std::path::Path::new(&folder_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default(),
+        session: &session.name,
+        session_number: naming::session_number(&session.folder_name).unwrap_or(position as u32 + 1),
+        captured_at: chrono::Local::now(),
+        camera_model: None,
+        camera_id: session.photos.last().and_then(|p| p.camera_id.as_deref()),
+        seq: session.shot_count + 1,
+        original: &original,
+    };
+    let next_filename = template.filename(&context, "jpg", |name| {
+        session.photos.iter().any(|p| p.filename == name) || session_folder.join(name).exists()
+    });
+    let preview = NamingSchemePreview {
+        photo_naming_scheme: session.photo_naming_scheme.clone(),
+        next_filename,
+    };
 
-        save_ptb_workspace(folder_path, workspace).await?;
-        Ok(())
-    } else {
-        Err(format!("Session not found: {}", session_id))
-    }
+    workspace.save()?;
+    Ok(preview)
 }
 
 /// Save photo data to session folder and update root .ptb file
@@ -671,7 +833,7 @@ pub async fn save_photo_to_working_folder(
         .map_err(|e| format!("Failed to write photo file: {}", e))?;
 
     // Load workspace, update session, and save
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     // Find and update the session
     let updated_session = if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
@@ -680,6 +842,10 @@ pub async fn save_photo_to_working_folder(
             original_path: original_daemon_path,
             camera_path,
             captured_at: chrono::Utc::now().to_rfc3339(),
+            camera_id: None,
+            group_id: None,
+            sha256: None,
+            raw_filename: None,
         };
         session.photos.push(photo_entry);
         session.shot_count = session.photos.len() as u32;
@@ -691,7 +857,7 @@ pub async fn save_photo_to_working_folder(
     };
 
     // Save updated workspace
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
 
     Ok(updated_session.unwrap())
 }
@@ -751,11 +917,54 @@ pub async fn save_file_to_path(
     Ok(())
 }
 
+/// Record a downloaded photo in its group capture, creating the group on first use.
+/// Members stay sorted by camera id (numeric where possible) so the group plays
+/// back as a left-to-right sweep.
+fn add_photo_to_group(
+    session: &mut PtbSessionData,
+    group: &PhotoGroupCapture,
+    camera_id: String,
+    filename: String,
+    captured_at: String,
+) {
+    let index = match session.photo_groups.iter().position(|g| g.id == group.group_id) {
+        Some(index) => index,
+        None => {
+            session.photo_groups.push(PtbPhotoGroup {
+                id: group.group_id.clone(),
+                captured_at,
+                max_skew_ms: group.max_skew_ms,
+                members: Vec::new(),
+            });
+            session.photo_groups.len() - 1
+        }
+    };
+    let entry = &mut session.photo_groups[index];
+    if group.max_skew_ms.is_some() {
+        entry.max_skew_ms = group.max_skew_ms;
+    }
+    entry.members.push(PtbPhotoGroupMember {
+        camera_id,
+        filename,
+        trigger_skew_ms: group.trigger_skew_ms,
+    });
+    entry.members.sort_by(|a, b| {
+        match (a.camera_id.parse::<u32>(), b.camera_id.parse::<u32>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => a.camera_id.cmp(&b.camera_id),
+        }
+    });
+}
+
 /// Download photo directly from daemon and save to session folder
 /// This is much faster than passing binary data through JS/IPC
 /// Photos are saved to: {working_folder}/{session_id}/{filename}
+/// The download is resumed if the connection drops and verified against the daemon's SHA-256
+/// `raw_filename` is the RAW of the same shot (RAW+JPEG mode); it is stored next to the photo.
+/// A RAW `filename` is stored as-is and converted to a JPEG for the session.
 #[tauri::command]
 pub async fn download_photo_from_daemon(
+    app: tauri::AppHandle,
     daemon_url: String,
     folder_path: String,
     session_id: String,
@@ -763,6 +972,44 @@ pub async fn download_photo_from_daemon(
     camera_path: String,
     original_daemon_path: String,
     photo_naming_scheme: String,
+    camera_id: Option<String>,
+    group: Option<PhotoGroupCapture>,
+    raw_filename: Option<String>,
+) -> Result<PtbSessionData, String> {
+    let daemon_token = load_daemon_token(&app);
+    download_photo_from_daemon_internal(
+        daemon_url,
+        folder_path,
+        session_id,
+        filename,
+        camera_path,
+        original_daemon_path,
+        photo_naming_scheme,
+        camera_id,
+        group,
+        daemon_token.as_deref(),
+        None,
+        raw_filename.map(|name| (name, None)),
+        None,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn download_photo_from_daemon_internal(
+    daemon_url: String,
+    folder_path: String,
+    session_id: String,
+    filename: String,
+    camera_path: String,
+    original_daemon_path: String,
+    photo_naming_scheme: String,
+    camera_id: Option<String>,
+    group: Option<PhotoGroupCapture>,
+    daemon_token: Option<&str>,
+    expected_sha256: Option<&str>,
+    raw: Option<(String, Option<String>)>,
+    daemon_captured_at_ms: Option<u64>,
 ) -> Result<PtbSessionData, String> {
     println!("[Rust::download_photo_from_daemon] START");
     println!("[Rust::download_photo_from_daemon] daemon_url: {}", daemon_url);
@@ -789,55 +1036,109 @@ pub async fn download_photo_from_daemon(
     );
 
     // Download photo directly from daemon
-    let photo_url = format!("{}/api/photo/{}", daemon_url, filename);
+    // Photos from additional cameras live in that camera's directory on the daemon
+    let photo_url = daemon_photo_url(&daemon_url, &filename, camera_id.as_deref(), "");
     println!(
         "[Rust::download_photo_from_daemon] photo_url: {}",
         photo_url
     );
 
     let client = reqwest::Client::new();
-    let response = client.get(&photo_url).send().await.map_err(|e| {
-        println!(
-            "[Rust::download_photo_from_daemon] ERROR: Failed to fetch photo from daemon: {}",
-            e
-        );
-        format!("Failed to fetch photo from daemon: {}", e)
+    let (photo_data, sha256) = fetch_photo_verified(
+        &client,
+        &photo_url,
+        daemon_token,
+        camera_id.as_deref(),
+        &filename,
+        expected_sha256,
+    )
+    .await
+    .map_err(|e| {
+        println!("[Rust::download_photo_from_daemon] ERROR: {}", e);
+        e
     })?;
 
     println!(
-        "[Rust::download_photo_from_daemon] response status: {}",
-        response.status()
+        "[Rust::download_photo_from_daemon] photo_data size: {} bytes, sha256: {}",
+        photo_data.len(),
+        sha256
     );
 
-    if !response.status().is_success() {
-        println!("[Rust::download_photo_from_daemon] ERROR: Daemon returned non-success status");
-        return Err(format!("Daemon returned error: {}", response.status()));
-    }
-
-    let photo_data = response.bytes().await.map_err(|e| {
-        println!(
-            "[Rust::download_photo_from_daemon] ERROR: Failed to read photo data: {}",
-            e
-        );
-        format!("Failed to read photo data: {}", e)
-    })?;
+    // Fetch the paired RAW before writing anything, so a failure leaves both on the daemon
+    let raw_download = match &raw {
+        Some((raw_name, expected_raw_sha256)) => {
+            let raw_url = daemon_photo_url(&daemon_url, raw_name, camera_id.as_deref(), "");
+            let (raw_data, _) = fetch_photo_verified(
+                &client,
+                &raw_url,
+                daemon_token,
+                camera_id.as_deref(),
+                raw_name,
+                expected_raw_sha256.as_deref(),
+            )
+            .await?;
+            println!(
+                "[Rust::download_photo_from_daemon] RAW {} size: {} bytes",
+                raw_name,
+                raw_data.len()
+            );
+            Some((raw_name.clone(), raw_data))
+        }
+        None => None,
+    };
 
-    println!(
-        "[Rust::download_photo_from_daemon] photo_data size: {} bytes",
-        photo_data.len()
-    );
+    // A RAW on its own is stored with a JPEG for the rest of the app. A demosaic can take
+    // seconds, so it is made before the workspace is locked.
+    let (photo_data, converted_jpeg) = if raw_download.is_none() && raw::is_raw(std::path::Path::new(&filename)) {
+        let (photo_data, jpeg) = tokio::task::spawn_blocking(move || {
+            let jpeg = raw::raw_to_jpeg(&photo_data);
+            (photo_data, jpeg)
+        })
+        .await
+        .map_err(|e| format!("RAW conversion task failed: {}", e))?;
+        match jpeg {
+            Ok(jpeg) => (photo_data, Some(jpeg)),
+            Err(e) => {
+                eprintln!("[Rust::download_photo_from_daemon] WARN: Keeping RAW without a JPEG: {}", e);
+                (photo_data, None)
+            }
+        }
+    } else {
+        (photo_data, None)
+    };
 
-    // Load workspace first to determine the next photo number
+    // Load workspace first to determine the next photo number. It stays locked until the
+    // photo is recorded, so two cameras downloading at once can't get the same number.
     println!("[Rust::download_photo_from_daemon] Loading workspace to determine photo number");
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
     println!(
         "[Rust::download_photo_from_daemon] Workspace loaded, sessions count: {}",
         workspace.sessions.len()
     );
 
+    // After a reconnect the same photo can arrive twice (the live event and a sweep);
+    // whichever download gets here second only releases it on the daemon
+    if let Some(session) = workspace
+        .sessions
+        .iter()
+        .find(|s| s.photos.iter().any(|p| p.sha256.as_deref() == Some(sha256.as_str())))
+    {
+        let session = session.clone();
+        drop(workspace);
+        println!(
+            "[Rust::download_photo_from_daemon] {} is already stored in session {}, releasing it on the daemon",
+            filename, session.id
+        );
+        release_daemon_photo(&client, &daemon_url, &filename, camera_id.as_deref(), daemon_token).await;
+        if let Some((raw_name, _)) = &raw {
+            release_daemon_photo(&client, &daemon_url, raw_name, camera_id.as_deref(), daemon_token).await;
+        }
+        return Ok(session);
+    }
+
     // Find the session and determine the next photo number
-    let (next_photo_num, session_folder) = if let Some(session) =
-        workspace.sessions.iter().find(|s| s.id == session_id)
+    let (next_photo_num, session_folder, session_name, session_number) = if let Some((position, session)) =
+        workspace.sessions.iter().enumerate().find(|(_, s)| s.id == session_id)
     {
         // Use shot_count instead of photos.len() to handle deletions correctly
         // shot_count tracks total photos taken, not current count
@@ -847,7 +1148,8 @@ pub async fn download_photo_from_daemon(
             "[Rust::download_photo_from_daemon] Session found: {}, next photo number: {}",
             session.name, next_num
         );
-        (next_num, folder)
+        let number = naming::session_number(&session.folder_name).unwrap_or(position as u32 + 1);
+        (next_num, folder, session.name.clone(), number)
     } else {
         println!(
             "[Rust::download_photo_from_daemon] ERROR: Session not found: {}",
@@ -864,32 +1166,59 @@ pub async fn download_photo_from_daemon(
     }
 
     // Generate custom filename using the naming scheme
-    // Default to "photo_{number}" if scheme is empty
-    let scheme = if photo_naming_scheme.is_empty() {
-        "photo_{number}"
-    } else {
-        photo_naming_scheme.as_str()
-    };
+    let template = NamingTemplate::parse_or_default(&photo_naming_scheme);
 
     let extension = std::path::Path::new(&filename)
         .extension()
         .and_then(|e| e.to_str())
         .unwrap_or("jpg");
-
-    // Replace {number} placeholder with 4-digit zero-padded number.
-    // If {number} is not in the scheme, append the number to prevent file overwrites.
-    let custom_filename = if scheme.contains("{number}") {
-        scheme.replace("{number}", &format!("{:04}", next_photo_num))
+    let raw_extension = raw_download.as_ref().and_then(|(raw_name, _)| {
+        std::path::Path::new(raw_name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_string())
+    });
+    let camera_model = if template.uses_camera_model() {
+        naming::exif_camera_model(&photo_data)
     } else {
-        format!("{}_{:04}", scheme, next_photo_num)
+        None
     };
 
-    // Add extension if not already present in the scheme
-    let custom_filename = if custom_filename.contains('.') {
-        custom_filename
-    } else {
-        format!("{}.{}", custom_filename, extension)
+    // Swept photos may be hours old: name them by when they were shot, not downloaded
+    let captured_at = naming::exif_captured_at(&photo_data)
+        .or_else(|| {
+            use chrono::TimeZone;
+            daemon_captured_at_ms.and_then(|ms| chrono::Local.timestamp_millis_opt(ms as i64).single())
+        })
+        .unwrap_or_else(chrono::Local::now);
+
+    let event = std::path::Path::new(&folder_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let context = NamingContext {
+        event,
+        session: &session_name,
+        session_number,
+        captured_at,
+        camera_model: camera_model.as_deref(),
+        camera_id: camera_id.as_deref(),
+        seq: next_photo_num,
+        original: &filename,
     };
+    // A name is taken if a photo of the session has it, or the file (or its RAW or JPEG) exists
+    let custom_filename = template.filename(&context, extension, |name| {
+        let path = session_folder.join(name);
+        workspace
+            .sessions
+            .iter()
+            .filter(|s| s.id == session_id)
+            .flat_map(|s| s.photos.iter())
+            .any(|p| p.filename == name || p.raw_filename.as_deref() == Some(name))
+            || path.exists()
+            || raw_extension.as_ref().is_some_and(|ext| path.with_extension(ext).exists())
+            || (converted_jpeg.is_some() && raw::preview_path(&path).exists())
+    });
 
     println!(
         "[Rust::download_photo_from_daemon] Generated custom filename: {}",
@@ -910,18 +1239,55 @@ pub async fn download_photo_from_daemon(
         custom_photo_path
     );
 
+    // The RAW keeps the photo's name with its own extension
+    let (custom_filename, raw_custom_filename) = match raw_download {
+        Some((raw_name, raw_data)) => {
+            let raw_extension = std::path::Path::new(&raw_name)
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("raw");
+            let raw_path = custom_photo_path.with_extension(raw_extension);
+            fs::write(&raw_path, &raw_data).map_err(|e| format!("Failed to write RAW file: {}", e))?;
+            println!("[Rust::download_photo_from_daemon] RAW written: {:?}", raw_path);
+            (custom_filename, file_name_of(&raw_path))
+        }
+        None => match converted_jpeg {
+            Some(jpeg) => {
+                let jpeg_path = raw::preview_path(&custom_photo_path);
+                fs::write(&jpeg_path, jpeg).map_err(|e| format!("Failed to write JPEG of RAW: {}", e))?;
+                println!("[Rust::download_photo_from_daemon] JPEG of RAW written: {:?}", jpeg_path);
+                (file_name_of(&jpeg_path).unwrap_or(custom_filename.clone()), Some(custom_filename))
+            }
+            None => (custom_filename, None),
+        },
+    };
+
     // Find and update the session
     let updated_session = if let Some(session) =
         workspace.sessions.iter_mut().find(|s| s.id == session_id)
     {
         println!("[Rust::download_photo_from_daemon] Found session, adding photo entry");
+        let captured_at = chrono::Utc::now().to_rfc3339();
         let photo_entry = PtbPhoto {
             filename: custom_filename.clone(),
             original_path: original_daemon_path,
             camera_path,
-            captured_at: chrono::Utc::now().to_rfc3339(),
+            captured_at: captured_at.clone(),
+            camera_id: camera_id.clone(),
+            group_id: group.as_ref().map(|g| g.group_id.clone()),
+            sha256: Some(sha256),
+            raw_filename: raw_custom_filename,
         };
         session.photos.push(photo_entry);
+        if let Some(group) = &group {
+            add_photo_to_group(
+                session,
+                group,
+                camera_id.clone().unwrap_or_else(|| "0".to_string()),
+                custom_filename.clone(),
+                captured_at,
+            );
+        }
         session.shot_count += 1; // Increment shot counter (handles deletions correctly)
         session.last_used_at = chrono::Utc::now().to_rfc3339();
         println!(
@@ -940,12 +1306,125 @@ pub async fn download_photo_from_daemon(
 
     // Save updated workspace
     println!("[Rust::download_photo_from_daemon] Saving workspace");
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     println!("[Rust::download_photo_from_daemon] Workspace saved successfully");
 
-    // Delete photo from daemon after successful download to prevent duplicate filename conflicts on camera restart
-    let delete_url = format!("{}/api/photo/{}", daemon_url, filename);
-    match client.delete(&delete_url).send().await {
+    release_daemon_photo(&client, &daemon_url, &filename, camera_id.as_deref(), daemon_token).await;
+    if let Some((raw_name, _)) = &raw {
+        release_daemon_photo(&client, &daemon_url, raw_name, camera_id.as_deref(), daemon_token).await;
+    }
+
+    println!("[Rust::download_photo_from_daemon] END - returning session");
+    Ok(updated_session.unwrap())
+}
+
+/// Store a RAW that reached the daemon after its JPEG was announced (raw_attached) next
+/// to the session photo downloaded from `original_daemon_path`
+#[tauri::command]
+pub async fn attach_daemon_raw(
+    app: tauri::AppHandle,
+    daemon_url: String,
+    folder_path: String,
+    session_id: String,
+    original_daemon_path: String,
+    raw_filename: String,
+    camera_id: Option<String>,
+) -> Result<PtbSessionData, String> {
+    let daemon_token = load_daemon_token(&app);
+    // Look the photo up without keeping the workspace locked while the RAW transfers
+    let (workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let session = workspace
+        .sessions
+        .iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    let session_folder = std::path::Path::new(&folder_path).join(&session.folder_name);
+    let photo_filename = session
+        .photos
+        .iter()
+        .rev()
+        .find(|p| p.original_path == original_daemon_path)
+        .map(|p| p.filename.clone())
+        .ok_or_else(|| format!("No photo from {} in session {}", original_daemon_path, session_id))?;
+
+    let client = reqwest::Client::new();
+    let raw_url = daemon_photo_url(&daemon_url, &raw_filename, camera_id.as_deref(), "");
+    let (raw_data, _) = fetch_photo_verified(
+        &client,
+        &raw_url,
+        daemon_token.as_deref(),
+        camera_id.as_deref(),
+        &raw_filename,
+        None,
+    )
+    .await?;
+
+    let raw_extension = std::path::Path::new(&raw_filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("raw");
+    let raw_path = session_folder.join(&photo_filename).with_extension(raw_extension);
+    fs::write(&raw_path, &raw_data).map_err(|e| format!("Failed to write RAW file: {}", e))?;
+    println!("[attach_daemon_raw] {} stored as {:?}", raw_filename, raw_path);
+
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
+    let session = workspace
+        .sessions
+        .iter_mut()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    let photo = session
+        .photos
+        .iter_mut()
+        .rev()
+        .find(|p| p.original_path == original_daemon_path)
+        .ok_or_else(|| format!("No photo from {} in session {}", original_daemon_path, session_id))?;
+    photo.raw_filename = file_name_of(&raw_path);
+
+    let updated_session = session.clone();
+    workspace.save()?;
+    release_daemon_photo(&client, &daemon_url, &raw_filename, camera_id.as_deref(), daemon_token.as_deref()).await;
+    Ok(updated_session)
+}
+
+fn file_name_of(path: &std::path::Path) -> Option<String> {
+    path.file_name().and_then(|n| n.to_str()).map(|n| n.to_string())
+}
+
+/// Tell the daemon a photo is stored: acknowledge it (so cleanup may evict it even if
+/// the delete fails), then delete it to prevent duplicate filename conflicts on camera restart
+async fn release_daemon_photo(
+    client: &reqwest::Client,
+    daemon_url: &str,
+    filename: &str,
+    camera_id: Option<&str>,
+    daemon_token: Option<&str>,
+) {
+    let ack_url = daemon_photo_url(daemon_url, filename, camera_id, "/ack");
+    match with_daemon_auth(client.post(&ack_url), daemon_token).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            println!(
+                "[Rust::download_photo_from_daemon] Acknowledged photo on daemon: {}",
+                filename
+            );
+        }
+        Ok(resp) => {
+            eprintln!(
+                "[Rust::download_photo_from_daemon] WARN: Failed to acknowledge photo on daemon (status {}): {}",
+                resp.status(),
+                filename
+            );
+        }
+        Err(e) => {
+            eprintln!(
+                "[Rust::download_photo_from_daemon] WARN: Failed to acknowledge photo on daemon: {} - {}",
+                filename, e
+            );
+        }
+    }
+
+    let delete_url = daemon_photo_url(daemon_url, filename, camera_id, "");
+    match with_daemon_auth(client.delete(&delete_url), daemon_token).send().await {
         Ok(resp) if resp.status().is_success() => {
             println!(
                 "[Rust::download_photo_from_daemon] Deleted photo from daemon: {}",
@@ -966,9 +1445,131 @@ pub async fn download_photo_from_daemon(
             );
         }
     }
+}
 
-    println!("[Rust::download_photo_from_daemon] END - returning session");
-    Ok(updated_session.unwrap())
+/// Photos younger than this are left to the live photo_downloaded handler
+const SWEEP_MIN_AGE_MS: u64 = 30_000;
+
+/// Sweep up photos still stored on the daemon (e.g. taken while the app was disconnected)
+/// into a session. Photos already in the workspace (same SHA-256) are only released on
+/// the daemon. A RAW with a JPEG of the same name is stored with that JPEG; a RAW
+/// on its own is converted like any other RAW download.
+#[tauri::command]
+pub async fn sweep_daemon_photos(
+    app: tauri::AppHandle,
+    daemon_url: String,
+    folder_path: String,
+    session_id: String,
+    photo_naming_scheme: String,
+) -> Result<DaemonSweepResult, String> {
+    let daemon_token = load_daemon_token(&app);
+    let client = reqwest::Client::new();
+    let (photos, daemon_now_ms) = list_daemon_photos(&client, &daemon_url, daemon_token.as_deref()).await?;
+
+    let (workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let stored: std::collections::HashSet<String> = workspace
+        .sessions
+        .iter()
+        .flat_map(|s| s.photos.iter())
+        .filter_map(|p| p.sha256.clone())
+        .collect();
+
+    // RAW+JPEG pairs share camera and file stem
+    let pair_key = |photo: &DaemonPhoto| {
+        let stem = std::path::Path::new(&photo.filename).with_extension("");
+        (photo.camera_id.clone(), stem.to_string_lossy().to_string())
+    };
+    let is_jpeg = |photo: &DaemonPhoto| {
+        let extension = std::path::Path::new(&photo.filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        matches!(extension.as_str(), "jpg" | "jpeg")
+    };
+    let jpeg_keys: std::collections::HashSet<_> = photos.iter().filter(|p| is_jpeg(p)).map(pair_key).collect();
+    let mut raws: std::collections::HashMap<_, DaemonPhoto> = photos
+        .iter()
+        .filter(|p| raw::is_raw(std::path::Path::new(&p.filename)))
+        .map(|p| (pair_key(p), p.clone()))
+        .collect();
+
+    let mut result = DaemonSweepResult::default();
+    for photo in photos {
+        let path = std::path::Path::new(&photo.filename);
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        let is_raw = raw::is_raw(path);
+        if !matches!(extension.as_str(), "jpg" | "jpeg" | "png") && !is_raw {
+            continue;
+        }
+        // Paired RAWs go with their JPEG
+        if is_raw && jpeg_keys.contains(&pair_key(&photo)) {
+            continue;
+        }
+        if daemon_now_ms.is_some_and(|now| now.saturating_sub(photo.captured_at_ms) < SWEEP_MIN_AGE_MS) {
+            continue;
+        }
+        let paired_raw = if is_jpeg(&photo) { raws.remove(&pair_key(&photo)) } else { None };
+
+        if stored.contains(&photo.sha256) {
+            println!(
+                "[sweep_daemon_photos] {} (camera {}) is already stored, releasing it on the daemon",
+                photo.filename, photo.camera_id
+            );
+            release_daemon_photo(&client, &daemon_url, &photo.filename, Some(photo.camera_id.as_str()), daemon_token.as_deref()).await;
+            if let Some(paired_raw) = &paired_raw {
+                release_daemon_photo(&client, &daemon_url, &paired_raw.filename, Some(photo.camera_id.as_str()), daemon_token.as_deref()).await;
+            }
+            result.already_stored.push(photo.filename);
+            continue;
+        }
+
+        println!(
+            "[sweep_daemon_photos] Recovering {} (camera {}, {} bytes{})",
+            photo.filename,
+            photo.camera_id,
+            photo.size,
+            paired_raw.as_ref().map(|r| format!(", RAW {}", r.filename)).unwrap_or_default()
+        );
+        match download_photo_from_daemon_internal(
+            daemon_url.clone(),
+            folder_path.clone(),
+            session_id.clone(),
+            photo.filename.clone(),
+            String::new(),
+            photo.filename.clone(),
+            photo_naming_scheme.clone(),
+            Some(photo.camera_id.clone()),
+            None,
+            daemon_token.as_deref(),
+            Some(photo.sha256.as_str()),
+            paired_raw.map(|r| (r.filename, Some(r.sha256))),
+            Some(photo.captured_at_ms),
+        )
+        .await
+        {
+            Ok(session) => {
+                result.downloaded.push(photo.filename);
+                result.session = Some(session);
+            }
+            Err(e) => {
+                eprintln!("[sweep_daemon_photos] Failed to recover {}: {}", photo.filename, e);
+                result.failed.push(format!("{}: {}", photo.filename, e));
+            }
+        }
+    }
+
+    println!(
+        "[sweep_daemon_photos] Recovered {}, already stored {}, failed {}",
+        result.downloaded.len(),
+        result.already_stored.len(),
+        result.failed.len()
+    );
+    Ok(result)
 }
 
 /// Delete a single photo from a session
@@ -981,7 +1582,7 @@ pub async fn delete_session_photo(
 ) -> Result<PtbSessionData, String> {
     println!("[delete_session_photo] Starting deletion for photo: {} in session: {}", filename, session_id);
 
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     // Find the session to get its folder name
     let session_folder_name = workspace
@@ -1025,16 +1626,38 @@ pub async fn delete_session_photo(
         );
     }
 
+    // The RAW kept next to the photo goes with it
+    let raw_filename = workspace
+        .sessions
+        .iter()
+        .find(|s| s.id == session_id)
+        .and_then(|s| s.photos.iter().find(|p| p.filename == filename))
+        .and_then(|p| p.raw_filename.clone());
+    if let Some(raw_filename) = raw_filename {
+        let raw_path = std::path::Path::new(&folder_path).join(&session_folder_name).join(&raw_filename);
+        match fs::remove_file(&raw_path) {
+            Ok(()) => println!("[delete_session_photo] Deleted RAW: {:?}", raw_path),
+            Err(e) => eprintln!("[delete_session_photo] WARN: Failed to delete RAW {:?}: {}", raw_path, e),
+        }
+    }
+
     // Remove photo from session metadata and update workspace
     let updated_session = if let Some(session) = workspace.sessions.iter_mut().find(|s| s.id == session_id) {
         println!("[delete_session_photo] Removing photo from session metadata");
 
         // Also remove from Google Drive uploaded images if present
         session.google_drive_metadata.uploaded_images.retain(|img| img.filename != filename);
+        session.upload_target.uploaded_files.retain(|f| f.filename != filename);
 
         // Remove from photos array
         let original_len = session.photos.len();
         session.photos.retain(|p| p.filename != filename);
+
+        // Drop it from any group capture, and groups left empty
+        for group in session.photo_groups.iter_mut() {
+            group.members.retain(|m| m.filename != filename);
+        }
+        session.photo_groups.retain(|g| !g.members.is_empty());
         let removed_count = original_len - session.photos.len();
 
         if removed_count > 0 {
@@ -1058,7 +1681,7 @@ pub async fn delete_session_photo(
 
     // Save updated workspace
     println!("[delete_session_photo] Saving workspace");
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     println!("[delete_session_photo] Workspace saved successfully");
 
     println!("[delete_session_photo] END - deletion complete");
@@ -1074,7 +1697,7 @@ pub async fn delete_photobooth_session(
 ) -> Result<(), String> {
     println!("[delete_photobooth_session] Starting deletion for session: {}", session_id);
 
-    let (mut workspace, _) = load_ptb_workspace_internal(folder_path.clone()).await?;
+    let mut workspace = lock_ptb_workspace(&folder_path).await?;
 
     // Find the session to get its folder name
     let session_folder_name = workspace
@@ -1140,12 +1763,12 @@ pub async fn delete_photobooth_session(
     // If the deleted session was the current session, clear current_session_id
     // or set it to the first available session
     if workspace.current_session_id.as_ref() == Some(&session_id) {
-        if let Some(first_session) = workspace.sessions.first() {
-            workspace.current_session_id = Some(first_session.id.clone());
+        if let Some(first_session_id) = workspace.sessions.first().map(|s| s.id.clone()) {
             println!(
                 "[delete_photobooth_session] Changed current session to: {}",
-                first_session.id
+                first_session_id
             );
+            workspace.current_session_id = Some(first_session_id);
         } else {
             workspace.current_session_id = None;
             println!(
@@ -1155,7 +1778,7 @@ pub async fn delete_photobooth_session(
     }
 
     // Save updated workspace
-    save_ptb_workspace(folder_path, workspace).await?;
+    workspace.save()?;
     println!("[delete_photobooth_session] Workspace saved successfully");
 
     println!("[delete_photobooth_session] END - deletion complete");
@@ -1300,3 +1923,122 @@ pub async fn get_photo_exif(
         date_taken,
     })
 }
+
+/// Open the folder's catalog. The first time, it is filled from the .ptb plus any session
+/// folder whose .session.json the .ptb doesn't list (those are added to the .ptb too, so
+/// the two agree). Call with the folder lock held.
+fn open_catalog(folder_path: &str) -> Result<Catalog, String> {
+    store::flush_catalog_sync(folder_path);
+    let mut catalog = Catalog::open(folder_path)?;
+    if catalog.is_imported()? {
+        return Ok(catalog);
+    }
+
+    let (mut workspace, _) = open_ptb_workspace(folder_path)?;
+    let unlisted: Vec<PtbSessionData> = scan_existing_sessions(folder_path)
+        .into_iter()
+        .filter(|s| std::path::Path::new(folder_path).join(&s.folder_name).join(".session.json").exists())
+        .filter(|s| !workspace.sessions.iter().any(|w| w.id == s.id || w.folder_name == s.folder_name))
+        .collect();
+    if !unlisted.is_empty() {
+        println!(
+            "[catalog] Adding {} session(s) found only in .session.json files to the workspace",
+            unlisted.len()
+        );
+        workspace.sessions.extend(unlisted);
+        store::write_workspace(folder_path, &workspace)?;
+    }
+
+    catalog.import_workspace(&workspace)?;
+    println!(
+        "[catalog] Created catalog for {} with {} sessions and {} photos",
+        folder_path,
+        workspace.sessions.len(),
+        workspace.sessions.iter().map(|s| s.photos.len()).sum::<usize>()
+    );
+    Ok(catalog)
+}
+
+/// Photos of a session, from the catalog
+#[tauri::command]
+pub async fn get_catalog_session_photos(folder_path: String, session_id: String) -> Result<Vec<CatalogPhoto>, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.photos_in_session(&session_id)
+}
+
+/// Photos not uploaded to their session's upload target yet (all sessions if session_id is None)
+#[tauri::command]
+pub async fn get_catalog_photos_not_uploaded(
+    folder_path: String,
+    session_id: Option<String>,
+) -> Result<Vec<CatalogPhoto>, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.photos_not_uploaded(session_id.as_deref())
+}
+
+/// Photos captured between two RFC 3339 times, across all sessions
+#[tauri::command]
+pub async fn get_catalog_photos_captured_between(
+    folder_path: String,
+    from: String,
+    to: String,
+) -> Result<Vec<CatalogPhoto>, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.photos_captured_between(&from, &to)
+}
+
+/// Record a generated GIF, video or collage in the catalog
+#[tauri::command]
+pub async fn record_catalog_media(folder_path: String, media: CatalogMedia) -> Result<CatalogMedia, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.add_media(media)
+}
+
+/// Generated GIFs, videos and collages (all sessions if session_id is None)
+#[tauri::command]
+pub async fn list_catalog_media(folder_path: String, session_id: Option<String>) -> Result<Vec<CatalogMedia>, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.media(session_id.as_deref())
+}
+
+/// Record a print job in the catalog
+#[tauri::command]
+pub async fn record_print_job(folder_path: String, job: CatalogPrintJob) -> Result<CatalogPrintJob, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.add_print_job(job)
+}
+
+/// Update a print job's status ("queued" | "printed" | "failed")
+#[tauri::command]
+pub async fn update_print_job_status(
+    folder_path: String,
+    job_id: i64,
+    status: String,
+    error: Option<String>,
+) -> Result<(), String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.set_print_job_status(job_id, &status, error.as_deref())
+}
+
+/// Print jobs (all sessions if session_id is None)
+#[tauri::command]
+pub async fn list_print_jobs(folder_path: String, session_id: Option<String>) -> Result<Vec<CatalogPrintJob>, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    open_catalog(&folder_path)?.print_jobs(session_id.as_deref())
+}
+
+/// Rewrite the .ptb from the catalog (e.g. after the .ptb was lost, or for older app versions)
+#[tauri::command]
+pub async fn export_catalog_to_ptb(folder_path: String) -> Result<PtbWorkspace, String> {
+    let _lock = store::lock_folder(&folder_path).await;
+    let workspace = open_catalog(&folder_path)?
+        .export_workspace()?
+        .ok_or_else(|| "The catalog has no workspace to export".to_string())?;
+    store::write_workspace(&folder_path, &workspace)?;
+    println!(
+        "[catalog] Exported {} sessions to the .ptb in {}",
+        workspace.sessions.len(),
+        folder_path
+    );
+    Ok(workspace)
+}
@@ -0,0 +1,312 @@
+// Verified, resumable photo transfers from the camera daemon
+//
+// Photos are streamed into a `.partial` file in the temp dir. If the connection drops,
+// the next attempt sends `Range` + `If-Range` with the ETag the daemon gave us, so a
+// large RAW only transfers what is missing. The daemon's ETag is the file's SHA-256,
+// which the finished download is checked against. Downloads of the same file (the live
+// event and a sweep after a reconnect) take turns, so they never share a partial file.
+
+use crate::daemon_auth::with_daemon_auth;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex as TokioMutex;
+
+/// Attempts per photo (the first request plus resumes)
+const MAX_ATTEMPTS: u32 = 4;
+
+/// One lock per partial file while it is being downloaded
+static PARTIAL_LOCKS: Lazy<StdMutex<HashMap<PathBuf, Arc<TokioMutex<()>>>>> =
+    Lazy::new(|| StdMutex::new(HashMap::new()));
+
+/// A photo stored on the daemon, from GET /api/photos
+#[derive(Deserialize, Clone, Debug)]
+pub struct DaemonPhoto {
+    pub filename: String,
+    pub camera_id: String,
+    pub size: u64,
+    pub captured_at_ms: u64,
+    pub sha256: String,
+    /// captured / downloaded / acknowledged
+    pub state: String,
+}
+
+#[derive(Deserialize)]
+struct DaemonPhotoList {
+    photos: Vec<DaemonPhoto>,
+    #[serde(default)]
+    now_ms: Option<u64>,
+}
+
+/// List every photo stored on the daemon, with the daemon's clock (for ages)
+pub async fn list_daemon_photos(
+    client: &reqwest::Client,
+    daemon_url: &str,
+    token: Option<&str>,
+) -> Result<(Vec<DaemonPhoto>, Option<u64>), String> {
+    let url = format!("{}/api/photos", daemon_url);
+    let response = with_daemon_auth(client.get(&url), token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to list photos on daemon: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Daemon returned error listing photos: {}", response.status()));
+    }
+    let list: DaemonPhotoList = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid photo list from daemon: {}", e))?;
+    Ok((list.photos, list.now_ms))
+}
+
+/// Photo URL on the daemon (photos of additional cameras live in that camera's directory)
+pub fn daemon_photo_url(daemon_url: &str, filename: &str, camera_id: Option<&str>, suffix: &str) -> String {
+    match camera_id {
+        Some(id) if !id.is_empty() => format!("{}/api/photo/{}{}?camera={}", daemon_url, filename, suffix, id),
+        _ => format!("{}/api/photo/{}{}", daemon_url, filename, suffix),
+    }
+}
+
+fn partial_paths(camera_id: Option<&str>, filename: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join("photobooth-downloads");
+    let stem = format!("{}-{}", camera_id.filter(|id| !id.is_empty()).unwrap_or("0"), filename);
+    (
+        dir.join(format!("{}.partial", stem)),
+        dir.join(format!("{}.etag", stem)),
+    )
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Download a photo, resuming after dropped connections, and verify its SHA-256
+/// against the daemon's ETag (or `expected_sha256` when known from the inventory).
+/// Returns the photo bytes and their SHA-256.
+pub async fn fetch_photo_verified(
+    client: &reqwest::Client,
+    photo_url: &str,
+    token: Option<&str>,
+    camera_id: Option<&str>,
+    filename: &str,
+    expected_sha256: Option<&str>,
+) -> Result<(Vec<u8>, String), String> {
+    let (partial_path, etag_path) = partial_paths(camera_id, filename);
+    if let Some(dir) = partial_path.parent() {
+        fs::create_dir_all(dir).await.map_err(|e| format!("Failed to create download folder: {}", e))?;
+    }
+
+    let lock = PARTIAL_LOCKS
+        .lock()
+        .unwrap()
+        .entry(partial_path.clone())
+        .or_insert_with(|| Arc::new(TokioMutex::new(())))
+        .clone();
+    let guard = lock.lock().await;
+    let result = fetch_into_partial(client, photo_url, token, filename, expected_sha256, &partial_path, &etag_path).await;
+    drop(guard);
+    drop(lock);
+    PARTIAL_LOCKS.lock().unwrap().retain(|_, lock| Arc::strong_count(lock) > 1);
+    result
+}
+
+/// The download itself; the caller holds the partial file's lock
+async fn fetch_into_partial(
+    client: &reqwest::Client,
+    photo_url: &str,
+    token: Option<&str>,
+    filename: &str,
+    expected_sha256: Option<&str>,
+    partial_path: &Path,
+    etag_path: &Path,
+) -> Result<(Vec<u8>, String), String> {
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_ATTEMPTS {
+        let partial_len = fs::metadata(partial_path).await.map(|m| m.len()).unwrap_or(0);
+        let saved_etag = fs::read_to_string(etag_path).await.ok().filter(|e| !e.is_empty());
+
+        let mut request = with_daemon_auth(client.get(photo_url), token);
+        let mut resume_from = 0;
+        if let (true, Some(etag)) = (partial_len > 0, saved_etag.as_deref()) {
+            println!(
+                "[daemon_transfer] Resuming {} at byte {} (attempt {})",
+                filename, partial_len, attempt
+            );
+            request = request
+                .header("Range", format!("bytes={}-", partial_len))
+                .header("If-Range", etag);
+            resume_from = partial_len;
+        }
+
+        match download_attempt(request, partial_path, etag_path, resume_from).await {
+            Ok(etag) => {
+                let data = fs::read(partial_path)
+                    .await
+                    .map_err(|e| format!("Failed to read downloaded photo: {}", e))?;
+                let (data, sha256) = tokio::task::spawn_blocking(move || {
+                    let sha256 = sha256_hex(&data);
+                    (data, sha256)
+                })
+                .await
+                .map_err(|e| format!("Checksum task failed: {}", e))?;
+                let expected = expected_sha256
+                    .map(|s| s.to_string())
+                    .or_else(|| etag.map(|e| e.trim_matches('"').to_string()));
+                let _ = fs::remove_file(partial_path).await;
+                let _ = fs::remove_file(etag_path).await;
+
+                match expected {
+                    Some(expected) if !expected.eq_ignore_ascii_case(&sha256) => {
+                        last_error = format!(
+                            "Checksum mismatch for {}: expected {}, got {}",
+                            filename, expected, sha256
+                        );
+                        eprintln!("[daemon_transfer] {} (attempt {})", last_error, attempt);
+                    }
+                    _ => return Ok((data, sha256)),
+                }
+            }
+            Err(DownloadError::Fatal(e)) => return Err(e),
+            Err(DownloadError::Retry(e)) => {
+                eprintln!("[daemon_transfer] {} (attempt {})", e, attempt);
+                last_error = e;
+                tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await;
+            }
+        }
+    }
+
+    Err(format!(
+        "Failed to download {} after {} attempts: {}",
+        filename, MAX_ATTEMPTS, last_error
+    ))
+}
+
+enum DownloadError {
+    /// Worth resuming (connection dropped, partial data kept)
+    Retry(String),
+    /// The photo can't be downloaded (404, auth, local disk)
+    Fatal(String),
+}
+
+/// One request: append a 206 to the partial file or replace it with a 200.
+/// `resume_from` is the offset sent in the Range header (0 when the whole photo was requested).
+/// Returns the response's ETag.
+async fn download_attempt(
+    request: reqwest::RequestBuilder,
+    partial_path: &Path,
+    etag_path: &Path,
+    resume_from: u64,
+) -> Result<Option<String>, DownloadError> {
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| DownloadError::Retry(format!("Failed to fetch photo from daemon: {}", e)))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // Our partial file doesn't fit the photo any more; start over
+        discard_partial(partial_path, etag_path).await;
+        return Err(DownloadError::Retry("Stale partial download discarded".to_string()));
+    }
+    if !status.is_success() {
+        let message = format!("Daemon returned error: {}", status);
+        return Err(if status.is_server_error() {
+            DownloadError::Retry(message)
+        } else {
+            DownloadError::Fatal(message)
+        });
+    }
+
+    let etag = response
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let appending = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    if appending {
+        // A range that doesn't start where our partial file ends would corrupt it
+        let range_start = response
+            .headers()
+            .get("content-range")
+            .and_then(|v| v.to_str().ok())
+            .and_then(content_range_start);
+        if range_start != Some(resume_from) {
+            discard_partial(partial_path, etag_path).await;
+            return Err(DownloadError::Retry(format!(
+                "Daemon sent a range starting at {:?} instead of byte {}, restarting the download",
+                range_start, resume_from
+            )));
+        }
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(appending)
+        .truncate(!appending)
+        .open(partial_path)
+        .await
+        .map_err(|e| DownloadError::Fatal(format!("Failed to open partial download: {}", e)))?;
+    match &etag {
+        Some(etag) => {
+            let _ = fs::write(etag_path, etag).await;
+        }
+        None => {
+            let _ = fs::remove_file(etag_path).await;
+        }
+    }
+
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => file
+                .write_all(&chunk)
+                .await
+                .map_err(|e| DownloadError::Fatal(format!("Failed to write partial download: {}", e)))?,
+            Ok(None) => break,
+            Err(e) => return Err(DownloadError::Retry(format!("Photo transfer interrupted: {}", e))),
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| DownloadError::Fatal(format!("Failed to write partial download: {}", e)))?;
+
+    Ok(etag)
+}
+
+async fn discard_partial(partial_path: &Path, etag_path: &Path) {
+    let _ = fs::remove_file(partial_path).await;
+    let _ = fs::remove_file(etag_path).await;
+}
+
+/// First byte of a `Content-Range: bytes <start>-<end>/<size>` header
+fn content_range_start(value: &str) -> Option<u64> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_range_start() {
+        let cases = [
+            ("bytes 1024-4095/4096", Some(1024)),
+            ("bytes 0-99/100", Some(0)),
+            ("bytes 512-1023/*", Some(512)),
+            (" bytes 7-9/10 ", Some(7)),
+            ("bytes */4096", None),
+            ("items 0-1/2", None),
+            ("", None),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(content_range_start(header), expected, "{}", header);
+        }
+    }
+}
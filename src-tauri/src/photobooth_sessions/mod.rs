@@ -1,6 +1,10 @@
 // Photobooth sessions module (stub - to be fully implemented)
 
 pub mod types;
+mod catalog;
 mod commands;
+mod daemon_transfer;
+mod naming;
+mod store;
 
 pub use commands::*;
@@ -0,0 +1,429 @@
+// Photo naming templates
+//
+// A session's photo_naming_scheme is a file name template with tokens in braces:
+//   {event}            working folder name
+//   {session}          session name
+//   {session_number}   number of the session folder ({session_number:03} pads it)
+//   {date} / {time}    capture time, %Y%m%d / %H%M%S unless given ({date:%Y-%m-%d})
+//   {camera_model}     model from the photo's EXIF
+//   {camera_id}        daemon camera id (multi-camera booths)
+//   {seq} / {number}   shot number in the session, 4 digits unless given ({seq:06})
+//   {original}         the camera's own file name, without extension
+// Templates without {seq}, {number} or {original} get "_{seq}" appended, so photos can't
+// overwrite each other. The photo's extension is added unless the template ends with it.
+
+use serde::{Deserialize, Serialize};
+
+/// Template used when a scheme is empty, or invalid at download time
+pub const DEFAULT_TEMPLATE: &str = "photo_{number}";
+
+/// Characters Windows doesn't allow in file names (the working folder may be on NTFS)
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+const MAX_PAD_WIDTH: usize = 10;
+
+/// Result of updating a session's naming scheme
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingSchemePreview {
+    pub photo_naming_scheme: String,
+    /// File name the session's next photo would get
+    pub next_filename: String,
+}
+
+/// Values the tokens are filled from
+pub struct NamingContext<'a> {
+    pub event: &'a str,
+    pub session: &'a str,
+    pub session_number: u32,
+    pub captured_at: chrono::DateTime<chrono::Local>,
+    pub camera_model: Option<&'a str>,
+    pub camera_id: Option<&'a str>,
+    pub seq: u32,
+    /// Camera file name the photo was downloaded as
+    pub original: &'a str,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Event,
+    Session,
+    SessionNumber(usize),
+    Date(String),
+    Time(String),
+    CameraModel,
+    CameraId,
+    Seq(usize),
+    Original,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    Token(Token),
+}
+
+#[derive(Clone, Debug)]
+pub struct NamingTemplate {
+    segments: Vec<Segment>,
+}
+
+fn pad_width(name: &str, spec: Option<&str>, default: usize) -> Result<usize, String> {
+    let Some(spec) = spec else {
+        return Ok(default);
+    };
+    match spec.parse::<usize>() {
+        Ok(width) if width <= MAX_PAD_WIDTH => Ok(width),
+        _ => Err(format!(
+            "{{{}:{}}} needs a width between 0 and {} digits, like {{{}:04}}",
+            name, spec, MAX_PAD_WIDTH, name
+        )),
+    }
+}
+
+/// Check a strftime format, and that the times it writes are valid in file names
+fn time_format(name: &str, spec: Option<&str>, default: &str) -> Result<String, String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    let format = spec.unwrap_or(default);
+    if format.is_empty() || StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("{{{}:{}}} is not a valid date/time format", name, format));
+    }
+    let sample = chrono::Local::now().format(format).to_string();
+    if let Some(c) = sample.chars().find(|c| ILLEGAL_CHARS.contains(c) || c.is_control()) {
+        return Err(format!("{{{}:{}}} would put '{}' in file names", name, format, c));
+    }
+    Ok(format.to_string())
+}
+
+fn parse_token(body: &str) -> Result<Token, String> {
+    let (name, spec) = match body.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (body, None),
+    };
+    let no_spec = |token: Token| match spec {
+        Some(_) => Err(format!("{{{}}} takes no format", name)),
+        None => Ok(token),
+    };
+    match name {
+        "event" => no_spec(Token::Event),
+        "session" => no_spec(Token::Session),
+        "session_number" => Ok(Token::SessionNumber(pad_width(name, spec, 3)?)),
+        "date" => Ok(Token::Date(time_format(name, spec, "%Y%m%d")?)),
+        "time" => Ok(Token::Time(time_format(name, spec, "%H%M%S")?)),
+        "camera_model" => no_spec(Token::CameraModel),
+        "camera_id" => no_spec(Token::CameraId),
+        "seq" | "number" => Ok(Token::Seq(pad_width(name, spec, 4)?)),
+        "original" => no_spec(Token::Original),
+        _ => Err(format!(
+            "Unknown token {{{}}}. Available: {{event}}, {{session}}, {{session_number}}, {{date}}, {{time}}, {{camera_model}}, {{camera_id}}, {{seq}}, {{original}}",
+            name
+        )),
+    }
+}
+
+/// Token values come from session names, EXIF and the camera, so they may contain anything
+fn sanitize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| if ILLEGAL_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect()
+}
+
+impl NamingTemplate {
+    /// Parse and validate a template
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err("The naming scheme is empty".to_string());
+        }
+
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = template;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '{' => {
+                    let end = rest.find('}').ok_or_else(|| format!("Missing '}}' in \"{}\"", rest))?;
+                    let body = &rest[1..end];
+                    if body.contains('{') {
+                        return Err(format!("Missing '}}' in \"{}\"", &rest[..end + 1]));
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Token(parse_token(body)?));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                '}' => return Err(format!("Unexpected '}}' in \"{}\"", template)),
+                c if ILLEGAL_CHARS.contains(&c) || c.is_control() => {
+                    return Err(format!(
+                        "'{}' is not allowed in file names (not allowed: {})",
+                        c.escape_default(),
+                        ILLEGAL_CHARS.iter().collect::<String>()
+                    ));
+                }
+                c => text.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        let unique = segments
+            .iter()
+            .any(|s| matches!(s, Segment::Token(Token::Seq(_)) | Segment::Token(Token::Original)));
+        if !unique {
+            segments.push(Segment::Text("_".to_string()));
+            segments.push(Segment::Token(Token::Seq(4)));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Parse a scheme at download time: an empty or invalid one falls back to the default
+    /// rather than failing the download
+    pub fn parse_or_default(template: &str) -> Self {
+        if template.trim().is_empty() {
+            return Self::parse(DEFAULT_TEMPLATE).expect("default template is valid");
+        }
+        Self::parse(template).unwrap_or_else(|e| {
+            eprintln!(
+                "[naming] Invalid naming scheme \"{}\" ({}), using {}",
+                template, e, DEFAULT_TEMPLATE
+            );
+            Self::parse(DEFAULT_TEMPLATE).expect("default template is valid")
+        })
+    }
+
+    pub fn uses_camera_model(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|s| matches!(s, Segment::Token(Token::CameraModel)))
+    }
+
+    /// File name without extension
+    fn render_stem(&self, ctx: &NamingContext) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Token(token) => out.push_str(&match token {
+                    Token::Event => sanitize(ctx.event),
+                    Token::Session => sanitize(ctx.session),
+                    Token::SessionNumber(width) => format!("{:0width$}", ctx.session_number, width = *width),
+                    Token::Date(format) | Token::Time(format) => ctx.captured_at.format(format).to_string(),
+                    Token::CameraModel => sanitize(ctx.camera_model.unwrap_or("camera")),
+                    Token::CameraId => sanitize(ctx.camera_id.unwrap_or("0")),
+                    Token::Seq(width) => format!("{:0width$}", ctx.seq, width = *width),
+                    Token::Original => sanitize(
+                        std::path::Path::new(ctx.original)
+                            .file_stem()
+                            .and_then(|s| s.to_str())
+                            .unwrap_or(ctx.original),
+                    ),
+                }),
+            }
+        }
+
+        // Windows drops trailing dots and spaces, and refuses device names
+        let mut stem = out.trim_end_matches(['.', ' ']).to_string();
+        if stem.is_empty() {
+            stem = format!("photo_{:04}", ctx.seq);
+        }
+        let base = stem.split('.').next().unwrap_or_default();
+        if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+            stem.push('_');
+        }
+        stem
+    }
+
+    /// File name for a photo with `extension`. `taken` says whether a name is already used;
+    /// on a collision "-2", "-3", ... is added before the extension.
+    pub fn filename(&self, ctx: &NamingContext, extension: &str, taken: impl Fn(&str) -> bool) -> String {
+        let mut stem = self.render_stem(ctx);
+        // Old schemes like "IMG_{number}.jpg" already name the extension
+        let suffix = format!(".{}", extension);
+        if stem.len() > suffix.len() && stem.to_lowercase().ends_with(&suffix.to_lowercase()) {
+            stem.truncate(stem.len() - suffix.len());
+        }
+
+        let mut name = format!("{}.{}", stem, extension);
+        let mut attempt = 2;
+        while taken(&name) {
+            name = format!("{}-{}.{}", stem, attempt, extension);
+            attempt += 1;
+        }
+        name
+    }
+}
+
+/// Number of a session folder ("Wedding_003" -> 3)
+pub fn session_number(folder_name: &str) -> Option<u32> {
+    folder_name.rsplit('_').next()?.parse().ok()
+}
+
+fn exif_ascii(photo_data: &[u8], tag: rexif::ExifTag) -> Option<String> {
+    let exif = rexif::parse_buffer(photo_data).ok()?;
+    exif.entries.iter().find_map(|entry| match &entry.value {
+        rexif::TagValue::Ascii(value) if entry.tag == tag => {
+            Some(value.trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Camera model from a photo's EXIF, if it has any
+pub fn exif_camera_model(photo_data: &[u8]) -> Option<String> {
+    exif_ascii(photo_data, rexif::ExifTag::Model)
+}
+
+/// When the shot was taken, from EXIF DateTimeOriginal (camera clock, local time)
+pub fn exif_captured_at(photo_data: &[u8]) -> Option<chrono::DateTime<chrono::Local>> {
+    use chrono::TimeZone;
+
+    let value = exif_ascii(photo_data, rexif::ExifTag::DateTimeOriginal)?;
+    let naive = chrono::NaiveDateTime::parse_from_str(&value, "%Y:%m:%d %H:%M:%S").ok()?;
+    chrono::Local.from_local_datetime(&naive).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ctx<'a>(session: &'a str, original: &'a str, camera_model: Option<&'a str>) -> NamingContext<'a> {
+        NamingContext {
+            event: "Wedding",
+            session,
+            session_number: 3,
+            captured_at: chrono::Local.with_ymd_and_hms(2025, 6, 14, 15, 30, 5).unwrap(),
+            camera_model,
+            camera_id: None,
+            seq: 7,
+            original,
+        }
+    }
+
+    fn filename(template: &str, ctx: &NamingContext, extension: &str) -> String {
+        NamingTemplate::parse(template).unwrap().filename(ctx, extension, |_| false)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", "The naming scheme is empty"),
+            ("   ", "The naming scheme is empty"),
+            ("photo:{seq}", "':' is not allowed in file names"),
+            ("a/b_{seq}", "'/' is not allowed in file names"),
+            ("a\\b_{seq}", "'\\\\' is not allowed in file names"),
+            ("what?_{seq}", "'?' is not allowed in file names"),
+            ("photo\t{seq}", "'\\t' is not allowed in file names"),
+            ("{bogus}", "Unknown token {bogus}"),
+            ("photo_{seq", "Missing '}'"),
+            ("photo_{seq_{number}", "Missing '}'"),
+            ("photo_seq}", "Unexpected '}'"),
+            ("{event:upper}_{seq}", "{event} takes no format"),
+            ("{seq:abc}", "{seq:abc} needs a width"),
+            ("{number:99}", "{number:99} needs a width"),
+            ("{date:%Y/%m/%d}_{seq}", "{date:%Y/%m/%d} would put '/' in file names"),
+            ("{time:%H:%M}_{seq}", "{time:%H:%M} would put ':' in file names"),
+            ("{date:}_{seq}", "{date:} is not a valid date/time format"),
+        ];
+        for (template, expected) in cases {
+            let error = NamingTemplate::parse(template).unwrap_err();
+            assert!(error.starts_with(expected), "\"{}\": {}", template, error);
+        }
+    }
+
+    #[test]
+    fn test_filename() {
+        let plain = ctx("Ceremony", "IMG_0042.CR3", Some("Canon EOS R100"));
+        let cases = [
+            ("{event}_{session_number}_{seq}", &plain, "jpg", "Wedding_003_0007.jpg"),
+            ("{session}_{session_number:0}_{number:06}", &plain, "jpg", "Ceremony_3_000007.jpg"),
+            ("{date:%Y-%m-%d}_{seq:02}", &plain, "jpg", "2025-06-14_07.jpg"),
+            ("{camera_model}_{original}", &plain, "jpg", "Canon EOS R100_IMG_0042.jpg"),
+            ("{camera_id}-{seq}", &plain, "jpg", "0-0007.jpg"),
+            // Without {seq}, {number} or {original} the shot number is appended
+            ("{date}_{time}", &plain, "jpg", "20250614_153005_0007.jpg"),
+            ("{event}", &plain, "cr3", "Wedding_0007.cr3"),
+        ];
+        for (template, ctx, extension, expected) in cases {
+            assert_eq!(filename(template, ctx, extension), expected, "\"{}\"", template);
+        }
+    }
+
+    #[test]
+    fn test_filename_sanitizes_token_values() {
+        let messy = ctx("Smith / Jones: 2*", "DSC\u{1}01.JPG", None);
+        assert_eq!(filename("{session}_{seq}", &messy, "jpg"), "Smith _ Jones_ 2__0007.jpg");
+        assert_eq!(filename("{original}", &messy, "jpg"), "DSC_01.jpg");
+        assert_eq!(filename("{camera_model}_{seq}", &messy, "jpg"), "camera_0007.jpg");
+    }
+
+    #[test]
+    fn test_filename_windows_names() {
+        let cases = [
+            // Device names, with or without an extension
+            ("aux.JPG", "{original}", "aux_.jpg"),
+            ("CON", "{original}", "CON_.jpg"),
+            ("IMG_1", "COM1.{seq}", "COM1.0007_.jpg"),
+            ("IMG_1", "LPT10_{seq}", "LPT10_0007.jpg"),
+            // Trailing dots and spaces are dropped, an empty name falls back
+            ("x...", "{original}", "x.jpg"),
+            ("IMG_1", "{original}. .", "IMG_1.jpg"),
+            ("", "{original}", "photo_0007.jpg"),
+        ];
+        for (original, template, expected) in cases {
+            assert_eq!(filename(template, &ctx("S", original, None), "jpg"), expected, "\"{}\" {}", template, original);
+        }
+    }
+
+    #[test]
+    fn test_filename_extension_in_template() {
+        let plain = ctx("S", "IMG_1.JPG", None);
+        assert_eq!(filename("IMG_{number}.jpg", &plain, "jpg"), "IMG_0007.jpg");
+        assert_eq!(filename("IMG_{number}.JPG", &plain, "jpg"), "IMG_0007.jpg");
+        assert_eq!(filename("IMG_{number}.jpg", &plain, "JPG"), "IMG_0007.JPG");
+        // Another extension is kept as part of the name
+        assert_eq!(filename("IMG_{number}.jpg", &plain, "cr3"), "IMG_0007.jpg.cr3");
+        // A name that is only the extension isn't stripped
+        assert_eq!(filename("{original}", &ctx("S", ".jpg", None), "jpg"), ".jpg.jpg");
+    }
+
+    #[test]
+    fn test_filename_collisions() {
+        let template = NamingTemplate::parse("IMG_{number}").unwrap();
+        let plain = ctx("S", "IMG_1.JPG", None);
+        let taken = ["IMG_0007.jpg", "IMG_0007-2.jpg", "IMG_0007-4.jpg"];
+        assert_eq!(template.filename(&plain, "jpg", |name| taken.contains(&name)), "IMG_0007-3.jpg");
+        assert_eq!(template.filename(&plain, "cr3", |name| taken.contains(&name)), "IMG_0007.cr3");
+        assert_eq!(template.filename(&plain, "jpg", |_| false), "IMG_0007.jpg");
+    }
+
+    #[test]
+    fn test_parse_or_default() {
+        let plain = ctx("S", "IMG_1.JPG", None);
+        for template in ["", "  ", "{bogus}", "a:b"] {
+            assert_eq!(NamingTemplate::parse_or_default(template).filename(&plain, "jpg", |_| false), "photo_0007.jpg");
+        }
+        assert!(NamingTemplate::parse("{camera_model}_{seq}").unwrap().uses_camera_model());
+        assert!(!NamingTemplate::parse(DEFAULT_TEMPLATE).unwrap().uses_camera_model());
+    }
+
+    #[test]
+    fn test_session_number() {
+        assert_eq!(session_number("Wedding_003"), Some(3));
+        assert_eq!(session_number("My_Event_12"), Some(12));
+        assert_eq!(session_number("Wedding"), None);
+        assert_eq!(session_number("Wedding_"), None);
+    }
+}
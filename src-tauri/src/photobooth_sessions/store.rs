@@ -0,0 +1,397 @@
+// Crash-safe, serialized persistence of the .ptb workspace file
+//
+// Every read-modify-write of a working folder's .ptb goes through a per-folder lock
+// (see `WorkspaceGuard`), so concurrent commands (a download and the upload processor,
+// two cameras downloading at once) can't drop each other's changes. Saves go to a temp
+// file that is synced and renamed over the .ptb, so a crash leaves either the old or the
+// new file, never half of one. Rotating backups (.ptb.bak.1 newest .. .ptb.bak.N) are
+// kept, and a .ptb that can't be parsed is replaced by the newest backup that can.
+// Mirroring a save into the folder's SQLite catalog happens in the background, after
+// the lock is released; catalog readers flush what's pending first.
+
+use super::types::PtbWorkspace;
+use crate::utils::schema::{self, SchemaError};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex as TokioMutex, OwnedMutexGuard};
+
+/// Workspace file at the working folder root
+pub const PTB_FILE: &str = ".ptb";
+
+/// Number of rotating backups kept next to the .ptb
+const BACKUP_COUNT: usize = 5;
+
+/// Minimum age of the newest backup before another one is taken. Saves happen on every
+/// photo, so backing up each one would only keep the last few seconds of history.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// One lock per working folder, created on first use
+static FOLDER_LOCKS: Lazy<StdMutex<HashMap<String, Arc<TokioMutex<()>>>>> =
+    Lazy::new(|| StdMutex::new(HashMap::new()));
+
+/// Latest saved workspace per folder that hasn't been mirrored into its catalog yet.
+/// Saves in quick succession replace the entry, so only the newest one is imported.
+static PENDING_CATALOG_SYNCS: Lazy<StdMutex<HashMap<String, (String, PtbWorkspace)>>> =
+    Lazy::new(|| StdMutex::new(HashMap::new()));
+
+pub fn ptb_path(folder_path: &str) -> PathBuf {
+    Path::new(folder_path).join(PTB_FILE)
+}
+
+fn backup_path(folder_path: &str, index: usize) -> PathBuf {
+    Path::new(folder_path).join(format!("{}.bak.{}", PTB_FILE, index))
+}
+
+/// Wait for exclusive access to a working folder's workspace file
+pub async fn lock_folder(folder_path: &str) -> OwnedMutexGuard<()> {
+    let key = crate::upload_queue::journal::normalize_folder(folder_path);
+    let lock = FOLDER_LOCKS
+        .lock()
+        .unwrap()
+        .entry(key)
+        .or_insert_with(|| Arc::new(TokioMutex::new(())))
+        .clone();
+    lock.lock_owned().await
+}
+
+/// Parse a workspace file, migrated to the current schema.
+/// Returns the workspace, the file's content and whether it was migrated.
+fn parse_workspace(path: &Path) -> Result<(PtbWorkspace, String, bool), SchemaError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| SchemaError::Invalid(format!("Failed to read .ptb file: {}", e)))?;
+    let (workspace, migrated) = schema::WORKSPACE.parse(&content)?;
+    Ok((workspace, content, migrated))
+}
+
+/// Read the workspace file, or None if the folder has none yet.
+/// An older file is migrated and saved, keeping the original as .ptb.v<N>.bak.
+/// A damaged file is set aside as .ptb.corrupt-<time> and replaced by the newest
+/// backup that still parses. Call with the folder lock held.
+pub fn read_workspace(folder_path: &str) -> Result<Option<PtbWorkspace>, String> {
+    let path = ptb_path(folder_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let error = match parse_workspace(&path) {
+        Ok((workspace, content, migrated)) => {
+            if migrated {
+                schema::backup_original(&path, &content)?;
+                write_atomically(&path, &serialize(&workspace)?)?;
+            }
+            return Ok(Some(workspace));
+        }
+        // A file from a newer app version (or with sessions from one) isn't damaged;
+        // refuse it instead of replacing it
+        Err(SchemaError::Newer(e)) => return Err(e),
+        Err(SchemaError::Invalid(e)) => e,
+    };
+    eprintln!("[ptb_store] {:?} could not be read ({}), looking for a backup", path, error);
+
+    for index in 1..=BACKUP_COUNT {
+        let backup = backup_path(folder_path, index);
+        if !backup.exists() {
+            continue;
+        }
+        match parse_workspace(&backup) {
+            Ok((workspace, _, _)) => {
+                let corrupt = Path::new(folder_path).join(format!(
+                    "{}.corrupt-{}",
+                    PTB_FILE,
+                    chrono::Utc::now().format("%Y%m%dT%H%M%S")
+                ));
+                fs::rename(&path, &corrupt)
+                    .map_err(|e| format!("Failed to set aside the damaged .ptb file: {}", e))?;
+                write_atomically(&path, &serialize(&workspace)?)?;
+                println!(
+                    "[ptb_store] Recovered workspace from {:?}; the damaged file was kept as {:?}",
+                    backup, corrupt
+                );
+                return Ok(Some(workspace));
+            }
+            Err(e) => eprintln!("[ptb_store] Backup {:?} is not usable either: {}", backup, e),
+        }
+    }
+
+    Err(format!(
+        "The workspace file (.ptb) is corrupted and could not be read ({}), and no usable backup was found. Open the file to fix it manually, or choose a new folder.",
+        error
+    ))
+}
+
+fn serialize(workspace: &PtbWorkspace) -> Result<String, String> {
+    serde_json::to_string_pretty(workspace).map_err(|e| format!("Failed to serialize .ptb workspace: {}", e))
+}
+
+/// Write `content` to a temp file next to `path`, sync it and rename it into place
+fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let temp_path = path.with_file_name(format!(
+        "{}.tmp",
+        path.file_name().and_then(|n| n.to_str()).unwrap_or(PTB_FILE)
+    ));
+    let mut file = fs::File::create(&temp_path).map_err(|e| format!("Failed to write .ptb file: {}", e))?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write .ptb file: {}", e))?;
+    drop(file);
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace .ptb file: {}", e))?;
+
+    // Make the rename itself durable (directories can't be opened for syncing on Windows)
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Shift the backups and copy the current .ptb to .ptb.bak.1, unless the newest backup
+/// is recent or the current file is damaged (it must never push out a good backup)
+fn rotate_backups(folder_path: &str) {
+    let path = ptb_path(folder_path);
+    let newest = backup_path(folder_path, 1);
+    let newest_is_recent = fs::metadata(&newest)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < BACKUP_INTERVAL);
+    if newest_is_recent {
+        return;
+    }
+    // Only parsed when a backup is due, not on every save
+    if parse_workspace(&path).is_err() {
+        return;
+    }
+
+    for index in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(folder_path, index);
+        if from.exists() {
+            let _ = fs::rename(&from, backup_path(folder_path, index + 1));
+        }
+    }
+    if let Err(e) = fs::copy(&path, &newest) {
+        eprintln!("[ptb_store] Failed to back up {:?}: {}", path, e);
+    }
+}
+
+/// Save the workspace file (backup, then atomic replace) and schedule mirroring it into
+/// the folder's catalog. Call with the folder lock held.
+pub fn write_workspace(folder_path: &str, workspace: &PtbWorkspace) -> Result<(), String> {
+    let json = serialize(workspace)?;
+    rotate_backups(folder_path);
+    write_atomically(&ptb_path(folder_path), &json)?;
+    schedule_catalog_sync(folder_path, workspace);
+    Ok(())
+}
+
+/// Queue the saved workspace for the catalog and start a background sync, unless one is
+/// already waiting (it will pick up this workspace instead)
+fn schedule_catalog_sync(folder_path: &str, workspace: &PtbWorkspace) {
+    if !super::catalog::exists(folder_path) {
+        return;
+    }
+    let key = crate::upload_queue::journal::normalize_folder(folder_path);
+    let already_scheduled = PENDING_CATALOG_SYNCS
+        .lock()
+        .unwrap()
+        .insert(key, (folder_path.to_string(), workspace.clone()))
+        .is_some();
+    if already_scheduled {
+        return;
+    }
+
+    let folder_path = folder_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        let _lock = lock_folder(&folder_path).await;
+        let folder = folder_path.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || flush_catalog_sync(&folder)).await {
+            eprintln!("[ptb_store] Catalog sync for {} failed: {}", folder_path, e);
+        }
+    });
+}
+
+/// Import the workspace still waiting for the folder's catalog, if any.
+/// Call with the folder lock held, before reading the catalog.
+pub fn flush_catalog_sync(folder_path: &str) {
+    let key = crate::upload_queue::journal::normalize_folder(folder_path);
+    let pending = PENDING_CATALOG_SYNCS.lock().unwrap().remove(&key);
+    if let Some((folder_path, workspace)) = pending {
+        super::catalog::sync_workspace(&folder_path, &workspace);
+    }
+}
+
+/// A loaded workspace with its folder locked until the guard is saved or dropped.
+/// Dropping it without `save` discards the changes.
+pub struct WorkspaceGuard {
+    folder_path: String,
+    workspace: PtbWorkspace,
+    _lock: OwnedMutexGuard<()>,
+}
+
+impl WorkspaceGuard {
+    pub fn new(lock: OwnedMutexGuard<()>, folder_path: &str, workspace: PtbWorkspace) -> Self {
+        Self {
+            folder_path: folder_path.to_string(),
+            workspace,
+            _lock: lock,
+        }
+    }
+
+    /// Write the changes and release the folder
+    pub fn save(mut self) -> Result<(), String> {
+        self.workspace.last_used_at = chrono::Utc::now().to_rfc3339();
+        write_workspace(&self.folder_path, &self.workspace)
+    }
+}
+
+impl Deref for WorkspaceGuard {
+    type Target = PtbWorkspace;
+
+    fn deref(&self) -> &PtbWorkspace {
+        &self.workspace
+    }
+}
+
+impl DerefMut for WorkspaceGuard {
+    fn deref_mut(&mut self) -> &mut PtbWorkspace {
+        &mut self.workspace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_folder(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("ptb_store_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.to_string_lossy().into_owned()
+    }
+
+    fn workspace(name: &str) -> PtbWorkspace {
+        serde_json::from_value(serde_json::json!({
+            "schemaVersion": schema::workspace_version(),
+            "name": name,
+            "createdAt": "2025-06-14T15:00:00Z",
+            "lastUsedAt": "2025-06-14T15:00:00Z",
+            "currentSessionId": null,
+            "sessions": []
+        }))
+        .unwrap()
+    }
+
+    fn name_in(path: &Path) -> String {
+        parse_workspace(path).unwrap().0.name
+    }
+
+    /// Make the newest backup old enough for the next save to take another one
+    fn age_newest_backup(folder: &str) {
+        let file = fs::File::options().write(true).open(backup_path(folder, 1)).unwrap();
+        file.set_modified(SystemTime::now() - BACKUP_INTERVAL * 2).unwrap();
+    }
+
+    #[test]
+    fn test_read_workspace_recovers_from_newest_usable_backup() {
+        let folder = test_folder("recover");
+        fs::write(ptb_path(&folder), "{\"name\": \"Wedd").unwrap();
+        fs::write(backup_path(&folder, 1), "not json").unwrap();
+        fs::write(backup_path(&folder, 2), serialize(&workspace("Wedding")).unwrap()).unwrap();
+
+        let recovered = read_workspace(&folder).unwrap().unwrap();
+        assert_eq!(recovered.name, "Wedding");
+        assert_eq!(name_in(&ptb_path(&folder)), "Wedding");
+
+        let corrupt: Vec<String> = fs::read_dir(&folder)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|n| n.starts_with(".ptb.corrupt-"))
+            .collect();
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(
+            fs::read_to_string(Path::new(&folder).join(&corrupt[0])).unwrap(),
+            "{\"name\": \"Wedd"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_read_workspace_without_usable_backup() {
+        let folder = test_folder("unrecoverable");
+        assert!(read_workspace(&folder).unwrap().is_none());
+
+        fs::write(ptb_path(&folder), "not json").unwrap();
+        fs::write(backup_path(&folder, 1), "not json either").unwrap();
+        assert!(read_workspace(&folder).unwrap_err().contains("no usable backup"));
+        // Nothing is moved or overwritten when there's nothing to recover from
+        assert_eq!(fs::read_to_string(ptb_path(&folder)).unwrap(), "not json");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_read_workspace_refuses_newer_schema() {
+        let folder = test_folder("newer");
+        let mut doc = serde_json::to_value(workspace("Wedding")).unwrap();
+        doc["schemaVersion"] = serde_json::json!(schema::workspace_version() + 1);
+        let content = doc.to_string();
+        fs::write(ptb_path(&folder), &content).unwrap();
+        fs::write(backup_path(&folder, 1), serialize(&workspace("Old")).unwrap()).unwrap();
+
+        assert!(read_workspace(&folder).is_err());
+        assert_eq!(fs::read_to_string(ptb_path(&folder)).unwrap(), content);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_backup_rotation() {
+        let folder = test_folder("rotation");
+
+        // Nothing to back up before the first save
+        write_workspace(&folder, &workspace("v1")).unwrap();
+        assert!(!backup_path(&folder, 1).exists());
+
+        write_workspace(&folder, &workspace("v2")).unwrap();
+        assert_eq!(name_in(&backup_path(&folder, 1)), "v1");
+
+        // The newest backup is recent, so this save doesn't take another
+        write_workspace(&folder, &workspace("v3")).unwrap();
+        assert_eq!(name_in(&backup_path(&folder, 1)), "v1");
+        assert!(!backup_path(&folder, 2).exists());
+
+        for version in 4..=9 {
+            age_newest_backup(&folder);
+            write_workspace(&folder, &workspace(&format!("v{}", version))).unwrap();
+        }
+        assert_eq!(name_in(&ptb_path(&folder)), "v9");
+        let backups: Vec<String> = (1..=BACKUP_COUNT).map(|i| name_in(&backup_path(&folder, i))).collect();
+        assert_eq!(backups, ["v8", "v7", "v6", "v5", "v4"]);
+        assert!(!backup_path(&folder, BACKUP_COUNT + 1).exists());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_damaged_file_is_not_backed_up() {
+        let folder = test_folder("damaged");
+        write_workspace(&folder, &workspace("v1")).unwrap();
+        write_workspace(&folder, &workspace("v2")).unwrap();
+        fs::write(ptb_path(&folder), "not json").unwrap();
+
+        age_newest_backup(&folder);
+        write_workspace(&folder, &workspace("v3")).unwrap();
+        assert_eq!(name_in(&backup_path(&folder, 1)), "v1");
+        assert!(!backup_path(&folder, 2).exists());
+        assert_eq!(name_in(&ptb_path(&folder)), "v3");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
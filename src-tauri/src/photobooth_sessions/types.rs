@@ -1,3 +1,4 @@
+use crate::upload_targets::types::UploadBackend;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -12,6 +13,8 @@ pub struct PhotoboothSessionInfo {
     pub thumbnails: Vec<String>, // Thumbnail URLs for the session's photos
     #[serde(default)]
     pub google_drive_metadata: GoogleDriveMetadata,
+    #[serde(default)]
+    pub upload_target: SessionUploadTarget,
 }
 
 /// Google Drive uploaded image metadata
@@ -34,6 +37,56 @@ pub struct GoogleDriveMetadata {
     pub uploaded_images: Vec<DriveUploadedImage>,
 }
 
+/// A file uploaded to the session's upload target
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUploadedFile {
+    pub filename: String,
+    pub remote_id: String,
+    #[serde(default)]
+    pub link: Option<String>,
+    pub uploaded_at: String,
+}
+
+/// Where a session's files are uploaded, for any backend.
+/// Supersedes GoogleDriveMetadata, which is kept in sync for Drive sessions.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUploadTarget {
+    pub backend: Option<UploadBackend>,
+    /// Drive folder id, S3 key prefix, WebDAV collection or sub folder
+    pub remote_location: Option<String>,
+    pub remote_name: Option<String>,
+    /// Shareable link to the session's remote location (used for QR codes)
+    pub remote_link: Option<String>,
+    pub account_id: Option<String>,
+    #[serde(default)]
+    pub uploaded_files: Vec<SessionUploadedFile>,
+}
+
+impl SessionUploadTarget {
+    /// Build the target record from legacy Drive-only metadata
+    pub fn from_drive_metadata(drive: &GoogleDriveMetadata) -> Self {
+        Self {
+            backend: Some(UploadBackend::GoogleDrive),
+            remote_location: drive.folder_id.clone(),
+            remote_name: drive.folder_name.clone(),
+            remote_link: drive.folder_link.clone(),
+            account_id: drive.account_id.clone(),
+            uploaded_files: drive
+                .uploaded_images
+                .iter()
+                .map(|img| SessionUploadedFile {
+                    filename: img.filename.clone(),
+                    remote_id: img.drive_file_id.clone(),
+                    link: None,
+                    uploaded_at: img.uploaded_at.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
 /// Photo entry in the .ptb session file
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -42,12 +95,75 @@ pub struct PtbPhoto {
     pub original_path: String,
     pub camera_path: String,
     pub captured_at: String,
+    /// Daemon camera id that took the photo (multi-camera booths)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera_id: Option<String>,
+    /// Group capture this photo belongs to (see PtbPhotoGroup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// SHA-256 verified when downloading from the daemon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Original RAW stored next to `filename` (RAW+JPEG mode, or the RAW the JPEG was converted from)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_filename: Option<String>,
+}
+
+/// Outcome of sweeping missed photos off the daemon
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonSweepResult {
+    /// Photos downloaded into the session
+    pub downloaded: Vec<String>,
+    /// Photos the workspace already had (only released on the daemon)
+    pub already_stored: Vec<String>,
+    /// "filename: error" for photos that could not be recovered
+    pub failed: Vec<String>,
+    /// The session after the last recovered photo
+    pub session: Option<PtbSessionData>,
+}
+
+/// One camera's photo within a group capture
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PtbPhotoGroupMember {
+    pub camera_id: String,
+    pub filename: String,
+    /// Trigger delay relative to the earliest camera in the group
+    #[serde(default)]
+    pub trigger_skew_ms: Option<i64>,
+}
+
+/// Photos taken together by several cameras (synchronized "bullet-time" capture).
+/// Members are kept in camera order so they can be played back as a sweep.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PtbPhotoGroup {
+    pub id: String,
+    pub captured_at: String,
+    #[serde(default)]
+    pub max_skew_ms: Option<i64>,
+    pub members: Vec<PtbPhotoGroupMember>,
+}
+
+/// Group information passed with a downloaded photo that belongs to a group capture
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoGroupCapture {
+    pub group_id: String,
+    #[serde(default)]
+    pub trigger_skew_ms: Option<i64>,
+    #[serde(default)]
+    pub max_skew_ms: Option<i64>,
 }
 
 /// Session data in the .ptb file
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PtbSessionData {
+    /// See utils::schema
+    #[serde(default = "crate::utils::schema::session_version")]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub folder_name: String,
@@ -57,12 +173,16 @@ pub struct PtbSessionData {
     pub photos: Vec<PtbPhoto>,
     #[serde(default)]
     pub google_drive_metadata: GoogleDriveMetadata,
+    #[serde(default)]
+    pub upload_target: SessionUploadTarget,
     #[serde(default = "default_true")]
     pub qr_upload_enabled: bool,
     #[serde(default)]
     pub qr_upload_all_images: bool,
     #[serde(default = "default_photo_naming_scheme")]
     pub photo_naming_scheme: String,
+    #[serde(default)]
+    pub photo_groups: Vec<PtbPhotoGroup>,
 }
 
 fn default_true() -> bool {
@@ -165,6 +285,9 @@ impl Default for PrintSettings {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PtbWorkspace {
+    /// See utils::schema
+    #[serde(default = "crate::utils::schema::workspace_version")]
+    pub schema_version: u32,
     pub name: String,
     pub created_at: String,
     pub last_used_at: String,
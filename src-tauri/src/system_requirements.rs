@@ -33,6 +33,7 @@ macro_rules! log_file {
     };
 }
 
+use crate::daemon_auth::{load_daemon_token, with_daemon_auth};
 use crate::usb_camera::ensure_usb_filters;
 use crate::vm::commands::wait_for_vm_unlocked;
 use crate::version::APP_VERSION;
@@ -1657,6 +1658,7 @@ pub async fn initialize_app(window: tauri::Window) -> Result<String, String> {
         .build()
         .unwrap_or_default();
 
+    let daemon_token = load_daemon_token(window.app_handle());
     let start_time = std::time::Instant::now();
     let mut vm_online = false;
     let mut last_error = String::new();
@@ -1676,7 +1678,7 @@ pub async fn initialize_app(window: tauri::Window) -> Result<String, String> {
             }));
         }
 
-        match client.get(DAEMON_URL).send().await {
+        match with_daemon_auth(client.get(DAEMON_URL), daemon_token.as_deref()).send().await {
             Ok(resp) if resp.status().is_success() => {
                 vm_online = true;
                 log_file!(" Daemon is online! (took {}s)", elapsed);
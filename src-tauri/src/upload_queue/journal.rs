@@ -0,0 +1,56 @@
+use super::types::{UploadQueueItem, UploadQueueJournal};
+use std::fs;
+use std::path::Path;
+
+/// Journal file stored at the working folder root, next to the .ptb file
+pub const UPLOAD_QUEUE_JOURNAL_FILE: &str = ".upload_queue.json";
+
+/// Normalize a working folder path so journal lookups don't depend on trailing separators
+pub fn normalize_folder(folder_path: &str) -> String {
+    folder_path.trim_end_matches(['/', '\\']).to_string()
+}
+
+/// Read the journaled queue items for a working folder.
+/// A missing journal is not an error - it just means nothing was queued there yet.
+pub fn load_journal(folder_path: &str) -> Result<Vec<UploadQueueItem>, String> {
+    let journal_path = Path::new(folder_path).join(UPLOAD_QUEUE_JOURNAL_FILE);
+
+    if !journal_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&journal_path)
+        .map_err(|e| format!("Failed to read upload queue journal: {}", e))?;
+
+    let journal: UploadQueueJournal = serde_json::from_str(&content)
+        .map_err(|e| format!("Upload queue journal is corrupted: {}", e))?;
+
+    Ok(journal.items)
+}
+
+/// Write the queue items that belong to a working folder.
+/// Written to a temp file first and renamed so a crash mid-write can't truncate the journal.
+pub fn save_journal(folder_path: &str, items: &[UploadQueueItem]) -> Result<(), String> {
+    let folder = Path::new(folder_path);
+    if !folder.is_dir() {
+        return Err(format!("Working folder does not exist: {}", folder_path));
+    }
+
+    let journal = UploadQueueJournal {
+        items: items.to_vec(),
+        saved_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let json = serde_json::to_string_pretty(&journal)
+        .map_err(|e| format!("Failed to serialize upload queue journal: {}", e))?;
+
+    let journal_path = folder.join(UPLOAD_QUEUE_JOURNAL_FILE);
+    let temp_path = folder.join(format!("{}.tmp", UPLOAD_QUEUE_JOURNAL_FILE));
+
+    fs::write(&temp_path, json)
+        .map_err(|e| format!("Failed to write upload queue journal: {}", e))?;
+    fs::rename(&temp_path, &journal_path)
+        .map_err(|e| format!("Failed to replace upload queue journal: {}", e))?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 pub mod types;
 pub mod queue;
+pub mod journal;
 pub mod commands;
 pub mod processor;
 
@@ -31,43 +31,36 @@ pub fn start_upload_queue_processor_with_queue(
 
             // Return an async closure that returns Result<String, String>
             async move {
-                // Call the internal upload function directly
-                use crate::google_drive::queue_upload::upload_photo_to_drive_internal;
+                use crate::upload_targets::target::build_upload_target;
 
-                match upload_photo_to_drive_internal(
-                    item.local_path.clone(),
-                    item.drive_folder_id.clone(),
-                    app.clone(),
-                ).await {
-                    Ok(drive_file_id) => {
-                        // Track the uploaded image in session metadata
-                        // Parse working folder and session ID from local_path
-                        // Expected format: {working_folder}/{session_id}/{filename}
-                        if let Some(working_folder) = item.local_path.rsplit_once('/').or_else(|| item.local_path.rsplit_once('\\')) {
-                            if let Some(session_id_part) = working_folder.0.rsplit_once('/').or_else(|| working_folder.0.rsplit_once('\\')) {
-                                let session_id = session_id_part.1;
-                                let folder_path = session_id_part.0;
+                let target = build_upload_target(&app, &item.backend)?;
+                let remote_file = target
+                    .upload(std::path::Path::new(&item.local_path), &item.remote_location)
+                    .await?;
 
-                                println!("📝 Tracking upload: folder={}, session={}, file={}", folder_path, session_id, item.filename);
+                // Track the uploaded image in session metadata
+                // Working folder is parsed from local_path: {working_folder}/{session_id}/{filename}
+                if let Some(folder_path) = item.working_folder() {
+                    println!(
+                        "📝 Tracking upload: folder={}, session={}, file={}, backend={}",
+                        folder_path, item.session_id, item.filename, target.backend().as_str()
+                    );
 
-                                // Add to session's uploaded images list
-                                if let Err(e) = crate::photobooth_sessions::add_session_drive_upload(
-                                    folder_path.to_string(),
-                                    session_id.to_string(),
-                                    item.filename.clone(),
-                                    drive_file_id.clone(),
-                                ).await {
-                                    println!("⚠️  Failed to track uploaded image in session metadata: {}", e);
-                                } else {
-                                    println!("✅ Upload tracked in session metadata");
-                                }
-                            }
-                        }
-
-                        Ok(drive_file_id)
+                    // Add to session's uploaded images list
+                    if let Err(e) = crate::photobooth_sessions::add_session_upload(
+                        folder_path,
+                        item.session_id.clone(),
+                        item.filename.clone(),
+                        target.backend(),
+                        remote_file.clone(),
+                    ).await {
+                        println!("⚠️  Failed to track uploaded image in session metadata: {}", e);
+                    } else {
+                        println!("✅ Upload tracked in session metadata");
                     }
-                    Err(e) => Err(e)
                 }
+
+                Ok(remote_file.remote_id)
             }
         };
 
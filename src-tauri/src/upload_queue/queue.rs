@@ -1,4 +1,6 @@
+use super::journal::{load_journal, normalize_folder, save_journal};
 use super::types::{UploadQueueItem, UploadQueueState, UploadStatus, UploadQueueStats};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
@@ -11,6 +13,8 @@ pub struct UploadQueue {
     is_processing: Arc<Mutex<bool>>,
     processor_started: Arc<Mutex<bool>>,
     app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    /// Working folders that have a journal on disk (also serializes journal writes)
+    journal_folders: Arc<Mutex<HashSet<String>>>,
 }
 
 impl UploadQueue {
@@ -20,6 +24,7 @@ impl UploadQueue {
             is_processing: Arc::new(Mutex::new(false)),
             processor_started: Arc::new(Mutex::new(false)),
             app_handle: Arc::new(Mutex::new(None)),
+            journal_folders: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
@@ -28,38 +33,123 @@ impl UploadQueue {
         *handle = Some(app_handle);
     }
 
+    /// Start the processor the first time there is work for it
+    async fn ensure_processor_started(&self) {
+        let mut started = self.processor_started.lock().await;
+        if *started {
+            return;
+        }
+
+        // Get the app handle
+        let app_handle_opt = self.app_handle.lock().await.clone();
+        if let Some(app_handle) = app_handle_opt {
+            *started = true;
+            drop(started);
+
+            let queue = Arc::new(self.clone());
+            tokio::spawn(async move {
+                use super::processor::start_upload_queue_processor_with_queue;
+
+                start_upload_queue_processor_with_queue(queue, app_handle);
+            });
+        }
+    }
+
+    /// Write the outstanding items of the queue to the journal of every working folder it touches.
+    /// Folders whose items were all finished or removed get an empty journal so nothing resurrects on restart.
+    /// The files are written on the blocking pool; the folder lock is held until they are, so a newer
+    /// snapshot is never overwritten by an older one.
+    async fn persist(&self) {
+        let mut folders = self.journal_folders.lock().await;
+        let items: Vec<UploadQueueItem> = self.state.read().await.items.iter()
+            .filter(|item| item.is_outstanding())
+            .cloned()
+            .collect();
+
+        let mut by_folder: HashMap<String, Vec<UploadQueueItem>> = HashMap::new();
+        for item in items {
+            if let Some(folder) = item.working_folder() {
+                by_folder.entry(normalize_folder(&folder)).or_default().push(item);
+            }
+        }
+        folders.extend(by_folder.keys().cloned());
+
+        let journals: Vec<(String, Vec<UploadQueueItem>)> = folders.iter()
+            .map(|folder| (folder.clone(), by_folder.remove(folder).unwrap_or_default()))
+            .collect();
+        let written = tokio::task::spawn_blocking(move || {
+            for (folder, folder_items) in journals {
+                if let Err(e) = save_journal(&folder, &folder_items) {
+                    println!("⚠️  Failed to journal upload queue for {}: {}", folder, e);
+                }
+            }
+        }).await;
+        if let Err(e) = written {
+            println!("⚠️  Upload queue journal task failed: {}", e);
+        }
+    }
+
+    /// Reload the journaled queue of a working folder (e.g. after a crash or restart).
+    /// Items that were uploading when the app went down come back as pending; finished items
+    /// left by older journals are skipped. The processor is started if there is outstanding
+    /// work. Returns the number of restored items.
+    pub async fn restore_from_folder(&self, folder_path: &str) -> Result<usize, String> {
+        let folder = normalize_folder(folder_path);
+        let journaled = load_journal(&folder)?;
+
+        let finished = journaled.iter().filter(|item| !item.is_outstanding()).count();
+
+        let (restored, has_outstanding) = {
+            let mut state = self.state.write().await;
+            let known_ids: HashSet<String> = state.items.iter().map(|i| i.id.clone()).collect();
+
+            let mut restored = 0;
+            for mut item in journaled {
+                if known_ids.contains(&item.id) || !item.is_outstanding() {
+                    continue;
+                }
+                item.reset_interrupted();
+                state.items.push(item);
+                restored += 1;
+            }
+
+            let has_outstanding = state.items.iter()
+                .any(|i| matches!(i.status, UploadStatus::Pending | UploadStatus::Retrying));
+            (restored, has_outstanding)
+        };
+
+        self.journal_folders.lock().await.insert(folder.clone());
+
+        if restored > 0 {
+            println!("📂 Restored {} upload queue item(s) from {}", restored, folder);
+        }
+        if restored > 0 || finished > 0 {
+            self.persist().await;
+        }
+
+        if has_outstanding {
+            self.ensure_processor_started().await;
+        }
+
+        Ok(restored)
+    }
+
     /// Add an item to the upload queue
     #[allow(dead_code)]
     pub async fn enqueue(&self, item: UploadQueueItem) -> Result<(), String> {
-        let mut state = self.state.write().await;
-        state.items.push(item);
-        Ok(())
+        self.enqueue_batch(vec![item]).await
     }
 
     /// Add multiple items to the upload queue
     pub async fn enqueue_batch(&self, items: Vec<UploadQueueItem>) -> Result<(), String> {
         // Start processor if this is the first enqueue
+        self.ensure_processor_started().await;
+
         {
-            let mut started = self.processor_started.lock().await;
-            if !*started {
-                *started = true;
-                drop(started);
-
-                // Get the app handle
-                let app_handle_opt = self.app_handle.lock().await;
-                if let Some(app_handle) = app_handle_opt.clone() {
-                    let queue = Arc::new(self.clone());
-                    tokio::spawn(async move {
-                        use super::processor::start_upload_queue_processor_with_queue;
-
-                        start_upload_queue_processor_with_queue(queue, app_handle);
-                    });
-                }
-            }
+            let mut state = self.state.write().await;
+            state.items.extend(items);
         }
-
-        let mut state = self.state.write().await;
-        state.items.extend(items);
+        self.persist().await;
         Ok(())
     }
 
@@ -92,53 +182,64 @@ impl UploadQueue {
 
     /// Remove items from queue (e.g., when a session is deleted)
     pub async fn remove_session_items(&self, session_id: &str) {
-        let mut state = self.state.write().await;
-        state.items.retain(|item| item.session_id != session_id);
+        {
+            let mut state = self.state.write().await;
+            state.items.retain(|item| item.session_id != session_id);
+        }
+        self.persist().await;
     }
 
     /// Remove a specific item from the queue
     #[allow(dead_code)]
     pub async fn remove_item(&self, item_id: &str) -> Result<(), String> {
-        let mut state = self.state.write().await;
-        let original_len = state.items.len();
-        state.items.retain(|item| item.id != item_id);
+        {
+            let mut state = self.state.write().await;
+            let original_len = state.items.len();
+            state.items.retain(|item| item.id != item_id);
 
-        if state.items.len() == original_len {
-            return Err(format!("Item not found: {}", item_id));
+            if state.items.len() == original_len {
+                return Err(format!("Item not found: {}", item_id));
+            }
         }
 
+        self.persist().await;
         Ok(())
     }
 
     /// Retry a failed upload
     pub async fn retry_item(&self, item_id: &str) -> Result<(), String> {
-        let mut state = self.state.write().await;
+        {
+            let mut state = self.state.write().await;
 
-        if let Some(item) = state.items.iter_mut().find(|i| i.id == item_id) {
-            if item.status == UploadStatus::Failed {
-                item.status = UploadStatus::Pending;
-                item.error = None;
-                return Ok(());
+            let item = state.items.iter_mut().find(|i| i.id == item_id)
+                .ok_or_else(|| format!("Item not found: {}", item_id))?;
+            if item.status != UploadStatus::Failed {
+                return Err(format!("Item is not in failed state: {}", item_id));
             }
-            return Err(format!("Item is not in failed state: {}", item_id));
+            item.status = UploadStatus::Pending;
+            item.error = None;
         }
 
-        Err(format!("Item not found: {}", item_id))
+        self.persist().await;
+        self.ensure_processor_started().await;
+        Ok(())
     }
 
     /// Cancel an upload
     pub async fn cancel_item(&self, item_id: &str) -> Result<(), String> {
-        let mut state = self.state.write().await;
+        {
+            let mut state = self.state.write().await;
 
-        if let Some(item) = state.items.iter_mut().find(|i| i.id == item_id) {
-            if matches!(item.status, UploadStatus::Pending | UploadStatus::Retrying | UploadStatus::Uploading) {
-                item.status = UploadStatus::Cancelled;
-                return Ok(());
+            let item = state.items.iter_mut().find(|i| i.id == item_id)
+                .ok_or_else(|| format!("Item not found: {}", item_id))?;
+            if !matches!(item.status, UploadStatus::Pending | UploadStatus::Retrying | UploadStatus::Uploading) {
+                return Err(format!("Cannot cancel item in state: {:?}", item.status));
             }
-            return Err(format!("Cannot cancel item in state: {:?}", item.status));
+            item.status = UploadStatus::Cancelled;
         }
 
-        Err(format!("Item not found: {}", item_id))
+        self.persist().await;
+        Ok(())
     }
 
     /// Start the queue processor (runs in background)
@@ -149,6 +250,7 @@ impl UploadQueue {
     {
         let state = self.state.clone();
         let is_processing = self.is_processing.clone();
+        let queue = self.clone();
 
         tokio::spawn(async move {
             loop {
@@ -198,6 +300,9 @@ impl UploadQueue {
                         // Now set current_upload_id after we're done with item
                         state_write.current_upload_id = Some(item_id.clone());
 
+                        drop(state_write);
+                        queue.persist().await;
+
                         (item_id, item_clone)
                     } else {
                         // No items to process
@@ -264,6 +369,7 @@ impl UploadQueue {
 
                     state_write.current_upload_id = None;
                 }
+                queue.persist().await;
 
                 // Release processing lock
                 *is_processing.lock().await = false;
@@ -280,3 +386,85 @@ impl Default for UploadQueue {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_queue::journal::UPLOAD_QUEUE_JOURNAL_FILE;
+    use crate::upload_targets::types::UploadBackend;
+
+    fn temp_folder(name: &str) -> String {
+        let folder = std::env::temp_dir().join(format!("upload_queue_{}_test_{}", name, std::process::id()));
+        std::fs::create_dir_all(folder.join("session-1")).unwrap();
+        folder.to_string_lossy().to_string()
+    }
+
+    fn item(folder: &str, id: &str, status: UploadStatus) -> UploadQueueItem {
+        let mut item = UploadQueueItem::new(
+            id.to_string(),
+            "session-1".to_string(),
+            format!("{}.jpg", id),
+            format!("{}/session-1/{}.jpg", folder, id),
+            UploadBackend::default(),
+            "remote".to_string(),
+        );
+        item.status = status;
+        item
+    }
+
+    fn journaled_ids(folder: &str) -> Vec<String> {
+        load_journal(folder).unwrap().into_iter().map(|item| item.id).collect()
+    }
+
+    #[tokio::test]
+    async fn restores_only_outstanding_items() {
+        let folder = temp_folder("restore");
+        let statuses = [
+            ("pending", UploadStatus::Pending),
+            ("uploading", UploadStatus::Uploading),
+            ("failed", UploadStatus::Failed),
+            ("retrying", UploadStatus::Retrying),
+            ("completed", UploadStatus::Completed),
+            ("cancelled", UploadStatus::Cancelled),
+        ];
+        let items: Vec<_> = statuses.into_iter().map(|(id, status)| item(&folder, id, status)).collect();
+        save_journal(&folder, &items).unwrap();
+
+        let queue = UploadQueue::new();
+        assert_eq!(queue.restore_from_folder(&format!("{}/", folder)).await.unwrap(), 4);
+
+        let restored = queue.get_session_items("session-1").await;
+        let ids: Vec<&str> = restored.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, ["pending", "uploading", "failed", "retrying"]);
+        // Interrupted uploads start over
+        assert_eq!(restored[1].status, UploadStatus::Pending);
+
+        // The finished items are dropped from the journal, and restoring again adds nothing
+        assert_eq!(journaled_ids(&folder), ["pending", "uploading", "failed", "retrying"]);
+        assert_eq!(queue.restore_from_folder(&folder).await.unwrap(), 0);
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[tokio::test]
+    async fn emptied_folder_gets_an_empty_journal() {
+        let folder = temp_folder("empty");
+        let other = temp_folder("other");
+
+        let queue = UploadQueue::new();
+        queue.enqueue_batch(vec![item(&folder, "a", UploadStatus::Pending), item(&folder, "b", UploadStatus::Pending)]).await.unwrap();
+        queue.enqueue(item(&other, "c", UploadStatus::Pending)).await.unwrap();
+        assert_eq!(journaled_ids(&folder), ["a", "b"]);
+
+        queue.cancel_item("a").await.unwrap();
+        assert_eq!(journaled_ids(&folder), ["b"]);
+
+        queue.remove_session_items("session-1").await;
+        assert!(std::path::Path::new(&folder).join(UPLOAD_QUEUE_JOURNAL_FILE).exists());
+        assert!(journaled_ids(&folder).is_empty());
+        assert!(journaled_ids(&other).is_empty());
+
+        std::fs::remove_dir_all(&folder).unwrap();
+        std::fs::remove_dir_all(&other).unwrap();
+    }
+}
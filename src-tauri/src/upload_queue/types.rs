@@ -1,3 +1,4 @@
+use crate::upload_targets::types::UploadBackend;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -33,7 +34,11 @@ pub struct UploadQueueItem {
     pub session_id: String,
     pub filename: String,
     pub local_path: String,
-    pub drive_folder_id: String,
+    #[serde(default)]
+    pub backend: UploadBackend,
+    /// Drive folder id, S3 key prefix, WebDAV collection or sub folder, depending on the backend
+    #[serde(alias = "driveFolderId")]
+    pub remote_location: String,
     pub status: UploadStatus,
     pub progress: u8,
     pub error: Option<String>,
@@ -75,6 +80,14 @@ impl Default for UploadQueueState {
     }
 }
 
+/// On-disk journal of the queue items belonging to one working folder
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadQueueJournal {
+    pub items: Vec<UploadQueueItem>,
+    pub saved_at: String,
+}
+
 impl UploadQueueItem {
     #[allow(dead_code)]
     pub fn new(
@@ -82,14 +95,16 @@ impl UploadQueueItem {
         session_id: String,
         filename: String,
         local_path: String,
-        drive_folder_id: String,
+        backend: UploadBackend,
+        remote_location: String,
     ) -> Self {
         Self {
             id,
             session_id,
             filename,
             local_path,
-            drive_folder_id,
+            backend,
+            remote_location,
             status: UploadStatus::Pending,
             progress: 0,
             error: None,
@@ -111,6 +126,34 @@ impl UploadQueueItem {
         self.retry_count += 1;
     }
 
+    /// Working folder this item belongs to.
+    /// Expected local_path format: {working_folder}/{session_id}/{filename}
+    pub fn working_folder(&self) -> Option<String> {
+        let (session_dir, _) = self.local_path.rsplit_once('/')
+            .or_else(|| self.local_path.rsplit_once('\\'))?;
+        let (folder, _) = session_dir.rsplit_once('/')
+            .or_else(|| session_dir.rsplit_once('\\'))?;
+        Some(folder.to_string())
+    }
+
+    /// Whether the item still needs work (or a decision) and so belongs in the journal.
+    /// Completed and cancelled items are dropped so the journal doesn't grow with every upload.
+    pub fn is_outstanding(&self) -> bool {
+        matches!(
+            self.status,
+            UploadStatus::Pending | UploadStatus::Retrying | UploadStatus::Failed | UploadStatus::Uploading
+        )
+    }
+
+    /// Reset an item that was interrupted mid-upload (e.g. by a crash) so it gets picked up again
+    pub fn reset_interrupted(&mut self) {
+        if self.status == UploadStatus::Uploading {
+            self.status = UploadStatus::Pending;
+            self.progress = 0;
+            self.started_at = None;
+        }
+    }
+
     #[allow(dead_code)]
     pub fn get_timeout_duration(&self) -> Duration {
         Duration::from_secs(self.timeout_secs)
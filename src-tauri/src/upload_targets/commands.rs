@@ -0,0 +1,47 @@
+use crate::upload_targets::types::UploadTargetsConfig;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// Path of the upload targets configuration in app data
+fn get_upload_targets_config_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(app_data_dir.join("upload_targets.json"))
+}
+
+/// Load the upload targets configuration (defaults to nothing configured)
+pub fn load_upload_targets_config(app: &tauri::AppHandle) -> Result<UploadTargetsConfig, String> {
+    let config_path = get_upload_targets_config_path(app)?;
+
+    if !config_path.exists() {
+        return Ok(UploadTargetsConfig::default());
+    }
+
+    let json = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read upload targets config: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse upload targets config: {}", e))
+}
+
+/// Get the configured upload targets (S3, WebDAV, local folder), without their secrets
+#[tauri::command]
+pub async fn get_upload_targets_config(app: tauri::AppHandle) -> Result<UploadTargetsConfig, String> {
+    Ok(load_upload_targets_config(&app)?.redacted())
+}
+
+/// Save the upload targets configuration. Secrets left empty keep their saved value.
+#[tauri::command]
+pub async fn save_upload_targets_config(
+    app: tauri::AppHandle,
+    config: UploadTargetsConfig,
+) -> Result<(), String> {
+    let config = config.with_saved_secrets(&load_upload_targets_config(&app)?);
+    let config_path = get_upload_targets_config_path(&app)?;
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize upload targets config: {}", e))?;
+    fs::write(config_path, json).map_err(|e| format!("Failed to write upload targets config: {}", e))
+}
@@ -0,0 +1,42 @@
+use crate::google_drive::queue_upload::upload_photo_to_drive_internal;
+use crate::upload_targets::target::UploadTarget;
+use crate::upload_targets::types::{RemoteFile, UploadBackend};
+use futures::future::BoxFuture;
+use std::path::Path;
+
+/// Google Drive target - wraps the existing Drive upload (uses the OAuth login in AppState)
+pub struct GoogleDriveTarget {
+    app: tauri::AppHandle,
+}
+
+impl GoogleDriveTarget {
+    pub fn new(app: tauri::AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl UploadTarget for GoogleDriveTarget {
+    fn backend(&self) -> UploadBackend {
+        UploadBackend::GoogleDrive
+    }
+
+    fn upload<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote_location: &'a str,
+    ) -> BoxFuture<'a, Result<RemoteFile, String>> {
+        Box::pin(async move {
+            let file_id = upload_photo_to_drive_internal(
+                local_path.to_string_lossy().to_string(),
+                remote_location.to_string(),
+                self.app.clone(),
+            )
+            .await?;
+
+            Ok(RemoteFile {
+                link: Some(format!("https://drive.google.com/file/d/{}/view", file_id)),
+                remote_id: file_id,
+            })
+        })
+    }
+}
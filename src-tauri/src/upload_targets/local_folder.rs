@@ -0,0 +1,105 @@
+use crate::upload_targets::target::{remote_file_name, UploadTarget};
+use crate::upload_targets::types::{LocalFolderConfig, RemoteFile, UploadBackend};
+use futures::future::BoxFuture;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Copies files into a folder (typically a mounted network share)
+pub struct LocalFolderTarget {
+    config: LocalFolderConfig,
+}
+
+impl LocalFolderTarget {
+    pub fn new(config: LocalFolderConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl UploadTarget for LocalFolderTarget {
+    fn backend(&self) -> UploadBackend {
+        UploadBackend::LocalFolder
+    }
+
+    fn upload<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote_location: &'a str,
+    ) -> BoxFuture<'a, Result<RemoteFile, String>> {
+        Box::pin(async move {
+            let name = remote_file_name(local_path)?;
+            let root = Path::new(&self.config.root_path);
+            if !root.is_dir() {
+                return Err(format!("Destination folder is not available: {}", self.config.root_path));
+            }
+
+            let dest_dir = destination_dir(root, remote_location)?;
+            let dest_path = dest_dir.join(&name);
+            let temp_path = dest_dir.join(format!(".{}.part", name));
+
+            let remote_id = dest_path.to_string_lossy().replace('\\', "/");
+            println!("   📁 Copying to: {}", remote_id);
+
+            let source = local_path.to_path_buf();
+            tokio::task::spawn_blocking(move || -> Result<(), String> {
+                fs::create_dir_all(&dest_dir)
+                    .map_err(|e| format!("Failed to create destination folder: {}", e))?;
+                // Copy under a temporary name so readers of the share never see half-written files
+                fs::copy(&source, &temp_path)
+                    .map_err(|e| format!("Failed to copy file: {}", e))?;
+                fs::rename(&temp_path, &dest_path)
+                    .map_err(|e| format!("Failed to finalize copied file: {}", e))?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| format!("Task join error: {}", e))??;
+
+            Ok(RemoteFile {
+                remote_id,
+                link: None,
+            })
+        })
+    }
+}
+
+/// Folder under `root` for a session's remote location ("2024/Wedding Smith"). Only plain
+/// folder names are accepted, so a location can't point outside the root.
+fn destination_dir(root: &Path, remote_location: &str) -> Result<PathBuf, String> {
+    let mut dir = root.to_path_buf();
+    for part in remote_location.split(['/', '\\']).filter(|part| !part.is_empty()) {
+        let mut components = Path::new(part).components();
+        let plain = matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none();
+        // "C:" is a plain name on Unix but a drive prefix on Windows
+        if !plain || part.contains(':') {
+            return Err(format!("Invalid destination folder: {}", remote_location));
+        }
+        dir.push(part);
+    }
+    if !dir.starts_with(root) {
+        return Err(format!("Invalid destination folder: {}", remote_location));
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination_dir() {
+        let root = Path::new("/mnt/share");
+        let cases = [
+            ("", Some("/mnt/share")),
+            ("2024/Wedding Smith", Some("/mnt/share/2024/Wedding Smith")),
+            ("/2024//Wedding\\", Some("/mnt/share/2024/Wedding")),
+            ("..", None),
+            ("2024/../../etc", None),
+            ("./2024", None),
+            ("C:\\Windows", None),
+            ("2024/D:", None),
+        ];
+        for (location, expected) in cases {
+            let dir = destination_dir(root, location).ok();
+            assert_eq!(dir.as_deref(), expected.map(Path::new), "{:?}", location);
+        }
+    }
+}
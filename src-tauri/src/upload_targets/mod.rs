@@ -0,0 +1,11 @@
+// Upload targets module - pluggable destinations for the upload queue
+
+pub mod types;
+pub mod target;
+pub mod google_drive;
+pub mod s3;
+pub mod webdav;
+pub mod local_folder;
+mod commands;
+
+pub use commands::*;
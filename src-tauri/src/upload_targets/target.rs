@@ -0,0 +1,88 @@
+use crate::upload_targets::commands::load_upload_targets_config;
+use crate::upload_targets::google_drive::GoogleDriveTarget;
+use crate::upload_targets::local_folder::LocalFolderTarget;
+use crate::upload_targets::s3::S3Target;
+use crate::upload_targets::types::{RemoteFile, UploadBackend};
+use crate::upload_targets::webdav::WebDavTarget;
+use futures::future::BoxFuture;
+use std::path::Path;
+
+/// A destination the upload queue can send files to.
+///
+/// `remote_location` is the per-session location chosen when the session was set up:
+/// a Drive folder id, an object key prefix (S3), a collection path (WebDAV)
+/// or a sub folder of the configured root (local folder).
+pub trait UploadTarget: Send + Sync {
+    fn backend(&self) -> UploadBackend;
+
+    fn upload<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote_location: &'a str,
+    ) -> BoxFuture<'a, Result<RemoteFile, String>>;
+}
+
+/// Create the upload target for a backend, using the saved target configuration
+pub fn build_upload_target(
+    app: &tauri::AppHandle,
+    backend: &UploadBackend,
+) -> Result<Box<dyn UploadTarget>, String> {
+    match backend {
+        UploadBackend::GoogleDrive => Ok(Box::new(GoogleDriveTarget::new(app.clone()))),
+        UploadBackend::S3 => {
+            let config = load_upload_targets_config(app)?
+                .s3
+                .ok_or("S3 upload target is not configured")?;
+            Ok(Box::new(S3Target::new(config)))
+        }
+        UploadBackend::WebDav => {
+            let config = load_upload_targets_config(app)?
+                .webdav
+                .ok_or("WebDAV upload target is not configured")?;
+            Ok(Box::new(WebDavTarget::new(config)))
+        }
+        UploadBackend::LocalFolder => {
+            let config = load_upload_targets_config(app)?
+                .local_folder
+                .ok_or("Local folder upload target is not configured")?;
+            Ok(Box::new(LocalFolderTarget::new(config)))
+        }
+    }
+}
+
+/// Content type for the file kinds the photobooth produces
+pub fn content_type_for(path: &Path) -> Result<&'static str, String> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .ok_or("No file extension")?;
+
+    match ext.as_str() {
+        "png" => Ok("image/png"),
+        "jpg" | "jpeg" => Ok("image/jpeg"),
+        "gif" => Ok("image/gif"),
+        "webp" => Ok("image/webp"),
+        "mp4" => Ok("video/mp4"),
+        "webm" => Ok("video/webm"),
+        "mov" => Ok("video/quicktime"),
+        _ => Err(format!("Unsupported file format: {}", ext)),
+    }
+}
+
+/// File name of the local file, used as the remote file name
+pub fn remote_file_name(path: &Path) -> Result<String, String> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_string())
+        .ok_or_else(|| "Invalid filename".to_string())
+}
+
+/// Join a remote location and a file name with exactly one '/' between non-empty parts
+pub fn join_remote_path(location: &str, name: &str) -> String {
+    let location = location.trim_matches('/');
+    if location.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", location, name)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::upload_targets::target::{content_type_for, join_remote_path, remote_file_name, UploadTarget};
+use crate::upload_targets::types::{RemoteFile, UploadBackend, WebDavConfig};
+use futures::future::BoxFuture;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use std::path::Path;
+
+/// Characters escaped in WebDAV path segments
+const PATH_SEGMENT_ENCODE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'?')
+    .add(b'<')
+    .add(b'>');
+
+/// WebDAV target (Nextcloud/ownCloud). Creates the session collection with MKCOL, then PUTs the file.
+pub struct WebDavTarget {
+    config: WebDavConfig,
+    client: reqwest::Client,
+}
+
+impl WebDavTarget {
+    pub fn new(config: WebDavConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn resource_url(&self, path: &str) -> String {
+        let encoded = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| utf8_percent_encode(s, PATH_SEGMENT_ENCODE).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{}/{}", self.config.base_url.trim_end_matches('/'), encoded)
+    }
+
+    /// Create every collection along the path. 405 means the collection already exists.
+    async fn ensure_collection(&self, location: &str) -> Result<(), String> {
+        let mkcol = reqwest::Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+        let mut current = String::new();
+
+        for segment in location.split('/').filter(|s| !s.is_empty()) {
+            current = join_remote_path(&current, segment);
+
+            let response = self
+                .client
+                .request(mkcol.clone(), self.resource_url(&current))
+                .basic_auth(&self.config.username, Some(&self.config.password))
+                .send()
+                .await
+                .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+            let status = response.status();
+            if !status.is_success() && status.as_u16() != 405 {
+                return Err(format!("Failed to create WebDAV folder '{}' ({})", current, status));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl UploadTarget for WebDavTarget {
+    fn backend(&self) -> UploadBackend {
+        UploadBackend::WebDav
+    }
+
+    fn upload<'a>(
+        &'a self,
+        local_path: &'a Path,
+        remote_location: &'a str,
+    ) -> BoxFuture<'a, Result<RemoteFile, String>> {
+        Box::pin(async move {
+            let name = remote_file_name(local_path)?;
+            let content_type = content_type_for(local_path)?;
+            let data = tokio::fs::read(local_path)
+                .await
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+
+            self.ensure_collection(remote_location).await?;
+
+            let url = self.resource_url(&join_remote_path(remote_location, &name));
+            println!("   🌐 Uploading to WebDAV: {}", url);
+
+            let response = self
+                .client
+                .put(&url)
+                .basic_auth(&self.config.username, Some(&self.config.password))
+                .header("content-type", content_type)
+                .body(data)
+                .send()
+                .await
+                .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                return Err(format!("WebDAV upload failed ({})", status));
+            }
+
+            Ok(RemoteFile {
+                remote_id: url,
+                link: None,
+            })
+        })
+    }
+}
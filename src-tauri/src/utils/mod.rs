@@ -4,6 +4,7 @@ pub mod deserializers;
 pub mod qr_code;
 pub mod random;
 pub mod file_helpers;
+pub mod schema;
 
 // Re-export commonly used utilities
 pub use deserializers::*;
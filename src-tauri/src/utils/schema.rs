@@ -0,0 +1,382 @@
+// Schema versions of persisted documents
+//
+// Every document the app stores (.ptb workspace, .session.json, custom sets, display
+// layouts) carries a `schemaVersion`. Documents are upgraded as raw JSON when loaded, one
+// registered step at a time, so fields can be renamed or restructured rather than only
+// added with #[serde(default)]. A migrated file's original is kept as <file>.v<N>.bak.
+// A document from a newer app version is refused: reading it would silently drop the
+// fields this version doesn't know, and the next save would lose them for good.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VERSION_FIELD: &str = "schemaVersion";
+
+/// Why a document couldn't be loaded
+#[derive(Debug)]
+pub enum SchemaError {
+    /// Written by a newer app version (the document or one nested in it); refuse it
+    /// rather than treating it as damaged
+    Newer(String),
+    /// Not valid JSON, not the expected shape, or a migration step failed
+    Invalid(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Newer(message) | SchemaError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<SchemaError> for String {
+    fn from(error: SchemaError) -> Self {
+        error.to_string()
+    }
+}
+
+/// One upgrade step, from schema `from` to `from + 1`
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    pub apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// A kind of stored document and the steps that bring older copies up to date
+pub struct DocumentSchema {
+    pub name: &'static str,
+    pub current: u32,
+    migrations: &'static [Migration],
+    /// Array field whose items are documents of their own (the workspace's sessions)
+    nested: Option<(&'static str, &'static DocumentSchema)>,
+}
+
+pub static WORKSPACE: DocumentSchema = DocumentSchema {
+    name: "workspace (.ptb)",
+    current: 1,
+    migrations: &[Migration {
+        from: 0,
+        description: "add schemaVersion",
+        apply: no_changes,
+    }],
+    nested: Some(("sessions", &SESSION)),
+};
+
+pub static SESSION: DocumentSchema = DocumentSchema {
+    name: "session",
+    current: 1,
+    migrations: &[Migration {
+        from: 0,
+        description: "fill uploadTarget from googleDriveMetadata",
+        apply: upload_target_from_drive_metadata,
+    }],
+    nested: None,
+};
+
+pub static CUSTOM_SET: DocumentSchema = DocumentSchema {
+    name: "custom set",
+    current: 1,
+    migrations: &[Migration {
+        from: 0,
+        description: "add schemaVersion",
+        apply: no_changes,
+    }],
+    nested: None,
+};
+
+pub static DISPLAY_LAYOUT: DocumentSchema = DocumentSchema {
+    name: "display layout",
+    current: 1,
+    migrations: &[Migration {
+        from: 0,
+        description: "add schemaVersion",
+        apply: no_changes,
+    }],
+    nested: None,
+};
+
+// Serde defaults, for documents built by the frontend (which doesn't send the version)
+pub fn workspace_version() -> u32 {
+    WORKSPACE.current
+}
+
+pub fn session_version() -> u32 {
+    SESSION.current
+}
+
+pub fn custom_set_version() -> u32 {
+    CUSTOM_SET.current
+}
+
+pub fn display_layout_version() -> u32 {
+    DISPLAY_LAYOUT.current
+}
+
+fn no_changes(_doc: &mut Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Sessions created before upload targets only have Drive metadata
+fn upload_target_from_drive_metadata(session: &mut Value) -> Result<(), String> {
+    let has_backend = session
+        .pointer("/uploadTarget/backend")
+        .is_some_and(|backend| !backend.is_null());
+    let Some(drive) = session.get("googleDriveMetadata").filter(|d| !d.is_null()) else {
+        return Ok(());
+    };
+    let folder_id = drive.get("folderId").cloned().unwrap_or(Value::Null);
+    if has_backend || folder_id.is_null() {
+        return Ok(());
+    }
+
+    let uploaded_files: Vec<Value> = drive
+        .get("uploadedImages")
+        .and_then(Value::as_array)
+        .map(|images| {
+            images
+                .iter()
+                .map(|image| {
+                    json!({
+                        "filename": image.get("filename"),
+                        "remoteId": image.get("driveFileId"),
+                        "link": null,
+                        "uploadedAt": image.get("uploadedAt"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let target = json!({
+        "backend": "googleDrive",
+        "remoteLocation": folder_id,
+        "remoteName": drive.get("folderName"),
+        "remoteLink": drive.get("folderLink"),
+        "accountId": drive.get("accountId"),
+        "uploadedFiles": uploaded_files,
+    });
+    session["uploadTarget"] = target;
+    Ok(())
+}
+
+/// Schema version a document was written with (0 for documents from before versioning)
+pub fn version_of(doc: &Value) -> u32 {
+    doc.get(VERSION_FIELD).and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+impl DocumentSchema {
+    fn newer_error(&self, version: u32) -> SchemaError {
+        SchemaError::Newer(format!(
+            "This {} was saved by a newer version of the app (schema {}, this version supports up to {}). Update the app to open it.",
+            self.name, version, self.current
+        ))
+    }
+
+    /// Upgrade a document to the current schema in place. Returns whether anything changed.
+    pub fn migrate(&self, doc: &mut Value) -> Result<bool, SchemaError> {
+        if !doc.is_object() {
+            return Err(SchemaError::Invalid(format!("The {} is not a JSON object", self.name)));
+        }
+        let version = version_of(doc);
+        if version > self.current {
+            return Err(self.newer_error(version));
+        }
+
+        let mut changed = false;
+        if let Some((field, schema)) = self.nested {
+            if let Some(items) = doc.get_mut(field).and_then(Value::as_array_mut) {
+                for item in items {
+                    changed |= schema.migrate(item)?;
+                }
+            }
+        }
+
+        for from in version..self.current {
+            let step = self
+                .migrations
+                .iter()
+                .find(|m| m.from == from)
+                .ok_or_else(|| SchemaError::Invalid(format!("No migration for {} schema {}", self.name, from)))?;
+            (step.apply)(doc).map_err(|e| {
+                SchemaError::Invalid(format!("Migrating {} from schema {} failed: {}", self.name, from, e))
+            })?;
+            println!("[schema] Migrated {} from schema {} to {}: {}", self.name, from, from + 1, step.description);
+        }
+        if version < self.current {
+            doc[VERSION_FIELD] = json!(self.current);
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Deserialize a document, migrating it first. Returns whether it was migrated.
+    pub fn parse<T: DeserializeOwned>(&self, json: &str) -> Result<(T, bool), SchemaError> {
+        let mut doc: Value = serde_json::from_str(json).map_err(|e| SchemaError::Invalid(e.to_string()))?;
+        let migrated = self.migrate(&mut doc)?;
+        let value = serde_json::from_value(doc).map_err(|e| SchemaError::Invalid(e.to_string()))?;
+        Ok((value, migrated))
+    }
+
+    /// Read a document file, migrating it if needed. A migrated file is backed up and
+    /// rewritten in the current schema.
+    pub fn load_file<T: DeserializeOwned + Serialize>(&self, path: &Path) -> Result<T, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let (value, migrated) = self.parse::<T>(&json)?;
+        if migrated {
+            backup_original(path, &json)?;
+            let migrated_json = serde_json::to_string_pretty(&value)
+                .map_err(|e| format!("Failed to serialize migrated {}: {}", self.name, e))?;
+            let temp_path = sibling(path, ".tmp");
+            fs::write(&temp_path, migrated_json)
+                .and_then(|_| fs::rename(&temp_path, path))
+                .map_err(|e| format!("Failed to write migrated {}: {}", self.name, e))?;
+        }
+        Ok(value)
+    }
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}{}", name, suffix))
+}
+
+/// Keep a document as it was before migrating, as <file>.v<N>.bak (the first one is kept)
+pub fn backup_original(path: &Path, json: &str) -> Result<(), String> {
+    let version = serde_json::from_str::<Value>(json).map(|doc| version_of(&doc)).unwrap_or(0);
+    let backup = sibling(path, &format!(".v{}.bak", version));
+    if backup.exists() {
+        return Ok(());
+    }
+    fs::write(&backup, json).map_err(|e| format!("Failed to back up {} before migrating: {}", path.display(), e))?;
+    println!("[schema] Kept the original of {} as {}", path.display(), backup.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v0_workspace() -> Value {
+        json!({
+            "name": "Wedding",
+            "sessions": [
+                {
+                    "id": "s1",
+                    "name": "Ceremony",
+                    "googleDriveMetadata": {
+                        "folderId": "drive-folder",
+                        "folderName": "Ceremony photos",
+                        "folderLink": "https://drive.google.com/drive/folders/drive-folder",
+                        "accountId": "account-1",
+                        "uploadedImages": [
+                            { "filename": "photo_0001.jpg", "driveFileId": "file-1", "uploadedAt": "2025-06-14T15:30:05Z" }
+                        ]
+                    }
+                },
+                {
+                    "id": "s2",
+                    "name": "Party",
+                    "googleDriveMetadata": { "folderId": "drive-folder-2" },
+                    "uploadTarget": { "backend": "s3", "remoteLocation": "party/" }
+                },
+                { "id": "s3", "name": "Dinner", "googleDriveMetadata": null }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_migrate_v0_workspace() {
+        let mut doc = v0_workspace();
+        assert!(WORKSPACE.migrate(&mut doc).unwrap());
+        assert_eq!(version_of(&doc), 1);
+
+        let sessions = doc["sessions"].as_array().unwrap();
+        assert!(sessions.iter().all(|s| version_of(s) == 1));
+        assert_eq!(
+            sessions[0]["uploadTarget"],
+            json!({
+                "backend": "googleDrive",
+                "remoteLocation": "drive-folder",
+                "remoteName": "Ceremony photos",
+                "remoteLink": "https://drive.google.com/drive/folders/drive-folder",
+                "accountId": "account-1",
+                "uploadedFiles": [
+                    { "filename": "photo_0001.jpg", "remoteId": "file-1", "link": null, "uploadedAt": "2025-06-14T15:30:05Z" }
+                ]
+            })
+        );
+        // A target that is already set is kept, and no target is made up without a Drive folder
+        assert_eq!(sessions[1]["uploadTarget"], json!({ "backend": "s3", "remoteLocation": "party/" }));
+        assert!(sessions[2].get("uploadTarget").is_none());
+
+        // Migrating again changes nothing
+        let migrated = doc.clone();
+        assert!(!WORKSPACE.migrate(&mut doc).unwrap());
+        assert_eq!(doc, migrated);
+    }
+
+    #[test]
+    fn test_migrate_v0_session_in_current_workspace() {
+        let mut doc = v0_workspace();
+        doc[VERSION_FIELD] = json!(1);
+        assert!(WORKSPACE.migrate(&mut doc).unwrap());
+        assert_eq!(doc["sessions"][0]["uploadTarget"]["backend"], "googleDrive");
+    }
+
+    #[test]
+    fn test_migrate_newer_workspace() {
+        let mut doc = json!({ "schemaVersion": 2, "name": "Wedding", "sessions": [] });
+        match WORKSPACE.migrate(&mut doc) {
+            Err(SchemaError::Newer(message)) => {
+                assert!(message.contains("workspace (.ptb)"), "{}", message);
+                assert!(message.contains("schema 2"), "{}", message);
+            }
+            other => panic!("expected SchemaError::Newer, got {:?}", other),
+        }
+        // Left as it was
+        assert_eq!(version_of(&doc), 2);
+    }
+
+    #[test]
+    fn test_migrate_newer_nested_session() {
+        let mut doc = json!({
+            "schemaVersion": 1,
+            "sessions": [{ "schemaVersion": 1, "id": "s1" }, { "schemaVersion": 3, "id": "s2" }]
+        });
+        match WORKSPACE.migrate(&mut doc) {
+            Err(SchemaError::Newer(message)) => {
+                assert!(message.starts_with("This session was saved"), "{}", message);
+                assert!(message.contains("schema 3"), "{}", message);
+            }
+            other => panic!("expected SchemaError::Newer, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_migrate_invalid() {
+        assert!(matches!(WORKSPACE.migrate(&mut json!([])), Err(SchemaError::Invalid(_))));
+        assert!(matches!(SESSION.parse::<Value>("{ not json"), Err(SchemaError::Invalid(_))));
+        assert!(matches!(SESSION.parse::<Value>(r#"{ "schemaVersion": 9 }"#), Err(SchemaError::Newer(_))));
+    }
+
+    #[test]
+    fn test_load_file_backs_up_migrated_document() {
+        let dir = std::env::temp_dir().join(format!("schema_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Wedding.ptb");
+        let original = v0_workspace().to_string();
+        fs::write(&path, &original).unwrap();
+
+        let doc: Value = WORKSPACE.load_file(&path).unwrap();
+        assert_eq!(version_of(&doc), 1);
+        assert_eq!(fs::read_to_string(dir.join("Wedding.ptb.v0.bak")).unwrap(), original);
+        let rewritten: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten, doc);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
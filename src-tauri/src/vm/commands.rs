@@ -153,7 +153,7 @@ pub async fn get_vm_logs(lines: Option<usize>) -> Result<VmLogsResponse, String>
 /// Check if the VM is online by pinging the health endpoint
 /// Returns true if the VM daemon is responding
 #[tauri::command]
-pub async fn check_vm_online() -> Result<bool, String> {
+pub async fn check_vm_online(app: tauri::AppHandle) -> Result<bool, String> {
     const DAEMON_URL: &str = "http://localhost:58321/api/health";
     const HEALTH_TIMEOUT_MS: u64 = 2000; // 2 second timeout
 
@@ -162,7 +162,8 @@ pub async fn check_vm_online() -> Result<bool, String> {
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-    let response = client.get(DAEMON_URL).send().await;
+    let token = crate::daemon_auth::load_daemon_token(&app);
+    let response = crate::daemon_auth::with_daemon_auth(client.get(DAEMON_URL), token.as_deref()).send().await;
 
     match response {
         Ok(resp) => {
@@ -1,3 +1,4 @@
+use super::raw;
 use crate::types::{ImageDimensions, ThumbnailLoadProgress, WorkingFolderInfo, WorkingImage, ImageFileInfo};
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
@@ -349,32 +350,49 @@ async fn scan_folder_for_images(
     let path = PathBuf::from(folder_path);
     let mut images = Vec::new();
 
-    let entries = fs::read_dir(&path).map_err(|e| format!("Failed to read directory: {}", e))?;
+    // Read the listing up front: converting RAW files adds JPEGs to the folder
+    let entries: Vec<_> = fs::read_dir(&path)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to read entry: {}", e))?;
 
     // Collect all valid image files first
     let mut image_files = Vec::new();
     for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let file_path = entry.path();
+        let mut file_path = entry.path();
 
         if !file_path.is_file() {
             continue;
         }
 
-        let extension = file_path
+        let mut extension = file_path
             .extension()
             .and_then(|e| e.to_str())
             .unwrap_or("")
             .to_lowercase();
 
         // Support common image formats
-        if !matches!(
-            extension.as_str(),
-            "jpg" | "jpeg" | "png" | "raw" | "cr2" | "nef" | "arw"
-        ) {
+        if !matches!(extension.as_str(), "jpg" | "jpeg" | "png" | "raw") && !raw::is_raw(&file_path) {
             continue;
         }
 
+        // A RAW is shown as its JPEG: the camera's (RAW+JPEG, listed on its own) or one
+        // converted from the RAW now. RAWs that can't be converted are listed as-is.
+        if raw::is_raw(&file_path) {
+            if raw::sibling_jpeg(&file_path).is_some() {
+                continue;
+            }
+            let raw_path = file_path.clone();
+            match tokio::task::spawn_blocking(move || raw::convert_raw(&raw_path)).await {
+                Ok(Ok(jpeg)) => {
+                    file_path = jpeg;
+                    extension = "jpg".to_string();
+                }
+                Ok(Err(e)) => eprintln!("[scan_folder_for_images] {}", e),
+                Err(e) => eprintln!("[scan_folder_for_images] RAW conversion task failed: {}", e),
+            }
+        }
+
         let filename = file_path
             .file_name()
             .and_then(|n| n.to_str())
@@ -512,6 +530,10 @@ async fn generate_thumbnail_cached(
     app: &tauri::AppHandle,
     _task_id: usize,
 ) -> Result<ThumbnailResult, String> {
+    // RAW files are thumbnailed from their JPEG
+    let usable_path = raw::usable_image_path(std::path::Path::new(image_path))?;
+    let image_path = usable_path.to_str().ok_or("Invalid path")?;
+
     // Get thumbnail path
     let app_data_dir = app
         .path()
@@ -814,6 +836,10 @@ async fn generate_thumbnail_with_settings(
     cache_dir_name: &str,
     thumb_prefix: &str,
 ) -> Result<ThumbnailResult, String> {
+    // RAW files are thumbnailed from their JPEG
+    let usable_path = raw::usable_image_path(std::path::Path::new(image_path))?;
+    let image_path = usable_path.to_str().ok_or("Invalid path")?;
+
     // Get thumbnail path
     let app_data_dir = app
         .path()
@@ -1,5 +1,7 @@
 // Working folder management module (stub - to be fully implemented)
 
 pub mod commands;
+pub mod raw;
 
 pub use commands::*;
+pub use raw::convert_raw_image;
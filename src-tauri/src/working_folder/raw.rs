@@ -0,0 +1,197 @@
+// RAW ingest: turn camera RAW files into JPEGs the rest of the app can decode
+//
+// Every RAW format carries a full-size JPEG preview, found by the raw-preview crate
+// (shared with the camera daemon). The largest one is written next to the RAW as
+// `<stem>.JPG`, so the original stays where it was. Uncompressed CFA DNGs without a
+// preview fall back to a half-size demosaic.
+// A JPEG the camera wrote next to the RAW (RAW+JPEG mode) is used as-is.
+
+use raw_preview::embedded_jpeg;
+use raw_preview::tiff::{Entry, Tiff};
+use std::path::{Path, PathBuf};
+
+pub use raw_preview::{is_raw, preview_path, sibling_jpeg};
+
+/// Quality of JPEGs produced by the demosaic fallback
+const DEMOSAIC_JPEG_QUALITY: u8 = 92;
+
+/// Path of a decodable version of `path`: the file itself unless it is a RAW, else its
+/// sibling JPEG, created from the embedded preview (or a demosaic) on first use
+pub fn usable_image_path(path: &Path) -> Result<PathBuf, String> {
+    if !is_raw(path) {
+        return Ok(path.to_path_buf());
+    }
+    if let Some(jpeg) = sibling_jpeg(path) {
+        return Ok(jpeg);
+    }
+    convert_raw(path)
+}
+
+/// JPEG of a RAW file's contents: its largest embedded preview, else a demosaic
+pub fn raw_to_jpeg(data: &[u8]) -> Result<Vec<u8>, String> {
+    match embedded_jpeg(data) {
+        Some(jpeg) => Ok(jpeg.to_vec()),
+        None => {
+            println!("[raw] No embedded preview, demosaicing");
+            demosaic_to_jpeg(data)
+        }
+    }
+}
+
+/// Write the RAW's JPEG to `preview_path` and return that path
+pub fn convert_raw(raw: &Path) -> Result<PathBuf, String> {
+    let data = std::fs::read(raw).map_err(|e| format!("Failed to read {}: {}", raw.display(), e))?;
+    let jpeg = raw_to_jpeg(&data).map_err(|e| format!("Can't convert {}: {}", raw.display(), e))?;
+    let path = preview_path(raw);
+    std::fs::write(&path, jpeg).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    // Keep the shot's time so folders sort the JPEG where the RAW was
+    if let Ok(modified) = std::fs::metadata(raw).and_then(|m| m.modified()) {
+        let _ = std::fs::File::options().write(true).open(&path).and_then(|f| f.set_modified(modified));
+    }
+    println!("[raw] Converted {} to {}", raw.display(), path.display());
+    Ok(path)
+}
+
+/// Convert a RAW to its sibling JPEG; returns the JPEG's path
+#[tauri::command]
+pub async fn convert_raw_image(path: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || usable_image_path(Path::new(&path)))
+        .await
+        .map_err(|e| format!("RAW conversion task failed: {}", e))?
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+// Demosaic fallback
+
+const TAG_NEW_SUBFILE_TYPE: u16 = 0x00FE;
+const TAG_IMAGE_WIDTH: u16 = 0x0100;
+const TAG_IMAGE_LENGTH: u16 = 0x0101;
+const TAG_BITS_PER_SAMPLE: u16 = 0x0102;
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_PHOTOMETRIC: u16 = 0x0106;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_SAMPLES_PER_PIXEL: u16 = 0x0115;
+const TAG_STRIP_BYTE_COUNTS: u16 = 0x0117;
+const TAG_CFA_REPEAT_DIM: u16 = 0x828D;
+const TAG_CFA_PATTERN: u16 = 0x828E;
+const TAG_BLACK_LEVEL: u16 = 0xC61A;
+const TAG_WHITE_LEVEL: u16 = 0xC61D;
+const TAG_AS_SHOT_NEUTRAL: u16 = 0xC628;
+const PHOTOMETRIC_CFA: f64 = 32803.0;
+/// Sanity limit on strip counts read from a (possibly corrupt) file
+const MAX_STRIPS: usize = 1 << 16;
+
+/// Colour of each cell of a 2x2 CFA pattern: 0 red, 1 green, 2 blue
+type CfaPattern = [u8; 4];
+
+/// Half-size demosaic of an uncompressed CFA image (DNG without a preview): each 2x2
+/// block becomes one pixel, white balanced with AsShotNeutral and gamma corrected
+fn demosaic_to_jpeg(data: &[u8]) -> Result<Vec<u8>, String> {
+    let tiff = Tiff::parse(data).ok_or("not a TIFF-based RAW and no embedded preview")?;
+    let entries = tiff
+        .ifds()
+        .into_iter()
+        .filter(|e| tiff.first(e, TAG_PHOTOMETRIC) == Some(PHOTOMETRIC_CFA))
+        // The full-resolution image, not a reduced one
+        .find(|e| tiff.first(e, TAG_NEW_SUBFILE_TYPE).unwrap_or(0.0) == 0.0)
+        .ok_or("no CFA image (compressed RAW without a preview isn't supported)")?;
+
+    let field = |tag| tiff.first(&entries, tag);
+    if field(TAG_COMPRESSION).unwrap_or(1.0) != 1.0 {
+        return Err("CFA data is compressed".to_string());
+    }
+    if field(TAG_SAMPLES_PER_PIXEL).unwrap_or(1.0) != 1.0 {
+        return Err("unexpected samples per pixel".to_string());
+    }
+    let width = field(TAG_IMAGE_WIDTH).ok_or("missing image width")? as usize;
+    let height = field(TAG_IMAGE_LENGTH).ok_or("missing image height")? as usize;
+    let bits = field(TAG_BITS_PER_SAMPLE).unwrap_or(16.0) as usize;
+    if bits != 8 && bits != 16 {
+        return Err(format!("{}-bit packed CFA data isn't supported", bits));
+    }
+
+    // Strips are stored back to back in row order
+    let strip_entry = |tag| entries.iter().find(|e| e.tag == tag).map(|e| tiff.values(e, MAX_STRIPS));
+    let offsets = strip_entry(TAG_STRIP_OFFSETS).ok_or("missing strip offsets (tiled CFA isn't supported)")?;
+    let counts = strip_entry(TAG_STRIP_BYTE_COUNTS).ok_or("missing strip byte counts")?;
+    let mut samples = Vec::with_capacity(width * height * bits / 8);
+    for (&offset, &count) in offsets.iter().zip(counts.iter()) {
+        let strip = data
+            .get(offset as usize..offset as usize + count as usize)
+            .ok_or("strip runs past the end of the file")?;
+        samples.extend_from_slice(strip);
+    }
+    let bytes_per_sample = bits / 8;
+    if samples.len() < width * height * bytes_per_sample {
+        return Err("CFA data is truncated".to_string());
+    }
+    let sample = |x: usize, y: usize| -> f64 {
+        let i = (y * width + x) * bytes_per_sample;
+        if bytes_per_sample == 1 {
+            samples[i] as f64
+        } else {
+            let pair = [samples[i], samples[i + 1]];
+            (if tiff.little_endian() { u16::from_le_bytes(pair) } else { u16::from_be_bytes(pair) }) as f64
+        }
+    };
+
+    let pattern = cfa_pattern(&tiff, &entries)?;
+    let black = field(TAG_BLACK_LEVEL).unwrap_or(0.0);
+    let white = field(TAG_WHITE_LEVEL).unwrap_or(((1u32 << bits) - 1) as f64);
+    let range = (white - black).max(1.0);
+    // AsShotNeutral is the camera's white point; scale each channel so it becomes grey
+    let neutral = entries
+        .iter()
+        .find(|e| e.tag == TAG_AS_SHOT_NEUTRAL)
+        .map(|e| tiff.values(e, 3))
+        .filter(|n| n.len() == 3 && n.iter().all(|&v| v > 0.0))
+        .unwrap_or_else(|| vec![1.0, 1.0, 1.0]);
+    let gains = [neutral[1] / neutral[0], 1.0, neutral[1] / neutral[2]];
+
+    let (out_width, out_height) = (width / 2, height / 2);
+    if out_width == 0 || out_height == 0 {
+        return Err("image is too small".to_string());
+    }
+    let mut image = image::RgbImage::new(out_width as u32, out_height as u32);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let (x0, y0) = (x as usize * 2, y as usize * 2);
+        let mut sums = [0.0f64; 3];
+        let mut counts = [0u32; 3];
+        for (cell, &colour) in pattern.iter().enumerate() {
+            let value = sample(x0 + cell % 2, y0 + cell / 2);
+            sums[colour as usize] += value;
+            counts[colour as usize] += 1;
+        }
+        for channel in 0..3 {
+            let linear = if counts[channel] == 0 {
+                0.0
+            } else {
+                ((sums[channel] / counts[channel] as f64 - black) / range * gains[channel]).clamp(0.0, 1.0)
+            };
+            pixel[channel] = (linear.powf(1.0 / 2.2) * 255.0).round() as u8;
+        }
+    }
+
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, DEMOSAIC_JPEG_QUALITY)
+        .encode_image(&image)
+        .map_err(|e| format!("Failed to encode JPEG: {}", e))?;
+    Ok(jpeg)
+}
+
+/// The image's 2x2 CFA pattern (RGGB if it doesn't say)
+fn cfa_pattern(tiff: &Tiff, entries: &[Entry]) -> Result<CfaPattern, String> {
+    let Some(entry) = entries.iter().find(|e| e.tag == TAG_CFA_PATTERN) else {
+        return Ok([0, 1, 1, 2]);
+    };
+    let repeat = entries
+        .iter()
+        .find(|e| e.tag == TAG_CFA_REPEAT_DIM)
+        .map(|e| tiff.values(e, 2))
+        .unwrap_or_else(|| vec![2.0, 2.0]);
+    let values = tiff.values(entry, 4);
+    if repeat != [2.0, 2.0] || values.len() != 4 || values.iter().any(|&v| v > 2.0) {
+        return Err("only 2x2 RGB CFA patterns are supported".to_string());
+    }
+    Ok([values[0] as u8, values[1] as u8, values[2] as u8, values[3] as u8])
+}
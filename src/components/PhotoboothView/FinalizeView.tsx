@@ -13,13 +13,14 @@ import * as fs from "@tauri-apps/plugin-fs";
 import { emitTo } from "@tauri-apps/api/event";
 import { Frame, FrameZone } from "../../types/frame";
 import { PlacedImage } from "../../types/collage";
-import { usePhotobooth, useWorkspaceSettings } from "../../contexts";
+import { usePhotobooth, usePhotoboothSession, useWorkspaceSettings } from "../../contexts";
 import { DisplayLayout } from "../../types/displayLayout";
 import { invoke } from "@tauri-apps/api/core";
 import { autoPlacePhotos, PhotoForPlacement } from "../../utils/autoPlacement";
 import { useToast } from "../../contexts";
 import "./FinalizeView.css";
 import { createLogger } from '../../utils/logger';
+import { recordCatalogMedia } from '../../utils/catalog';
 import { useKeyboardZoom } from '../../hooks/useKeyboardZoom';
 
 const logger = createLogger('FinalizeView');
@@ -99,6 +100,7 @@ export default function FinalizeView({
     collageIsDirty,
   } = usePhotobooth();
   const { selectedDisplayLayoutId } = useWorkspaceSettings();
+  const { currentSession } = usePhotoboothSession();
   const { showToast } = useToast();
 
   const getDisplayLayoutForGuest = useCallback(async (): Promise<DisplayLayout | null> => {
@@ -489,6 +491,13 @@ export default function FinalizeView({
         await fs.writeFile(`${sessionPath}/${filename}`, exportResult.bytes);
         setCurrentCollageFilename(filename);
         const newFilePath = `${workingFolder}/${sessionFolderName}/${filename}`;
+        await recordCatalogMedia(
+          workingFolder,
+          currentSession?.id ?? null,
+          "collage",
+          newFilePath,
+          selectedPhotos.map((p) => p.filename),
+        );
         imageUrl = convertFileSrc(newFilePath);
 
         // Reset dirty state after regenerating
@@ -547,6 +556,8 @@ export default function FinalizeView({
     currentCollageFilename,
     workingFolder,
     sessionFolderName,
+    currentSession,
+    selectedPhotos,
     exportPhotoboothCanvasAsPNG,
     setCurrentCollageFilename,
     setCollageIsDirty,
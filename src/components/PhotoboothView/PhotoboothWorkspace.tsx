@@ -29,7 +29,7 @@ export default function PhotoboothWorkspace() {
   const { timerDelay, autoCount, delayBetweenPhotos, photoReviewTime } = useCaptureTiming();
   const { workingFolder, photoNamingScheme, qrUploadEnabled, qrUploadAllImages } = useWorkspaceSettings();
   const { sessions, currentSession, loadSession, updateCurrentSessionFromDownload, createNewSession } = usePhotoboothSession();
-  const { captureError, clearCaptureError, isCameraConnected, hasEverConnected, isConnecting, setDownloading, addPhotoDownloadedListener, removePhotoDownloadedListener } = useCamera();
+  const { captureError, clearCaptureError, isWsConnected, isCameraConnected, hasEverConnected, isConnecting, setDownloading, addPhotoDownloadedListener, removePhotoDownloadedListener, addGroupCapturedListener, removeGroupCapturedListener, addRawAttachedListener, removeRawAttachedListener } = useCamera();
   const { stream: liveViewStream, hdmi, ptp } = useLiveView();
   const { showToast } = useToast();
   const { photoboothFrame, finalizeViewMode, setFinalizeViewMode, setFinalizeEditingZoneId, placedImages, setPlacedImages } = usePhotobooth();
@@ -212,6 +212,11 @@ export default function PhotoboothWorkspace() {
     setCurrentSetPhotos,
     addPhotoDownloadedListener,
     removePhotoDownloadedListener,
+    addGroupCapturedListener,
+    removeGroupCapturedListener,
+    addRawAttachedListener,
+    removeRawAttachedListener,
+    isWsConnected,
   });
 
   // Debug logging for QR data changes
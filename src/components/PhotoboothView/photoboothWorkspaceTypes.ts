@@ -1,4 +1,5 @@
 import { Camera, Grid3x3, Layers } from "lucide-react";
+import type { SessionUploadTarget } from "../../types/uploadTargets";
 
 export type DisplayMode = 'single' | 'center' | 'canvas' | 'finalize';
 
@@ -34,6 +35,20 @@ export interface PtbSession {
     originalPath: string;
     cameraPath: string;
     capturedAt: string;
+    cameraId?: string;
+    groupId?: string;
+    sha256?: string;
+    rawFilename?: string;
+  }>;
+  photoGroups?: Array<{
+    id: string;
+    capturedAt: string;
+    maxSkewMs?: number | null;
+    members: Array<{
+      cameraId: string;
+      filename: string;
+      triggerSkewMs?: number | null;
+    }>;
   }>;
   googleDriveMetadata?: {
     folderId?: string | null;
@@ -45,4 +60,5 @@ export interface PtbSession {
       uploadedAt: string;
     }>;
   };
+  uploadTarget?: SessionUploadTarget;
 }
@@ -36,6 +36,7 @@ import { getCameraSettingsService } from "../../../services/cameraSettingsServic
 import CameraWebSocketManager from "../../../services/cameraWebSocket";
 import "./CameraSection.css";
 import { createLogger } from '../../../utils/logger';
+import { daemonFetch } from '../../../services/daemonAuth';
 
 const logger = createLogger('CameraSection');
 
@@ -266,7 +267,7 @@ export function CameraSection({
     }
     const startTime = Date.now();
     try {
-      const response = await fetch(`${API_BASE}/api/cameras`);
+      const response = await daemonFetch(`${API_BASE}/api/cameras`);
       if (response.ok) {
         const cameras = await response.json();
         setAvailableCameras(cameras);
@@ -304,19 +305,20 @@ export function CameraSection({
     // Update the global camera settings service
     cameraSettingsService.setCamera(camera.id, camera.manufacturer, camera.model);
 
-    // Tell controller to track this camera for polling/disconnect detection
+    // Start polling this camera's controller for shutter presses and disconnects.
+    // The daemon's supervisor assigns each controller its camera, so no switch is sent.
     try {
-      await fetch(`${API_BASE}/api/controller/switch?camera=${camera.id}`, { method: 'POST' });
-      logger.debug(`Controller switched to camera ${camera.id}`);
+      await daemonFetch(`${API_BASE}/api/controller/resume-polling?camera=${encodeURIComponent(camera.id)}`, { method: 'POST' });
+      logger.debug(`Polling camera ${camera.id}`);
     } catch (error) {
-      logger.warn('Failed to switch controller camera:', error);
+      logger.warn('Failed to start polling the camera:', error);
     }
 
     // Fetch initial status and config
     try {
       const [statusResponse, configResponse] = await Promise.all([
-        fetch(`${API_BASE}/api/camera/status?camera=${camera.id}`),
-        fetch(`${API_BASE}/api/camera/config?camera=${camera.id}`)
+        daemonFetch(`${API_BASE}/api/camera/status?camera=${camera.id}`),
+        daemonFetch(`${API_BASE}/api/camera/config?camera=${camera.id}`)
       ]);
 
       if (statusResponse.ok) {
@@ -489,7 +491,7 @@ export function CameraSection({
     }
 
     try {
-      const response = await fetch(`${API_BASE}/api/camera/config?camera=${selectedCamera.id}`, {
+      const response = await daemonFetch(`${API_BASE}/api/camera/config?camera=${selectedCamera.id}`, {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json',
@@ -30,9 +30,11 @@ import {
   ConnectionInfoSection,
   CustomSetsSection,
   DisplayLayoutSection,
+  LocalGallerySection,
   NamingSchemeSection,
   PhotoboothSettingsSection,
   PrintSettingsSection,
+  UploadTargetSection,
   WorkingFolderSection,
 } from "./sections";
 import {
@@ -51,7 +53,7 @@ interface PhotoboothSidebarProps {
 }
 
 type PhotoboothTab = 'camera' | 'photobooth' | 'print' | 'qr' | 'gif' | 'edit';
-type CollapsibleSection = 'camera' | 'liveview' | 'connection' | 'polling' | 'folder' | 'photobooth' | 'frame' | 'session' | 'naming' | 'qr' | 'gif' | 'print' | 'displaylayout';
+type CollapsibleSection = 'camera' | 'liveview' | 'connection' | 'polling' | 'folder' | 'photobooth' | 'frame' | 'session' | 'naming' | 'qr' | 'uploadtarget' | 'localgallery' | 'gif' | 'print' | 'displaylayout';
 type SettingType = 'shutter' | 'aperture' | 'iso' | 'ev' | 'wb' | 'metering' | 'folder' | 'mode' | null;
 
 export default function PhotoboothSidebar(props: PhotoboothSidebarProps) {
@@ -100,6 +102,8 @@ export default function PhotoboothSidebar(props: PhotoboothSidebarProps) {
     session: false,
     naming: false,
     qr: false,
+    uploadtarget: false,
+    localgallery: false,
     gif: false,
     print: false,
     displaylayout: false,
@@ -429,6 +433,16 @@ export default function PhotoboothSidebar(props: PhotoboothSidebarProps) {
                   onShowInfo={handleShowQrInfo}
                 />
 
+                <UploadTargetSection
+                  expanded={expandedSections.uploadtarget}
+                  onToggle={() => toggleSection('uploadtarget')}
+                />
+
+                <LocalGallerySection
+                  expanded={expandedSections.localgallery}
+                  onToggle={() => toggleSection('localgallery')}
+                />
+
                 <GifSettingsSection
                   expanded={expandedSections.gif}
                   onToggle={() => toggleSection('gif')}
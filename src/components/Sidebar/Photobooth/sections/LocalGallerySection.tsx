@@ -0,0 +1,103 @@
+import { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ChevronDown, ChevronRight } from 'lucide-react';
+import { useToast, useWorkspaceSettings } from '../../../../contexts';
+import type { LocalGalleryStatus } from '../../../../types/localGallery';
+
+interface LocalGallerySectionProps {
+  expanded: boolean;
+  onToggle: () => void;
+}
+
+export function LocalGallerySection({ expanded, onToggle }: LocalGallerySectionProps) {
+  const { workingFolder } = useWorkspaceSettings();
+  const { showToast } = useToast();
+  const [status, setStatus] = useState<LocalGalleryStatus>({ running: false });
+  const [host, setHost] = useState('');
+  const [isBusy, setIsBusy] = useState(false);
+
+  const start = useCallback(async (folderPath: string) => {
+    const next = await invoke<LocalGalleryStatus>('start_local_gallery', {
+      folderPath,
+      port: null,
+      host: host.trim() || null,
+    });
+    setStatus(next);
+  }, [host]);
+
+  useEffect(() => {
+    invoke<LocalGalleryStatus>('get_local_gallery_status')
+      .then(setStatus)
+      .catch((error) => showToast('Failed to get the local gallery status: ' + error, 'error'));
+  }, [showToast]);
+
+  // A running gallery follows the working folder
+  useEffect(() => {
+    if (!status.running || !workingFolder || status.workingFolder === workingFolder) return;
+    start(workingFolder).catch((error) => showToast('Failed to switch the local gallery: ' + error, 'error'));
+  }, [workingFolder, status.running, status.workingFolder, start, showToast]);
+
+  const handleToggleRunning = async () => {
+    if (!workingFolder) return;
+    setIsBusy(true);
+    try {
+      if (status.running) {
+        await invoke('stop_local_gallery');
+        setStatus({ running: false });
+      } else {
+        await start(workingFolder);
+      }
+    } catch (error) {
+      showToast('Local gallery: ' + error, 'error');
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  return (
+    <div className="collapsible-section">
+      <button
+        className="collapsible-header"
+        onClick={onToggle}
+      >
+        <div className="collapsible-header-left">
+          {expanded ? <ChevronDown size={12} /> : <ChevronRight size={12} />}
+          <span className="collapsible-title">Local Gallery</span>
+        </div>
+      </button>
+      {expanded && (
+        <div className="collapsible-content">
+          <div className="qr-upload-toggle-row">
+            <div className="setting-label-full">Serve sessions on the LAN</div>
+            <button
+              className={`toggle-btn ${status.running ? 'active' : ''}`}
+              onClick={handleToggleRunning}
+              disabled={isBusy || !workingFolder}
+            >
+              <span className="toggle-slider" />
+            </button>
+          </div>
+          <div className="setting-hint">
+            {status.running
+              ? <>Guests on the booth's network open <strong>{status.baseUrl}</strong>. Session QR codes link to the gallery while it runs.</>
+              : 'Session QR codes link to each session\'s Drive folder. Start the gallery to share photos without internet.'}
+          </div>
+
+          {!status.running && (
+            <>
+              <div className="setting-label-full" style={{ marginTop: '8px', marginBottom: '4px' }}>Host (optional)</div>
+              <input
+                type="text"
+                className="property-input"
+                value={host}
+                placeholder="Detected LAN address"
+                onChange={(e) => setHost(e.target.value)}
+                style={{ width: '100%' }}
+              />
+            </>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,4 +1,7 @@
+import { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { ChevronDown, ChevronRight, Info } from 'lucide-react';
+import { usePhotoboothSession, useWorkspaceSettings } from '../../../../contexts';
 
 interface NamingSchemeSectionProps {
   expanded: boolean;
@@ -7,12 +10,58 @@ interface NamingSchemeSectionProps {
   onPhotoNamingSchemeChange: (value: string) => void;
 }
 
+/** Result of update_session_naming_scheme */
+interface NamingSchemePreview {
+  photoNamingScheme: string;
+  nextFilename: string;
+}
+
+/** Wait for typing to pause before validating the pattern */
+const VALIDATE_DELAY_MS = 400;
+
 export function NamingSchemeSection({
   expanded,
   onToggle,
   photoNamingScheme,
   onPhotoNamingSchemeChange,
 }: NamingSchemeSectionProps) {
+  const { workingFolder } = useWorkspaceSettings();
+  const { currentSession } = usePhotoboothSession();
+  const [nextFilename, setNextFilename] = useState<string | null>(null);
+  const [schemeError, setSchemeError] = useState<string | null>(null);
+  const validateTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+  useEffect(() => () => {
+    if (validateTimerRef.current) clearTimeout(validateTimerRef.current);
+  }, []);
+
+  // Apply the pattern to the current session; the backend validates it and
+  // returns the name the next photo would get
+  const handleSchemeChange = (value: string) => {
+    onPhotoNamingSchemeChange(value);
+    if (validateTimerRef.current) clearTimeout(validateTimerRef.current);
+    if (!workingFolder || !currentSession) {
+      setNextFilename(null);
+      setSchemeError(null);
+      return;
+    }
+    const sessionId = currentSession.id;
+    validateTimerRef.current = setTimeout(async () => {
+      try {
+        const preview = await invoke<NamingSchemePreview>('update_session_naming_scheme', {
+          folderPath: workingFolder,
+          sessionId,
+          photoNamingScheme: value,
+        });
+        setNextFilename(preview.nextFilename);
+        setSchemeError(null);
+      } catch (error) {
+        setNextFilename(null);
+        setSchemeError(String(error));
+      }
+    }, VALIDATE_DELAY_MS);
+  };
+
   return (
     <div className="collapsible-section">
       <button
@@ -33,10 +82,16 @@ export function NamingSchemeSection({
             type="text"
             className="property-input"
             value={photoNamingScheme}
-            onChange={(e) => onPhotoNamingSchemeChange(e.target.value)}
+            onChange={(e) => handleSchemeChange(e.target.value)}
             placeholder="IPH_{number}"
             style={{ width: '100%' }}
           />
+          {schemeError && <div className="naming-scheme-error">{schemeError}</div>}
+          {!schemeError && nextFilename && (
+            <div className="naming-scheme-preview">
+              Next photo: <code className="naming-scheme-example">{nextFilename}</code>
+            </div>
+          )}
           <div className="qr-info-banner" style={{ marginBottom: 0, marginTop: '8px' }}>
             <Info size={14} className="info-icon" />
             <div className="info-text">
@@ -48,6 +103,22 @@ export function NamingSchemeSection({
               <code className="naming-scheme-token">{'{number}'}</code>
               <span>inserts a 4-digit counter — e.g., <code className="naming-scheme-example">IPH_0001</code></span>
             </div>
+            <div className="naming-scheme-hint-row">
+              <code className="naming-scheme-token">{'{seq:06}'}</code>
+              <span>counter with a chosen width — e.g., <code className="naming-scheme-example">000001</code></span>
+            </div>
+            <div className="naming-scheme-hint-row">
+              <code className="naming-scheme-token">{'{date}'} {'{time}'}</code>
+              <span>capture date and time — e.g., <code className="naming-scheme-example">20250614_153012</code>, or <code className="naming-scheme-example">{'{date:%Y-%m-%d}'}</code></span>
+            </div>
+            <div className="naming-scheme-hint-row">
+              <code className="naming-scheme-token">{'{event}'} {'{session}'} {'{session_number}'}</code>
+              <span>working folder name, session name and number</span>
+            </div>
+            <div className="naming-scheme-hint-row">
+              <code className="naming-scheme-token">{'{camera_model}'} {'{camera_id}'} {'{original}'}</code>
+              <span>camera model, camera number and the camera's own file name</span>
+            </div>
             <div className="naming-scheme-hint-row">
               <code className="naming-scheme-token">omitted</code>
               <span>number is appended automatically — e.g., <code className="naming-scheme-example">IPH_0001</code></span>
@@ -0,0 +1,201 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ChevronDown, ChevronRight } from 'lucide-react';
+import { usePhotoboothSession, useToast, useWorkspaceSettings } from '../../../../contexts';
+import type { UploadBackend } from '../../../../types/uploadQueue';
+import type { UploadTargetsConfig } from '../../../../types/uploadTargets';
+
+interface UploadTargetSectionProps {
+  expanded: boolean;
+  onToggle: () => void;
+}
+
+const BACKENDS: { value: UploadBackend; label: string }[] = [
+  { value: 'googleDrive', label: 'Drive' },
+  { value: 's3', label: 'S3' },
+  { value: 'webDav', label: 'WebDAV' },
+  { value: 'localFolder', label: 'Folder' },
+];
+
+const EMPTY_S3 = { endpoint: '', region: '', bucket: '', accessKeyId: '', secretAccessKey: '', pathStyle: false, publicBaseUrl: null };
+const EMPTY_WEBDAV = { baseUrl: '', username: '', password: '' };
+const EMPTY_LOCAL_FOLDER = { rootPath: '' };
+
+export function UploadTargetSection({ expanded, onToggle }: UploadTargetSectionProps) {
+  const { workingFolder } = useWorkspaceSettings();
+  const { currentSession, loadSession } = usePhotoboothSession();
+  const { showToast } = useToast();
+  const [config, setConfig] = useState<UploadTargetsConfig>({});
+  const [backend, setBackend] = useState<UploadBackend>('googleDrive');
+  const [remoteLocation, setRemoteLocation] = useState('');
+  const [isSaving, setIsSaving] = useState(false);
+
+  useEffect(() => {
+    if (!expanded) return;
+    invoke<UploadTargetsConfig>('get_upload_targets_config')
+      .then(setConfig)
+      .catch((error) => showToast('Failed to load upload targets: ' + error, 'error'));
+  }, [expanded, showToast]);
+
+  // Start from the session's current target whenever another session is opened
+  useEffect(() => {
+    const target = currentSession?.uploadTarget;
+    setBackend(target?.backend ?? 'googleDrive');
+    setRemoteLocation(target?.backend && target.backend !== 'googleDrive'
+      ? target.remoteLocation ?? ''
+      : currentSession?.name ?? '');
+  }, [currentSession?.id]); // eslint-disable-line react-hooks/exhaustive-deps
+
+  const s3 = config.s3 ?? EMPTY_S3;
+  const webdav = config.webdav ?? EMPTY_WEBDAV;
+  const localFolder = config.localFolder ?? EMPTY_LOCAL_FOLDER;
+  const driveFolder = currentSession?.googleDriveMetadata;
+
+  const handleApply = async () => {
+    if (!workingFolder || !currentSession) return;
+    setIsSaving(true);
+    try {
+      if (backend === 'googleDrive') {
+        if (!driveFolder?.folderId) {
+          showToast('Choose a Drive folder in QR Upload first', 'warning');
+          return;
+        }
+        await invoke('update_session_upload_target', {
+          folderPath: workingFolder,
+          sessionId: currentSession.id,
+          backend,
+          remoteLocation: driveFolder.folderId,
+          remoteName: driveFolder.folderName ?? null,
+          remoteLink: driveFolder.folderLink ?? null,
+          accountId: driveFolder.accountId ?? null,
+        });
+      } else {
+        await invoke('save_upload_targets_config', { config });
+        await invoke('update_session_upload_target', {
+          folderPath: workingFolder,
+          sessionId: currentSession.id,
+          backend,
+          remoteLocation,
+          remoteName: remoteLocation || null,
+          remoteLink: null,
+          accountId: null,
+        });
+      }
+      await loadSession(currentSession.id);
+      showToast('Upload target saved', 'success', 2000);
+    } catch (error) {
+      showToast('Failed to save upload target: ' + error, 'error');
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  const field = (label: string, value: string, onChange: (value: string) => void, type = 'text', placeholder?: string) => (
+    <>
+      <div className="setting-label-full" style={{ marginTop: '8px', marginBottom: '4px' }}>{label}</div>
+      <input
+        type={type}
+        className="property-input"
+        value={value}
+        placeholder={placeholder}
+        onChange={(e) => onChange(e.target.value)}
+        style={{ width: '100%' }}
+      />
+    </>
+  );
+
+  // Saved secrets aren't sent back; leaving the field empty keeps them
+  const secretPlaceholder = (saved?: boolean) => (saved ? 'Saved (leave empty to keep)' : undefined);
+
+  return (
+    <div className="collapsible-section">
+      <button
+        className="collapsible-header"
+        onClick={onToggle}
+      >
+        <div className="collapsible-header-left">
+          {expanded ? <ChevronDown size={12} /> : <ChevronRight size={12} />}
+          <span className="collapsible-title">Upload Target</span>
+        </div>
+      </button>
+      {expanded && (
+        <div className="collapsible-content">
+          {!currentSession ? (
+            <div className="setting-hint">Open a session to choose where its photos are uploaded</div>
+          ) : (
+            <>
+              <div className="setting-option-group">
+                {BACKENDS.map(({ value, label }) => (
+                  <button
+                    key={value}
+                    className={`setting-option-btn ${backend === value ? 'active' : ''}`}
+                    onClick={() => setBackend(value)}
+                  >
+                    {label}
+                  </button>
+                ))}
+              </div>
+
+              {backend === 'googleDrive' && (
+                <div className="setting-hint" style={{ marginTop: '8px' }}>
+                  {driveFolder?.folderName
+                    ? <>Uploads to the Drive folder <strong>{driveFolder.folderName}</strong></>
+                    : 'Choose a Drive folder in QR Upload'}
+                </div>
+              )}
+
+              {backend === 's3' && (
+                <>
+                  {field('Endpoint', s3.endpoint, (endpoint) => setConfig({ ...config, s3: { ...s3, endpoint } }))}
+                  {field('Region', s3.region, (region) => setConfig({ ...config, s3: { ...s3, region } }))}
+                  {field('Bucket', s3.bucket, (bucket) => setConfig({ ...config, s3: { ...s3, bucket } }))}
+                  {field('Access key ID', s3.accessKeyId, (accessKeyId) => setConfig({ ...config, s3: { ...s3, accessKeyId } }))}
+                  {field('Secret access key', s3.secretAccessKey, (secretAccessKey) => setConfig({ ...config, s3: { ...s3, secretAccessKey } }), 'password', secretPlaceholder(s3.hasSecretAccessKey))}
+                  {field('Public URL', s3.publicBaseUrl ?? '', (publicBaseUrl) => setConfig({ ...config, s3: { ...s3, publicBaseUrl: publicBaseUrl || null } }))}
+                  <div className="qr-upload-toggle-row" style={{ marginTop: '8px' }}>
+                    <div className="setting-label-full">Path-style addressing</div>
+                    <button
+                      className={`toggle-btn ${s3.pathStyle ? 'active' : ''}`}
+                      onClick={() => setConfig({ ...config, s3: { ...s3, pathStyle: !s3.pathStyle } })}
+                    >
+                      <span className="toggle-slider" />
+                    </button>
+                  </div>
+                </>
+              )}
+
+              {backend === 'webDav' && (
+                <>
+                  {field('Server URL', webdav.baseUrl, (baseUrl) => setConfig({ ...config, webdav: { ...webdav, baseUrl } }))}
+                  {field('Username', webdav.username, (username) => setConfig({ ...config, webdav: { ...webdav, username } }))}
+                  {field('Password', webdav.password, (password) => setConfig({ ...config, webdav: { ...webdav, password } }), 'password', secretPlaceholder(webdav.hasPassword))}
+                </>
+              )}
+
+              {backend === 'localFolder' && (
+                field('Root folder', localFolder.rootPath, (rootPath) => setConfig({ ...config, localFolder: { rootPath } }))
+              )}
+
+              {backend !== 'googleDrive' && (
+                <>
+                  {field('Session location', remoteLocation, setRemoteLocation)}
+                  <div className="setting-hint">
+                    Key prefix, collection or sub folder for this session's files
+                  </div>
+                </>
+              )}
+
+              <button
+                className="folder-browse-btn"
+                onClick={handleApply}
+                disabled={isSaving || !workingFolder}
+              >
+                {isSaving ? 'Saving...' : 'Use for this session'}
+              </button>
+            </>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
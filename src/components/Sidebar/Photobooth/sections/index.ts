@@ -1,7 +1,9 @@
 export { ConnectionInfoSection } from './ConnectionInfoSection';
 export { CustomSetsSection } from './CustomSetsSection';
 export { DisplayLayoutSection } from './DisplayLayoutSection';
+export { LocalGallerySection } from './LocalGallerySection';
 export { NamingSchemeSection } from './NamingSchemeSection';
 export { PhotoboothSettingsSection } from './PhotoboothSettingsSection';
 export { PrintSettingsSection } from './PrintSettingsSection';
+export { UploadTargetSection } from './UploadTargetSection';
 export { WorkingFolderSection } from './WorkingFolderSection';
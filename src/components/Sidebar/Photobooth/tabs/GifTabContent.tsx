@@ -12,7 +12,9 @@ import { useUploadQueue } from '../../../../contexts';
 import { useAuth } from '../../../../contexts';
 import { useToast } from '../../../../contexts';
 import { getDriveAuthState, areUploadsEnabled } from '../../../../utils/driveAuthState';
+import { canUploadTo, getUploadDestination } from '../../../../utils/uploadTarget';
 import { createLogger } from '../../../../utils/logger';
+import { recordCatalogMedia } from '../../../../utils/catalog';
 
 const logger = createLogger('GifTabContent');
 
@@ -184,7 +186,7 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
     let unlistenVideo: UnlistenFn | null = null;
 
     // Collect generated files for auto-upload
-    const generatedFiles: Array<{ filePath: string; fileName: string }> = [];
+    const generatedFiles: Array<{ filePath: string; fileName: string; kind: 'gif' | 'video' }> = [];
 
     try {
       const results: string[] = [];
@@ -222,7 +224,7 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
         const videoFileName = videoResult.file_path.split('/').pop() || videoResult.file_path.split('\\').pop() || 'slideshow.mp4';
         setLastGif({ filePath: gifResult.file_path, fileName: gifFileName, fileSize: gifResult.file_size, photoCount: imagePaths.length });
         setLastVideo({ filePath: videoResult.file_path, fileName: videoFileName, fileSize: videoResult.file_size, photoCount: imagePaths.length });
-        generatedFiles.push({ filePath: gifResult.file_path, fileName: gifFileName }, { filePath: videoResult.file_path, fileName: videoFileName });
+        generatedFiles.push({ filePath: gifResult.file_path, fileName: gifFileName, kind: 'gif' }, { filePath: videoResult.file_path, fileName: videoFileName, kind: 'video' });
       } else if (format === 'gif') {
         unlistenGif = await listen<{ current: number; total: number; stage: string }>(
           'gif-generation-progress',
@@ -239,7 +241,7 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
         results.push(`GIF: ${(gifResult.file_size / 1024).toFixed(0)} KB`);
         const gifFileName = gifResult.file_path.split('/').pop() || gifResult.file_path.split('\\').pop() || 'slideshow.gif';
         setLastGif({ filePath: gifResult.file_path, fileName: gifFileName, fileSize: gifResult.file_size, photoCount: imagePaths.length });
-        generatedFiles.push({ filePath: gifResult.file_path, fileName: gifFileName });
+        generatedFiles.push({ filePath: gifResult.file_path, fileName: gifFileName, kind: 'gif' });
       } else if (format === 'video') {
         unlistenVideo = await listen<{ current: number; total: number; stage: string }>(
           'video-generation-progress',
@@ -258,7 +260,13 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
         results.push(`MP4: ${(videoResult.file_size / 1024).toFixed(0)} KB`);
         const videoFileName = videoResult.file_path.split('/').pop() || videoResult.file_path.split('\\').pop() || 'slideshow.mp4';
         setLastVideo({ filePath: videoResult.file_path, fileName: videoFileName, fileSize: videoResult.file_size, photoCount: imagePaths.length });
-        generatedFiles.push({ filePath: videoResult.file_path, fileName: videoFileName });
+        generatedFiles.push({ filePath: videoResult.file_path, fileName: videoFileName, kind: 'video' });
+      }
+
+      if (workingFolder) {
+        for (const file of generatedFiles) {
+          await recordCatalogMedia(workingFolder, currentSession?.id ?? null, file.kind, file.filePath, imagePaths);
+        }
       }
 
       showToast('Generation complete!', 'success', 3000, results.join(' | '));
@@ -266,12 +274,12 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
 
       // Auto-upload generated files to Drive if requested
       if (shouldAutoUpload && generatedFiles.length > 0) {
-        const driveFolderId = currentSession?.googleDriveMetadata?.folderId;
-        if (currentSession?.id && driveFolderId && areUploadsEnabled(driveAuthState.state)) {
+        const destination = getUploadDestination(currentSession);
+        if (currentSession?.id && canUploadTo(destination, driveAuthState.state)) {
           try {
             const uploadItems = generatedFiles.map(f => ({ filename: f.fileName, localPath: f.filePath }));
-            await enqueuePhotos(currentSession.id, uploadItems, driveFolderId);
-            logger.debug('[GifTabContent] Auto-uploaded generated media to Drive');
+            await enqueuePhotos(currentSession.id, uploadItems, destination);
+            logger.debug('[GifTabContent] Auto-uploaded generated media to', destination.backend);
           } catch (uploadErr) {
             logger.error('[GifTabContent] Auto-upload failed:', uploadErr);
           }
@@ -350,20 +358,20 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
 
   // Upload media to Drive
   const handleUploadToDrive = useCallback(async (filePath: string, fileName: string) => {
-    const driveFolderId = currentSession?.googleDriveMetadata?.folderId;
-    logger.debug('[GifTabContent] Upload request:', { filePath, fileName, driveFolderId, sessionId: currentSession?.id });
+    const destination = getUploadDestination(currentSession);
+    logger.debug('[GifTabContent] Upload request:', { filePath, fileName, destination, sessionId: currentSession?.id });
 
     if (!currentSession?.id) {
       showToast('No active session', 'warning', 3000);
       return;
     }
-    if (!driveFolderId) {
-      showToast('No Drive folder', 'warning', 3000, 'Please create a Drive folder first');
+    if (!destination) {
+      showToast('No upload target', 'warning', 3000, 'Please create a Drive folder or choose an upload target first');
       return;
     }
 
-    // Check if uploads are enabled (matching account, logged in)
-    if (!areUploadsEnabled(driveAuthState.state)) {
+    // Drive uploads need the account that owns the folder
+    if (destination.backend === 'googleDrive' && !areUploadsEnabled(driveAuthState.state)) {
       if (driveAuthState.state === 'not_authenticated') {
         showToast('Not signed in', 'warning', 3000, 'Please login to Google Drive');
       } else if (driveAuthState.state === 'authenticated_mismatch') {
@@ -376,7 +384,7 @@ const [showFfmpegModal, setShowFfmpegModal] = useState(false);
       await enqueuePhotos(
         currentSession.id,
         [{ filename: fileName, localPath: filePath }],
-        driveFolderId
+        destination
       );
       showToast('Upload queued', 'success', 2000);
     } catch (err) {
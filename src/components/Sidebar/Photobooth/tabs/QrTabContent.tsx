@@ -13,6 +13,7 @@ import { usePhotobooth } from '../../../../contexts';
 import { useToast } from '../../../../contexts';
 import { useCollageUpload } from '../../../../hooks/useCollageUpload';
 import { getDriveAuthState, getAuthStateText, DriveAuthState, areUploadsEnabled } from '../../../../utils/driveAuthState';
+import { getSessionShareLink, type SessionShareLink } from '../../../../utils/sessionShareLink';
 import { createLogger } from '../../../../utils/logger';
 
 const logger = createLogger('QrTabContent');
@@ -28,11 +29,27 @@ export function QrTabContent() {
   const [copied, setCopied] = useState(false);
   const [showQr, setShowQr] = useState(false);
   const [qrBase64, setQrBase64] = useState<string | null>(null);
+  const [shareLink, setShareLink] = useState<SessionShareLink | null>(null);
   const [showRegenerateOptions, setShowRegenerateOptions] = useState(false);
 
   const driveMetadata = currentSession?.googleDriveMetadata;
   const folderLink = driveMetadata?.folderLink || '';
 
+  // QR of the session's local gallery page while the gallery runs, else of its Drive folder
+  const loadQr = useCallback(async (): Promise<string | null> => {
+    if (!currentSession) return null;
+    if (qrBase64) return qrBase64;
+    try {
+      const link = await getSessionShareLink(currentSession.id, folderLink);
+      setShareLink(link);
+      setQrBase64(link?.qrData ?? null);
+      return link?.qrData ?? null;
+    } catch (err) {
+      logger.error('[QrTabContent] Failed to generate QR:', err);
+      return null;
+    }
+  }, [currentSession, folderLink, qrBase64]);
+
   // Load the selected display layout for the guest display at finalize time
   const getSelectedDisplayLayout = useCallback(async (): Promise<DisplayLayout | null> => {
     if (!selectedDisplayLayoutId) return null;
@@ -61,6 +78,7 @@ export function QrTabContent() {
   useEffect(() => {
     setShowQr(false);
     setQrBase64(null);
+    setShareLink(null);
   }, [currentSession?.id]);
 
   // Get upload items for current session
@@ -133,8 +151,9 @@ export function QrTabContent() {
   };
 
   const handleOpenLink = () => {
-    if (folderLink) {
-      shellOpen(folderLink);
+    const link = shareLink?.link || folderLink;
+    if (link) {
+      shellOpen(link);
     }
   };
 
@@ -143,14 +162,7 @@ export function QrTabContent() {
       setShowQr(false);
       return;
     }
-    if (!qrBase64 && folderLink) {
-      try {
-        const data = await invoke<string>('generate_qr_code', { url: folderLink });
-        setQrBase64(data);
-      } catch (err) {
-        logger.error('[QrTabContent] Failed to generate QR:', err);
-      }
-    }
+    await loadQr();
     setShowQr(true);
   };
 
@@ -183,16 +195,7 @@ export function QrTabContent() {
     }
 
     // Ensure QR code is generated BEFORE proceeding
-    let qrDataToSend = qrBase64;
-    if (!qrDataToSend && folderLink) {
-      try {
-        const data = await invoke<string>('generate_qr_code', { url: folderLink });
-        qrDataToSend = data;
-        setQrBase64(data);
-      } catch (err) {
-        logger.error('[QrTabContent] Failed to generate QR:', err);
-      }
-    }
+    const qrDataToSend = await loadQr();
 
     // Proceed with normal upload flow
     const displayLayout = await getSelectedDisplayLayout();
@@ -204,21 +207,12 @@ export function QrTabContent() {
         displayLayout,
       });
     });
-  }, [currentSession, workingFolder, sessions, driveMetadata, currentCollageFilename, collageIsDirty, authStateInfo.state, authStateText, qrUploadEnabled, uploadCollage, showToast, showRegenerateOptions, folderLink, qrBase64, getSelectedDisplayLayout]);
+  }, [currentSession, workingFolder, sessions, driveMetadata, currentCollageFilename, collageIsDirty, authStateInfo.state, authStateText, qrUploadEnabled, uploadCollage, showToast, showRegenerateOptions, loadQr, getSelectedDisplayLayout]);
 
   const confirmRegenerate = useCallback(async () => {
     setShowRegenerateOptions(false);
     if (currentSession && workingFolder && driveMetadata) {
-      let qrDataToSend = qrBase64;
-      if (!qrDataToSend && folderLink) {
-        try {
-          const data = await invoke<string>('generate_qr_code', { url: folderLink });
-          qrDataToSend = data;
-          setQrBase64(data);
-        } catch (err) {
-          logger.error('[QrTabContent] Failed to generate QR:', err);
-        }
-      }
+      const qrDataToSend = await loadQr();
 
       const displayLayout = await getSelectedDisplayLayout();
       await uploadCollage(currentSession, workingFolder, sessions, driveMetadata, async (_, imageUrl) => {
@@ -230,21 +224,12 @@ export function QrTabContent() {
         });
       });
     }
-  }, [currentSession, workingFolder, sessions, driveMetadata, uploadCollage, folderLink, qrBase64, getSelectedDisplayLayout]);
+  }, [currentSession, workingFolder, sessions, driveMetadata, uploadCollage, loadQr, getSelectedDisplayLayout]);
 
   const cancelRegenerate = useCallback(async () => {
     setShowRegenerateOptions(false);
     if (currentSession && workingFolder && driveMetadata) {
-      let qrDataToSend = qrBase64;
-      if (!qrDataToSend && folderLink) {
-        try {
-          const data = await invoke<string>('generate_qr_code', { url: folderLink });
-          qrDataToSend = data;
-          setQrBase64(data);
-        } catch (err) {
-          logger.error('[QrTabContent] Failed to generate QR:', err);
-        }
-      }
+      const qrDataToSend = await loadQr();
 
       const displayLayout = await getSelectedDisplayLayout();
       await uploadCollage(currentSession, workingFolder, sessions, driveMetadata, async (_, imageUrl) => {
@@ -256,7 +241,7 @@ export function QrTabContent() {
         });
       });
     }
-  }, [currentSession, workingFolder, sessions, driveMetadata, uploadCollage, folderLink, qrBase64, getSelectedDisplayLayout]);
+  }, [currentSession, workingFolder, sessions, driveMetadata, uploadCollage, loadQr, getSelectedDisplayLayout]);
 
   // Auto-refresh upload queue for current session
   useEffect(() => {
@@ -375,11 +360,11 @@ export function QrTabContent() {
             <button
               className="qr-action-btn secondary"
               onClick={handleOpenLink}
-              title="Open in Google Drive"
+              title={shareLink?.source === 'localGallery' ? 'Open the local gallery page' : 'Open in Google Drive'}
               style={{ alignSelf: 'stretch', justifyContent: 'center' }}
             >
               <ExternalLink size={12} />
-              <span>Open in Drive</span>
+              <span>{shareLink?.source === 'localGallery' ? 'Open Gallery Page' : 'Open in Drive'}</span>
             </button>
           </div>
         )}
@@ -9,6 +9,7 @@ import { UploadQueueStatus } from "./UploadQueueStatus";
 import { UploadStatus } from "../../../types/uploadQueue";
 import CameraWebSocketManager from "../../../services/cameraWebSocket";
 import { createLogger } from '../../../utils/logger';
+import { getSessionShareLink, type SessionShareLink } from '../../../utils/sessionShareLink';
 import "./PhotoSessionsSidebar.css";
 import "../../../styles/Modal.css";
 import "../../../styles/Buttons.css";
@@ -82,7 +83,7 @@ export default function PhotoSessionsSidebar({
   const [loadingExif, setLoadingExif] = useState(false);
   const [deletingPhoto, setDeletingPhoto] = useState<string | null>(null);
   const [qrPopupSessionId, setQrPopupSessionId] = useState<string | null>(null);
-  const [sessionQrCache, setSessionQrCache] = useState<Map<string, string>>(new Map());
+  const [sessionQrCache, setSessionQrCache] = useState<Map<string, SessionShareLink>>(new Map());
   const [generatingQrForId, setGeneratingQrForId] = useState<string | null>(null);
   const contextMenuRef = useRef<HTMLDivElement>(null);
 
@@ -154,17 +155,17 @@ export default function PhotoSessionsSidebar({
     if (!folderLink) return;
 
     setQrPopupSessionId(set.id);
-    if (!sessionQrCache.has(set.id)) {
-      setGeneratingQrForId(set.id);
-      try {
-        const data = await invoke<string>('generate_qr_code', { url: folderLink });
-        setSessionQrCache(prev => new Map(prev).set(set.id, data));
-      } catch (err) {
-        logger.error('[PhotoSessionsSidebar] Failed to generate QR:', err);
-        setQrPopupSessionId(null);
-      } finally {
-        setGeneratingQrForId(null);
-      }
+    // Not cached: the link changes when the local gallery starts or stops
+    setGeneratingQrForId(set.id);
+    try {
+      const shareLink = await getSessionShareLink(set.id, folderLink);
+      if (!shareLink) throw new Error('No link for this session');
+      setSessionQrCache(prev => new Map(prev).set(set.id, shareLink));
+    } catch (err) {
+      logger.error('[PhotoSessionsSidebar] Failed to generate QR:', err);
+      setQrPopupSessionId(null);
+    } finally {
+      setGeneratingQrForId(null);
     }
   };
 
@@ -688,12 +689,14 @@ export default function PhotoSessionsSidebar({
                               <div className="session-qr-panel">
                                 <div className="session-qr-image-wrap">
                                   <img
-                                    src={`data:image/png;base64,${sessionQrCache.get(set.id)}`}
+                                    src={`data:image/png;base64,${sessionQrCache.get(set.id)?.qrData}`}
                                     alt="QR Code"
                                     className="session-qr-image"
                                   />
                                 </div>
-                                <p className="session-qr-label">Scan to open Drive folder</p>
+                                <p className="session-qr-label">
+                                  {sessionQrCache.get(set.id)?.source === 'localGallery' ? 'Scan to open the gallery' : 'Scan to open Drive folder'}
+                                </p>
                               </div>
                             )}
                           </div>
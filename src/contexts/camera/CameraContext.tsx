@@ -1,10 +1,11 @@
 import { createContext, useContext, useEffect, useState, useRef, useCallback, type ReactNode } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
-import CameraWebSocketManager, { type CameraStatus, type CaptureErrorEvent, type PhotoDownloadedEvent } from '../../services/cameraWebSocket';
+import CameraWebSocketManager, { type CameraStatus, type CaptureErrorEvent, type GroupCapturedEvent, type PhotoDownloadedEvent, type RawAttachedEvent } from '../../services/cameraWebSocket';
 import type { ConnectionState } from '../../types/connection';
 import { getConnectionStateText } from '../../types/connection';
 import { createLogger } from '../../utils/logger';
+import { daemonFetch } from '../../services/daemonAuth';
 
 
 const logger = createLogger('CameraContext');
@@ -54,6 +55,14 @@ interface CameraContextType {
   addPhotoDownloadedListener: (cb: (event: PhotoDownloadedEvent) => void) => void;
   /** Unregister a photo_downloaded callback */
   removePhotoDownloadedListener: (cb: (event: PhotoDownloadedEvent) => void) => void;
+  /** Register a callback for group_captured events (multi-camera group capture) */
+  addGroupCapturedListener: (cb: (event: GroupCapturedEvent) => void) => void;
+  /** Unregister a group_captured callback */
+  removeGroupCapturedListener: (cb: (event: GroupCapturedEvent) => void) => void;
+  /** Register a callback for raw_attached events (RAW that arrived after its JPEG) */
+  addRawAttachedListener: (cb: (event: RawAttachedEvent) => void) => void;
+  /** Unregister a raw_attached callback */
+  removeRawAttachedListener: (cb: (event: RawAttachedEvent) => void) => void;
   /** Camera serial number (from camera_connected event) */
   serialNumber: string | null;
   /** Camera firmware version (from camera_connected event) */
@@ -114,6 +123,10 @@ export function CameraProvider({ children }: { children: ReactNode }) {
   const statusListenersRef = useRef<Set<(status: CameraStatus) => void>>(new Set());
   // External photo_downloaded listeners (for PhotoboothWorkspace photo handling)
   const photoDownloadedListenersRef = useRef<Set<(event: PhotoDownloadedEvent) => void>>(new Set());
+  // External group_captured listeners (for PhotoboothWorkspace group photo handling)
+  const groupCapturedListenersRef = useRef<Set<(event: GroupCapturedEvent) => void>>(new Set());
+  // External raw_attached listeners (for PhotoboothWorkspace RAW+JPEG handling)
+  const rawAttachedListenersRef = useRef<Set<(event: RawAttachedEvent) => void>>(new Set());
   // Track previous connection state to detect actual changes
   const wasCameraConnectedRef = useRef(false);
   // Count consecutive empty status messages (to avoid false disconnects during capture)
@@ -138,6 +151,22 @@ export function CameraProvider({ children }: { children: ReactNode }) {
     photoDownloadedListenersRef.current.delete(cb);
   }, []);
 
+  const addGroupCapturedListener = useCallback((cb: (event: GroupCapturedEvent) => void) => {
+    groupCapturedListenersRef.current.add(cb);
+  }, []);
+
+  const removeGroupCapturedListener = useCallback((cb: (event: GroupCapturedEvent) => void) => {
+    groupCapturedListenersRef.current.delete(cb);
+  }, []);
+
+  const addRawAttachedListener = useCallback((cb: (event: RawAttachedEvent) => void) => {
+    rawAttachedListenersRef.current.add(cb);
+  }, []);
+
+  const removeRawAttachedListener = useCallback((cb: (event: RawAttachedEvent) => void) => {
+    rawAttachedListenersRef.current.delete(cb);
+  }, []);
+
   const clearCaptureError = useCallback(() => {
     setCaptureError(null);
   }, []);
@@ -171,10 +200,10 @@ export function CameraProvider({ children }: { children: ReactNode }) {
     updateConnectionState('Connecting');
     CameraWebSocketManager.getInstance().connect();
 
-    // Re-register the camera with the daemon so it resumes polling
+    // Resume polling the camera's controller (the daemon keeps it on the right camera)
     const cameraId = selectedCameraIdRef.current;
     if (cameraId) {
-      fetch(`http://localhost:58321/api/controller/switch?camera=${cameraId}`, { method: 'POST' })
+      daemonFetch(`http://localhost:58321/api/controller/resume-polling?camera=${encodeURIComponent(cameraId)}`, { method: 'POST' })
         .catch((err) => logger.warn('[CameraContext] Failed to re-register camera:', err));
     }
   }, [updateConnectionState]);
@@ -194,7 +223,7 @@ export function CameraProvider({ children }: { children: ReactNode }) {
       clearTimeout(downloadTimeoutRef.current);
       downloadTimeoutRef.current = null;
     }
-    fetch('http://localhost:58321/api/controller/disconnect', { method: 'POST' })
+    daemonFetch('http://localhost:58321/api/controller/disconnect', { method: 'POST' })
       .catch(err => logger.warn('[CameraContext] Failed to send disconnect to controller:', err));
     CameraWebSocketManager.getInstance().disconnect();
   }, []);
@@ -202,7 +231,7 @@ export function CameraProvider({ children }: { children: ReactNode }) {
   // Pause camera polling
   const pausePolling = useCallback(async () => {
     try {
-      const response = await fetch('http://localhost:58321/api/controller/pause-polling', { method: 'POST' });
+      const response = await daemonFetch('http://localhost:58321/api/controller/pause-polling', { method: 'POST' });
       if (!response.ok) {
         logger.warn('[CameraContext] Failed to pause polling');
       }
@@ -214,7 +243,7 @@ export function CameraProvider({ children }: { children: ReactNode }) {
   // Resume camera polling
   const resumePolling = useCallback(async () => {
     try {
-      const response = await fetch('http://localhost:58321/api/controller/resume-polling', { method: 'POST' });
+      const response = await daemonFetch('http://localhost:58321/api/controller/resume-polling', { method: 'POST' });
       if (!response.ok) {
         logger.warn('[CameraContext] Failed to resume polling');
       }
@@ -329,6 +358,20 @@ export function CameraProvider({ children }: { children: ReactNode }) {
       });
     };
 
+    const handleGroupCaptured = (data: GroupCapturedEvent) => {
+      logger.debug('[CameraContext] group_captured event:', data.group_id, 'max skew', data.max_skew_ms);
+      groupCapturedListenersRef.current.forEach(cb => {
+        try { cb(data); } catch (e) { logger.error('[CameraContext] group_captured listener error:', e); }
+      });
+    };
+
+    const handleRawAttached = (data: RawAttachedEvent) => {
+      logger.debug('[CameraContext] raw_attached event:', data.raw_path, '->', data.file_path);
+      rawAttachedListenersRef.current.forEach(cb => {
+        try { cb(data); } catch (e) { logger.error('[CameraContext] raw_attached listener error:', e); }
+      });
+    };
+
     const handleCameraConnecting = (_data: { type: string; camera_id: string }) => {
       setIsConnecting(true);
     };
@@ -363,6 +406,8 @@ export function CameraProvider({ children }: { children: ReactNode }) {
     manager.on('capture_error', handleCaptureError);
     manager.on('camera_disconnected', handleCameraDisconnected);
     manager.on('photo_downloaded', handlePhotoDownloaded);
+    manager.on('group_captured', handleGroupCaptured);
+    manager.on('raw_attached', handleRawAttached);
     manager.on('camera_connecting', handleCameraConnecting);
     manager.on('camera_connect_failed', handleCameraConnectFailed);
     manager.on('camera_connected', handleCameraConnected);
@@ -379,6 +424,8 @@ export function CameraProvider({ children }: { children: ReactNode }) {
       manager.off('capture_error', handleCaptureError);
       manager.off('camera_disconnected', handleCameraDisconnected);
       manager.off('photo_downloaded', handlePhotoDownloaded);
+      manager.off('group_captured', handleGroupCaptured);
+      manager.off('raw_attached', handleRawAttached);
       manager.off('camera_connecting', handleCameraConnecting);
       manager.off('camera_connect_failed', handleCameraConnectFailed);
       manager.off('camera_connected', handleCameraConnected);
@@ -441,6 +488,10 @@ export function CameraProvider({ children }: { children: ReactNode }) {
       removeStatusListener,
       addPhotoDownloadedListener,
       removePhotoDownloadedListener,
+      addGroupCapturedListener,
+      removeGroupCapturedListener,
+      addRawAttachedListener,
+      removeRawAttachedListener,
       setCameraHttpConnected,
       serialNumber,
       firmware,
@@ -4,6 +4,8 @@ import { Frame, FrameZone } from '../../types/frame';
 import { OverlayLayer } from '../../types/overlay';
 import { PlacedImage, ImageTransform } from '../../types/collage';
 import { DEFAULT_TRANSFORM } from '../../types/collage';
+import { Background } from '../../types/background';
+import { CustomSet } from '../../types/customSet';
 import { applyZoneClipPath } from '../../utils/canvasShapeClip';
 import { useAssetLibrary } from '../system/AssetLibraryContext';
 import { createLogger } from '../../utils/logger';
@@ -69,6 +71,8 @@ interface PhotoboothContextType {
 
   // Export function
   exportPhotoboothCanvasAsPNG: (targetMp?: number) => Promise<{ bytes: Uint8Array; filename: string } | null>;
+  // Render the collage natively at full canvas resolution and save it to outputPath (PNG or JPEG by extension)
+  renderPhotoboothCollage: (outputPath: string) => Promise<boolean>;
 
   // Current collage filename — shared between print and upload; reset on finalize exit
   currentCollageFilename: string | null;
@@ -466,6 +470,65 @@ export function PhotoboothProvider({ children }: { children: ReactNode }) {
     }
   }, [photoboothFrame, photoboothCanvasSize, photoboothAutoMatchBackground, photoboothBackgroundDimensions, photoboothBackground, photoboothBackgroundTransform, photoboothOverlays, placedImages, loadImageAsBitmap]);
 
+  const renderPhotoboothCollage = useCallback(async (outputPath: string): Promise<boolean> => {
+    const frame = photoboothFrame;
+    if (!frame || !photoboothBackground || placedImages.size === 0) {
+      logger.warn('[render] Frame, background or placed images missing, skipping render');
+      return false;
+    }
+
+    const width = photoboothAutoMatchBackground && photoboothBackgroundDimensions
+      ? photoboothBackgroundDimensions.width
+      : photoboothCanvasSize?.width ?? frame.width;
+    const height = photoboothAutoMatchBackground && photoboothBackgroundDimensions
+      ? photoboothBackgroundDimensions.height
+      : photoboothCanvasSize?.height ?? frame.height;
+
+    const now = new Date().toISOString();
+    const background: Background = {
+      id: 'photobooth-render',
+      name: 'Photobooth background',
+      description: '',
+      background_type: photoboothBackground.startsWith('#') || photoboothBackground.startsWith('rgb') ? 'color' :
+                       photoboothBackground.startsWith('linear-gradient') ? 'gradient' : 'image',
+      value: photoboothBackground.replace('asset://', ''),
+      is_default: false,
+      created_at: now,
+    };
+    const customSet: CustomSet = {
+      id: '',
+      name: 'Photobooth collage',
+      description: '',
+      canvasSize: { width: Math.round(width), height: Math.round(height), name: photoboothCanvasSize?.name ?? frame.name },
+      autoMatchBackground: photoboothAutoMatchBackground,
+      background,
+      backgroundTransform: photoboothBackgroundTransform,
+      frame,
+      overlays: photoboothOverlays,
+      createdAt: now,
+      modifiedAt: now,
+      isDefault: false,
+    };
+    const photos = frame.zones
+      .map(zone => ({ zone, placed: placedImages.get(zone.id) }))
+      .filter(({ placed }) => placed)
+      .map(({ zone, placed }) => ({
+        path: placed!.sourceFile.replace('asset://', ''),
+        zoneId: zone.id,
+        transform: placed!.transform,
+      }));
+
+    try {
+      const startTime = performance.now();
+      await invoke('render_collage', { customSet, photos, outputPath });
+      logger.debug(`[render] ${outputPath} in ${(performance.now() - startTime).toFixed(0)}ms`);
+      return true;
+    } catch (error) {
+      logger.error('Failed to render photobooth collage:', error);
+      return false;
+    }
+  }, [photoboothFrame, photoboothCanvasSize, photoboothAutoMatchBackground, photoboothBackgroundDimensions, photoboothBackground, photoboothBackgroundTransform, photoboothOverlays, placedImages]);
+
   return (
     <PhotoboothContext.Provider
       value={{
@@ -498,6 +561,7 @@ export function PhotoboothProvider({ children }: { children: ReactNode }) {
         isGeneratingCollage,
         setIsGeneratingCollage,
         exportPhotoboothCanvasAsPNG,
+        renderPhotoboothCollage,
         currentCollageFilename,
         setCurrentCollageFilename,
       }}
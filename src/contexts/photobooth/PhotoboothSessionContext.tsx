@@ -296,6 +296,7 @@ export function PhotoboothSessionProvider({ children }: { children: ReactNode })
           shotCount: ptbSession.shotCount,
           photos: ptbSession.photos,
           googleDriveMetadata: ptbSession.googleDriveMetadata || { uploadedImages: [] },
+          uploadTarget: ptbSession.uploadTarget,
         });
 
         showToast('New workspace created', 'success', 3000, 'Session 1 ready');
@@ -332,6 +333,7 @@ export function PhotoboothSessionProvider({ children }: { children: ReactNode })
           shotCount: ptbSession.shotCount,
           photos: ptbSession.photos,
           googleDriveMetadata: ptbSession.googleDriveMetadata || { uploadedImages: [] },
+          uploadTarget: ptbSession.uploadTarget,
         });
       }
     } catch (error) {
@@ -404,6 +406,7 @@ export function PhotoboothSessionProvider({ children }: { children: ReactNode })
         shotCount: ptbSession.shotCount,
         photos: ptbSession.photos,
         googleDriveMetadata: ptbSession.googleDriveMetadata || { uploadedImages: [] },
+        uploadTarget: ptbSession.uploadTarget,
       });
 
       // Also set as current session in workspace
@@ -681,6 +684,7 @@ export function PhotoboothSessionProvider({ children }: { children: ReactNode })
           lastUsedAt: updatedSession.lastUsedAt,
           photos: updatedSession.photos,
           googleDriveMetadata: updatedSession.googleDriveMetadata || { uploadedImages: [] },
+          uploadTarget: updatedSession.uploadTarget,
         } : null);
       }
 
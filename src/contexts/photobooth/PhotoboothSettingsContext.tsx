@@ -1,4 +1,5 @@
 import { ReactNode } from "react";
+import type { SessionUploadTarget } from "../../types/uploadTargets";
 import { CaptureTimingProvider, useCaptureTiming } from "./PhotoboothCaptureSettingsContext";
 import { WorkspaceSettingsProvider, useWorkspaceSettings } from "./PhotoboothWorkspaceSettingsContext";
 import { PhotoboothSessionProvider, usePhotoboothSession } from "./PhotoboothSessionContext";
@@ -39,6 +40,7 @@ export interface PhotoboothSessionInfo {
   lastUsedAt: string;
   thumbnails: string[]; // Thumbnail URLs for the session's photos
   googleDriveMetadata: GoogleDriveMetadata;
+  uploadTarget?: SessionUploadTarget;
   qrUploadEnabled?: boolean;
   qrUploadAllImages?: boolean;
   photoNamingScheme?: string;
@@ -61,6 +63,7 @@ export interface PhotoboothSession {
   shotCount: number;
   photos: SessionPhoto[];
   googleDriveMetadata: GoogleDriveMetadata;
+  uploadTarget?: SessionUploadTarget;
   qrUploadEnabled?: boolean;
   qrUploadAllImages?: boolean;
   photoNamingScheme?: string;
@@ -6,6 +6,7 @@ import { usePhotoboothSession } from './PhotoboothSessionContext';
 import { useCollage } from "../collage";
 import { usePhotobooth } from './PhotoboothContext';
 import { createLogger } from '../../utils/logger';
+import { recordCatalogMedia, recordPrintJob, updatePrintJobStatus } from '../../utils/catalog';
 import * as fs from '@tauri-apps/plugin-fs';
 
 const logger = createLogger('PrintSettings');
@@ -145,6 +146,8 @@ export function PrintSettingsProvider({ children }: { children: ReactNode }) {
 
     const folder = sessions.find((s: any) => s.id === currentSession.id)?.folderName || currentSession.id;
     let filename: string;
+    // Collage files written for this print, recorded in the catalog below
+    const savedCollages: string[] = [];
 
     const isDoublePage = doublePageModeRef.current;
 
@@ -164,6 +167,7 @@ export function PrintSettingsProvider({ children }: { children: ReactNode }) {
           filename = `Collage_${randomId()}_2x.png`;
 
           await saveFileDirect(workingFolder, folder, filename, printBytes);
+          savedCollages.push(filename);
 
           setCurrentDoubleCollageFilename(filename);
           cachedDoubleBytesRef.current = printBytes;
@@ -178,6 +182,7 @@ export function PrintSettingsProvider({ children }: { children: ReactNode }) {
           filename = `Collage_${randomId()}_2x.png`;
 
           await saveFileDirect(workingFolder, folder, filename, printBytes);
+          savedCollages.push(filename);
           setCurrentDoubleCollageFilename(filename);
           cachedDoubleBytesRef.current = printBytes;
         }
@@ -214,6 +219,7 @@ export function PrintSettingsProvider({ children }: { children: ReactNode }) {
       // Save the single-page version as the canonical cached file
       const saveStart = performance.now();
       await saveFileDirect(workingFolder, folder, filename, exportResult.bytes);
+      savedCollages.push(filename);
 
       logger.debug(`[performPrint] File save (${(exportResult.bytes.length / 1024 / 1024).toFixed(1)}MB): ${(performance.now() - saveStart).toFixed(0)}ms`);
 
@@ -226,6 +232,7 @@ export function PrintSettingsProvider({ children }: { children: ReactNode }) {
 
         const doubledFilename = `Collage_${randomId()}_2x.png`;
         await saveFileDirect(workingFolder, folder, doubledFilename, printBytes);
+        savedCollages.push(doubledFilename);
 
         setCurrentDoubleCollageFilename(doubledFilename);
         cachedDoubleBytesRef.current = printBytes;
@@ -235,15 +242,22 @@ export function PrintSettingsProvider({ children }: { children: ReactNode }) {
       showToast('Saved to session folder', 'success', 2000, `Saved as ${filename}`);
     }
 
+    for (const saved of savedCollages) {
+      await recordCatalogMedia(workingFolder, currentSession.id, 'collage', `${workingFolder}\\${folder}\\${saved}`);
+    }
+
     // Open Windows Photo Printing Wizard
     const fullPath = `${workingFolder}\\${folder}\\${filename}`;
+    const printJobId = await recordPrintJob(workingFolder, currentSession.id, fullPath);
     showToast('Opening Windows print dialog...', 'success', 2000);
     const dialogStart = performance.now();
     try {
       await invoke('print_image_with_windows_dialog', { filePath: fullPath });
+      await updatePrintJobStatus(workingFolder, printJobId, 'printed');
     } catch (error) {
       logger.error('Failed to open Windows print dialog:', error);
       showToast('Print dialog failed', 'error', 5000, String(error));
+      await updatePrintJobStatus(workingFolder, printJobId, 'failed', String(error));
     }
 
     setIsPrinting(false);
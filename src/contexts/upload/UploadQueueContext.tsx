@@ -2,6 +2,7 @@ import { createContext, useContext, useState, useCallback, useEffect, ReactNode,
 import { invoke } from '@tauri-apps/api/core';
 import { useToast } from "../system";
 import type { UploadQueueItem, UploadQueueStats, UploadStatus as UploadStatusType } from '../../types/uploadQueue';
+import type { UploadDestination } from '../../types/uploadTargets';
 import { createLogger } from '../../utils/logger';
 
 const logger = createLogger('UploadQueue');
@@ -23,7 +24,7 @@ interface UploadQueueContextType {
   stats: UploadQueueStats;
 
   // Operations
-  enqueuePhotos: (sessionId: string, photos: Array<{ filename: string; localPath: string }>, driveFolderId: string) => Promise<void>;
+  enqueuePhotos: (sessionId: string, photos: Array<{ filename: string; localPath: string }>, destination: UploadDestination) => Promise<void>;
   getSessionQueue: (sessionId: string) => Promise<void>;
   retryUpload: (itemId: string) => Promise<void>;
   cancelUpload: (itemId: string) => Promise<void>;
@@ -57,7 +58,7 @@ export function UploadQueueProvider({ children }: { children: ReactNode }) {
   const enqueuePhotos = useCallback(async (
     sessionId: string,
     photos: Array<{ filename: string; localPath: string }>,
-    driveFolderId: string
+    destination: UploadDestination
   ) => {
     try {
       const items = photos.map((photo, index) => ({
@@ -65,7 +66,8 @@ export function UploadQueueProvider({ children }: { children: ReactNode }) {
         sessionId,
         filename: photo.filename,
         localPath: photo.localPath,
-        driveFolderId,
+        backend: destination.backend,
+        remoteLocation: destination.remoteLocation,
         status: UploadStatus.PENDING,
         progress: 0,
         retryCount: 0,
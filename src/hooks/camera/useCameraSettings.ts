@@ -3,6 +3,7 @@ import { useCamera } from '../../contexts/camera/CameraContext';
 import { getCameraSettingsService } from '../../services/cameraSettingsService';
 import type { CameraStatus } from '../../services/cameraWebSocket';
 import { createLogger } from '../../utils/logger';
+import { daemonFetch } from '../../services/daemonAuth';
 const logger = createLogger('useCameraSettings');
 
 const API_BASE = 'http://localhost:58321';
@@ -89,7 +90,7 @@ export function useCameraSettings(propsShutterSpeeds?: string[]) {
   const sendCameraSetting = async (setting: string, value: string) => {
     try {
       logger.debug(`[API] Setting ${setting} to ${value}`);
-      const response = await fetch(`${API_BASE}/api/camera/config`, {
+      const response = await daemonFetch(`${API_BASE}/api/camera/config`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ setting, value })
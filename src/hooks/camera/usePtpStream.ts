@@ -2,6 +2,7 @@ import { useState, useCallback, useRef, useEffect } from 'react';
 import { emit } from '@tauri-apps/api/event';
 import { useMjpegStream } from './useMjpegStream';
 import { createLogger } from '../../utils/logger';
+import { daemonFetch, withDaemonToken } from '../../services/daemonAuth';
 const logger = createLogger('usePtpStream');
 
 const DAEMON_URL = 'http://localhost:58321';
@@ -92,7 +93,7 @@ export function usePtpStream(): PtpStreamState {
       const reconnectTimer = setTimeout(() => {
         if (streamingRef.current && !error) {
           logger.debug('Reconnecting to stream...');
-          setStreamUrl(`${DAEMON_URL}/api/liveview/ptp-stream?t=${Date.now()}`);
+          setStreamUrl(withDaemonToken(`${DAEMON_URL}/api/liveview/ptp-stream?t=${Date.now()}`));
         } else {
           logger.debug('Reconnect cancelled (stopped or error)');
         }
@@ -112,7 +113,7 @@ export function usePtpStream(): PtpStreamState {
     frameCountRef.current = 0;
 
     try {
-      const response = await fetch(`${DAEMON_URL}/api/liveview/ptp-stream/start`, {
+      const response = await daemonFetch(`${DAEMON_URL}/api/liveview/ptp-stream/start`, {
         method: 'POST',
       });
 
@@ -125,7 +126,7 @@ export function usePtpStream(): PtpStreamState {
         throw new Error(data.error || 'Failed to start PTP streaming');
       }
 
-      setStreamUrl(`${DAEMON_URL}/api/liveview/ptp-stream?t=${Date.now()}`);
+      setStreamUrl(withDaemonToken(`${DAEMON_URL}/api/liveview/ptp-stream?t=${Date.now()}`));
     } catch (err) {
       const msg = err instanceof Error ? err.message : String(err);
       logger.error('Failed to start streaming:', msg);
@@ -147,7 +148,7 @@ export function usePtpStream(): PtpStreamState {
     setFrameUrl(null);
 
     try {
-      const response = await fetch(`${DAEMON_URL}/api/liveview/ptp-stream/stop`, {
+      const response = await daemonFetch(`${DAEMON_URL}/api/liveview/ptp-stream/stop`, {
         method: 'POST',
       });
 
@@ -171,7 +172,7 @@ export function usePtpStream(): PtpStreamState {
         streamingRef.current = false;
         setStreamUrl(null);
 
-        fetch(`${DAEMON_URL}/api/liveview/ptp-stream/stop`, { method: 'POST' })
+        daemonFetch(`${DAEMON_URL}/api/liveview/ptp-stream/stop`, { method: 'POST' })
           .catch((err) => logger.warn('Cleanup error stopping stream:', err));
       }
     };
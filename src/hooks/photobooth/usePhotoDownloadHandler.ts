@@ -1,36 +1,34 @@
 import { useEffect, useCallback, useRef } from 'react';
 import { invoke, convertFileSrc } from '@tauri-apps/api/core';
-import { type PhotoDownloadedEvent } from '../../services/cameraWebSocket';
+import { type GroupCapturedEvent, type PhotoDownloadedEvent, type RawAttachedEvent } from '../../services/cameraWebSocket';
 import type { PhotoboothSession, PhotoboothSessionInfo } from '../../contexts/photobooth/PhotoboothSettingsContext';
 import type { CurrentSetPhoto, PtbSession, DisplayMode } from '../../components/PhotoboothView/photoboothWorkspaceTypes';
 import { createLogger } from '../../utils/logger';
+import { getUploadDestination } from '../../utils/uploadTarget';
+import type { UploadDestination } from '../../types/uploadTargets';
 import { useToast } from '../../contexts';
 
 const logger = createLogger('PhotoboothWorkspace');
 
 const DAEMON_URL = 'http://localhost:58321';
 
-// Check if file is a RAW image format
-function isRawFile(filename: string): boolean {
-  const lowerName = filename.toLowerCase();
-  const rawExtensions = [
-    '.cr2', '.cr3', // Canon
-    '.nef', '.nrw', // Nikon
-    '.arw', // Sony
-    '.dng', // Adobe
-    '.raf', // Fujifilm
-    '.orf', // Olympus
-    '.pef', // Pentax
-    '.rw2', // Panasonic
-    '.raw', '.rw', // Generic
-    '.sr2', '.srf', // Sony older
-    '.mrw', // Minolta
-    '.3fr', // Hasselblad
-    '.erf', // Epson
-    '.mos', // Leaf
-    '.iiq', // Phase One
-  ];
-  return rawExtensions.some(ext => lowerName.endsWith(ext));
+// Group capture context for a photo saved from a group_captured event
+interface GroupPhotoContext {
+  groupId: string;
+  triggerSkewMs: number | null;
+  maxSkewMs: number | null;
+  // Only the first photo of a group counts as the shot (sequence + display)
+  primary: boolean;
+  // Session the group is being saved into (set after the first photo)
+  sessionId?: string;
+}
+
+// Result of sweep_daemon_photos (photos left on the daemon while we were disconnected)
+interface DaemonSweepResult {
+  downloaded: string[];
+  alreadyStored: string[];
+  failed: string[];
+  session: PtbSession | null;
 }
 
 function getNextSessionNumber(sessions: PhotoboothSessionInfo[]): number {
@@ -57,7 +55,7 @@ interface UsePhotoDownloadHandlerParams {
   sequenceStartManualReview: () => void;
   updateCurrentSessionFromDownload: (ptbSession: PhotoboothSession, newPhotoFilename?: string) => void;
   loadSession: (sessionId: string) => Promise<void>;
-  enqueuePhotos: (sessionId: string, photos: Array<{ filename: string; localPath: string }>, driveFolderId: string) => Promise<void>;
+  enqueuePhotos: (sessionId: string, photos: Array<{ filename: string; localPath: string }>, destination: UploadDestination) => Promise<void>;
   updateGuestDisplay: (data: {
     currentSetPhotos?: CurrentSetPhoto[];
     selectedPhotoIndex?: number | null;
@@ -74,6 +72,11 @@ interface UsePhotoDownloadHandlerParams {
   setCurrentSetPhotos: (photos: CurrentSetPhoto[] | ((prev: CurrentSetPhoto[]) => CurrentSetPhoto[])) => void;
   addPhotoDownloadedListener: (listener: (event: PhotoDownloadedEvent) => void) => void;
   removePhotoDownloadedListener: (listener: (event: PhotoDownloadedEvent) => void) => void;
+  addGroupCapturedListener: (listener: (event: GroupCapturedEvent) => void) => void;
+  removeGroupCapturedListener: (listener: (event: GroupCapturedEvent) => void) => void;
+  addRawAttachedListener: (listener: (event: RawAttachedEvent) => void) => void;
+  removeRawAttachedListener: (listener: (event: RawAttachedEvent) => void) => void;
+  isWsConnected: boolean;
 }
 
 export function usePhotoDownloadHandler({
@@ -100,25 +103,41 @@ export function usePhotoDownloadHandler({
   setCurrentSetPhotos,
   addPhotoDownloadedListener,
   removePhotoDownloadedListener,
+  addGroupCapturedListener,
+  removeGroupCapturedListener,
+  addRawAttachedListener,
+  removeRawAttachedListener,
+  isWsConnected,
 }: UsePhotoDownloadHandlerParams) {
   const { showToast } = useToast();
-  const handlePhotoDownloaded = useCallback(async (event: PhotoDownloadedEvent) => {
+  const sweepInProgressRef = useRef(false);
+  // Downloads still in flight, by daemon file path (a late RAW waits for its JPEG)
+  const pendingDownloadsRef = useRef<Map<string, Promise<string | undefined>>>(new Map());
+  const handlePhotoDownloaded = useCallback(async (event: PhotoDownloadedEvent, group?: GroupPhotoContext): Promise<string | undefined> => {
     logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] START');
     logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] event:', event);
 
+    // Group capture photos are saved together from the group_captured event
+    if (event.group_id && !group) {
+      logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Part of group capture, waiting for group_captured:', event.group_id);
+      return;
+    }
+    const isSecondaryGroupPhoto = !!group && !group.primary;
+
     const filename = event.file_path.split('/').pop() || event.file_path;
     logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] extracted filename:', filename);
 
-    // Skip RAW files (camera is likely set to RAW + JPEG mode)
-    if (isRawFile(filename)) {
-      logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Skipping RAW file:', filename);
-      showToast('RAW File Detected', 'warning', 5000, 'Camera is set to RAW + JPEG mode. Please set camera to JPEG only for faster photobooth operation.');
-      return;
+    // RAW shots arrive as their JPEG (camera's own or the embedded preview) with the RAW alongside
+    const rawFilename = event.raw_path ? event.raw_path.split('/').pop() || event.raw_path : null;
+    if (event.preview_error) {
+      logger.warn('[PhotoboothWorkspace::handlePhotoDownloaded] No preview in RAW, saving RAW only:', filename, event.preview_error);
     }
 
     // Immediately advance the sequence state machine (adds placeholder + moves to review/next)
     // This decouples state progression from slower download pipeline
-    sequenceNotifyCaptureComplete();
+    if (!isSecondaryGroupPhoto) {
+      sequenceNotifyCaptureComplete();
+    }
 
     if (!workingFolder) {
       // Show warning toast if working folder is not set
@@ -128,7 +147,7 @@ export function usePhotoDownloadHandler({
     }
 
     try {
-      let sessionId = currentSession?.id;
+      let sessionId = group?.sessionId ?? currentSession?.id;
       logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] initial sessionId:', sessionId);
 
       // Auto-create session if none exists
@@ -159,6 +178,8 @@ export function usePhotoDownloadHandler({
         sessionId,
         cameraPath: event.camera_path,
         originalDaemonPath: event.file_path,
+        cameraId: event.camera_id,
+        rawFilename,
       });
 
       // Download photo directly via Rust (bypasses slow JS ArrayBuffer -> Array conversion)
@@ -170,6 +191,9 @@ export function usePhotoDownloadHandler({
         cameraPath: event.camera_path,
         originalDaemonPath: event.file_path,
         photoNamingScheme,
+        cameraId: event.camera_id ?? null,
+        group: group ? { groupId: group.groupId, triggerSkewMs: group.triggerSkewMs, maxSkewMs: group.maxSkewMs } : null,
+        rawFilename,
       });
 
       logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Photo saved, session updated:', updatedSession);
@@ -182,56 +206,59 @@ export function usePhotoDownloadHandler({
       const photoPath = `${workingFolder}/${folderName}/${customFilename}`;
       const photoUrl = convertFileSrc(photoPath);
 
-      logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Showing photo on guest display immediately');
-
-      // Reset flag for new photo
-      previewTimerStartedRef.current = false;
-
-      // Update main workspace state first (for guest display sync)
-      // NOTE: Timer will be started when onCapturePreviewLoad is called (after image loads)
-      setCapturedPhotoUrl(photoUrl);
-      setShowCapturePreview(true);
+      // Other cameras of a group capture are stored with the group, not shown as separate shots
+      if (!isSecondaryGroupPhoto) {
+        logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Showing photo on guest display immediately');
+
+        // Reset flag for new photo
+        previewTimerStartedRef.current = false;
+
+        // Update main workspace state first (for guest display sync)
+        // NOTE: Timer will be started when onCapturePreviewLoad is called (after image loads)
+        setCapturedPhotoUrl(photoUrl);
+        setShowCapturePreview(true);
+
+        // Send to guest display IMMEDIATELY - don't wait for anything else
+        updateGuestDisplay({
+          currentSetPhotos,
+          selectedPhotoIndex,
+          displayMode,
+          showCapturePreview: true,
+          capturedPhotoUrl: photoUrl,
+        });
 
-      // Send to guest display IMMEDIATELY - don't wait for anything else
-      updateGuestDisplay({
-        currentSetPhotos,
-        selectedPhotoIndex,
-        displayMode,
-        showCapturePreview: true,
-        capturedPhotoUrl: photoUrl,
-      });
+        // Start manual review mode if not in automatic sequence
+        if (!sequenceIsActive) {
+          logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Manual capture - starting manual review mode');
+          sequenceStartManualReview();
+        }
 
-      // Start manual review mode if not in automatic sequence
-      if (!sequenceIsActive) {
-        logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Manual capture - starting manual review mode');
-        sequenceStartManualReview();
+        // Create new photo entry for current set
+        // Use timestamp + filename to create unique ID (prevents duplicate key errors when photos are deleted and re-taken)
+        const newPhoto: CurrentSetPhoto = {
+          id: `${Date.now()}-${customFilename}`,
+          filename: customFilename, // Actual filename for file operations
+          thumbnailUrl: photoUrl, // Use full-res for display quality
+          fullUrl: photoUrl,
+          timestamp: new Date().toLocaleTimeString(),
+        };
+
+        // Update current set photos (main workspace display)
+        setCurrentSetPhotos(prev => {
+          const placeholderIndex = prev.findIndex(p => p.thumbnailUrl.startsWith('data:image/svg+xml'));
+          if (placeholderIndex !== -1) {
+            // Replace the placeholder with the real photo
+            const updated = [...prev];
+            updated[placeholderIndex] = newPhoto;
+            logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Replaced placeholder at index:', placeholderIndex);
+            return updated;
+          }
+          // If no placeholder found, append the new photo
+          logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] No placeholder found, appending photo');
+          return [...prev, newPhoto];
+        });
       }
 
-      // Create new photo entry for current set
-      // Use timestamp + filename to create unique ID (prevents duplicate key errors when photos are deleted and re-taken)
-      const newPhoto: CurrentSetPhoto = {
-        id: `${Date.now()}-${customFilename}`,
-        filename: customFilename, // Actual filename for file operations
-        thumbnailUrl: photoUrl, // Use full-res for display quality
-        fullUrl: photoUrl,
-        timestamp: new Date().toLocaleTimeString(),
-      };
-
-      // Update current set photos (main workspace display)
-      setCurrentSetPhotos(prev => {
-        const placeholderIndex = prev.findIndex(p => p.thumbnailUrl.startsWith('data:image/svg+xml'));
-        if (placeholderIndex !== -1) {
-          // Replace the placeholder with the real photo
-          const updated = [...prev];
-          updated[placeholderIndex] = newPhoto;
-          logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Replaced placeholder at index:', placeholderIndex);
-          return updated;
-        }
-        // If no placeholder found, append the new photo
-        logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] No placeholder found, appending photo');
-        return [...prev, newPhoto];
-      });
-
       // PRIORITY 2 (LOW): Update session state in background (non-blocking)
       // Don't await this - let it complete in background
       updateCurrentSessionFromDownload({
@@ -242,14 +269,15 @@ export function usePhotoDownloadHandler({
         shotCount: updatedSession.shotCount,
         photos: updatedSession.photos,
         googleDriveMetadata: updatedSession.googleDriveMetadata || { uploadedImages: [] },
+        uploadTarget: updatedSession.uploadTarget,
       }, customFilename);
       logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Session state updated directly');
 
-      // PRIORITY 3: Auto-upload to Google Drive (non-blocking)
-      // Only if user is logged in, Drive folder is configured, AND "Upload all images" is enabled
-      if (qrUploadEnabled && account && updatedSession.googleDriveMetadata?.folderId && qrUploadAllImages) {
-        const driveFolderId = updatedSession.googleDriveMetadata.folderId;
-        logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Auto-uploading to Drive folder:', driveFolderId);
+      // PRIORITY 3: Auto-upload to the session's upload target (non-blocking)
+      // Only if a target is configured (Drive: and the user is logged in) AND "Upload all images" is enabled
+      const destination = getUploadDestination(updatedSession);
+      if (qrUploadEnabled && destination && (destination.backend !== 'googleDrive' || account) && qrUploadAllImages) {
+        logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Auto-uploading to:', destination);
 
         // Check if photo was already uploaded
         try {
@@ -264,7 +292,7 @@ export function usePhotoDownloadHandler({
             enqueuePhotos(sessionId!, [{
               filename: customFilename,
               localPath: photoPath
-            }], driveFolderId).catch(err => {
+            }], destination).catch(err => {
               logger.error('[PhotoboothWorkspace::handlePhotoDownloaded] Upload queue error:', err);
             });
             logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] Photo queued for immediate upload:', customFilename);
@@ -285,17 +313,164 @@ export function usePhotoDownloadHandler({
       }
 
       logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] END - photo displayed immediately');
+      return sessionId;
     } catch (error) {
       logger.error('[PhotoboothWorkspace::handlePhotoDownloaded] ERROR:', error);
     }
     logger.debug('[PhotoboothWorkspace::handlePhotoDownloaded] END');
   }, [workingFolder, currentSession, sessions, photoNamingScheme, updateCurrentSessionFromDownload, loadSession, sequenceNotifyCaptureComplete, updateGuestDisplay, currentSetPhotos, selectedPhotoIndex, displayMode, account, qrUploadAllImages, enqueuePhotos]);
 
+  // Remember in-flight downloads so a raw_attached for the same photo can wait for it
+  const trackDownload = useCallback((filePath: string, download: Promise<string | undefined>) => {
+    pendingDownloadsRef.current.set(filePath, download);
+    download.finally(() => {
+      if (pendingDownloadsRef.current.get(filePath) === download) {
+        pendingDownloadsRef.current.delete(filePath);
+      }
+    });
+    return download;
+  }, []);
+
+  const handlePhotoDownloadedEvent = useCallback((event: PhotoDownloadedEvent) => {
+    trackDownload(event.file_path, handlePhotoDownloaded(event));
+  }, [handlePhotoDownloaded, trackDownload]);
+
   // Subscribe to photo_downloaded events
   useEffect(() => {
-    addPhotoDownloadedListener(handlePhotoDownloaded);
+    addPhotoDownloadedListener(handlePhotoDownloadedEvent);
+    return () => {
+      removePhotoDownloadedListener(handlePhotoDownloadedEvent);
+    };
+  }, [handlePhotoDownloadedEvent, addPhotoDownloadedListener, removePhotoDownloadedListener]);
+
+  // Save a group capture: one photo per camera, in camera order, as a single shot
+  const handleGroupCaptured = useCallback(async (event: GroupCapturedEvent) => {
+    logger.debug('[PhotoboothWorkspace::handleGroupCaptured] group:', event.group_id, 'max skew ms:', event.max_skew_ms);
+
+    const failed = event.members.filter(m => !m.ok);
+    if (failed.length > 0) {
+      logger.warn('[PhotoboothWorkspace::handleGroupCaptured] Cameras failed to capture:', failed.map(m => `${m.camera_id}: ${m.error}`));
+      showToast('Group Capture Incomplete', 'warning', 5000, `${failed.length} camera(s) did not capture`);
+    }
+
+    const photos = event.members
+      .flatMap(member => member.photos.map(photo => ({ member, photo })));
+    if (photos.length === 0) {
+      logger.warn('[PhotoboothWorkspace::handleGroupCaptured] No photos in group:', event.group_id);
+      return;
+    }
+
+    // One at a time - every download rewrites the session file
+    let sessionId: string | undefined;
+    for (let i = 0; i < photos.length; i++) {
+      const { member, photo } = photos[i];
+      const savedTo = await trackDownload(photo.file_path, handlePhotoDownloaded({
+        type: 'photo_downloaded',
+        file_path: photo.file_path,
+        camera_path: photo.camera_path,
+        camera_id: member.camera_id,
+        group_id: event.group_id,
+        raw_path: photo.raw_path,
+      }, {
+        groupId: event.group_id,
+        triggerSkewMs: member.skew_ms,
+        maxSkewMs: event.max_skew_ms,
+        primary: i === 0,
+        sessionId,
+      }));
+      sessionId = sessionId ?? savedTo;
+    }
+  }, [handlePhotoDownloaded, trackDownload, showToast]);
+
+  // Subscribe to group_captured events
+  useEffect(() => {
+    addGroupCapturedListener(handleGroupCaptured);
     return () => {
-      removePhotoDownloadedListener(handlePhotoDownloaded);
+      removeGroupCapturedListener(handleGroupCaptured);
     };
-  }, [handlePhotoDownloaded, addPhotoDownloadedListener, removePhotoDownloadedListener]);
+  }, [handleGroupCaptured, addGroupCapturedListener, removeGroupCapturedListener]);
+
+  // Store a RAW that the camera delivered after its JPEG next to the saved photo
+  const handleRawAttached = useCallback(async (event: RawAttachedEvent) => {
+    const savedTo = await pendingDownloadsRef.current.get(event.file_path);
+    const sessionId = savedTo ?? currentSession?.id;
+    if (!workingFolder || !sessionId) {
+      logger.warn('[PhotoboothWorkspace::handleRawAttached] No session for RAW, leaving it on the daemon:', event.raw_path);
+      return;
+    }
+
+    try {
+      const updatedSession = await invoke<PtbSession>('attach_daemon_raw', {
+        daemonUrl: DAEMON_URL,
+        folderPath: workingFolder,
+        sessionId,
+        originalDaemonPath: event.file_path,
+        rawFilename: event.raw_path.split('/').pop() || event.raw_path,
+        cameraId: event.camera_id ?? null,
+      });
+      updateCurrentSessionFromDownload({
+        id: sessionId,
+        name: updatedSession.name,
+        createdAt: updatedSession.createdAt,
+        lastUsedAt: updatedSession.lastUsedAt,
+        shotCount: updatedSession.shotCount,
+        photos: updatedSession.photos,
+        googleDriveMetadata: updatedSession.googleDriveMetadata || { uploadedImages: [] },
+        uploadTarget: updatedSession.uploadTarget,
+      });
+    } catch (error) {
+      logger.error('[PhotoboothWorkspace::handleRawAttached] ERROR:', error);
+    }
+  }, [workingFolder, currentSession, updateCurrentSessionFromDownload]);
+
+  // Subscribe to raw_attached events
+  useEffect(() => {
+    addRawAttachedListener(handleRawAttached);
+    return () => {
+      removeRawAttachedListener(handleRawAttached);
+    };
+  }, [handleRawAttached, addRawAttachedListener, removeRawAttachedListener]);
+
+  // On (re)connect, recover photos still on the daemon that we never received
+  useEffect(() => {
+    if (!isWsConnected || !workingFolder || !currentSession?.id || sweepInProgressRef.current) {
+      return;
+    }
+    const sessionId = currentSession.id;
+    sweepInProgressRef.current = true;
+
+    invoke<DaemonSweepResult>('sweep_daemon_photos', {
+      daemonUrl: DAEMON_URL,
+      folderPath: workingFolder,
+      sessionId,
+      photoNamingScheme,
+    })
+      .then(result => {
+        logger.debug('[PhotoboothWorkspace::sweepDaemonPhotos] result:', result);
+        if (result.session && result.downloaded.length > 0) {
+          updateCurrentSessionFromDownload({
+            id: sessionId,
+            name: result.session.name,
+            createdAt: result.session.createdAt,
+            lastUsedAt: result.session.lastUsedAt,
+            shotCount: result.session.shotCount,
+            photos: result.session.photos,
+            googleDriveMetadata: result.session.googleDriveMetadata || { uploadedImages: [] },
+            uploadTarget: result.session.uploadTarget,
+          });
+          showToast('Recovered Photos', 'info', 5000, `${result.downloaded.length} photo(s) taken while disconnected were added to this session`);
+        }
+        if (result.failed.length > 0) {
+          logger.warn('[PhotoboothWorkspace::sweepDaemonPhotos] Could not recover:', result.failed);
+        }
+      })
+      .catch(error => {
+        logger.error('[PhotoboothWorkspace::sweepDaemonPhotos] ERROR:', error);
+      })
+      .finally(() => {
+        sweepInProgressRef.current = false;
+      });
+    // Only sweep when the connection comes up, not on every session change
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [isWsConnected]);
 }
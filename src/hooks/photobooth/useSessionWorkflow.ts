@@ -1,10 +1,13 @@
 import { useState, useEffect, useRef, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { getDriveAuthState, areUploadsEnabled } from '../../utils/driveAuthState';
+import { getDriveAuthState } from '../../utils/driveAuthState';
+import { canUploadTo, getUploadDestination } from '../../utils/uploadTarget';
+import { getSessionShareLink } from '../../utils/sessionShareLink';
 import { imageCache } from '../../services/ImageCacheService';
 import type { PhotoboothSessionInfo, PhotoboothSession } from '../../contexts/photobooth/PhotoboothSettingsContext';
 import type { CurrentSetPhoto, DisplayMode } from '../../components/PhotoboothView/photoboothWorkspaceTypes';
 import type { PlacedImage } from '../../types/collage';
+import type { UploadDestination } from '../../types/uploadTargets';
 import { createLogger } from '../../utils/logger';
 
 const logger = createLogger('PhotoboothWorkspace');
@@ -29,7 +32,7 @@ interface UseSessionWorkflowParams {
   qrUploadEnabled: boolean;
   qrUploadAllImages: boolean;
   account: any;
-  enqueuePhotos: (sessionId: string, photos: Array<{ filename: string; localPath: string }>, driveFolderId: string) => Promise<void>;
+  enqueuePhotos: (sessionId: string, photos: Array<{ filename: string; localPath: string }>, destination: UploadDestination) => Promise<void>;
   currentSetPhotos: CurrentSetPhoto[];
   selectedPhotos: string[];
   placedImages: Map<string, PlacedImage>;
@@ -164,27 +167,26 @@ export function useSessionWorkflow({
     setPreviousDisplayMode('center');
     setDisplayMode('finalize');
 
-    // Generate QR code from session's Drive folder link if available
+    // QR code of the session's local gallery page, or of its Drive folder
     const folderLink = currentSession?.googleDriveMetadata?.folderLink;
     logger.debug('[useSessionWorkflow::handleFinalizeSession] folderLink:', folderLink, 'accountId:', currentSession?.googleDriveMetadata?.accountId, 'currentAccount:', account?.email);
-    if (folderLink) {
+    if (currentSession) {
       try {
-        const qrBase64 = await invoke<string>('generate_qr_code', { url: folderLink });
-        logger.debug('[useSessionWorkflow::handleFinalizeSession] QR code generated successfully, length:', qrBase64.length);
-        setSessionQrData(qrBase64);
+        const shareLink = await getSessionShareLink(currentSession.id, folderLink);
+        logger.debug('[useSessionWorkflow::handleFinalizeSession] QR code for', shareLink?.source ?? 'nothing', shareLink?.link);
+        setSessionQrData(shareLink?.qrData ?? null);
       } catch (err) {
         logger.error('[PhotoboothWorkspace] Failed to generate QR code:', err);
         setSessionQrData(null);
       }
     } else {
-      logger.debug('[useSessionWorkflow::handleFinalizeSession] No folderLink, setting sessionQrData to null');
       setSessionQrData(null);
     }
 
-    // Upload photos to Google Drive if configured and account matches
+    // Upload photos to the session's upload target if configured (Drive: and the account matches)
     const driveAuthState = getDriveAuthState(currentSession?.googleDriveMetadata, account);
-    if (qrUploadEnabled && account && currentSession?.googleDriveMetadata?.folderId && workingFolder && areUploadsEnabled(driveAuthState.state)) {
-      const driveFolderId = currentSession.googleDriveMetadata.folderId;
+    const destination = getUploadDestination(currentSession);
+    if (qrUploadEnabled && currentSession && workingFolder && canUploadTo(destination, driveAuthState.state)) {
 
       try {
         let photoFilenames: string[] = [];
@@ -243,7 +245,7 @@ export function useSessionWorkflow({
 
         if (photosToUpload.length > 0) {
           logger.debug(`[PhotoboothWorkspace::handleFinalizeSession] Queueing ${photosToUpload.length} photos for upload`);
-          await enqueuePhotos(currentSession.id, photosToUpload, driveFolderId);
+          await enqueuePhotos(currentSession.id, photosToUpload, destination);
         } else {
           logger.debug('[PhotoboothWorkspace::handleFinalizeSession] All photos already uploaded');
         }
@@ -251,7 +253,7 @@ export function useSessionWorkflow({
         logger.error('[PhotoboothWorkspace::handleFinalizeSession] Upload error:', error);
       }
     } else {
-      logger.debug('[PhotoboothWorkspace::handleFinalizeSession] Skipping upload - Drive not configured or account mismatch');
+      logger.debug('[PhotoboothWorkspace::handleFinalizeSession] Skipping upload - no upload target or Drive account mismatch');
     }
   }, [currentSession, workingFolder, sessions, qrUploadEnabled, qrUploadAllImages, account, enqueuePhotos, currentSetPhotos, selectedPhotos, placedImages, setFinalizeViewMode, displayMode, setPreviousDisplayMode, setDisplayMode]);
 
@@ -3,6 +3,8 @@ import { usePhotobooth, useUploadQueue, useAuth, useWorkspaceSettings, useToast
 import * as fs from "@tauri-apps/plugin-fs";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { createLogger } from "../utils/logger";
+import { recordCatalogMedia } from "../utils/catalog";
+import { canUploadTo, getUploadDestination } from "../utils/uploadTarget";
 
 const logger = createLogger('useCollageUpload');
 
@@ -13,6 +15,7 @@ export function useCollageUpload() {
     collageIsDirty,
     resetCollageDirtyState,
     exportPhotoboothCanvasAsPNG,
+    renderPhotoboothCollage,
     isGeneratingCollage,
     setIsGeneratingCollage,
   } = usePhotobooth();
@@ -31,8 +34,9 @@ export function useCollageUpload() {
       driveMetadata: any,
       onNewCollageGenerated?: (filename: string, imageUrl: string) => void
     ) => {
-      if (!currentSession || !workingFolder || !driveMetadata?.folderId) {
-        logger.debug('[useCollageUpload] Skipping upload - missing session, folder, or Drive folder ID');
+      const destination = getUploadDestination(currentSession);
+      if (!currentSession || !workingFolder || !destination) {
+        logger.debug('[useCollageUpload] Skipping upload - missing session, folder, or upload target');
         return;
       }
 
@@ -42,10 +46,10 @@ export function useCollageUpload() {
         return;
       }
 
-      // Check authentication state
-      const { getDriveAuthState, areUploadsEnabled } = await import("../utils/driveAuthState");
+      // Check authentication state (Drive only)
+      const { getDriveAuthState } = await import("../utils/driveAuthState");
       const driveAuthState = getDriveAuthState(driveMetadata, account);
-      if (!areUploadsEnabled(driveAuthState.state)) {
+      if (!canUploadTo(destination, driveAuthState.state)) {
         logger.debug('[useCollageUpload] Skipping upload - not authenticated or account mismatch');
         return;
       }
@@ -71,25 +75,29 @@ export function useCollageUpload() {
           // Set generating state to block other operations
           setIsGeneratingCollage(true);
 
-          // Export collage canvas as PNG
-          const exportResult = await exportPhotoboothCanvasAsPNG();
-          if (!exportResult) {
-            logger.warn('[useCollageUpload] Export failed - not ready');
-            setIsGeneratingCollage(false);
-            return;
-          }
-
           const chars = "abcdefghijklmnopqrstuvwxyz0123456789";
           const randomStr = Array.from(
             { length: 8 },
             () => chars[Math.floor(Math.random() * chars.length)]
           ).join("");
           filename = `Collage_${randomStr}.png`;
-
-          // Save to session folder using FS plugin
           const sessionPath = `${workingFolder}/${sessionFolder}`;
-          await fs.mkdir(sessionPath, { recursive: true });
-          await fs.writeFile(`${sessionPath}/${filename}`, exportResult.bytes);
+
+          // Render natively so the file doesn't depend on the webview; fall back to the canvas export
+          const rendered = await renderPhotoboothCollage(`${sessionPath}/${filename}`);
+          if (!rendered) {
+            const exportResult = await exportPhotoboothCanvasAsPNG();
+            if (!exportResult) {
+              logger.warn('[useCollageUpload] Export failed - not ready');
+              setIsGeneratingCollage(false);
+              return;
+            }
+
+            // Save to session folder using FS plugin
+            await fs.mkdir(sessionPath, { recursive: true });
+            await fs.writeFile(`${sessionPath}/${filename}`, exportResult.bytes);
+          }
+          await recordCatalogMedia(workingFolder, currentSession.id, "collage", `${sessionPath}/${filename}`);
 
           logger.debug('[useCollageUpload] New collage saved:', filename);
         }
@@ -99,7 +107,7 @@ export function useCollageUpload() {
         await enqueuePhotos(
           currentSession.id,
           [{ filename, localPath }],
-          driveMetadata.folderId
+          destination
         );
 
         logger.debug('[useCollageUpload] Collage uploaded successfully');
@@ -138,6 +146,7 @@ export function useCollageUpload() {
       collageIsDirty,
       currentCollageFilename,
       exportPhotoboothCanvasAsPNG,
+      renderPhotoboothCollage,
       isGeneratingCollage,
       qrUploadEnabled,
       setCurrentCollageFilename,
@@ -1,6 +1,7 @@
 const API_BASE = 'http://localhost:58321';
 
 import { createLogger } from '../utils/logger';
+import { daemonFetch } from './daemonAuth';
 const logger = createLogger('cameraCaptureService');
 
 export interface CaptureResponse {
@@ -22,7 +23,7 @@ export async function triggerCapture(onCaptureStart?: () => void): Promise<Captu
   logger.debug('[CameraCapture] Sending capture request to', `${API_BASE}/api/capture`);
   onCaptureStart?.(); // Call callback when capture request is sent
   try {
-    const response = await fetch(`${API_BASE}/api/capture`, {
+    const response = await daemonFetch(`${API_BASE}/api/capture`, {
       method: 'POST',
       headers: { 'Content-Type': 'application/json' },
     });
@@ -52,7 +53,7 @@ export async function triggerCapture(onCaptureStart?: () => void): Promise<Captu
 export async function fetchPhoto(filename: string): Promise<FetchPhotoResponse> {
   logger.debug('[CameraCapture] Fetching photo:', filename);
   try {
-    const response = await fetch(`${API_BASE}/api/photo/${encodeURIComponent(filename)}`, {
+    const response = await daemonFetch(`${API_BASE}/api/photo/${encodeURIComponent(filename)}`, {
       method: 'GET',
     });
 
@@ -0,0 +1,110 @@
+/**
+ * Camera Presets Service
+ *
+ * Named camera settings presets ("studio strobe", "window light", ...).
+ * Presets are captured from the live camera, stored by the app, diffed against the
+ * camera and applied in one daemon call with a per-setting report.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { createLogger } from '../utils/logger';
+
+const logger = createLogger('cameraPresetsService');
+
+const DAEMON_URL = 'http://localhost:58321';
+
+export interface CameraPresetSetting {
+  setting: string;
+  value: string;
+  label?: string | null;
+}
+
+export interface CameraPreset {
+  id: string;
+  name: string;
+  cameraModel?: string | null;
+  settings: CameraPresetSetting[];
+  createdAt: string;
+  modifiedAt: string;
+}
+
+export type PresetDiffStatus = 'same' | 'changed' | 'missing' | 'invalidChoice';
+
+export interface CameraPresetDiffEntry {
+  setting: string;
+  label: string | null;
+  presetValue: string;
+  cameraValue: string | null;
+  status: PresetDiffStatus;
+}
+
+export type PresetApplyStatus = 'unchanged' | 'applied' | 'failed' | 'skipped' | 'rolled_back';
+
+export interface CameraPresetApplyResult {
+  setting: string;
+  value: string;
+  previous: string | null;
+  status: PresetApplyStatus;
+  error?: string | null;
+}
+
+export interface CameraPresetApplyReport {
+  success: boolean;
+  applied: number;
+  unchanged: number;
+  failed: number;
+  rolledBack: number;
+  results: CameraPresetApplyResult[];
+}
+
+export function listCameraPresets(): Promise<CameraPreset[]> {
+  return invoke<CameraPreset[]>('list_camera_presets');
+}
+
+export function saveCameraPreset(preset: CameraPreset): Promise<CameraPreset> {
+  return invoke<CameraPreset>('save_camera_preset', { preset });
+}
+
+export function deleteCameraPreset(presetId: string): Promise<void> {
+  return invoke('delete_camera_preset', { presetId });
+}
+
+/**
+ * Save the camera's current settings as a new preset.
+ * Pass `settings` to keep only some widgets (e.g. ['iso', 'shutterspeed', 'aperture']).
+ */
+export function captureCameraPreset(name: string, cameraId?: string, settings?: string[]): Promise<CameraPreset> {
+  logger.debug('[CameraPresets] Capturing preset', name, 'from camera', cameraId ?? '0');
+  return invoke<CameraPreset>('capture_camera_preset', {
+    daemonUrl: DAEMON_URL,
+    cameraId: cameraId ?? null,
+    name,
+    settings: settings ?? null,
+  });
+}
+
+/** Compare a preset with the live camera settings */
+export function diffCameraPreset(presetId: string, cameraId?: string): Promise<CameraPresetDiffEntry[]> {
+  return invoke<CameraPresetDiffEntry[]>('diff_camera_preset', {
+    daemonUrl: DAEMON_URL,
+    cameraId: cameraId ?? null,
+    presetId,
+  });
+}
+
+/**
+ * Apply a preset in one call. By default a failing setting rolls back the ones already
+ * changed, so the camera stays on its previous look.
+ */
+export async function applyCameraPreset(presetId: string, cameraId?: string, rollbackOnFailure = true): Promise<CameraPresetApplyReport> {
+  const report = await invoke<CameraPresetApplyReport>('apply_camera_preset', {
+    daemonUrl: DAEMON_URL,
+    cameraId: cameraId ?? null,
+    presetId,
+    rollbackOnFailure,
+  });
+  if (!report.success) {
+    logger.warn('[CameraPresets] Preset applied with failures:', report.results.filter(r => r.status === 'failed'));
+  }
+  return report;
+}
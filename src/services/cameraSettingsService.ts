@@ -8,6 +8,7 @@
 import type { CameraBrand, StandardMode, StandardSetting } from './cameraBrands';
 import { detectBrand, normalizeMode, getBrandModeName, isSettingAdjustable } from './cameraBrands';
 import { createLogger } from '../utils/logger';
+import { daemonFetch } from './daemonAuth';
 
 const logger = createLogger('cameraSettingsService');
 
@@ -81,6 +82,52 @@ export {
   canonEvToDisplay, canonEvToCamera,
 };
 
+/**
+ * Canonical settings (daemon GET/POST /api/camera/settings)
+ *
+ * The daemon maps each brand's widgets and choice strings to one schema: numbers in
+ * the setting's unit (ISO, f-number, seconds, EV, %) or names such as "auto",
+ * "bulb", "daylight" and "continuous_high".
+ */
+export type CanonicalSettingName =
+  | 'drive_mode' | 'iso' | 'aperture' | 'shutter_speed'
+  | 'exposure_compensation' | 'white_balance' | 'battery';
+
+export type CanonicalValue = number | string;
+
+export interface CanonicalSettingState {
+  widget: string;
+  raw: string;
+  value: CanonicalValue | null;
+  unit?: 'iso' | 'f' | 's' | 'ev' | '%';
+  read_only: boolean;
+  options: { raw: string; value: CanonicalValue | null }[];
+}
+
+export interface CanonicalSettings {
+  camera_id: string;
+  brand: 'canon' | 'fujifilm' | 'sony' | 'nikon' | 'other';
+  settings: Partial<Record<CanonicalSettingName, CanonicalSettingState>>;
+  unsupported: CanonicalSettingName[];
+}
+
+/**
+ * Focus and live view zoom (daemon GET/POST /api/camera/focus)
+ *
+ * Routed through the camera's controller, so they work while live view is streaming.
+ * Point coordinates are fractions (0-1) of the live view frame.
+ */
+export type FocusAction = 'autofocus' | 'cancel' | 'near' | 'far' | 'point' | 'zoom';
+
+export type FocusRequest =
+  | { action: 'autofocus' | 'cancel' }
+  | { action: 'near' | 'far'; steps?: number }
+  | { action: 'point'; x: number; y: number }
+  | { action: 'zoom'; level: number };
+
+/** Widget each action drives on this body, null when unsupported */
+export type FocusCapabilities = Record<FocusAction, string | null>;
+
 interface CameraSettingsServiceConfig {
   cameraId?: string;
   brand?: CameraBrand;
@@ -139,7 +186,7 @@ class CameraSettingsService {
 
     try {
       logger.debug(`[CameraSettingsService] Setting ${setting} to ${value}`);
-      const response = await fetch(`${API_BASE}/api/camera/config${this.cameraId !== '0' ? `?camera=${this.cameraId}` : ''}`, {
+      const response = await daemonFetch(`${API_BASE}/api/camera/config${this.cameraId !== '0' ? `?camera=${this.cameraId}` : ''}`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ setting, value }),
@@ -157,6 +204,94 @@ class CameraSettingsService {
     }
   }
 
+  private settingsUrl(): string {
+    return `${API_BASE}/api/camera/settings${this.cameraId && this.cameraId !== '0' ? `?camera=${this.cameraId}` : ''}`;
+  }
+
+  /**
+   * Read the camera's settings in the canonical cross-brand schema
+   */
+  async getCanonicalSettings(): Promise<CanonicalSettings | null> {
+    try {
+      const response = await daemonFetch(this.settingsUrl());
+      if (!response.ok) {
+        logger.error('[CameraSettingsService] Failed to read canonical settings:', response.statusText);
+        return null;
+      }
+      return await response.json() as CanonicalSettings;
+    } catch (error) {
+      logger.error('[CameraSettingsService] Error reading canonical settings:', error);
+      return null;
+    }
+  }
+
+  /**
+   * Apply canonical settings; the daemon picks each brand's widget and choice and
+   * rolls back if one of them fails
+   */
+  async setCanonicalSettings(settings: Partial<Record<CanonicalSettingName, CanonicalValue>>): Promise<boolean> {
+    try {
+      const response = await daemonFetch(this.settingsUrl(), {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ settings }),
+      });
+      const result = await response.json().catch(() => null);
+      if (!response.ok || !result?.success) {
+        logger.error('[CameraSettingsService] Failed to apply canonical settings:', result?.error ?? response.statusText);
+        return false;
+      }
+      return true;
+    } catch (error) {
+      logger.error('[CameraSettingsService] Error applying canonical settings:', error);
+      return false;
+    }
+  }
+
+  private focusUrl(): string {
+    return `${API_BASE}/api/camera/focus${this.cameraId && this.cameraId !== '0' ? `?camera=${this.cameraId}` : ''}`;
+  }
+
+  /**
+   * Focus actions this camera supports
+   */
+  async getFocusCapabilities(): Promise<FocusCapabilities | null> {
+    try {
+      const response = await daemonFetch(this.focusUrl());
+      const result = await response.json().catch(() => null);
+      if (!response.ok || !result?.success) {
+        logger.error('[CameraSettingsService] Failed to read focus capabilities:', result?.error ?? response.statusText);
+        return null;
+      }
+      return result.actions as FocusCapabilities;
+    } catch (error) {
+      logger.error('[CameraSettingsService] Error reading focus capabilities:', error);
+      return null;
+    }
+  }
+
+  /**
+   * Trigger autofocus, step focus near/far, move the AF point or zoom live view
+   */
+  async focus(request: FocusRequest): Promise<boolean> {
+    try {
+      const response = await daemonFetch(this.focusUrl(), {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(request),
+      });
+      const result = await response.json().catch(() => null);
+      if (!response.ok || !result?.success) {
+        logger.error(`[CameraSettingsService] Focus ${request.action} failed:`, result?.error ?? response.statusText);
+        return false;
+      }
+      return true;
+    } catch (error) {
+      logger.error(`[CameraSettingsService] Error running focus ${request.action}:`, error);
+      return false;
+    }
+  }
+
   /**
    * Set shooting mode (P, A, S, M)
    * Automatically maps to brand-specific mode name (e.g., P -> "Action" for Fuji)
@@ -2,9 +2,14 @@
  * Singleton WebSocket manager for camera daemon communication.
  * Lives outside React lifecycle — no useEffect, no state dependencies.
  * No auto-reconnect — UI shows a modal for the user to reconnect manually.
+ *
+ * Daemon events carry a sequence number. A reconnect resumes from the last one seen, so
+ * photos downloaded while the socket was down are replayed; if the daemon no longer has
+ * them it sends `event_gap` instead.
  */
 
 import { createLogger } from '../utils/logger';
+import { loadDaemonToken, withDaemonToken } from './daemonAuth';
 const logger = createLogger('cameraWebSocket');
 
 export interface CameraStatus {
@@ -24,6 +29,65 @@ export interface PhotoDownloadedEvent {
   type: 'photo_downloaded';
   file_path: string;
   camera_path: string;
+  /** Daemon camera id the photo came from (absent on older daemons) */
+  camera_id?: string;
+  /** Set when the photo is part of a group capture (delivered again in group_captured) */
+  group_id?: string;
+  /** Set when the photo is one frame of a burst/bracket (also listed in sequence_captured) */
+  sequence_id?: string;
+  /** RAW file shot alongside this photo; file_path is then its JPEG */
+  raw_path?: string;
+  raw_camera_path?: string;
+  /** Where the JPEG came from for RAW shots: the camera's own JPEG or the RAW's embedded preview */
+  preview?: 'camera' | 'embedded';
+  /** Set when a RAW-only shot had no usable preview; file_path is then the RAW itself */
+  preview_error?: string;
+}
+
+/** RAW file that arrived after its JPEG was already announced in photo_downloaded */
+export interface RawAttachedEvent {
+  type: 'raw_attached';
+  /** The JPEG it belongs to, as sent in the earlier photo_downloaded */
+  file_path: string;
+  raw_path: string;
+  raw_camera_path: string;
+  camera_id?: string;
+}
+
+export interface GroupCapturedMember {
+  camera_id: string;
+  ok: boolean;
+  error?: string;
+  triggered_at_ms: number | null;
+  skew_ms: number | null;
+  photos: Array<{ file_path: string; camera_path: string; raw_path?: string }>;
+}
+
+export interface GroupCapturedEvent {
+  type: 'group_captured';
+  group_id: string;
+  members: GroupCapturedMember[];
+  max_skew_ms: number | null;
+}
+
+export interface SequenceCapturedFrame {
+  index: number;
+  /** Setting value the frame was taken at (brackets only) */
+  value?: string;
+  triggered_at_ms: number | null;
+  offset_ms: number | null;
+}
+
+export interface SequenceCapturedEvent {
+  type: 'sequence_captured';
+  sequence_id: string;
+  kind: 'burst' | 'bracket';
+  camera_id: string;
+  setting?: string;
+  requested: number;
+  frames: SequenceCapturedFrame[];
+  photos: Array<{ file_path: string; camera_path: string }>;
+  error?: string;
 }
 
 export interface CaptureErrorEvent {
@@ -48,7 +112,74 @@ export interface PollingResumedEvent {
   type: 'polling_resumed';
 }
 
-type EventType = 'status' | 'photo_downloaded' | 'capture_error' | 'camera_disconnected' | 'camera_switched' | 'camera_connecting' | 'camera_connect_failed' | 'camera_connected' | 'connected' | 'disconnected' | 'polling_paused' | 'polling_resumed';
+/** Events the daemon could no longer replay (its log was truncated) */
+export interface EventGapEvent {
+  type: 'event_gap';
+  epoch: number;
+  first_missing_seq: number;
+  last_missing_seq: number;
+  reason: 'log_truncated' | 'client_lagged';
+}
+
+/** Storage is low and only photos the app hasn't acknowledged are left to evict */
+export interface StorageLowEvent {
+  type: 'storage_low';
+  available_mb: number;
+  required_mb: number;
+  unacknowledged_files: number;
+  unacknowledged_bytes: number;
+}
+
+/** Camera health alert (battery, card, capture failures, reconnect loops) */
+export interface CameraAlert {
+  id: number;
+  camera_id: string;
+  kind: 'battery_low' | 'shots_low' | 'card_space_low' | 'capture_failures' | 'reconnect_loop';
+  severity: 'warning' | 'critical';
+  state: 'active' | 'cleared' | 'resolved';
+  message: string;
+  value: number;
+  threshold: number;
+  raised_at_ms: number;
+  updated_at_ms: number;
+  occurrences: number;
+  closed_at_ms?: number;
+}
+
+/** An alert was raised, changed severity, was cleared by a user or resolved on its own */
+export interface CameraAlertEvent {
+  type: 'camera_alert';
+  action: 'raised' | 'escalated' | 'updated' | 'cleared' | 'resolved';
+  camera_id: string;
+  alert: CameraAlert;
+}
+
+/** A USB device was plugged into or unplugged from the daemon's VM */
+export interface UsbDeviceEvent {
+  type: 'usb_device_added' | 'usb_device_removed';
+  /** Hex, e.g. "04a9" */
+  vendor_id: string;
+  product_id: string;
+  /** gphoto2 port, e.g. "usb:001,005" */
+  port: string;
+  busnum: number;
+  devnum: number;
+  devpath: string;
+  /** Has a still image (PTP) interface */
+  still_image: boolean;
+  /** Camera id when the device is a detected camera */
+  camera_id: string | null;
+}
+
+/** Sent after the replay on a resumed connection; `epoch` changes when the daemon restarts */
+export interface ReplayCompleteEvent {
+  type: 'replay_complete';
+  epoch: number;
+  seq: number;
+  replayed: number;
+}
+
+type EventType = 'status' | 'photo_downloaded' | 'group_captured' | 'sequence_captured' | 'capture_error' | 'camera_disconnected' | 'camera_switched' | 'camera_connecting' | 'camera_connect_failed' | 'camera_connected' | 'connected' | 'disconnected' | 'polling_paused' | 'polling_resumed' | 'event_gap' | 'replay_complete' | 'storage_low' | 'camera_alert' | 'raw_attached' | 'usb_device_added' | 'usb_device_removed';
 type Listener = (data: any) => void;
 
 const WS_URL = 'ws://localhost:58321/ws';
@@ -59,9 +190,11 @@ class CameraWebSocketManager {
   private ws: WebSocket | null = null;
   private listeners: Map<EventType, Set<Listener>> = new Map();
   private intentionalDisconnect: boolean = false; // Track intentional disconnects across all windows
+  private lastSeq: number | null = null; // Last daemon event seq received
+  private epoch: number | null = null; // Daemon run the seq belongs to
 
   private constructor() {
-    for (const event of ['status', 'photo_downloaded', 'capture_error', 'camera_disconnected', 'camera_switched', 'camera_connecting', 'camera_connect_failed', 'camera_connected', 'connected', 'disconnected', 'polling_paused', 'polling_resumed'] as EventType[]) {
+    for (const event of ['status', 'photo_downloaded', 'group_captured', 'sequence_captured', 'capture_error', 'camera_disconnected', 'camera_switched', 'camera_connecting', 'camera_connect_failed', 'camera_connected', 'connected', 'disconnected', 'polling_paused', 'polling_resumed', 'event_gap', 'replay_complete', 'storage_low', 'camera_alert', 'raw_attached', 'usb_device_added', 'usb_device_removed'] as EventType[]) {
       this.listeners.set(event, new Set());
     }
   }
@@ -74,7 +207,8 @@ class CameraWebSocketManager {
   }
 
   connect(): void {
-    this.createConnection();
+    // The token (if the daemon requires one) goes in the URL - browsers can't set WS headers
+    void loadDaemonToken().then(() => this.createConnection());
   }
 
   disconnect(): void {
@@ -136,7 +270,7 @@ class CameraWebSocketManager {
 
     let ws: WebSocket;
     try {
-      ws = new WebSocket(WS_URL);
+      ws = new WebSocket(withDaemonToken(this.resumeUrl()));
     } catch (err) {
       logger.error('[WS Manager] Failed to create WebSocket:', err);
       this.emit('disconnected');
@@ -162,8 +296,32 @@ class CameraWebSocketManager {
         try {
           const data = JSON.parse(msg);
 
-          if (data.type === 'photo_downloaded') {
+          if (typeof data.seq === 'number' && (this.lastSeq === null || data.seq > this.lastSeq)) {
+            this.lastSeq = data.seq;
+          }
+
+          if (data.type === 'replay_complete') {
+            this.handleReplayComplete(data as ReplayCompleteEvent);
+          } else if (data.type === 'event_gap') {
+            logger.warn('[WS Manager] Daemon events lost:', data.first_missing_seq, '-', data.last_missing_seq, `(${data.reason})`);
+            this.emit('event_gap', data as EventGapEvent);
+          } else if (data.type === 'storage_low') {
+            logger.warn('[WS Manager] Daemon storage low:', data.available_mb, 'MB free,', data.unacknowledged_files, 'unacknowledged photo(s) kept');
+            this.emit('storage_low', data as StorageLowEvent);
+          } else if (data.type === 'camera_alert') {
+            logger.warn('[WS Manager] Camera alert', data.action + ':', data.alert?.message);
+            this.emit('camera_alert', data as CameraAlertEvent);
+          } else if (data.type === 'usb_device_added' || data.type === 'usb_device_removed') {
+            logger.info('[WS Manager] USB device', data.type === 'usb_device_added' ? 'added:' : 'removed:', `${data.vendor_id}:${data.product_id}`, 'at', data.port);
+            this.emit(data.type, data as UsbDeviceEvent);
+          } else if (data.type === 'photo_downloaded') {
             this.emit('photo_downloaded', data as PhotoDownloadedEvent);
+          } else if (data.type === 'raw_attached') {
+            this.emit('raw_attached', data as RawAttachedEvent);
+          } else if (data.type === 'group_captured') {
+            this.emit('group_captured', data as GroupCapturedEvent);
+          } else if (data.type === 'sequence_captured') {
+            this.emit('sequence_captured', data as SequenceCapturedEvent);
           } else if (data.type === 'capture_error') {
             logger.error('[WS Manager] Capture error:', data.error);
             this.emit('capture_error', data as CaptureErrorEvent);
@@ -204,6 +362,28 @@ class CameraWebSocketManager {
 
     this.ws = ws;
   }
+
+  /** First connection only asks for the current position; reconnects resume after lastSeq */
+  private resumeUrl(): string {
+    if (this.epoch === null || this.lastSeq === null) {
+      return `${WS_URL}?since=latest`;
+    }
+    return `${WS_URL}?since=${this.lastSeq}&epoch=${this.epoch}`;
+  }
+
+  private handleReplayComplete(data: ReplayCompleteEvent): void {
+    if (this.epoch !== null && this.epoch !== data.epoch) {
+      logger.info('[WS Manager] Daemon restarted, sequence numbers reset');
+      this.lastSeq = data.seq;
+    } else if (this.lastSeq === null || data.seq > this.lastSeq) {
+      this.lastSeq = data.seq;
+    }
+    if (data.replayed > 0) {
+      logger.info(`[WS Manager] Replayed ${data.replayed} missed daemon event(s)`);
+    }
+    this.epoch = data.epoch;
+    this.emit('replay_complete', data);
+  }
 }
 
 export default CameraWebSocketManager;
@@ -0,0 +1,58 @@
+/**
+ * Daemon Auth
+ *
+ * The camera daemon can require a shared token (PHOTOBOOTH_AUTH_TOKEN). The token is
+ * stored by the Tauri backend; this module caches it and attaches it to requests from
+ * the webview. Headers are used for fetch; WebSocket and <img> stream URLs can't carry
+ * headers, so those get a `token` query parameter instead.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { createLogger } from '../utils/logger';
+
+const logger = createLogger('daemonAuth');
+
+let cachedToken: string | null = null;
+let tokenPromise: Promise<string | null> | null = null;
+
+/** Load the daemon token once (subsequent calls return the cached value) */
+export function loadDaemonToken(): Promise<string | null> {
+  if (!tokenPromise) {
+    tokenPromise = invoke<string | null>('get_daemon_auth_token')
+      .then(token => {
+        cachedToken = token;
+        return token;
+      })
+      .catch(err => {
+        logger.warn('[DaemonAuth] Could not load daemon token:', err);
+        return null;
+      });
+  }
+  return tokenPromise;
+}
+
+/** Store (or clear with null) the daemon token */
+export async function setDaemonToken(token: string | null): Promise<void> {
+  await invoke('set_daemon_auth_token', { token });
+  cachedToken = token && token.trim() ? token.trim() : null;
+  tokenPromise = Promise.resolve(cachedToken);
+}
+
+/** fetch() against the daemon with the token attached */
+export async function daemonFetch(url: string, init: RequestInit = {}): Promise<Response> {
+  const token = await loadDaemonToken();
+  if (!token) return fetch(url, init);
+  const headers = new Headers(init.headers);
+  headers.set('Authorization', `Bearer ${token}`);
+  return fetch(url, { ...init, headers });
+}
+
+/**
+ * Add the token to a daemon URL for WebSocket / <img> use.
+ * Uses the cached token, so call loadDaemonToken() first.
+ */
+export function withDaemonToken(url: string): string {
+  if (!cachedToken) return url;
+  const separator = url.includes('?') ? '&' : '?';
+  return `${url}${separator}token=${encodeURIComponent(cachedToken)}`;
+}
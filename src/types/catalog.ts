@@ -0,0 +1,29 @@
+// Working folder catalog types (mirror src-tauri/src/photobooth_sessions/catalog.rs)
+
+export type CatalogMediaKind = 'gif' | 'video' | 'collage';
+
+export type PrintJobStatus = 'queued' | 'printed' | 'failed';
+
+/** A GIF, video or collage generated from session photos */
+export interface CatalogMedia {
+  id: number;
+  sessionId: string | null;
+  kind: CatalogMediaKind;
+  filePath: string;
+  /** Filenames of the photos it was made from */
+  sourcePhotos: string[];
+  createdAt: string;
+}
+
+/** A print sent to the printer */
+export interface CatalogPrintJob {
+  id: number;
+  sessionId: string | null;
+  filePath: string;
+  copies: number;
+  printer?: string | null;
+  status: PrintJobStatus;
+  error?: string | null;
+  createdAt: string;
+  updatedAt: string;
+}
@@ -0,0 +1,9 @@
+// Local gallery types (mirror src-tauri/src/local_gallery/types.rs)
+
+export interface LocalGalleryStatus {
+  running: boolean;
+  port?: number | null;
+  /** Base URL guests can reach, e.g. "http://192.168.4.1:8765" */
+  baseUrl?: string | null;
+  workingFolder?: string | null;
+}
@@ -9,12 +9,16 @@ export enum UploadStatus {
   CANCELLED = 'cancelled'
 }
 
+export type UploadBackend = 'googleDrive' | 's3' | 'webDav' | 'localFolder';
+
 export interface UploadQueueItem {
   id: string;
   sessionId: string;
   filename: string;
   localPath: string;
-  driveFolderId: string;
+  backend: UploadBackend;
+  // Drive folder id, S3 key prefix, WebDAV collection or sub folder
+  remoteLocation: string;
   status: UploadStatus;
   progress: number;
   error?: string;
@@ -0,0 +1,63 @@
+// Upload target types (mirror src-tauri/src/upload_targets/types.rs)
+
+import type { UploadBackend } from './uploadQueue';
+
+export interface S3Config {
+  /** e.g. "https://s3.eu-west-1.amazonaws.com" or "http://192.168.1.10:9000" for MinIO */
+  endpoint: string;
+  region: string;
+  bucket: string;
+  accessKeyId: string;
+  /** Never returned by the app; empty when saving keeps the saved one */
+  secretAccessKey: string;
+  /** Whether a secret access key is saved */
+  hasSecretAccessKey?: boolean;
+  /** Address the bucket as {endpoint}/{bucket} (required by MinIO) */
+  pathStyle: boolean;
+  /** Public URL prefix used to build share links */
+  publicBaseUrl?: string | null;
+}
+
+export interface WebDavConfig {
+  baseUrl: string;
+  username: string;
+  /** Never returned by the app; empty when saving keeps the saved one */
+  password: string;
+  /** Whether a password is saved */
+  hasPassword?: boolean;
+}
+
+export interface LocalFolderConfig {
+  rootPath: string;
+}
+
+/** Credentials of the non-Drive targets, saved in app data */
+export interface UploadTargetsConfig {
+  s3?: S3Config | null;
+  webdav?: WebDavConfig | null;
+  localFolder?: LocalFolderConfig | null;
+}
+
+export interface SessionUploadedFile {
+  filename: string;
+  remoteId: string;
+  link?: string | null;
+  uploadedAt: string;
+}
+
+/** Where a session's files are uploaded */
+export interface SessionUploadTarget {
+  backend?: UploadBackend | null;
+  /** Drive folder id, S3 key prefix, WebDAV collection or sub folder */
+  remoteLocation?: string | null;
+  remoteName?: string | null;
+  remoteLink?: string | null;
+  accountId?: string | null;
+  uploadedFiles?: SessionUploadedFile[];
+}
+
+/** Backend and location an upload queue item is sent to */
+export interface UploadDestination {
+  backend: UploadBackend;
+  remoteLocation: string;
+}
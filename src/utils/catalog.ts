@@ -0,0 +1,63 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { CatalogMediaKind, CatalogPrintJob, PrintJobStatus } from '../types/catalog';
+import { createLogger } from './logger';
+const logger = createLogger('catalog');
+
+// The catalog only keeps a history of what was made and printed, so a failure to
+// record something is logged rather than failing the GIF, collage or print itself.
+
+/** File name of a path with either separator */
+function baseName(path: string): string {
+  return path.split(/[\\/]/).pop() || path;
+}
+
+/** Record a generated GIF, video or collage in the working folder's catalog */
+export async function recordCatalogMedia(
+  folderPath: string,
+  sessionId: string | null,
+  kind: CatalogMediaKind,
+  filePath: string,
+  sourcePhotos: string[] = []
+): Promise<void> {
+  try {
+    await invoke('record_catalog_media', {
+      folderPath,
+      media: { sessionId, kind, filePath, sourcePhotos: sourcePhotos.map(baseName) },
+    });
+  } catch (error) {
+    logger.warn(`Failed to record ${kind} in the catalog:`, error);
+  }
+}
+
+/** Record a print as queued; returns its job id, or null if it couldn't be recorded */
+export async function recordPrintJob(
+  folderPath: string,
+  sessionId: string | null,
+  filePath: string
+): Promise<number | null> {
+  try {
+    const job = await invoke<CatalogPrintJob>('record_print_job', {
+      folderPath,
+      job: { sessionId, filePath },
+    });
+    return job.id;
+  } catch (error) {
+    logger.warn('Failed to record print job in the catalog:', error);
+    return null;
+  }
+}
+
+/** Update a recorded print job once the print was handed off or failed */
+export async function updatePrintJobStatus(
+  folderPath: string,
+  jobId: number | null,
+  status: PrintJobStatus,
+  error?: string
+): Promise<void> {
+  if (jobId === null) return;
+  try {
+    await invoke('update_print_job_status', { folderPath, jobId, status, error: error ?? null });
+  } catch (err) {
+    logger.warn('Failed to update print job in the catalog:', err);
+  }
+}
@@ -0,0 +1,28 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { LocalGalleryStatus } from '../types/localGallery';
+import type { Result as ProcessResult } from '../types/qr';
+
+export interface SessionShareLink {
+  link: string;
+  /** Base64 PNG of the link's QR code */
+  qrData: string;
+  source: 'localGallery' | 'drive';
+}
+
+/**
+ * Link and QR code guests scan for a session: its page on the local gallery while
+ * the gallery is running, else its Drive folder. Null if the session has neither.
+ */
+export async function getSessionShareLink(
+  sessionId: string,
+  driveFolderLink?: string | null
+): Promise<SessionShareLink | null> {
+  const status = await invoke<LocalGalleryStatus>('get_local_gallery_status');
+  if (status.running) {
+    const result = await invoke<ProcessResult>('get_local_gallery_session_link', { sessionId });
+    return { link: result.link, qrData: result.qr_data, source: 'localGallery' };
+  }
+  if (!driveFolderLink) return null;
+  const qrData = await invoke<string>('generate_qr_code', { url: driveFolderLink });
+  return { link: driveFolderLink, qrData, source: 'drive' };
+}
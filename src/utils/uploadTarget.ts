@@ -0,0 +1,38 @@
+import type { GoogleDriveMetadata } from '../contexts';
+import type { SessionUploadTarget, UploadDestination } from '../types/uploadTargets';
+import { DriveAuthState, areUploadsEnabled } from './driveAuthState';
+
+interface SessionWithUploadTarget {
+  uploadTarget?: SessionUploadTarget | null;
+  googleDriveMetadata?: GoogleDriveMetadata | null;
+}
+
+/**
+ * Where a session's uploads go: its upload target, or its Drive folder for
+ * sessions set up before upload targets existed. Null if nothing is set up.
+ */
+export function getUploadDestination(session: SessionWithUploadTarget | null | undefined): UploadDestination | null {
+  if (!session) return null;
+  const target = session.uploadTarget;
+  const driveFolderId = session.googleDriveMetadata?.folderId;
+  const backend = target?.backend ?? (driveFolderId ? 'googleDrive' : null);
+  if (!backend) return null;
+
+  if (backend === 'googleDrive') {
+    const folderId = target?.remoteLocation || driveFolderId;
+    return folderId ? { backend, remoteLocation: folderId } : null;
+  }
+  // An empty location uploads to the root of the configured bucket, server or folder
+  return { backend, remoteLocation: target?.remoteLocation ?? '' };
+}
+
+/**
+ * Whether uploads to a destination can start: Drive needs the account that owns
+ * the folder, the other backends use the saved target configuration
+ */
+export function canUploadTo(
+  destination: UploadDestination | null,
+  driveAuthState: DriveAuthState
+): destination is UploadDestination {
+  return !!destination && (destination.backend !== 'googleDrive' || areUploadsEnabled(driveAuthState));
+}